@@ -1,4 +1,5 @@
-use std::path::Path;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::Result;
@@ -7,6 +8,8 @@ use forge_domain::*;
 use forge_infra::ForgeInfra;
 use forge_stream::MpscStream;
 
+use crate::completion::ForgeCompletionService;
+use crate::edit::ForgeEditService;
 use crate::executor::ForgeExecutorService;
 use crate::loader::ForgeLoaderService;
 use crate::suggestion::ForgeSuggestionService;
@@ -17,6 +20,8 @@ pub struct ForgeAPI<F> {
     executor_service: ForgeExecutorService<F>,
     suggestion_service: ForgeSuggestionService<F>,
     loader: ForgeLoaderService<F>,
+    edit_service: ForgeEditService<F>,
+    completion_service: ForgeCompletionService<F>,
 }
 
 impl<F: App + Infrastructure> ForgeAPI<F> {
@@ -26,13 +31,29 @@ impl<F: App + Infrastructure> ForgeAPI<F> {
             executor_service: ForgeExecutorService::new(app.clone()),
             suggestion_service: ForgeSuggestionService::new(app.clone()),
             loader: ForgeLoaderService::new(app.clone()),
+            edit_service: ForgeEditService::new(app.clone()),
+            completion_service: ForgeCompletionService::new(app.clone()),
         }
     }
+
+    /// Model of the requests made outside of the conversations, the one of
+    /// the first agent of the workflow.
+    async fn model(&self) -> anyhow::Result<ModelId> {
+        let workflow = self.loader.load(None).await?;
+        workflow
+            .agents
+            .iter()
+            .find(|agent| agent.enable)
+            .map(|agent| agent.model.clone())
+            .ok_or_else(|| anyhow::anyhow!("The workflow has no agent"))
+    }
 }
 
 impl ForgeAPI<ForgeApp<ForgeInfra>> {
-    pub fn init(restricted: bool) -> Self {
-        let infra = Arc::new(ForgeInfra::new(restricted));
+    /// Creates the API with the settings of the flags, which take precedence
+    /// over the environment and the config files.
+    pub fn init(flags: Config) -> Self {
+        let infra = Arc::new(ForgeInfra::new(flags));
         let app = Arc::new(ForgeApp::new(infra));
         ForgeAPI::new(app)
     }
@@ -71,10 +92,113 @@ impl<F: App + Infrastructure> API for ForgeAPI<F> {
         self.loader.load(path).await
     }
 
+    async fn set_parameters(
+        &self,
+        conversation_id: &ConversationId,
+        parameters: &ModelParameters,
+    ) -> anyhow::Result<()> {
+        self.app
+            .conversation_service()
+            .set_parameters(conversation_id, parameters)
+            .await
+    }
+
+    async fn set_model(
+        &self,
+        conversation_id: &ConversationId,
+        model: &ModelId,
+    ) -> anyhow::Result<()> {
+        self.app
+            .conversation_service()
+            .set_model(conversation_id, model)
+            .await
+    }
+
+    async fn set_mode(
+        &self,
+        conversation_id: &ConversationId,
+        mode: AgentMode,
+    ) -> anyhow::Result<()> {
+        self.app
+            .conversation_service()
+            .set_mode(conversation_id, mode)
+            .await
+    }
+
+    fn answer(&self, conversation_id: &ConversationId, answer: String) -> anyhow::Result<()> {
+        self.executor_service.answer(conversation_id, answer)
+    }
+
+    fn cancel(&self, conversation_id: &ConversationId) -> bool {
+        self.executor_service.cancel(conversation_id)
+    }
+
+    fn running(&self) -> Vec<ConversationId> {
+        self.executor_service.running()
+    }
+
+    async fn checkpoint(&self, conversation_id: &ConversationId, name: &str) -> anyhow::Result<()> {
+        self.app
+            .conversation_service()
+            .checkpoint(conversation_id, name)
+            .await
+    }
+
+    async fn branch(&self, conversation_id: &ConversationId, name: &str) -> anyhow::Result<()> {
+        self.app
+            .conversation_service()
+            .branch(conversation_id, name)
+            .await
+    }
+
+    async fn set_pinned_files(
+        &self,
+        conversation_id: &ConversationId,
+        files: BTreeSet<PathBuf>,
+    ) -> anyhow::Result<()> {
+        self.app
+            .conversation_service()
+            .set_pinned_files(conversation_id, files)
+            .await
+    }
+
     async fn conversation(
         &self,
         conversation_id: &ConversationId,
     ) -> anyhow::Result<Option<Conversation>> {
         self.app.conversation_service().get(conversation_id).await
     }
+
+    async fn import_conversation(
+        &self,
+        conversation: Conversation,
+    ) -> anyhow::Result<ConversationId> {
+        self.app.conversation_service().import(conversation).await
+    }
+
+    async fn edit_selection(
+        &self,
+        path: &Path,
+        range: LineRange,
+        instruction: &str,
+    ) -> anyhow::Result<EditedFile> {
+        let model = self.model().await?;
+        self.edit_service
+            .edit_selection(&model, path, range, instruction)
+            .await
+    }
+
+    async fn complete(&self, prefix: &str, suffix: &str, language: &str) -> anyhow::Result<String> {
+        let env = self.app.environment_service().get_environment();
+        let model = match env.config.completion_model.clone() {
+            Some(model) => model,
+            None => self.model().await?,
+        };
+        let completion = CodeCompletion {
+            prefix: prefix.to_string(),
+            suffix: suffix.to_string(),
+            language: language.to_string(),
+        };
+        self.completion_service.complete(&model, completion).await
+    }
 }
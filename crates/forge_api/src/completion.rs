@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use forge_app::{EnvironmentService, Infrastructure};
+use forge_domain::{App, CodeCompletion, Content, FimFormat, ModelId, ProviderService, Redactor};
+use futures::TryStreamExt;
+
+/// Inline code completions at the cursor of an editor, with one request to
+/// the model and without the agents, tools and context of the workflow.
+pub struct ForgeCompletionService<F> {
+    app: Arc<F>,
+}
+
+impl<F: App + Infrastructure> ForgeCompletionService<F> {
+    pub fn new(app: Arc<F>) -> Self {
+        Self { app }
+    }
+
+    /// The code to insert at the cursor. Code models are prompted to fill in
+    /// the middle, the others are asked for it in a chat.
+    pub async fn complete(
+        &self,
+        model: &ModelId,
+        mut completion: CodeCompletion,
+    ) -> Result<String> {
+        let env = self.app.environment_service().get_environment();
+        // note: the secrets of the code are masked like those of the
+        // conversations.
+        let redactor = Redactor::new(&env.config.redaction)?.key(&env.redaction_key);
+        if !redactor.is_empty() {
+            completion.prefix = redactor.redact(&completion.prefix, "completion").0;
+            completion.suffix = redactor.redact(&completion.suffix, "completion").0;
+        }
+        let provider = self.app.provider_service();
+
+        if let Some(format) = FimFormat::of(model) {
+            return provider.complete(model, completion.fim(format)).await;
+        }
+
+        let reply = provider
+            .chat(model, completion.context())
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?
+            .iter()
+            .filter_map(|message| message.content.as_ref())
+            .map(Content::as_str)
+            .collect::<String>();
+        Ok(CodeCompletion::parse_chat(&reply))
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use forge_app::{EnvironmentService, FileReadService, Infrastructure};
+use forge_domain::{
+    App, Content, EditedFile, LineRange, ModelId, ProviderService, Redactor, SelectionEdit,
+};
+use futures::TryStreamExt;
+
+/// Quick edits of a single file with one request to the model, without the
+/// agents, tools and context of the workflow.
+pub struct ForgeEditService<F> {
+    app: Arc<F>,
+}
+
+impl<F: App + Infrastructure> ForgeEditService<F> {
+    pub fn new(app: Arc<F>) -> Self {
+        Self { app }
+    }
+
+    pub async fn edit_selection(
+        &self,
+        model: &ModelId,
+        path: &Path,
+        range: LineRange,
+        instruction: &str,
+    ) -> Result<EditedFile> {
+        let env = self.app.environment_service().get_environment();
+        let path = env.cwd.join(path);
+        let content = self
+            .app
+            .file_read_service()
+            .read(&path)
+            .await
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let edit = SelectionEdit { path, range, instruction: instruction.to_string() };
+
+        let mut context = edit.context(&content)?;
+        // note: the secrets of the selection are masked like those of the
+        // conversations.
+        let redactor = Redactor::new(&env.config.redaction)?.key(&env.redaction_key);
+        if !redactor.is_empty() {
+            context = redactor.redact_context(context).0;
+        }
+        let response = self
+            .app
+            .provider_service()
+            .chat(model, context)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        let response = response
+            .iter()
+            .filter_map(|message| message.content.as_ref())
+            .map(Content::as_str)
+            .collect::<String>();
+        edit.apply(&content, &response)
+    }
+}
@@ -1,18 +1,38 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
 
 use forge_app::{EnvironmentService, Infrastructure};
 use forge_domain::{
-    AgentMessage, App, ChatRequest, ChatResponse, Orchestrator, SystemContext, ToolService,
+    AgentMessage, App, ChatRequest, ChatResponse, ConversationId, Orchestrator, SystemContext,
+    ToolService,
 };
 use forge_stream::MpscStream;
 use forge_walker::Walker;
+use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+
+/// The chat request a conversation is running, each conversation running at
+/// most one at a time.
+struct Run {
+    /// Sender of the answers to the questions its agents ask
+    answers: mpsc::UnboundedSender<String>,
+    abort: AbortHandle,
+}
 
 pub struct ForgeExecutorService<F> {
     infra: Arc<F>,
+    runs: Arc<Mutex<HashMap<ConversationId, Run>>>,
 }
 impl<F: Infrastructure + App> ForgeExecutorService<F> {
     pub fn new(infra: Arc<F>) -> Self {
-        Self { infra }
+        Self { infra, runs: Default::default() }
+    }
+
+    /// The runs of the conversations that haven't finished.
+    fn active(&self) -> MutexGuard<'_, HashMap<ConversationId, Run>> {
+        let mut runs = self.runs.lock().unwrap_or_else(|error| error.into_inner());
+        runs.retain(|_, run| !run.abort.is_finished());
+        runs
     }
 }
 
@@ -21,6 +41,14 @@ impl<F: Infrastructure + App> ForgeExecutorService<F> {
         &self,
         request: ChatRequest,
     ) -> anyhow::Result<MpscStream<anyhow::Result<AgentMessage<ChatResponse>>>> {
+        // note: the other conversations run concurrently, but a conversation's
+        // requests would interleave their changes to its context.
+        if self.active().contains_key(&request.conversation_id) {
+            anyhow::bail!(
+                "The conversation {} is already running",
+                request.conversation_id.into_string()
+            );
+        }
         let env = self.infra.environment_service().get_environment();
         let mut files = Walker::max_all()
             .max_depth(4)
@@ -39,17 +67,48 @@ impl<F: Infrastructure + App> ForgeExecutorService<F> {
             tool_information: Some(self.infra.tool_service().usage_prompt()),
             tool_supported: Some(true),
             files,
+            repo_map: None,
+            instructions: None,
         };
 
         let app = self.infra.clone();
-
-        Ok(MpscStream::spawn(move |tx| async move {
+        let conversation_id = request.conversation_id.clone();
+        let (answer_tx, answer_rx) = mpsc::unbounded_channel();
+        let stream = MpscStream::spawn(move |tx| async move {
             let tx = Arc::new(tx);
-            let orch = Orchestrator::new(app, request, ctx, Some(tx.clone()));
+            let orch = Orchestrator::new(app, request, ctx, Some(tx.clone())).answers(answer_rx);
             match orch.execute().await {
                 Ok(_) => {}
                 Err(err) => tx.send(Err(err)).await.unwrap(),
             }
-        }))
+        });
+        self.active().insert(
+            conversation_id,
+            Run { answers: answer_tx, abort: stream.abort_handle() },
+        );
+
+        Ok(stream)
+    }
+
+    /// Answers the question an agent of the conversation is waiting on.
+    pub fn answer(&self, conversation_id: &ConversationId, answer: String) -> anyhow::Result<()> {
+        self.active()
+            .get(conversation_id)
+            .and_then(|run| run.answers.send(answer).ok())
+            .ok_or_else(|| anyhow::anyhow!("No question is waiting for an answer"))
+    }
+
+    /// Stops the chat request the conversation is running, if any, leaving
+    /// the other conversations running.
+    pub fn cancel(&self, conversation_id: &ConversationId) -> bool {
+        self.active()
+            .get(conversation_id)
+            .map(|run| run.abort.abort())
+            .is_some()
+    }
+
+    /// The conversations that are running a chat request.
+    pub fn running(&self) -> Vec<ConversationId> {
+        self.active().keys().cloned().collect()
     }
 }
@@ -1,12 +1,17 @@
 mod api;
+mod completion;
+mod edit;
 mod executor;
 mod loader;
 mod suggestion;
 
-use std::path::Path;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 
 pub use api::*;
+pub use forge_app::{grammars, Dialect, ForgeHookService, ToolAuditLog};
 pub use forge_domain::*;
+pub use forge_infra::{config, config_path, is_configured, keychain, trust};
 use forge_stream::MpscStream;
 
 #[async_trait::async_trait]
@@ -39,9 +44,79 @@ pub trait API {
     /// precedence
     async fn load(&self, path: Option<&Path>) -> anyhow::Result<Workflow>;
 
+    /// Overrides the generation parameters of all agents in the conversation
+    async fn set_parameters(
+        &self,
+        conversation_id: &ConversationId,
+        parameters: &ModelParameters,
+    ) -> anyhow::Result<()>;
+
+    /// Switches all agents in the conversation to the given model, for the
+    /// next turns
+    async fn set_model(
+        &self,
+        conversation_id: &ConversationId,
+        model: &ModelId,
+    ) -> anyhow::Result<()>;
+
+    /// Switches all agents in the conversation to the given mode, eg: to carry
+    /// out an approved plan
+    async fn set_mode(
+        &self,
+        conversation_id: &ConversationId,
+        mode: AgentMode,
+    ) -> anyhow::Result<()>;
+
+    /// Answers the question an agent of the conversation is waiting on
+    fn answer(&self, conversation_id: &ConversationId, answer: String) -> anyhow::Result<()>;
+
+    /// Stops the chat request the conversation is running, returning whether
+    /// there was one. The other conversations keep running.
+    fn cancel(&self, conversation_id: &ConversationId) -> bool;
+
+    /// The conversations that are running a chat request
+    fn running(&self) -> Vec<ConversationId>;
+
+    /// Snapshots the state of the conversation under the given name
+    async fn checkpoint(&self, conversation_id: &ConversationId, name: &str) -> anyhow::Result<()>;
+
+    /// Rolls the conversation back to the named checkpoint
+    async fn branch(&self, conversation_id: &ConversationId, name: &str) -> anyhow::Result<()>;
+
+    /// Replaces the files whose content is given to the agents every turn
+    async fn set_pinned_files(
+        &self,
+        conversation_id: &ConversationId,
+        files: BTreeSet<PathBuf>,
+    ) -> anyhow::Result<()>;
+
     /// Returns the conversation with the given ID
     async fn conversation(
         &self,
         conversation_id: &ConversationId,
     ) -> anyhow::Result<Option<Conversation>>;
+
+    /// Adds a conversation exported elsewhere, eg: from a `.forgeconv` file,
+    /// returning its new ID
+    async fn import_conversation(
+        &self,
+        conversation: Conversation,
+    ) -> anyhow::Result<ConversationId>;
+
+    /// Edits the lines of the file following the instruction, with a single
+    /// request to the model of the workflow's first agent, and returns the
+    /// file as edited along with the diff, without writing it. It's the
+    /// primitive of the inline edits of editors.
+    async fn edit_selection(
+        &self,
+        path: &Path,
+        range: LineRange,
+        instruction: &str,
+    ) -> anyhow::Result<EditedFile>;
+
+    /// Completes the code at the cursor between the prefix and the suffix,
+    /// with a single request to the completion model of the config or else
+    /// the model of the workflow's first agent. It's the primitive of the
+    /// inline completions of editors, so it skips the agents and tools.
+    async fn complete(&self, prefix: &str, suffix: &str, language: &str) -> anyhow::Result<String>;
 }
@@ -2,7 +2,7 @@ use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::Context;
-use forge_app::{FileReadService, Infrastructure};
+use forge_app::{EnvironmentService, FileReadService, Infrastructure};
 use forge_domain::Workflow;
 
 // Default forge.yaml content embedded in the binary
@@ -21,14 +21,15 @@ impl<F> ForgeLoaderService<F> {
 impl<F: Infrastructure> ForgeLoaderService<F> {
     /// loads the workflow from the given path.
     /// Loads the workflow from the given path if provided, otherwise tries to
-    /// read from current directory's forge.yaml, and falls back to embedded
-    /// default if neither exists.
+    /// read from current directory's forge.yaml, when it's trusted, and falls
+    /// back to embedded default if neither exists.
     pub async fn load(&self, path: Option<&Path>) -> anyhow::Result<Workflow> {
+        let env = self.0.environment_service().get_environment();
         let content = match path {
             Some(path) => self.0.file_read_service().read(path).await?,
             None => {
                 let current_dir_config = Path::new("forge.yaml");
-                if current_dir_config.exists() {
+                if env.trusted && current_dir_config.exists() {
                     self.0.file_read_service().read(current_dir_config).await?
                 } else {
                     DEFAULT_FORGE_WORKFLOW.to_string()
@@ -36,8 +37,12 @@ impl<F: Infrastructure> ForgeLoaderService<F> {
             }
         };
 
-        let workflow: Workflow =
+        let mut workflow: Workflow =
             serde_yaml::from_str(&content).with_context(|| "Failed to parse workflow")?;
+
+        // note: the models of the default workflow are specific to OpenRouter, so
+        // the model of the config, eg: picked during the setup, replaces them.
+        env.config.apply(&mut workflow);
         Ok(workflow)
     }
 }
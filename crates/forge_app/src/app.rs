@@ -2,11 +2,13 @@ use std::sync::Arc;
 
 use forge_domain::App;
 
+use crate::audit::ForgeAuditService;
 use crate::conversation::ForgeConversationService;
 use crate::provider::ForgeProviderService;
 use crate::template::ForgeTemplateService;
 use crate::tool_service::ForgeToolService;
-use crate::Infrastructure;
+use crate::tools::ForgeHookService;
+use crate::{EnvironmentService, Infrastructure};
 
 /// ForgeApp is the main application container that implements the App trait.
 /// It provides access to all core services required by the application.
@@ -20,16 +22,21 @@ pub struct ForgeApp<F> {
     provider_service: ForgeProviderService,
     conversation_service: ForgeConversationService,
     prompt_service: ForgeTemplateService<F, ForgeToolService>,
+    audit_service: ForgeAuditService,
+    hook_service: ForgeHookService,
 }
 
 impl<F: Infrastructure> ForgeApp<F> {
     pub fn new(infra: Arc<F>) -> Self {
         let tool_service = Arc::new(ForgeToolService::new(infra.clone()));
+        let env = infra.environment_service().get_environment();
         Self {
             infra: infra.clone(),
             provider_service: ForgeProviderService::new(infra.clone()),
             conversation_service: ForgeConversationService::new(),
             prompt_service: ForgeTemplateService::new(infra.clone(), tool_service.clone()),
+            audit_service: ForgeAuditService::new(env.audit_path(), env.cwd.clone()),
+            hook_service: ForgeHookService::from_env(&env),
             tool_service,
         }
     }
@@ -40,6 +47,8 @@ impl<F: Infrastructure> App for ForgeApp<F> {
     type ProviderService = ForgeProviderService;
     type ConversationService = ForgeConversationService;
     type TemplateService = ForgeTemplateService<F, ForgeToolService>;
+    type AuditService = ForgeAuditService;
+    type HookService = ForgeHookService;
 
     fn tool_service(&self) -> &Self::ToolService {
         &self.tool_service
@@ -56,6 +65,14 @@ impl<F: Infrastructure> App for ForgeApp<F> {
     fn template_service(&self) -> &Self::TemplateService {
         &self.prompt_service
     }
+
+    fn audit_service(&self) -> &Self::AuditService {
+        &self.audit_service
+    }
+
+    fn hook_service(&self) -> &Self::HookService {
+        &self.hook_service
+    }
 }
 
 impl<F: Infrastructure> Infrastructure for ForgeApp<F> {
@@ -0,0 +1,654 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+use forge_domain::{
+    parse_audit_log, verify_audit_log, AuditEntry, AuditService, AuditViolation, FileChange,
+    FileSnapshot, ToolAuditEntry, ToolCallFull, ToolResult,
+};
+
+use crate::tools::PackageManager;
+
+/// Bytes read at a time from the end of the log to find its last entry.
+const CHUNK_SIZE: u64 = 8 * 1024;
+
+/// Prefix of the tools that change the dependencies of the project at their
+/// `path` argument, or the working directory.
+const PACKAGE_TOOLS: &str = "tool_forge_package_";
+
+/// Tool that renames a symbol across the files of the project.
+const RENAME_TOOL: &str = "tool_forge_lsp_rename";
+
+/// The audit log in its file, in JSON lines.
+#[derive(Debug, Clone)]
+pub struct ToolAuditLog {
+    path: PathBuf,
+}
+
+/// The last entry of the log along with the length of the file once it was
+/// appended, which tells whether another process appended since.
+#[derive(Debug, Clone)]
+struct Tail {
+    seq: u64,
+    hash: String,
+    len: u64,
+}
+
+impl ToolAuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The entries of the log, oldest first, none when it doesn't exist.
+    pub fn load(&self) -> anyhow::Result<Vec<ToolAuditEntry>> {
+        self.read()?
+            .map_or_else(|| Ok(Vec::new()), |content| parse_audit_log(&content))
+    }
+
+    /// Checks that every entry is chained to the previous one and has the
+    /// hash of its content, returning the number of entries.
+    pub fn verify(&self) -> anyhow::Result<Result<usize, AuditViolation>> {
+        self.read()?
+            .map_or(Ok(Ok(0)), |content| verify_audit_log(&content))
+    }
+
+    fn read(&self) -> anyhow::Result<Option<String>> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(content) => Ok(Some(content)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error)
+                .with_context(|| format!("Failed to read the audit log: {}", self.path.display())),
+        }
+    }
+
+    /// Chains the entry to the last one of the log and appends it, under an
+    /// exclusive lock of the file so that the forge processes sharing the log
+    /// don't fork the chain. The last entry is only read from the file when
+    /// it changed since `tail`.
+    fn append(
+        &self,
+        entry: ToolAuditEntry,
+        tail: &mut Option<Tail>,
+    ) -> anyhow::Result<ToolAuditEntry> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open the audit log: {}", self.path.display()))?;
+        file.lock()
+            .with_context(|| format!("Failed to lock the audit log: {}", self.path.display()))?;
+
+        let len = file.metadata()?.len();
+        let last = match tail.take() {
+            Some(tail) if tail.len == len => Some((tail.seq, tail.hash)),
+            _ => last_line(&mut file, len)?
+                .map(|line| {
+                    serde_json::from_str::<ToolAuditEntry>(&line)
+                        .context("Invalid last entry of the audit log")
+                })
+                .transpose()?
+                .map(|last| (last.seq, last.hash)),
+        };
+        let entry = entry.chain(last.as_ref().map(|(seq, hash)| (*seq, hash.as_str())))?;
+
+        let line = format!("{}\n", serde_json::to_string(&entry)?);
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("Failed to write the audit log: {}", self.path.display()))?;
+        *tail = Some(Tail {
+            seq: entry.seq,
+            hash: entry.hash.clone(),
+            len: len + line.len() as u64,
+        });
+        Ok(entry)
+    }
+}
+
+/// The last non-blank line of the file of `len` bytes, read backwards by
+/// chunks rather than reading the whole log.
+fn last_line(file: &mut File, len: u64) -> anyhow::Result<Option<String>> {
+    let mut buffer = Vec::new();
+    let mut start = len;
+    while start > 0 {
+        let end = start;
+        start = start.saturating_sub(CHUNK_SIZE);
+        let mut chunk = vec![0; (end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.append(&mut buffer);
+        buffer = chunk;
+
+        let content = buffer
+            .iter()
+            .rposition(|byte| !byte.is_ascii_whitespace())
+            .map_or(&buffer[..0], |last| &buffer[..=last]);
+        if content.is_empty() {
+            continue;
+        }
+        if let Some(newline) = content.iter().rposition(|byte| *byte == b'\n') {
+            return Ok(Some(String::from_utf8(content[newline + 1..].to_vec())?));
+        }
+        if start == 0 {
+            return Ok(Some(String::from_utf8(content.to_vec())?));
+        }
+    }
+    Ok(None)
+}
+
+/// The files the tool call can change: those at its paths, the files under
+/// them for directories along with where a move puts them, and the manifests
+/// and lockfiles of the project for the package tools.
+fn paths(tool_call: &ToolCallFull, cwd: &Path) -> Vec<PathBuf> {
+    let argument = |name: &str| {
+        tool_call
+            .arguments
+            .get(name)
+            .and_then(|path| path.as_str())
+            .map(|path| cwd.join(path))
+    };
+
+    if tool_call.name.as_str().starts_with(PACKAGE_TOOLS) {
+        let dir = argument("path").unwrap_or_else(|| cwd.to_path_buf());
+        return PackageManager::detect(&dir)
+            .map(|manager| {
+                manager
+                    .manifests()
+                    .iter()
+                    .chain(manager.lockfiles())
+                    .map(|file| dir.join(file))
+                    .collect()
+            })
+            .unwrap_or_default();
+    }
+
+    let mut paths = FileSnapshot::paths(tool_call)
+        .into_iter()
+        .flat_map(|path| files(&cwd.join(path)))
+        .collect::<Vec<_>>();
+    if let (Some(source), Some(destination)) = (argument("source"), argument("destination")) {
+        let moved = files(&source)
+            .into_iter()
+            .filter_map(|file| match file.strip_prefix(&source).ok()? {
+                relative if relative.as_os_str().is_empty() => Some(destination.clone()),
+                relative => Some(destination.join(relative)),
+            })
+            .collect::<Vec<_>>();
+        paths.extend(moved);
+    }
+    let mut unique = Vec::with_capacity(paths.len());
+    for path in paths {
+        if !unique.contains(&path) {
+            unique.push(path);
+        }
+    }
+    unique
+}
+
+/// The file at `path`, or the files under it when it's a directory.
+fn files(path: &Path) -> Vec<PathBuf> {
+    if !path.is_dir() {
+        return vec![path.to_path_buf()];
+    }
+    std::fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .flat_map(|entry| files(&entry.path()))
+        .collect()
+}
+
+/// The unified diff in the `<diff>` block of the result, split by file, along
+/// with the path of each file.
+fn reported(content: &str) -> Vec<(PathBuf, String)> {
+    let Some(diff) = content
+        .split_once("<diff>")
+        .and_then(|(_, rest)| rest.split_once("</diff>"))
+        .map(|(diff, _)| diff.trim_start_matches('\n'))
+    else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<(String, String)> = Vec::new();
+    for line in diff.split_inclusive('\n') {
+        if line.starts_with("--- ") || files.is_empty() {
+            files.push((String::new(), String::new()));
+        }
+        if let Some((path, diff)) = files.last_mut() {
+            if let Some(header) = line.strip_prefix("+++ ") {
+                let header = header.trim_end();
+                *path = header.strip_prefix('b').unwrap_or(header).to_string();
+            }
+            diff.push_str(line);
+        }
+    }
+    files
+        .into_iter()
+        .map(|(path, diff)| (PathBuf::from(path), diff))
+        .collect()
+}
+
+/// The content the file had before the change of the unified diff, given its
+/// content after it. `None` when the diff doesn't match the content.
+fn unapply(new: &str, diff: &str) -> Option<String> {
+    let lines = new.split_inclusive('\n').collect::<Vec<_>>();
+    let same = |line: &str, diff_line: &str| {
+        line.trim_end_matches(['\r', '\n']) == diff_line.trim_end_matches(['\r', '\n'])
+    };
+    let mut old = String::new();
+    let mut next = 0;
+    let mut in_hunk = false;
+    // Whether the last line of the diff was only in the old content, which
+    // the diff ends with a newline even when the file didn't.
+    let mut removed = false;
+    for line in diff.split_inclusive('\n') {
+        if let Some(ranges) = line.strip_prefix("@@ ") {
+            let range = ranges
+                .split(' ')
+                .find_map(|range| range.strip_prefix('+'))?;
+            let (start, len) = range.split_once(',').unwrap_or((range, "1"));
+            let (start, len) = (start.parse::<usize>().ok()?, len.parse::<usize>().ok()?);
+            // note: an empty range starts at the line before it.
+            let start = if len == 0 {
+                start
+            } else {
+                start.checked_sub(1)?
+            };
+            old.extend(lines.get(next..start)?.iter().copied());
+            (next, in_hunk, removed) = (start, true, false);
+            continue;
+        }
+        if !in_hunk {
+            continue;
+        }
+        match line.split_at_checked(1)? {
+            (" ", content) => {
+                let line = lines.get(next).filter(|line| same(line, content))?;
+                old.push_str(line);
+                (next, removed) = (next + 1, false);
+            }
+            ("+", content) => {
+                lines.get(next).filter(|line| same(line, content))?;
+                (next, removed) = (next + 1, false);
+            }
+            ("-", content) => {
+                old.push_str(content);
+                removed = true;
+            }
+            ("\\", _) if removed && old.ends_with('\n') => {
+                old.pop();
+            }
+            _ => {}
+        }
+    }
+    old.extend(lines.get(next..)?.iter().copied());
+    Some(old)
+}
+
+/// Records the tool calls in the audit log of the environment.
+pub struct ForgeAuditService {
+    log: ToolAuditLog,
+    tail: Arc<Mutex<Option<Tail>>>,
+    /// Directory the relative paths of the tool calls are resolved from
+    cwd: PathBuf,
+    /// Held while appending to the redaction log
+    redactions: Arc<Mutex<()>>,
+}
+
+impl ForgeAuditService {
+    pub fn new(path: impl Into<PathBuf>, cwd: impl Into<PathBuf>) -> Self {
+        Self {
+            log: ToolAuditLog::new(path),
+            tail: Arc::new(Mutex::new(None)),
+            cwd: cwd.into(),
+            redactions: Arc::new(Mutex::new(())),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditService for ForgeAuditService {
+    async fn snapshot(&self, tool_call: &ToolCallFull) -> FileSnapshot {
+        let (tool_call, cwd) = (tool_call.clone(), self.cwd.clone());
+        tokio::task::spawn_blocking(move || FileSnapshot {
+            files: paths(&tool_call, &cwd)
+                .into_iter()
+                .map(|path| {
+                    let content = std::fs::read(&path).ok();
+                    (path, content)
+                })
+                .collect(),
+        })
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn changes(&self, snapshot: &FileSnapshot, result: &ToolResult) -> Vec<FileChange> {
+        let files = snapshot.files.clone();
+        // note: the files a rename changes aren't known before it runs, they
+        // are taken from the diff it reports.
+        let reported = if result.name.as_str() == RENAME_TOOL && !result.is_error {
+            reported(&result.content)
+        } else {
+            Vec::new()
+        };
+        tokio::task::spawn_blocking(move || {
+            let mut changes = files
+                .into_iter()
+                .filter_map(|(path, old)| {
+                    let new = std::fs::read(&path).ok();
+                    (old != new).then(|| FileChange::new(path, old, new))
+                })
+                .collect::<Vec<_>>();
+            for (path, diff) in reported {
+                if changes.iter().any(|change| change.path == path) {
+                    continue;
+                }
+                // note: a file whose content before can't be worked out is
+                // left out, rather than taken as created by the rename.
+                let new = std::fs::read_to_string(&path).ok();
+                if let Some(old) = new.as_deref().and_then(|new| unapply(new, &diff)) {
+                    let new = new.map(String::into_bytes);
+                    changes.push(FileChange::new(path, Some(old.into_bytes()), new));
+                }
+            }
+            changes
+        })
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn append(&self, entry: ToolAuditEntry) -> anyhow::Result<ToolAuditEntry> {
+        let log = self.log.clone();
+        let tail = self.tail.clone();
+        tokio::task::spawn_blocking(move || {
+            // note: the lock serializes the appends of the agents of this
+            // process, the lock of the file those of the other processes.
+            let mut tail = tail.lock().unwrap_or_else(|error| error.into_inner());
+            log.append(entry, &mut tail)
+        })
+        .await?
+    }
+
+    async fn redactions(&self, path: &Path, entries: &[AuditEntry<'_>]) -> anyhow::Result<()> {
+        let mut lines = String::new();
+        for entry in entries {
+            lines.push_str(&serde_json::to_string(entry)?);
+            lines.push('\n');
+        }
+        let (path, lock) = (path.to_path_buf(), self.redactions.clone());
+        tokio::task::spawn_blocking(move || {
+            let _guard = lock.lock().unwrap_or_else(|error| error.into_inner());
+            append_lines(&path, &lines)
+        })
+        .await?
+    }
+}
+
+/// Appends the lines to the file at `path`, under an exclusive lock of the
+/// file so that the lines of the forge processes sharing it don't interleave.
+fn append_lines(path: &Path, lines: &str) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open the audit log: {}", path.display()))?;
+    file.lock()
+        .with_context(|| format!("Failed to lock the audit log: {}", path.display()))?;
+    file.write_all(lines.as_bytes())
+        .with_context(|| format!("Failed to write the audit log: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+    use forge_display::DiffFormat;
+    use forge_domain::{AgentId, ConversationId, Redacted, RedactionAction, ToolName};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    fn entry(tool: &str) -> ToolAuditEntry {
+        let tool_call = ToolCallFull::new(ToolName::new(tool)).arguments(json!({"path": "a.txt"}));
+        let result = ToolResult::from(tool_call.clone()).success("done");
+        ToolAuditEntry::new(
+            ConversationId::generate(),
+            AgentId::new("software-engineer"),
+            &tool_call,
+            &result,
+            &[],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_append_chains_the_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let service = ForgeAuditService::new(&path, dir.path());
+
+        let first = service.append(entry("tool_forge_fs_read")).await.unwrap();
+        let second = service.append(entry("tool_forge_fs_create")).await.unwrap();
+
+        assert_eq!(first.seq, 0);
+        assert_eq!(second.seq, 1);
+        assert_eq!(second.prev, first.hash);
+        let log = ToolAuditLog::new(&path);
+        assert_eq!(log.load().unwrap(), vec![first, second]);
+        assert_eq!(log.verify().unwrap(), Ok(2));
+    }
+
+    #[tokio::test]
+    async fn test_redactions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("redactions.jsonl");
+        let service = ForgeAuditService::new(dir.path().join("audit.jsonl"), dir.path());
+        let conversation = ConversationId::parse("0195b1a4-5f6c-7e89-a0b1-c2d3e4f50617").unwrap();
+        let agent = AgentId::new("software-engineer");
+        let redacted = Redacted {
+            kind: "email".to_string(),
+            action: RedactionAction::Anonymize,
+            replacement: "[email-3f9a1c]".to_string(),
+            source: "a user message".to_string(),
+        };
+        let entry = AuditEntry {
+            timestamp: DateTime::parse_from_rfc3339("2025-03-01T12:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            conversation: &conversation,
+            agent: &agent,
+            redacted: &redacted,
+        };
+
+        service
+            .redactions(&path, std::slice::from_ref(&entry))
+            .await
+            .unwrap();
+        service.redactions(&path, &[entry]).await.unwrap();
+
+        let actual = std::fs::read_to_string(&path).unwrap();
+        let line = r#"{"timestamp":"2025-03-01T12:00:00Z","conversation":"0195b1a4-5f6c-7e89-a0b1-c2d3e4f50617","agent":"software-engineer","kind":"email","action":"anonymize","replacement":"[email-3f9a1c]","source":"a user message"}"#;
+        assert_eq!(actual, format!("{line}\n{line}\n"));
+    }
+
+    #[tokio::test]
+    async fn test_append_after_another_process() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let (a, b) = (
+            ForgeAuditService::new(&path, dir.path()),
+            ForgeAuditService::new(&path, dir.path()),
+        );
+
+        a.append(entry("tool_forge_fs_read")).await.unwrap();
+        b.append(entry("tool_forge_fs_create")).await.unwrap();
+        let actual = a.append(entry("tool_forge_fs_patch")).await.unwrap();
+
+        assert_eq!(actual.seq, 2);
+        assert_eq!(ToolAuditLog::new(&path).verify().unwrap(), Ok(3));
+    }
+
+    #[test]
+    fn test_last_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let long = "x".repeat(CHUNK_SIZE as usize * 2);
+        let fixtures = [
+            ("", None),
+            ("\n\n", None),
+            ("one", Some("one")),
+            ("one\ntwo\n", Some("two")),
+            ("one\ntwo\n\n", Some("two")),
+        ];
+        for (content, expected) in fixtures {
+            std::fs::write(&path, content).unwrap();
+            let mut file = File::open(&path).unwrap();
+            let actual = last_line(&mut file, content.len() as u64).unwrap();
+            assert_eq!(actual.as_deref(), expected, "{content:?}");
+        }
+
+        let content = format!("one\n{long}\n");
+        std::fs::write(&path, &content).unwrap();
+        let mut file = File::open(&path).unwrap();
+        let actual = last_line(&mut file, content.len() as u64).unwrap();
+        assert_eq!(actual, Some(long));
+    }
+
+    fn tool_call(name: &str, arguments: serde_json::Value) -> ToolCallFull {
+        ToolCallFull::new(ToolName::new(name)).arguments(arguments)
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "one\ntwo\n").unwrap();
+        let tool_call = tool_call("tool_forge_fs_patch", json!({"path": "a.txt"}));
+        let result = ToolResult::from(tool_call.clone()).success("done");
+        let service = ForgeAuditService::new(dir.path().join("audit.jsonl"), dir.path());
+
+        let snapshot = service.snapshot(&tool_call).await;
+        assert_eq!(service.changes(&snapshot, &result).await, Vec::new());
+
+        std::fs::write(&path, "one\n2\n").unwrap();
+        let actual = service.changes(&snapshot, &result).await;
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].path, path);
+        assert_eq!(actual[0].old.as_deref(), Some(b"one\ntwo\n".as_slice()));
+        assert_eq!(actual[0].new.as_deref(), Some(b"one\n2\n".as_slice()));
+        assert!(actual[0].diff.contains("-two\n+2\n"));
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_moved_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+        std::fs::write(dir.path().join("src/nested/a.txt"), "a\n").unwrap();
+        let tool_call = tool_call(
+            "tool_forge_fs_move",
+            json!({"source": "src", "destination": "lib"}),
+        );
+        let result = ToolResult::from(tool_call.clone()).success("done");
+        let service = ForgeAuditService::new(dir.path().join("audit.jsonl"), dir.path());
+
+        let snapshot = service.snapshot(&tool_call).await;
+        std::fs::rename(dir.path().join("src"), dir.path().join("lib")).unwrap();
+
+        let actual = service
+            .changes(&snapshot, &result)
+            .await
+            .into_iter()
+            .map(|change| (change.path, change.old, change.new))
+            .collect::<Vec<_>>();
+        let expected = vec![
+            (
+                dir.path().join("src/nested/a.txt"),
+                Some(b"a\n".to_vec()),
+                None,
+            ),
+            (
+                dir.path().join("lib/nested/a.txt"),
+                None,
+                Some(b"a\n".to_vec()),
+            ),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_package_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[dependencies]\n").unwrap();
+        let tool_call = tool_call("tool_forge_package_add", json!({"packages": ["serde"]}));
+        let service = ForgeAuditService::new(dir.path().join("audit.jsonl"), dir.path());
+
+        let actual = service.snapshot(&tool_call).await.files;
+        let expected = vec![
+            (
+                dir.path().join("Cargo.toml"),
+                Some(b"[dependencies]\n".to_vec()),
+            ),
+            (dir.path().join("Cargo.lock"), None),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_changes_reported_by_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        let old = "fn foo() {}\n\nfn main() {\n    foo();\n}";
+        let new = "fn bar() {}\n\nfn main() {\n    bar();\n}";
+        std::fs::write(&path, new).unwrap();
+        let diff = DiffFormat::unified(&path.display().to_string(), old, new);
+        let tool_call = tool_call("tool_forge_lsp_rename", json!({"path": "lib.rs"}));
+        let result = ToolResult::from(tool_call.clone()).success(format!(
+            "Renamed to 'bar' in 1 file(s):\n{}\n<diff>\n{diff}</diff>",
+            path.display()
+        ));
+        let service = ForgeAuditService::new(dir.path().join("audit.jsonl"), dir.path());
+
+        let actual = service
+            .changes(&FileSnapshot::default(), &result)
+            .await
+            .into_iter()
+            .map(|change| (change.path, change.old, change.new))
+            .collect::<Vec<_>>();
+        let expected = vec![(
+            path,
+            Some(old.as_bytes().to_vec()),
+            Some(new.as_bytes().to_vec()),
+        )];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_unapply() {
+        let fixtures = [
+            ("a\nb\nc\n", "a\nB\nc\n"),
+            ("", "a\n"),
+            ("a\n", ""),
+            ("a\nb", "a\nb\n"),
+            ("a\nb\n", "a\nc"),
+            (
+                "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n",
+                "0\n1\n2\n3\n4\n5\n6\n7\n8\n9\n",
+            ),
+        ];
+        for (old, new) in fixtures {
+            let diff = DiffFormat::unified("/a.txt", old, new);
+            let actual = unapply(new, &diff);
+            assert_eq!(actual.as_deref(), Some(old), "{diff}");
+        }
+
+        let actual = unapply("x\ny\n", "@@ -1 +1 @@\n-a\n+b\n");
+        assert_eq!(actual, None);
+    }
+}
@@ -1,8 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use forge_domain::{
-    AgentId, Context, Conversation, ConversationId, ConversationService, Event, Workflow,
+    AgentId, AgentMode, Context, Conversation, ConversationId, ConversationService, Event, ModelId,
+    ModelParameters, Workflow,
 };
 use tokio::sync::Mutex;
 
@@ -35,6 +37,13 @@ impl ConversationService for ForgeConversationService {
         Ok(id)
     }
 
+    async fn import(&self, conversation: Conversation) -> anyhow::Result<ConversationId> {
+        let id = ConversationId::generate();
+        let conversation = conversation.id(id.clone());
+        self.workflows.lock().await.insert(id.clone(), conversation);
+        Ok(id)
+    }
+
     async fn inc_turn(&self, id: &ConversationId, agent: &AgentId) -> anyhow::Result<()> {
         if let Some(c) = self.workflows.lock().await.get_mut(id) {
             c.state.entry(agent.clone()).or_default().turn_count += 1;
@@ -62,4 +71,72 @@ impl ConversationService for ForgeConversationService {
             .push(event);
         Ok(())
     }
+
+    async fn set_parameters(
+        &self,
+        id: &ConversationId,
+        parameters: &ModelParameters,
+    ) -> anyhow::Result<()> {
+        let mut guard = self.workflows.lock().await;
+        let conversation = guard
+            .get_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?;
+        for agent in conversation.workflow.agents.iter_mut() {
+            agent.parameters = agent.parameters.clone().merge(parameters);
+        }
+        Ok(())
+    }
+
+    async fn set_model(&self, id: &ConversationId, model: &ModelId) -> anyhow::Result<()> {
+        let mut guard = self.workflows.lock().await;
+        let conversation = guard
+            .get_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?;
+        for agent in conversation.workflow.agents.iter_mut() {
+            agent.model = model.clone();
+        }
+        Ok(())
+    }
+
+    async fn set_mode(&self, id: &ConversationId, mode: AgentMode) -> anyhow::Result<()> {
+        let mut guard = self.workflows.lock().await;
+        let conversation = guard
+            .get_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?;
+        for agent in conversation.workflow.agents.iter_mut() {
+            agent.mode = mode;
+        }
+        Ok(())
+    }
+
+    async fn checkpoint(&self, id: &ConversationId, name: &str) -> anyhow::Result<()> {
+        let mut guard = self.workflows.lock().await;
+        guard
+            .get_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?
+            .checkpoint(name);
+        Ok(())
+    }
+
+    async fn branch(&self, id: &ConversationId, name: &str) -> anyhow::Result<()> {
+        let mut guard = self.workflows.lock().await;
+        guard
+            .get_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?
+            .branch(name)?;
+        Ok(())
+    }
+
+    async fn set_pinned_files(
+        &self,
+        id: &ConversationId,
+        files: BTreeSet<PathBuf>,
+    ) -> anyhow::Result<()> {
+        let mut guard = self.workflows.lock().await;
+        guard
+            .get_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?
+            .pinned_files = files;
+        Ok(())
+    }
 }
@@ -0,0 +1,159 @@
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+
+use crate::repo_map::estimate_tokens;
+
+/// Names of the files holding instructions for the agents, in the order
+/// they're read within a directory.
+const FILE_NAMES: &[&str] = &["AGENTS.md", ".forge.md", "CONVENTIONS.md"];
+
+/// Collects the instruction files of the repository at `cwd`: those of its
+/// root and of every directory down to the ones of the given paths, that the
+/// task touched. Parents come before their subdirectories, so that the more
+/// specific instructions come last, and the files that don't fit in
+/// `token_budget` are only listed.
+pub async fn instructions(cwd: &Path, paths: &[String], token_budget: usize) -> Option<String> {
+    let mut directories = BTreeSet::from([PathBuf::new()]);
+    for path in paths {
+        let Some(path) = relative(cwd, Path::new(path)) else {
+            continue;
+        };
+        let is_dir = tokio::fs::metadata(cwd.join(&path))
+            .await
+            .is_ok_and(|metadata| metadata.is_dir());
+        let directory = if is_dir {
+            path.as_path()
+        } else {
+            path.parent().unwrap_or(Path::new(""))
+        };
+        directories.extend(directory.ancestors().map(Path::to_path_buf));
+    }
+    let mut directories = directories.into_iter().collect::<Vec<_>>();
+    directories.sort_by_key(|directory| directory.components().count());
+
+    let mut sections = vec![];
+    let mut omitted = vec![];
+    let mut used = 0;
+    for directory in directories {
+        for name in FILE_NAMES {
+            let file = directory.join(name);
+            let Ok(content) = tokio::fs::read_to_string(cwd.join(&file)).await else {
+                continue;
+            };
+            let section = format!(
+                "<instructions path=\"{}\">\n{}\n</instructions>",
+                file.display(),
+                content.trim()
+            );
+            let tokens = estimate_tokens(&section);
+            if used + tokens > token_budget {
+                omitted.push(file.display().to_string());
+                continue;
+            }
+            used += tokens;
+            sections.push(section);
+        }
+    }
+
+    if !omitted.is_empty() {
+        sections.push(format!(
+            "Instruction files left out for their length, read them before working in their directory: {}",
+            omitted.join(", ")
+        ));
+    }
+    (!sections.is_empty()).then(|| sections.join("\n\n"))
+}
+
+/// The path relative to `cwd`, `None` when it's outside of it.
+fn relative(cwd: &Path, path: &Path) -> Option<PathBuf> {
+    let path = if path.is_absolute() {
+        path.strip_prefix(cwd).ok()?
+    } else {
+        path
+    };
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        .then(|| {
+            path.components()
+                .filter(|component| matches!(component, Component::Normal(_)))
+                .collect()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tokio::fs;
+
+    use super::*;
+    use crate::tools::TempDir;
+
+    async fn fixture() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path();
+        fs::write(path.join("AGENTS.md"), "Run cargo test.\n")
+            .await
+            .unwrap();
+        fs::create_dir_all(path.join("web/src")).await.unwrap();
+        fs::write(path.join("web/.forge.md"), "Use pnpm, not npm.\n")
+            .await
+            .unwrap();
+        fs::write(path.join("web/src/app.ts"), "export {}\n")
+            .await
+            .unwrap();
+        fs::create_dir(path.join("docs")).await.unwrap();
+        fs::write(
+            path.join("docs/CONVENTIONS.md"),
+            "Write in British English.\n",
+        )
+        .await
+        .unwrap();
+        temp_dir
+    }
+
+    #[tokio::test]
+    async fn test_instructions_of_touched_directories() {
+        let fixture = fixture().await;
+        let touched = fixture.path().join("web/src/app.ts");
+
+        let actual = instructions(&fixture.path(), &[touched.display().to_string()], 1000).await;
+
+        let expected = r#"<instructions path="AGENTS.md">
+Run cargo test.
+</instructions>
+
+<instructions path="web/.forge.md">
+Use pnpm, not npm.
+</instructions>"#;
+        assert_eq!(actual.as_deref(), Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_instructions_of_root_only() {
+        let fixture = fixture().await;
+
+        let actual = instructions(&fixture.path(), &["../outside.rs".to_string()], 1000).await;
+
+        let expected = "<instructions path=\"AGENTS.md\">\nRun cargo test.\n</instructions>";
+        assert_eq!(actual.as_deref(), Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_instructions_over_budget() {
+        let fixture = fixture().await;
+
+        let actual = instructions(&fixture.path(), &["docs".to_string()], 20).await;
+
+        let expected = "<instructions path=\"AGENTS.md\">\nRun cargo test.\n</instructions>\n\nInstruction files left out for their length, read them before working in their directory: docs/CONVENTIONS.md";
+        assert_eq!(actual.as_deref(), Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_no_instructions() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let actual = instructions(&temp_dir.path(), &[], 1000).await;
+
+        assert_eq!(actual, None);
+    }
+}
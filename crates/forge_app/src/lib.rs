@@ -1,6 +1,11 @@
 mod app;
+mod audit;
 mod conversation;
+mod instructions;
+mod prompts;
 mod provider;
+mod repo_map;
+mod retrieval;
 mod template;
 mod tool_service;
 mod tools;
@@ -8,7 +13,9 @@ mod tools;
 use std::path::Path;
 
 pub use app::*;
+pub use audit::ToolAuditLog;
 use forge_domain::{Point, Query, Suggestion};
+pub use tools::{grammars, Dialect, ForgeHookService};
 
 /// Repository for accessing system environment information
 #[async_trait::async_trait]
@@ -0,0 +1,219 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Context;
+use forge_domain::{AgentId, Environment};
+use handlebars::Handlebars;
+use rust_embed::Embed;
+use tokio::sync::Mutex;
+
+#[derive(Embed)]
+#[folder = "../../templates/"]
+struct Templates;
+
+/// Extension of the files that are registered as templates.
+const TEMPLATE_EXTENSION: &str = "hbs";
+
+struct Cached {
+    fingerprint: u64,
+    registry: Arc<Handlebars<'static>>,
+}
+
+/// Registry of the embedded prompt templates along with the overrides found in
+/// the user's `~/.config/forge/prompts/` and the repository's
+/// `.forge/prompts/` directories, in increasing order of precedence.
+///
+/// Overrides are registered under their path relative to the prompts
+/// directory, so `system-prompt-engineer.hbs` replaces the embedded template
+/// of the same name and `<agent-id>/system.hbs` or `<agent-id>/user.hbs`
+/// replace the prompts of a single agent. The registry is rebuilt whenever an
+/// override is added, changed or removed.
+#[derive(Clone, Default)]
+pub struct PromptLibrary {
+    cache: Arc<Mutex<Option<Cached>>>,
+}
+
+impl PromptLibrary {
+    /// Directories that are searched for overrides, lowest precedence first.
+    /// The ones of the repository are only searched when it's trusted.
+    pub fn dirs(env: &Environment) -> Vec<PathBuf> {
+        let mut dirs = Vec::new();
+        if let Some(home) = &env.home {
+            dirs.push(home.join(".config").join("forge").join("prompts"));
+        }
+        if env.trusted {
+            dirs.push(env.cwd.join(".forge").join("prompts"));
+        }
+        dirs
+    }
+
+    /// Name of the template that overrides the system prompt of an agent.
+    pub fn system_template(agent: &AgentId) -> String {
+        format!("{}/system.{TEMPLATE_EXTENSION}", agent.as_str())
+    }
+
+    /// Name of the template that overrides the user prompt of an agent.
+    pub fn user_template(agent: &AgentId) -> String {
+        format!("{}/user.{TEMPLATE_EXTENSION}", agent.as_str())
+    }
+
+    /// Returns the registry including the overrides currently present in
+    /// `dirs`.
+    pub async fn registry(&self, dirs: &[PathBuf]) -> anyhow::Result<Arc<Handlebars<'static>>> {
+        let overrides = dirs
+            .iter()
+            .flat_map(|dir| {
+                let mut files = Vec::new();
+                collect_templates(dir, dir, &mut files);
+                files.sort();
+                files
+            })
+            .collect::<Vec<_>>();
+
+        let fingerprint = fingerprint(&overrides);
+        let mut cache = self.cache.lock().await;
+        if let Some(cached) = cache.as_ref() {
+            if cached.fingerprint == fingerprint {
+                return Ok(cached.registry.clone());
+            }
+        }
+
+        let mut hb = Handlebars::new();
+        hb.set_strict_mode(true);
+        hb.register_escape_fn(|str| str.to_string());
+
+        // Register all partial templates
+        hb.register_embed_templates::<Templates>()?;
+
+        for (name, path) in overrides {
+            let template = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read prompt template {}", path.display()))?;
+            hb.register_template_string(&name, template)
+                .with_context(|| format!("Invalid prompt template {}", path.display()))?;
+        }
+
+        let registry = Arc::new(hb);
+        *cache = Some(Cached { fingerprint, registry: registry.clone() });
+        Ok(registry)
+    }
+}
+
+/// Recursively collects the templates in `dir` as pairs of their name, ie: the
+/// path relative to `root`, and their path.
+fn collect_templates(root: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            collect_templates(root, &path, files);
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext == TEMPLATE_EXTENSION)
+        {
+            if let Ok(relative) = path.strip_prefix(root) {
+                let name = relative
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.push((name, path));
+            }
+        }
+    }
+}
+
+/// Hash of the override paths along with their size and modification time.
+fn fingerprint(overrides: &[(String, PathBuf)]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for (name, path) in overrides {
+        name.hash(&mut hasher);
+        if let Ok(metadata) = std::fs::metadata(path) {
+            metadata.len().hash(&mut hasher);
+            metadata.modified().ok().hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+    use crate::tools::TempDir;
+
+    #[tokio::test]
+    async fn test_embedded_templates() {
+        let fixture = TempDir::new().unwrap();
+        let library = PromptLibrary::default();
+
+        let actual = library.registry(&[fixture.path()]).await.unwrap();
+
+        assert!(actual.has_template("system-prompt-engineer.hbs"));
+    }
+
+    #[tokio::test]
+    async fn test_override_partial() {
+        let fixture = TempDir::new().unwrap();
+        std::fs::write(
+            fixture.path().join("partial-tool-examples.hbs"),
+            "custom examples",
+        )
+        .unwrap();
+        let library = PromptLibrary::default();
+
+        let registry = library.registry(&[fixture.path()]).await.unwrap();
+        let actual = registry
+            .render_template("{{> partial-tool-examples.hbs }}", &json!({}))
+            .unwrap();
+
+        assert_eq!(actual, "custom examples");
+    }
+
+    #[tokio::test]
+    async fn test_agent_template_and_reload() {
+        let fixture = TempDir::new().unwrap();
+        let agent = AgentId::new("software-engineer");
+        let library = PromptLibrary::default();
+
+        let registry = library.registry(&[fixture.path()]).await.unwrap();
+        assert!(!registry.has_template(&PromptLibrary::system_template(&agent)));
+
+        std::fs::create_dir_all(fixture.path().join("software-engineer")).unwrap();
+        std::fs::write(
+            fixture.path().join("software-engineer").join("system.hbs"),
+            "You are {{name}}",
+        )
+        .unwrap();
+
+        let registry = library.registry(&[fixture.path()]).await.unwrap();
+        let actual = registry
+            .render(
+                &PromptLibrary::system_template(&agent),
+                &json!({"name": "forge"}),
+            )
+            .unwrap();
+
+        assert_eq!(actual, "You are forge");
+    }
+
+    #[tokio::test]
+    async fn test_later_directories_take_precedence() {
+        let user = TempDir::new().unwrap();
+        let repo = TempDir::new().unwrap();
+        std::fs::write(user.path().join("partial-tool-examples.hbs"), "user").unwrap();
+        std::fs::write(repo.path().join("partial-tool-examples.hbs"), "repo").unwrap();
+        let library = PromptLibrary::default();
+
+        let registry = library.registry(&[user.path(), repo.path()]).await.unwrap();
+        let actual = registry
+            .render_template("{{> partial-tool-examples.hbs }}", &json!({}))
+            .unwrap();
+
+        assert_eq!(actual, "repo");
+    }
+}
@@ -1,29 +1,71 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use forge_domain::{
-    ChatCompletionMessage, Context as ChatContext, Model, ModelId, Parameters, ProviderService,
-    ResultStream,
+    ChatCompletionMessage, Context as ChatContext, Model, ModelCapabilities, ModelId,
+    ProviderService, RateLimiter, ResponseCache, ResultStream, TextCompletion,
 };
-use forge_open_router::ProviderBuilder;
+use forge_open_router::{CachedProvider, ProviderBuilder, ReplayProvider};
 use moka2::future::Cache;
+use tokio_stream::StreamExt;
+use tracing::{field, info_span, Instrument};
 
 use crate::{EnvironmentService, Infrastructure};
 
 pub struct ForgeProviderService {
     or: Box<dyn ProviderService>,
-    cache: Cache<ModelId, Parameters>,
+    cache: Cache<ModelId, ModelCapabilities>,
+    limiter: Mutex<RateLimiter>,
 }
 
 impl ForgeProviderService {
     pub fn new<F: Infrastructure>(infra: Arc<F>) -> Self {
         let env = infra.environment_service().get_environment();
-        let or = ProviderBuilder::from_url(env.provider_url)
-            .with_key(env.provider_key)
-            .build()
-            .expect("Failed to build provider");
+        let or: Box<dyn ProviderService> = match (&env.replay_path, &env.record_path) {
+            (Some(path), _) => {
+                Box::new(ReplayProvider::replay(path).expect("Failed to load the recording"))
+            }
+            (None, record_path) => {
+                let provider = ProviderBuilder::from_url(&env.provider_url)
+                    .with_key(&env.provider_key)
+                    .build()
+                    .expect("Failed to build provider");
+                match record_path {
+                    Some(path) => {
+                        let secrets = [Some(env.provider_key.clone()), env.openai_key.clone()];
+                        Box::new(
+                            ReplayProvider::record(
+                                provider,
+                                path,
+                                secrets.into_iter().flatten().collect(),
+                            )
+                            .expect("Failed to start the recording"),
+                        )
+                    }
+                    None => provider,
+                }
+            }
+        };
+        // note: a recording is replayed as is, without the cache.
+        let response_cache = &env.config.response_cache;
+        let or: Box<dyn ProviderService> = match response_cache.enabled {
+            Some(true) if env.replay_path.is_none() => {
+                let dir = response_cache
+                    .dir
+                    .clone()
+                    .unwrap_or_else(|| env.base_path.join("cache").join("responses"));
+                let ttl = response_cache.ttl.unwrap_or(ResponseCache::DEFAULT_TTL);
+                Box::new(CachedProvider::new(or, dir, Duration::from_secs(ttl)))
+            }
+            _ => or,
+        };
 
-        Self { or, cache: Cache::new(1024) }
+        Self {
+            or,
+            cache: Cache::new(1024),
+            limiter: Mutex::new(RateLimiter::new(env.config.rate_limit)),
+        }
     }
 }
 
@@ -34,26 +76,58 @@ impl ProviderService for ForgeProviderService {
         model_id: &ModelId,
         request: ChatContext,
     ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
-        self.or
+        // note: the span is held by the stream, so that it lasts until the
+        // response is complete and carries its usage.
+        let span = info_span!(
+            "chat",
+            model = %model_id,
+            prompt_tokens = field::Empty,
+            completion_tokens = field::Empty,
+            cached_tokens = field::Empty,
+        );
+        let stream = self
+            .or
             .chat(model_id, request)
+            .instrument(span.clone())
+            .await
+            .with_context(|| format!("Failed to chat with model: {}", model_id))?;
+
+        Ok(Box::pin(stream.map(move |message| {
+            if let Ok(ChatCompletionMessage { usage: Some(usage), .. }) = &message {
+                span.record("prompt_tokens", usage.prompt_tokens);
+                span.record("completion_tokens", usage.completion_tokens);
+                span.record("cached_tokens", usage.cached_tokens);
+            }
+            message
+        })))
+    }
+
+    async fn complete(&self, model_id: &ModelId, request: TextCompletion) -> Result<String> {
+        self.or
+            .complete(model_id, request)
+            .instrument(info_span!("complete", model = %model_id))
             .await
-            .with_context(|| format!("Failed to chat with model: {}", model_id))
+            .with_context(|| format!("Failed to complete with model: {}", model_id))
     }
 
     async fn models(&self) -> Result<Vec<Model>> {
         self.or.models().await
     }
 
-    async fn parameters(&self, model: &ModelId) -> anyhow::Result<Parameters> {
+    async fn capabilities(&self, model: &ModelId) -> anyhow::Result<ModelCapabilities> {
         Ok(self
             .cache
             .try_get_with_by_ref(model, async {
                 self.or
-                    .parameters(model)
+                    .capabilities(model)
                     .await
-                    .with_context(|| format!("Failed to get parameters for model: {}", model))
+                    .with_context(|| format!("Failed to get capabilities for model: {}", model))
             })
             .await
             .map_err(|e| anyhow::anyhow!(e))?)
     }
+
+    async fn reserve(&self, tokens: u64) -> Duration {
+        self.limiter.lock().unwrap().reserve(tokens)
+    }
 }
@@ -0,0 +1,399 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use forge_walker::Walker;
+use regex::Regex;
+use tokio::sync::Mutex;
+
+use crate::tools::{definitions, Definition};
+
+/// Files larger than this are left out of the map.
+pub(crate) const MAX_FILE_SIZE: u64 = 256 * 1024;
+
+/// Maximum depth of the directories listed in the map.
+const MAX_DIRECTORY_DEPTH: usize = 2;
+
+/// Maximum number of directories listed in the map.
+const MAX_DIRECTORIES: usize = 30;
+
+/// Files that describe a project and are always worth knowing about.
+const KEY_FILES: &[&str] = &[
+    "README.md",
+    "Cargo.toml",
+    "package.json",
+    "pyproject.toml",
+    "setup.py",
+    "go.mod",
+    "pom.xml",
+    "build.gradle",
+    "Gemfile",
+    "composer.json",
+    "Makefile",
+    "Dockerfile",
+];
+
+/// Size and modification time of a file, which tell whether it changed since
+/// it was read.
+type Stamp = (u64, Option<SystemTime>);
+
+struct Cached {
+    fingerprint: u64,
+    token_budget: usize,
+    map: String,
+}
+
+#[derive(Default)]
+struct Cache {
+    /// Each file read so far along with its stamp at the time, `None` for the
+    /// ones that define nothing.
+    sources: HashMap<String, (Option<Stamp>, Option<SourceFile>)>,
+    map: Option<Cached>,
+}
+
+/// Source file along with the definitions it contains and the identifiers it
+/// uses.
+struct SourceFile {
+    path: String,
+    definitions: Vec<Definition>,
+    identifiers: HashSet<String>,
+}
+
+/// Builds a ranked, token budgeted summary of a repository: its directory
+/// structure, key files and the definitions of the most referenced source
+/// files. The map is cached and only rebuilt when files change, reading only
+/// the files that changed.
+#[derive(Clone, Default)]
+pub struct RepoMap {
+    cache: Arc<Mutex<Cache>>,
+}
+
+impl RepoMap {
+    /// Returns the map of the repository at `cwd`, limited to roughly
+    /// `token_budget` tokens.
+    pub async fn render(&self, cwd: &Path, token_budget: usize) -> anyhow::Result<String> {
+        let mut files = Walker::max_all()
+            .cwd(cwd.to_path_buf())
+            .max_file_size(MAX_FILE_SIZE)
+            .skip_binary(true)
+            .get()
+            .await?
+            .into_iter()
+            .filter(|file| !file.is_dir())
+            .map(|file| file.path)
+            .collect::<Vec<_>>();
+        files.sort();
+
+        let fingerprint = fingerprint(cwd, &files);
+        let mut cache = self.cache.lock().await;
+        if let Some(cached) = cache.map.as_ref() {
+            if cached.fingerprint == fingerprint && cached.token_budget == token_budget {
+                return Ok(cached.map.clone());
+            }
+        }
+
+        let map = build(cwd, &files, token_budget, &mut cache.sources).await;
+        cache.map = Some(Cached { fingerprint, token_budget, map: map.clone() });
+        Ok(map)
+    }
+}
+
+/// Hashes the paths, sizes and modification times of the files so that any
+/// change to the repository can be detected without reading the files.
+pub(crate) fn fingerprint(cwd: &Path, files: &[String]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for file in files {
+        file.hash(&mut hasher);
+        stamp(&cwd.join(file)).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+fn stamp(path: &Path) -> Option<Stamp> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()))
+}
+
+/// Rough estimate of the number of tokens in the text.
+pub(crate) fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// Reads the definitions and identifiers of the file, `None` when it can't be
+/// read or defines nothing.
+async fn parse(cwd: &Path, path: &str, identifier: &Regex) -> Option<SourceFile> {
+    let content = tokio::fs::read_to_string(cwd.join(path)).await.ok()?;
+    let definitions = definitions(Path::new(path), &content).ok()?;
+    if definitions.is_empty() {
+        return None;
+    }
+
+    let identifiers = identifier
+        .find_iter(&content)
+        .map(|m| m.as_str().to_string())
+        .collect();
+    Some(SourceFile { path: path.to_string(), definitions, identifiers })
+}
+
+/// Builds the map of the `files`, which are sorted, reusing the files of
+/// `cache` that didn't change since they were read.
+async fn build(
+    cwd: &Path,
+    files: &[String],
+    token_budget: usize,
+    cache: &mut HashMap<String, (Option<Stamp>, Option<SourceFile>)>,
+) -> String {
+    let identifier = Regex::new(r"[A-Za-z_][A-Za-z0-9_]{2,}").unwrap();
+
+    cache.retain(|path, _| files.binary_search(path).is_ok());
+    for path in files {
+        let stamp = stamp(&cwd.join(path));
+        if stamp.is_some() && cache.get(path).is_some_and(|(cached, _)| *cached == stamp) {
+            continue;
+        }
+        let source = parse(cwd, path, &identifier).await;
+        cache.insert(path.clone(), (stamp, source));
+    }
+    let sources = files
+        .iter()
+        .filter_map(|path| cache.get(path)?.1.as_ref())
+        .collect::<Vec<_>>();
+
+    // Rank files by the number of other files that refer to their definitions.
+    let mut ranked = sources
+        .iter()
+        .map(|source| {
+            let names = source
+                .definitions
+                .iter()
+                .map(|definition| definition.name.as_str())
+                .filter(|name| name.len() > 2)
+                .collect::<HashSet<_>>();
+            let score = names
+                .iter()
+                .map(|name| {
+                    sources
+                        .iter()
+                        .filter(|other| other.path != source.path)
+                        .filter(|other| other.identifiers.contains(*name))
+                        .count()
+                })
+                .sum::<usize>();
+            (score, source)
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.path.cmp(&b.1.path)));
+
+    let mut sections = vec![];
+    let mut used = 0;
+
+    if let Some(section) = directories(files, token_budget) {
+        used += estimate_tokens(&section);
+        sections.push(section);
+    }
+
+    let key_files = files
+        .iter()
+        .filter(|file| KEY_FILES.contains(&file.as_str()))
+        .map(|file| file.as_str())
+        .collect::<Vec<_>>();
+    if !key_files.is_empty() {
+        let section = format!("Key files: {}", key_files.join(", "));
+        let tokens = estimate_tokens(&section);
+        if used + tokens <= token_budget {
+            used += tokens;
+            sections.push(section);
+        }
+    }
+
+    let mut omitted = 0;
+    for (_, source) in &ranked {
+        let lines = source
+            .definitions
+            .iter()
+            .map(|definition| {
+                format!(
+                    "│{}: [{}] {}",
+                    definition.line, definition.kind, definition.text
+                )
+            })
+            .collect::<Vec<_>>();
+        let section = format!("{}\n{}", source.path, lines.join("\n"));
+        let tokens = estimate_tokens(&section);
+        if used + tokens > token_budget {
+            omitted += 1;
+            continue;
+        }
+        used += tokens;
+        sections.push(section);
+    }
+
+    if omitted > 0 {
+        sections.push(format!("... {} more source files omitted", omitted));
+    }
+
+    sections.join("\n\n")
+}
+
+/// Lists the directories up to [`MAX_DIRECTORY_DEPTH`] along with the number
+/// of files they contain, as many as fit in `token_budget` tokens. `None`
+/// when none fits.
+fn directories(files: &[String], token_budget: usize) -> Option<String> {
+    let mut counts = BTreeMap::<String, usize>::new();
+    for file in files {
+        let components = file.split('/').collect::<Vec<_>>();
+        for depth in 1..components.len().min(MAX_DIRECTORY_DEPTH + 1) {
+            *counts.entry(components[..depth].join("/")).or_default() += 1;
+        }
+    }
+
+    let directories = counts
+        .into_iter()
+        .map(|(dir, count)| format!("{}/ ({} files)", dir, count))
+        .collect::<Vec<_>>();
+    (1..=directories.len().min(MAX_DIRECTORIES))
+        .rev()
+        .map(|listed| {
+            let mut section = format!("Directories:\n{}", directories[..listed].join("\n"));
+            if listed < directories.len() {
+                let omitted = directories.len() - listed;
+                section.push_str(&format!("\n... {} more directories", omitted));
+            }
+            section
+        })
+        .find(|section| estimate_tokens(section) <= token_budget)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tokio::fs;
+
+    use super::*;
+    use crate::tools::TempDir;
+
+    async fn fixture() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("Cargo.toml"), "[package]\n")
+            .await
+            .unwrap();
+        fs::create_dir(temp_dir.path().join("src")).await.unwrap();
+        fs::write(
+            temp_dir.path().join("src/util.rs"),
+            "pub fn shared_helper() {}\n",
+        )
+        .await
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("src/a.rs"),
+            "fn lonely() { shared_helper(); }\n",
+        )
+        .await
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("src/b.rs"),
+            "fn other() { shared_helper(); }\n",
+        )
+        .await
+        .unwrap();
+        temp_dir
+    }
+
+    #[tokio::test]
+    async fn test_render_ranks_referenced_files_first() {
+        let fixture = fixture().await;
+
+        let actual = RepoMap::default()
+            .render(&fixture.path(), 1000)
+            .await
+            .unwrap();
+
+        let expected = r#"Directories:
+src/ (3 files)
+
+Key files: Cargo.toml
+
+src/util.rs
+│1: [function] pub fn shared_helper() {}
+
+src/a.rs
+│1: [function] fn lonely() { shared_helper(); }
+
+src/b.rs
+│1: [function] fn other() { shared_helper(); }"#;
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_render_respects_token_budget() {
+        let fixture = fixture().await;
+
+        let actual = RepoMap::default()
+            .render(&fixture.path(), 30)
+            .await
+            .unwrap();
+
+        assert!(actual.contains("src/util.rs"));
+        assert!(actual.ends_with("... 2 more source files omitted"));
+    }
+
+    #[tokio::test]
+    async fn test_render_refreshes_when_files_change() {
+        let fixture = fixture().await;
+        let repo_map = RepoMap::default();
+        repo_map.render(&fixture.path(), 1000).await.unwrap();
+
+        fs::write(
+            fixture.path().join("src/b.rs"),
+            "fn other() {}\nfn added() {}\n",
+        )
+        .await
+        .unwrap();
+        let actual = repo_map.render(&fixture.path(), 1000).await.unwrap();
+
+        assert!(actual.contains("│2: [function] fn added() {}"));
+    }
+
+    #[tokio::test]
+    async fn test_render_reads_only_changed_files() {
+        let fixture = fixture().await;
+        let repo_map = RepoMap::default();
+        repo_map.render(&fixture.path(), 1000).await.unwrap();
+
+        // note: a file whose size and modification time are unchanged isn't
+        // read again.
+        let b = fixture.path().join("src/b.rs");
+        let modified = std::fs::metadata(&b).unwrap().modified().unwrap();
+        fs::write(&b, "fn renam() { shared_helper(); }\n")
+            .await
+            .unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&b)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        fs::write(fixture.path().join("src/a.rs"), "fn lonely() {}\n")
+            .await
+            .unwrap();
+        let actual = repo_map.render(&fixture.path(), 1000).await.unwrap();
+
+        assert!(actual.contains("│1: [function] fn lonely() {}"));
+        assert!(actual.contains("│1: [function] fn other() { shared_helper(); }"));
+    }
+
+    #[test]
+    fn test_directories_respect_token_budget() {
+        let files = ["a/x.rs", "b/x.rs", "c/x.rs"].map(String::from);
+
+        let actual = [13, 12, 3].map(|token_budget| directories(&files, token_budget));
+        let expected = [
+            Some("Directories:\na/ (1 files)\nb/ (1 files)\nc/ (1 files)".to_string()),
+            Some("Directories:\na/ (1 files)\n... 2 more directories".to_string()),
+            None,
+        ];
+        assert_eq!(actual, expected);
+    }
+}
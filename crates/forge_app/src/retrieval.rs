@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use forge_domain::RelevantFile;
+use forge_walker::Walker;
+use tokio::sync::Mutex;
+
+use crate::repo_map::{estimate_tokens, fingerprint, MAX_FILE_SIZE};
+
+/// Maximum number of files attached to a message.
+const MAX_FILES: usize = 5;
+
+/// Term frequency saturation of BM25.
+const K1: f64 = 1.2;
+
+/// Length normalization of BM25.
+const B: f64 = 0.75;
+
+/// Terms of the path count as much as this many occurrences in the content,
+/// as a file named after a term of the query is likely what it's about.
+const PATH_WEIGHT: usize = 5;
+
+/// Words too common in messages to tell files apart.
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "with", "that", "this", "from", "into", "are", "was", "were", "you",
+    "your", "not", "but", "all", "can", "use", "should", "would", "could", "have", "has", "make",
+    "when", "then", "them", "they", "there", "what", "which", "how", "why", "add", "fix", "let",
+    "please", "also", "its", "our", "out", "new", "get", "set",
+];
+
+struct Document {
+    path: String,
+    terms: HashMap<String, usize>,
+    len: usize,
+}
+
+/// BM25 index of the files of a repository.
+struct Index {
+    fingerprint: u64,
+    documents: Vec<Document>,
+    /// Number of documents each term appears in
+    frequencies: HashMap<String, usize>,
+    average_len: f64,
+}
+
+impl Index {
+    async fn build(cwd: &Path, files: &[String], fingerprint: u64) -> Self {
+        let mut documents = vec![];
+        let mut frequencies = HashMap::<String, usize>::new();
+        for path in files {
+            let Ok(content) = tokio::fs::read_to_string(cwd.join(path)).await else {
+                continue;
+            };
+            let mut terms = HashMap::<String, usize>::new();
+            for term in tokenize(&content) {
+                *terms.entry(term).or_default() += 1;
+            }
+            for term in tokenize(path) {
+                *terms.entry(term).or_default() += PATH_WEIGHT;
+            }
+            for term in terms.keys() {
+                *frequencies.entry(term.clone()).or_default() += 1;
+            }
+            let len = terms.values().sum();
+            documents.push(Document { path: path.clone(), terms, len });
+        }
+        let average_len = documents.iter().map(|document| document.len).sum::<usize>() as f64
+            / documents.len().max(1) as f64;
+        Self { fingerprint, documents, frequencies, average_len }
+    }
+
+    /// Documents that match the query, the best first.
+    fn search(&self, query: &str) -> Vec<&Document> {
+        let mut terms = tokenize(query);
+        terms.sort();
+        terms.dedup();
+
+        let count = self.documents.len() as f64;
+        let mut scored = self
+            .documents
+            .iter()
+            .map(|document| {
+                let score = terms
+                    .iter()
+                    .filter_map(|term| {
+                        let frequency = *document.terms.get(term)? as f64;
+                        let documents = self.frequencies[term] as f64;
+                        let idf = ((count - documents + 0.5) / (documents + 0.5) + 1.0).ln();
+                        let norm = 1.0 - B + B * document.len as f64 / self.average_len;
+                        Some(idf * frequency * (K1 + 1.0) / (frequency + K1 * norm))
+                    })
+                    .sum::<f64>();
+                (score, document)
+            })
+            .filter(|(score, _)| *score > 0.0)
+            .collect::<Vec<_>>();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.path.cmp(&b.1.path)));
+        scored.into_iter().map(|(_, document)| document).collect()
+    }
+}
+
+/// Finds the files of a repository relevant to a message of the user, by
+/// ranking them with BM25 against the terms of the message. The index is
+/// cached and only rebuilt when files change.
+#[derive(Clone, Default)]
+pub struct Retriever {
+    index: Arc<Mutex<Option<Index>>>,
+}
+
+impl Retriever {
+    /// Returns up to [`MAX_FILES`] files of the repository at `cwd` that are
+    /// the most relevant to the query and fit in `token_budget` tokens.
+    pub async fn search(
+        &self,
+        cwd: &Path,
+        query: &str,
+        token_budget: usize,
+    ) -> anyhow::Result<Vec<RelevantFile>> {
+        let mut files = Walker::max_all()
+            .cwd(cwd.to_path_buf())
+            .max_file_size(MAX_FILE_SIZE)
+            .skip_binary(true)
+            .get()
+            .await?
+            .into_iter()
+            .filter(|file| !file.is_dir())
+            .map(|file| file.path)
+            .collect::<Vec<_>>();
+        files.sort();
+
+        let fingerprint = fingerprint(cwd, &files);
+        let mut index = self.index.lock().await;
+        let index = match index.take() {
+            Some(cached) if cached.fingerprint == fingerprint => index.insert(cached),
+            _ => index.insert(Index::build(cwd, &files, fingerprint).await),
+        };
+
+        let mut relevant = vec![];
+        let mut used = 0;
+        for document in index.search(query) {
+            if relevant.len() == MAX_FILES {
+                break;
+            }
+            let Ok(content) = tokio::fs::read_to_string(cwd.join(&document.path)).await else {
+                continue;
+            };
+            let tokens = estimate_tokens(&content);
+            if used + tokens > token_budget {
+                continue;
+            }
+            used += tokens;
+            relevant.push(RelevantFile { path: document.path.clone(), tokens, content });
+        }
+        Ok(relevant)
+    }
+}
+
+/// Lowercase terms of the text, splitting identifiers in `snake_case` and
+/// `camelCase` into their words.
+fn tokenize(text: &str) -> Vec<String> {
+    let mut terms = vec![];
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        let mut term = String::new();
+        let mut previous_lower = false;
+        for c in word.chars() {
+            if c.is_uppercase() && previous_lower {
+                terms.push(std::mem::take(&mut term));
+            }
+            previous_lower = c.is_lowercase() || c.is_numeric();
+            term.extend(c.to_lowercase());
+        }
+        terms.push(term);
+    }
+    terms.retain(|term| term.chars().count() > 2 && !STOP_WORDS.contains(&term.as_str()));
+    terms
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tokio::fs;
+
+    use super::*;
+    use crate::tools::TempDir;
+
+    async fn fixture() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("src")).await.unwrap();
+        fs::write(
+            temp_dir.path().join("src/billing.rs"),
+            "pub fn compute_invoice_total(items: &[Item]) -> u64 { items.iter().map(|i| i.price).sum() }\n",
+        )
+        .await
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("src/auth.rs"),
+            "pub fn verify_password(hash: &str, password: &str) -> bool { hash == password }\n",
+        )
+        .await
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("README.md"),
+            "A sample project with billing and authentication.\n",
+        )
+        .await
+        .unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let actual = tokenize("Fix the computeInvoiceTotal in src/billing_v2.rs");
+        let expected = ["compute", "invoice", "total", "src", "billing"].map(String::from);
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_matching_files_first() {
+        let fixture = fixture().await;
+
+        let actual = Retriever::default()
+            .search(&fixture.path(), "The invoice total is wrong", 1000)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|file| file.path)
+            .collect::<Vec<_>>();
+
+        assert_eq!(actual, vec!["src/billing.rs".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_search_respects_token_budget() {
+        let fixture = fixture().await;
+
+        let actual = Retriever::default()
+            .search(&fixture.path(), "billing password", 20)
+            .await
+            .unwrap();
+
+        assert!(actual.iter().map(|file| file.tokens).sum::<usize>() <= 20);
+        assert!(actual.iter().all(|file| file.path != "src/billing.rs"));
+    }
+
+    #[tokio::test]
+    async fn test_search_without_match() {
+        let fixture = fixture().await;
+
+        let actual = Retriever::default()
+            .search(&fixture.path(), "kubernetes deployment", 1000)
+            .await
+            .unwrap();
+
+        assert_eq!(actual, vec![]);
+    }
+}
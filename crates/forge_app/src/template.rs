@@ -1,35 +1,35 @@
 use std::sync::Arc;
 
 use forge_domain::{
-    Agent, Event, EventContext, Query, SystemContext, Template, TemplateService, ToolService,
+    Agent, Event, EventContext, Query, RelevantFile, SystemContext, Template, TemplateService,
+    ToolService,
 };
 use forge_walker::Walker;
-use handlebars::Handlebars;
-use rust_embed::Embed;
-use tracing::debug;
+use tracing::{debug, warn};
 
+use crate::instructions::instructions;
+use crate::prompts::PromptLibrary;
+use crate::repo_map::RepoMap;
+use crate::retrieval::Retriever;
 use crate::{EmbeddingService, EnvironmentService, Infrastructure, VectorIndex};
 
-#[derive(Embed)]
-#[folder = "../../templates/"]
-struct Templates;
-
 pub struct ForgeTemplateService<F, T> {
-    hb: Handlebars<'static>,
+    prompts: PromptLibrary,
     infra: Arc<F>,
     tool_service: Arc<T>,
+    repo_map: RepoMap,
+    retriever: Retriever,
 }
 
 impl<F, T> ForgeTemplateService<F, T> {
     pub fn new(infra: Arc<F>, tool_service: Arc<T>) -> Self {
-        let mut hb = Handlebars::new();
-        hb.set_strict_mode(true);
-        hb.register_escape_fn(|str| str.to_string());
-
-        // Register all partial templates
-        hb.register_embed_templates::<Templates>().unwrap();
-
-        Self { hb, infra, tool_service }
+        Self {
+            prompts: PromptLibrary::default(),
+            infra,
+            tool_service,
+            repo_map: RepoMap::default(),
+            retriever: Retriever::default(),
+        }
     }
 }
 
@@ -39,6 +39,7 @@ impl<F: Infrastructure, T: ToolService> TemplateService for ForgeTemplateService
         &self,
         agent: &Agent,
         prompt: &Template<SystemContext>,
+        paths: &[String],
     ) -> anyhow::Result<String> {
         let env = self.infra.environment_service().get_environment();
 
@@ -56,14 +57,37 @@ impl<F: Infrastructure, T: ToolService> TemplateService for ForgeTemplateService
         // Sort the files alphabetically to ensure consistent ordering
         files.sort();
 
+        let repo_map = if agent.repo_map {
+            match self.repo_map.render(&env.cwd, agent.repo_map_tokens).await {
+                Ok(map) => Some(map),
+                Err(error) => {
+                    warn!(%error, "Failed to build the repository map");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let instructions = instructions(&env.cwd, paths, agent.instructions_tokens).await;
+
+        let hb = self.prompts.registry(&PromptLibrary::dirs(&env)).await?;
+
         let ctx = SystemContext {
             env: Some(env),
             tool_information: Some(self.tool_service.usage_prompt()),
             tool_supported: Some(true),
             files,
+            repo_map,
+            instructions,
         };
 
-        Ok(self.hb.render_template(prompt.template.as_str(), &ctx)?)
+        let name = PromptLibrary::system_template(&agent.id);
+        if hb.has_template(&name) {
+            return Ok(hb.render(&name, &ctx)?);
+        }
+
+        Ok(hb.render_template(prompt.template.as_str(), &ctx)?)
     }
 
     async fn render_event(
@@ -98,9 +122,24 @@ impl<F: Infrastructure, T: ToolService> TemplateService for ForgeTemplateService
             event_context = event_context.suggestions(suggestion_strings);
         }
 
+        let env = self.infra.environment_service().get_environment();
+        let hb = self.prompts.registry(&PromptLibrary::dirs(&env)).await?;
+
         // Render the template with the event context
-        Ok(self
-            .hb
-            .render_template(prompt.template.as_str(), &event_context)?)
+        let name = PromptLibrary::user_template(&agent.id);
+        if hb.has_template(&name) {
+            return Ok(hb.render(&name, &event_context)?);
+        }
+
+        Ok(hb.render_template(prompt.template.as_str(), &event_context)?)
+    }
+
+    async fn relevant_files(
+        &self,
+        query: &str,
+        token_budget: usize,
+    ) -> anyhow::Result<Vec<RelevantFile>> {
+        let env = self.infra.environment_service().get_environment();
+        self.retriever.search(&env.cwd, query, token_budget).await
     }
 }
@@ -2,8 +2,9 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use forge_domain::{Tool, ToolCallFull, ToolDefinition, ToolName, ToolResult, ToolService};
+use serde_json::Value;
 use tokio::time::{timeout, Duration};
-use tracing::{debug, error};
+use tracing::{debug, error, instrument};
 
 use crate::Infrastructure;
 
@@ -12,6 +13,8 @@ const TOOL_CALL_TIMEOUT: Duration = Duration::from_secs(300);
 
 pub struct ForgeToolService {
     tools: HashMap<ToolName, Tool>,
+    /// Validators compiled from the input schema of each tool
+    validators: HashMap<ToolName, jsonschema::Validator>,
 }
 
 impl ForgeToolService {
@@ -27,12 +30,56 @@ impl FromIterator<Tool> for ForgeToolService {
             .map(|tool| (tool.definition.name.clone(), tool))
             .collect::<HashMap<_, _>>();
 
-        Self { tools }
+        let validators = tools
+            .iter()
+            .filter_map(|(name, tool)| {
+                let schema = serde_json::to_value(&tool.definition.input_schema).ok()?;
+                match jsonschema::validator_for(&schema) {
+                    Ok(validator) => Some((name.clone(), validator)),
+                    Err(err) => {
+                        error!(tool_name = %name.as_str(), error = %err, "Invalid tool input schema");
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        Self { tools, validators }
+    }
+}
+
+impl ForgeToolService {
+    /// Checks the arguments against the input schema of the tool so that the
+    /// model gets every missing or invalid field at once, instead of the first
+    /// deserialization failure.
+    fn validate(&self, name: &ToolName, arguments: &Value) -> anyhow::Result<()> {
+        let Some(validator) = self.validators.get(name) else {
+            return Ok(());
+        };
+
+        let errors = validator
+            .iter_errors(arguments)
+            .map(|error| match error.instance_path.to_string() {
+                path if path.is_empty() => format!("- {error}"),
+                path => format!("- {path}: {error}"),
+            })
+            .collect::<Vec<_>>();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Invalid arguments for tool '{}', fix the following and call it again:\n{}",
+                name.as_str(),
+                errors.join("\n")
+            ))
+        }
     }
 }
 
 #[async_trait::async_trait]
 impl ToolService for ForgeToolService {
+    #[instrument(name = "tool", skip_all, fields(tool = %call.name.as_str(), call_id = ?call.call_id))]
     async fn call(&self, call: ToolCallFull) -> ToolResult {
         let name = call.name.clone();
         let input = call.arguments.clone();
@@ -46,6 +93,12 @@ impl ToolService for ForgeToolService {
         available_tools.sort();
         let output = match self.tools.get(&name) {
             Some(tool) => {
+                if let Err(err) = self.validate(&name, &input) {
+                    let result = ToolResult::from(call).failure(err);
+                    debug!(result = ?result, "Tool call arguments are invalid");
+                    return result;
+                }
+
                 // Wrap tool call with timeout
                 match timeout(TOOL_CALL_TIMEOUT, tool.executable.call(input)).await {
                     Ok(result) => result,
@@ -199,6 +252,35 @@ mod test {
         insta::assert_snapshot!(result);
     }
 
+    #[derive(serde::Deserialize, schemars::JsonSchema)]
+    #[allow(dead_code)]
+    struct ReadInput {
+        path: String,
+        start_line: Option<u64>,
+    }
+
+    #[tokio::test]
+    async fn test_invalid_arguments() {
+        let read_tool = Tool {
+            definition: ToolDefinition {
+                name: ToolName::new("read_tool"),
+                description: "A test tool with required arguments".to_string(),
+                input_schema: schemars::schema_for!(ReadInput),
+                output_schema: None,
+            },
+            executable: Box::new(SuccessTool),
+        };
+        let service = ForgeToolService::from_iter(vec![read_tool]);
+        let call = ToolCallFull {
+            name: ToolName::new("read_tool"),
+            arguments: json!({"start_line": "ten"}),
+            call_id: Some(ToolCallId::new("test")),
+        };
+
+        let result = service.call(call).await;
+        insta::assert_snapshot!(result);
+    }
+
     // Mock tool that simulates a long-running task
     struct SlowTool;
     #[async_trait::async_trait]
@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+/// Relative changes of the mean below which a benchmark is unchanged, even
+/// when the change is significant, as they are within the noise of a machine.
+const NOISE_THRESHOLD: f64 = 0.02;
+
+/// Critical values of the two sided t-test at a 5% significance level, by
+/// degrees of freedom.
+const T_CRITICAL: [(f64, f64); 16] = [
+    (1.0, 12.706),
+    (2.0, 4.303),
+    (3.0, 3.182),
+    (4.0, 2.776),
+    (5.0, 2.571),
+    (6.0, 2.447),
+    (7.0, 2.365),
+    (8.0, 2.306),
+    (9.0, 2.262),
+    (10.0, 2.228),
+    (12.0, 2.179),
+    (15.0, 2.131),
+    (20.0, 2.086),
+    (30.0, 2.042),
+    (60.0, 2.000),
+    (120.0, 1.980),
+];
+
+/// The time per iteration of a benchmark, in nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Estimate {
+    pub mean: f64,
+    pub stddev: f64,
+    pub samples: usize,
+}
+
+impl Estimate {
+    /// The estimate of the times of the samples, None without any.
+    pub fn from_samples(times: &[f64]) -> Option<Self> {
+        if times.is_empty() {
+            return None;
+        }
+        let samples = times.len();
+        let mean = times.iter().sum::<f64>() / samples as f64;
+        let variance = if samples > 1 {
+            times.iter().map(|time| (time - mean).powi(2)).sum::<f64>() / (samples - 1) as f64
+        } else {
+            0.0
+        };
+        Some(Self { mean, stddev: variance.sqrt(), samples })
+    }
+}
+
+/// The results of a run of the benchmarks of a project, saved to compare the
+/// next runs with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    /// When the baseline was saved, eg: `2025-01-31 14:05`
+    pub saved_at: String,
+    pub benchmarks: BTreeMap<String, Estimate>,
+}
+
+impl Baseline {
+    pub fn new(benchmarks: BTreeMap<String, Estimate>) -> Self {
+        let saved_at = chrono::Local::now().format("%Y-%m-%d %H:%M").to_string();
+        Self { saved_at, benchmarks }
+    }
+
+    /// The file of the baseline of the project in the directory of the
+    /// baselines, eg: `<dir>/home-alice-parser/main.json`.
+    pub fn path(dir: &Path, project: &Path, name: &str) -> anyhow::Result<PathBuf> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        anyhow::ensure!(
+            valid,
+            "Invalid baseline name `{name}`, use letters, digits, `-` and `_`"
+        );
+        let project = project
+            .to_string_lossy()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        Ok(dir.join(project).join(format!("{name}.json")))
+    }
+
+    /// The saved baseline, None when there's none.
+    pub async fn load(path: &Path) -> anyhow::Result<Option<Self>> {
+        let content = match tokio::fs::read_to_string(path).await {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("Failed to read the baseline {}", path.display()))
+            }
+        };
+        let baseline = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse the baseline {}", path.display()))?;
+        Ok(Some(baseline))
+    }
+
+    pub async fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, serde_json::to_string_pretty(self)?)
+            .await
+            .with_context(|| format!("Failed to save the baseline {}", path.display()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Regressed,
+    Improved,
+    Unchanged,
+    /// The benchmark isn't in the baseline.
+    New,
+}
+
+/// A benchmark of the run compared with the baseline.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Comparison {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<String>,
+    pub current: String,
+    /// Relative change of the mean, eg: `+12.5%`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change: Option<String>,
+    pub verdict: Verdict,
+}
+
+/// Compares the benchmark with its baseline. The change is significant when
+/// Welch's t-test rejects that the means are equal and it's above the noise
+/// threshold.
+pub fn compare(name: &str, baseline: Option<&Estimate>, current: &Estimate) -> Comparison {
+    let Some(baseline) = baseline else {
+        return Comparison {
+            name: name.to_string(),
+            baseline: None,
+            current: format_time(current.mean),
+            change: None,
+            verdict: Verdict::New,
+        };
+    };
+    let change = (current.mean - baseline.mean) / baseline.mean;
+    let verdict = if change.abs() < NOISE_THRESHOLD || !significant(baseline, current) {
+        Verdict::Unchanged
+    } else if change > 0.0 {
+        Verdict::Regressed
+    } else {
+        Verdict::Improved
+    };
+    Comparison {
+        name: name.to_string(),
+        baseline: Some(format_time(baseline.mean)),
+        current: format_time(current.mean),
+        change: Some(format!("{:+.1}%", change * 100.0)),
+        verdict,
+    }
+}
+
+fn significant(a: &Estimate, b: &Estimate) -> bool {
+    let (va, vb) = (
+        a.stddev.powi(2) / a.samples.max(1) as f64,
+        b.stddev.powi(2) / b.samples.max(1) as f64,
+    );
+    let error = va + vb;
+    if error == 0.0 {
+        return a.mean != b.mean;
+    }
+    let t = (a.mean - b.mean).abs() / error.sqrt();
+    // note: the Welch–Satterthwaite approximation of the degrees of freedom.
+    let df = error.powi(2)
+        / (va.powi(2) / a.samples.saturating_sub(1).max(1) as f64
+            + vb.powi(2) / b.samples.saturating_sub(1).max(1) as f64);
+    let critical = if df > 120.0 {
+        1.960
+    } else {
+        // note: rounding the degrees of freedom down keeps the test
+        // conservative.
+        T_CRITICAL
+            .iter()
+            .rev()
+            .find(|(freedom, _)| *freedom <= df)
+            .map_or(T_CRITICAL[0].1, |(_, critical)| *critical)
+    };
+    t > critical
+}
+
+/// The time in the unit that suits it, eg: `1.25 ms`.
+pub fn format_time(nanos: f64) -> String {
+    let (value, unit) = if nanos < 1e3 {
+        (nanos, "ns")
+    } else if nanos < 1e6 {
+        (nanos / 1e3, "µs")
+    } else if nanos < 1e9 {
+        (nanos / 1e6, "ms")
+    } else {
+        (nanos / 1e9, "s")
+    };
+    format!("{value:.2} {unit}")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn estimate(mean: f64, stddev: f64) -> Estimate {
+        Estimate { mean, stddev, samples: 100 }
+    }
+
+    #[test]
+    fn test_compare() {
+        let baseline = estimate(1_000_000.0, 20_000.0);
+        let actual = [
+            estimate(1_200_000.0, 20_000.0),
+            estimate(800_000.0, 20_000.0),
+            // significant but within the noise
+            estimate(1_010_000.0, 20_000.0),
+            // above the noise but not significant
+            estimate(1_050_000.0, 500_000.0),
+        ]
+        .map(|current| {
+            let comparison = compare("parse", Some(&baseline), &current);
+            (comparison.change.unwrap(), comparison.verdict)
+        });
+        let expected = [
+            ("+20.0%".to_string(), Verdict::Regressed),
+            ("-20.0%".to_string(), Verdict::Improved),
+            ("+1.0%".to_string(), Verdict::Unchanged),
+            ("+5.0%".to_string(), Verdict::Unchanged),
+        ];
+        assert_eq!(actual, expected);
+
+        let actual = compare("parse", None, &estimate(1_500.0, 10.0));
+        let expected = Comparison {
+            name: "parse".to_string(),
+            baseline: None,
+            current: "1.50 µs".to_string(),
+            change: None,
+            verdict: Verdict::New,
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_significant_few_samples() {
+        let a = Estimate { mean: 100.0, stddev: 10.0, samples: 3 };
+        let b = Estimate { mean: 120.0, stddev: 10.0, samples: 3 };
+        assert!(!significant(&a, &b));
+
+        let b = Estimate { mean: 160.0, stddev: 10.0, samples: 3 };
+        assert!(significant(&a, &b));
+    }
+
+    #[test]
+    fn test_path() {
+        let actual = Baseline::path(
+            Path::new("/forge/benchmarks"),
+            Path::new("/home/alice/parser"),
+            "before-cache",
+        )
+        .unwrap();
+        assert_eq!(
+            actual,
+            PathBuf::from("/forge/benchmarks/home-alice-parser/before-cache.json")
+        );
+
+        let actual = Baseline::path(Path::new("/forge"), Path::new("/parser"), "../main");
+        assert!(actual.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_save_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("parser/main.json");
+        assert_eq!(Baseline::load(&path).await.unwrap(), None);
+
+        let baseline = Baseline::new(BTreeMap::from([(
+            "parse".to_string(),
+            estimate(1_000.0, 10.0),
+        )]));
+        baseline.save(&path).await.unwrap();
+        assert_eq!(Baseline::load(&path).await.unwrap(), Some(baseline));
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use super::baseline::Estimate;
+
+/// Name of the report pytest-benchmark writes to the results directory.
+const PYTEST_REPORT: &str = "benchmark.json";
+
+/// Benchmark harnesses the benchmarks of a project are run with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framework {
+    Criterion,
+    PytestBenchmark,
+}
+
+impl Framework {
+    /// Detects the harness from the manifests in the directory of the
+    /// project.
+    pub fn detect(dir: &Path) -> Option<Self> {
+        if dir.join("Cargo.toml").is_file() {
+            return Some(Self::Criterion);
+        }
+        [
+            "pytest.ini",
+            "pyproject.toml",
+            "setup.cfg",
+            "tox.ini",
+            "setup.py",
+        ]
+        .iter()
+        .any(|file| dir.join(file).is_file())
+        .then_some(Self::PytestBenchmark)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Criterion => "criterion",
+            Self::PytestBenchmark => "pytest-benchmark",
+        }
+    }
+
+    /// The program, arguments and variables that run the benchmarks matching
+    /// `filter` if any and write their samples to `out`.
+    pub fn command(
+        &self,
+        filter: Option<&str>,
+        out: &Path,
+    ) -> (&'static str, Vec<String>, Vec<(String, String)>) {
+        match self {
+            Self::Criterion => {
+                let mut args = vec!["bench".to_string()];
+                if let Some(filter) = filter {
+                    args.extend(["--".to_string(), filter.to_string()]);
+                }
+                // note: criterion writes its results to the directory set
+                // rather than the target directory, where they would mix with
+                // the ones of previous runs.
+                let env = vec![("CRITERION_HOME".to_string(), out.display().to_string())];
+                ("cargo", args, env)
+            }
+            Self::PytestBenchmark => {
+                let mut args = ["-m", "pytest", "-q", "--benchmark-only"]
+                    .map(String::from)
+                    .to_vec();
+                args.push(format!(
+                    "--benchmark-json={}",
+                    out.join(PYTEST_REPORT).display()
+                ));
+                if let Some(filter) = filter {
+                    args.extend(["-k".to_string(), filter.to_string()]);
+                }
+                ("python", args, Vec::new())
+            }
+        }
+    }
+
+    /// Reads the time per iteration of each benchmark from the results the
+    /// run wrote to `out`, in nanoseconds.
+    pub fn results(&self, out: &Path) -> anyhow::Result<BTreeMap<String, Estimate>> {
+        match self {
+            Self::Criterion => criterion_results(out),
+            Self::PytestBenchmark => {
+                let report = std::fs::read_to_string(out.join(PYTEST_REPORT))
+                    .context("pytest-benchmark wrote no report")?;
+                parse_pytest(&report)
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CriterionBenchmark {
+    full_id: String,
+}
+
+#[derive(Deserialize)]
+struct CriterionSample {
+    iters: Vec<f64>,
+    times: Vec<f64>,
+}
+
+/// Collects the `new/benchmark.json` and `new/sample.json` files criterion
+/// writes for each benchmark, the times of the samples being in nanoseconds
+/// for their number of iterations.
+fn criterion_results(out: &Path) -> anyhow::Result<BTreeMap<String, Estimate>> {
+    let mut results = BTreeMap::new();
+    let mut dirs = vec![out.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            if path.file_name().is_some_and(|name| name == "new") {
+                let benchmark: CriterionBenchmark = read_json(&path.join("benchmark.json"))?;
+                let sample: CriterionSample = read_json(&path.join("sample.json"))?;
+                let times = sample
+                    .times
+                    .iter()
+                    .zip(&sample.iters)
+                    .filter(|(_, iters)| **iters > 0.0)
+                    .map(|(time, iters)| time / iters)
+                    .collect::<Vec<_>>();
+                if let Some(estimate) = Estimate::from_samples(&times) {
+                    results.insert(benchmark.full_id, estimate);
+                }
+            } else {
+                dirs.push(path);
+            }
+        }
+    }
+    Ok(results)
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+#[derive(Deserialize)]
+struct PytestReport {
+    benchmarks: Vec<PytestBenchmark>,
+}
+
+#[derive(Deserialize)]
+struct PytestBenchmark {
+    fullname: String,
+    stats: PytestStats,
+}
+
+#[derive(Deserialize)]
+struct PytestStats {
+    mean: f64,
+    stddev: f64,
+    rounds: usize,
+}
+
+/// Parses the report of `--benchmark-json`, whose times are in seconds.
+fn parse_pytest(report: &str) -> anyhow::Result<BTreeMap<String, Estimate>> {
+    let report: PytestReport =
+        serde_json::from_str(report).context("Failed to parse the pytest-benchmark report")?;
+    Ok(report
+        .benchmarks
+        .into_iter()
+        .map(|benchmark| {
+            let estimate = Estimate {
+                mean: benchmark.stats.mean * 1e9,
+                stddev: benchmark.stats.stddev * 1e9,
+                samples: benchmark.stats.rounds,
+            };
+            (benchmark.fullname, estimate)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for project in ["rust", "python", "web"] {
+            std::fs::create_dir_all(root.join(project)).unwrap();
+        }
+        std::fs::write(root.join("rust/Cargo.toml"), "[package]\n").unwrap();
+        std::fs::write(root.join("python/pyproject.toml"), "[project]\n").unwrap();
+        std::fs::write(root.join("web/package.json"), "{}").unwrap();
+
+        let actual =
+            ["rust", "python", "web"].map(|project| Framework::detect(&root.join(project)));
+        let expected = [
+            Some(Framework::Criterion),
+            Some(Framework::PytestBenchmark),
+            None,
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_criterion_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let new = dir.path().join("parse/large/new");
+        std::fs::create_dir_all(&new).unwrap();
+        std::fs::write(
+            new.join("benchmark.json"),
+            r#"{"group_id": "parse", "function_id": "large", "full_id": "parse/large"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            new.join("sample.json"),
+            r#"{"sampling_mode": "Linear", "iters": [1.0, 2.0, 4.0], "times": [90.0, 200.0, 440.0]}"#,
+        )
+        .unwrap();
+        // note: the results of the previous run are left out.
+        let base = dir.path().join("parse/large/base");
+        std::fs::create_dir_all(&base).unwrap();
+        std::fs::write(base.join("sample.json"), "{}").unwrap();
+
+        let actual = Framework::Criterion.results(dir.path()).unwrap();
+        let expected = BTreeMap::from([(
+            "parse/large".to_string(),
+            Estimate { mean: 100.0, stddev: 10.0, samples: 3 },
+        )]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_pytest() {
+        let report = r#"{
+            "machine_info": {},
+            "benchmarks": [{
+                "group": null,
+                "name": "test_sort",
+                "fullname": "tests/test_sort.py::test_sort",
+                "stats": {"min": 0.0009, "max": 0.0012, "mean": 0.001, "stddev": 0.00005, "rounds": 250, "median": 0.001}
+            }]
+        }"#;
+
+        let actual = parse_pytest(report)
+            .unwrap()
+            .into_iter()
+            .map(|(name, estimate)| {
+                let (mean, stddev) = (estimate.mean.round(), estimate.stddev.round());
+                (name, mean, stddev, estimate.samples)
+            })
+            .collect::<Vec<_>>();
+        let expected = vec![("tests/test_sort.py::test_sort".to_string(), 1e6, 5e4, 250)];
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,5 @@
+mod baseline;
+mod framework;
+mod run_bench;
+
+pub use run_bench::*;
@@ -0,0 +1,193 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Context;
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tokio::process::Command;
+
+use super::baseline::{compare, format_time, Baseline, Verdict};
+use super::framework::Framework;
+use crate::tools::shell::executor::CommandExecutor;
+
+/// Name of the baseline unless another one is given.
+const DEFAULT_BASELINE: &str = "default";
+
+/// Lines at the end of the output that are returned when the benchmarks
+/// fail, eg: when they don't compile.
+const MAX_OUTPUT_LINES: usize = 60;
+
+/// Default seconds after which the run is killed.
+const DEFAULT_TIMEOUT_SECS: u64 = 1800;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct RunBenchInput {
+    /// The absolute path of the project's root directory (default: the
+    /// current working directory).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Runs only the benchmarks whose name matches the filter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    /// Name of the baseline the results are compared with or saved as, eg:
+    /// `before-cache` (default: `default`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<String>,
+    /// Whether the results replace the baseline rather than being compared
+    /// with it (default: false). The results are saved when the baseline
+    /// doesn't exist yet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub save: Option<bool>,
+    /// Seconds after which the run is killed (default: 1800).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Runs the benchmarks of a project with criterion (Cargo.toml) or
+/// pytest-benchmark (pyproject.toml, setup.py...) and compares them with a
+/// saved baseline, reporting each benchmark as JSON with its time before and
+/// after, the change and whether it regressed, improved or is unchanged by a
+/// t-test. The first run saves the baseline. To prove an optimization, run
+/// it before changing the code, then again after it, and keep the baseline
+/// name the same.
+#[derive(ToolDescription)]
+pub struct RunBench {
+    guard: PathGuard,
+    /// Directory the baselines are saved in
+    baselines: PathBuf,
+}
+
+impl RunBench {
+    pub fn new(guard: PathGuard, baselines: PathBuf) -> Self {
+        Self { guard, baselines }
+    }
+}
+
+impl NamedTool for RunBench {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_test_bench")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for RunBench {
+    type Input = RunBenchInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let dir = match &input.path {
+            Some(path) => self.guard.resolve(path)?,
+            None => self.guard.cwd().to_path_buf(),
+        };
+        let name = input.baseline.as_deref().unwrap_or(DEFAULT_BASELINE);
+        let path = Baseline::path(&self.baselines, &dir, name)?;
+        let framework = Framework::detect(&dir).with_context(|| {
+            format!(
+                "No supported benchmark harness detected in {}, run the benchmarks with the shell tool instead",
+                dir.display()
+            )
+        })?;
+        let out = tempfile::Builder::new()
+            .prefix("forge-bench-")
+            .tempdir()
+            .context("Failed to create the directory of the results")?;
+        let (program, args, env) = framework.command(input.filter.as_deref(), out.path());
+        let command_line = format!("{program} {}", args.join(" "));
+
+        #[cfg(not(test))]
+        {
+            use forge_display::TitleFormat;
+
+            println!("{}", TitleFormat::execute(&command_line).format());
+        }
+
+        let mut command = Command::new(program);
+        command
+            .args(&args)
+            .envs(env)
+            .current_dir(&dir)
+            .kill_on_drop(true);
+        let timeout = input.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS);
+        let output = CommandExecutor::new(command)
+            .timeout(Some(Duration::from_secs(timeout)))
+            .execute()
+            .await
+            .with_context(|| format!("Failed to run {command_line}"))?;
+
+        let results = framework
+            .results(out.path())
+            .inspect_err(|error| tracing::debug!(error = ?error, "Failed to read the results"))
+            .unwrap_or_default();
+        if !output.success || results.is_empty() {
+            let combined = format!("{}\n{}", output.stdout, output.stderr);
+            let lines = combined.trim_end().lines().collect::<Vec<_>>();
+            let tail = &lines[lines.len().saturating_sub(MAX_OUTPUT_LINES)..];
+            let mut report = format!("<command>{command_line}</command>\n");
+            if let Some(timeout) = output.timed_out {
+                report.push_str(&format!(
+                    "The benchmarks timed out after {} seconds and were killed.\n",
+                    timeout.as_secs()
+                ));
+            } else if output.success {
+                report.push_str(&format!(
+                    "No {} results were found, check that the benchmarks use it.\n",
+                    framework.name()
+                ));
+            }
+            report.push_str(&format!("<output>{}</output>", tail.join("\n")));
+            anyhow::bail!(report);
+        }
+
+        let baseline = Baseline::load(&path).await?;
+        let baseline = match baseline {
+            Some(baseline) if !input.save.unwrap_or(false) => baseline,
+            _ => {
+                let baseline = Baseline::new(results);
+                baseline.save(&path).await?;
+                let mut report = format!(
+                    "Saved {} benchmarks as the baseline `{name}`, run them again to compare:",
+                    baseline.benchmarks.len()
+                );
+                for (benchmark, estimate) in &baseline.benchmarks {
+                    report.push_str(&format!(
+                        "\n{}",
+                        serde_json::json!({
+                            "name": benchmark,
+                            "mean": format_time(estimate.mean),
+                            "stddev": format_time(estimate.stddev),
+                            "samples": estimate.samples,
+                        })
+                    ));
+                }
+                return Ok(report);
+            }
+        };
+
+        let comparisons = results
+            .iter()
+            .map(|(benchmark, estimate)| {
+                compare(benchmark, baseline.benchmarks.get(benchmark), estimate)
+            })
+            .collect::<Vec<_>>();
+        let count = |verdict: Verdict| {
+            comparisons
+                .iter()
+                .filter(|comparison| comparison.verdict == verdict)
+                .count()
+        };
+        let mut report = format!(
+            "Compared {} benchmarks with the baseline `{name}` saved {}: {} regressed, {} improved, {} unchanged, {} new",
+            comparisons.len(),
+            baseline.saved_at,
+            count(Verdict::Regressed),
+            count(Verdict::Improved),
+            count(Verdict::Unchanged),
+            count(Verdict::New)
+        );
+        for comparison in &comparisons {
+            report.push_str(&format!("\n{}", serde_json::to_string(comparison)?));
+        }
+        Ok(report)
+    }
+}
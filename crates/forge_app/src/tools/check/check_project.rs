@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tokio::process::Command;
+
+use super::checker::{Checker, Severity};
+use crate::tools::shell::executor::CommandExecutor;
+
+/// Diagnostics that are returned, the count of the rest is reported.
+const MAX_DIAGNOSTICS: usize = 50;
+
+/// Lines at the end of the output that are returned when a checker fails
+/// without any diagnostic, eg: when it isn't installed.
+const MAX_OUTPUT_LINES: usize = 30;
+
+/// Default seconds after which a checker is killed.
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CheckProjectInput {
+    /// The absolute path of the project's root directory (default: the
+    /// current working directory).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Seconds after which each checker is killed (default: 300).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Runs the project's compiler and linters and returns their diagnostics as
+/// JSON objects with the file, line, column, severity and message. The
+/// checkers are detected from the project: cargo check (Cargo.toml), tsc
+/// (tsconfig.json), eslint and ruff (their configs). Use it after editing
+/// files to find the errors the changes introduced, it fails when any error
+/// is reported.
+#[derive(ToolDescription)]
+pub struct CheckProject {
+    guard: PathGuard,
+}
+
+impl CheckProject {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for CheckProject {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_project_check")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for CheckProject {
+    type Input = CheckProjectInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let dir = match &input.path {
+            Some(path) => self.guard.resolve(path)?,
+            None => self.guard.cwd().to_path_buf(),
+        };
+        let checkers = Checker::detect(&dir);
+        if checkers.is_empty() {
+            anyhow::bail!(
+                "No supported compiler or linter detected in {}, run it with the shell tool instead",
+                dir.display()
+            );
+        }
+        let timeout = Duration::from_secs(input.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+
+        let mut diagnostics = Vec::new();
+        let mut failures = Vec::new();
+        for checker in checkers {
+            let (program, args) = checker.command();
+            let command_line = format!("{program} {}", args.join(" "));
+
+            #[cfg(not(test))]
+            {
+                use forge_display::TitleFormat;
+
+                println!("{}", TitleFormat::execute(&command_line).format());
+            }
+
+            let mut command = Command::new(program);
+            command.args(args).current_dir(&dir).kill_on_drop(true);
+            let output = CommandExecutor::new(command)
+                .timeout(Some(timeout))
+                .execute()
+                .await
+                .with_context(|| format!("Failed to run {command_line}"))?;
+
+            let combined = format!("{}\n{}", output.stdout, output.stderr);
+            let found = checker.parse(&combined);
+            if !output.success && found.is_empty() {
+                let lines = combined.trim_end().lines().collect::<Vec<_>>();
+                let tail = &lines[lines.len().saturating_sub(MAX_OUTPUT_LINES)..];
+                failures.push(format!(
+                    "{command_line} failed without diagnostics:\n{}",
+                    tail.join("\n")
+                ));
+            }
+            diagnostics.extend(found);
+        }
+
+        let errors = diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .count();
+        let mut report = if diagnostics.is_empty() {
+            "No diagnostics reported".to_string()
+        } else {
+            format!(
+                "{errors} errors and {} warnings",
+                diagnostics.len() - errors
+            )
+        };
+        for diagnostic in diagnostics.iter().take(MAX_DIAGNOSTICS) {
+            report.push_str(&format!("\n{}", serde_json::to_string(diagnostic)?));
+        }
+        if diagnostics.len() > MAX_DIAGNOSTICS {
+            report.push_str(&format!(
+                "\n... {} more diagnostics",
+                diagnostics.len() - MAX_DIAGNOSTICS
+            ));
+        }
+        for failure in &failures {
+            report.push_str(&format!("\n{failure}"));
+        }
+
+        if errors > 0 || !failures.is_empty() {
+            Err(anyhow::anyhow!(report))
+        } else {
+            Ok(report)
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+/// Compilers and linters the diagnostics of a project are collected with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Checker {
+    Cargo,
+    Tsc,
+    Eslint,
+    Ruff,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem reported by a checker, normalized across all of them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Checker {
+    /// Detects the checkers that apply to the project from the manifests and
+    /// configs in its directory.
+    pub fn detect(dir: &Path) -> Vec<Self> {
+        let read = |file: &str| std::fs::read_to_string(dir.join(file)).unwrap_or_default();
+        let exists = |file: &str| dir.join(file).is_file();
+        let mut checkers = Vec::new();
+        if exists("Cargo.toml") {
+            checkers.push(Self::Cargo);
+        }
+        if exists("tsconfig.json") {
+            checkers.push(Self::Tsc);
+        }
+        let eslint_config = [
+            "eslint.config.js",
+            "eslint.config.mjs",
+            ".eslintrc.js",
+            ".eslintrc.json",
+            ".eslintrc",
+        ];
+        if read("package.json").contains("eslint") || eslint_config.iter().any(|f| exists(f)) {
+            checkers.push(Self::Eslint);
+        }
+        if exists("ruff.toml") || exists(".ruff.toml") || read("pyproject.toml").contains("ruff") {
+            checkers.push(Self::Ruff);
+        }
+        checkers
+    }
+
+    /// The program and arguments that report the diagnostics of the project
+    /// in the format `parse` understands.
+    pub fn command(&self) -> (&'static str, &'static [&'static str]) {
+        match self {
+            Self::Cargo => (
+                "cargo",
+                &["check", "--all-targets", "--message-format=short"],
+            ),
+            Self::Tsc => ("npx", &["tsc", "--noEmit", "--pretty", "false"]),
+            Self::Eslint => ("npx", &["eslint", "--format", "unix", "."]),
+            Self::Ruff => ("ruff", &["check", "--output-format", "concise", "."]),
+        }
+    }
+
+    /// Parses the diagnostics from the combined output of the checker.
+    pub fn parse(&self, output: &str) -> Vec<Diagnostic> {
+        output
+            .lines()
+            .filter_map(|line| match self {
+                Self::Cargo => parse_cargo(line),
+                Self::Tsc => parse_tsc(line),
+                Self::Eslint => parse_eslint(line),
+                Self::Ruff => parse_ruff(line),
+            })
+            .collect()
+    }
+}
+
+/// Splits `<file>:<line>:<column>: <rest>`.
+fn location(line: &str) -> Option<(&str, u32, u32, &str)> {
+    let (head, rest) = line.split_once(": ")?;
+    let mut parts = head.rsplitn(3, ':');
+    let column = parts.next()?.parse().ok()?;
+    let line = parts.next()?.parse().ok()?;
+    let file = parts.next()?;
+    Some((file, line, column, rest))
+}
+
+/// `src/main.rs:2:5: error[E0425]: cannot find value `x` in this scope`
+fn parse_cargo(line: &str) -> Option<Diagnostic> {
+    let (file, line, column, rest) = location(line)?;
+    let (kind, message) = rest.split_once(": ")?;
+    let severity = if kind.starts_with("error") {
+        Severity::Error
+    } else if kind.starts_with("warning") {
+        Severity::Warning
+    } else {
+        return None;
+    };
+    let message = match kind.find('[') {
+        Some(code) => format!("{message} ({})", kind[code..].trim_matches(['[', ']'])),
+        None => message.to_string(),
+    };
+    Some(Diagnostic { file: file.to_string(), line, column, severity, message })
+}
+
+/// `src/a.ts(10,5): error TS2322: Type 'string' is not assignable to type
+/// 'number'.`
+fn parse_tsc(line: &str) -> Option<Diagnostic> {
+    let (head, rest) = line.split_once("): ")?;
+    let (file, position) = head.rsplit_once('(')?;
+    let (line, column) = position.split_once(',')?;
+    let (severity, message) = match rest.strip_prefix("error ") {
+        Some(message) => (Severity::Error, message),
+        None => (Severity::Warning, rest.strip_prefix("warning ")?),
+    };
+    let message = match message.split_once(": ") {
+        Some((code, text)) => format!("{text} ({code})"),
+        None => message.to_string(),
+    };
+    Some(Diagnostic {
+        file: file.to_string(),
+        line: line.parse().ok()?,
+        column: column.parse().ok()?,
+        severity,
+        message,
+    })
+}
+
+/// `/app/src/a.js:10:5: 'x' is defined but never used.
+/// [Error/no-unused-vars]`
+fn parse_eslint(line: &str) -> Option<Diagnostic> {
+    let (file, line, column, rest) = location(line)?;
+    let (message, tag) = rest.rsplit_once(" [")?;
+    let tag = tag.strip_suffix(']')?;
+    let (severity, rule) = tag.split_once('/').unwrap_or((tag, ""));
+    let severity = match severity {
+        "Error" => Severity::Error,
+        _ => Severity::Warning,
+    };
+    let message = if rule.is_empty() {
+        message.to_string()
+    } else {
+        format!("{message} ({rule})")
+    };
+    Some(Diagnostic { file: file.to_string(), line, column, severity, message })
+}
+
+/// `src/a.py:1:8: F401 [*] `os` imported but unused`, only syntax errors keep
+/// the code from running.
+fn parse_ruff(line: &str) -> Option<Diagnostic> {
+    let (file, line, column, rest) = location(line)?;
+    let (code, message) = rest.split_once(' ')?;
+    let message = message.trim_start_matches("[*] ");
+    let severity = if code.starts_with("SyntaxError") || code == "E999" {
+        Severity::Error
+    } else {
+        Severity::Warning
+    };
+    Some(Diagnostic {
+        file: file.to_string(),
+        line,
+        column,
+        severity,
+        message: format!("{message} ({})", code.trim_end_matches(':')),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    fn diagnostic(
+        file: &str,
+        line: u32,
+        column: u32,
+        severity: Severity,
+        message: &str,
+    ) -> Diagnostic {
+        Diagnostic {
+            file: file.to_string(),
+            line,
+            column,
+            severity,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_detect() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("tsconfig.json"), "{}").unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"devDependencies": {"eslint": "^9"}}"#,
+        )
+        .unwrap();
+
+        let actual = Checker::detect(&dir.path());
+        assert_eq!(actual, vec![Checker::Tsc, Checker::Eslint]);
+    }
+
+    #[test]
+    fn test_parse_cargo() {
+        let output = "    Checking app v0.1.0\nsrc/main.rs:2:5: error[E0425]: cannot find value `x` in this scope\nsrc/lib.rs:7:9: warning: unused variable: `y`\nerror: could not compile `app` (bin \"app\") due to 1 previous error\n";
+        let actual = Checker::Cargo.parse(output);
+        let expected = vec![
+            diagnostic(
+                "src/main.rs",
+                2,
+                5,
+                Severity::Error,
+                "cannot find value `x` in this scope (E0425)",
+            ),
+            diagnostic(
+                "src/lib.rs",
+                7,
+                9,
+                Severity::Warning,
+                "unused variable: `y`",
+            ),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_tsc() {
+        let output =
+            "src/a.ts(10,5): error TS2322: Type 'string' is not assignable to type 'number'.\n";
+        let actual = Checker::Tsc.parse(output);
+        let expected = vec![diagnostic(
+            "src/a.ts",
+            10,
+            5,
+            Severity::Error,
+            "Type 'string' is not assignable to type 'number'. (TS2322)",
+        )];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_eslint() {
+        let output = "/app/src/a.js:10:5: 'x' is defined but never used. [Warning/no-unused-vars]\n/app/src/a.js:12:1: Parsing error: Unexpected token [Error]\n\n2 problems\n";
+        let actual = Checker::Eslint.parse(output);
+        let expected = vec![
+            diagnostic(
+                "/app/src/a.js",
+                10,
+                5,
+                Severity::Warning,
+                "'x' is defined but never used. (no-unused-vars)",
+            ),
+            diagnostic(
+                "/app/src/a.js",
+                12,
+                1,
+                Severity::Error,
+                "Parsing error: Unexpected token",
+            ),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_ruff() {
+        let output = "src/a.py:1:8: F401 [*] `os` imported but unused\nsrc/b.py:3:1: SyntaxError: Expected an expression\nFound 2 errors.\n";
+        let actual = Checker::Ruff.parse(output);
+        let expected = vec![
+            diagnostic(
+                "src/a.py",
+                1,
+                8,
+                Severity::Warning,
+                "`os` imported but unused (F401)",
+            ),
+            diagnostic(
+                "src/b.py",
+                3,
+                1,
+                Severity::Error,
+                "Expected an expression (SyntaxError)",
+            ),
+        ];
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,4 @@
+mod check_project;
+mod checker;
+
+pub use check_project::*;
@@ -0,0 +1,71 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, bail, Context};
+use arboard::Clipboard;
+use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ClipboardCopyInput {
+    /// The text to place on the clipboard, exactly as the user should paste
+    /// it.
+    pub text: String,
+}
+
+/// Places text on the user's clipboard, replacing its content. Use it only
+/// when the user asks for something to be copied, eg: a generated snippet or
+/// command, and tell them it's ready to paste.
+#[derive(Clone, Default, ToolDescription)]
+pub struct ClipboardCopy {
+    /// Opened on the first copy and kept open, as on Linux the content is only
+    /// served while the clipboard is open
+    clipboard: Arc<Mutex<Option<Clipboard>>>,
+}
+
+impl NamedTool for ClipboardCopy {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_clipboard_copy")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for ClipboardCopy {
+    type Input = ClipboardCopyInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        if input.text.is_empty() {
+            bail!("Nothing to copy, the text is empty");
+        }
+        let mut guard = self
+            .clipboard
+            .lock()
+            .map_err(|_| anyhow!("The clipboard is unavailable"))?;
+        let clipboard = match guard.take() {
+            Some(clipboard) => clipboard,
+            None => Clipboard::new().context("Failed to open the clipboard")?,
+        };
+        guard
+            .insert(clipboard)
+            .set_text(input.text.as_str())
+            .context("Failed to copy to the clipboard")?;
+        Ok(format!(
+            "Copied {} characters to the clipboard",
+            input.text.chars().count()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_copy_empty_text() {
+        let actual = ClipboardCopy::default()
+            .call(ClipboardCopyInput { text: String::new() })
+            .await;
+        assert!(actual.unwrap_err().to_string().contains("empty"));
+    }
+}
@@ -0,0 +1,234 @@
+use anyhow::Context;
+use forge_display::{Kind, TitleFormat};
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use forge_walker::Walker;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tree_sitter::Query;
+
+use crate::tools::syn;
+
+/// Maximum number of matches returned by a single query.
+const MAX_MATCHES: usize = 200;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CodeQueryInput {
+    /// The file or directory to search (absolute path required). Directories
+    /// are searched recursively.
+    pub path: String,
+    /// Extension of the files to query, which selects the grammar (eg: rs, py,
+    /// ts, tsx, go, java).
+    pub language: String,
+    /// The tree-sitter query, eg: `(call_expression function: (identifier)
+    /// @f (#eq? @f "foo")) @call`.
+    pub query: String,
+    /// Capture to report for each match. Defaults to the outermost capture.
+    pub capture: Option<String>,
+}
+
+/// Searches source code structurally using tree-sitter query patterns instead
+/// of regular expressions, eg: every call to a function, every struct
+/// implementing a trait or every class with a given decorator. Predicates such
+/// as #eq? and #match? are supported. Returns file:line:column and the first
+/// line of every matched node.
+#[derive(ToolDescription)]
+pub struct CodeQuery {
+    guard: PathGuard,
+}
+
+impl CodeQuery {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for CodeQuery {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_code_query")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for CodeQuery {
+    type Input = CodeQueryInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let path = &self.guard.resolve(&input.path)?;
+
+        let extension = input.language.trim_start_matches('.').to_lowercase();
+        let language = syn::extension(&extension)
+            .with_context(|| format!("Unsupported language '{}'", input.language))?;
+        let query = Query::new(&language, &input.query)
+            .with_context(|| format!("Invalid tree-sitter query: {}", input.query))?;
+
+        let files = if path.is_dir() {
+            Walker::max_all()
+                .cwd(path.to_path_buf())
+                .get()
+                .await
+                .with_context(|| format!("Failed to walk directory '{}'", path.display()))?
+                .into_iter()
+                .filter(|file| !file.is_dir())
+                .map(|file| path.join(file.path))
+                .collect()
+        } else if path.exists() {
+            vec![path.to_path_buf()]
+        } else {
+            anyhow::bail!("Path '{}' does not exist", input.path);
+        };
+
+        let mut matches = vec![];
+        for file in files {
+            let matches_extension = file
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case(&extension));
+            if !matches_extension {
+                continue;
+            }
+
+            // Skip binary or unreadable files
+            let Ok(content) = tokio::fs::read_to_string(&file).await else {
+                continue;
+            };
+
+            for found in syn::query(&language, &query, &content, input.capture.as_deref())? {
+                matches.push(format!(
+                    "{}:{}:{}: {}",
+                    file.display(),
+                    found.line,
+                    found.column,
+                    found.snippet
+                ));
+            }
+        }
+
+        println!(
+            "{}",
+            TitleFormat {
+                kind: Kind::Execute,
+                title: format!("query {} files", extension),
+                sub_title: Some(input.path.clone()),
+                error: None,
+            }
+            .format()
+        );
+
+        if matches.is_empty() {
+            return Ok("No matches found".to_string());
+        }
+
+        let total = matches.len();
+        matches.truncate(MAX_MATCHES);
+        if total > MAX_MATCHES {
+            matches.push(format!(
+                "... {} more matches omitted, narrow down the query or the path",
+                total - MAX_MATCHES
+            ));
+        }
+
+        Ok(matches.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+    use tokio::fs;
+
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    #[tokio::test]
+    async fn test_code_query_across_files() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("a.rs"),
+            "struct A;\nimpl Display for A {}\n",
+        )
+        .await
+        .unwrap();
+        fs::create_dir(temp_dir.path().join("nested"))
+            .await
+            .unwrap();
+        fs::write(
+            temp_dir.path().join("nested/b.rs"),
+            "struct B;\nimpl Debug for B {}\nimpl Display for B {}\n",
+        )
+        .await
+        .unwrap();
+        fs::write(temp_dir.path().join("c.py"), "class Display: pass\n")
+            .await
+            .unwrap();
+
+        let actual = CodeQuery::new(TempDir::guard())
+            .call(CodeQueryInput {
+                path: temp_dir.path().to_string_lossy().to_string(),
+                language: "rs".to_string(),
+                query: r#"(impl_item trait: (type_identifier) @trait (#eq? @trait "Display") type: (type_identifier) @type)"#.to_string(),
+                capture: Some("type".to_string()),
+            })
+            .await
+            .unwrap();
+
+        let mut actual = actual.lines().collect::<Vec<_>>();
+        actual.sort();
+        let expected = vec![
+            format!("{}:2:18: A", temp_dir.path().join("a.rs").display()),
+            format!("{}:3:18: B", temp_dir.path().join("nested/b.rs").display()),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_code_query_no_matches() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("a.rs");
+        fs::write(&file, "fn main() {}\n").await.unwrap();
+
+        let actual = CodeQuery::new(TempDir::guard())
+            .call(CodeQueryInput {
+                path: file.to_string_lossy().to_string(),
+                language: "rs".to_string(),
+                query: "(struct_item) @struct".to_string(),
+                capture: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(actual, "No matches found");
+    }
+
+    #[tokio::test]
+    async fn test_code_query_invalid_query() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let actual = CodeQuery::new(TempDir::guard())
+            .call(CodeQueryInput {
+                path: temp_dir.path().to_string_lossy().to_string(),
+                language: "rs".to_string(),
+                query: "(not_a_node) @x".to_string(),
+                capture: None,
+            })
+            .await;
+
+        assert!(actual.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_code_query_unsupported_language() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let actual = CodeQuery::new(TempDir::guard())
+            .call(CodeQueryInput {
+                path: temp_dir.path().to_string_lossy().to_string(),
+                language: "cobol".to_string(),
+                query: "(x) @x".to_string(),
+                capture: None,
+            })
+            .await;
+
+        assert!(actual.is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Context;
+use forge_domain::{CommandTool, ExecutableTool, Tool, ToolDefinition, ToolName};
+use serde_json::{json, Value};
+use tokio::process::Command;
+
+use crate::tools::shell::executor::{CommandExecutor, Output};
+
+/// Bytes at the end of each stream that are returned.
+const MAX_OUTPUT_BYTES: usize = 20_000;
+
+/// A tool of the config that runs a command, eg: `tool_command_deploy`.
+struct ExternalCommand {
+    name: String,
+    tool: CommandTool,
+    cwd: PathBuf,
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for ExternalCommand {
+    type Input = Value;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        #[cfg(not(test))]
+        {
+            use forge_display::TitleFormat;
+
+            println!("{}", TitleFormat::execute(&self.tool.cmd).format());
+        }
+
+        // note: the command is run by the shell of the platform rather than
+        // the one of the agents, which may be restricted.
+        let mut command = if cfg!(target_os = "windows") {
+            let mut command = Command::new("cmd");
+            command.args(["/C", &self.tool.cmd]);
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.args(["-c", &self.tool.cmd]);
+            command
+        };
+        command
+            .current_dir(&self.cwd)
+            .envs(self.tool.env.iter().flatten())
+            .envs(arguments(&input))
+            .kill_on_drop(true);
+        let timeout = self.tool.timeout.unwrap_or(CommandTool::DEFAULT_TIMEOUT);
+        let output = CommandExecutor::new(command)
+            .timeout(Some(Duration::from_secs(timeout)))
+            .execute()
+            .await
+            .with_context(|| format!("Failed to run the command of {}", self.name))?;
+
+        let report = report(&output);
+        if output.success {
+            Ok(report)
+        } else {
+            Err(anyhow::anyhow!(report))
+        }
+    }
+}
+
+/// The variables the arguments are passed in.
+fn arguments(input: &Value) -> Vec<(String, String)> {
+    let mut variables = vec![("FORGE_TOOL_ARGS".to_string(), input.to_string())];
+    for (name, value) in input.as_object().into_iter().flatten() {
+        let value = match value {
+            Value::String(value) => value.clone(),
+            Value::Null => continue,
+            value => value.to_string(),
+        };
+        let name = name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_uppercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        variables.push((format!("FORGE_ARG_{name}"), value));
+    }
+    variables
+}
+
+fn report(output: &Output) -> String {
+    let mut report = Vec::new();
+    for (tag, stream) in [("stdout", &output.stdout), ("stderr", &output.stderr)] {
+        if !stream.trim().is_empty() {
+            report.push(format!("<{tag}>{}</{tag}>", tail(stream)));
+        }
+    }
+    if let Some(timeout) = output.timed_out {
+        report.push(format!(
+            "The command timed out after {} seconds and was killed.",
+            timeout.as_secs()
+        ));
+    }
+    if report.is_empty() {
+        return if output.success {
+            "The command succeeded with no output.".to_string()
+        } else {
+            "The command failed with no output.".to_string()
+        };
+    }
+    report.join("\n")
+}
+
+/// The end of the stream, where the outcome of a command usually is.
+fn tail(stream: &str) -> &str {
+    let mut start = stream.len().saturating_sub(MAX_OUTPUT_BYTES);
+    while !stream.is_char_boundary(start) {
+        start += 1;
+    }
+    &stream[start..]
+}
+
+/// The tools of the commands of the config, eg: `tool_command_deploy` for
+/// `[tools.deploy]`.
+pub fn command_tools<'a>(
+    tools: impl IntoIterator<Item = (&'a String, &'a CommandTool)>,
+    cwd: PathBuf,
+) -> Vec<Tool> {
+    tools
+        .into_iter()
+        .filter_map(|(name, tool)| {
+            let schema = tool
+                .schema
+                .clone()
+                .unwrap_or_else(|| json!({"type": "object", "properties": {}}));
+            let input_schema = match serde_json::from_value(schema) {
+                Ok(schema) => schema,
+                Err(error) => {
+                    tracing::error!(tool = %name, error = %error, "Invalid schema of the command tool");
+                    return None;
+                }
+            };
+            let definition = ToolDefinition {
+                name: ToolName::new(format!("tool_command_{name}")),
+                description: tool
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| format!("Runs `{}` in the working directory.", tool.cmd)),
+                input_schema,
+                output_schema: None,
+            };
+            let executable = ExternalCommand {
+                name: definition.name.as_str().to_string(),
+                tool: tool.clone(),
+                cwd: cwd.clone(),
+            };
+            Some(Tool { executable: Box::new(executable), definition })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn tool(cmd: &str) -> Tool {
+        let tool = CommandTool::default()
+            .cmd(cmd.to_string())
+            .env(BTreeMap::from([(
+                "REGION".to_string(),
+                "eu-west-1".to_string(),
+            )]))
+            .timeout(1);
+        let tools = BTreeMap::from([("deploy".to_string(), tool)]);
+        command_tools(&tools, std::env::temp_dir()).remove(0)
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn test_command_tool() {
+        let tool = tool("echo \"$FORGE_ARG_TARGET in $REGION\"; echo \"$FORGE_TOOL_ARGS\" >&2");
+        assert_eq!(tool.definition.name.as_str(), "tool_command_deploy");
+
+        let actual = tool
+            .executable
+            .call(json!({"target": "staging"}))
+            .await
+            .unwrap();
+        let expected =
+            "<stdout>staging in eu-west-1\n</stdout>\n<stderr>{\"target\":\"staging\"}\n</stderr>";
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn test_command_tool_failure() {
+        let actual = tool("exit 3").executable.call(json!({})).await.unwrap_err();
+        assert_eq!(actual.to_string(), "The command failed with no output.");
+
+        let actual = tool("sleep 5")
+            .executable
+            .call(json!({}))
+            .await
+            .unwrap_err();
+        assert!(actual.to_string().contains("timed out after 1 seconds"));
+    }
+}
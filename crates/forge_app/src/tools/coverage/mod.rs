@@ -0,0 +1,4 @@
+mod runner;
+mod test_coverage;
+
+pub use test_coverage::*;
@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the LCOV report every runner writes to the report directory.
+pub const REPORT_FILE: &str = "lcov.info";
+
+/// Coverage tools the tests of a project are measured with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Runner {
+    /// cargo-llvm-cov
+    LlvmCov,
+    /// coverage.py running pytest
+    CoveragePy,
+    /// jest with its istanbul reporter
+    Jest,
+    /// nyc, the istanbul command line
+    Nyc,
+}
+
+impl Runner {
+    /// Detects the coverage tool from the manifests in the directory of the
+    /// project.
+    pub fn detect(dir: &Path) -> Option<Self> {
+        if dir.join("Cargo.toml").is_file() {
+            return Some(Self::LlvmCov);
+        }
+        if let Ok(manifest) = std::fs::read_to_string(dir.join("package.json")) {
+            if manifest.contains("\"nyc\"") {
+                return Some(Self::Nyc);
+            }
+            if manifest.contains("jest") {
+                return Some(Self::Jest);
+            }
+        }
+        [
+            "pytest.ini",
+            "pyproject.toml",
+            "setup.cfg",
+            "tox.ini",
+            "setup.py",
+            ".coveragerc",
+        ]
+        .iter()
+        .any(|file| dir.join(file).is_file())
+        .then_some(Self::CoveragePy)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::LlvmCov => "cargo-llvm-cov",
+            Self::CoveragePy => "coverage.py",
+            Self::Jest => "jest",
+            Self::Nyc => "nyc",
+        }
+    }
+
+    /// The programs and arguments that run the tests and write their
+    /// coverage to [`REPORT_FILE`] in `out`, in order. The report is written
+    /// even when some tests fail.
+    pub fn commands(&self, out: &Path) -> Vec<(&'static str, Vec<String>)> {
+        let report = out.join(REPORT_FILE).display().to_string();
+        let out = out.display().to_string();
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        match self {
+            Self::LlvmCov => {
+                let mut llvm_cov = args(&["llvm-cov", "--lcov", "--ignore-run-fail"]);
+                llvm_cov.extend(["--output-path".to_string(), report]);
+                vec![("cargo", llvm_cov)]
+            }
+            Self::CoveragePy => {
+                let mut lcov = args(&["-m", "coverage", "lcov", "-o"]);
+                lcov.push(report);
+                vec![
+                    (
+                        "python",
+                        args(&["-m", "coverage", "run", "-m", "pytest", "-q"]),
+                    ),
+                    ("python", lcov),
+                ]
+            }
+            Self::Jest => {
+                let mut jest = args(&["jest", "--ci", "--coverage", "--coverageReporters=lcov"]);
+                jest.push(format!("--coverageDirectory={out}"));
+                vec![("npx", jest)]
+            }
+            Self::Nyc => {
+                let mut nyc = args(&["nyc", "--reporter=lcovonly"]);
+                nyc.push(format!("--report-dir={out}"));
+                nyc.extend(args(&["npm", "test"]));
+                vec![("npx", nyc)]
+            }
+        }
+    }
+}
+
+/// The hits of the lines of a source file, by their number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCoverage {
+    pub path: PathBuf,
+    pub lines: BTreeMap<usize, u64>,
+}
+
+/// Parses the `SF:` (source file) and `DA:` (line hits) records of an LCOV
+/// report, which every coverage tool can write. Relative paths are joined to
+/// `dir`.
+pub fn parse_lcov(report: &str, dir: &Path) -> Vec<FileCoverage> {
+    let mut files = Vec::new();
+    let mut current: Option<FileCoverage> = None;
+    for line in report.lines().map(str::trim) {
+        if let Some(path) = line.strip_prefix("SF:") {
+            files.extend(current.take());
+            current = Some(FileCoverage { path: dir.join(path), lines: BTreeMap::new() });
+        } else if let Some(hits) = line.strip_prefix("DA:") {
+            // note: the hits may be followed by a checksum of the line.
+            let mut fields = hits.split(',');
+            let (Some(Ok(line)), Some(Ok(count))) = (
+                fields.next().map(str::parse::<usize>),
+                fields.next().map(str::parse::<u64>),
+            ) else {
+                continue;
+            };
+            if let Some(file) = current.as_mut() {
+                *file.lines.entry(line).or_default() += count;
+            }
+        } else if line == "end_of_record" {
+            files.extend(current.take());
+        }
+    }
+    files.extend(current);
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for project in ["rust", "jest", "nyc", "python", "docs"] {
+            std::fs::create_dir_all(root.join(project)).unwrap();
+        }
+        std::fs::write(root.join("rust/Cargo.toml"), "[package]\n").unwrap();
+        std::fs::write(
+            root.join("jest/package.json"),
+            r#"{"scripts": {"test": "jest"}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("nyc/package.json"),
+            r#"{"scripts": {"test": "mocha"}, "devDependencies": {"nyc": "17"}}"#,
+        )
+        .unwrap();
+        std::fs::write(root.join("python/pyproject.toml"), "[project]\n").unwrap();
+
+        let actual = ["rust", "jest", "nyc", "python", "docs"]
+            .map(|project| Runner::detect(&root.join(project)));
+        let expected = [
+            Some(Runner::LlvmCov),
+            Some(Runner::Jest),
+            Some(Runner::Nyc),
+            Some(Runner::CoveragePy),
+            None,
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_lcov() {
+        let report = "\
+TN:
+SF:src/lib.rs
+FN:1,add
+FNDA:3,add
+DA:1,3
+DA:2,3
+DA:5,0,qDPWxHcSPtFz
+LF:3
+LH:2
+end_of_record
+SF:/project/src/main.rs
+DA:1,0
+DA:1,2
+end_of_record
+";
+
+        let actual = parse_lcov(report, Path::new("/project"));
+        let expected = vec![
+            FileCoverage {
+                path: PathBuf::from("/project/src/lib.rs"),
+                lines: BTreeMap::from([(1, 3), (2, 3), (5, 0)]),
+            },
+            FileCoverage {
+                path: PathBuf::from("/project/src/main.rs"),
+                lines: BTreeMap::from([(1, 2)]),
+            },
+        ];
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Context;
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use super::runner::{parse_lcov, FileCoverage, Runner, REPORT_FILE};
+use crate::tools::shell::executor::CommandExecutor;
+use crate::tools::{definitions, Definition};
+
+/// Functions that are returned, the count of the rest is reported.
+const DEFAULT_MAX_RESULTS: usize = 20;
+
+/// Lines at the end of the output that are returned when no report was
+/// written, eg: when the coverage tool isn't installed.
+const MAX_OUTPUT_LINES: usize = 60;
+
+/// Default seconds after which the run is killed.
+const DEFAULT_TIMEOUT_SECS: u64 = 900;
+
+/// Kinds of the definitions uncovered lines are attributed to.
+const FUNCTION_KINDS: [&str; 3] = ["function", "method", "constructor"];
+
+#[derive(Deserialize, JsonSchema)]
+pub struct TestCoverageInput {
+    /// The absolute path of the project's root directory (default: the
+    /// current working directory).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Maximum functions to return (default: 20).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_results: Option<usize>,
+    /// Seconds after which the run is killed (default: 900).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// A function with lines the tests don't run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UncoveredFunction {
+    pub file: String,
+    pub function: String,
+    /// Line where the function starts
+    pub line: usize,
+    pub covered_lines: usize,
+    pub total_lines: usize,
+    /// Ranges of the lines that aren't run, eg: `12-18, 25`
+    pub uncovered: String,
+}
+
+impl UncoveredFunction {
+    fn uncovered_lines(&self) -> usize {
+        self.total_lines - self.covered_lines
+    }
+}
+
+/// Runs the tests of a project with coverage and lists the functions with the
+/// most lines the tests don't run, as JSON objects with their file, line,
+/// covered and total lines and the ranges of the uncovered ones, after the
+/// line coverage of the project. The coverage tool is detected from the
+/// project's manifest: cargo-llvm-cov (Cargo.toml), nyc or jest
+/// (package.json) or coverage.py with pytest (pyproject.toml, setup.py...).
+/// Use it before writing tests to target the code that isn't tested rather
+/// than guessing.
+#[derive(ToolDescription)]
+pub struct TestCoverage {
+    guard: PathGuard,
+}
+
+impl TestCoverage {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for TestCoverage {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_test_coverage")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for TestCoverage {
+    type Input = TestCoverageInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let dir = match &input.path {
+            Some(path) => self.guard.resolve(path)?,
+            None => self.guard.cwd().to_path_buf(),
+        };
+        let runner = Runner::detect(&dir).with_context(|| {
+            format!(
+                "No supported coverage tool detected in {}, measure the coverage with the shell tool instead",
+                dir.display()
+            )
+        })?;
+        let out = tempfile::Builder::new()
+            .prefix("forge-coverage-")
+            .tempdir()
+            .context("Failed to create the directory of the report")?;
+        let timeout = Duration::from_secs(input.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+
+        let mut outputs = Vec::new();
+        for (program, args) in runner.commands(out.path()) {
+            let command_line = format!("{program} {}", args.join(" "));
+
+            #[cfg(not(test))]
+            {
+                use forge_display::TitleFormat;
+
+                println!("{}", TitleFormat::execute(&command_line).format());
+            }
+
+            let mut command = Command::new(program);
+            command.args(&args).current_dir(&dir).kill_on_drop(true);
+            let output = CommandExecutor::new(command)
+                .timeout(Some(timeout))
+                .execute()
+                .await
+                .with_context(|| format!("Failed to run {command_line}"))?;
+            // note: the tests failing doesn't stop the report from being
+            // written, the run timing out does.
+            let timed_out = output.timed_out.is_some();
+            outputs.push(format!("{}\n{}", output.stdout, output.stderr));
+            if timed_out {
+                anyhow::bail!(
+                    "{command_line} timed out after {} seconds and was killed",
+                    timeout.as_secs()
+                );
+            }
+        }
+
+        let Ok(report) = tokio::fs::read_to_string(out.path().join(REPORT_FILE)).await else {
+            let combined = outputs.join("\n");
+            let lines = combined.trim_end().lines().collect::<Vec<_>>();
+            let tail = &lines[lines.len().saturating_sub(MAX_OUTPUT_LINES)..];
+            anyhow::bail!(
+                "{} wrote no coverage report, check that it's installed. The last lines of its output are:\n<output>{}</output>",
+                runner.name(),
+                tail.join("\n")
+            );
+        };
+
+        let files = parse_lcov(&report, &dir);
+        let max_results = input.max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+        let mut functions = Vec::new();
+        for file in &files {
+            let Ok(content) = tokio::fs::read_to_string(&file.path).await else {
+                continue;
+            };
+            let definitions = definitions(&file.path, &content).unwrap_or_default();
+            let name = file.path.strip_prefix(&dir).unwrap_or(&file.path);
+            functions.extend(uncovered_functions(file, &definitions, name));
+        }
+        functions.sort_by(|a, b| {
+            b.uncovered_lines()
+                .cmp(&a.uncovered_lines())
+                .then_with(|| a.file.cmp(&b.file))
+                .then_with(|| a.line.cmp(&b.line))
+        });
+
+        let total = files.iter().map(|file| file.lines.len()).sum::<usize>();
+        let covered = files
+            .iter()
+            .flat_map(|file| file.lines.values())
+            .filter(|hits| **hits > 0)
+            .count();
+        let mut result = format!(
+            "Line coverage: {:.1}% ({covered} of {total} lines of {} files), measured with {}",
+            percent(covered, total),
+            files.len(),
+            runner.name()
+        );
+        if functions.is_empty() {
+            result.push_str("\nNo function has uncovered lines");
+            return Ok(result);
+        }
+        result.push_str(&format!(
+            "\n{} functions have uncovered lines, by uncovered lines:",
+            functions.len()
+        ));
+        for function in functions.iter().take(max_results) {
+            result.push_str(&format!("\n{}", serde_json::to_string(function)?));
+        }
+        if functions.len() > max_results {
+            result.push_str(&format!(
+                "\n... {} more functions",
+                functions.len() - max_results
+            ));
+        }
+        Ok(result)
+    }
+}
+
+fn percent(covered: usize, total: usize) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        covered as f64 * 100.0 / total as f64
+    }
+}
+
+/// The functions of the file with uncovered lines. A line is attributed to the
+/// innermost function it's in, the lines outside of any function are left
+/// out.
+fn uncovered_functions(
+    file: &FileCoverage,
+    definitions: &[Definition],
+    name: &Path,
+) -> Vec<UncoveredFunction> {
+    let functions = definitions
+        .iter()
+        .filter(|definition| FUNCTION_KINDS.contains(&definition.kind.as_str()))
+        .collect::<Vec<_>>();
+    let mut lines = vec![Vec::new(); functions.len()];
+    for (line, hits) in &file.lines {
+        let innermost = functions
+            .iter()
+            .enumerate()
+            .filter(|(_, function)| (function.line..=function.end_line).contains(line))
+            .max_by_key(|(_, function)| function.line)
+            .map(|(index, _)| index);
+        if let Some(index) = innermost {
+            lines[index].push((*line, *hits > 0));
+        }
+    }
+
+    functions
+        .into_iter()
+        .zip(lines)
+        .filter(|(_, lines)| lines.iter().any(|(_, covered)| !covered))
+        .map(|(function, lines)| UncoveredFunction {
+            file: name.display().to_string(),
+            function: function.name.clone(),
+            line: function.line,
+            covered_lines: lines.iter().filter(|(_, covered)| *covered).count(),
+            total_lines: lines.len(),
+            uncovered: ranges(&lines, &file.lines),
+        })
+        .collect()
+}
+
+/// The ranges of the uncovered lines of a function, eg: `12-18, 25`. Two
+/// uncovered lines are in the same range unless a line of code of the file is
+/// between them, so that the blank lines and comments don't split a range.
+fn ranges(lines: &[(usize, bool)], file: &BTreeMap<usize, u64>) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (line, covered) in lines {
+        if *covered {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some((_, end)) if file.range(*end + 1..*line).next().is_none() => *end = *line,
+            _ => ranges.push((*line, *line)),
+        }
+    }
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}-{end}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_uncovered_functions() {
+        let content = r#"fn parse(input: &str) -> Vec<u32> {
+    let mut numbers = Vec::new();
+    for part in input.split(',') {
+        if part.is_empty() {
+            continue;
+        }
+
+        numbers.push(part.parse().unwrap());
+    }
+    numbers
+}
+
+fn main() {
+    fn usage() {
+        println!("usage: sum <numbers>");
+    }
+    usage();
+}
+
+fn add(a: u32, b: u32) -> u32 {
+    a + b
+}
+"#;
+        let path = PathBuf::from("/project/src/main.rs");
+        let definitions = definitions(&path, content).unwrap();
+        let file = FileCoverage {
+            path,
+            lines: BTreeMap::from([
+                (1, 2),
+                (2, 2),
+                (3, 5),
+                (4, 3),
+                (5, 0),
+                (8, 0),
+                (10, 2),
+                (13, 0),
+                (14, 0),
+                (15, 0),
+                (17, 0),
+                (20, 4),
+                (21, 4),
+            ]),
+        };
+
+        let actual = uncovered_functions(&file, &definitions, Path::new("src/main.rs"));
+        let expected = vec![
+            UncoveredFunction {
+                file: "src/main.rs".to_string(),
+                function: "parse".to_string(),
+                line: 1,
+                covered_lines: 5,
+                total_lines: 7,
+                uncovered: "5-8".to_string(),
+            },
+            UncoveredFunction {
+                file: "src/main.rs".to_string(),
+                function: "main".to_string(),
+                line: 13,
+                covered_lines: 0,
+                total_lines: 2,
+                uncovered: "13, 17".to_string(),
+            },
+            UncoveredFunction {
+                file: "src/main.rs".to_string(),
+                function: "usage".to_string(),
+                line: 14,
+                covered_lines: 0,
+                total_lines: 2,
+                uncovered: "14-15".to_string(),
+            },
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_ranges() {
+        let file = BTreeMap::from([(3, 1), (4, 0), (5, 0), (7, 0), (9, 0), (10, 1), (12, 0)]);
+        let lines = [
+            (3, true),
+            (4, false),
+            (5, false),
+            (9, false),
+            (10, true),
+            (12, false),
+        ];
+        assert_eq!(ranges(&lines, &file), "4-5, 9, 12");
+    }
+}
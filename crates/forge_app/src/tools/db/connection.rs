@@ -0,0 +1,344 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::sync::Once;
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use forge_domain::Database;
+use futures::TryStreamExt;
+use sqlx::any::{AnyConnection, AnyRow};
+use sqlx::{Column, Connection as _, Row};
+
+/// Seconds after which connecting or running a query fails.
+const CONNECT_TIMEOUT_SECS: u64 = 10;
+const QUERY_TIMEOUT_SECS: u64 = 60;
+
+/// Characters of a value that are returned, the rest is cut.
+const MAX_VALUE_CHARS: usize = 200;
+
+/// Characters of the rows that are returned, the rows after them are left
+/// out.
+const MAX_OUTPUT_CHARS: usize = 20_000;
+
+/// The database systems the tools connect to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Sqlite,
+    Postgres,
+    MySql,
+}
+
+impl Backend {
+    /// Detects the system from the scheme of the connection string.
+    fn from_url(url: &str) -> anyhow::Result<Self> {
+        let scheme = url.split(':').next().unwrap_or_default();
+        match scheme {
+            "sqlite" => Ok(Self::Sqlite),
+            "postgres" | "postgresql" => Ok(Self::Postgres),
+            "mysql" | "mariadb" => Ok(Self::MySql),
+            _ => bail!("Unsupported database `{scheme}`, expected sqlite, postgres or mysql"),
+        }
+    }
+
+    /// The statement that makes the session read-only, so that writes are
+    /// refused by the database too.
+    fn read_only(&self) -> &'static str {
+        match self {
+            Self::Sqlite => "PRAGMA query_only = ON",
+            Self::Postgres => "SET SESSION CHARACTERISTICS AS TRANSACTION READ ONLY",
+            Self::MySql => "SET SESSION TRANSACTION READ ONLY",
+        }
+    }
+
+    /// The placeholder of the first bound parameter.
+    pub fn placeholder(&self) -> &'static str {
+        match self {
+            Self::Postgres => "$1",
+            Self::Sqlite | Self::MySql => "?",
+        }
+    }
+}
+
+/// A connection to one of the databases of the config.
+pub struct Connection {
+    pub name: String,
+    pub backend: Backend,
+    pub allow_writes: bool,
+    connection: AnyConnection,
+}
+
+impl Connection {
+    /// Connects to the database named `name`, or to the only one configured,
+    /// in a read-only session unless it allows writes.
+    pub async fn open(
+        databases: &BTreeMap<String, Database>,
+        name: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let (name, database) = match name {
+            Some(name) => databases.get_key_value(name).with_context(|| {
+                format!(
+                    "Unknown database `{name}`, the configured ones are: {}",
+                    databases.keys().cloned().collect::<Vec<_>>().join(", ")
+                )
+            })?,
+            None if databases.len() == 1 => databases.iter().next().unwrap(),
+            None if databases.is_empty() => bail!(
+                "No database is configured, add one to the config, eg: `[databases.app]` with \
+                 `url = \"postgres://localhost/app\"`"
+            ),
+            None => bail!(
+                "Several databases are configured, pick one of: {}",
+                databases.keys().cloned().collect::<Vec<_>>().join(", ")
+            ),
+        };
+
+        static DRIVERS: Once = Once::new();
+        DRIVERS.call_once(sqlx::any::install_default_drivers);
+
+        let url = database.connection_string()?;
+        let backend = Backend::from_url(&url)?;
+        let mut connection = tokio::time::timeout(
+            Duration::from_secs(CONNECT_TIMEOUT_SECS),
+            AnyConnection::connect(&url),
+        )
+        .await
+        .with_context(|| format!("Timed out connecting to the database `{name}`"))?
+        .with_context(|| format!("Failed to connect to the database `{name}`"))?;
+        if !database.allow_writes {
+            sqlx::raw_sql(backend.read_only())
+                .execute(&mut connection)
+                .await
+                .context("Failed to make the session read-only")?;
+        }
+
+        Ok(Self {
+            name: name.clone(),
+            backend,
+            allow_writes: database.allow_writes,
+            connection,
+        })
+    }
+
+    /// Runs the query and returns up to `max_rows` of its rows, with the
+    /// parameters bound in order.
+    pub async fn rows(
+        &mut self,
+        query: &str,
+        parameters: &[&str],
+        max_rows: usize,
+    ) -> anyhow::Result<Rows> {
+        let run = async {
+            let mut query = sqlx::query(query);
+            for parameter in parameters {
+                query = query.bind(parameter.to_string());
+            }
+            let mut stream = query.fetch(&mut self.connection);
+            let mut rows = Rows::default();
+            while let Some(row) = stream.try_next().await.map_err(hint)? {
+                if rows.values.len() == max_rows {
+                    rows.truncated = true;
+                    break;
+                }
+                if rows.columns.is_empty() {
+                    rows.columns = row
+                        .columns()
+                        .iter()
+                        .map(|column| column.name().to_string())
+                        .collect();
+                }
+                rows.values.push(values(&row));
+            }
+            anyhow::Ok(rows)
+        };
+        tokio::time::timeout(Duration::from_secs(QUERY_TIMEOUT_SECS), run)
+            .await
+            .with_context(|| format!("The query timed out after {QUERY_TIMEOUT_SECS} seconds"))?
+    }
+
+    /// Runs a statement that doesn't return rows and returns the number of
+    /// rows it affected.
+    pub async fn execute(&mut self, query: &str) -> anyhow::Result<u64> {
+        let run = sqlx::query(query).execute(&mut self.connection);
+        let result = tokio::time::timeout(Duration::from_secs(QUERY_TIMEOUT_SECS), run)
+            .await
+            .with_context(|| format!("The query timed out after {QUERY_TIMEOUT_SECS} seconds"))??;
+        Ok(result.rows_affected())
+    }
+}
+
+/// Explains how to get around the types the connection can't decode, eg: the
+/// dates or UUIDs of Postgres.
+fn hint(error: sqlx::Error) -> anyhow::Error {
+    let message = error.to_string();
+    let error = anyhow::Error::new(error);
+    if message.contains("does not support") {
+        error.context(
+            "A column has a type that can't be read, cast it to text, eg: `created_at::text` or \
+             `CAST(created_at AS CHAR)`",
+        )
+    } else {
+        error
+    }
+}
+
+/// The values of the row as text, `NULL` for the missing ones.
+fn values(row: &AnyRow) -> Vec<String> {
+    (0..row.len())
+        .map(|index| {
+            let value = row
+                .try_get::<Option<String>, _>(index)
+                .or_else(|_| {
+                    row.try_get::<Option<i64>, _>(index)
+                        .map(|v| v.map(|v| v.to_string()))
+                })
+                .or_else(|_| {
+                    row.try_get::<Option<f64>, _>(index)
+                        .map(|v| v.map(|v| v.to_string()))
+                })
+                .or_else(|_| {
+                    row.try_get::<Option<bool>, _>(index)
+                        .map(|v| v.map(|v| v.to_string()))
+                })
+                .or_else(|_| {
+                    row.try_get::<Option<Vec<u8>>, _>(index)
+                        .map(|v| v.map(|bytes| format!("<{} bytes>", bytes.len())))
+                });
+            match value {
+                Ok(Some(value)) => value,
+                Ok(None) => "NULL".to_string(),
+                Err(_) => "<unreadable>".to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Rows returned by a query, formatted as a table with one row per line.
+#[derive(Debug, Default, PartialEq)]
+pub struct Rows {
+    pub columns: Vec<String>,
+    pub values: Vec<Vec<String>>,
+    /// Whether there were more rows than requested
+    pub truncated: bool,
+}
+
+impl Display for Rows {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.values.is_empty() {
+            return write!(f, "No rows");
+        }
+        let mut output = self.columns.join(" | ");
+        let mut shown = 0;
+        for row in &self.values {
+            let line = row
+                .iter()
+                .map(|value| cell(value))
+                .collect::<Vec<_>>()
+                .join(" | ");
+            if output.len() + line.len() > MAX_OUTPUT_CHARS {
+                break;
+            }
+            output.push('\n');
+            output.push_str(&line);
+            shown += 1;
+        }
+        write!(f, "{output}")?;
+        if shown < self.values.len() {
+            write!(
+                f,
+                "\n({shown} of {} rows shown, the rest exceeds the size limit, select fewer columns or rows)",
+                self.values.len()
+            )
+        } else if self.truncated {
+            write!(
+                f,
+                "\n(first {shown} rows shown, add a LIMIT or raise max_rows to see more)"
+            )
+        } else {
+            write!(f, "\n({shown} rows)")
+        }
+    }
+}
+
+/// A value on a single line of the table, cut to its maximum length.
+fn cell(value: &str) -> String {
+    let value = value.replace('\n', "\\n").replace('|', "\\|");
+    if value.chars().count() > MAX_VALUE_CHARS {
+        let cut = value.chars().take(MAX_VALUE_CHARS).collect::<String>();
+        format!("{cut}…")
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_backend_from_url() {
+        assert_eq!(
+            Backend::from_url("postgresql://localhost/app").unwrap(),
+            Backend::Postgres
+        );
+        assert_eq!(
+            Backend::from_url("sqlite://data/dev.db").unwrap(),
+            Backend::Sqlite
+        );
+        assert!(Backend::from_url("mongodb://localhost").is_err());
+    }
+
+    #[test]
+    fn test_rows_display() {
+        let fixture = Rows {
+            columns: strings(&["id", "bio"]),
+            values: vec![
+                strings(&["1", "line\nbreak | pipe"]),
+                strings(&["2", "NULL"]),
+            ],
+            truncated: true,
+        };
+        let actual = fixture.to_string();
+        let expected = "id | bio\n1 | line\\nbreak \\| pipe\n2 | NULL\n(first 2 rows shown, add a LIMIT or raise max_rows to see more)";
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_sqlite() {
+        let dir = TempDir::new().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("app.db").display());
+        let writable = BTreeMap::from([(
+            "app".to_string(),
+            Database::default().url(url.clone()).allow_writes(true),
+        )]);
+        let mut connection = Connection::open(&writable, None).await.unwrap();
+        connection
+            .execute("CREATE TABLE users (id INTEGER, name TEXT)")
+            .await
+            .unwrap();
+        connection
+            .execute("INSERT INTO users VALUES (1, 'ada')")
+            .await
+            .unwrap();
+
+        let read_only = BTreeMap::from([("app".to_string(), Database::default().url(url))]);
+        let mut connection = Connection::open(&read_only, Some("app")).await.unwrap();
+        let actual = connection
+            .rows("SELECT * FROM users", &[], 10)
+            .await
+            .unwrap();
+        let expected = Rows {
+            columns: strings(&["id", "name"]),
+            values: vec![strings(&["1", "ada"])],
+            truncated: false,
+        };
+        assert_eq!(actual, expected);
+        assert!(connection.execute("DELETE FROM users").await.is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use anyhow::bail;
+use forge_domain::{Database, ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::connection::Connection;
+use super::statement::Statement;
+
+/// Rows returned when the input doesn't set how many.
+const DEFAULT_MAX_ROWS: usize = 100;
+const MAX_ROWS: usize = 1000;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DbQueryInput {
+    /// The name of the database in the config (default: the only one
+    /// configured).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database: Option<String>,
+    /// A single SQL statement, in the dialect of the database.
+    pub query: String,
+    /// The maximum number of rows returned (default: 100, at most 1000).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_rows: Option<usize>,
+}
+
+/// Runs a single SQL statement against one of the SQLite, Postgres or MySQL
+/// databases set up in the config and returns its rows, one `value | value`
+/// line per row after a line of column names, with long values cut. Databases
+/// are read-only unless the config sets `allow_writes` for them: statements
+/// that change the data or the schema are rejected and the session itself is
+/// read-only. Prefer filtering and aggregating in SQL over fetching many rows,
+/// and use tool_forge_db_schema to find the tables and their columns first.
+#[derive(ToolDescription)]
+pub struct DbQuery {
+    databases: BTreeMap<String, Database>,
+}
+
+impl DbQuery {
+    pub fn new(databases: BTreeMap<String, Database>) -> Self {
+        Self { databases }
+    }
+}
+
+impl NamedTool for DbQuery {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_db_query")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for DbQuery {
+    type Input = DbQueryInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let statement = Statement::parse(&input.query)?;
+        let mut connection = Connection::open(&self.databases, input.database.as_deref()).await?;
+        if let Some(keyword) = &statement.write {
+            if !connection.allow_writes {
+                bail!(
+                    "The database `{}` is read-only and the query writes to it (`{}`). Set \
+                     `allow_writes = true` for it in the config to allow writes",
+                    connection.name,
+                    keyword.to_uppercase()
+                );
+            }
+        }
+
+        if statement.returns_rows {
+            let max_rows = input
+                .max_rows
+                .unwrap_or(DEFAULT_MAX_ROWS)
+                .clamp(1, MAX_ROWS);
+            let rows = connection.rows(&input.query, &[], max_rows).await?;
+            Ok(rows.to_string())
+        } else {
+            let affected = connection.execute(&input.query).await?;
+            Ok(format!("{affected} rows affected"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    #[tokio::test]
+    async fn test_write_rejected_when_read_only() {
+        let dir = TempDir::new().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("app.db").display());
+        let tool = DbQuery::new(BTreeMap::from([(
+            "app".to_string(),
+            Database::default().url(url),
+        )]));
+
+        let actual = tool
+            .call(DbQueryInput {
+                database: None,
+                query: "CREATE TABLE users (id INTEGER)".to_string(),
+                max_rows: None,
+            })
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert_eq!(
+            actual,
+            "The database `app` is read-only and the query writes to it (`CREATE`). Set \
+             `allow_writes = true` for it in the config to allow writes"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_database_configured() {
+        let tool = DbQuery::new(BTreeMap::new());
+
+        let actual = tool
+            .call(DbQueryInput {
+                database: None,
+                query: "SELECT 1".to_string(),
+                max_rows: None,
+            })
+            .await;
+
+        assert!(actual
+            .unwrap_err()
+            .to_string()
+            .starts_with("No database is configured"));
+    }
+}
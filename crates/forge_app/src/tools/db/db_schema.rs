@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+
+use forge_domain::{Database, ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::connection::{Backend, Connection};
+
+/// Tables or columns returned, more than any schema an agent should read at
+/// once.
+const MAX_ROWS: usize = 1000;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DbSchemaInput {
+    /// The name of the database in the config (default: the only one
+    /// configured).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database: Option<String>,
+    /// The table whose columns are listed (default: list the tables).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+}
+
+/// Inspects the schema of one of the SQLite, Postgres or MySQL databases set
+/// up in the config. Lists its tables and views, or with a table the columns
+/// of that table with their type, whether they are nullable and their
+/// default. Only the tables of the current schema are listed for Postgres
+/// (`public` unless the search path says otherwise) and of the current
+/// database for MySQL.
+#[derive(ToolDescription)]
+pub struct DbSchema {
+    databases: BTreeMap<String, Database>,
+}
+
+impl DbSchema {
+    pub fn new(databases: BTreeMap<String, Database>) -> Self {
+        Self { databases }
+    }
+}
+
+impl NamedTool for DbSchema {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_db_schema")
+    }
+}
+
+/// The query listing the tables of the database.
+fn tables(backend: Backend) -> &'static str {
+    match backend {
+        Backend::Sqlite => {
+            "SELECT name, type FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT \
+             LIKE 'sqlite_%' ORDER BY name"
+        }
+        Backend::Postgres => {
+            "SELECT table_name::text AS name, table_type::text AS type FROM \
+             information_schema.tables WHERE table_schema = current_schema() ORDER BY table_name"
+        }
+        Backend::MySql => {
+            "SELECT CAST(table_name AS CHAR) AS name, CAST(table_type AS CHAR) AS type FROM \
+             information_schema.tables WHERE table_schema = DATABASE() ORDER BY table_name"
+        }
+    }
+}
+
+/// The query listing the columns of the table bound as its parameter.
+fn columns(backend: Backend) -> String {
+    let placeholder = backend.placeholder();
+    match backend {
+        Backend::Sqlite => format!(
+            "SELECT name, type, CASE WHEN \"notnull\" = 1 THEN 'NO' ELSE 'YES' END AS nullable, \
+             dflt_value AS \"default\", pk AS primary_key FROM pragma_table_info({placeholder}) \
+             ORDER BY cid"
+        ),
+        Backend::Postgres => format!(
+            "SELECT column_name::text AS name, data_type::text AS type, is_nullable::text AS \
+             nullable, column_default::text AS \"default\" FROM information_schema.columns WHERE \
+             table_schema = current_schema() AND table_name = {placeholder} ORDER BY \
+             ordinal_position"
+        ),
+        Backend::MySql => format!(
+            "SELECT CAST(column_name AS CHAR) AS name, CAST(column_type AS CHAR) AS type, \
+             CAST(is_nullable AS CHAR) AS nullable, CAST(column_default AS CHAR) AS `default`, \
+             CAST(column_key AS CHAR) AS `key` FROM information_schema.columns WHERE \
+             table_schema = DATABASE() AND table_name = {placeholder} ORDER BY ordinal_position"
+        ),
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for DbSchema {
+    type Input = DbSchemaInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let mut connection = Connection::open(&self.databases, input.database.as_deref()).await?;
+        match input.table {
+            Some(table) => {
+                let query = columns(connection.backend);
+                let rows = connection.rows(&query, &[&table], MAX_ROWS).await?;
+                if rows.values.is_empty() {
+                    anyhow::bail!(
+                        "No table named `{table}` in the database `{}`",
+                        connection.name
+                    );
+                }
+                Ok(rows.to_string())
+            }
+            None => {
+                let query = tables(connection.backend);
+                let rows = connection.rows(query, &[], MAX_ROWS).await?;
+                Ok(rows.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    #[tokio::test]
+    async fn test_sqlite_schema() {
+        let dir = TempDir::new().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("app.db").display());
+        let databases = BTreeMap::from([(
+            "app".to_string(),
+            Database::default().url(url).allow_writes(true),
+        )]);
+        let mut connection = Connection::open(&databases, None).await.unwrap();
+        connection
+            .execute("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+            .await
+            .unwrap();
+        let tool = DbSchema::new(databases);
+
+        let actual = tool
+            .call(DbSchemaInput { database: None, table: None })
+            .await
+            .unwrap();
+        assert_eq!(actual, "name | type\nusers | table\n(1 rows)");
+
+        let actual = tool
+            .call(DbSchemaInput { database: None, table: Some("users".to_string()) })
+            .await
+            .unwrap();
+        assert_eq!(
+            actual,
+            "name | type | nullable | default | primary_key\nid | INTEGER | YES | NULL | 1\nname \
+             | TEXT | NO | NULL | 0\n(2 rows)"
+        );
+    }
+}
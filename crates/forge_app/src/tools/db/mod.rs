@@ -0,0 +1,7 @@
+mod connection;
+mod db_query;
+mod db_schema;
+mod statement;
+
+pub use db_query::*;
+pub use db_schema::*;
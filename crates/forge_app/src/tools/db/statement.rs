@@ -0,0 +1,182 @@
+use anyhow::bail;
+
+/// Keywords that start the statements which only read data.
+const READ_KEYWORDS: &[&str] = &[
+    "select", "with", "values", "table", "show", "explain", "describe", "desc",
+];
+
+/// Keywords of the statements, or the clauses, that change the data or the
+/// schema, eg: a `WITH` followed by a `DELETE` or `SELECT ... INTO`. Checked
+/// anywhere in the query, so that some reads are rejected as well, eg:
+/// `EXPLAIN ANALYZE` which runs the statement.
+const WRITE_KEYWORDS: &[&str] = &[
+    "insert", "update", "delete", "merge", "upsert", "replace", "into", "create", "alter", "drop",
+    "truncate", "rename", "grant", "revoke", "attach", "detach", "copy", "call", "exec", "execute",
+    "do", "lock", "set", "reset", "vacuum", "reindex", "analyze", "load", "pragma",
+];
+
+/// A single SQL statement, as far as the read-only mode is concerned.
+#[derive(Debug, PartialEq)]
+pub struct Statement {
+    /// The keyword that makes it a write, `None` for a read
+    pub write: Option<String>,
+    /// Whether it returns rows, eg: a read or a write with `RETURNING`
+    pub returns_rows: bool,
+}
+
+impl Statement {
+    /// Classifies the query, which must be a single statement. Only the
+    /// keywords outside of comments, strings and quoted identifiers count.
+    pub fn parse(query: &str) -> anyhow::Result<Self> {
+        let (words, statements) = words(query);
+        if statements > 1 {
+            bail!("Only one statement can be run at a time");
+        }
+        let Some(first) = words.first() else {
+            bail!("The query is empty");
+        };
+
+        let write = if READ_KEYWORDS.contains(&first.as_str()) {
+            words
+                .iter()
+                .find(|word| WRITE_KEYWORDS.contains(&word.as_str()))
+                .cloned()
+        } else {
+            Some(first.clone())
+        };
+        let returns_rows = write.is_none() || words.iter().any(|word| word == "returning");
+        Ok(Self { write, returns_rows })
+    }
+}
+
+/// The lowercase words of the query outside of comments, strings and quoted
+/// identifiers, along with its number of statements.
+fn words(query: &str) -> (Vec<String>, usize) {
+    let chars = query.chars().collect::<Vec<_>>();
+    let mut words = Vec::new();
+    let mut statements = 0;
+    // note: a statement is counted at its first word, so that a trailing
+    // semicolon doesn't count as another one.
+    let mut in_statement = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            '-' if next == Some('-') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            // note: the executable comments of MySQL, eg: `/*! ... */`, are
+            // read as the rest of the query.
+            '/' if next == Some('*') && chars.get(i + 2) == Some(&'!') => i += 3,
+            '/' if next == Some('*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            '\'' | '"' | '`' => {
+                // note: a doubled quote is an escaped one, which is read as the
+                // end of the quote followed by the start of another. The
+                // quoting that only some databases support, eg: backslash
+                // escapes or the dollar quotes of Postgres, is ignored so that
+                // the keywords are rather found in a string than hidden.
+                i += 1;
+                while i < chars.len() && chars[i] != c {
+                    i += 1;
+                }
+                i += 1;
+            }
+            ';' => {
+                in_statement = false;
+                i += 1;
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                if !in_statement {
+                    in_statement = true;
+                    statements += 1;
+                }
+                words.push(chars[start..i].iter().collect::<String>().to_lowercase());
+            }
+            _ => i += 1,
+        }
+    }
+    (words, statements)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn write(query: &str) -> Option<String> {
+        Statement::parse(query).unwrap().write
+    }
+
+    #[test]
+    fn test_reads() {
+        let fixtures = [
+            "SELECT * FROM users WHERE name = 'drop table';",
+            "with recent as (select * from orders) select count(*) from recent",
+            "EXPLAIN SELECT 1",
+            "SELECT \"update\" FROM t -- delete everything\n",
+            "SELECT * FROM t WHERE id = $1 AND name = 'it''s'",
+        ];
+        for fixture in fixtures {
+            assert_eq!(write(fixture), None, "{fixture}");
+        }
+    }
+
+    #[test]
+    fn test_writes() {
+        let fixtures = [
+            ("DELETE FROM users", "delete"),
+            (
+                "with old as (delete from t returning *) select * from old",
+                "delete",
+            ),
+            ("SELECT * INTO backup FROM users", "into"),
+            ("EXPLAIN ANALYZE SELECT 1", "analyze"),
+            ("PRAGMA journal_mode = WAL", "pragma"),
+            ("/* report */ update t set a = 1", "update"),
+            ("SELECT 1 /*! , sleep(1) INTO @a */", "into"),
+        ];
+        for (fixture, keyword) in fixtures {
+            assert_eq!(write(fixture), Some(keyword.to_string()), "{fixture}");
+        }
+    }
+
+    #[test]
+    fn test_returns_rows() {
+        assert!(
+            !Statement::parse("INSERT INTO t VALUES (1)")
+                .unwrap()
+                .returns_rows
+        );
+        assert!(
+            Statement::parse("INSERT INTO t VALUES (1) RETURNING id")
+                .unwrap()
+                .returns_rows
+        );
+    }
+
+    #[test]
+    fn test_multiple_statements() {
+        let actual = Statement::parse("SELECT 1; DROP TABLE users").unwrap_err();
+        assert_eq!(
+            actual.to_string(),
+            "Only one statement can be run at a time"
+        );
+        assert!(Statement::parse("SELECT ';'; -- done").is_ok());
+        // A backslash doesn't escape the quote in Postgres
+        assert!(Statement::parse("SELECT '\\'; DELETE FROM t; --'").is_err());
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use forge_domain::PathGuard;
+use serde::Deserialize;
+
+use crate::tools::shell::executor::{CommandExecutor, Output};
+
+/// Lines at the end of an output that are returned.
+pub const MAX_OUTPUT_LINES: usize = 50;
+
+/// The files docker compose reads by default, in its order of preference.
+const COMPOSE_FILES: [&str; 4] = [
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yaml",
+    "docker-compose.yml",
+];
+
+/// Runs the docker CLI in `dir`, returning its command line along with its
+/// output.
+pub async fn docker(
+    dir: &Path,
+    args: &[String],
+    timeout: Duration,
+) -> anyhow::Result<(String, Output)> {
+    let command_line = format!("docker {}", args.join(" "));
+
+    #[cfg(not(test))]
+    {
+        use forge_display::TitleFormat;
+
+        println!("{}", TitleFormat::execute(&command_line).format());
+    }
+
+    let mut command = tokio::process::Command::new("docker");
+    command.args(args).current_dir(dir).kill_on_drop(true);
+    let output = CommandExecutor::new(command)
+        .timeout(Some(timeout))
+        .execute()
+        .await
+        .with_context(|| format!("Failed to run {command_line}, is docker installed?"))?;
+    Ok((command_line, output))
+}
+
+/// Rejects the values that the docker CLI would read as options, eg: an image
+/// named `--privileged`.
+pub fn argument<'a>(name: &str, value: &'a str) -> anyhow::Result<&'a str> {
+    if value.is_empty() || value.starts_with('-') {
+        bail!("Invalid {name} `{value}`");
+    }
+    Ok(value)
+}
+
+/// The last lines of the text.
+pub fn tail(text: &str, lines: usize) -> String {
+    let all = text.trim().lines().collect::<Vec<_>>();
+    all[all.len().saturating_sub(lines)..].join("\n")
+}
+
+/// Why the command failed, with the end of its output.
+pub fn failure(command_line: &str, output: &Output) -> String {
+    let mut report = String::new();
+    if let Some(timeout) = output.timed_out {
+        report.push_str(&format!(
+            "The command timed out after {} seconds and was killed.\n",
+            timeout.as_secs()
+        ));
+    }
+    report.push_str(&format!(
+        "{command_line} failed:\n<output>{}</output>",
+        tail(
+            &format!("{}\n{}", output.stdout, output.stderr),
+            MAX_OUTPUT_LINES
+        )
+    ));
+    report
+}
+
+/// A compose project: the directory docker compose runs in, along with the
+/// compose file when it isn't one it finds by default.
+pub struct ComposeProject {
+    pub dir: PathBuf,
+    file: Option<PathBuf>,
+}
+
+impl ComposeProject {
+    /// The project of the compose file at `path`, or of the compose file in
+    /// the directory at `path` or in the current working directory.
+    pub fn resolve(guard: &PathGuard, path: Option<&str>) -> anyhow::Result<Self> {
+        let path = match path {
+            Some(path) => guard.resolve(path)?,
+            None => guard.cwd().to_path_buf(),
+        };
+        if path.is_file() {
+            let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            return Ok(Self { dir, file: Some(path) });
+        }
+        if !COMPOSE_FILES.iter().any(|file| path.join(file).is_file()) {
+            bail!(
+                "No compose file found in {}, expected one of: {}",
+                path.display(),
+                COMPOSE_FILES.join(", ")
+            );
+        }
+        Ok(Self { dir: path, file: None })
+    }
+
+    /// The arguments of a docker compose command.
+    pub fn args<'a>(&self, args: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        let mut all = vec!["compose".to_string()];
+        if let Some(file) = &self.file {
+            all.push("--file".to_string());
+            all.push(file.display().to_string());
+        }
+        all.extend(args.into_iter().map(str::to_string));
+        all
+    }
+
+    /// The state of the services of the project, stopped ones included.
+    pub async fn services(&self) -> anyhow::Result<Vec<Service>> {
+        let args = self.args(["ps", "--all", "--format", "json"]);
+        let (command_line, output) = docker(&self.dir, &args, Duration::from_secs(60)).await?;
+        if !output.success {
+            bail!(failure(&command_line, &output));
+        }
+        Service::parse(&output.stdout)
+    }
+}
+
+/// A container of a compose service, as listed by `docker compose ps`.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Service {
+    pub service: String,
+    pub state: String,
+    #[serde(default)]
+    pub health: String,
+    #[serde(default)]
+    pub exit_code: i64,
+    #[serde(default)]
+    pub publishers: Option<Vec<Publisher>>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Publisher {
+    #[serde(rename = "URL", default)]
+    pub url: String,
+    pub target_port: u16,
+    pub published_port: u16,
+    pub protocol: String,
+}
+
+impl Service {
+    /// Parses the output of `docker compose ps --format json`, which is an
+    /// array in older versions and one object per line in newer ones.
+    pub fn parse(output: &str) -> anyhow::Result<Vec<Self>> {
+        let output = output.trim();
+        if output.starts_with('[') {
+            return serde_json::from_str(output).context("Failed to parse the services");
+        }
+        output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).context("Failed to parse the services"))
+            .collect()
+    }
+
+    /// Whether the service is up, and healthy when it has a health check.
+    pub fn is_up(&self) -> bool {
+        self.state == "running" && (self.health.is_empty() || self.health == "healthy")
+    }
+}
+
+impl std::fmt::Display for Service {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.service, self.state)?;
+        if !self.health.is_empty() {
+            write!(f, " ({})", self.health)?;
+        }
+        if self.state == "exited" {
+            write!(f, " with code {}", self.exit_code)?;
+        }
+        for publisher in self.publishers.iter().flatten() {
+            if publisher.published_port != 0 {
+                let host = if publisher.url.is_empty() {
+                    "0.0.0.0"
+                } else {
+                    &publisher.url
+                };
+                write!(
+                    f,
+                    " {host}:{}->{}/{}",
+                    publisher.published_port, publisher.target_port, publisher.protocol
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A step of a BuildKit build, as printed with `--progress=plain`.
+#[derive(Debug, Default, PartialEq)]
+pub struct BuildStep {
+    pub name: String,
+    pub cached: bool,
+    pub error: Option<String>,
+    pub output: Vec<String>,
+}
+
+/// Parses the plain progress of a BuildKit build into its steps, in the order
+/// they started, along with the error that ended the build.
+pub fn parse_build(output: &str) -> (Vec<BuildStep>, Option<String>) {
+    let mut steps: BTreeMap<usize, BuildStep> = BTreeMap::new();
+    let mut order = Vec::new();
+    let mut error = None;
+    for line in output.lines() {
+        if let Some(message) = line.strip_prefix("ERROR: ") {
+            error = Some(message.to_string());
+            continue;
+        }
+        let Some((id, rest)) = line
+            .strip_prefix('#')
+            .and_then(|line| line.split_once(' '))
+            .and_then(|(id, rest)| Some((id.parse::<usize>().ok()?, rest)))
+        else {
+            continue;
+        };
+        let step = steps.entry(id).or_insert_with(|| {
+            order.push(id);
+            BuildStep::default()
+        });
+        if step.name.is_empty() {
+            step.name = rest.to_string();
+        } else if rest == "CACHED" {
+            step.cached = true;
+        } else if let Some(message) = rest.strip_prefix("ERROR: ") {
+            step.error = Some(message.to_string());
+        } else if !rest.starts_with("DONE ") {
+            // note: the output of the step is prefixed with the seconds since
+            // it started, eg: `#7 0.412 Compiling foo`.
+            let text = match rest.split_once(' ') {
+                Some((seconds, text)) if seconds.parse::<f64>().is_ok() => text,
+                _ => rest,
+            };
+            step.output.push(text.to_string());
+        }
+    }
+    let steps = order
+        .into_iter()
+        .filter_map(|id| steps.remove(&id))
+        .collect();
+    (steps, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    #[test]
+    fn test_parse_build() {
+        let fixture = "#1 [internal] load build definition from Dockerfile\n\
+                       #1 DONE 0.0s\n\
+                       #5 [1/3] FROM docker.io/library/rust:1.85\n\
+                       #5 CACHED\n\
+                       #7 [2/3] RUN cargo build --release\n\
+                       #7 0.412    Compiling app v0.1.0\n\
+                       #7 3.101 error[E0425]: cannot find value `x` in this scope\n\
+                       #7 ERROR: process \"/bin/sh -c cargo build --release\" did not complete successfully: exit code: 101\n\
+                       ERROR: failed to solve: exit code: 101";
+
+        let (steps, error) = parse_build(fixture);
+
+        assert_eq!(steps.len(), 3);
+        assert!(steps[1].cached);
+        assert_eq!(
+            steps[2],
+            BuildStep {
+                name: "[2/3] RUN cargo build --release".to_string(),
+                cached: false,
+                error: Some(
+                    "process \"/bin/sh -c cargo build --release\" did not complete successfully: exit code: 101"
+                        .to_string()
+                ),
+                output: vec![
+                    "   Compiling app v0.1.0".to_string(),
+                    "error[E0425]: cannot find value `x` in this scope".to_string()
+                ],
+            }
+        );
+        assert_eq!(error, Some("failed to solve: exit code: 101".to_string()));
+    }
+
+    #[test]
+    fn test_parse_services() {
+        let fixture = r#"{"Service":"db","State":"running","Health":"healthy","ExitCode":0,"Publishers":[{"URL":"127.0.0.1","TargetPort":5432,"PublishedPort":5433,"Protocol":"tcp"}]}
+{"Service":"web","State":"exited","Health":"","ExitCode":1,"Publishers":null}"#;
+
+        let actual = Service::parse(fixture)
+            .unwrap()
+            .iter()
+            .map(|service| service.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            actual,
+            vec![
+                "db running (healthy) 127.0.0.1:5433->5432/tcp",
+                "web exited with code 1"
+            ]
+        );
+        assert_eq!(Service::parse("[]").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_compose_project_without_file() {
+        let dir = TempDir::new().unwrap();
+
+        let actual = ComposeProject::resolve(
+            &TempDir::guard(),
+            Some(dir.path().to_string_lossy().as_ref()),
+        );
+
+        assert!(actual
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("No compose file found"));
+    }
+
+    #[test]
+    fn test_argument() {
+        assert_eq!(argument("image", "app:dev").unwrap(), "app:dev");
+        assert!(argument("image", "--privileged").is_err());
+    }
+}
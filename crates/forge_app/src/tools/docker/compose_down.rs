@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::cli::{docker, failure, ComposeProject};
+
+/// Seconds after which stopping the services is killed.
+const TIMEOUT_SECS: u64 = 300;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ComposeDownInput {
+    /// The absolute path of the compose file, or of the directory that
+    /// contains it (default: the current working directory).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Whether the volumes of the project are removed too, losing their data
+    /// (default: false).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volumes: Option<bool>,
+}
+
+/// Stops the services of a docker compose project and removes their
+/// containers and networks, along with its volumes when asked to.
+#[derive(ToolDescription)]
+pub struct ComposeDown {
+    guard: PathGuard,
+}
+
+impl ComposeDown {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for ComposeDown {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_compose_down")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for ComposeDown {
+    type Input = ComposeDownInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let project = ComposeProject::resolve(&self.guard, input.path.as_deref())?;
+        let volumes = input.volumes.unwrap_or(false);
+        let mut args = vec!["down", "--remove-orphans"];
+        if volumes {
+            args.push("--volumes");
+        }
+        let args = project.args(args);
+
+        let (command_line, output) =
+            docker(&project.dir, &args, Duration::from_secs(TIMEOUT_SECS)).await?;
+        if !output.success {
+            anyhow::bail!(failure(&command_line, &output));
+        }
+        Ok(if volumes {
+            "Removed the containers, networks and volumes of the project".to_string()
+        } else {
+            "Removed the containers and networks of the project".to_string()
+        })
+    }
+}
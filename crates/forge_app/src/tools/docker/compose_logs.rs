@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::cli::{argument, docker, failure, ComposeProject};
+
+/// Lines returned when the input doesn't set how many.
+const DEFAULT_LINES: usize = 100;
+const MAX_LINES: usize = 1000;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ComposeLogsInput {
+    /// The absolute path of the compose file, or of the directory that
+    /// contains it (default: the current working directory).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// The services whose logs are returned (default: all of them).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub services: Option<Vec<String>>,
+    /// The number of lines returned from the end of the logs of each service
+    /// (default: 100, at most 1000).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lines: Option<usize>,
+    /// Only the logs since then, as a timestamp or a duration, eg: `10m`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+}
+
+/// Returns the end of the logs of the services of a docker compose project,
+/// each line prefixed with the container it comes from.
+#[derive(ToolDescription)]
+pub struct ComposeLogs {
+    guard: PathGuard,
+}
+
+impl ComposeLogs {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for ComposeLogs {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_compose_logs")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for ComposeLogs {
+    type Input = ComposeLogsInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let project = ComposeProject::resolve(&self.guard, input.path.as_deref())?;
+        let lines = input.lines.unwrap_or(DEFAULT_LINES).clamp(1, MAX_LINES);
+        let lines_arg = lines.to_string();
+        let mut args = vec!["logs", "--no-color", "--tail", lines_arg.as_str()];
+        if let Some(since) = &input.since {
+            args.push("--since");
+            args.push(argument("since", since)?);
+        }
+        let services = input.services.unwrap_or_default();
+        for service in &services {
+            args.push(argument("service", service)?);
+        }
+        let args = project.args(args);
+
+        let (command_line, output) = docker(&project.dir, &args, Duration::from_secs(60)).await?;
+        if !output.success {
+            anyhow::bail!(failure(&command_line, &output));
+        }
+        // note: the logs are split between stdout and stderr like the output
+        // of the containers, so only the lines of each are in order.
+        let logs = format!("{}\n{}", output.stdout, output.stderr);
+        if logs.trim().is_empty() {
+            return Ok("No logs".to_string());
+        }
+        Ok(logs.trim().to_string())
+    }
+}
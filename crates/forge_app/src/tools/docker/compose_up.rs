@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::cli::{argument, docker, failure, tail, ComposeProject};
+
+/// Default seconds after which starting the services is killed, building
+/// their images can take a while.
+const DEFAULT_TIMEOUT_SECS: u64 = 900;
+
+/// Lines of the logs of each service that failed to start that are returned.
+const MAX_LOG_LINES: usize = 30;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ComposeUpInput {
+    /// The absolute path of the compose file, or of the directory that
+    /// contains it (default: the current working directory).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// The services to start, along with their dependencies (default: all
+    /// of them).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub services: Option<Vec<String>>,
+    /// Whether the images are built before starting the services (default:
+    /// true).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build: Option<bool>,
+    /// Seconds after which starting the services is killed (default: 900).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Starts the services of a docker compose project in the background and
+/// waits until they are running, and healthy for those with a health check.
+/// Returns the state of each service with its published ports, eg:
+/// `web running (healthy) 127.0.0.1:8080->80/tcp`, along with the end of the
+/// logs of the services that failed to start. Read more of their logs with
+/// tool_forge_compose_logs and stop them with tool_forge_compose_down once
+/// done.
+#[derive(ToolDescription)]
+pub struct ComposeUp {
+    guard: PathGuard,
+}
+
+impl ComposeUp {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for ComposeUp {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_compose_up")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for ComposeUp {
+    type Input = ComposeUpInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let project = ComposeProject::resolve(&self.guard, input.path.as_deref())?;
+        let services = input.services.unwrap_or_default();
+        let mut args = vec!["up", "--detach", "--wait"];
+        if input.build.unwrap_or(true) {
+            args.push("--build");
+        }
+        for service in &services {
+            args.push(argument("service", service)?);
+        }
+        let args = project.args(args);
+
+        let timeout = Duration::from_secs(input.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+        let (command_line, output) = docker(&project.dir, &args, timeout).await?;
+        let states = project.services().await?;
+
+        let mut report = states
+            .iter()
+            .map(|service| service.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if output.success {
+            return Ok(report);
+        }
+        for service in states.iter().filter(|service| !service.is_up()) {
+            let lines = MAX_LOG_LINES.to_string();
+            let args = project.args([
+                "logs",
+                "--no-color",
+                "--tail",
+                lines.as_str(),
+                service.service.as_str(),
+            ]);
+            let (_, logs) = docker(&project.dir, &args, Duration::from_secs(60)).await?;
+            report.push_str(&format!(
+                "\n<logs service=\"{}\">{}</logs>",
+                service.service,
+                tail(&format!("{}\n{}", logs.stdout, logs.stderr), MAX_LOG_LINES)
+            ));
+        }
+        report.push_str(&format!("\n{}", failure(&command_line, &output)));
+        Err(anyhow::anyhow!(report.trim_start().to_string()))
+    }
+}
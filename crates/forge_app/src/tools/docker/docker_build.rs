@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::cli::{argument, docker, failure, parse_build, tail, MAX_OUTPUT_LINES};
+
+/// Default seconds after which the build is killed.
+const DEFAULT_TIMEOUT_SECS: u64 = 900;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DockerBuildInput {
+    /// The absolute path of the build context (default: the current working
+    /// directory).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// The absolute path of the Dockerfile (default: the Dockerfile of the
+    /// build context).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dockerfile: Option<String>,
+    /// The name and tag of the image, eg: `app:dev`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// The stage of a multi-stage Dockerfile to build.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Build arguments, by their name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_args: Option<BTreeMap<String, String>>,
+    /// Seconds after which the build is killed (default: 900).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Builds a Docker image from a Dockerfile and returns its ID along with the
+/// number of steps and how many were cached. When the build fails, it
+/// returns the step that failed with its error and the end of its output,
+/// instead of the whole build log. Tag the image to run it afterwards with
+/// tool_forge_docker_run.
+#[derive(ToolDescription)]
+pub struct DockerBuild {
+    guard: PathGuard,
+}
+
+impl DockerBuild {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for DockerBuild {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_docker_build")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for DockerBuild {
+    type Input = DockerBuildInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let context = match &input.path {
+            Some(path) => self.guard.resolve(path)?,
+            None => self.guard.cwd().to_path_buf(),
+        };
+        let iid_file =
+            std::env::temp_dir().join(format!("forge-{}.iid", uuid::Uuid::new_v4().simple()));
+
+        let mut args = vec![
+            "build".to_string(),
+            "--progress=plain".to_string(),
+            "--iidfile".to_string(),
+            iid_file.display().to_string(),
+        ];
+        if let Some(dockerfile) = &input.dockerfile {
+            args.push("--file".to_string());
+            args.push(self.guard.resolve(dockerfile)?.display().to_string());
+        }
+        if let Some(tag) = &input.tag {
+            args.push("--tag".to_string());
+            args.push(argument("tag", tag)?.to_string());
+        }
+        if let Some(target) = &input.target {
+            args.push("--target".to_string());
+            args.push(argument("target", target)?.to_string());
+        }
+        for (name, value) in input.build_args.iter().flatten() {
+            args.push("--build-arg".to_string());
+            args.push(format!("{}={value}", argument("build argument", name)?));
+        }
+        args.push(context.display().to_string());
+
+        let timeout = Duration::from_secs(input.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+        let (command_line, output) = docker(&context, &args, timeout).await?;
+        let image = std::fs::read_to_string(&iid_file).ok();
+        let _ = std::fs::remove_file(&iid_file);
+
+        // note: BuildKit prints its progress to stderr.
+        let (steps, error) = parse_build(&output.stderr);
+        if !output.success {
+            let Some(step) = steps.iter().find(|step| step.error.is_some()) else {
+                anyhow::bail!(failure(&command_line, &output));
+            };
+            anyhow::bail!(
+                "The build failed at step {}: {}\n<output>{}</output>",
+                step.name,
+                step.error.as_deref().unwrap_or_default(),
+                tail(&step.output.join("\n"), MAX_OUTPUT_LINES)
+            );
+        }
+        if let Some(error) = error {
+            anyhow::bail!("The build failed: {error}");
+        }
+
+        let mut report = format!(
+            "Built image {}",
+            image.as_deref().map(str::trim).unwrap_or("without an ID")
+        );
+        if let Some(tag) = &input.tag {
+            report.push_str(&format!(" tagged {tag}"));
+        }
+        if !steps.is_empty() {
+            report.push_str(&format!(
+                " in {} steps, {} cached",
+                steps.len(),
+                steps.iter().filter(|step| step.cached).count()
+            ));
+        }
+        Ok(report)
+    }
+}
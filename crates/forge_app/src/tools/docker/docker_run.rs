@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::cli::{argument, docker, tail, MAX_OUTPUT_LINES};
+
+/// Default seconds after which the container is removed.
+const DEFAULT_TIMEOUT_SECS: u64 = 120;
+const MAX_TIMEOUT_SECS: u64 = 1800;
+
+/// Default limits of the container.
+const DEFAULT_MEMORY: &str = "1g";
+const DEFAULT_CPUS: f64 = 1.0;
+const PIDS_LIMIT: u32 = 512;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DockerRunInput {
+    /// The image to run, eg: `app:dev`.
+    pub image: String,
+    /// The command run in the container, each argument on its own (default:
+    /// the command of the image).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<Vec<String>>,
+    /// Environment variables of the container, by their name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<BTreeMap<String, String>>,
+    /// Whether the container has network access (default: false).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<bool>,
+    /// The memory limit of the container, eg: `512m` (default: `1g`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<String>,
+    /// The number of CPUs the container can use (default: 1).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<f64>,
+    /// Seconds after which the container is removed (default: 120, at most
+    /// 1800).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Runs a command in a new container of a Docker image and returns the end of
+/// its stdout and stderr, eg: to smoke-test an image built with
+/// tool_forge_docker_build. The container is removed once the command exits
+/// or times out. It runs with limits: 1 CPU, 1g of memory and 512 processes
+/// by default, no network unless enabled, no new privileges and no access to
+/// the files of the host. Use the compose tools for services that keep
+/// running.
+#[derive(ToolDescription)]
+pub struct DockerRun {
+    guard: PathGuard,
+}
+
+impl DockerRun {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for DockerRun {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_docker_run")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for DockerRun {
+    type Input = DockerRunInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let cpus = input.cpus.unwrap_or(DEFAULT_CPUS);
+        if cpus <= 0.0 {
+            anyhow::bail!("The number of CPUs must be positive, got {cpus}");
+        }
+        let memory = match &input.memory {
+            Some(memory) => argument("memory limit", memory)?,
+            None => DEFAULT_MEMORY,
+        };
+        let name = format!("forge-run-{}", uuid::Uuid::new_v4().simple());
+
+        let mut args = vec![
+            "run".to_string(),
+            "--rm".to_string(),
+            "--init".to_string(),
+            "--name".to_string(),
+            name.clone(),
+            "--memory".to_string(),
+            memory.to_string(),
+            "--cpus".to_string(),
+            cpus.to_string(),
+            "--pids-limit".to_string(),
+            PIDS_LIMIT.to_string(),
+            "--security-opt".to_string(),
+            "no-new-privileges".to_string(),
+        ];
+        if !input.network.unwrap_or(false) {
+            args.push("--network".to_string());
+            args.push("none".to_string());
+        }
+        for (key, value) in input.env.iter().flatten() {
+            args.push("--env".to_string());
+            args.push(format!(
+                "{}={value}",
+                argument("environment variable", key)?
+            ));
+        }
+        args.push(argument("image", &input.image)?.to_string());
+        args.extend(input.command.into_iter().flatten());
+
+        let timeout = Duration::from_secs(
+            input
+                .timeout_secs
+                .unwrap_or(DEFAULT_TIMEOUT_SECS)
+                .min(MAX_TIMEOUT_SECS),
+        );
+        let dir = self.guard.cwd();
+        let (_, output) = docker(dir, &args, timeout).await?;
+        if output.timed_out.is_some() {
+            // note: killing the docker CLI leaves the container running.
+            let remove = ["rm".to_string(), "--force".to_string(), name];
+            let _ = docker(dir, &remove, Duration::from_secs(30)).await;
+        }
+
+        let mut report = Vec::new();
+        if !output.stdout.trim().is_empty() {
+            report.push(format!(
+                "<stdout>{}</stdout>",
+                tail(&output.stdout, MAX_OUTPUT_LINES)
+            ));
+        }
+        if !output.stderr.trim().is_empty() {
+            report.push(format!(
+                "<stderr>{}</stderr>",
+                tail(&output.stderr, MAX_OUTPUT_LINES)
+            ));
+        }
+        if let Some(timeout) = output.timed_out {
+            report.push(format!(
+                "The container timed out after {} seconds and was removed.",
+                timeout.as_secs()
+            ));
+        }
+        let report = if !report.is_empty() {
+            report.join("\n")
+        } else if output.success {
+            "The container exited successfully with no output.".to_string()
+        } else {
+            "The container failed with no output.".to_string()
+        };
+        if output.success {
+            Ok(report)
+        } else {
+            Err(anyhow::anyhow!(report))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    #[tokio::test]
+    async fn test_image_like_an_option() {
+        let tool = DockerRun::new(TempDir::guard());
+
+        let actual = tool
+            .call(DockerRunInput {
+                image: "--privileged".to_string(),
+                command: None,
+                env: None,
+                network: None,
+                memory: None,
+                cpus: None,
+                timeout_secs: None,
+            })
+            .await;
+
+        assert_eq!(
+            actual.unwrap_err().to_string(),
+            "Invalid image `--privileged`"
+        );
+    }
+}
@@ -0,0 +1,12 @@
+mod cli;
+mod compose_down;
+mod compose_logs;
+mod compose_up;
+mod docker_build;
+mod docker_run;
+
+pub use compose_down::*;
+pub use compose_logs::*;
+pub use compose_up::*;
+pub use docker_build::*;
+pub use docker_run::*;
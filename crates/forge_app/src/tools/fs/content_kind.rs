@@ -0,0 +1,89 @@
+use std::path::Path;
+
+use derive_more::Display;
+
+/// Lockfiles, which are generated and mostly noise to the agent.
+const LOCKFILES: [&str; 9] = [
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "Cargo.lock",
+    "poetry.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+];
+
+/// Size above which a lockfile is skipped.
+const LOCKFILE_MIN_BYTES: usize = 32 * 1024;
+
+/// Bytes sniffed for a NUL byte, like git does.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+/// Average line length above which a file is considered minified.
+const MINIFIED_LINE_BYTES: usize = 500;
+
+/// Content that is skipped by the fs tools unless they're forced, since it
+/// would flood the context without helping the agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum ContentKind {
+    #[display("binary")]
+    Binary,
+    #[display("minified")]
+    Minified,
+    #[display("lockfile")]
+    Lockfile,
+}
+
+impl ContentKind {
+    /// Detects the kind of the file at `path` from its name and content,
+    /// which is `None` for regular text.
+    pub fn detect(path: &Path, content: &[u8]) -> Option<Self> {
+        let sniffed = &content[..content.len().min(BINARY_SNIFF_BYTES)];
+        if sniffed.contains(&0) || std::str::from_utf8(content).is_err() {
+            return Some(Self::Binary);
+        }
+        let name = path.file_name().and_then(|name| name.to_str());
+        if content.len() > LOCKFILE_MIN_BYTES && name.is_some_and(|name| LOCKFILES.contains(&name))
+        {
+            return Some(Self::Lockfile);
+        }
+        let lines = content.split(|byte| *byte == b'\n').count();
+        if content.len() > MINIFIED_LINE_BYTES && content.len() / lines > MINIFIED_LINE_BYTES {
+            return Some(Self::Minified);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let lockfile = "[[package]]\nname = \"anyhow\"\n".repeat(2000);
+        let minified = format!("var a={};", "b".repeat(2000));
+
+        let actual = [
+            ContentKind::detect(Path::new("/logo.png"), b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+            ContentKind::detect(Path::new("/latin1.txt"), b"caf\xe9"),
+            ContentKind::detect(Path::new("/Cargo.lock"), lockfile.as_bytes()),
+            ContentKind::detect(Path::new("/Cargo.lock"), b"version = 4\n"),
+            ContentKind::detect(Path::new("/dist/app.min.js"), minified.as_bytes()),
+            ContentKind::detect(Path::new("/src/main.rs"), b"fn main() {}\n"),
+        ];
+        let expected = [
+            Some(ContentKind::Binary),
+            Some(ContentKind::Binary),
+            Some(ContentKind::Lockfile),
+            None,
+            Some(ContentKind::Minified),
+            None,
+        ];
+        assert_eq!(actual, expected);
+    }
+}
@@ -1,13 +1,9 @@
-use std::path::Path;
-
 use anyhow::Context;
-use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
 use forge_tool_macros::ToolDescription;
 use schemars::JsonSchema;
 use serde::Deserialize;
 
-use crate::tools::utils::assert_absolute_path;
-
 #[derive(Deserialize, JsonSchema)]
 pub struct FSFileInfoInput {
     /// The path of the file or directory to inspect (absolute path required)
@@ -20,7 +16,15 @@ pub struct FSFileInfoInput {
 /// this when you need to understand file characteristics without reading the
 /// actual content.
 #[derive(ToolDescription)]
-pub struct FSFileInfo;
+pub struct FSFileInfo {
+    guard: PathGuard,
+}
+
+impl FSFileInfo {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
 
 impl NamedTool for FSFileInfo {
     fn tool_name() -> ToolName {
@@ -33,10 +37,9 @@ impl ExecutableTool for FSFileInfo {
     type Input = FSFileInfoInput;
 
     async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
-        let path = Path::new(&input.path);
-        assert_absolute_path(path)?;
+        let path = &self.guard.resolve(&input.path)?;
 
-        let meta = tokio::fs::metadata(&input.path)
+        let meta = tokio::fs::metadata(path)
             .await
             .with_context(|| format!("Failed to get metadata for '{}'", input.path))?;
         Ok(format!("{:?}", meta))
@@ -56,7 +59,7 @@ mod test {
         let file_path = temp_dir.path().join("test.txt");
         fs::write(&file_path, "test content").await.unwrap();
 
-        let fs_info = FSFileInfo;
+        let fs_info = FSFileInfo::new(TempDir::guard());
         let result = fs_info
             .call(FSFileInfoInput { path: file_path.to_string_lossy().to_string() })
             .await
@@ -73,7 +76,7 @@ mod test {
         let dir_path = temp_dir.path().join("test_dir");
         fs::create_dir(&dir_path).await.unwrap();
 
-        let fs_info = FSFileInfo;
+        let fs_info = FSFileInfo::new(TempDir::guard());
         let result = fs_info
             .call(FSFileInfoInput { path: dir_path.to_string_lossy().to_string() })
             .await
@@ -89,7 +92,7 @@ mod test {
         let temp_dir = TempDir::new().unwrap();
         let nonexistent_path = temp_dir.path().join("nonexistent");
 
-        let fs_info = FSFileInfo;
+        let fs_info = FSFileInfo::new(TempDir::guard());
         let result = fs_info
             .call(FSFileInfoInput { path: nonexistent_path.to_string_lossy().to_string() })
             .await;
@@ -99,7 +102,7 @@ mod test {
 
     #[tokio::test]
     async fn test_fs_file_info_relative_path() {
-        let fs_info = FSFileInfo;
+        let fs_info = FSFileInfo::new(TempDir::guard());
         let result = fs_info
             .call(FSFileInfoInput { path: "relative/path.txt".to_string() })
             .await;
@@ -0,0 +1,218 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tokio::process::Command;
+
+/// Time a formatter has to format a file before it's killed.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Formatters the files written by the tools are formatted with, so that
+/// their content matches the style of the project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Formatter {
+    Rustfmt { edition: Option<String> },
+    Prettier,
+    Black,
+    Gofmt,
+}
+
+impl Formatter {
+    /// Detects the formatter of the file from the configs of the project in
+    /// its directory or the ones above it, along with the directory it's run
+    /// in. None when the project doesn't use one.
+    pub fn detect(path: &Path) -> Option<(Self, PathBuf)> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        path.ancestors().skip(1).find_map(|dir| {
+            let read = |file: &str| std::fs::read_to_string(dir.join(file)).unwrap_or_default();
+            let exists = |file: &str| dir.join(file).is_file();
+            let formatter = match extension.as_str() {
+                "rs" if ["Cargo.toml", "rustfmt.toml", ".rustfmt.toml"]
+                    .iter()
+                    .any(|file| exists(file)) =>
+                {
+                    Some(Self::Rustfmt { edition: edition(&read("Cargo.toml")) })
+                }
+                "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "json" | "css" | "scss" | "less"
+                | "html" | "vue" | "md" | "yaml" | "yml" => {
+                    let configured = [
+                        ".prettierrc",
+                        ".prettierrc.json",
+                        ".prettierrc.yaml",
+                        ".prettierrc.yml",
+                        ".prettierrc.js",
+                        ".prettierrc.cjs",
+                        ".prettierrc.toml",
+                        "prettier.config.js",
+                        "prettier.config.cjs",
+                        "prettier.config.mjs",
+                    ]
+                    .iter()
+                    .any(|file| exists(file))
+                        || read("package.json").contains("\"prettier\"");
+                    configured.then_some(Self::Prettier)
+                }
+                "py" | "pyi" if read("pyproject.toml").contains("[tool.black]") => {
+                    Some(Self::Black)
+                }
+                "go" if exists("go.mod") => Some(Self::Gofmt),
+                _ => None,
+            };
+            formatter.map(|formatter| (formatter, dir.to_path_buf()))
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rustfmt { .. } => "rustfmt",
+            Self::Prettier => "prettier",
+            Self::Black => "black",
+            Self::Gofmt => "gofmt",
+        }
+    }
+
+    fn command(&self, path: &Path, dir: &Path) -> Command {
+        let mut command = match self {
+            Self::Rustfmt { edition } => {
+                let mut command = Command::new("rustfmt");
+                if let Some(edition) = edition {
+                    command.args(["--edition", edition]);
+                }
+                command
+            }
+            Self::Prettier => {
+                // note: the version of the project is preferred over a global
+                // one, whose defaults may differ.
+                let local = dir.join("node_modules").join(".bin").join("prettier");
+                let mut command = if local.is_file() {
+                    Command::new(local)
+                } else {
+                    let mut command = Command::new("npx");
+                    command.args(["--no-install", "prettier"]);
+                    command
+                };
+                command.arg("--write");
+                command
+            }
+            Self::Black => {
+                let mut command = Command::new("black");
+                command.arg("--quiet");
+                command
+            }
+            Self::Gofmt => {
+                let mut command = Command::new("gofmt");
+                command.arg("-w");
+                command
+            }
+        };
+        command.arg(path).current_dir(dir).kill_on_drop(true);
+        command
+    }
+
+    /// Formats the file in place with the formatter of its project, returning
+    /// the name of the formatter when it changed the file. A formatter that
+    /// isn't installed or fails, eg: on a syntax error, leaves the file as it
+    /// was written.
+    pub async fn format(path: &Path) -> Option<&'static str> {
+        let (formatter, dir) = Self::detect(path)?;
+        let before = tokio::fs::read(path).await.ok()?;
+        let output = tokio::time::timeout(TIMEOUT, formatter.command(path, &dir).output()).await;
+        match output {
+            Ok(Ok(output)) if output.status.success() => {}
+            Ok(Ok(output)) => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let formatter = formatter.name();
+                tracing::debug!(formatter, stderr = %stderr, "Failed to format the file");
+                return None;
+            }
+            Ok(Err(error)) => {
+                let formatter = formatter.name();
+                tracing::debug!(formatter, error = %error, "Failed to run the formatter");
+                return None;
+            }
+            Err(_) => {
+                tracing::debug!(formatter = formatter.name(), "The formatter timed out");
+                return None;
+            }
+        }
+        let after = tokio::fs::read(path).await.ok()?;
+        (before != after).then_some(formatter.name())
+    }
+}
+
+/// The edition set in a `Cargo.toml`, which rustfmt doesn't read by itself.
+/// None when it's inherited from the workspace.
+fn edition(manifest: &str) -> Option<String> {
+    manifest.lines().find_map(|line| {
+        let value = line
+            .trim()
+            .strip_prefix("edition")?
+            .trim_start()
+            .strip_prefix('=')?;
+        Some(value.trim().trim_matches('"').to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("Cargo.toml"), "[package]\nedition = \"2021\"\n").unwrap();
+        std::fs::write(
+            root.join("package.json"),
+            r#"{"devDependencies": {"prettier": "3"}}"#,
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("api")).unwrap();
+        std::fs::write(root.join("api/go.mod"), "module api\n").unwrap();
+
+        let actual = [
+            "src/main.rs",
+            "web/app.tsx",
+            "api/main.go",
+            "main.py",
+            "README",
+        ]
+        .map(|file| Formatter::detect(&root.join(file)));
+        let expected = [
+            Some((
+                Formatter::Rustfmt { edition: Some("2021".to_string()) },
+                root.to_path_buf(),
+            )),
+            Some((Formatter::Prettier, root.to_path_buf())),
+            Some((Formatter::Gofmt, root.join("api"))),
+            None,
+            None,
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_detect_nearest_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("pyproject.toml"),
+            "[tool.black]\nline-length = 100\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("crates/forge")).unwrap();
+        std::fs::write(root.join("crates/forge/Cargo.toml"), "[package]\n").unwrap();
+        std::fs::write(root.join("rustfmt.toml"), "max_width = 100\n").unwrap();
+
+        let actual = Formatter::detect(&root.join("crates/forge/src/lib.rs"));
+        let expected = Some((
+            Formatter::Rustfmt { edition: None },
+            root.join("crates/forge"),
+        ));
+        assert_eq!(actual, expected);
+
+        let actual = Formatter::detect(&root.join("scripts/release.py"));
+        assert_eq!(actual, Some((Formatter::Black, root.to_path_buf())));
+    }
+}
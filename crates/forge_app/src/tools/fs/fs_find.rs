@@ -3,33 +3,54 @@ use std::path::Path;
 
 use anyhow::Context;
 use forge_display::{GrepFormat, Kind, TitleFormat};
-use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
 use forge_tool_macros::ToolDescription;
 use forge_walker::Walker;
-use regex::Regex;
+use regex::RegexBuilder;
 use schemars::JsonSchema;
 use serde::Deserialize;
 
-use crate::tools::utils::assert_absolute_path;
+use super::content_kind::ContentKind;
 
-#[derive(Deserialize, JsonSchema)]
+/// Matches returned when `max_results` isn't set.
+const DEFAULT_MAX_RESULTS: usize = 100;
+
+#[derive(Default, Deserialize, JsonSchema)]
 pub struct FSSearchInput {
     /// The path of the directory to search in (absolute path required). This
     /// directory will be recursively searched.
     pub path: String,
     /// The regular expression pattern to search for. Uses Rust regex syntax.
     pub regex: String,
-    /// Glob pattern to filter files (e.g., '*.ts' for TypeScript files). If not
-    /// provided, it will search all files (*).
+    /// Glob pattern of the files to search, matched against the file name or
+    /// relative path (e.g., '*.ts', 'src/**/*.rs'). Defaults to all files.
     pub file_pattern: Option<String>,
+    /// Glob pattern of the files to skip (e.g., '*.test.ts').
+    pub exclude_pattern: Option<String>,
+    /// Whether the regex is case sensitive. Defaults to false.
+    pub case_sensitive: Option<bool>,
+    /// Maximum matches to return. Defaults to 100.
+    pub max_results: Option<usize>,
+    /// Lines shown before and after each match. Defaults to 0.
+    pub context_lines: Option<usize>,
+    /// Also searches minified files and big lockfiles.
+    pub force: Option<bool>,
 }
 
 /// Request to perform a regex search on the content across files in a specified
-/// directory, providing context-rich results. This tool searches for patterns
-/// or specific content across multiple files, displaying each match with
-/// encapsulating context. The path must be absolute.
+/// directory, providing context-rich results. The path must be absolute. Each
+/// match is returned in a `<match>` tag with its path, line and column, the
+/// matching line prefixed with `line:` and its context lines with `line-`.
 #[derive(ToolDescription)]
-pub struct FSSearch;
+pub struct FSSearch {
+    guard: PathGuard,
+}
+
+impl FSSearch {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
 
 impl From<&FSSearchInput> for TitleFormat {
     fn from(input: &FSSearchInput) -> Self {
@@ -55,30 +76,40 @@ impl ExecutableTool for FSSearch {
     type Input = FSSearchInput;
 
     async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
-        let dir = Path::new(&input.path);
-        assert_absolute_path(dir)?;
+        let dir = &self.guard.resolve(&input.path)?;
 
         if !dir.exists() {
             return Err(anyhow::anyhow!("Directory '{}' does not exist", input.path));
         }
 
-        // Create regex pattern - case-insensitive by default
-        let pattern = format!("(?i){}", input.regex);
-        let regex = Regex::new(&pattern)
+        // Case-insensitive by default
+        let regex = RegexBuilder::new(&input.regex)
+            .case_insensitive(!input.case_sensitive.unwrap_or(false))
+            .build()
             .with_context(|| format!("Invalid regex pattern: {}", input.regex))?;
+        let include = glob_pattern(input.file_pattern.as_deref())?;
+        let exclude = glob_pattern(input.exclude_pattern.as_deref())?;
+        let max_results = input.max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+        let context_lines = input.context_lines.unwrap_or(0);
 
         // TODO: Current implementation is extremely slow and inefficient.
         // It should ideally be taking in a stream of files and processing them
         // concurrently.
         let walker = Walker::max_all().cwd(dir.to_path_buf());
 
-        let files = walker
+        let mut files = walker
             .get()
             .await
             .with_context(|| format!("Failed to walk directory '{}'", dir.display()))?;
+        // Sort the files so that the matches kept by `max_results` are stable
+        files.sort_by(|a, b| a.path.cmp(&b.path));
 
         let mut matches = Vec::new();
+        let mut lines = Vec::new();
+        let mut skipped = Vec::new();
+        let mut errors = Vec::new();
         let mut seen_paths = HashSet::new();
+        let mut truncated = false;
 
         for file in files {
             if file.is_dir() {
@@ -88,20 +119,15 @@ impl ExecutableTool for FSSearch {
             let path = Path::new(&file.path);
             let full_path = dir.join(path);
 
-            // Apply file pattern filter if provided
-            if let Some(ref pattern) = input.file_pattern {
-                let glob = glob::Pattern::new(pattern).with_context(|| {
-                    format!(
-                        "Invalid glob pattern '{}' for file '{}'",
-                        pattern,
-                        full_path.display(),
-                    )
-                })?;
-                if let Some(filename) = path.file_name().unwrap_or(path.as_os_str()).to_str() {
-                    if !glob.matches(filename) {
-                        continue;
-                    }
-                }
+            // Apply the file pattern filters if provided
+            if include
+                .as_ref()
+                .is_some_and(|glob| !matches_glob(glob, path))
+                || exclude
+                    .as_ref()
+                    .is_some_and(|glob| matches_glob(glob, path))
+            {
+                continue;
             }
 
             // Skip if we've already processed this file
@@ -110,23 +136,53 @@ impl ExecutableTool for FSSearch {
             }
 
             // Try to read the file content
-            let content = match tokio::fs::read_to_string(&full_path).await {
+            let content = match tokio::fs::read(&full_path).await {
                 Ok(content) => content,
                 Err(e) => {
-                    // Skip binary or unreadable files silently
-                    if e.kind() != std::io::ErrorKind::InvalidData {
-                        matches.push(format!("Error reading {:?}: {}", full_path.display(), e));
-                    }
+                    errors.push(format!("Error reading {:?}: {}", full_path.display(), e));
                     continue;
                 }
             };
+            let kind = ContentKind::detect(&full_path, &content);
+            // Skip binary files silently
+            if kind == Some(ContentKind::Binary) {
+                continue;
+            }
+            let content = String::from_utf8_lossy(&content);
+            if let Some(kind) = kind.filter(|_| !input.force.unwrap_or(false)) {
+                let count = content.lines().filter(|line| regex.is_match(line)).count();
+                if count > 0 {
+                    skipped.push(format!(
+                        r#"<skipped path="{}" kind="{kind}" matches="{count}" />"#,
+                        full_path.display()
+                    ));
+                }
+                continue;
+            }
 
             // Process the file line by line
-            for (line_num, line) in content.lines().enumerate() {
-                if regex.is_match(line) {
-                    // Format match in ripgrep style: filepath:line_num:content
-                    matches.push(format!("{}:{}:{}", full_path.display(), line_num + 1, line));
+            let content = content.lines().collect::<Vec<_>>();
+            for (index, line) in content.iter().enumerate() {
+                let Some(found) = regex.find(line) else {
+                    continue;
+                };
+                if matches.len() == max_results {
+                    truncated = true;
+                    break;
                 }
+                let column = line[..found.start()].chars().count() + 1;
+                matches.push(format_match(
+                    &full_path,
+                    &content,
+                    index,
+                    column,
+                    context_lines,
+                ));
+                // Format match in ripgrep style: filepath:line_num:content
+                lines.push(format!("{}:{}:{}", full_path.display(), index + 1, line));
+            }
+            if truncated {
+                break;
             }
         }
 
@@ -134,13 +190,60 @@ impl ExecutableTool for FSSearch {
         println!("{}", TitleFormat::from(&input).format());
 
         // Print results using GrepFormat for all cases
-        let formatted_output = GrepFormat::new(matches.clone()).format(&regex);
+        let formatted_output = GrepFormat::new(lines).format(&regex);
         println!("{}", formatted_output);
 
+        matches.extend(skipped);
+        if truncated {
+            matches.push(format!(
+                "Showing the first {max_results} matches, narrow the search with a more specific regex or file_pattern, or increase max_results."
+            ));
+        }
+        matches.extend(errors);
         Ok(matches.join("\n"))
     }
 }
 
+fn glob_pattern(pattern: Option<&str>) -> anyhow::Result<Option<glob::Pattern>> {
+    pattern
+        .map(|pattern| {
+            glob::Pattern::new(pattern).with_context(|| format!("Invalid glob pattern '{pattern}'"))
+        })
+        .transpose()
+}
+
+/// Matches the glob against the file name, eg: `*.rs`, or the path relative
+/// to the searched directory, eg: `src/**/*.rs`.
+fn matches_glob(glob: &glob::Pattern, path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| glob.matches_path(Path::new(name)))
+        || glob.matches_path(path)
+}
+
+/// Formats the match on the line at `index` of `lines` with `context` lines
+/// before and after it.
+fn format_match(
+    path: &Path,
+    lines: &[&str],
+    index: usize,
+    column: usize,
+    context: usize,
+) -> String {
+    let start = index.saturating_sub(context);
+    let end = (index + context).min(lines.len() - 1);
+    let mut output = format!(
+        r#"<match path="{}" line="{}" column="{column}">"#,
+        path.display(),
+        index + 1
+    );
+    for (number, line) in lines.iter().enumerate().take(end + 1).skip(start) {
+        let separator = if number == index { ':' } else { '-' };
+        output.push_str(&format!("\n{}{separator}{line}", number + 1));
+    }
+    output.push_str("\n</match>");
+    output
+}
+
 #[cfg(test)]
 mod test {
     use pretty_assertions::assert_eq;
@@ -163,18 +266,18 @@ mod test {
             .await
             .unwrap();
 
-        let fs_search = FSSearch;
+        let fs_search = FSSearch::new(TempDir::guard());
         let result = fs_search
             .call(FSSearchInput {
                 path: temp_dir.path().to_string_lossy().to_string(),
                 regex: "test".to_string(),
                 file_pattern: None,
+                ..Default::default()
             })
             .await
             .unwrap();
 
-        let lines: Vec<_> = result.lines().collect();
-        assert_eq!(lines.len(), 2);
+        assert_eq!(result.matches("<match ").count(), 2);
         assert!(result.contains("test1.txt"));
         assert!(result.contains("test2.txt"));
     }
@@ -190,18 +293,18 @@ mod test {
             .await
             .unwrap();
 
-        let fs_search = FSSearch;
+        let fs_search = FSSearch::new(TempDir::guard());
         let result = fs_search
             .call(FSSearchInput {
                 path: temp_dir.path().to_string_lossy().to_string(),
                 regex: "test".to_string(),
                 file_pattern: Some("*.rs".to_string()),
+                ..Default::default()
             })
             .await
             .unwrap();
 
-        let lines: Vec<_> = result.lines().collect();
-        assert_eq!(lines.len(), 1);
+        assert_eq!(result.matches("<match ").count(), 1);
         assert!(result.contains("test2.rs"));
     }
 
@@ -214,18 +317,18 @@ mod test {
             .await
             .unwrap();
 
-        let fs_search = FSSearch;
+        let fs_search = FSSearch::new(TempDir::guard());
         let result = fs_search
             .call(FSSearchInput {
                 path: temp_dir.path().to_string_lossy().to_string(),
                 regex: "test".to_string(),
                 file_pattern: None,
+                ..Default::default()
             })
             .await
             .unwrap();
 
-        let lines: Vec<_> = result.lines().collect();
-        assert_eq!(lines.len(), 1);
+        assert_eq!(result.matches("<match ").count(), 1);
         assert!(result.contains("test line"));
     }
 
@@ -246,18 +349,18 @@ mod test {
             .await
             .unwrap();
 
-        let fs_search = FSSearch;
+        let fs_search = FSSearch::new(TempDir::guard());
         let result = fs_search
             .call(FSSearchInput {
                 path: temp_dir.path().to_string_lossy().to_string(),
                 regex: "test".to_string(),
                 file_pattern: None,
+                ..Default::default()
             })
             .await
             .unwrap();
 
-        let lines: Vec<_> = result.lines().collect();
-        assert_eq!(lines.len(), 3);
+        assert_eq!(result.matches("<match ").count(), 3);
         assert!(result.contains("test1.txt"));
         assert!(result.contains("test2.txt"));
         assert!(result.contains("best.txt"));
@@ -274,18 +377,18 @@ mod test {
         .await
         .unwrap();
 
-        let fs_search = FSSearch;
+        let fs_search = FSSearch::new(TempDir::guard());
         let result = fs_search
             .call(FSSearchInput {
                 path: temp_dir.path().to_string_lossy().to_string(),
                 regex: "test".to_string(),
                 file_pattern: None,
+                ..Default::default()
             })
             .await
             .unwrap();
 
-        let lines: Vec<_> = result.lines().collect();
-        assert_eq!(lines.len(), 2);
+        assert_eq!(result.matches("<match ").count(), 2);
         assert!(result.contains("TEST CONTENT"));
         assert!(result.contains("test content"));
     }
@@ -298,12 +401,13 @@ mod test {
             .await
             .unwrap();
 
-        let fs_search = FSSearch;
+        let fs_search = FSSearch::new(TempDir::guard());
         let result = fs_search
             .call(FSSearchInput {
                 path: temp_dir.path().to_string_lossy().to_string(),
                 regex: "nonexistent".to_string(),
                 file_pattern: None,
+                ..Default::default()
             })
             .await
             .unwrap();
@@ -315,12 +419,13 @@ mod test {
     async fn test_fs_search_invalid_regex() {
         let temp_dir = TempDir::new().unwrap();
 
-        let fs_search = FSSearch;
+        let fs_search = FSSearch::new(TempDir::guard());
         let result = fs_search
             .call(FSSearchInput {
                 path: temp_dir.path().to_string_lossy().to_string(),
                 regex: "[invalid".to_string(),
                 file_pattern: None,
+                ..Default::default()
             })
             .await;
 
@@ -333,12 +438,13 @@ mod test {
 
     #[tokio::test]
     async fn test_fs_search_relative_path() {
-        let fs_search = FSSearch;
+        let fs_search = FSSearch::new(TempDir::guard());
         let result = fs_search
             .call(FSSearchInput {
                 path: "relative/path".to_string(),
                 regex: "test".to_string(),
                 file_pattern: None,
+                ..Default::default()
             })
             .await;
 
@@ -348,4 +454,149 @@ mod test {
             .to_string()
             .contains("Path must be absolute"));
     }
+
+    #[tokio::test]
+    async fn test_fs_search_structured_matches() {
+        let temp_dir = TempDir::new().unwrap();
+        let content = "line 1\nline 2\nthe test line\nline 4\nline 5";
+        fs::write(temp_dir.path().join("test.txt"), content)
+            .await
+            .unwrap();
+
+        let fs_search = FSSearch::new(TempDir::guard());
+        let actual = fs_search
+            .call(FSSearchInput {
+                path: temp_dir.path().to_string_lossy().to_string(),
+                regex: "test".to_string(),
+                context_lines: Some(1),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let expected = format!(
+            "<match path=\"{}\" line=\"3\" column=\"5\">\n2-line 2\n3:the test line\n4-line 4\n</match>",
+            temp_dir.path().join("test.txt").display()
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_fs_search_exclude_pattern() {
+        let temp_dir = TempDir::new().unwrap();
+        let sub_dir = temp_dir.path().join("src");
+        fs::create_dir(&sub_dir).await.unwrap();
+        fs::write(sub_dir.join("lib.rs"), "fn test() {}")
+            .await
+            .unwrap();
+        fs::write(sub_dir.join("lib.test.rs"), "fn test() {}")
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("test.txt"), "test")
+            .await
+            .unwrap();
+
+        let fs_search = FSSearch::new(TempDir::guard());
+        let result = fs_search
+            .call(FSSearchInput {
+                path: temp_dir.path().to_string_lossy().to_string(),
+                regex: "test".to_string(),
+                file_pattern: Some("src/*.rs".to_string()),
+                exclude_pattern: Some("*.test.rs".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(result.matches("<match ").count(), 1);
+        assert!(result.contains("lib.rs"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_search_case_sensitive() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("test.txt"),
+            "TEST CONTENT\ntest content",
+        )
+        .await
+        .unwrap();
+
+        let fs_search = FSSearch::new(TempDir::guard());
+        let result = fs_search
+            .call(FSSearchInput {
+                path: temp_dir.path().to_string_lossy().to_string(),
+                regex: "test".to_string(),
+                case_sensitive: Some(true),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(result.matches("<match ").count(), 1);
+        assert!(result.contains("2:test content"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_search_max_results() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("test.txt"), "test\ntest\ntest")
+            .await
+            .unwrap();
+
+        let fs_search = FSSearch::new(TempDir::guard());
+        let result = fs_search
+            .call(FSSearchInput {
+                path: temp_dir.path().to_string_lossy().to_string(),
+                regex: "test".to_string(),
+                max_results: Some(2),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(result.matches("<match ").count(), 2);
+        assert!(result.ends_with("Showing the first 2 matches, narrow the search with a more specific regex or file_pattern, or increase max_results."));
+    }
+
+    #[test]
+    fn test_format_match_context_at_the_edges() {
+        let lines = ["fn main() {", "}"];
+
+        let actual = format_match(Path::new("/main.rs"), &lines, 0, 1, 3);
+        let expected =
+            "<match path=\"/main.rs\" line=\"1\" column=\"1\">\n1:fn main() {\n2-}\n</match>";
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_fs_search_skips_minified_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let minified = format!("var test={};", "b".repeat(2000));
+        fs::write(temp_dir.path().join("app.min.js"), &minified)
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("logo.png"), b"test\0\x89PNG")
+            .await
+            .unwrap();
+
+        let fs_search = FSSearch::new(TempDir::guard());
+        let input = || FSSearchInput {
+            path: temp_dir.path().to_string_lossy().to_string(),
+            regex: "test".to_string(),
+            ..Default::default()
+        };
+        let skipped = fs_search.call(input()).await.unwrap();
+        let forced = fs_search
+            .call(FSSearchInput { force: Some(true), ..input() })
+            .await
+            .unwrap();
+
+        let expected = format!(
+            r#"<skipped path="{}" kind="minified" matches="1" />"#,
+            temp_dir.path().join("app.min.js").display()
+        );
+        assert_eq!(skipped, expected);
+        assert_eq!(forced.matches("<match ").count(), 1);
+    }
 }
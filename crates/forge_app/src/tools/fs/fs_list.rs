@@ -1,14 +1,10 @@
-use std::path::Path;
-
 use anyhow::Context;
-use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
 use forge_tool_macros::ToolDescription;
 use forge_walker::Walker;
 use schemars::JsonSchema;
 use serde::Deserialize;
 
-use crate::tools::utils::assert_absolute_path;
-
 #[derive(Deserialize, JsonSchema)]
 pub struct FSListInput {
     /// The path of the directory to list contents for (absolute path required)
@@ -24,11 +20,18 @@ pub struct FSListInput {
 /// contents. The path must be absolute. Do not use this tool to confirm the
 /// existence of files you may have created, as the user will let you know if
 /// the files were created successfully or not.
-#[derive(Default, ToolDescription)]
+#[derive(ToolDescription)]
 pub struct FSList {
+    guard: PathGuard,
     sorted: bool,
 }
 
+impl FSList {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard, sorted: false }
+    }
+}
+
 impl NamedTool for FSList {
     fn tool_name() -> ToolName {
         ToolName::new("tool_forge_fs_list")
@@ -40,8 +43,7 @@ impl ExecutableTool for FSList {
     type Input = FSListInput;
 
     async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
-        let dir = Path::new(&input.path);
-        assert_absolute_path(dir)?;
+        let dir = &self.guard.resolve(&input.path)?;
 
         if !dir.exists() {
             return Err(anyhow::anyhow!("Directory '{}' does not exist", input.path));
@@ -97,8 +99,8 @@ mod test {
     use crate::tools::utils::TempDir;
 
     impl FSList {
-        fn new(sorted: bool) -> Self {
-            Self { sorted }
+        fn sorted(guard: PathGuard) -> Self {
+            Self { guard, sorted: true }
         }
     }
 
@@ -106,7 +108,7 @@ mod test {
     async fn test_fs_list_empty_directory() {
         let temp_dir = TempDir::new().unwrap();
 
-        let fs_list = FSList::new(true);
+        let fs_list = FSList::sorted(TempDir::guard());
         let result = fs_list
             .call(FSListInput {
                 path: temp_dir.path().to_string_lossy().to_string(),
@@ -131,7 +133,7 @@ mod test {
         fs::create_dir(temp_dir.path().join("dir1")).await.unwrap();
         fs::create_dir(temp_dir.path().join("dir2")).await.unwrap();
 
-        let fs_list = FSList::new(true);
+        let fs_list = FSList::sorted(TempDir::guard());
         let result = fs_list
             .call(FSListInput {
                 path: temp_dir.path().to_string_lossy().to_string(),
@@ -148,7 +150,7 @@ mod test {
         let temp_dir = TempDir::new().unwrap();
         let nonexistent_dir = temp_dir.path().join("nonexistent");
 
-        let fs_list = FSList::new(true);
+        let fs_list = FSList::sorted(TempDir::guard());
         let result = fs_list
             .call(FSListInput {
                 path: nonexistent_dir.to_string_lossy().to_string(),
@@ -173,7 +175,7 @@ mod test {
             .await
             .unwrap();
 
-        let fs_list = FSList::new(true);
+        let fs_list = FSList::sorted(TempDir::guard());
         let result = fs_list
             .call(FSListInput {
                 path: temp_dir.path().to_string_lossy().to_string(),
@@ -206,7 +208,7 @@ mod test {
             .await
             .unwrap();
 
-        let fs_list = FSList::new(true);
+        let fs_list = FSList::sorted(TempDir::guard());
 
         // Test recursive listing
         let result = fs_list
@@ -222,7 +224,7 @@ mod test {
 
     #[tokio::test]
     async fn test_fs_list_relative_path() {
-        let fs_list = FSList::new(true);
+        let fs_list = FSList::sorted(TempDir::guard());
         let result = fs_list
             .call(FSListInput { path: "relative/path".to_string(), recursive: None })
             .await;
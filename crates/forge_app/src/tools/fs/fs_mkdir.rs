@@ -0,0 +1,104 @@
+use anyhow::{bail, Context};
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct FSMkdirInput {
+    /// The path of the directory to create (absolute path required)
+    pub path: String,
+}
+
+/// Creates a directory at the specified path, along with its missing parent
+/// directories. The path must be absolute. Creating a directory that already
+/// exists succeeds without changing it. Files don't need it, their directories
+/// are created when they're written.
+#[derive(ToolDescription)]
+pub struct FSMkdir {
+    guard: PathGuard,
+}
+
+impl FSMkdir {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for FSMkdir {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_fs_mkdir")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for FSMkdir {
+    type Input = FSMkdirInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let path = &self.guard.resolve(&input.path)?;
+
+        if path.is_dir() {
+            return Ok(format!("Directory already exists: {}", input.path));
+        }
+        if path.exists() {
+            bail!("Path exists and is not a directory: {}", input.path);
+        }
+
+        tokio::fs::create_dir_all(path)
+            .await
+            .with_context(|| format!("Failed to create directory {}", input.path))?;
+
+        Ok(format!("Successfully created directory: {}", input.path))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::fs;
+
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    #[tokio::test]
+    async fn test_fs_mkdir_nested() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("a/b/c");
+
+        let result = FSMkdir::new(TempDir::guard())
+            .call(FSMkdirInput { path: path.to_string_lossy().to_string() })
+            .await
+            .unwrap();
+
+        assert!(result.contains("Successfully created directory"));
+        assert!(path.is_dir());
+    }
+
+    #[tokio::test]
+    async fn test_fs_mkdir_existing_directory() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = FSMkdir::new(TempDir::guard())
+            .call(FSMkdirInput { path: temp_dir.path().to_string_lossy().to_string() })
+            .await
+            .unwrap();
+
+        assert!(result.contains("Directory already exists"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_mkdir_existing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("file.txt");
+        fs::write(&path, "content").await.unwrap();
+
+        let result = FSMkdir::new(TempDir::guard())
+            .call(FSMkdirInput { path: path.to_string_lossy().to_string() })
+            .await;
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("is not a directory"));
+    }
+}
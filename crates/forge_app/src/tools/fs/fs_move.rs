@@ -0,0 +1,207 @@
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct FSMoveInput {
+    /// The path of the file or directory to move (absolute path required)
+    pub source: String,
+    /// The path to move it to, including its new name (absolute path
+    /// required). It must not exist yet.
+    pub destination: String,
+}
+
+/// Moves or renames a file or a directory, creating the missing parent
+/// directories of the destination. Both paths must be absolute and the
+/// destination must not exist. Use it instead of running `mv` in the shell, so
+/// that the move can be undone.
+#[derive(ToolDescription)]
+pub struct FSMove {
+    guard: PathGuard,
+}
+
+impl FSMove {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for FSMove {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_fs_move")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for FSMove {
+    type Input = FSMoveInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let source = self.guard.resolve(&input.source)?;
+        let destination = self.guard.resolve(&input.destination)?;
+
+        if !source.exists() {
+            bail!("File not found: {}", input.source);
+        }
+        if source == self.guard.cwd() {
+            bail!("The working directory can't be moved");
+        }
+        if destination.exists() {
+            bail!("Destination already exists: {}", input.destination);
+        }
+        if destination.starts_with(&source) {
+            bail!("A directory can't be moved into itself: {}", input.source);
+        }
+
+        move_path(&source, &destination)
+            .await
+            .with_context(|| format!("Failed to move {} to {}", input.source, input.destination))?;
+
+        Ok(format!(
+            "Successfully moved {} to {}",
+            input.source, input.destination
+        ))
+    }
+}
+
+/// Moves a file or a directory, creating the parent directories of `to`. It's
+/// copied and then removed when it can't be renamed, eg: across file systems.
+pub(crate) async fn move_path(from: &Path, to: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = to.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    if tokio::fs::rename(from, to).await.is_ok() {
+        return Ok(());
+    }
+    let (from, to) = (from.to_path_buf(), to.to_path_buf());
+    tokio::task::spawn_blocking(move || {
+        copy_all(&from, &to)?;
+        if from.is_dir() {
+            std::fs::remove_dir_all(&from)
+        } else {
+            std::fs::remove_file(&from)
+        }
+    })
+    .await??;
+    Ok(())
+}
+
+fn copy_all(from: &Path, to: &Path) -> std::io::Result<()> {
+    if !from.is_dir() {
+        return std::fs::copy(from, to).map(|_| ());
+    }
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        copy_all(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+    use tokio::fs;
+
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    fn input(source: &Path, destination: &Path) -> FSMoveInput {
+        FSMoveInput {
+            source: source.to_string_lossy().to_string(),
+            destination: destination.to_string_lossy().to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fs_move_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("old.txt");
+        let destination = temp_dir.path().join("nested/new.txt");
+        fs::write(&source, "content").await.unwrap();
+
+        let result = FSMove::new(TempDir::guard())
+            .call(input(&source, &destination))
+            .await
+            .unwrap();
+
+        assert!(result.contains("Successfully moved"));
+        assert!(!source.exists());
+        assert_eq!(fs::read_to_string(&destination).await.unwrap(), "content");
+    }
+
+    #[tokio::test]
+    async fn test_fs_move_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("src/old");
+        let destination = temp_dir.path().join("src/new");
+        fs::create_dir_all(&source).await.unwrap();
+        fs::write(source.join("lib.rs"), "mod a;").await.unwrap();
+
+        FSMove::new(TempDir::guard())
+            .call(input(&source, &destination))
+            .await
+            .unwrap();
+
+        assert!(!source.exists());
+        assert_eq!(
+            fs::read_to_string(destination.join("lib.rs"))
+                .await
+                .unwrap(),
+            "mod a;"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fs_move_existing_destination() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("a.txt");
+        let destination = temp_dir.path().join("b.txt");
+        fs::write(&source, "a").await.unwrap();
+        fs::write(&destination, "b").await.unwrap();
+
+        let result = FSMove::new(TempDir::guard())
+            .call(input(&source, &destination))
+            .await;
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Destination already exists"));
+        assert_eq!(fs::read_to_string(&destination).await.unwrap(), "b");
+    }
+
+    #[tokio::test]
+    async fn test_fs_move_into_itself() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("dir");
+        fs::create_dir(&source).await.unwrap();
+
+        let result = FSMove::new(TempDir::guard())
+            .call(input(&source, &source.join("inner")))
+            .await;
+
+        assert!(result.unwrap_err().to_string().contains("into itself"));
+    }
+
+    #[tokio::test]
+    async fn test_copy_all() {
+        let temp_dir = TempDir::new().unwrap();
+        let from = temp_dir.path().join("from");
+        let to = temp_dir.path().join("to");
+        fs::create_dir_all(from.join("inner")).await.unwrap();
+        fs::write(from.join("inner/a.txt"), "a").await.unwrap();
+
+        copy_all(&from, &to).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(to.join("inner/a.txt")).await.unwrap(),
+            "a"
+        );
+        assert!(from.exists());
+    }
+}
@@ -1,27 +1,51 @@
-use std::path::Path;
-
-use anyhow::Context;
-use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use anyhow::{bail, Context};
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
 use forge_tool_macros::ToolDescription;
 use schemars::JsonSchema;
 use serde::Deserialize;
 
-use crate::tools::utils::assert_absolute_path;
+use super::content_kind::ContentKind;
+use super::{Encoding, Notebook, TextFormat};
+
+/// Bytes of content returned when `max_bytes` isn't set, about 10k tokens.
+const DEFAULT_MAX_BYTES: usize = 40_000;
 
-#[derive(Deserialize, JsonSchema)]
+#[derive(Default, Deserialize, JsonSchema)]
 pub struct FSReadInput {
     /// The path of the file to read, always provide absolute paths.
     pub path: String,
+    /// The line to start reading from, 1-based. Defaults to the first line.
+    pub start_line: Option<usize>,
+    /// The last line to read, inclusive. Defaults to the last line of the
+    /// file.
+    pub end_line: Option<usize>,
+    /// Maximum bytes of content to return, cut after the last line that fits.
+    /// Defaults to 40000.
+    pub max_bytes: Option<usize>,
+    /// Reads the file even when it's binary, minified or a big lockfile.
+    pub force: Option<bool>,
 }
 
 /// Request to read the contents of a file at the specified path. Use this when
 /// you need to examine the contents of an existing file you do not know the
 /// contents of, for example to analyze code, review text files, or extract
-/// information from configuration files. Automatically extracts raw text from
-/// PDF and DOCX files. May not be suitable for other types of binary files, as
-/// it returns the raw content as a string.
+/// information from configuration files. The content of binary files, minified
+/// files and big lockfiles is skipped unless forced. Returns the content in a
+/// `<file>` tag with the size, total lines and lines shown. At most `max_bytes`
+/// are returned, so read big files in ranges with `start_line` and `end_line`,
+/// continuing after the last line shown. Jupyter notebooks are shown as their
+/// cells, with their index, type and source but not their outputs, unless
+/// forced.
 #[derive(ToolDescription)]
-pub struct FSRead;
+pub struct FSRead {
+    guard: PathGuard,
+}
+
+impl FSRead {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
 
 impl NamedTool for FSRead {
     fn tool_name() -> ToolName {
@@ -34,13 +58,124 @@ impl ExecutableTool for FSRead {
     type Input = FSReadInput;
 
     async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
-        let path = Path::new(&input.path);
-        assert_absolute_path(path)?;
+        let path = &self.guard.resolve(&input.path)?;
 
-        tokio::fs::read_to_string(path)
+        let content = tokio::fs::read(path)
             .await
-            .with_context(|| format!("Failed to read file content from {}", input.path))
+            .with_context(|| format!("Failed to read file content from {}", input.path))?;
+        let (format, text) = TextFormat::decode(&content);
+        // note: UTF-16 text is full of NUL bytes, it's detected on its decoded
+        // content instead.
+        let content = match format.encoding {
+            Encoding::Utf16Le | Encoding::Utf16Be => text.as_bytes(),
+            Encoding::Utf8 | Encoding::Latin1 => &content,
+        };
+        if !input.force.unwrap_or(false) {
+            // note: a notebook that can't be parsed is read as it is, so that
+            // the agent can see what's wrong with it.
+            let notebook = Notebook::is_notebook(path)
+                .then(|| Notebook::parse(&text).ok())
+                .flatten();
+            if let Some(notebook) = notebook {
+                let mut output = read_range(&notebook.view(), &input)?;
+                output.push_str(
+                    "\nThe notebook is shown as its cells, edit them with the `cell` of tool_forge_fs_patch.",
+                );
+                return Ok(output);
+            }
+            if let Some(kind) = ContentKind::detect(path, content) {
+                return Ok(skipped(&input.path, content, kind));
+            }
+        }
+        read_range(&text, &input)
+    }
+}
+
+/// Summary of a file whose content is skipped.
+fn skipped(path: &str, content: &[u8], kind: ContentKind) -> String {
+    let mut output = format!(
+        r#"<file path="{path}" size="{}" kind="{kind}""#,
+        content.len()
+    );
+    if kind != ContentKind::Binary {
+        let lines = content.split_inclusive(|byte| *byte == b'\n').count();
+        output.push_str(&format!(r#" total_lines="{lines}""#));
+    }
+    output.push_str(&format!(
+        " />\nThe content of this {kind} file was skipped, set force to true to read it anyway."
+    ));
+    output
+}
+
+/// Formats the range of lines of `content` selected by the input, cut to
+/// `max_bytes`, with a note on how to continue when it's cut.
+fn read_range(content: &str, input: &FSReadInput) -> anyhow::Result<String> {
+    let lines = content.split_inclusive('\n').collect::<Vec<_>>();
+    let total = lines.len();
+    let start = input.start_line.unwrap_or(1);
+    if start == 0 {
+        bail!("start_line is 1-based, use 1 for the first line");
+    }
+    if start > total.max(1) {
+        bail!("start_line {start} is past the end of the file, which has {total} lines");
+    }
+    if input.end_line.is_some_and(|end| end < start) {
+        bail!("end_line must not be before start_line");
+    }
+    let end = input.end_line.unwrap_or(total).min(total);
+    let max_bytes = input.max_bytes.unwrap_or(DEFAULT_MAX_BYTES);
+
+    let mut body = String::new();
+    let mut last = start - 1;
+    for line in &lines[start - 1..end] {
+        if body.len() + line.len() > max_bytes {
+            break;
+        }
+        body.push_str(line);
+        last += 1;
+    }
+
+    let mut note = None;
+    if last < end {
+        if last < start {
+            // note: a line longer than `max_bytes` is cut, eg: minified code.
+            let line = lines[start - 1];
+            let cut = (0..=max_bytes)
+                .rev()
+                .find(|index| line.is_char_boundary(*index))
+                .unwrap_or_default();
+            body.push_str(&line[..cut]);
+            last = start;
+            note = Some(format!(
+                "Line {start} is longer than {max_bytes} bytes, only its first {cut} bytes are shown. Increase max_bytes to read it whole."
+            ));
+        } else {
+            note = Some(format!(
+                "Showing lines {start}-{last} of {total}, the range is longer than {max_bytes} bytes. Continue with start_line={}.",
+                last + 1
+            ));
+        }
+    }
+
+    let mut output = format!(
+        r#"<file path="{}" size="{}" total_lines="{total}""#,
+        input.path,
+        content.len()
+    );
+    if last >= start {
+        output.push_str(&format!(r#" lines="{start}-{last}""#));
+    }
+    output.push_str(">\n");
+    output.push_str(&body);
+    if !body.is_empty() && !body.ends_with('\n') {
+        output.push('\n');
+    }
+    output.push_str("</file>");
+    if let Some(note) = note {
+        output.push('\n');
+        output.push_str(&note);
     }
+    Ok(output)
 }
 
 #[cfg(test)]
@@ -59,13 +194,20 @@ mod test {
         let test_content = "Hello, World!";
         fs::write(&file_path, test_content).await.unwrap();
 
-        let fs_read = FSRead;
+        let fs_read = FSRead::new(TempDir::guard());
         let result = fs_read
-            .call(FSReadInput { path: file_path.to_string_lossy().to_string() })
+            .call(FSReadInput {
+                path: file_path.to_string_lossy().to_string(),
+                ..Default::default()
+            })
             .await
             .unwrap();
 
-        assert_eq!(result, test_content);
+        let expected = format!(
+            "<file path=\"{}\" size=\"13\" total_lines=\"1\" lines=\"1-1\">\nHello, World!\n</file>",
+            file_path.display()
+        );
+        assert_eq!(result, expected);
     }
 
     #[tokio::test]
@@ -73,9 +215,12 @@ mod test {
         let temp_dir = TempDir::new().unwrap();
         let nonexistent_file = temp_dir.path().join("nonexistent.txt");
 
-        let fs_read = FSRead;
+        let fs_read = FSRead::new(TempDir::guard());
         let result = fs_read
-            .call(FSReadInput { path: nonexistent_file.to_string_lossy().to_string() })
+            .call(FSReadInput {
+                path: nonexistent_file.to_string_lossy().to_string(),
+                ..Default::default()
+            })
             .await;
 
         assert!(result.is_err());
@@ -87,25 +232,32 @@ mod test {
         let file_path = temp_dir.path().join("empty.txt");
         fs::write(&file_path, "").await.unwrap();
 
-        let fs_read = FSRead;
+        let fs_read = FSRead::new(TempDir::guard());
         let result = fs_read
-            .call(FSReadInput { path: file_path.to_string_lossy().to_string() })
+            .call(FSReadInput {
+                path: file_path.to_string_lossy().to_string(),
+                ..Default::default()
+            })
             .await
             .unwrap();
 
-        assert_eq!(result, "");
+        let expected = format!(
+            "<file path=\"{}\" size=\"0\" total_lines=\"0\">\n</file>",
+            file_path.display()
+        );
+        assert_eq!(result, expected);
     }
 
     #[test]
     fn test_description() {
-        assert!(FSRead.description().len() > 100)
+        assert!(FSRead::new(TempDir::guard()).description().len() > 100)
     }
 
     #[tokio::test]
     async fn test_fs_read_relative_path() {
-        let fs_read = FSRead;
+        let fs_read = FSRead::new(TempDir::guard());
         let result = fs_read
-            .call(FSReadInput { path: "relative/path.txt".to_string() })
+            .call(FSReadInput { path: "relative/path.txt".to_string(), ..Default::default() })
             .await;
 
         assert!(result.is_err());
@@ -114,4 +266,189 @@ mod test {
             .to_string()
             .contains("Path must be absolute"));
     }
+
+    #[tokio::test]
+    async fn test_fs_read_outside_cwd() {
+        let cwd = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let file_path = outside.path().join("secret.txt");
+        fs::write(&file_path, "secret").await.unwrap();
+
+        let fs_read = FSRead::new(PathGuard::new(cwd.path()));
+        let result = fs_read
+            .call(FSReadInput {
+                path: cwd
+                    .path()
+                    .join("..")
+                    .join(outside.path().file_name().unwrap())
+                    .join("secret.txt")
+                    .to_string_lossy()
+                    .to_string(),
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.unwrap_err().to_string().starts_with("Access denied"));
+    }
+
+    fn fixture(
+        start_line: Option<usize>,
+        end_line: Option<usize>,
+        max_bytes: Option<usize>,
+    ) -> FSReadInput {
+        FSReadInput {
+            path: "/test.txt".to_string(),
+            start_line,
+            end_line,
+            max_bytes,
+            force: None,
+        }
+    }
+
+    #[test]
+    fn test_read_range() {
+        let content = "one\ntwo\nthree\nfour\n";
+
+        let actual = read_range(content, &fixture(Some(2), Some(3), None)).unwrap();
+        let expected = "<file path=\"/test.txt\" size=\"19\" total_lines=\"4\" lines=\"2-3\">\ntwo\nthree\n</file>";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_read_range_end_past_the_end() {
+        let actual = read_range("one\ntwo", &fixture(Some(2), Some(10), None)).unwrap();
+        let expected =
+            "<file path=\"/test.txt\" size=\"7\" total_lines=\"2\" lines=\"2-2\">\ntwo\n</file>";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_read_range_max_bytes() {
+        let content = "one\ntwo\nthree\nfour\n";
+
+        let actual = read_range(content, &fixture(None, None, Some(10))).unwrap();
+        let expected = "<file path=\"/test.txt\" size=\"19\" total_lines=\"4\" lines=\"1-2\">\none\ntwo\n</file>\nShowing lines 1-2 of 4, the range is longer than 10 bytes. Continue with start_line=3.";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_read_range_long_line() {
+        let actual = read_range("héllo world\nbye\n", &fixture(None, None, Some(2))).unwrap();
+        let expected = "<file path=\"/test.txt\" size=\"17\" total_lines=\"2\" lines=\"1-1\">\nh\n</file>\nLine 1 is longer than 2 bytes, only its first 1 bytes are shown. Increase max_bytes to read it whole.";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_read_range_invalid() {
+        let content = "one\ntwo\n";
+
+        let actual = [
+            fixture(Some(0), None, None),
+            fixture(Some(3), None, None),
+            fixture(Some(2), Some(1), None),
+        ]
+        .map(|input| read_range(content, &input).unwrap_err().to_string());
+        let expected = [
+            "start_line is 1-based, use 1 for the first line",
+            "start_line 3 is past the end of the file, which has 2 lines",
+            "end_line must not be before start_line",
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_fs_read_binary_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("logo.png");
+        fs::write(&file_path, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR")
+            .await
+            .unwrap();
+
+        let fs_read = FSRead::new(TempDir::guard());
+        let actual = fs_read
+            .call(FSReadInput { path: file_path.display().to_string(), ..Default::default() })
+            .await
+            .unwrap();
+
+        let expected = format!(
+            "<file path=\"{}\" size=\"16\" kind=\"binary\" />\nThe content of this binary file was skipped, set force to true to read it anyway.",
+            file_path.display()
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_fs_read_lockfile_forced() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("Cargo.lock");
+        let content = "[[package]]\nname = \"anyhow\"\n".repeat(2000);
+        fs::write(&file_path, &content).await.unwrap();
+
+        let fs_read = FSRead::new(TempDir::guard());
+        let skipped = fs_read
+            .call(FSReadInput { path: file_path.display().to_string(), ..Default::default() })
+            .await
+            .unwrap();
+        let forced = fs_read
+            .call(FSReadInput {
+                path: file_path.display().to_string(),
+                end_line: Some(2),
+                force: Some(true),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert!(skipped.contains(r#"kind="lockfile" total_lines="4000""#));
+        assert!(forced.contains("[[package]]\nname = \"anyhow\"\n</file>"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_read_notebook() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("report.ipynb");
+        let content = r#"{"cells": [{"cell_type": "code", "metadata": {}, "outputs": [], "source": ["x = 1\n", "x"]}], "metadata": {}, "nbformat": 4, "nbformat_minor": 5}"#;
+        fs::write(&file_path, content).await.unwrap();
+
+        let fs_read = FSRead::new(TempDir::guard());
+        let actual = fs_read
+            .call(FSReadInput { path: file_path.display().to_string(), ..Default::default() })
+            .await
+            .unwrap();
+        let expected = format!(
+            "<file path=\"{}\" size=\"45\" total_lines=\"4\" lines=\"1-4\">\n<cell index=\"0\" type=\"code\">\nx = 1\nx\n</cell>\n</file>\nThe notebook is shown as its cells, edit them with the `cell` of tool_forge_fs_patch.",
+            file_path.display()
+        );
+        assert_eq!(actual, expected);
+
+        let forced = fs_read
+            .call(FSReadInput {
+                path: file_path.display().to_string(),
+                force: Some(true),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(forced.contains(content));
+    }
+
+    #[tokio::test]
+    async fn test_fs_read_utf16_crlf() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("notes.txt");
+        let mut content = vec![0xFF, 0xFE];
+        content.extend(
+            "first\r\nsecond\r\n"
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes),
+        );
+        fs::write(&file_path, &content).await.unwrap();
+
+        let actual = FSRead::new(TempDir::guard())
+            .call(FSReadInput { path: file_path.display().to_string(), ..Default::default() })
+            .await
+            .unwrap();
+
+        assert!(actual.contains("first\nsecond\n</file>"), "{actual}");
+    }
 }
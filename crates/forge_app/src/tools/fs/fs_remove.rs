@@ -1,24 +1,35 @@
-use std::path::Path;
+use std::path::PathBuf;
 
-use anyhow::Context;
-use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use anyhow::{bail, Context};
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
 use forge_tool_macros::ToolDescription;
 use schemars::JsonSchema;
 use serde::Deserialize;
 
-use crate::tools::utils::assert_absolute_path;
+use super::fs_move::move_path;
 
 #[derive(Deserialize, JsonSchema)]
 pub struct FSRemoveInput {
-    /// The path of the file to remove (absolute path required)
+    /// The path of the file or directory to remove (absolute path required)
     pub path: String,
 }
 
-/// Request to remove a file at the specified path. Use this when you need to
-/// delete an existing file. The path must be absolute. This operation cannot
-/// be undone, so use it carefully.
+/// Request to remove a file or a directory, along with its content, at the
+/// specified path. Use this when you need to delete existing files instead of
+/// running `rm` in the shell. The path must be absolute. The removed files are
+/// moved to the trash of forge, from which they can be recovered.
 #[derive(ToolDescription)]
-pub struct FSRemove;
+pub struct FSRemove {
+    guard: PathGuard,
+    /// Directory the removed files are moved to
+    trash: PathBuf,
+}
+
+impl FSRemove {
+    pub fn new(guard: PathGuard, trash: PathBuf) -> Self {
+        Self { guard, trash }
+    }
+}
 
 impl NamedTool for FSRemove {
     fn tool_name() -> ToolName {
@@ -31,31 +42,38 @@ impl ExecutableTool for FSRemove {
     type Input = FSRemoveInput;
 
     async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
-        let path = Path::new(&input.path);
-        assert_absolute_path(path)?;
+        let path = &self.guard.resolve(&input.path)?;
 
         // Check if the file exists
         if !path.exists() {
-            return Err(anyhow::anyhow!("File not found: {}", input.path));
+            bail!("File not found: {}", input.path);
         }
-
-        // Check if it's a file
-        if !path.is_file() {
-            return Err(anyhow::anyhow!("Path is not a file: {}", input.path));
+        if path == self.guard.cwd() {
+            bail!("The working directory can't be removed");
         }
 
-        // Remove the file
-        tokio::fs::remove_file(&input.path)
+        // note: every removal gets its own directory in the trash, so that
+        // removing files of the same name doesn't overwrite them.
+        let name = path.file_name().context("Path has no file name")?;
+        let trashed = self
+            .trash
+            .join(chrono::Local::now().format("%Y%m%d-%H%M%S%.3f").to_string())
+            .join(name);
+        move_path(path, &trashed)
             .await
-            .with_context(|| format!("Failed to remove file {}", input.path))?;
+            .with_context(|| format!("Failed to remove {}", input.path))?;
 
-        Ok(format!("Successfully removed file: {}", input.path))
+        Ok(format!(
+            "Successfully removed {}, it can be recovered from {}",
+            input.path,
+            trashed.display()
+        ))
     }
 }
 
 #[cfg(test)]
 mod test {
-
+    use pretty_assertions::assert_eq;
     use tokio::fs;
 
     use super::*;
@@ -70,7 +88,7 @@ mod test {
         fs::write(&file_path, "test content").await.unwrap();
         assert!(file_path.exists());
 
-        let fs_remove = FSRemove;
+        let fs_remove = FSRemove::new(TempDir::guard(), temp_dir.path().join(".trash"));
         let result = fs_remove
             .call(FSRemoveInput { path: file_path.to_string_lossy().to_string() })
             .await
@@ -85,7 +103,7 @@ mod test {
         let temp_dir = TempDir::new().unwrap();
         let nonexistent_file = temp_dir.path().join("nonexistent.txt");
 
-        let fs_remove = FSRemove;
+        let fs_remove = FSRemove::new(TempDir::guard(), temp_dir.path().join(".trash"));
         let result = fs_remove
             .call(FSRemoveInput { path: nonexistent_file.to_string_lossy().to_string() })
             .await;
@@ -101,24 +119,40 @@ mod test {
 
         // Create a test directory
         fs::create_dir(&dir_path).await.unwrap();
-        assert!(dir_path.exists());
+        fs::write(dir_path.join("a.txt"), "a").await.unwrap();
 
-        let fs_remove = FSRemove;
+        let fs_remove = FSRemove::new(TempDir::guard(), temp_dir.path().join(".trash"));
         let result = fs_remove
             .call(FSRemoveInput { path: dir_path.to_string_lossy().to_string() })
-            .await;
+            .await
+            .unwrap();
 
-        assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("Path is not a file"));
-        assert!(dir_path.exists());
+        assert!(result.contains("Successfully removed"));
+        assert!(!dir_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_fs_remove_moves_to_trash() {
+        let temp_dir = TempDir::new().unwrap();
+        let trash = temp_dir.path().join(".trash");
+        let file_path = temp_dir.path().join("test.txt");
+        fs::write(&file_path, "test content").await.unwrap();
+
+        let fs_remove = FSRemove::new(TempDir::guard(), trash.clone());
+        fs_remove
+            .call(FSRemoveInput { path: file_path.to_string_lossy().to_string() })
+            .await
+            .unwrap();
+
+        let mut removals = std::fs::read_dir(&trash).unwrap();
+        let trashed = removals.next().unwrap().unwrap().path().join("test.txt");
+        assert_eq!(fs::read_to_string(trashed).await.unwrap(), "test content");
     }
 
     #[tokio::test]
     async fn test_fs_remove_relative_path() {
-        let fs_remove = FSRemove;
+        let temp_dir = TempDir::new().unwrap();
+        let fs_remove = FSRemove::new(TempDir::guard(), temp_dir.path().join(".trash"));
         let result = fs_remove
             .call(FSRemoveInput { path: "relative/path.txt".to_string() })
             .await;
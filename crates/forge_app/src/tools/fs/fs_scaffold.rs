@@ -0,0 +1,233 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::tools::syn;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ScaffoldEntry {
+    /// The path of the file or directory to create (absolute path required)
+    pub path: String,
+    /// The complete content of the file. Omit it to create a directory
+    /// instead.
+    pub content: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct FSScaffoldInput {
+    /// The files and directories to create, parents are created as needed
+    pub entries: Vec<ScaffoldEntry>,
+}
+
+/// Creates a set of files and directories in one operation, eg: to bootstrap
+/// the structure of a new module or project instead of creating its files one
+/// by one. Each entry has an absolute path, and the content of the file unless
+/// it's a directory. Either every entry is created or none: when one fails,
+/// those created before it are removed. Existing files aren't overwritten,
+/// the operation fails instead.
+#[derive(ToolDescription)]
+pub struct FSScaffold {
+    guard: PathGuard,
+}
+
+impl FSScaffold {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for FSScaffold {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_fs_scaffold")
+    }
+}
+
+/// Paths created by the scaffold so far, in the order they were created.
+#[derive(Default)]
+struct Created(Vec<PathBuf>);
+
+impl Created {
+    /// Creates the directory and its missing parents, remembering each one.
+    async fn dir(&mut self, path: &Path) -> anyhow::Result<()> {
+        let missing = path
+            .ancestors()
+            .take_while(|ancestor| !ancestor.exists())
+            .collect::<Vec<_>>();
+        for dir in missing.into_iter().rev() {
+            tokio::fs::create_dir(dir)
+                .await
+                .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+            self.0.push(dir.to_path_buf());
+        }
+        Ok(())
+    }
+
+    async fn file(&mut self, path: &Path, content: &str) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            self.dir(parent).await?;
+        }
+        tokio::fs::write(path, content)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        self.0.push(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Removes everything that was created, the newest first.
+    async fn rollback(self) {
+        for path in self.0.into_iter().rev() {
+            let _ = if path.is_dir() {
+                tokio::fs::remove_dir(&path).await
+            } else {
+                tokio::fs::remove_file(&path).await
+            };
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for FSScaffold {
+    type Input = FSScaffoldInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        if input.entries.is_empty() {
+            bail!("No entries to create");
+        }
+
+        // note: every entry is checked before anything is created, so that the
+        // usual mistakes don't need a rollback.
+        let mut entries = Vec::new();
+        for entry in &input.entries {
+            let path = self.guard.resolve(&entry.path)?;
+            match &entry.content {
+                Some(_) if path.exists() => bail!("File already exists: {}", entry.path),
+                None if path.exists() && !path.is_dir() => {
+                    bail!("Path exists and is not a directory: {}", entry.path)
+                }
+                _ => entries.push((path, entry)),
+            }
+        }
+
+        let mut created = Created::default();
+        let mut warnings = Vec::new();
+        for (path, entry) in entries {
+            let result = match &entry.content {
+                Some(content) => {
+                    if let Some(warning) = syn::validate(&entry.path, content) {
+                        warnings.push(format!("Warning: {}: {warning}", entry.path));
+                    }
+                    created.file(&path, content).await
+                }
+                None => created.dir(&path).await,
+            };
+            if let Err(error) = result {
+                created.rollback().await;
+                bail!("{error:#}, nothing was created");
+            }
+        }
+
+        let mut result = format!(
+            "Successfully created {} entries:\n{}",
+            input.entries.len(),
+            input
+                .entries
+                .iter()
+                .map(|entry| format!("- {}", entry.path))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+        for warning in warnings {
+            result.push('\n');
+            result.push_str(&warning);
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+    use tokio::fs;
+
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    fn entry(path: &Path, content: Option<&str>) -> ScaffoldEntry {
+        ScaffoldEntry {
+            path: path.to_string_lossy().to_string(),
+            content: content.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fs_scaffold() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("billing");
+
+        let result = FSScaffold::new(TempDir::guard())
+            .call(FSScaffoldInput {
+                entries: vec![
+                    entry(&root.join("src/lib.rs"), Some("mod invoice;\n")),
+                    entry(&root.join("src/invoice.rs"), Some("pub struct Invoice;\n")),
+                    entry(&root.join("tests"), None),
+                ],
+            })
+            .await
+            .unwrap();
+
+        assert!(result.contains("Successfully created 3 entries"));
+        assert_eq!(
+            fs::read_to_string(root.join("src/lib.rs")).await.unwrap(),
+            "mod invoice;\n"
+        );
+        assert!(root.join("src/invoice.rs").is_file());
+        assert!(root.join("tests").is_dir());
+    }
+
+    #[tokio::test]
+    async fn test_fs_scaffold_existing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let existing = temp_dir.path().join("existing.txt");
+        let new = temp_dir.path().join("new/file.txt");
+        fs::write(&existing, "keep").await.unwrap();
+
+        let result = FSScaffold::new(TempDir::guard())
+            .call(FSScaffoldInput {
+                entries: vec![entry(&new, Some("new")), entry(&existing, Some("replaced"))],
+            })
+            .await;
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("File already exists"));
+        assert!(!temp_dir.path().join("new").exists());
+        assert_eq!(fs::read_to_string(&existing).await.unwrap(), "keep");
+    }
+
+    #[tokio::test]
+    async fn test_fs_scaffold_rolls_back_on_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("module");
+
+        // The second entry can't be created, as its parent is the first one
+        let result = FSScaffold::new(TempDir::guard())
+            .call(FSScaffoldInput {
+                entries: vec![
+                    entry(&root.join("mod.rs"), Some("")),
+                    entry(&root.join("mod.rs/inner.rs"), Some("")),
+                ],
+            })
+            .await;
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("nothing was created"));
+        assert!(!root.exists());
+    }
+}
@@ -1,14 +1,12 @@
-use std::path::Path;
-
 use anyhow::Context;
 use forge_display::DiffFormat;
-use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
 use forge_tool_macros::ToolDescription;
 use schemars::JsonSchema;
 use serde::Deserialize;
 
+use super::{Formatter, TextFormat};
 use crate::tools::syn;
-use crate::tools::utils::assert_absolute_path;
 
 #[derive(Deserialize, JsonSchema)]
 pub struct FSWriteInput {
@@ -23,6 +21,11 @@ pub struct FSWriteInput {
     /// existing file.
     #[serde(default)]
     pub overwrite: bool,
+    /// If set to true, nothing is written to disk and a unified diff of the
+    /// change that would be made is returned instead. Use it to review a
+    /// change before applying it.
+    #[serde(default)]
+    pub preview: bool,
 }
 
 /// Use it to create a new file at a specified path with the provided content.
@@ -30,9 +33,24 @@ pub struct FSWriteInput {
 /// automatically handles the creation of any missing intermediary directories
 /// in the specified path.
 /// IMPORTANT: DO NOT attempt to use this tool to move or rename files, use the
-/// shell tool instead.
+/// move tool instead.
 #[derive(ToolDescription)]
-pub struct FSWrite;
+pub struct FSWrite {
+    guard: PathGuard,
+    format_on_write: bool,
+}
+
+impl FSWrite {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard, format_on_write: false }
+    }
+
+    /// Formats the files written with the formatter of their project.
+    pub fn format_on_write(mut self, format_on_write: bool) -> Self {
+        self.format_on_write = format_on_write;
+        self
+    }
+}
 
 impl NamedTool for FSWrite {
     fn tool_name() -> ToolName {
@@ -46,60 +64,71 @@ impl ExecutableTool for FSWrite {
 
     async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
         // Validate absolute path requirement
-        let path = Path::new(&input.path);
-        assert_absolute_path(path)?;
+        let path = &self.guard.resolve(&input.path)?;
 
         // Validate file content if it's a supported language file
         let syntax_warning = syn::validate(&input.path, &input.content);
 
-        // Create parent directories if they don't exist
-        if let Some(parent) = Path::new(&input.path).parent() {
-            tokio::fs::create_dir_all(parent)
-                .await
-                .with_context(|| format!("Failed to create directories: {}", input.path))?;
-        }
-
         // Check if the file exists
         let file_exists = path.is_file();
 
+        // record the file content before they're modified. An existing file
+        // keeps its encoding and line endings.
+        let (format, old_content) = if file_exists {
+            TextFormat::read(path).await?
+        } else {
+            (TextFormat::default(), String::new())
+        };
+
         // If file exists and overwrite flag is not set, return an error with the
         // existing content
         if file_exists && !input.overwrite {
-            let existing_content = tokio::fs::read_to_string(path).await?;
             return Err(anyhow::anyhow!(
                 "File already exists at {}. If you need to overwrite it, set overwrite to true.\n\nExisting content:\n{}",
                 input.path,
-                existing_content
+                old_content
             ));
         }
 
-        // record the file content before they're modified
-        let old_content = if file_exists {
-            // if file already exists, we should be able to read it.
-            tokio::fs::read_to_string(path).await?
-        } else {
-            // if file doesn't exist, we should record it as an empty string.
-            "".to_string()
-        };
+        if input.preview {
+            let mut result = DiffFormat::unified(&input.path, &old_content, &input.content);
+            if let Some(warning) = syntax_warning {
+                result.push_str("\nWarning: ");
+                result.push_str(&warning.to_string());
+            }
+            return Ok(result);
+        }
+
+        // Create parent directories if they don't exist
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("Failed to create directories: {}", input.path))?;
+        }
 
         // Write file only after validation passes and directories are created
-        tokio::fs::write(&input.path, &input.content).await?;
+        format.write(path, &input.content).await?;
+        let formatter = if self.format_on_write {
+            Formatter::format(path).await
+        } else {
+            None
+        };
 
         let mut result = format!(
             "Successfully wrote {} bytes to {}",
             input.content.len(),
             input.path
         );
+        if let Some(formatter) = formatter {
+            result.push_str(&format!(
+                "\nThe file was formatted with {formatter}, read it before patching it."
+            ));
+        }
         if let Some(warning) = syntax_warning {
             result.push_str("\nWarning: ");
             result.push_str(&warning.to_string());
         }
 
-        // record the file content after they're modified
-        let new_content = tokio::fs::read_to_string(path).await?;
-        let diff = DiffFormat::format(path.to_path_buf(), &old_content, &new_content);
-        println!("{}", diff);
-
         Ok(result)
     }
 }
@@ -124,12 +153,13 @@ mod test {
         let file_path = temp_dir.path().join("test.txt");
         let content = "Hello, World!";
 
-        let fs_write = FSWrite;
+        let fs_write = FSWrite::new(TempDir::guard());
         let output = fs_write
             .call(FSWriteInput {
                 path: file_path.to_string_lossy().to_string(),
                 content: content.to_string(),
                 overwrite: false,
+                preview: false,
             })
             .await
             .unwrap();
@@ -148,12 +178,13 @@ mod test {
         let temp_dir = TempDir::new().unwrap();
         let file_path = temp_dir.path().join("test.rs");
 
-        let fs_write = FSWrite;
+        let fs_write = FSWrite::new(TempDir::guard());
         let result = fs_write
             .call(FSWriteInput {
                 path: file_path.to_string_lossy().to_string(),
                 content: "fn main() { let x = ".to_string(),
                 overwrite: false,
+                preview: false,
             })
             .await;
 
@@ -166,13 +197,14 @@ mod test {
         let temp_dir = TempDir::new().unwrap();
         let file_path = temp_dir.path().join("test.rs");
 
-        let fs_write = FSWrite;
+        let fs_write = FSWrite::new(TempDir::guard());
         let content = "fn main() { let x = 42; }";
         let result = fs_write
             .call(FSWriteInput {
                 path: file_path.to_string_lossy().to_string(),
                 content: content.to_string(),
                 overwrite: false,
+                preview: false,
             })
             .await;
 
@@ -192,12 +224,13 @@ mod test {
         let nested_path = temp_dir.path().join("new_dir").join("test.txt");
         let content = "Hello from nested file!";
 
-        let fs_write = FSWrite;
+        let fs_write = FSWrite::new(TempDir::guard());
         let result = fs_write
             .call(FSWriteInput {
                 path: nested_path.to_string_lossy().to_string(),
                 content: content.to_string(),
                 overwrite: false,
+                preview: false,
             })
             .await
             .unwrap();
@@ -223,12 +256,13 @@ mod test {
             .join("deep.txt");
         let content = "Deep in the directory structure";
 
-        let fs_write = FSWrite;
+        let fs_write = FSWrite::new(TempDir::guard());
         let result = fs_write
             .call(FSWriteInput {
                 path: deep_path.to_string_lossy().to_string(),
                 content: content.to_string(),
                 overwrite: false,
+                preview: false,
             })
             .await
             .unwrap();
@@ -256,12 +290,13 @@ mod test {
         let path_str = format!("{}/dir_a/dir_b/file.txt", temp_dir.path().to_string_lossy());
         let content = "Testing path separators";
 
-        let fs_write = FSWrite;
+        let fs_write = FSWrite::new(TempDir::guard());
         let result = fs_write
             .call(FSWriteInput {
                 path: path_str,
                 content: content.to_string(),
                 overwrite: false,
+                preview: false,
             })
             .await
             .unwrap();
@@ -283,12 +318,13 @@ mod test {
 
     #[tokio::test]
     async fn test_fs_write_relative_path() {
-        let fs_write = FSWrite;
+        let fs_write = FSWrite::new(TempDir::guard());
         let result = fs_write
             .call(FSWriteInput {
                 path: "relative/path/file.txt".to_string(),
                 content: "test content".to_string(),
                 overwrite: false,
+                preview: false,
             })
             .await;
 
@@ -309,12 +345,13 @@ mod test {
         fs::write(&file_path, original_content).await.unwrap();
 
         // Now attempt to write without overwrite flag
-        let fs_write = FSWrite;
+        let fs_write = FSWrite::new(TempDir::guard());
         let result = fs_write
             .call(FSWriteInput {
                 path: file_path.to_string_lossy().to_string(),
                 content: "New content".to_string(),
                 overwrite: false,
+                preview: false,
             })
             .await;
 
@@ -344,12 +381,13 @@ mod test {
         fs::write(&file_path, original_content).await.unwrap();
 
         // Now attempt to write with overwrite flag
-        let fs_write = FSWrite;
+        let fs_write = FSWrite::new(TempDir::guard());
         let result = fs_write
             .call(FSWriteInput {
                 path: file_path.to_string_lossy().to_string(),
                 content: new_content.to_string(),
                 overwrite: true,
+                preview: false,
             })
             .await;
 
@@ -364,4 +402,70 @@ mod test {
         let content = fs::read_to_string(&file_path).await.unwrap();
         assert_eq!(content, new_content);
     }
+
+    #[tokio::test]
+    async fn test_fs_write_preview() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("nested").join("test.txt");
+
+        let fs_write = FSWrite::new(TempDir::guard());
+        let actual = fs_write
+            .call(FSWriteInput {
+                path: file_path.to_string_lossy().to_string(),
+                content: "Hello\n".to_string(),
+                overwrite: false,
+                preview: true,
+            })
+            .await
+            .unwrap();
+
+        assert!(actual.contains("@@ -0,0 +1 @@\n+Hello\n"));
+
+        // Verify nothing was written
+        assert!(!temp_dir.path().join("nested").exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_fs_write_symlink_escape() {
+        let cwd = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::os::unix::fs::symlink(outside.path(), cwd.path().join("link")).unwrap();
+
+        let fs_write = FSWrite::new(PathGuard::new(cwd.path()));
+        let result = fs_write
+            .call(FSWriteInput {
+                path: cwd.path().join("link/test.txt").display().to_string(),
+                content: "Hello, World!".to_string(),
+                overwrite: false,
+                preview: false,
+            })
+            .await;
+
+        assert!(result.unwrap_err().to_string().starts_with("Access denied"));
+        assert!(!outside.path().join("test.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_fs_write_keeps_crlf_line_endings() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("build.bat");
+        fs::write(&file_path, "@echo off\r\ncall build\r\n")
+            .await
+            .unwrap();
+
+        let fs_write = FSWrite::new(TempDir::guard());
+        fs_write
+            .call(FSWriteInput {
+                path: file_path.to_string_lossy().to_string(),
+                content: "@echo off\ncall build --release\n".to_string(),
+                overwrite: true,
+                preview: false,
+            })
+            .await
+            .unwrap();
+
+        let content = fs::read(&file_path).await.unwrap();
+        assert_eq!(content, b"@echo off\r\ncall build --release\r\n");
+    }
 }
@@ -1,13 +1,26 @@
+mod content_kind;
 mod file_info;
+mod formatter;
 mod fs_find;
 mod fs_list;
+mod fs_mkdir;
+mod fs_move;
 mod fs_read;
 mod fs_remove;
+mod fs_scaffold;
 mod fs_write;
+mod notebook;
+mod text;
 
 pub use file_info::*;
+pub use formatter::*;
 pub use fs_find::*;
 pub use fs_list::*;
+pub use fs_mkdir::*;
+pub use fs_move::*;
 pub use fs_read::*;
 pub use fs_remove::*;
+pub use fs_scaffold::*;
 pub use fs_write::*;
+pub use notebook::*;
+pub use text::*;
@@ -0,0 +1,196 @@
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use serde::Serialize;
+use serde_json::Value;
+
+/// A Jupyter notebook, whose cells are shown and edited on their own rather
+/// than as the raw JSON of the file. The outputs and the metadata are kept as
+/// they are.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notebook {
+    json: Value,
+}
+
+impl Notebook {
+    /// Whether the file is a notebook, from its extension.
+    pub fn is_notebook(path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("ipynb"))
+    }
+
+    pub fn parse(content: &str) -> anyhow::Result<Self> {
+        let json: Value = serde_json::from_str(content).context("Invalid notebook JSON")?;
+        if !json.get("cells").is_some_and(Value::is_array) {
+            bail!("Invalid notebook, it has no cells");
+        }
+        Ok(Self { json })
+    }
+
+    fn cells(&self) -> &[Value] {
+        self.json["cells"].as_array().map_or(&[], Vec::as_slice)
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.cells().len()
+    }
+
+    fn cell(&self, index: usize) -> anyhow::Result<&Value> {
+        self.cells().get(index).with_context(|| {
+            format!(
+                "Cell {index} doesn't exist, the notebook has {} cells",
+                self.cell_count()
+            )
+        })
+    }
+
+    /// The source of the cell, which is stored either as a string or as its
+    /// lines.
+    pub fn source(&self, index: usize) -> anyhow::Result<String> {
+        self.cell(index).map(source)
+    }
+
+    /// Replaces the source of the cell, stored as its lines like Jupyter
+    /// does.
+    pub fn set_source(&mut self, index: usize, source: &str) -> anyhow::Result<()> {
+        self.cell(index)?;
+        let cell = &mut self.json["cells"][index];
+        let lines = source
+            .split_inclusive('\n')
+            .map(|line| Value::String(line.to_string()))
+            .collect();
+        cell["source"] = Value::Array(lines);
+        Ok(())
+    }
+
+    /// The cells with their index, type and source, leaving out their outputs.
+    pub fn view(&self) -> String {
+        let mut view = String::new();
+        for (index, cell) in self.cells().iter().enumerate() {
+            let outputs = cell["outputs"].as_array().map_or(0, Vec::len);
+            view.push_str(&format!(
+                r#"<cell index="{index}" type="{}""#,
+                cell["cell_type"].as_str().unwrap_or("unknown")
+            ));
+            if outputs > 0 {
+                view.push_str(&format!(r#" outputs="{outputs}""#));
+            }
+            view.push_str(">\n");
+            let source = source(cell);
+            view.push_str(&source);
+            if !source.is_empty() && !source.ends_with('\n') {
+                view.push('\n');
+            }
+            view.push_str("</cell>\n");
+        }
+        view
+    }
+
+    /// The JSON of the notebook as Jupyter writes it, indented by one space.
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        let mut json = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+        let mut serializer = serde_json::Serializer::with_formatter(&mut json, formatter);
+        self.json.serialize(&mut serializer)?;
+        json.push(b'\n');
+        Ok(String::from_utf8(json)?)
+    }
+}
+
+fn source(cell: &Value) -> String {
+    match &cell["source"] {
+        Value::String(source) => source.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": [
+    "# Sales"
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {
+    "tags": [
+     "setup"
+    ]
+   },
+   "outputs": [
+    {
+     "name": "stdout",
+     "output_type": "stream",
+     "text": [
+      "loaded\n"
+     ]
+    }
+   ],
+   "source": "import pandas as pd\nprint('loaded')"
+  }
+ ],
+ "metadata": {
+  "kernelspec": {
+   "display_name": "Python 3",
+   "language": "python",
+   "name": "python3"
+  }
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##;
+
+    #[test]
+    fn test_view() {
+        let notebook = Notebook::parse(NOTEBOOK).unwrap();
+
+        let actual = notebook.view();
+        let expected = r##"<cell index="0" type="markdown">
+# Sales
+</cell>
+<cell index="1" type="code" outputs="1">
+import pandas as pd
+print('loaded')
+</cell>
+"##;
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_set_source_keeps_the_rest() {
+        let mut notebook = Notebook::parse(NOTEBOOK).unwrap();
+        notebook
+            .set_source(1, "import polars as pl\nprint('loaded')")
+            .unwrap();
+
+        let actual = notebook.to_json().unwrap();
+        let expected = NOTEBOOK.replace(
+            r#""source": "import pandas as pd\nprint('loaded')""#,
+            "\"source\": [\n    \"import polars as pl\\n\",\n    \"print('loaded')\"\n   ]",
+        );
+        assert_eq!(actual, expected);
+
+        let actual = notebook.set_source(2, "").unwrap_err().to_string();
+        assert_eq!(actual, "Cell 2 doesn't exist, the notebook has 2 cells");
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let actual = ["{", r#"{"metadata": {}}"#]
+            .map(|content| Notebook::parse(content).unwrap_err().to_string());
+        let expected = ["Invalid notebook JSON", "Invalid notebook, it has no cells"];
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,213 @@
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use derive_more::Display;
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Display)]
+pub enum Encoding {
+    #[default]
+    #[display("UTF-8")]
+    Utf8,
+    #[display("UTF-16LE")]
+    Utf16Le,
+    #[display("UTF-16BE")]
+    Utf16Be,
+    #[display("Latin-1")]
+    Latin1,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+/// How the text of a file is stored, so that the tools editing it write it
+/// back the same way instead of silently converting it to UTF-8 with LF line
+/// endings.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TextFormat {
+    pub encoding: Encoding,
+    /// Whether the file starts with a byte order mark
+    pub bom: bool,
+    pub line_ending: LineEnding,
+}
+
+impl TextFormat {
+    /// Detects the format of the content and decodes it. The text of a file
+    /// with CRLF line endings is given with LF ones, so that the agents can
+    /// match it.
+    pub fn decode(bytes: &[u8]) -> (Self, String) {
+        let (encoding, bom) = if bytes.starts_with(UTF8_BOM) {
+            (Encoding::Utf8, true)
+        } else if bytes.starts_with(UTF16LE_BOM) {
+            (Encoding::Utf16Le, true)
+        } else if bytes.starts_with(UTF16BE_BOM) {
+            (Encoding::Utf16Be, true)
+        } else if std::str::from_utf8(bytes).is_ok() {
+            (Encoding::Utf8, false)
+        } else {
+            (Encoding::Latin1, false)
+        };
+        let bytes = if bom {
+            &bytes[encoding.bom().len()..]
+        } else {
+            bytes
+        };
+        let text = match encoding {
+            Encoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            Encoding::Utf16Le => utf16(bytes, u16::from_le_bytes),
+            Encoding::Utf16Be => utf16(bytes, u16::from_be_bytes),
+            Encoding::Latin1 => bytes.iter().map(|byte| *byte as char).collect(),
+        };
+
+        // note: the line ending of most lines wins, as files with mixed ones
+        // are mostly LF ones with a few stray CRLF.
+        let crlf = text.matches("\r\n").count();
+        let line_ending = if crlf > text.matches('\n').count() - crlf {
+            LineEnding::CrLf
+        } else {
+            LineEnding::Lf
+        };
+        let text = match line_ending {
+            LineEnding::CrLf => text.replace("\r\n", "\n"),
+            LineEnding::Lf => text,
+        };
+        (Self { encoding, bom, line_ending }, text)
+    }
+
+    /// Encodes the text in this format, failing when it has characters the
+    /// encoding can't represent.
+    pub fn encode(&self, text: &str) -> anyhow::Result<Vec<u8>> {
+        let text = match self.line_ending {
+            LineEnding::CrLf => text.replace("\r\n", "\n").replace('\n', "\r\n"),
+            LineEnding::Lf => text.to_string(),
+        };
+        let mut bytes = if self.bom {
+            self.encoding.bom().to_vec()
+        } else {
+            Vec::new()
+        };
+        match self.encoding {
+            Encoding::Utf8 => bytes.extend(text.as_bytes()),
+            Encoding::Utf16Le => bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes)),
+            Encoding::Utf16Be => bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes)),
+            Encoding::Latin1 => {
+                for c in text.chars() {
+                    let Ok(byte) = u8::try_from(c) else {
+                        bail!("The file is encoded in Latin-1, which can't represent '{c}'");
+                    };
+                    bytes.push(byte);
+                }
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Reads the file at `path` along with its format.
+    pub async fn read(path: &Path) -> anyhow::Result<(Self, String)> {
+        let bytes = tokio::fs::read(path)
+            .await
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(Self::decode(&bytes))
+    }
+
+    /// Writes the text to the file at `path` in this format.
+    pub async fn write(&self, path: &Path, text: &str) -> anyhow::Result<()> {
+        tokio::fs::write(path, self.encode(text)?)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+impl Encoding {
+    fn bom(&self) -> &'static [u8] {
+        match self {
+            Self::Utf8 => UTF8_BOM,
+            Self::Utf16Le => UTF16LE_BOM,
+            Self::Utf16Be => UTF16BE_BOM,
+            Self::Latin1 => &[],
+        }
+    }
+}
+
+fn utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let units = bytes
+        .chunks_exact(2)
+        .map(|pair| unit([pair[0], pair[1]]))
+        .collect::<Vec<_>>();
+    String::from_utf16_lossy(&units)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_crlf_round_trip() {
+        let fixture = b"[core]\r\n\tautocrlf = true\r\n";
+
+        let (format, text) = TextFormat::decode(fixture);
+
+        assert_eq!(format.line_ending, LineEnding::CrLf);
+        assert_eq!(text, "[core]\n\tautocrlf = true\n");
+        let actual = format.encode("[core]\n\tautocrlf = false\n").unwrap();
+        assert_eq!(actual, b"[core]\r\n\tautocrlf = false\r\n");
+    }
+
+    #[test]
+    fn test_mostly_lf() {
+        let fixture = b"a\nb\nc\r\n";
+
+        let (format, text) = TextFormat::decode(fixture);
+
+        assert_eq!(format, TextFormat::default());
+        assert_eq!(text, "a\nb\nc\r\n");
+    }
+
+    #[test]
+    fn test_utf16_with_bom() {
+        let fixture = [0xFF, 0xFE, b'h', 0, 0xE9, 0, b'\r', 0, b'\n', 0];
+
+        let (format, text) = TextFormat::decode(&fixture);
+
+        let expected = TextFormat {
+            encoding: Encoding::Utf16Le,
+            bom: true,
+            line_ending: LineEnding::CrLf,
+        };
+        assert_eq!(format, expected);
+        assert_eq!(text, "hé\n");
+        assert_eq!(format.encode(&text).unwrap(), fixture);
+    }
+
+    #[test]
+    fn test_utf8_bom() {
+        let fixture = b"\xEF\xBB\xBFname,value\n";
+
+        let (format, text) = TextFormat::decode(fixture);
+
+        assert!(format.bom);
+        assert_eq!(text, "name,value\n");
+        assert_eq!(format.encode(&text).unwrap(), fixture);
+    }
+
+    #[test]
+    fn test_latin1() {
+        let fixture = b"caf\xE9\n";
+
+        let (format, text) = TextFormat::decode(fixture);
+
+        assert_eq!(format.encoding, Encoding::Latin1);
+        assert_eq!(text, "café\n");
+        assert_eq!(format.encode(&text).unwrap(), fixture);
+        assert!(format.encode("€").is_err());
+    }
+}
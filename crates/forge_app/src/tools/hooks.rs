@@ -0,0 +1,193 @@
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::Context as _;
+use forge_domain::{Environment, Hook, HookEvent, HookOutcome, HookService};
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::warn;
+
+/// Exit code with which a hook blocks the action.
+const BLOCK: i32 = 2;
+
+/// Seconds after which a hook is killed unless its timeout is set.
+const DEFAULT_TIMEOUT: u64 = 60;
+
+/// Runs the hooks of the config as commands of the shell of the platform,
+/// see [`forge_domain::HookEvent`] for the protocol.
+#[derive(Debug, Clone, Default)]
+pub struct ForgeHookService {
+    hooks: Vec<Hook>,
+    cwd: PathBuf,
+}
+
+impl ForgeHookService {
+    pub fn new(hooks: Vec<Hook>, cwd: PathBuf) -> Self {
+        Self { hooks, cwd }
+    }
+
+    pub fn from_env(env: &Environment) -> Self {
+        Self::new(
+            env.config.hooks.clone().unwrap_or_default(),
+            env.cwd.clone(),
+        )
+    }
+
+    async fn execute(&self, hook: &Hook, payload: &Value) -> anyhow::Result<std::process::Output> {
+        // note: the hooks are run by the shell of the platform, like the
+        // command tools.
+        let mut command = if cfg!(target_os = "windows") {
+            let mut command = Command::new("cmd");
+            command.args(["/C", &hook.cmd]);
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.args(["-c", &hook.cmd]);
+            command
+        };
+        let mut child = command
+            .current_dir(&self.cwd)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // note: a hook may exit without reading its stdin.
+            let _ = stdin.write_all(payload.to_string().as_bytes()).await;
+        }
+        let timeout = Duration::from_secs(hook.timeout.unwrap_or(DEFAULT_TIMEOUT));
+        tokio::time::timeout(timeout, child.wait_with_output())
+            .await
+            .with_context(|| format!("The hook timed out after {} seconds", timeout.as_secs()))?
+            .map_err(Into::into)
+    }
+}
+
+#[async_trait::async_trait]
+impl HookService for ForgeHookService {
+    async fn run(&self, event: HookEvent, target: Option<&str>, mut payload: Value) -> HookOutcome {
+        if let Value::Object(fields) = &mut payload {
+            fields.insert("event".to_string(), Value::String(event.to_string()));
+        }
+        for hook in self
+            .hooks
+            .iter()
+            .filter(|hook| event.matches(&hook.event, target))
+        {
+            let output = match self.execute(hook, &payload).await {
+                Ok(output) => output,
+                Err(error) => {
+                    warn!(hook = %hook.cmd, error = ?error, "Failed to run the hook");
+                    continue;
+                }
+            };
+            match output.status.code() {
+                Some(0) => {
+                    let stdout = String::from_utf8_lossy(&output.stdout);
+                    if let (Ok(Value::Object(changes)), Value::Object(fields)) =
+                        (serde_json::from_str(stdout.trim()), &mut payload)
+                    {
+                        fields.extend(changes);
+                    }
+                }
+                Some(BLOCK) => {
+                    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                    return HookOutcome::Block(if stderr.is_empty() {
+                        format!("Blocked by the hook `{}`", hook.cmd)
+                    } else {
+                        stderr
+                    });
+                }
+                code => {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    warn!(hook = %hook.cmd, code = ?code, stderr = %stderr, "The hook failed");
+                }
+            }
+        }
+        HookOutcome::Continue(payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    fn hooks(hooks: &[(&str, &str)]) -> ForgeHookService {
+        let hooks = hooks
+            .iter()
+            .map(|(event, cmd)| {
+                Hook::default()
+                    .event(event.to_string())
+                    .cmd(cmd.to_string())
+                    .timeout(1)
+            })
+            .collect();
+        ForgeHookService::new(hooks, std::env::temp_dir())
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn test_run_changes() {
+        let hooks = hooks(&[
+            (
+                "pre_tool:fs_create",
+                r#"echo '{"arguments": {"path": "b.txt"}}'"#,
+            ),
+            ("pre_tool", "cat >&2; echo 'not JSON'"),
+            ("post_tool", "exit 1"),
+        ]);
+        let actual = hooks
+            .run(
+                HookEvent::PreTool,
+                Some("tool_forge_fs_create"),
+                json!({"tool": "tool_forge_fs_create", "arguments": {"path": "a.txt"}}),
+            )
+            .await;
+        let expected = HookOutcome::Continue(json!({
+            "event": "pre_tool",
+            "tool": "tool_forge_fs_create",
+            "arguments": {"path": "b.txt"}
+        }));
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn test_run_block() {
+        let hooks = hooks(&[
+            ("pre_tool", "exit 1"),
+            ("pre_tool", "sleep 5"),
+            (
+                "pre_tool",
+                "grep -q .env && echo 'No editing .env' >&2 && exit 2",
+            ),
+            ("pre_tool", "exit 2"),
+        ]);
+        let actual = hooks
+            .run(
+                HookEvent::PreTool,
+                None,
+                json!({"arguments": {"path": ".env"}}),
+            )
+            .await;
+        assert_eq!(actual, HookOutcome::Block("No editing .env".to_string()));
+
+        let actual = hooks
+            .run(
+                HookEvent::PreTool,
+                None,
+                json!({"arguments": {"path": "a.txt"}}),
+            )
+            .await;
+        assert_eq!(
+            actual,
+            HookOutcome::Block("Blocked by the hook `exit 2`".to_string())
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use forge_lsp::SourceLocation;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct LspPositionInput {
+    /// The absolute path of the file containing the symbol.
+    pub path: String,
+    /// The line of the symbol (1-based).
+    pub line: u32,
+    /// The column of any character of the symbol (1-based).
+    pub column: u32,
+}
+
+/// Formats locations as `path:line:column` followed by the source line.
+pub fn format_locations(locations: &[SourceLocation]) -> String {
+    locations
+        .iter()
+        .map(|location| {
+            let source = std::fs::read_to_string(&location.path)
+                .ok()
+                .and_then(|text| {
+                    text.lines()
+                        .nth(location.line.saturating_sub(1) as usize)
+                        .map(|line| line.trim().to_string())
+                })
+                .unwrap_or_default();
+            format!(
+                "{}:{}:{}: {}",
+                location.path.display(),
+                location.line,
+                location.column,
+                source
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    #[test]
+    fn test_format_locations() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("main.rs");
+        std::fs::write(&path, "fn main() {\n    run();\n}\n").unwrap();
+        let fixture = vec![SourceLocation { path: path.clone(), line: 2, column: 5 }];
+
+        let actual = format_locations(&fixture);
+
+        let expected = format!("{}:2:5: run();", path.display());
+        assert_eq!(actual, expected);
+    }
+}
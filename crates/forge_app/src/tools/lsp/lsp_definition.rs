@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_lsp::LspManager;
+use forge_tool_macros::ToolDescription;
+
+use super::location::{format_locations, LspPositionInput};
+
+/// Uses the project's language server to find where the symbol at the given
+/// position is defined. Resolves imports, re-exports and overloads
+/// semantically, which makes it more precise than a text search. Supports
+/// Rust, TypeScript/JavaScript, Python, Go and C/C++ when the language server
+/// is installed.
+#[derive(ToolDescription)]
+pub struct LspDefinition {
+    manager: Arc<LspManager>,
+    guard: PathGuard,
+}
+
+impl LspDefinition {
+    pub fn new(manager: Arc<LspManager>, guard: PathGuard) -> Self {
+        Self { manager, guard }
+    }
+}
+
+impl NamedTool for LspDefinition {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_lsp_definition")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for LspDefinition {
+    type Input = LspPositionInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let path = &self.guard.resolve(&input.path)?;
+
+        let locations = self
+            .manager
+            .definition(path, input.line, input.column)
+            .await?;
+        if locations.is_empty() {
+            return Ok("No definition found".to_string());
+        }
+
+        Ok(format_locations(&locations))
+    }
+}
@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+use forge_lsp::LspManager;
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct LspDiagnosticsInput {
+    /// The absolute path of the file to check.
+    pub path: String,
+}
+
+/// Uses the project's language server to report the errors and warnings of a
+/// file, such as type errors or unresolved imports. Use it after editing a
+/// file to verify the change compiles. Supports Rust, TypeScript/JavaScript,
+/// Python, Go and C/C++ when the language server is installed.
+#[derive(ToolDescription)]
+pub struct LspDiagnostics {
+    manager: Arc<LspManager>,
+    guard: PathGuard,
+}
+
+impl LspDiagnostics {
+    pub fn new(manager: Arc<LspManager>, guard: PathGuard) -> Self {
+        Self { manager, guard }
+    }
+}
+
+impl NamedTool for LspDiagnostics {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_lsp_diagnostics")
+    }
+}
+
+fn format_diagnostic(diagnostic: &Diagnostic) -> String {
+    let severity = match diagnostic.severity {
+        Some(DiagnosticSeverity::ERROR) => "error",
+        Some(DiagnosticSeverity::WARNING) => "warning",
+        Some(DiagnosticSeverity::INFORMATION) => "info",
+        _ => "hint",
+    };
+    let source = diagnostic
+        .source
+        .as_ref()
+        .map(|source| format!(" [{}]", source))
+        .unwrap_or_default();
+
+    format!(
+        "{}:{}: {}{}: {}",
+        diagnostic.range.start.line + 1,
+        diagnostic.range.start.character + 1,
+        severity,
+        source,
+        diagnostic.message
+    )
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for LspDiagnostics {
+    type Input = LspDiagnosticsInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let path = &self.guard.resolve(&input.path)?;
+
+        let diagnostics = self.manager.diagnostics(path).await?;
+        if diagnostics.is_empty() {
+            return Ok(format!("No diagnostics reported for {}", input.path));
+        }
+
+        Ok(diagnostics
+            .iter()
+            .map(format_diagnostic)
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_lsp::lsp_types::{Position, Range};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_format_diagnostic() {
+        let fixture = Diagnostic {
+            range: Range {
+                start: Position { line: 3, character: 8 },
+                end: Position { line: 3, character: 12 },
+            },
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some("rustc".to_string()),
+            message: "cannot find value `foo` in this scope".to_string(),
+            ..Default::default()
+        };
+
+        let actual = format_diagnostic(&fixture);
+
+        let expected = "4:9: error [rustc]: cannot find value `foo` in this scope";
+        assert_eq!(actual, expected);
+    }
+}
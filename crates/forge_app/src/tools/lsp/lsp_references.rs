@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_lsp::LspManager;
+use forge_tool_macros::ToolDescription;
+
+use super::location::{format_locations, LspPositionInput};
+
+/// Uses the project's language server to find every reference to the symbol
+/// at the given position, including its declaration. Unlike a text search it
+/// ignores unrelated symbols that share the same name. Supports Rust,
+/// TypeScript/JavaScript, Python, Go and C/C++ when the language server is
+/// installed.
+#[derive(ToolDescription)]
+pub struct LspReferences {
+    manager: Arc<LspManager>,
+    guard: PathGuard,
+}
+
+impl LspReferences {
+    pub fn new(manager: Arc<LspManager>, guard: PathGuard) -> Self {
+        Self { manager, guard }
+    }
+}
+
+impl NamedTool for LspReferences {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_lsp_references")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for LspReferences {
+    type Input = LspPositionInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let path = &self.guard.resolve(&input.path)?;
+
+        let locations = self
+            .manager
+            .references(path, input.line, input.column)
+            .await?;
+        if locations.is_empty() {
+            return Ok("No references found".to_string());
+        }
+
+        Ok(format_locations(&locations))
+    }
+}
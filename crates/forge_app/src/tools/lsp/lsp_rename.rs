@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Context};
+use forge_display::DiffFormat;
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_lsp::{apply_text_edits, LspManager};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::tools::fs::{Formatter, TextFormat};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct LspRenameInput {
+    /// The absolute path of the file containing the symbol.
+    pub path: String,
+    /// The line of the symbol (1-based).
+    pub line: u32,
+    /// The column of any character of the symbol (1-based).
+    pub column: u32,
+    /// The new name of the symbol.
+    pub new_name: String,
+}
+
+/// Uses the project's language server to rename the symbol at the given
+/// position along with all of its references across the project, and writes
+/// the changes to disk, returning their diff. Safer than search and replace
+/// since only references to that exact symbol are changed. Supports Rust,
+/// TypeScript/JavaScript, Python, Go and C/C++ when the language server is
+/// installed.
+#[derive(ToolDescription)]
+pub struct LspRename {
+    manager: Arc<LspManager>,
+    guard: PathGuard,
+    format_on_write: bool,
+}
+
+impl LspRename {
+    pub fn new(manager: Arc<LspManager>, guard: PathGuard) -> Self {
+        Self { manager, guard, format_on_write: false }
+    }
+
+    /// Formats the files renamed in with the formatter of their project.
+    pub fn format_on_write(mut self, format_on_write: bool) -> Self {
+        self.format_on_write = format_on_write;
+        self
+    }
+}
+
+impl NamedTool for LspRename {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_lsp_rename")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for LspRename {
+    type Input = LspRenameInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let path = &self.guard.resolve(&input.path)?;
+
+        if input.new_name.trim().is_empty() {
+            bail!("New name can not be empty");
+        }
+
+        let edits = self
+            .manager
+            .rename(path, input.line, input.column, &input.new_name)
+            .await?;
+
+        // note: the whole rename is refused when a file is outside of the
+        // allowed paths or an edit doesn't apply, rather than leaving the
+        // project half renamed.
+        let mut changes = Vec::with_capacity(edits.len());
+        let mut diff = String::new();
+        for (path, edits) in edits {
+            let path = self.guard.resolve(&path).with_context(|| {
+                format!("The rename changes {}, nothing was renamed", path.display())
+            })?;
+            let (format, text) = TextFormat::read(&path).await?;
+            let failed = || {
+                format!(
+                    "Failed to rename in {}, nothing was renamed",
+                    path.display()
+                )
+            };
+            let renamed = apply_text_edits(&text, &edits).with_context(failed)?;
+            let content = format.encode(&renamed).with_context(failed)?;
+            diff.push_str(&DiffFormat::unified(
+                &path.display().to_string(),
+                &text,
+                &renamed,
+            ));
+            changes.push((path, content));
+        }
+
+        let mut formatted = Vec::new();
+        for (path, content) in &changes {
+            tokio::fs::write(path, content)
+                .await
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            if self.format_on_write && Formatter::format(path).await.is_some() {
+                formatted.push(path);
+            }
+            self.manager.sync(path).await?;
+        }
+
+        let files = changes
+            .iter()
+            .map(|(path, _)| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut result = format!(
+            "Renamed to '{}' in {} file(s):\n{}\n<diff>\n{}</diff>",
+            input.new_name,
+            changes.len(),
+            files,
+            diff
+        );
+        if !formatted.is_empty() {
+            result.push_str(&format!(
+                "\n{} file(s) were formatted, read them before patching them.",
+                formatted.len()
+            ));
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,10 @@
+mod location;
+mod lsp_definition;
+mod lsp_diagnostics;
+mod lsp_references;
+mod lsp_rename;
+
+pub use lsp_definition::*;
+pub use lsp_diagnostics::*;
+pub use lsp_references::*;
+pub use lsp_rename::*;
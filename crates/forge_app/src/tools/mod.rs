@@ -1,38 +1,185 @@
+mod bench;
+mod check;
+mod clipboard;
+mod code_query;
+mod command_tool;
+mod coverage;
+mod db;
+mod docker;
 mod fetch;
 mod fs;
+mod hooks;
+mod lsp;
+mod outline;
+mod package;
 mod patch;
+mod plugin;
+mod process;
+mod scm;
 mod shell;
 mod syn;
+mod test_runner;
 mod think;
+mod todos;
 mod utils;
 
 use std::sync::Arc;
 
+use bench::RunBench;
+use check::CheckProject;
+use clipboard::ClipboardCopy;
+use code_query::CodeQuery;
+use command_tool::command_tools;
+use coverage::TestCoverage;
+use db::{DbQuery, DbSchema};
+use docker::{ComposeDown, ComposeLogs, ComposeUp, DockerBuild, DockerRun};
 use fetch::Fetch;
-use forge_domain::Tool;
+use forge_domain::{PathGuard, Tool};
+use forge_lsp::LspManager;
+use forge_scm::{Scm, ScmTokens};
 use fs::*;
+pub use hooks::ForgeHookService;
+use lsp::{LspDefinition, LspDiagnostics, LspReferences, LspRename};
+use outline::Outline;
+pub use outline::{definitions, Definition};
+pub use package::PackageManager;
+use package::{PackageAdd, PackageList, PackageRemove, PackageUpgrade};
 use patch::*;
-use shell::Shell;
+use plugin::Capabilities;
+use process::{ProcessKill, ProcessLogs, ProcessRegistry, ProcessStart, ProcessStatus};
+use scm::{ScmIssue, ScmPullRequest, ScmReviewComment};
+pub use shell::Dialect;
+use shell::{Shell, ShellReset};
+pub use syn::grammars;
+use test_runner::RunTests;
 use think::Think;
+use todos::ScanTodos;
+#[cfg(test)]
+pub use utils::TempDir;
 
 use crate::{EnvironmentService, Infrastructure};
 
 pub fn tools<F: Infrastructure>(infra: Arc<F>) -> Vec<Tool> {
     let env = infra.environment_service().get_environment();
-    vec![
-        FSRead.into(),
-        FSWrite.into(),
-        FSRemove.into(),
-        FSList::default().into(),
-        FSSearch.into(),
-        FSFileInfo.into(),
+    let shell = Shell::new(env.clone());
+    let shell_reset = ShellReset::new(shell.sessions());
+    let processes = ProcessRegistry::default();
+    let lsp = Arc::new(LspManager::new(env.cwd.clone()));
+    let scm = Scm::new(
+        env.cwd.clone(),
+        ScmTokens {
+            github: env.github_token.clone(),
+            gitlab: env.gitlab_token.clone(),
+            bitbucket: env.bitbucket_token.clone(),
+        },
+    );
+    let databases = env.config.databases.clone().unwrap_or_default();
+    let guard = env
+        .config
+        .allowed_paths
+        .iter()
+        .flatten()
+        .fold(PathGuard::new(&env.cwd), |guard, path| {
+            guard.allow(env.cwd.join(path))
+        });
+    let plugins = if env.config.plugins.enabled != Some(false) {
+        let dir = env
+            .config
+            .plugins
+            .dir
+            .clone()
+            .unwrap_or_else(|| env.base_path.join("plugins"));
+        let capabilities = Capabilities {
+            guard: guard.clone(),
+            allowed_hosts: env.config.plugins.allowed_hosts.clone().unwrap_or_default(),
+        };
+        plugin::plugins(&dir, capabilities)
+    } else {
+        Vec::new()
+    };
+    let format_on_write = env.config.format_on_write != Some(false);
+    let mut tools: Vec<Tool> = vec![
+        FSRead::new(guard.clone()).into(),
+        FSWrite::new(guard.clone())
+            .format_on_write(format_on_write)
+            .into(),
+        FSRemove::new(guard.clone(), env.base_path.join("trash")).into(),
+        FSMove::new(guard.clone()).into(),
+        FSMkdir::new(guard.clone()).into(),
+        FSScaffold::new(guard.clone()).into(),
+        FSList::new(guard.clone()).into(),
+        FSSearch::new(guard.clone()).into(),
+        FSFileInfo::new(guard.clone()).into(),
+        CodeQuery::new(guard.clone()).into(),
+        Outline::new(guard.clone()).into(),
+        ScanTodos::new(guard.clone()).into(),
         // TODO: once ApplyPatchJson is stable we can delete ApplyPatch
-        // ApplyPatch.into(),
-        ApplyPatchJson.into(),
-        Shell::new(env.clone()).into(),
+        // ApplyPatch::new(guard.clone()).into(),
+        ApplyPatchJson::new(guard.clone())
+            .format_on_write(format_on_write)
+            .fuzzy_threshold(
+                env.config
+                    .fuzzy_threshold
+                    .unwrap_or(DEFAULT_FUZZY_THRESHOLD),
+            )
+            .into(),
+        shell.into(),
+        shell_reset.into(),
+        ProcessStart::new(&env.shell, processes.clone())
+            .restricted(env.config.restricted == Some(true))
+            .into(),
+        ProcessStatus::new(processes.clone()).into(),
+        ProcessLogs::new(processes.clone()).into(),
+        ProcessKill::new(processes).into(),
+        RunTests::new(guard.clone()).into(),
+        TestCoverage::new(guard.clone()).into(),
+        RunBench::new(guard.clone(), env.base_path.join("benchmarks")).into(),
+        CheckProject::new(guard.clone()).into(),
+        PackageAdd::new(guard.clone()).into(),
+        PackageRemove::new(guard.clone()).into(),
+        PackageUpgrade::new(guard.clone()).into(),
+        PackageList::new(guard.clone()).into(),
+        DbQuery::new(databases.clone()).into(),
+        DbSchema::new(databases).into(),
+        DockerBuild::new(guard.clone()).into(),
+        DockerRun::new(guard.clone()).into(),
+        ComposeUp::new(guard.clone()).into(),
+        ComposeDown::new(guard.clone()).into(),
+        ComposeLogs::new(guard.clone()).into(),
+        LspDefinition::new(lsp.clone(), guard.clone()).into(),
+        LspReferences::new(lsp.clone(), guard.clone()).into(),
+        LspDiagnostics::new(lsp.clone(), guard.clone()).into(),
+        LspRename::new(lsp, guard)
+            .format_on_write(format_on_write)
+            .into(),
         Think::default().into(),
         Fetch::default().into(),
-    ]
+        ClipboardCopy::default().into(),
+        ScmIssue::new(scm.clone()).into(),
+        ScmPullRequest::new(scm.clone()).into(),
+        ScmReviewComment::new(scm).into(),
+    ];
+    tools.extend(plugins);
+    tools.extend(command_tools(
+        env.config.tools.iter().flatten(),
+        env.cwd.clone(),
+    ));
+
+    // note: the tools are removed rather than refused, so that the agents
+    // don't even try to change anything.
+    if !env.trusted {
+        tools
+            .into_iter()
+            .filter(|tool| tool.definition.name.is_safe_untrusted())
+            .collect()
+    } else if env.config.read_only == Some(true) {
+        tools
+            .into_iter()
+            .filter(|tool| tool.definition.name.is_read_only())
+            .collect()
+    } else {
+        tools
+    }
 }
 
 #[cfg(test)]
@@ -56,6 +203,7 @@ mod tests {
                 } else {
                     "/bin/sh".to_string()
                 },
+                toolchain: Default::default(),
                 base_path: PathBuf::new(),
                 qdrant_key: Default::default(),
                 qdrant_cluster: Default::default(),
@@ -63,6 +211,14 @@ mod tests {
                 provider_url: Default::default(),
                 provider_key: Default::default(),
                 openai_key: Default::default(),
+                github_token: Default::default(),
+                gitlab_token: Default::default(),
+                bitbucket_token: Default::default(),
+                record_path: Default::default(),
+                replay_path: Default::default(),
+                trusted: true,
+                redaction_key: Default::default(),
+                config: Default::default(),
             },
         }
     }
@@ -157,4 +313,44 @@ mod tests {
             MAX_DESCRIPTION_LENGTH
         );
     }
+
+    #[test]
+    fn test_read_only_tools() {
+        let mut stub = stub();
+        stub.env.config = stub.env.config.read_only(true);
+
+        let actual = tools(Arc::new(stub))
+            .into_iter()
+            .map(|tool| tool.definition.name.into_string())
+            .collect::<Vec<_>>();
+
+        assert!(actual.contains(&"tool_forge_fs_read".to_string()));
+        assert!(!actual.contains(&"tool_forge_fs_create".to_string()));
+        assert!(!actual.contains(&"tool_forge_process_shell".to_string()));
+        assert!(actual.contains(&"tool_forge_lsp_references".to_string()));
+    }
+
+    #[test]
+    fn test_untrusted_tools() {
+        let mut stub = stub();
+        stub.env.trusted = false;
+
+        let actual = tools(Arc::new(stub))
+            .into_iter()
+            .map(|tool| tool.definition.name.into_string())
+            .collect::<Vec<_>>();
+
+        assert!(actual.contains(&"tool_forge_fs_read".to_string()));
+        for tool in [
+            "tool_forge_fs_create",
+            "tool_forge_process_shell",
+            "tool_forge_lsp_definition",
+            "tool_forge_lsp_references",
+            "tool_forge_lsp_diagnostics",
+            "tool_forge_package_list",
+            "tool_forge_compose_logs",
+        ] {
+            assert!(!actual.contains(&tool.to_string()), "{tool}");
+        }
+    }
 }
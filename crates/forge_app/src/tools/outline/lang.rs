@@ -0,0 +1,63 @@
+use tree_sitter::Language;
+
+/// Returns the tree-sitter language and the outline query for the given file
+/// extension, or `None` when the extension is not supported.
+///
+/// Queries capture every definition as `@definition.<kind>` along with its
+/// name as `@name.definition.<kind>`. When several patterns capture the same
+/// node, the pattern that appears first in the query wins.
+pub fn language(ext: &str) -> Option<(Language, &'static str)> {
+    let language = match ext.to_lowercase().as_str() {
+        "rs" => (
+            tree_sitter_rust::LANGUAGE.into(),
+            include_str!("../queries/rust.rkt"),
+        ),
+        "py" => (
+            tree_sitter_python::LANGUAGE.into(),
+            include_str!("../queries/python.rkt"),
+        ),
+        "ts" => (
+            tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            include_str!("../queries/typescript.rkt"),
+        ),
+        "tsx" => (
+            tree_sitter_typescript::LANGUAGE_TSX.into(),
+            include_str!("../queries/typescript.rkt"),
+        ),
+        "js" | "jsx" | "mjs" | "cjs" => (
+            tree_sitter_typescript::LANGUAGE_TSX.into(),
+            include_str!("../queries/javascript.rkt"),
+        ),
+        "css" => (
+            tree_sitter_css::LANGUAGE.into(),
+            include_str!("../queries/css.rkt"),
+        ),
+        "java" => (
+            tree_sitter_java::LANGUAGE.into(),
+            include_str!("../queries/java.rkt"),
+        ),
+        "scala" => (
+            tree_sitter_scala::LANGUAGE.into(),
+            include_str!("../queries/scala.rkt"),
+        ),
+        "go" => (
+            tree_sitter_go::LANGUAGE.into(),
+            include_str!("../queries/go.rkt"),
+        ),
+        "cs" => (
+            tree_sitter_c_sharp::LANGUAGE.into(),
+            include_str!("../queries/c_sharp.rkt"),
+        ),
+        "rb" => (
+            tree_sitter_ruby::LANGUAGE.into(),
+            include_str!("../queries/ruby.rkt"),
+        ),
+        "php" => (
+            tree_sitter_php::LANGUAGE_PHP.into(),
+            include_str!("../queries/php.rkt"),
+        ),
+        _ => return None,
+    };
+
+    Some(language)
+}
@@ -0,0 +1,192 @@
+mod lang;
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Context;
+use forge_display::{Kind, TitleFormat};
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use forge_walker::Walker;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tree_sitter::{Parser, Query, QueryCursor, StreamingIterator};
+
+/// Separates the outlines of the individual files.
+const SEPARATOR: &str = "\n|----\n";
+
+/// A top level or nested definition found in a source file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    /// Kind of the definition as named by the language, eg: class, trait,
+    /// interface or module.
+    pub kind: String,
+    pub name: String,
+    /// One based line where the definition starts.
+    pub line: usize,
+    /// One based line where the definition ends.
+    pub end_line: usize,
+    /// Source line where the definition starts.
+    pub text: String,
+}
+
+/// Extracts the definitions of a source file. Files with unsupported
+/// extensions have no definitions.
+pub fn definitions(path: &Path, content: &str) -> anyhow::Result<Vec<Definition>> {
+    let Some((language, source)) = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(lang::language)
+    else {
+        return Ok(vec![]);
+    };
+
+    let query = Query::new(&language, source)
+        .with_context(|| format!("Invalid outline query for {}", path.display()))?;
+    let mut parser = Parser::new();
+    parser.set_language(&language)?;
+    let Some(tree) = parser.parse(content, None) else {
+        return Ok(vec![]);
+    };
+
+    let lines = content.lines().collect::<Vec<_>>();
+    let capture_names = query.capture_names();
+
+    // Definitions keyed by the byte range of their node, along with the index
+    // of the pattern that captured them.
+    let mut found: HashMap<(usize, usize), (usize, Definition)> = HashMap::new();
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(&query, tree.root_node(), content.as_bytes());
+    while let Some(m) = matches.next() {
+        let mut definition = None;
+        let mut name = None;
+        for capture in m.captures {
+            let capture_name = capture_names[capture.index as usize];
+            if let Some(kind) = capture_name.strip_prefix("definition.") {
+                definition = Some((kind, capture.node));
+            } else if capture_name == "name" || capture_name.starts_with("name.") {
+                name = Some(capture.node);
+            }
+        }
+
+        let (Some((kind, node)), Some(name)) = (definition, name) else {
+            continue;
+        };
+
+        let row = node.start_position().row;
+        let definition = Definition {
+            kind: kind.to_string(),
+            name: content[name.byte_range()].to_string(),
+            line: row + 1,
+            end_line: node.end_position().row + 1,
+            text: lines.get(row).unwrap_or(&"").trim_end().to_string(),
+        };
+
+        let key = (node.start_byte(), node.end_byte());
+        match found.get(&key) {
+            Some((pattern, _)) if *pattern <= m.pattern_index => {}
+            _ => {
+                found.insert(key, (m.pattern_index, definition));
+            }
+        }
+    }
+
+    let mut definitions = found.into_iter().collect::<Vec<_>>();
+    definitions.sort_by_key(|(range, _)| *range);
+    Ok(definitions
+        .into_iter()
+        .map(|(_, (_, definition))| definition)
+        .collect())
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct OutlineInput {
+    /// The path of the directory to outline (absolute path required). Files are
+    /// collected recursively.
+    pub path: String,
+}
+
+/// Lists the definitions (classes, interfaces, traits, modules, functions,
+/// methods etc.) of the source files in a directory, along with their line
+/// numbers and kinds. Use it to get an overview of the structure of a codebase
+/// before reading individual files. Supports Rust, Python, JavaScript,
+/// TypeScript, CSS, Java, Scala, Go, C#, Ruby and PHP.
+#[derive(ToolDescription)]
+pub struct Outline {
+    guard: PathGuard,
+}
+
+impl Outline {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for Outline {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_code_outline")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for Outline {
+    type Input = OutlineInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let dir = &self.guard.resolve(&input.path)?;
+
+        let mut files = Walker::max_all()
+            .cwd(dir.to_path_buf())
+            .get()
+            .await
+            .with_context(|| format!("Failed to walk directory '{}'", dir.display()))?
+            .into_iter()
+            .filter(|file| !file.is_dir())
+            .map(|file| file.path)
+            .collect::<Vec<_>>();
+        files.sort();
+
+        let mut outlines = vec![];
+        for file in files {
+            // Skip binary or unreadable files
+            let Ok(content) = tokio::fs::read_to_string(dir.join(&file)).await else {
+                continue;
+            };
+
+            let definitions = definitions(Path::new(&file), &content)?;
+            if definitions.is_empty() {
+                continue;
+            }
+
+            let lines = definitions
+                .iter()
+                .map(|definition| {
+                    format!(
+                        "│{}: [{}] {}",
+                        definition.line, definition.kind, definition.text
+                    )
+                })
+                .collect::<Vec<_>>();
+            outlines.push(format!("{}\n{}", file, lines.join("\n")));
+        }
+
+        println!(
+            "{}",
+            TitleFormat {
+                kind: Kind::Execute,
+                title: "outline".to_string(),
+                sub_title: Some(input.path.clone()),
+                error: None,
+            }
+            .format()
+        );
+
+        if outlines.is_empty() {
+            return Ok("No source code definitions found.".to_string());
+        }
+
+        Ok(outlines.join(SEPARATOR))
+    }
+}
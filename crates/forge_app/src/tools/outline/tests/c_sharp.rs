@@ -0,0 +1,44 @@
+use forge_domain::ExecutableTool;
+use insta::assert_snapshot;
+use tokio::fs;
+
+use crate::tools::outline::{Outline, OutlineInput};
+use crate::tools::utils::TempDir;
+
+#[tokio::test]
+async fn c_sharp_outline() {
+    let temp_dir = TempDir::new().unwrap();
+    let content = r#"
+namespace Example.Users
+{
+    public enum Role { Admin, Regular }
+
+    public record Address(string Street, string City);
+
+    public struct Point { public int X; public int Y; }
+
+    public interface IUserRepository
+    {
+        User FindById(string id);
+    }
+
+    public class User
+    {
+        public User(string name) { Name = name; }
+
+        public string Name { get; }
+
+        public string Greet() => $"Hello, {Name}";
+    }
+}"#;
+    let file_path = temp_dir.path().join("test.cs");
+    fs::write(&file_path, content).await.unwrap();
+
+    let outline = Outline::new(TempDir::guard());
+    let result = outline
+        .call(OutlineInput { path: temp_dir.path().to_string_lossy().to_string() })
+        .await
+        .unwrap();
+
+    assert_snapshot!("outline_c_sharp", result);
+}
@@ -2,7 +2,7 @@ use forge_domain::ExecutableTool;
 use insta::assert_snapshot;
 use tokio::fs;
 
-use crate::outline::{Outline, OutlineInput};
+use crate::tools::outline::{Outline, OutlineInput};
 use crate::tools::utils::TempDir;
 
 #[tokio::test]
@@ -42,7 +42,7 @@ async fn css_outline() {
     let file_path = temp_dir.path().join("test.css");
     fs::write(&file_path, content).await.unwrap();
 
-    let outline = Outline;
+    let outline = Outline::new(TempDir::guard());
     let result = outline
         .call(OutlineInput { path: temp_dir.path().to_string_lossy().to_string() })
         .await
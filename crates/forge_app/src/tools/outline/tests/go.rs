@@ -0,0 +1,43 @@
+use forge_domain::ExecutableTool;
+use insta::assert_snapshot;
+use tokio::fs;
+
+use crate::tools::outline::{Outline, OutlineInput};
+use crate::tools::utils::TempDir;
+
+#[tokio::test]
+async fn go_outline() {
+    let temp_dir = TempDir::new().unwrap();
+    let content = r#"
+package users
+
+type Role int
+
+type User struct {
+    Name string
+    Role Role
+}
+
+type Repository interface {
+    FindByID(id string) (*User, error)
+    Save(user *User) error
+}
+
+func NewUser(name string) *User {
+    return &User{Name: name}
+}
+
+func (u *User) Greet() string {
+    return "Hello, " + u.Name
+}"#;
+    let file_path = temp_dir.path().join("test.go");
+    fs::write(&file_path, content).await.unwrap();
+
+    let outline = Outline::new(TempDir::guard());
+    let result = outline
+        .call(OutlineInput { path: temp_dir.path().to_string_lossy().to_string() })
+        .await
+        .unwrap();
+
+    assert_snapshot!("outline_go", result);
+}
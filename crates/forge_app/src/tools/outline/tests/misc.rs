@@ -2,7 +2,7 @@ use forge_domain::ExecutableTool;
 use insta::assert_snapshot;
 use tokio::fs;
 
-use crate::outline::{Outline, OutlineInput};
+use crate::tools::outline::{Outline, OutlineInput};
 use crate::tools::utils::TempDir;
 
 #[tokio::test]
@@ -33,7 +33,7 @@ async fn test_outline_multiple_files() {
     .await
     .unwrap();
 
-    let outline = Outline;
+    let outline = Outline::new(TempDir::guard());
     let result = outline
         .call(OutlineInput { path: temp_dir.path().to_string_lossy().to_string() })
         .await
@@ -51,7 +51,7 @@ async fn test_outline_multiple_files() {
 async fn test_outline_empty_directory() {
     let temp_dir = TempDir::new().unwrap();
 
-    let outline = Outline;
+    let outline = Outline::new(TempDir::guard());
     let result = outline
         .call(OutlineInput { path: temp_dir.path().to_string_lossy().to_string() })
         .await
@@ -67,7 +67,7 @@ async fn test_outline_unsupported_files() {
         .await
         .unwrap();
 
-    let outline = Outline;
+    let outline = Outline::new(TempDir::guard());
     let result = outline
         .call(OutlineInput { path: temp_dir.path().to_string_lossy().to_string() })
         .await
@@ -1,8 +1,12 @@
+pub mod c_sharp;
 pub mod css;
+pub mod go;
 pub mod java;
 pub mod javascript;
 pub mod misc;
+pub mod php;
 pub mod python;
+pub mod ruby;
 pub mod rust;
 pub mod scala;
 pub mod tsx;
@@ -0,0 +1,55 @@
+use forge_domain::ExecutableTool;
+use insta::assert_snapshot;
+use tokio::fs;
+
+use crate::tools::outline::{Outline, OutlineInput};
+use crate::tools::utils::TempDir;
+
+#[tokio::test]
+async fn php_outline() {
+    let temp_dir = TempDir::new().unwrap();
+    let content = r#"<?php
+
+namespace App\Users;
+
+interface Repository
+{
+    public function findById(string $id): ?User;
+}
+
+trait Greets
+{
+    public function greet(): string
+    {
+        return "Hello, {$this->name}";
+    }
+}
+
+enum Role: string
+{
+    case Admin = 'admin';
+}
+
+class User
+{
+    use Greets;
+
+    public function __construct(public string $name) {}
+}
+
+function create_user(string $name): User
+{
+    return new User($name);
+}
+"#;
+    let file_path = temp_dir.path().join("test.php");
+    fs::write(&file_path, content).await.unwrap();
+
+    let outline = Outline::new(TempDir::guard());
+    let result = outline
+        .call(OutlineInput { path: temp_dir.path().to_string_lossy().to_string() })
+        .await
+        .unwrap();
+
+    assert_snapshot!("outline_php", result);
+}
@@ -2,7 +2,7 @@ use forge_domain::ExecutableTool;
 use insta::assert_snapshot;
 use tokio::fs;
 
-use crate::outline::{Outline, OutlineInput};
+use crate::tools::outline::{Outline, OutlineInput};
 use crate::tools::utils::TempDir;
 
 #[tokio::test]
@@ -36,7 +36,7 @@ async def fetch_data():
     let file_path = temp_dir.path().join("test.py");
     fs::write(&file_path, content).await.unwrap();
 
-    let outline = Outline;
+    let outline = Outline::new(TempDir::guard());
     let result = outline
         .call(OutlineInput { path: temp_dir.path().to_string_lossy().to_string() })
         .await
@@ -0,0 +1,42 @@
+use forge_domain::ExecutableTool;
+use insta::assert_snapshot;
+use tokio::fs;
+
+use crate::tools::outline::{Outline, OutlineInput};
+use crate::tools::utils::TempDir;
+
+#[tokio::test]
+async fn ruby_outline() {
+    let temp_dir = TempDir::new().unwrap();
+    let content = r#"
+module Users
+  class User
+    attr_reader :name
+
+    def initialize(name)
+      @name = name
+    end
+
+    def self.build(attributes)
+      new(attributes[:name])
+    end
+
+    def greet
+      "Hello, #{name}"
+    end
+  end
+
+  class Admin::Account < User
+  end
+end"#;
+    let file_path = temp_dir.path().join("test.rb");
+    fs::write(&file_path, content).await.unwrap();
+
+    let outline = Outline::new(TempDir::guard());
+    let result = outline
+        .call(OutlineInput { path: temp_dir.path().to_string_lossy().to_string() })
+        .await
+        .unwrap();
+
+    assert_snapshot!("outline_ruby", result);
+}
@@ -51,7 +51,7 @@ const processUser = (user: User): UserResponse => {
     let file_path = temp_dir.path().join("test.ts");
     fs::write(&file_path, content).await.unwrap();
 
-    let outline = Outline;
+    let outline = Outline::new(TempDir::guard());
     let result = outline
         .call(OutlineInput { path: temp_dir.path().to_string_lossy().to_string() })
         .await
@@ -0,0 +1,441 @@
+use std::fmt::{self, Display};
+use std::path::Path;
+
+use anyhow::bail;
+
+/// Package managers the dependencies of a project are managed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    Cargo,
+    Npm,
+    Pnpm,
+    Yarn,
+    Pip,
+    Uv,
+    Go,
+}
+
+/// A dependency of the project, as listed by its package manager.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+}
+
+impl Display for Package {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.name, self.version)
+    }
+}
+
+/// A program and its arguments.
+pub type Command = (&'static str, Vec<String>);
+
+impl PackageManager {
+    /// Detects the package manager from the manifests and lockfiles in the
+    /// directory of the project.
+    pub fn detect(dir: &Path) -> Option<Self> {
+        let exists = |file: &str| dir.join(file).is_file();
+        if exists("Cargo.toml") {
+            Some(Self::Cargo)
+        } else if exists("go.mod") {
+            Some(Self::Go)
+        } else if exists("pnpm-lock.yaml") {
+            Some(Self::Pnpm)
+        } else if exists("yarn.lock") {
+            Some(Self::Yarn)
+        } else if exists("package.json") {
+            Some(Self::Npm)
+        } else if exists("uv.lock") {
+            Some(Self::Uv)
+        } else if exists("requirements.txt") || exists("pyproject.toml") {
+            Some(Self::Pip)
+        } else {
+            None
+        }
+    }
+
+    /// The files declaring the dependencies, whose changes are reported.
+    pub fn manifests(&self) -> &'static [&'static str] {
+        match self {
+            Self::Cargo => &["Cargo.toml"],
+            Self::Npm | Self::Pnpm | Self::Yarn => &["package.json"],
+            Self::Pip => &["requirements.txt", "pyproject.toml"],
+            Self::Uv => &["pyproject.toml"],
+            Self::Go => &["go.mod"],
+        }
+    }
+
+    /// The files pinning the resolved versions, which are only reported as
+    /// updated since their diffs are large.
+    pub fn lockfiles(&self) -> &'static [&'static str] {
+        match self {
+            Self::Cargo => &["Cargo.lock"],
+            Self::Npm => &["package-lock.json"],
+            Self::Pnpm => &["pnpm-lock.yaml"],
+            Self::Yarn => &["yarn.lock"],
+            Self::Pip => &[],
+            Self::Uv => &["uv.lock"],
+            Self::Go => &["go.sum"],
+        }
+    }
+
+    /// The command that adds the packages, as dev dependencies if `dev` and
+    /// with the given features, which only cargo supports.
+    pub fn add(
+        &self,
+        packages: &[String],
+        dev: bool,
+        features: &[String],
+    ) -> anyhow::Result<Command> {
+        if !features.is_empty() && *self != Self::Cargo {
+            bail!(
+                "Features are only supported by cargo, pass them in the package spec instead, \
+                 eg: `requests[socks]`"
+            );
+        }
+        let (program, args, dev_flag): (_, &[&str], _) = match self {
+            Self::Cargo => ("cargo", &["add"], Some("--dev")),
+            Self::Npm => ("npm", &["install"], Some("--save-dev")),
+            Self::Pnpm => ("pnpm", &["add"], Some("--save-dev")),
+            Self::Yarn => ("yarn", &["add"], Some("--dev")),
+            Self::Pip => ("python", &["-m", "pip", "install"], None),
+            Self::Uv => ("uv", &["add"], Some("--dev")),
+            Self::Go => ("go", &["get"], None),
+        };
+        let mut args = strings(args);
+        if dev {
+            let Some(dev_flag) = dev_flag else {
+                bail!("{self} has no dev dependencies");
+            };
+            args.push(dev_flag.to_string());
+        }
+        args.extend(packages.iter().cloned());
+        if !features.is_empty() {
+            args.push("--features".to_string());
+            args.push(features.join(","));
+        }
+        Ok((program, args))
+    }
+
+    /// The command that removes the packages.
+    pub fn remove(&self, packages: &[String]) -> Command {
+        let (program, args): (_, &[&str]) = match self {
+            Self::Cargo => ("cargo", &["remove"]),
+            Self::Npm => ("npm", &["uninstall"]),
+            Self::Pnpm => ("pnpm", &["remove"]),
+            Self::Yarn => ("yarn", &["remove"]),
+            Self::Pip => ("python", &["-m", "pip", "uninstall", "-y"]),
+            Self::Uv => ("uv", &["remove"]),
+            Self::Go => {
+                let args = packages.iter().map(|package| format!("{package}@none"));
+                return ("go", ["get".to_string()].into_iter().chain(args).collect());
+            }
+        };
+        let mut args = strings(args);
+        args.extend(packages.iter().cloned());
+        (program, args)
+    }
+
+    /// The command that upgrades the packages to the latest versions the
+    /// manifest allows, all of them when `packages` is empty.
+    pub fn upgrade(&self, packages: &[String]) -> anyhow::Result<Command> {
+        let (program, args): (_, &[&str]) = match self {
+            Self::Cargo => ("cargo", &["update"]),
+            Self::Npm => ("npm", &["update"]),
+            Self::Pnpm => ("pnpm", &["update"]),
+            Self::Yarn => ("yarn", &["upgrade"]),
+            Self::Pip if packages.is_empty() => {
+                bail!("pip can't upgrade every package, name the ones to upgrade")
+            }
+            Self::Pip => ("python", &["-m", "pip", "install", "--upgrade"]),
+            Self::Uv if packages.is_empty() => ("uv", &["lock", "--upgrade"]),
+            Self::Uv => {
+                let args = packages
+                    .iter()
+                    .flat_map(|package| ["--upgrade-package".to_string(), package.clone()]);
+                return Ok(("uv", ["lock".to_string()].into_iter().chain(args).collect()));
+            }
+            Self::Go if packages.is_empty() => ("go", &["get", "-u", "./..."]),
+            Self::Go => ("go", &["get", "-u"]),
+        };
+        let mut args = strings(args);
+        args.extend(packages.iter().cloned());
+        Ok((program, args))
+    }
+
+    /// The command that lists the direct dependencies in the format
+    /// `parse_list` understands.
+    pub fn list(&self) -> Command {
+        let (program, args): (_, &[&str]) = match self {
+            Self::Cargo => ("cargo", &["tree", "--depth", "1", "--prefix", "none"]),
+            Self::Npm => ("npm", &["ls", "--depth=0", "--json"]),
+            Self::Pnpm => ("pnpm", &["list", "--depth", "0", "--json"]),
+            Self::Yarn => ("yarn", &["list", "--depth=0"]),
+            Self::Pip => ("python", &["-m", "pip", "list", "--format=json"]),
+            Self::Uv => ("uv", &["pip", "list", "--format=json"]),
+            Self::Go => ("go", &["list", "-m", "all"]),
+        };
+        (program, strings(args))
+    }
+
+    /// Parses the dependencies from the output of the `list` command.
+    pub fn parse_list(&self, output: &str) -> Vec<Package> {
+        let mut packages = match self {
+            Self::Cargo => parse_cargo_tree(output),
+            Self::Npm => parse_npm(output),
+            Self::Pnpm => parse_pnpm(output),
+            Self::Yarn => parse_yarn(output),
+            Self::Pip | Self::Uv => parse_pip(output),
+            Self::Go => parse_go(output),
+        };
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+        packages.dedup();
+        packages
+    }
+}
+
+impl Display for PackageManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Cargo => "cargo",
+            Self::Npm => "npm",
+            Self::Pnpm => "pnpm",
+            Self::Yarn => "yarn",
+            Self::Pip => "pip",
+            Self::Uv => "uv",
+            Self::Go => "go",
+        };
+        f.write_str(name)
+    }
+}
+
+fn strings(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn package(name: &str, version: &str) -> Package {
+    Package { name: name.to_string(), version: version.to_string() }
+}
+
+/// `serde v1.0.219`, after the crate itself and the `[dev-dependencies]`
+/// headers. The crates of a workspace are separated by blank lines.
+fn parse_cargo_tree(output: &str) -> Vec<Package> {
+    let mut root = true;
+    let mut packages = Vec::new();
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            root = true;
+            continue;
+        }
+        if std::mem::take(&mut root) || line.starts_with('[') {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        if let (Some(name), Some(version)) = (parts.next(), parts.next()) {
+            packages.push(package(name, version.trim_start_matches('v')));
+        }
+    }
+    packages
+}
+
+/// The objects of the `dependencies` of the project.
+fn dependencies(value: &serde_json::Value, key: &str) -> Vec<Package> {
+    value
+        .get(key)
+        .and_then(|dependencies| dependencies.as_object())
+        .into_iter()
+        .flatten()
+        .map(|(name, dependency)| {
+            let version = dependency
+                .get("version")
+                .and_then(|version| version.as_str())
+                .unwrap_or_default();
+            package(name, version)
+        })
+        .collect()
+}
+
+/// `{"dependencies": {"lodash": {"version": "4.17.21"}}}`
+fn parse_npm(output: &str) -> Vec<Package> {
+    serde_json::from_str::<serde_json::Value>(output)
+        .map(|value| dependencies(&value, "dependencies"))
+        .unwrap_or_default()
+}
+
+/// `[{"dependencies": {...}, "devDependencies": {...}}]`, one object per
+/// project of the workspace.
+fn parse_pnpm(output: &str) -> Vec<Package> {
+    let projects = serde_json::from_str::<Vec<serde_json::Value>>(output).unwrap_or_default();
+    projects
+        .iter()
+        .flat_map(|project| {
+            ["dependencies", "devDependencies", "optionalDependencies"]
+                .into_iter()
+                .flat_map(|key| dependencies(project, key))
+        })
+        .collect()
+}
+
+/// `├─ @babel/core@7.26.0`
+fn parse_yarn(output: &str) -> Vec<Package> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let spec = line
+                .trim()
+                .strip_prefix("├─ ")
+                .or(line.trim().strip_prefix("└─ "))?;
+            let at = spec.get(1..)?.find('@')? + 1;
+            Some(package(&spec[..at], &spec[at + 1..]))
+        })
+        .collect()
+}
+
+/// `[{"name": "requests", "version": "2.32.3"}]`
+fn parse_pip(output: &str) -> Vec<Package> {
+    let packages = serde_json::from_str::<Vec<serde_json::Value>>(output).unwrap_or_default();
+    packages
+        .iter()
+        .filter_map(|value| {
+            let name = value.get("name")?.as_str()?;
+            let version = value.get("version")?.as_str()?;
+            Some(package(name, version))
+        })
+        .collect()
+}
+
+/// `golang.org/x/text v0.21.0`, after the module itself which has no version.
+fn parse_go(output: &str) -> Vec<Package> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            Some(package(parts.next()?, parts.next()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    fn packages(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_detect() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+        assert_eq!(
+            PackageManager::detect(&dir.path()),
+            Some(PackageManager::Npm)
+        );
+
+        std::fs::write(dir.path().join("pnpm-lock.yaml"), "").unwrap();
+        assert_eq!(
+            PackageManager::detect(&dir.path()),
+            Some(PackageManager::Pnpm)
+        );
+    }
+
+    #[test]
+    fn test_add() {
+        let actual = PackageManager::Cargo
+            .add(&packages(&["serde"]), false, &packages(&["derive"]))
+            .unwrap();
+        let expected = ("cargo", packages(&["add", "serde", "--features", "derive"]));
+        assert_eq!(actual, expected);
+
+        let actual = PackageManager::Npm
+            .add(&packages(&["vitest"]), true, &[])
+            .unwrap();
+        let expected = ("npm", packages(&["install", "--save-dev", "vitest"]));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_add_unsupported() {
+        let features =
+            PackageManager::Pip.add(&packages(&["requests"]), false, &packages(&["socks"]));
+        assert!(features
+            .unwrap_err()
+            .to_string()
+            .contains("only supported by cargo"));
+
+        let dev = PackageManager::Go.add(&packages(&["golang.org/x/text"]), true, &[]);
+        assert!(dev
+            .unwrap_err()
+            .to_string()
+            .contains("go has no dev dependencies"));
+    }
+
+    #[test]
+    fn test_remove_go() {
+        let actual = PackageManager::Go.remove(&packages(&["golang.org/x/text"]));
+        let expected = ("go", packages(&["get", "golang.org/x/text@none"]));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_upgrade_uv() {
+        let actual = PackageManager::Uv
+            .upgrade(&packages(&["httpx", "rich"]))
+            .unwrap();
+        let expected = (
+            "uv",
+            packages(&[
+                "lock",
+                "--upgrade-package",
+                "httpx",
+                "--upgrade-package",
+                "rich",
+            ]),
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_cargo_tree() {
+        let output = "app v0.1.0 (/home/app)\nanyhow v1.0.95\nserde v1.0.219\n[dev-dependencies]\ninsta v1.42.0\n\ncli v0.1.0 (/home/app/cli)\nanyhow v1.0.95\n";
+        let actual = PackageManager::Cargo.parse_list(output);
+        let expected = vec![
+            package("anyhow", "1.0.95"),
+            package("insta", "1.42.0"),
+            package("serde", "1.0.219"),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_npm() {
+        let output = r#"{"name": "app", "dependencies": {"lodash": {"version": "4.17.21"}, "react": {"version": "19.0.0"}}}"#;
+        let actual = PackageManager::Npm.parse_list(output);
+        let expected = vec![package("lodash", "4.17.21"), package("react", "19.0.0")];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_yarn() {
+        let output =
+            "yarn list v1.22.22\n├─ @babel/core@7.26.0\n└─ lodash@4.17.21\nDone in 0.05s.\n";
+        let actual = PackageManager::Yarn.parse_list(output);
+        let expected = vec![
+            package("@babel/core", "7.26.0"),
+            package("lodash", "4.17.21"),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_go() {
+        let output = "example.com/app\ngolang.org/x/text v0.21.0\n";
+        let actual = PackageManager::Go.parse_list(output);
+        let expected = vec![package("golang.org/x/text", "v0.21.0")];
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,12 @@
+mod manager;
+mod package_add;
+mod package_list;
+mod package_remove;
+mod package_upgrade;
+mod project;
+
+pub use manager::PackageManager;
+pub use package_add::*;
+pub use package_list::*;
+pub use package_remove::*;
+pub use package_upgrade::*;
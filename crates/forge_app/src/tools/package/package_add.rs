@@ -0,0 +1,62 @@
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::project::Project;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PackageAddInput {
+    /// The absolute path of the project's root directory (default: the
+    /// current working directory).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// The packages to add, optionally with a version in the syntax of the
+    /// package manager, eg: `serde@1`, `lodash@^4` or `requests==2.32`.
+    pub packages: Vec<String>,
+    /// Adds them as development dependencies (default: false).
+    #[serde(default)]
+    pub dev: bool,
+    /// Features of the packages to enable, only supported by cargo, eg:
+    /// `derive` for serde.
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+/// Adds dependencies to a project with its package manager, detected from its
+/// manifest and lockfile: cargo, npm, pnpm, yarn, uv, pip or go modules.
+/// Returns the diff of the manifest and the lockfiles that were updated. Use it
+/// instead of editing the manifest or running the package manager in the
+/// shell, eg: to add serde with its derive feature.
+#[derive(ToolDescription)]
+pub struct PackageAdd {
+    guard: PathGuard,
+}
+
+impl PackageAdd {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for PackageAdd {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_package_add")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for PackageAdd {
+    type Input = PackageAddInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        if input.packages.is_empty() {
+            anyhow::bail!("No packages to add");
+        }
+        let project = Project::resolve(&self.guard, input.path.as_deref())?;
+        let command = project
+            .manager
+            .add(&input.packages, input.dev, &input.features)?;
+        project.change(command).await
+    }
+}
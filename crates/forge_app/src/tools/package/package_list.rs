@@ -0,0 +1,66 @@
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::project::{tail, Project};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PackageListInput {
+    /// The absolute path of the project's root directory (default: the
+    /// current working directory).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// Lists the dependencies of a project with their resolved versions, one
+/// `<name> <version>` per line, using its package manager detected from its
+/// manifest and lockfile: cargo, npm, pnpm, yarn, uv, pip or go modules. Only
+/// the direct dependencies are listed, except for pip and uv which list the
+/// packages installed in the environment.
+#[derive(ToolDescription)]
+pub struct PackageList {
+    guard: PathGuard,
+}
+
+impl PackageList {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for PackageList {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_package_list")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for PackageList {
+    type Input = PackageListInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let project = Project::resolve(&self.guard, input.path.as_deref())?;
+        let (command_line, output) = project.run(&project.manager.list()).await?;
+
+        // note: npm reports problems such as missing peer dependencies with a
+        // failure, along with the dependencies.
+        let packages = project.manager.parse_list(&output.stdout);
+        if packages.is_empty() && !output.success {
+            anyhow::bail!("{command_line} failed:\n<output>{}</output>", tail(&output));
+        }
+        if packages.is_empty() {
+            return Ok(format!("No dependencies listed by {command_line}"));
+        }
+        Ok(format!(
+            "{} dependencies ({}):\n{}",
+            packages.len(),
+            project.manager,
+            packages
+                .iter()
+                .map(|package| package.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        ))
+    }
+}
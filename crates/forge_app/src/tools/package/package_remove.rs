@@ -0,0 +1,51 @@
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::project::Project;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PackageRemoveInput {
+    /// The absolute path of the project's root directory (default: the
+    /// current working directory).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// The names of the packages to remove.
+    pub packages: Vec<String>,
+}
+
+/// Removes dependencies from a project with its package manager, detected
+/// from its manifest and lockfile: cargo, npm, pnpm, yarn, uv, pip or go
+/// modules. Returns the diff of the manifest and the lockfiles that were
+/// updated.
+#[derive(ToolDescription)]
+pub struct PackageRemove {
+    guard: PathGuard,
+}
+
+impl PackageRemove {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for PackageRemove {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_package_remove")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for PackageRemove {
+    type Input = PackageRemoveInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        if input.packages.is_empty() {
+            anyhow::bail!("No packages to remove");
+        }
+        let project = Project::resolve(&self.guard, input.path.as_deref())?;
+        let command = project.manager.remove(&input.packages);
+        project.change(command).await
+    }
+}
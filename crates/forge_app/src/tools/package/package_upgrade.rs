@@ -0,0 +1,50 @@
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::project::Project;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PackageUpgradeInput {
+    /// The absolute path of the project's root directory (default: the
+    /// current working directory).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// The names of the packages to upgrade (default: all of them).
+    #[serde(default)]
+    pub packages: Vec<String>,
+}
+
+/// Upgrades dependencies of a project to the latest versions its manifest
+/// allows, with its package manager detected from its manifest and lockfile:
+/// cargo, npm, pnpm, yarn, uv, pip or go modules. Returns the diff of the
+/// manifest and the lockfiles that were updated. To move to a new major
+/// version, add the package again with that version instead.
+#[derive(ToolDescription)]
+pub struct PackageUpgrade {
+    guard: PathGuard,
+}
+
+impl PackageUpgrade {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for PackageUpgrade {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_package_upgrade")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for PackageUpgrade {
+    type Input = PackageUpgradeInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let project = Project::resolve(&self.guard, input.path.as_deref())?;
+        let command = project.manager.upgrade(&input.packages)?;
+        project.change(command).await
+    }
+}
@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Context;
+use forge_display::DiffFormat;
+use forge_domain::PathGuard;
+
+use super::manager::{Command, PackageManager};
+use crate::tools::shell::executor::{CommandExecutor, Output};
+
+/// Lines at the end of the output that are returned when the package manager
+/// fails.
+const MAX_OUTPUT_LINES: usize = 30;
+
+/// Seconds after which the package manager is killed, resolving and
+/// downloading packages can take a while.
+const TIMEOUT_SECS: u64 = 600;
+
+/// The project whose dependencies the package tools manage.
+pub struct Project {
+    pub dir: PathBuf,
+    pub manager: PackageManager,
+}
+
+impl Project {
+    /// The project at `path`, or the current working directory, along with
+    /// its package manager.
+    pub fn resolve(guard: &PathGuard, path: Option<&str>) -> anyhow::Result<Self> {
+        let dir = match path {
+            Some(path) => guard.resolve(path)?,
+            None => guard.cwd().to_path_buf(),
+        };
+        let manager = PackageManager::detect(&dir).with_context(|| {
+            format!(
+                "No supported package manager detected in {}, run it with the shell tool instead",
+                dir.display()
+            )
+        })?;
+        Ok(Self { dir, manager })
+    }
+
+    /// Runs the command in the directory of the project, returning its
+    /// command line along with its output.
+    pub async fn run(&self, (program, args): &Command) -> anyhow::Result<(String, Output)> {
+        let command_line = format!("{program} {}", args.join(" "));
+
+        #[cfg(not(test))]
+        {
+            use forge_display::TitleFormat;
+
+            println!("{}", TitleFormat::execute(&command_line).format());
+        }
+
+        let mut command = tokio::process::Command::new(program);
+        command.args(args).current_dir(&self.dir).kill_on_drop(true);
+        let output = CommandExecutor::new(command)
+            .timeout(Some(Duration::from_secs(TIMEOUT_SECS)))
+            .execute()
+            .await
+            .with_context(|| format!("Failed to run {command_line}"))?;
+        Ok((command_line, output))
+    }
+
+    /// Runs a command that changes the dependencies and reports the diff of
+    /// the manifests and the lockfiles that were updated, or the end of the
+    /// output when it fails.
+    pub async fn change(&self, command: Command) -> anyhow::Result<String> {
+        let manifests = self.read(self.manager.manifests());
+        let lockfiles = self.read(self.manager.lockfiles());
+
+        let (command_line, output) = self.run(&command).await?;
+
+        let mut report = format!("<command>{command_line}</command>\n");
+        let mut diff = String::new();
+        for (file, old) in manifests {
+            let new = std::fs::read_to_string(self.dir.join(file)).ok();
+            if old != new {
+                diff.push_str(&DiffFormat::unified(
+                    &format!("/{file}"),
+                    &old.unwrap_or_default(),
+                    &new.unwrap_or_default(),
+                ));
+            }
+        }
+        if diff.is_empty() {
+            report.push_str("The manifest is unchanged");
+        } else {
+            report.push_str(&format!("<diff>\n{}</diff>", diff));
+        }
+        for (file, old) in lockfiles {
+            if old != std::fs::read_to_string(self.dir.join(file)).ok() {
+                report.push_str(&format!("\n{file} was updated"));
+            }
+        }
+
+        if output.success {
+            return Ok(report);
+        }
+        if let Some(timeout) = output.timed_out {
+            report.push_str(&format!(
+                "\nThe command timed out after {} seconds and was killed.",
+                timeout.as_secs()
+            ));
+        }
+        report.push_str(&format!(
+            "\n{command_line} failed:\n<output>{}</output>",
+            tail(&output)
+        ));
+        Err(anyhow::anyhow!(report))
+    }
+
+    /// The content of the files of the project that exist, by their name.
+    fn read(&self, files: &[&'static str]) -> Vec<(&'static str, Option<String>)> {
+        files
+            .iter()
+            .map(|file| (*file, std::fs::read_to_string(self.dir.join(file)).ok()))
+            .collect()
+    }
+}
+
+/// The last lines of the combined output of the command.
+pub fn tail(output: &Output) -> String {
+    let combined = format!("{}\n{}", output.stdout, output.stderr);
+    let lines = combined.trim().lines().collect::<Vec<_>>();
+    lines[lines.len().saturating_sub(MAX_OUTPUT_LINES)..].join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    #[test]
+    fn test_resolve_without_manifest() {
+        let dir = TempDir::new().unwrap();
+
+        let result = Project::resolve(
+            &TempDir::guard(),
+            Some(dir.path().to_string_lossy().as_ref()),
+        );
+
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("No supported package manager detected"));
+    }
+}
@@ -1,18 +1,17 @@
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
-use anyhow::bail;
-use dissimilar::Chunk;
+use anyhow::{bail, Context};
 use forge_display::DiffFormat;
-use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
 use schemars::JsonSchema;
 use serde::Deserialize;
 use thiserror::Error;
 use tokio::fs;
 
 use super::marker::{DIVIDER, REPLACE, SEARCH};
-use super::parse::{self, PatchBlock};
+use super::matching::{find_close_match, Match, Strategy, DEFAULT_FUZZY_THRESHOLD};
+use super::parse::{self, Occurrence, PatchBlock};
 use crate::tools::syn;
-use crate::tools::utils::assert_absolute_path;
 
 #[derive(Debug, Error)]
 enum Error {
@@ -32,7 +31,23 @@ pub struct ApplyPatchInput {
     pub diff: String,
 }
 
-pub struct ApplyPatch;
+pub struct ApplyPatch {
+    guard: PathGuard,
+    fuzzy_threshold: f64,
+}
+
+impl ApplyPatch {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard, fuzzy_threshold: DEFAULT_FUZZY_THRESHOLD }
+    }
+
+    /// Sets the minimum similarity, from 0 to 1, of the text replaced when a
+    /// block doesn't match the file exactly.
+    pub fn fuzzy_threshold(mut self, threshold: f64) -> Self {
+        self.fuzzy_threshold = threshold;
+        self
+    }
+}
 
 impl NamedTool for ApplyPatch {
     fn tool_name() -> ToolName {
@@ -51,8 +66,11 @@ impl ToolDescription for ApplyPatch {
 {REPLACE}
 
 Rules:
-1. SEARCH must exactly match whitespace, indentation & line endings
-2. Each block replaces first match only
+1. SEARCH must exactly match whitespace, indentation & line endings. Close matches are only
+   applied when a single part of the file is similar enough, and reported in the output
+2. Each block replaces the first match only. Follow the SEARCH marker with a number to replace
+   another match, eg: `{SEARCH} 2`, or with `all` to replace every match, eg: to update all
+   the copies of a repeated line in one block
 3. Keep blocks minimal - include only changing lines plus needed context
 4. Provide complete lines only - no truncation
 5. Use multiple blocks for multiple changes in the same file
@@ -79,65 +97,75 @@ def new_function(x, y=0):
     }
 }
 
-/// Apply changes to file content based on search/replace blocks.
+/// Apply changes to file content based on search/replace blocks, returning
+/// how the search text of each block was found.
 /// Changes are only written to disk if all replacements are successful.
-async fn apply_patches(content: String, blocks: Vec<PatchBlock>) -> Result<String, Error> {
+fn apply_patches(
+    content: String,
+    blocks: Vec<PatchBlock>,
+    threshold: f64,
+) -> anyhow::Result<(String, Vec<Match>)> {
     let mut result = content;
+    let mut matches = Vec::new();
 
     // Apply each block sequentially
-    for block in blocks {
+    for (index, block) in blocks.into_iter().enumerate() {
         // For empty search string, append the replacement text at the end of file.
         if block.search.is_empty() {
             result.push_str(&block.replace);
+            matches.push(Match::exact(1));
             continue;
         }
 
         // For exact matching, first try to find the exact string
-        if let Some(start_idx) = result.find(&block.search) {
-            let end_idx = start_idx + block.search.len();
-            result.replace_range(start_idx..end_idx, &block.replace);
-            continue;
-        }
-
-        // If exact match fails, try fuzzy matching
-        let normalized_search = block.search.replace("\r\n", "\n").replace('\r', "\n");
-        let normalized_result = result.replace("\r\n", "\n").replace('\r', "\n");
-
-        if let Some(start_idx) = normalized_result.find(&normalized_search) {
-            result.replace_range(start_idx..start_idx + block.search.len(), &block.replace);
-            continue;
-        }
-
-        // If still no match, try more aggressive fuzzy matching
-        let chunks = dissimilar::diff(&result, &block.search);
-        let mut best_match = None;
-        let mut best_score = 0.0;
-        let mut current_pos = 0;
-
-        for chunk in chunks.iter() {
-            if let Chunk::Equal(text) = chunk {
-                let score = text.len() as f64 / block.search.len() as f64;
-                if score > best_score {
-                    best_score = score;
-                    best_match = Some((current_pos, text.len()));
-                }
+        let starts = result
+            .match_indices(&block.search)
+            .map(|(start, _)| start)
+            .collect::<Vec<_>>();
+        match block.occurrence {
+            Occurrence::All if !starts.is_empty() => {
+                result = result.replace(&block.search, &block.replace);
+                matches.push(Match::exact(starts.len()));
+                continue;
             }
-            match chunk {
-                Chunk::Equal(text) | Chunk::Delete(text) | Chunk::Insert(text) => {
-                    current_pos += text.len();
-                }
+            Occurrence::Nth(n) if n <= starts.len() => {
+                let start_idx = starts[n - 1];
+                let end_idx = start_idx + block.search.len();
+                result.replace_range(start_idx..end_idx, &block.replace);
+                matches.push(Match::exact(1));
+                continue;
             }
+            // note: only the first occurrence falls back to a close match, as
+            // the others are counted in exact matches.
+            Occurrence::Nth(1) => {}
+            Occurrence::Nth(n) => bail!(
+                "Failed to apply block {}: the search text occurs {} times, there is no \
+                 occurrence {n}",
+                index + 1,
+                starts.len()
+            ),
+            Occurrence::All => bail!(
+                "Failed to apply block {}: the search text doesn't occur in the file, replacing \
+                 all of its occurrences requires an exact match",
+                index + 1
+            ),
         }
 
-        if let Some((start_idx, len)) = best_match {
-            if best_score > 0.7 {
-                // Threshold for fuzzy matching
-                result.replace_range(start_idx..start_idx + len, &block.replace);
-            }
-        }
+        let (range, found) = find_close_match(&result, &block.search, threshold)
+            .and_then(|found| {
+                found.with_context(|| {
+                    format!(
+                        "The search text doesn't match the content of the file, no lines have \
+                         a similarity of at least {threshold}"
+                    )
+                })
+            })
+            .with_context(|| format!("Failed to apply block {}", index + 1))?;
+        result.replace_range(range, &block.replace);
+        matches.push(found);
     }
 
-    Ok(result)
+    Ok((result, matches))
 }
 
 #[async_trait::async_trait]
@@ -145,8 +173,7 @@ impl ExecutableTool for ApplyPatch {
     type Input = ApplyPatchInput;
 
     async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
-        let path = Path::new(&input.path);
-        assert_absolute_path(path)?;
+        let path = &self.guard.resolve(&input.path)?;
 
         if !path.exists() {
             bail!(Error::FileNotFound(path.to_path_buf()));
@@ -155,20 +182,34 @@ impl ExecutableTool for ApplyPatch {
         let blocks = parse::parse_blocks(&input.diff)?;
 
         // Read the content of the file before applying the patch
-        let old_content = fs::read_to_string(&input.path)
+        let old_content = fs::read_to_string(path)
             .await
             .map_err(Error::FileOperation)?;
 
         let result = async {
-            let modified = apply_patches(old_content.clone(), blocks).await?;
-            fs::write(&input.path, &modified)
+            let (modified, matches) =
+                apply_patches(old_content.clone(), blocks, self.fuzzy_threshold)?;
+            fs::write(path, &modified)
                 .await
                 .map_err(Error::FileOperation)?;
 
             let syntax_warning = syn::validate(&input.path, &modified);
 
             // Handle syntax warning and build output
-            let output = if let Some(warning) = syntax_warning {
+            let mut output = String::new();
+            for (index, found) in matches.iter().enumerate() {
+                if found.strategy != Strategy::Exact || found.replacements != 1 {
+                    output.push_str(&format!(
+                        "<match block=\"{}\" strategy=\"{}\" confidence=\"{:.2}\" \
+                         replacements=\"{}\"/>\n",
+                        index + 1,
+                        found.strategy,
+                        found.confidence,
+                        found.replacements
+                    ));
+                }
+            }
+            output.push_str(&if let Some(warning) = syntax_warning {
                 format!(
                     "<file_content\n  path=\"{}\"\n  syntax_checker_warning=\"{}\">\n{}</file_content>\n",
                     input.path,
@@ -181,7 +222,7 @@ impl ExecutableTool for ApplyPatch {
                     input.path,
                     modified.trim_end()
                 )
-            };
+            });
             anyhow::Ok(output)
         }
          .await?;
@@ -201,6 +242,9 @@ impl ExecutableTool for ApplyPatch {
 #[cfg(test)]
 mod test {
     use std::io::{Error as IoError, ErrorKind as IoErrorKind};
+    use std::path::Path;
+
+    use pretty_assertions::assert_eq;
 
     use super::*;
     use crate::tools::utils::TempDir;
@@ -230,7 +274,7 @@ mod test {
         let temp_dir = TempDir::new().unwrap();
         let nonexistent = temp_dir.path().join("nonexistent.txt");
 
-        let fs_replace = ApplyPatch;
+        let fs_replace = ApplyPatch::new(TempDir::guard());
         let result = fs_replace
             .call(ApplyPatchInput {
                 path: nonexistent.to_string_lossy().to_string(),
@@ -249,7 +293,7 @@ mod test {
 
         write_test_file(&file_path, content).await.unwrap();
 
-        let fs_replace = ApplyPatch;
+        let fs_replace = ApplyPatch::new(TempDir::guard());
         let result = fs_replace
             .call(ApplyPatchInput {
                 path: file_path.to_string_lossy().to_string(),
@@ -275,7 +319,7 @@ mod test {
 
         write_test_file(&file_path, "").await.unwrap();
 
-        let fs_replace = ApplyPatch;
+        let fs_replace = ApplyPatch::new(TempDir::guard());
         let result = fs_replace
             .call(ApplyPatchInput {
                 path: file_path.to_string_lossy().to_string(),
@@ -299,7 +343,7 @@ mod test {
 
         write_test_file(&file_path, content).await.unwrap();
 
-        let fs_replace = ApplyPatch;
+        let fs_replace = ApplyPatch::new(TempDir::guard());
         let diff = format!("{SEARCH}\n    First Line    \n{DIVIDER}\n    New First    \n{REPLACE}\n{SEARCH}\n    Last Line    \n{DIVIDER}\n    New Last    \n{REPLACE}\n").to_string();
 
         let result = fs_replace
@@ -322,7 +366,7 @@ mod test {
 
         write_test_file(&file_path, content).await.unwrap();
 
-        let fs_replace = ApplyPatch;
+        let fs_replace = ApplyPatch::new(TempDir::guard());
         let diff = format!("{SEARCH}\n  Middle Line  \n{DIVIDER}\n{REPLACE}\n");
         let result = fs_replace
             .call(ApplyPatchInput { path: file_path.to_string_lossy().to_string(), diff })
@@ -345,7 +389,7 @@ mod test {
         let content = "\n\n// Header comment\n\n\nfunction test() {\n    // Inside comment\n\n    let x = 1;\n\n\n    console.log(x);\n}\n\n// Footer comment\n\n\n";
         write_test_file(&file_path, content).await.unwrap();
 
-        let fs_replace = ApplyPatch;
+        let fs_replace = ApplyPatch::new(TempDir::guard());
 
         // Test 1: Replace content while preserving surrounding newlines
         let result = fs_replace
@@ -409,7 +453,7 @@ mod test {
 "#;
         write_test_file(&file_path, content).await.unwrap();
 
-        let fs_replace = ApplyPatch;
+        let fs_replace = ApplyPatch::new(TempDir::guard());
         // Search with different casing, spacing, and variable names
         let result = fs_replace
             .call(ApplyPatchInput {
@@ -453,7 +497,7 @@ mod test {
 "#;
         write_test_file(&file_path, content).await.unwrap();
 
-        let fs_replace = ApplyPatch;
+        let fs_replace = ApplyPatch::new(TempDir::guard());
         // Search with structural similarities but different variable names and spacing
         let result = fs_replace
             .call(ApplyPatchInput {
@@ -489,7 +533,7 @@ mod test {
 
         write_test_file(&file_path, content).await.unwrap();
 
-        let fs_replace = ApplyPatch;
+        let fs_replace = ApplyPatch::new(TempDir::guard());
         let result = fs_replace
             .call(ApplyPatchInput {
                 path: file_path.to_string_lossy().to_string(),
@@ -514,7 +558,7 @@ mod test {
 
         write_test_file(&file_path, content).await.unwrap();
 
-        let fs_replace = ApplyPatch;
+        let fs_replace = ApplyPatch::new(TempDir::guard());
         let result = fs_replace
             .call(ApplyPatchInput {
                 path: file_path.to_string_lossy().to_string(),
@@ -528,9 +572,147 @@ mod test {
         insta::assert_snapshot!(content);
     }
 
+    #[tokio::test]
+    async fn test_fuzzy_match_reports_confidence() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("total.rs");
+        let content =
+            "fn total(items: &[Item]) -> u32 {\n    items.iter().map(|item| item.price).sum()\n}\n";
+        write_test_file(&file_path, content).await.unwrap();
+
+        let result = ApplyPatch::new(TempDir::guard())
+            .call(ApplyPatchInput {
+                path: file_path.to_string_lossy().to_string(),
+                diff: format!("{SEARCH}\n    items.iter().map(|item| item.prices).sum()\n{DIVIDER}\n    items.iter().map(|item| item.price * item.quantity).sum()\n{REPLACE}\n"),
+            })
+            .await
+            .unwrap();
+
+        assert!(result.starts_with(
+            "<match block=\"1\" strategy=\"fuzzy\" confidence=\"0.99\" replacements=\"1\"/>"
+        ));
+        let actual = fs::read_to_string(&file_path).await.unwrap();
+        let expected = "fn total(items: &[Item]) -> u32 {\n    items.iter().map(|item| item.price * item.quantity).sum()\n}\n";
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_fuzzy_match_below_threshold() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("total.rs");
+        let content =
+            "fn total(items: &[Item]) -> u32 {\n    items.iter().map(|item| item.price).sum()\n}\n";
+        write_test_file(&file_path, content).await.unwrap();
+
+        let result = ApplyPatch::new(TempDir::guard())
+            .fuzzy_threshold(0.995)
+            .call(ApplyPatchInput {
+                path: file_path.to_string_lossy().to_string(),
+                diff: format!("{SEARCH}\n    items.iter().map(|item| item.prices).sum()\n{DIVIDER}\n    0\n{REPLACE}\n"),
+            })
+            .await;
+
+        let error = format!("{:#}", result.unwrap_err());
+        assert!(error.contains("no lines have a similarity of at least 0.995"));
+        assert_eq!(fs::read_to_string(&file_path).await.unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn test_fuzzy_match_ambiguous() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("main.rs");
+        let content = "let a = compute(1);\nlet b = other();\nlet a = compute(2);\n";
+        write_test_file(&file_path, content).await.unwrap();
+
+        let result = ApplyPatch::new(TempDir::guard())
+            .call(ApplyPatchInput {
+                path: file_path.to_string_lossy().to_string(),
+                diff: format!(
+                    "{SEARCH}\nlet a = compute(3);\n{DIVIDER}\nlet a = compute(4);\n{REPLACE}\n"
+                ),
+            })
+            .await;
+
+        let error = format!("{:#}", result.unwrap_err());
+        assert!(error.contains("matches lines 1 (0.95) and 3 (0.95)"));
+        assert_eq!(fs::read_to_string(&file_path).await.unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn test_normalized_match_ambiguous() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("main.py");
+        let content = "if a:\n    run()\nif b:\n        run()\n";
+        write_test_file(&file_path, content).await.unwrap();
+
+        let result = ApplyPatch::new(TempDir::guard())
+            .call(ApplyPatchInput {
+                path: file_path.to_string_lossy().to_string(),
+                diff: format!("{SEARCH}\n  run()  \n{DIVIDER}\n  stop()\n{REPLACE}\n"),
+            })
+            .await;
+
+        let error = format!("{:#}", result.unwrap_err());
+        assert!(error.contains("matches lines 2 and 4 once whitespace is ignored"));
+    }
+
+    #[tokio::test]
+    async fn test_replace_all_occurrences() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("main.js");
+        let content = "log(\"start\");\nrun();\nlog(\"start\");\n";
+        write_test_file(&file_path, content).await.unwrap();
+
+        let result = ApplyPatch::new(TempDir::guard())
+            .call(ApplyPatchInput {
+                path: file_path.to_string_lossy().to_string(),
+                diff: format!(
+                    "{SEARCH} all\nlog(\"start\");\n{DIVIDER}\ntrace(\"start\");\n{REPLACE}\n"
+                ),
+            })
+            .await
+            .unwrap();
+
+        assert!(result.starts_with(
+            "<match block=\"1\" strategy=\"exact\" confidence=\"1.00\" replacements=\"2\"/>"
+        ));
+        let actual = fs::read_to_string(&file_path).await.unwrap();
+        assert_eq!(actual, "trace(\"start\");\nrun();\ntrace(\"start\");\n");
+    }
+
+    #[tokio::test]
+    async fn test_replace_nth_occurrence() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("list.txt");
+        let content = "- item\n- item\n- item\n";
+        write_test_file(&file_path, content).await.unwrap();
+        let fs_replace = ApplyPatch::new(TempDir::guard());
+
+        fs_replace
+            .call(ApplyPatchInput {
+                path: file_path.to_string_lossy().to_string(),
+                diff: format!("{SEARCH} 2\n- item\n{DIVIDER}\n- second\n{REPLACE}\n"),
+            })
+            .await
+            .unwrap();
+        let result = fs_replace
+            .call(ApplyPatchInput {
+                path: file_path.to_string_lossy().to_string(),
+                diff: format!("{SEARCH} 3\n- item\n{DIVIDER}\n- third\n{REPLACE}\n"),
+            })
+            .await;
+
+        let actual = fs::read_to_string(&file_path).await.unwrap();
+        assert_eq!(actual, "- item\n- second\n- item\n");
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("the search text occurs 2 times, there is no occurrence 3"));
+    }
+
     #[tokio::test]
     async fn test_patch_relative_path() {
-        let fs_replace = ApplyPatch;
+        let fs_replace = ApplyPatch::new(TempDir::guard());
         let result = fs_replace
             .call(ApplyPatchInput {
                 path: "relative/path.txt".to_string(),
@@ -1,17 +1,16 @@
 use std::path::Path;
 
-// No longer using dissimilar for fuzzy matching
-use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_display::DiffFormat;
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
 use forge_tool_macros::ToolDescription;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tokio::fs;
 
+use super::matching::{find_close_match, Match, Strategy, DEFAULT_FUZZY_THRESHOLD};
+use super::parse::Occurrence;
+use crate::tools::fs::{Formatter, Notebook, TextFormat};
 use crate::tools::syn;
-use crate::tools::utils::assert_absolute_path;
-
-// Removed fuzzy matching threshold as we only use exact matching now
 
 /// A match found in the source text. Represents a range in the source text that
 /// can be used for extraction or replacement operations. Stores the position
@@ -42,7 +41,40 @@ impl Range {
             .map(|start| Self::new(start, search.len()))
     }
 
-    // Fuzzy matching removed - we only use exact matching
+    /// Finds the occurrences of the search text in the source. The first one
+    /// falls back to the lines closest to it, as the others are counted in
+    /// exact matches.
+    fn find(
+        source: &str,
+        search: &str,
+        occurrence: Occurrence,
+        threshold: f64,
+    ) -> anyhow::Result<(Vec<Self>, Match)> {
+        let matches = source
+            .match_indices(search)
+            .map(|(start, _)| Self::new(start, search.len()))
+            .collect::<Vec<_>>();
+        match occurrence {
+            Occurrence::All if !matches.is_empty() => {
+                let count = matches.len();
+                Ok((matches, Match::exact(count)))
+            }
+            Occurrence::Nth(n) if n <= matches.len() => Ok((vec![matches[n - 1]], Match::exact(1))),
+            Occurrence::Nth(1) => {
+                let (range, found) = find_close_match(source, search, threshold)?
+                    .ok_or_else(|| Error::NoMatch(search.to_string()))?;
+                Ok((vec![Self::new(range.start, range.len())], found))
+            }
+            Occurrence::Nth(n) => anyhow::bail!(
+                "The search text occurs {} times, there is no occurrence {n}",
+                matches.len()
+            ),
+            Occurrence::All => anyhow::bail!(
+                "The search text doesn't occur in the file, operating on all of its occurrences \
+                 requires an exact match"
+            ),
+        }
+    }
 }
 
 impl From<Range> for std::ops::Range<usize> {
@@ -51,27 +83,27 @@ impl From<Range> for std::ops::Range<usize> {
     }
 }
 
-// MatchSequence struct and implementation removed - we only use exact matching
-
 #[derive(Debug, Error)]
 enum Error {
-    #[error("Failed to read/write file: {0}")]
-    FileOperation(#[from] std::io::Error),
     #[error("Could not find match for search text: {0}")]
     NoMatch(String),
     #[error("Could not find swap target text: {0}")]
     NoSwapTarget(String),
 }
 
+/// Applies the operation to the occurrences of the search text in the source,
+/// returning how the search text was found along with the result.
 fn apply_replacement(
     source: String,
     search: &str,
     operation: &Operation,
     content: &str,
-) -> Result<String, Error> {
+    occurrence: Occurrence,
+    threshold: f64,
+) -> anyhow::Result<(String, Match)> {
     // Handle empty search string - only certain operations make sense here
     if search.is_empty() {
-        return match operation {
+        let result = match operation {
             // Append to the end of the file
             Operation::Append => Ok(format!("{}{}", source, content)),
             // Prepend to the beginning of the file
@@ -81,12 +113,30 @@ fn apply_replacement(
             // Swap doesn't make sense with empty search - keep source unchanged
             Operation::Swap => Ok(source),
         };
+        return result.map(|result| (result, Match::exact(1)));
+    }
+
+    if *operation == Operation::Swap && occurrence == Occurrence::All {
+        anyhow::bail!("Swap operates on a single occurrence of the search text");
     }
 
-    // Find the exact match to operate on
-    let patch =
-        Range::find_exact(&source, search).ok_or_else(|| Error::NoMatch(search.to_string()))?;
+    // Find the matches to operate on, applied from the last one so that the
+    // offsets of the others stay valid.
+    let (patches, found) = Range::find(&source, search, occurrence, threshold)?;
+    let mut result = source;
+    for patch in patches.into_iter().rev() {
+        result = apply_operation(&result, patch, operation, content)?;
+    }
+    Ok((result, found))
+}
 
+/// Applies the operation to the part of the source that was matched.
+fn apply_operation(
+    source: &str,
+    patch: Range,
+    operation: &Operation,
+    content: &str,
+) -> Result<String, Error> {
     // Apply the operation based on its type
     match operation {
         // Prepend content before the matched text
@@ -116,7 +166,7 @@ fn apply_replacement(
         // Swap with another text in the source
         Operation::Swap => {
             // Find the target text to swap with
-            let target_patch = Range::find_exact(&source, content)
+            let target_patch = Range::find_exact(source, content)
                 .ok_or_else(|| Error::NoSwapTarget(content.to_string()))?;
 
             // Handle the case where patches overlap
@@ -176,6 +226,33 @@ pub enum Operation {
     Swap,
 }
 
+/// The occurrences of the search text an operation applies to.
+#[derive(Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq)]
+#[serde(untagged)]
+pub enum OccurrenceInput {
+    /// The occurrence at this position, from 1
+    Nth(usize),
+    /// Every occurrence
+    Every(Every),
+}
+
+#[derive(Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Every {
+    All,
+}
+
+impl OccurrenceInput {
+    fn occurrence(input: Option<Self>) -> anyhow::Result<Occurrence> {
+        match input {
+            None => Ok(Occurrence::default()),
+            Some(Self::Nth(0)) => anyhow::bail!("The occurrence must be a number from 1 or `all`"),
+            Some(Self::Nth(n)) => Ok(Occurrence::Nth(n)),
+            Some(Self::Every(Every::All)) => Ok(Occurrence::All),
+        }
+    }
+}
+
 #[derive(Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct ApplyPatchJsonInput {
@@ -192,13 +269,63 @@ pub struct ApplyPatchJsonInput {
     /// The content to use for the operation (replacement text, text to
     /// prepend/append, or target text for swap operations)
     pub content: String,
+
+    /// If set to true, the file is left untouched and a unified diff of the
+    /// change that would be made is returned instead. Use it to review a
+    /// change before applying it.
+    #[serde(default)]
+    pub preview: bool,
+
+    /// The occurrence of the search text to operate on, from 1, or `all` to
+    /// operate on every occurrence, eg: to rename a variable in one call
+    /// (default: 1).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub occurrence: Option<OccurrenceInput>,
+
+    /// The index of the cell to edit in a Jupyter notebook (.ipynb), as shown
+    /// by the read tool. Without it, the first cell matching the search is
+    /// edited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cell: Option<usize>,
 }
 
 /// Performs a single text operation (prepend, append, replace, swap, delete) on
 /// matched text in a file. The operation is applied to the first match found in
-/// the text.
+/// the text, or to the occurrence set, or to all of them, reporting how many
+/// were changed. When the search text isn't in the file exactly, the lines closest
+/// to it are used if they're similar enough and no other lines are about as
+/// similar, and the output starts with how they matched and how confidently.
+/// In a Jupyter notebook, it's applied to the source of a cell, whose outputs
+/// and metadata are kept.
 #[derive(ToolDescription)]
-pub struct ApplyPatchJson;
+pub struct ApplyPatchJson {
+    guard: PathGuard,
+    format_on_write: bool,
+    fuzzy_threshold: f64,
+}
+
+impl ApplyPatchJson {
+    pub fn new(guard: PathGuard) -> Self {
+        Self {
+            guard,
+            format_on_write: false,
+            fuzzy_threshold: DEFAULT_FUZZY_THRESHOLD,
+        }
+    }
+
+    /// Formats the patched files with the formatter of their project.
+    pub fn format_on_write(mut self, format_on_write: bool) -> Self {
+        self.format_on_write = format_on_write;
+        self
+    }
+
+    /// Sets the minimum similarity, from 0 to 1, of the text replaced when the
+    /// search text isn't in the file exactly.
+    pub fn fuzzy_threshold(mut self, threshold: f64) -> Self {
+        self.fuzzy_threshold = threshold;
+        self
+    }
+}
 
 impl NamedTool for ApplyPatchJson {
     fn tool_name() -> ToolName {
@@ -222,23 +349,147 @@ fn format_output(path: &str, content: &str, warning: Option<&str>) -> String {
     }
 }
 
+/// Reports how the search text was found, unless it was found exactly.
+fn format_match(found: &Match) -> String {
+    if found.strategy == Strategy::Exact && found.replacements == 1 {
+        return String::new();
+    }
+    format!(
+        "<match strategy=\"{}\" confidence=\"{:.2}\" replacements=\"{}\"/>\n",
+        found.strategy, found.confidence, found.replacements
+    )
+}
+
+/// Applies the operation to the source of a cell of the notebook, the one
+/// given or else the first one matching the search, returning its index.
+fn patch_notebook(
+    notebook: &mut Notebook,
+    input: &ApplyPatchJsonInput,
+    threshold: f64,
+) -> anyhow::Result<(usize, Match)> {
+    let search = input.search.as_str();
+    let index = match input.cell {
+        Some(index) => index,
+        None if search.is_empty() => {
+            anyhow::bail!("Set the cell to edit in the notebook when the search is empty")
+        }
+        None => (0..notebook.cell_count())
+            .find(|index| {
+                notebook
+                    .source(*index)
+                    .is_ok_and(|source| source.contains(search))
+            })
+            .ok_or_else(|| Error::NoMatch(search.to_string()))?,
+    };
+    let (source, found) = apply_replacement(
+        notebook.source(index)?,
+        search,
+        &input.operation,
+        &input.content,
+        OccurrenceInput::occurrence(input.occurrence)?,
+        threshold,
+    )?;
+    notebook.set_source(index, &source)?;
+    Ok((index, found))
+}
+
 /// Process the file modification and return the formatted output
 async fn process_file_modifications(
     path: &Path,
-    search: &str,
-    operation: &Operation,
-    content: &str,
-) -> Result<String, Error> {
-    let file_content = fs::read_to_string(path).await?;
-    let file_content = apply_replacement(file_content, search, operation, content)?;
-    fs::write(path, &file_content).await?;
+    input: &ApplyPatchJsonInput,
+    threshold: f64,
+    format_on_write: bool,
+) -> anyhow::Result<String> {
+    // note: the file is written back with the encoding and line endings it was
+    // read with.
+    let (format, file_content) = TextFormat::read(path).await?;
+    if Notebook::is_notebook(path) {
+        let mut notebook = Notebook::parse(&file_content)?;
+        let (index, found) = patch_notebook(&mut notebook, input, threshold)?;
+        format.write(path, &notebook.to_json()?).await?;
+        let mut output = format_match(&found);
+        output.push_str(&format_output(
+            path.to_string_lossy().as_ref(),
+            &notebook.view(),
+            None,
+        ));
+        output.push_str(&format!("Cell {index} was edited.\n"));
+        return Ok(output);
+    }
+    let (file_content, found) = apply_replacement(
+        file_content,
+        &input.search,
+        &input.operation,
+        &input.content,
+        OccurrenceInput::occurrence(input.occurrence)?,
+        threshold,
+    )?;
+    format.write(path, &file_content).await?;
+
+    // note: the content returned is the formatted one, so that the next
+    // patches search for what's in the file.
+    let formatter = if format_on_write {
+        Formatter::format(path).await
+    } else {
+        None
+    };
+    let file_content = match formatter {
+        Some(_) => TextFormat::read(path).await?.1,
+        None => file_content,
+    };
 
     let warning = syn::validate(path, &file_content).map(|e| e.to_string());
-    Ok(format_output(
+    let mut output = format_match(&found);
+    output.push_str(&format_output(
         path.to_string_lossy().as_ref(),
         &file_content,
         warning.as_deref(),
-    ))
+    ));
+    if let Some(formatter) = formatter {
+        output.push_str(&format!("The file was formatted with {formatter}.\n"));
+    }
+    Ok(output)
+}
+
+/// Compute the file modification without persisting it and return the
+/// resulting unified diff
+async fn preview_file_modifications(
+    path: &Path,
+    input: &ApplyPatchJsonInput,
+    threshold: f64,
+) -> anyhow::Result<String> {
+    let (_, old_content) = TextFormat::read(path).await?;
+    if Notebook::is_notebook(path) {
+        let old = Notebook::parse(&old_content)?;
+        let mut new = old.clone();
+        let (_, found) = patch_notebook(&mut new, input, threshold)?;
+        let mut diff = format_match(&found);
+        diff.push_str(&DiffFormat::unified(
+            path.to_string_lossy().as_ref(),
+            &old.view(),
+            &new.view(),
+        ));
+        return Ok(diff);
+    }
+    let (new_content, found) = apply_replacement(
+        old_content.clone(),
+        &input.search,
+        &input.operation,
+        &input.content,
+        OccurrenceInput::occurrence(input.occurrence)?,
+        threshold,
+    )?;
+
+    let mut diff = format_match(&found);
+    diff.push_str(&DiffFormat::unified(
+        path.to_string_lossy().as_ref(),
+        &old_content,
+        &new_content,
+    ));
+    if let Some(warning) = syn::validate(path, &new_content) {
+        diff.push_str(&format!("\nWarning: {}", warning));
+    }
+    Ok(diff)
 }
 
 #[async_trait::async_trait]
@@ -246,20 +497,26 @@ impl ExecutableTool for ApplyPatchJson {
     type Input = ApplyPatchJsonInput;
 
     async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
-        let path = Path::new(&input.path);
-        assert_absolute_path(path)?;
+        let path = &self.guard.resolve(&input.path)?;
+        if input.cell.is_some() && !Notebook::is_notebook(path) {
+            anyhow::bail!("The cell can only be set to edit a Jupyter notebook (.ipynb)");
+        }
 
-        Ok(
-            process_file_modifications(path, &input.search, &input.operation, &input.content)
-                .await?,
-        )
+        if input.preview {
+            return preview_file_modifications(path, &input, self.fuzzy_threshold).await;
+        }
+
+        process_file_modifications(path, &input, self.fuzzy_threshold, self.format_on_write).await
     }
 }
 
 #[cfg(test)]
 mod test {
+    use pretty_assertions::assert_eq;
+    use tokio::fs;
 
     use super::*;
+    use crate::tools::utils::TempDir;
 
     // Enhanced test helper for running multiple operations
     #[derive(Debug)]
@@ -357,8 +614,10 @@ mod test {
                     &op_result.operation.search,
                     &op_result.operation.operation,
                     &op_result.operation.content,
+                    Occurrence::default(),
+                    DEFAULT_FUZZY_THRESHOLD,
                 ) {
-                    Ok(content) => {
+                    Ok((content, _)) => {
                         // Update the current content for the next operation
                         current_content = content.clone();
                         Ok(content)
@@ -421,4 +680,233 @@ mod test {
     }
 
     // The previous individual tests are removed since they're now consolidated
+
+    #[tokio::test]
+    async fn test_preview_leaves_file_untouched() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.txt");
+        fs::write(&path, "Hello World\n").await.unwrap();
+
+        let actual = ApplyPatchJson::new(TempDir::guard())
+            .call(ApplyPatchJsonInput {
+                path: path.to_string_lossy().to_string(),
+                search: "World".to_string(),
+                operation: Operation::Replace,
+                content: "Forge".to_string(),
+                preview: true,
+                occurrence: None,
+                cell: None,
+            })
+            .await
+            .unwrap();
+
+        assert!(actual.contains("-Hello World\n+Hello Forge\n"));
+        let content = fs::read_to_string(&path).await.unwrap();
+        assert_eq!(content, "Hello World\n");
+    }
+
+    #[tokio::test]
+    async fn test_patch_fuzzy_match_reports_confidence() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("total.rs");
+        fs::write(
+            &path,
+            "fn total(items: &[Item]) -> u32 {\n    items.iter().map(|item| item.price).sum()\n}\n",
+        )
+        .await
+        .unwrap();
+        let patch = || ApplyPatchJsonInput {
+            path: path.to_string_lossy().to_string(),
+            search: "    items.iter().map(|item| item.prices).sum()".to_string(),
+            operation: Operation::Replace,
+            content: "    items.iter().map(|item| item.price * item.quantity).sum()".to_string(),
+            preview: false,
+            occurrence: None,
+            cell: None,
+        };
+
+        let actual = ApplyPatchJson::new(TempDir::guard())
+            .fuzzy_threshold(0.995)
+            .call(patch())
+            .await
+            .unwrap_err();
+        assert!(actual
+            .to_string()
+            .starts_with("Could not find match for search text"));
+
+        let actual = ApplyPatchJson::new(TempDir::guard())
+            .call(patch())
+            .await
+            .unwrap();
+        assert!(actual
+            .starts_with("<match strategy=\"fuzzy\" confidence=\"0.99\" replacements=\"1\"/>\n"));
+        let actual = fs::read_to_string(&path).await.unwrap();
+        let expected = "fn total(items: &[Item]) -> u32 {\n    items.iter().map(|item| item.price * item.quantity).sum()\n}\n";
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_patch_fuzzy_match_ambiguous() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("main.rs");
+        let content = "let a = compute(1);\nlet b = other();\nlet a = compute(2);\n";
+        fs::write(&path, content).await.unwrap();
+
+        let actual = ApplyPatchJson::new(TempDir::guard())
+            .call(ApplyPatchJsonInput {
+                path: path.to_string_lossy().to_string(),
+                search: "let a = compute(3);".to_string(),
+                operation: Operation::Replace,
+                content: "let a = compute(4);".to_string(),
+                preview: false,
+                occurrence: None,
+                cell: None,
+            })
+            .await
+            .unwrap_err();
+
+        assert!(actual
+            .to_string()
+            .contains("matches lines 1 (0.95) and 3 (0.95)"));
+        assert_eq!(fs::read_to_string(&path).await.unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn test_patch_occurrences() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("main.js");
+        fs::write(&path, "log(\"start\");\nrun();\nlog(\"start\");\n")
+            .await
+            .unwrap();
+        let patch = |search: &str, content: &str, occurrence| ApplyPatchJsonInput {
+            path: path.to_string_lossy().to_string(),
+            search: search.to_string(),
+            operation: Operation::Replace,
+            content: content.to_string(),
+            preview: false,
+            occurrence: Some(occurrence),
+            cell: None,
+        };
+        let tool = ApplyPatchJson::new(TempDir::guard());
+
+        let actual = tool
+            .call(patch("log(", "trace(", OccurrenceInput::Every(Every::All)))
+            .await
+            .unwrap();
+        assert!(actual
+            .starts_with("<match strategy=\"exact\" confidence=\"1.00\" replacements=\"2\"/>\n"));
+        tool.call(patch("trace(", "debug(", OccurrenceInput::Nth(2)))
+            .await
+            .unwrap();
+        let actual = tool
+            .call(patch("trace(", "debug(", OccurrenceInput::Nth(2)))
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            actual.to_string(),
+            "The search text occurs 1 times, there is no occurrence 2"
+        );
+        let actual = fs::read_to_string(&path).await.unwrap();
+        assert_eq!(actual, "trace(\"start\");\nrun();\ndebug(\"start\");\n");
+    }
+
+    #[test]
+    fn test_occurrence_input() {
+        let actual =
+            [r#"2"#, r#""all""#].map(|json| serde_json::from_str::<OccurrenceInput>(json).unwrap());
+        let expected = [OccurrenceInput::Nth(2), OccurrenceInput::Every(Every::All)];
+        assert_eq!(actual, expected);
+        assert!(serde_json::from_str::<OccurrenceInput>(r#""first""#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_patch_keeps_crlf_line_endings() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("Program.cs");
+        fs::write(&path, "class Program\r\n{\r\n    int x = 1;\r\n}\r\n")
+            .await
+            .unwrap();
+
+        ApplyPatchJson::new(TempDir::guard())
+            .call(ApplyPatchJsonInput {
+                path: path.to_string_lossy().to_string(),
+                search: "{\n    int x = 1;".to_string(),
+                operation: Operation::Append,
+                content: "\n    int y = 2;".to_string(),
+                preview: false,
+                occurrence: None,
+                cell: None,
+            })
+            .await
+            .unwrap();
+
+        let content = fs::read(&path).await.unwrap();
+        assert_eq!(
+            content,
+            b"class Program\r\n{\r\n    int x = 1;\r\n    int y = 2;\r\n}\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_patch_notebook_cell() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("report.ipynb");
+        let notebook = serde_json::json!({
+            "cells": [
+                {"cell_type": "markdown", "metadata": {}, "source": ["x = 1"]},
+                {
+                    "cell_type": "code",
+                    "execution_count": 3,
+                    "metadata": {"scrolled": true},
+                    "outputs": [{"output_type": "stream", "name": "stdout", "text": ["1\n"]}],
+                    "source": ["x = 1\n", "print(x)"]
+                }
+            ],
+            "metadata": {},
+            "nbformat": 4,
+            "nbformat_minor": 5
+        });
+        fs::write(&path, notebook.to_string()).await.unwrap();
+        let patch = |cell| ApplyPatchJsonInput {
+            path: path.to_string_lossy().to_string(),
+            search: "x = 1".to_string(),
+            operation: Operation::Replace,
+            content: "x = 2".to_string(),
+            preview: false,
+            occurrence: None,
+            cell,
+        };
+
+        let actual = ApplyPatchJson::new(TempDir::guard())
+            .call(patch(Some(1)))
+            .await
+            .unwrap();
+        assert!(actual.ends_with("Cell 1 was edited.\n"), "{actual}");
+
+        let actual: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).await.unwrap()).unwrap();
+        let mut expected = notebook.clone();
+        expected["cells"][1]["source"] = serde_json::json!(["x = 2\n", "print(x)"]);
+        assert_eq!(actual, expected);
+
+        // note: without a cell, the first cell matching the search is edited.
+        ApplyPatchJson::new(TempDir::guard())
+            .call(patch(None))
+            .await
+            .unwrap();
+        let actual: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).await.unwrap()).unwrap();
+        expected["cells"][0]["source"] = serde_json::json!(["x = 2"]);
+        assert_eq!(actual, expected);
+
+        let actual = ApplyPatchJson::new(TempDir::guard())
+            .call(patch(Some(2)))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            actual.to_string(),
+            "Cell 2 doesn't exist, the notebook has 2 cells"
+        );
+    }
 }
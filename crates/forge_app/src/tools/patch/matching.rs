@@ -0,0 +1,168 @@
+//! Finds the part of a file a patch applies to when its search text isn't in
+//! the file exactly, eg: when the indentation or a few characters differ.
+
+use std::ops::Range;
+
+use anyhow::bail;
+use derive_more::Display;
+use dissimilar::Chunk;
+
+/// The minimum similarity of a fuzzy match, unless configured otherwise.
+pub const DEFAULT_FUZZY_THRESHOLD: f64 = 0.7;
+
+/// Fuzzy candidates scoring within this of the best one make the match
+/// ambiguous.
+const AMBIGUITY_EPSILON: f64 = 0.02;
+
+/// How the search text of a block was found in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Display)]
+pub enum Strategy {
+    #[display("exact")]
+    Exact,
+    /// The lines match once their leading and trailing whitespace is ignored
+    #[display("normalized")]
+    Normalized,
+    #[display("fuzzy")]
+    Fuzzy,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub strategy: Strategy,
+    /// The similarity of the matched text to the search one, from 0 to 1
+    pub confidence: f64,
+    /// The number of matches that were replaced
+    pub replacements: usize,
+}
+
+impl Match {
+    pub fn exact(replacements: usize) -> Self {
+        Self { strategy: Strategy::Exact, confidence: 1.0, replacements }
+    }
+}
+
+/// A line of the content, with the byte range of its text excluding the line
+/// ending and the offset of the next line.
+struct Line<'a> {
+    text: &'a str,
+    start: usize,
+    end: usize,
+    next: usize,
+}
+
+fn lines(content: &str) -> Vec<Line<'_>> {
+    let mut start = 0;
+    content
+        .split_inclusive('\n')
+        .map(|line| {
+            let text = line.trim_end_matches(['\n', '\r']);
+            let next = start + line.len();
+            let line = Line { text, start, end: start + text.len(), next };
+            start = next;
+            line
+        })
+        .collect()
+}
+
+/// The similarity of two texts from 0 to 1, as the share of their characters
+/// that are common.
+fn similarity(a: &str, b: &str) -> f64 {
+    let total = a.chars().count() + b.chars().count();
+    if total == 0 {
+        return 1.0;
+    }
+    let common: usize = dissimilar::diff(a, b)
+        .iter()
+        .map(|chunk| match chunk {
+            Chunk::Equal(text) => text.chars().count(),
+            Chunk::Delete(_) | Chunk::Insert(_) => 0,
+        })
+        .sum();
+    (2 * common) as f64 / total as f64
+}
+
+/// Finds the byte range of the lines of the content to replace, when it
+/// doesn't contain the search text exactly. None when no lines are similar
+/// enough, an error when several are about as similar.
+pub fn find_close_match(
+    content: &str,
+    search: &str,
+    threshold: f64,
+) -> anyhow::Result<Option<(Range<usize>, Match)>> {
+    let lines = lines(content);
+    let needle = search.trim_end_matches(['\n', '\r']);
+    let needle_lines = needle.lines().map(str::trim).collect::<Vec<_>>();
+    let count = needle_lines.len();
+    if count == 0 || lines.len() < count {
+        return Ok(None);
+    }
+
+    // note: the line ending is only replaced when the search text has one, so
+    // that the surrounding lines aren't joined.
+    let range = |first: usize| {
+        let last = &lines[first + count - 1];
+        let end = if search.ends_with('\n') {
+            last.next
+        } else {
+            last.end
+        };
+        lines[first].start..end
+    };
+
+    let normalized = (0..=lines.len() - count)
+        .filter(|&first| {
+            lines[first..first + count]
+                .iter()
+                .zip(&needle_lines)
+                .all(|(line, needle)| line.text.trim() == *needle)
+        })
+        .collect::<Vec<_>>();
+    match normalized.as_slice() {
+        [first] => {
+            let strategy = Strategy::Normalized;
+            return Ok(Some((
+                range(*first),
+                Match { strategy, confidence: 1.0, replacements: 1 },
+            )));
+        }
+        [first, second, ..] => bail!(
+            "The search text matches lines {} and {} once whitespace is ignored, add more \
+             context to select one",
+            first + 1,
+            second + 1
+        ),
+        [] => {}
+    }
+
+    let mut candidates = (0..=lines.len() - count)
+        .map(|first| {
+            let window = &content[lines[first].start..lines[first + count - 1].end];
+            (first, similarity(window, needle))
+        })
+        .filter(|(_, score)| *score >= threshold)
+        .collect::<Vec<_>>();
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let Some(&(best, confidence)) = candidates.first() else {
+        return Ok(None);
+    };
+    // note: windows overlapping the best one are shifted copies of the same
+    // match rather than another candidate.
+    if let Some((other, score)) = candidates
+        .iter()
+        .find(|(first, _)| first.abs_diff(best) >= count)
+        .filter(|(_, score)| confidence - score <= AMBIGUITY_EPSILON)
+    {
+        bail!(
+            "The search text matches lines {} ({:.2}) and {} ({:.2}) about as closely, add more \
+             context to select one",
+            best + 1,
+            confidence,
+            other + 1,
+            score
+        );
+    }
+    Ok(Some((
+        range(best),
+        Match { strategy: Strategy::Fuzzy, confidence, replacements: 1 },
+    )))
+}
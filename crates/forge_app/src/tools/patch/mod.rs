@@ -1,6 +1,8 @@
 mod apply;
 mod apply_json;
 mod marker;
+mod matching;
 mod parse;
 
 pub use apply_json::ApplyPatchJson;
+pub use matching::DEFAULT_FUZZY_THRESHOLD;
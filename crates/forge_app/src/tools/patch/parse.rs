@@ -1,6 +1,6 @@
 use nom::bytes::complete::{tag, take_until};
-use nom::character::complete::line_ending;
-use nom::combinator::{map, verify};
+use nom::character::complete::{line_ending, not_line_ending};
+use nom::combinator::{map, map_opt, verify};
 use nom::error::ErrorKind;
 use nom::multi::many0;
 use nom::sequence::delimited;
@@ -33,12 +33,42 @@ pub enum Kind {
     Incomplete,
     #[error("Invalid marker position - must start at beginning of line")]
     InvalidMarkerPosition,
+    #[error("Invalid occurrence after SEARCH marker - must be a number from 1 or `all`")]
+    Occurrence,
+}
+
+/// The matches of the search text a block replaces, set after the SEARCH
+/// marker, eg: `<<<<<<< SEARCH 2` or `<<<<<<< SEARCH all`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Occurrence {
+    /// The match at this position, from 1
+    Nth(usize),
+    All,
+}
+
+impl Default for Occurrence {
+    fn default() -> Self {
+        Self::Nth(1)
+    }
+}
+
+impl Occurrence {
+    /// Parses the text following the SEARCH marker, which is empty for the
+    /// first match.
+    fn parse(text: &str) -> Option<Self> {
+        match text.trim() {
+            "" => Some(Self::default()),
+            "all" => Some(Self::All),
+            n => n.parse().ok().filter(|n| *n > 0).map(Self::Nth),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
 pub struct PatchBlock {
     pub search: String,
     pub replace: String,
+    pub occurrence: Occurrence,
 }
 
 /// Verify input starts with a newline or is at start of input
@@ -46,14 +76,17 @@ fn ensure_line_start(input: &str) -> bool {
     input.is_empty() || input.starts_with('\n') || input.len() == input.trim_start().len()
 }
 
-fn parse_search_marker(input: &str) -> IResult<&str, ()> {
-    map(
+fn parse_search_marker(input: &str) -> IResult<&str, Occurrence> {
+    map_opt(
         delimited(
-            verify(take_until(SEARCH), |s: &str| ensure_line_start(s)),
-            tag(SEARCH),
+            (
+                verify(take_until(SEARCH), |s: &str| ensure_line_start(s)),
+                tag(SEARCH),
+            ),
+            not_line_ending,
             line_ending,
         ),
-        |_| (),
+        Occurrence::parse,
     )
     .parse(input)
 }
@@ -99,7 +132,7 @@ fn parse_patch_block(input: &str) -> IResult<&str, PatchBlock> {
             parse_replace_content,
             parse_replace_marker,
         ),
-        |(_, search, _, replace, _)| PatchBlock { search, replace },
+        |(occurrence, search, _, replace, _)| PatchBlock { search, replace, occurrence },
     )
     .parse(input)
 }
@@ -113,9 +146,16 @@ fn parse_one_block(input: &str, position: usize) -> Result<(&str, PatchBlock), E
         if !(ensure_line_start(&input[search_idx..]) || position == 1) {
             return Err(Error::Block { position, kind: Kind::InvalidMarkerPosition });
         }
-        if !input[search_idx..].contains(&format!("{SEARCH}\n")) {
+        // note: the marker can be followed by the occurrence to replace,
+        // separated by a space.
+        let marker = &input[search_idx + SEARCH.len()..];
+        let occurrence = marker.split('\n').next().unwrap_or_default();
+        if !marker.contains('\n') || !(occurrence.is_empty() || occurrence.starts_with(' ')) {
             return Err(Error::Block { position, kind: Kind::SearchNewline });
         }
+        if Occurrence::parse(occurrence).is_none() {
+            return Err(Error::Block { position, kind: Kind::Occurrence });
+        }
     }
 
     if let Some(divider_idx) = input.find(DIVIDER) {
@@ -367,6 +407,34 @@ mod test {
             Error::Block { position: 1, kind: Kind::SeparatorNewline }
         ));
     }
+
+    #[test]
+    fn test_occurrence() {
+        let diff = format!(
+            "{SEARCH} all\nold_name\n{DIVIDER}\nnew_name\n{REPLACE}\n{SEARCH} 2\nx\n{DIVIDER}\ny\n{REPLACE}\n{SEARCH}\na\n{DIVIDER}\nb\n{REPLACE}\n"
+        );
+        let result = parse_blocks(&diff).unwrap();
+        let actual = result
+            .iter()
+            .map(|block| block.occurrence)
+            .collect::<Vec<_>>();
+        let expected = vec![Occurrence::All, Occurrence::Nth(2), Occurrence::Nth(1)];
+        assert_eq!(actual, expected);
+        assert_eq!(result[0].search, "old_name\n");
+    }
+
+    #[test]
+    fn test_invalid_occurrence() {
+        for occurrence in ["0", "first", "-1"] {
+            let diff = format!("{SEARCH} {occurrence}\nold\n{DIVIDER}\nnew\n{REPLACE}\n");
+            let result = parse_blocks(&diff);
+            assert!(matches!(
+                result.unwrap_err(),
+                Error::Block { position: 1, kind: Kind::Occurrence }
+            ));
+        }
+    }
+
     #[test]
     fn test_multiple_blocks_without_newline_end() {
         let diff = "<<<<<<< SEARCH\nhello\nhi\n=======\nhey\nhello\nhola\n>>>>>>> REPLACE\n\n<<<<<<< SEARCH\n            hola,\n=======\n            hey\n>>>>>>> REPLACE";
@@ -0,0 +1,334 @@
+//! Host functions the plugins import from the `forge` module. They take
+//! strings by their pointer and length and return `{"ok": ...}` or
+//! `{"error": "..."}`, packed the same way as the exports of the plugins:
+//!
+//! - `fs_read(path) -> i64` returns the content of a file.
+//! - `fs_write(path, content) -> i64` writes a file, creating its directories.
+//! - `http_fetch(request) -> i64` sends `{"method": "GET", "url": "...",
+//!   "headers": {...}, "body": "..."}` and returns `{"status": 200, "body":
+//!   "..."}`.
+//! - `log(message)` logs a message of the plugin.
+//!
+//! The paths are relative to the cwd or absolute, confined to the cwd and the
+//! allowed paths like the ones of the file system tools, and the requests are
+//! limited to the allowed hosts.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use forge_domain::PathGuard;
+use reqwest::{redirect, Client, Method, Url};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use wasmtime::{
+    AsContext, AsContextMut, Caller, Extern, Instance, Linker, Memory, Store, StoreLimits,
+    TypedFunc,
+};
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Redirects a request follows, like the default policy of reqwest.
+const MAX_REDIRECTS: usize = 10;
+
+/// What the plugins can access.
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+    /// Confines the files to the cwd and the allowed paths
+    pub guard: PathGuard,
+    /// Hosts the plugins can send requests to, eg: `api.github.com` or
+    /// `*.atlassian.net`
+    pub allowed_hosts: Vec<String>,
+}
+
+/// State of an instance of a plugin.
+pub struct Host {
+    capabilities: Arc<Capabilities>,
+    pub limits: StoreLimits,
+}
+
+#[derive(Debug, Deserialize)]
+struct HttpRequest {
+    #[serde(default = "default_method")]
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+impl Host {
+    pub fn new(capabilities: Arc<Capabilities>, limits: StoreLimits) -> Self {
+        Self { capabilities, limits }
+    }
+
+    fn resolve(&self, path: &str) -> Result<std::path::PathBuf> {
+        let guard = &self.capabilities.guard;
+        guard.resolve(guard.cwd().join(Path::new(path)))
+    }
+
+    fn fs_read(&self, path: &str) -> Result<Value> {
+        let path = self.resolve(path)?;
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(json!(content))
+    }
+
+    fn fs_write(&self, path: &str, content: &str) -> Result<Value> {
+        let path = self.resolve(path)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(Value::Null)
+    }
+
+    fn http_fetch(&self, request: &str) -> Result<Value> {
+        let request: HttpRequest = serde_json::from_str(request)?;
+        let url = Url::parse(&request.url)?;
+        let host = url.host_str().context("The URL has no host")?;
+        let allowed_hosts = self.capabilities.allowed_hosts.clone();
+        if !allowed(host, &allowed_hosts) {
+            bail!(denied(host))
+        }
+        // note: the redirects are checked too, otherwise an allowed host could
+        // send the request to any other one.
+        let policy = redirect::Policy::custom(move |attempt| {
+            let host = attempt.url().host_str().unwrap_or_default().to_string();
+            if !allowed(&host, &allowed_hosts) {
+                attempt.error(denied(&host))
+            } else if attempt.previous().len() > MAX_REDIRECTS {
+                attempt.error("Too many redirects")
+            } else {
+                attempt.follow()
+            }
+        });
+        let client = Client::builder().redirect(policy).build()?;
+        let mut builder = client
+            .request(Method::from_bytes(request.method.as_bytes())?, url)
+            .timeout(HTTP_TIMEOUT);
+        for (name, value) in request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+        // note: the plugins run on the blocking threads of the runtime.
+        tokio::runtime::Handle::current().block_on(async {
+            let response = builder.send().await?;
+            let status = response.status().as_u16();
+            let body = response.text().await?;
+            Ok(json!({"status": status, "body": body}))
+        })
+    }
+}
+
+fn denied(host: &str) -> String {
+    format!("Access denied: {host} isn't one of the allowed hosts of the plugins. Add it to `plugins.allowed_hosts` in the config to allow it.")
+}
+
+/// Whether the host is one of the allowed hosts, which match their
+/// subdomains when they start with `*.`.
+fn allowed(host: &str, allowed_hosts: &[String]) -> bool {
+    allowed_hosts
+        .iter()
+        .any(|allowed| match allowed.strip_prefix("*.") {
+            Some(domain) => host
+                .strip_suffix(domain)
+                .is_some_and(|subdomain| subdomain.ends_with('.')),
+            None => host.eq_ignore_ascii_case(allowed),
+        })
+}
+
+/// The memory of a plugin along with its allocator.
+pub struct Guest {
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+}
+
+impl Guest {
+    pub fn of_instance(store: &mut Store<Host>, instance: &Instance) -> Result<Self> {
+        let memory = instance
+            .get_memory(&mut *store, "memory")
+            .context("The plugin doesn't export its memory")?;
+        let alloc = instance.get_typed_func(&mut *store, "alloc")?;
+        Ok(Self { memory, alloc })
+    }
+
+    fn of_caller(caller: &mut Caller<'_, Host>) -> Result<Self> {
+        let memory = caller
+            .get_export("memory")
+            .and_then(Extern::into_memory)
+            .context("The plugin doesn't export its memory")?;
+        let alloc = caller
+            .get_export("alloc")
+            .and_then(Extern::into_func)
+            .context("The plugin doesn't export `alloc`")?
+            .typed(&*caller)?;
+        Ok(Self { memory, alloc })
+    }
+
+    /// Reads the bytes at the packed pointer and length.
+    pub fn read(&self, store: impl AsContext, packed: i64) -> Result<Vec<u8>> {
+        let (ptr, len) = ((packed as u64 >> 32) as usize, packed as u32 as usize);
+        if ptr + len > self.memory.data_size(&store) {
+            bail!("The plugin returned bytes outside of its memory")
+        }
+        let mut bytes = vec![0; len];
+        self.memory.read(&store, ptr, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Writes the bytes to memory the plugin allocates, returning their
+    /// pointer and length.
+    pub fn write(&self, mut store: impl AsContextMut, bytes: &[u8]) -> Result<(i32, i32)> {
+        let len = i32::try_from(bytes.len())?;
+        let ptr = self.alloc.call(&mut store, len)?;
+        self.memory.write(&mut store, ptr as u32 as usize, bytes)?;
+        Ok((ptr, len))
+    }
+
+    fn read_str(&self, store: impl AsContext, ptr: i32, len: i32) -> Result<String> {
+        Ok(String::from_utf8(self.read(store, pack(ptr, len))?)?)
+    }
+}
+
+fn pack(ptr: i32, len: i32) -> i64 {
+    (((ptr as u32 as u64) << 32) | len as u32 as u64) as i64
+}
+
+/// Runs the host function on the strings the plugin passed and writes its
+/// result back. Its failures are returned to the plugin rather than trapping
+/// it, eg: a path outside of the cwd.
+fn respond(
+    mut caller: Caller<'_, Host>,
+    args: &[(i32, i32)],
+    f: impl FnOnce(&Host, &[String]) -> Result<Value>,
+) -> Result<i64> {
+    let guest = Guest::of_caller(&mut caller)?;
+    let args = args
+        .iter()
+        .map(|&(ptr, len)| guest.read_str(&caller, ptr, len))
+        .collect::<Result<Vec<_>>>()?;
+    let result = match f(caller.data(), &args) {
+        Ok(value) => json!({"ok": value}),
+        Err(error) => json!({"error": format!("{error:#}")}),
+    };
+    let (ptr, len) = guest.write(&mut caller, &serde_json::to_vec(&result)?)?;
+    Ok(pack(ptr, len))
+}
+
+pub fn link(linker: &mut Linker<Host>) -> Result<()> {
+    linker.func_wrap(
+        "forge",
+        "fs_read",
+        |caller: Caller<'_, Host>, ptr: i32, len: i32| {
+            respond(caller, &[(ptr, len)], |host, args| host.fs_read(&args[0]))
+        },
+    )?;
+    linker.func_wrap(
+        "forge",
+        "fs_write",
+        |caller: Caller<'_, Host>, path_ptr: i32, path_len: i32, ptr: i32, len: i32| {
+            respond(caller, &[(path_ptr, path_len), (ptr, len)], |host, args| {
+                host.fs_write(&args[0], &args[1])
+            })
+        },
+    )?;
+    linker.func_wrap(
+        "forge",
+        "http_fetch",
+        |caller: Caller<'_, Host>, ptr: i32, len: i32| {
+            respond(caller, &[(ptr, len)], |host, args| {
+                host.http_fetch(&args[0])
+            })
+        },
+    )?;
+    linker.func_wrap(
+        "forge",
+        "log",
+        |mut caller: Caller<'_, Host>, ptr: i32, len: i32| -> Result<()> {
+            let guest = Guest::of_caller(&mut caller)?;
+            let message = guest.read_str(&caller, ptr, len)?;
+            tracing::info!(message = %message, "Plugin");
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_allowed() {
+        let allowed_hosts = vec!["api.github.com".to_string(), "*.atlassian.net".to_string()];
+        let actual = [
+            "api.github.com",
+            "acme.atlassian.net",
+            "atlassian.net",
+            "evilatlassian.net",
+            "github.com",
+        ]
+        .map(|host| allowed(host, &allowed_hosts));
+        assert_eq!(actual, [true, true, false, false, false]);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let dir = tempfile::tempdir().unwrap();
+        let host = Host::new(
+            Arc::new(Capabilities { guard: PathGuard::new(dir.path()), allowed_hosts: Vec::new() }),
+            StoreLimits::default(),
+        );
+
+        host.fs_write("notes/cat.txt", "Juniper").unwrap();
+        assert_eq!(host.fs_read("notes/cat.txt").unwrap(), json!("Juniper"));
+        assert!(host.fs_read("../outside.txt").is_err());
+        assert!(host.fs_read("/etc/passwd").is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_http_fetch_redirect_to_other_host() {
+        let mut server = mockito::Server::new_async().await;
+        let port = server.socket_address().port();
+        server
+            .mock("GET", "/issues")
+            .with_status(302)
+            .with_header("location", &format!("http://localhost:{port}/secrets"))
+            .create_async()
+            .await;
+        let secrets = server
+            .mock("GET", "/secrets")
+            .expect(0)
+            .create_async()
+            .await;
+        let host = Host::new(
+            Arc::new(Capabilities {
+                guard: PathGuard::new(std::env::temp_dir()),
+                allowed_hosts: vec!["127.0.0.1".to_string()],
+            }),
+            StoreLimits::default(),
+        );
+
+        let request = json!({"url": format!("http://127.0.0.1:{port}/issues")}).to_string();
+        let actual = tokio::task::spawn_blocking(move || host.http_fetch(&request))
+            .await
+            .unwrap()
+            .map_err(|error| format!("{error:#}"));
+
+        assert!(actual.unwrap_err().contains(&denied("localhost")));
+        secrets.assert_async().await;
+    }
+}
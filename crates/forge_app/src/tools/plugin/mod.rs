@@ -0,0 +1,250 @@
+//! Tools of the WASM plugins of the plugins directory, eg:
+//! `~/.config/forge/plugins/jira.wasm`, so that tools can be added without
+//! recompiling forge.
+//!
+//! A plugin exports its `memory` along with these functions, which pass JSON
+//! by the pointer and the length of its bytes, packed in an `i64` as
+//! `ptr << 32 | len` when returned:
+//!
+//! - `alloc(len: i32) -> i32` reserves the memory the host writes to.
+//! - `describe() -> i64` returns its tools, eg: `[{"name": "search",
+//!   "description": "...", "input_schema": {...}}]`.
+//! - `call(ptr: i32, len: i32) -> i64` runs `{"name": "search", "arguments":
+//!   {...}}` and returns `{"content": "...", "is_error": false}`.
+//!
+//! The tools are named after the plugin, eg: `tool_plugin_jira_search`. A
+//! plugin runs in a sandbox, instantiated afresh for every call, with no
+//! access to the system but the host functions of the `forge` module.
+
+mod host;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Context;
+use forge_domain::{ExecutableTool, Tool, ToolDefinition, ToolName};
+use schemars::schema::RootSchema;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use wasmtime::{Engine, Instance, Linker, Module, Store, StoreLimitsBuilder};
+
+pub use host::Capabilities;
+use host::{Guest, Host};
+
+/// Instructions a call can run, so that a plugin stuck in a loop fails
+/// rather than holding a thread until the timeout of the tools.
+const FUEL: u64 = 10_000_000_000;
+/// Bytes of memory a plugin can grow to.
+const MAX_MEMORY: usize = 256 * 1024 * 1024;
+
+/// A tool a plugin describes.
+#[derive(Debug, Deserialize)]
+struct Description {
+    name: String,
+    #[serde(default)]
+    description: String,
+    input_schema: RootSchema,
+}
+
+#[derive(Debug, Deserialize)]
+struct Output {
+    content: String,
+    #[serde(default)]
+    is_error: bool,
+}
+
+#[derive(Clone)]
+struct Plugin {
+    name: String,
+    engine: Engine,
+    module: Module,
+    capabilities: Arc<Capabilities>,
+}
+
+impl Plugin {
+    fn load(path: &Path, engine: &Engine, capabilities: Arc<Capabilities>) -> anyhow::Result<Self> {
+        let name = path
+            .file_stem()
+            .map(|stem| {
+                stem.to_string_lossy()
+                    .chars()
+                    .map(|c| {
+                        if c.is_ascii_alphanumeric() {
+                            c.to_ascii_lowercase()
+                        } else {
+                            '_'
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .context("The plugin has no name")?;
+        let module = Module::from_file(engine, path)?;
+        Ok(Self { name, engine: engine.clone(), module, capabilities })
+    }
+
+    /// Instantiates the plugin and runs `f` on it.
+    fn run<R>(
+        &self,
+        f: impl FnOnce(&mut Store<Host>, &Instance) -> anyhow::Result<R>,
+    ) -> anyhow::Result<R> {
+        let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY).build();
+        let mut store = Store::new(&self.engine, Host::new(self.capabilities.clone(), limits));
+        store.limiter(|host| &mut host.limits);
+        store.set_fuel(FUEL)?;
+        let mut linker = Linker::new(&self.engine);
+        host::link(&mut linker)?;
+        let instance = linker.instantiate(&mut store, &self.module)?;
+        f(&mut store, &instance)
+    }
+
+    fn describe(&self) -> anyhow::Result<Vec<Description>> {
+        self.run(|store, instance| {
+            let guest = Guest::of_instance(store, instance)?;
+            let describe = instance.get_typed_func::<(), i64>(&mut *store, "describe")?;
+            let packed = describe.call(&mut *store, ())?;
+            Ok(serde_json::from_slice(&guest.read(&*store, packed)?)?)
+        })
+    }
+
+    fn call(&self, name: &str, arguments: Value) -> anyhow::Result<Output> {
+        let input = serde_json::to_vec(&json!({"name": name, "arguments": arguments}))?;
+        self.run(|store, instance| {
+            let guest = Guest::of_instance(store, instance)?;
+            let (ptr, len) = guest.write(&mut *store, &input)?;
+            let call = instance.get_typed_func::<(i32, i32), i64>(&mut *store, "call")?;
+            let packed = call.call(&mut *store, (ptr, len))?;
+            Ok(serde_json::from_slice(&guest.read(&*store, packed)?)?)
+        })
+    }
+}
+
+/// A tool of a plugin.
+struct PluginTool {
+    plugin: Plugin,
+    name: String,
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for PluginTool {
+    type Input = Value;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let plugin = self.plugin.clone();
+        let name = self.name.clone();
+        // note: the plugin runs on a blocking thread, since its host
+        // functions block on the file system and the network.
+        let output = tokio::task::spawn_blocking(move || plugin.call(&name, input))
+            .await?
+            .with_context(|| format!("The plugin {} failed", self.plugin.name))?;
+        if output.is_error {
+            anyhow::bail!(output.content)
+        }
+        Ok(output.content)
+    }
+}
+
+/// Loads the tools of the plugins of `dir`. The plugins that fail to load are
+/// skipped, so that a broken one doesn't take the others down.
+pub fn plugins(dir: &Path, capabilities: Capabilities) -> Vec<Tool> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "wasm")
+        })
+        .collect::<Vec<_>>();
+    if paths.is_empty() {
+        return Vec::new();
+    }
+    paths.sort();
+
+    let mut config = wasmtime::Config::new();
+    config.consume_fuel(true);
+    let engine = match Engine::new(&config) {
+        Ok(engine) => engine,
+        Err(error) => {
+            tracing::error!(error = %error, "Failed to start the WASM engine of the plugins");
+            return Vec::new();
+        }
+    };
+    let capabilities = Arc::new(capabilities);
+    paths
+        .iter()
+        .flat_map(|path| {
+            let tools = Plugin::load(path, &engine, capabilities.clone()).and_then(|plugin| {
+                let descriptions = plugin.describe()?;
+                Ok(descriptions
+                    .into_iter()
+                    .map(|description| tool(plugin.clone(), description))
+                    .collect::<Vec<_>>())
+            });
+            tools.unwrap_or_else(|error| {
+                tracing::error!(plugin = %path.display(), error = ?error, "Failed to load the plugin");
+                Vec::new()
+            })
+        })
+        .collect()
+}
+
+fn tool(plugin: Plugin, description: Description) -> Tool {
+    let definition = ToolDefinition {
+        name: ToolName::new(format!("tool_plugin_{}_{}", plugin.name, description.name)),
+        description: description.description,
+        input_schema: description.input_schema,
+        output_schema: None,
+    };
+    Tool {
+        executable: Box::new(PluginTool { plugin, name: description.name }),
+        definition,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_domain::PathGuard;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// A plugin with a `meow` tool, which replies the same whatever its
+    /// arguments.
+    const CAT: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 1024))
+  (data (i32.const 0) "[{\22name\22:\22meow\22,\22description\22:\22Meows\22,\22input_schema\22:{\22type\22:\22object\22}}]")
+  (data (i32.const 512) "{\22content\22:\22Meow\22,\22is_error\22:false}")
+  (func (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (global.get $next) (local.get $len)))
+    (local.get $ptr))
+  (func (export "describe") (result i64)
+    (i64.const 72))
+  (func (export "call") (param $ptr i32) (param $len i32) (result i64)
+    (i64.or (i64.shl (i64.const 512) (i64.const 32)) (i64.const 35))))
+"#;
+
+    #[tokio::test]
+    async fn test_plugins() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("cat.wasm"), wat::parse_str(CAT).unwrap()).unwrap();
+        std::fs::write(dir.path().join("broken.wasm"), "not a module").unwrap();
+        let capabilities =
+            Capabilities { guard: PathGuard::new(dir.path()), allowed_hosts: Vec::new() };
+
+        let tools = plugins(dir.path(), capabilities);
+        let names = tools
+            .iter()
+            .map(|tool| tool.definition.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["tool_plugin_cat_meow"]);
+        assert_eq!(tools[0].definition.description, "Meows");
+
+        let actual = tools[0].executable.call(json!({})).await.unwrap();
+        assert_eq!(actual, "Meow");
+    }
+}
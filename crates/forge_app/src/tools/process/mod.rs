@@ -0,0 +1,11 @@
+mod process_kill;
+mod process_logs;
+mod process_start;
+mod process_status;
+mod registry;
+
+pub use process_kill::*;
+pub use process_logs::*;
+pub use process_start::*;
+pub use process_status::*;
+pub use registry::*;
@@ -0,0 +1,65 @@
+use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::ProcessRegistry;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ProcessKillInput {
+    /// The id of the background process to stop.
+    pub process_id: u64,
+}
+
+/// Stops a background process started with the process start tool, including
+/// every process it spawned. Its logs remain readable afterwards.
+#[derive(ToolDescription)]
+pub struct ProcessKill {
+    registry: ProcessRegistry,
+}
+
+impl ProcessKill {
+    pub fn new(registry: ProcessRegistry) -> Self {
+        Self { registry }
+    }
+}
+
+impl NamedTool for ProcessKill {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_process_kill")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for ProcessKill {
+    type Input = ProcessKillInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let info = self.registry.kill(input.process_id).await?;
+        Ok(info.to_string())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    #[tokio::test]
+    async fn test_kill_process() {
+        let temp_dir = TempDir::new().unwrap();
+        let registry = ProcessRegistry::default();
+        let id = registry
+            .start("/bin/sh", "sleep 30", &temp_dir.path(), false)
+            .await
+            .unwrap();
+        let kill = ProcessKill::new(registry);
+
+        let result = kill
+            .call(ProcessKillInput { process_id: id })
+            .await
+            .unwrap();
+
+        assert!(result.starts_with("[1] killed"));
+    }
+}
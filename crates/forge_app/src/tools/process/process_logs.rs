@@ -0,0 +1,123 @@
+use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::ProcessRegistry;
+
+/// Number of lines returned when neither an offset nor a tail is given.
+const DEFAULT_TAIL: usize = 200;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ProcessLogsInput {
+    /// The id of the background process.
+    pub process_id: u64,
+    /// Line offset to read from. Pass the `next_offset` of a previous call to
+    /// only receive new output.
+    #[serde(default)]
+    pub offset: Option<usize>,
+    /// Only return the last N lines (default: 200 when no offset is given).
+    #[serde(default)]
+    pub tail: Option<usize>,
+}
+
+/// Reads the combined stdout and stderr of a background process started with
+/// the process start tool. Output can be polled incrementally using the
+/// returned next offset.
+#[derive(ToolDescription)]
+pub struct ProcessLogs {
+    registry: ProcessRegistry,
+}
+
+impl ProcessLogs {
+    pub fn new(registry: ProcessRegistry) -> Self {
+        Self { registry }
+    }
+}
+
+impl NamedTool for ProcessLogs {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_process_logs")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for ProcessLogs {
+    type Input = ProcessLogsInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let tail = match input.offset {
+            Some(_) => input.tail,
+            None => Some(input.tail.unwrap_or(DEFAULT_TAIL)),
+        };
+        let logs = self
+            .registry
+            .logs(input.process_id, input.offset.unwrap_or_default(), tail)
+            .await?;
+        let state = self.registry.status(Some(input.process_id)).await?[0].state;
+
+        let mut output = format!(
+            "<process id=\"{}\" state=\"{}\" start=\"{}\" next_offset=\"{}\">\n",
+            input.process_id, state, logs.start, logs.next_offset
+        );
+        for line in logs.lines {
+            output.push_str(&line);
+            output.push('\n');
+        }
+        output.push_str("</process>");
+
+        Ok(output)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::tools::process::ProcessState;
+    use crate::tools::utils::TempDir;
+
+    #[tokio::test]
+    async fn test_logs_of_finished_process() {
+        let temp_dir = TempDir::new().unwrap();
+        let registry = ProcessRegistry::default();
+        let id = registry
+            .start(
+                "/bin/sh",
+                "echo ready; echo failed >&2",
+                &temp_dir.path(),
+                false,
+            )
+            .await
+            .unwrap();
+        while registry.status(Some(id)).await.unwrap()[0].state == ProcessState::Running {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let logs = ProcessLogs::new(registry);
+
+        let result = logs
+            .call(ProcessLogsInput { process_id: id, offset: Some(2), tail: None })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result,
+            "<process id=\"1\" state=\"exited with code 0\" start=\"2\" next_offset=\"2\">\n</process>"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_logs_unknown_process() {
+        let logs = ProcessLogs::new(ProcessRegistry::default());
+
+        let result = logs
+            .call(ProcessLogsInput { process_id: 1, offset: None, tail: None })
+            .await;
+
+        assert!(result.is_err());
+    }
+}
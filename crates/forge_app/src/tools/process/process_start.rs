@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+
+use anyhow::bail;
+use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::ProcessRegistry;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ProcessStartInput {
+    /// The shell command to run in the background.
+    pub command: String,
+    /// The working directory where the command should be executed.
+    pub cwd: PathBuf,
+}
+
+/// Starts a long-running command (eg: a dev server or a test watcher) in the
+/// background and returns immediately with a process id. Use the process
+/// status, logs and kill tools with that id to check on the process, read its
+/// output and stop it. Prefer the shell tool for commands that finish on their
+/// own.
+#[derive(ToolDescription)]
+pub struct ProcessStart {
+    shell: String,
+    registry: ProcessRegistry,
+    restricted: bool,
+}
+
+impl ProcessStart {
+    pub fn new(shell: impl ToString, registry: ProcessRegistry) -> Self {
+        Self { shell: shell.to_string(), registry, restricted: false }
+    }
+
+    /// Whether the commands run in the restricted mode of the shell.
+    pub fn restricted(mut self, restricted: bool) -> Self {
+        self.restricted = restricted;
+        self
+    }
+}
+
+impl NamedTool for ProcessStart {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_process_start")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for ProcessStart {
+    type Input = ProcessStartInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        if input.command.trim().is_empty() {
+            bail!("Command string is empty or contains only whitespace");
+        }
+
+        let id = self
+            .registry
+            .start(&self.shell, &input.command, &input.cwd, self.restricted)
+            .await?;
+
+        Ok(format!(
+            "Started background process with id {}: {}",
+            id, input.command
+        ))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    #[tokio::test]
+    async fn test_start_process() {
+        let temp_dir = TempDir::new().unwrap();
+        let registry = ProcessRegistry::default();
+        let start = ProcessStart::new("/bin/sh", registry.clone());
+
+        let result = start
+            .call(ProcessStartInput { command: "sleep 1".to_string(), cwd: temp_dir.path() })
+            .await
+            .unwrap();
+
+        assert!(result.starts_with("Started background process with id 1"));
+        assert_eq!(registry.status(None).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_start_empty_command() {
+        let temp_dir = TempDir::new().unwrap();
+        let start = ProcessStart::new("/bin/sh", ProcessRegistry::default());
+
+        let result = start
+            .call(ProcessStartInput { command: "  ".to_string(), cwd: temp_dir.path() })
+            .await;
+
+        assert!(result.is_err());
+    }
+}
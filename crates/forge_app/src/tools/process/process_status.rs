@@ -0,0 +1,89 @@
+use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::ProcessRegistry;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ProcessStatusInput {
+    /// The id of the background process. Lists all background processes when
+    /// omitted.
+    #[serde(default)]
+    pub process_id: Option<u64>,
+}
+
+/// Reports whether background processes started with the process start tool
+/// are still running, along with their exit code, pid, uptime and command.
+#[derive(ToolDescription)]
+pub struct ProcessStatus {
+    registry: ProcessRegistry,
+}
+
+impl ProcessStatus {
+    pub fn new(registry: ProcessRegistry) -> Self {
+        Self { registry }
+    }
+}
+
+impl NamedTool for ProcessStatus {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_process_status")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for ProcessStatus {
+    type Input = ProcessStatusInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let processes = self.registry.status(input.process_id).await?;
+        if processes.is_empty() {
+            return Ok("No background processes".to_string());
+        }
+
+        Ok(processes
+            .iter()
+            .map(|info| info.to_string())
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    #[tokio::test]
+    async fn test_status_without_processes() {
+        let status = ProcessStatus::new(ProcessRegistry::default());
+
+        let result = status
+            .call(ProcessStatusInput { process_id: None })
+            .await
+            .unwrap();
+
+        assert_eq!(result, "No background processes");
+    }
+
+    #[tokio::test]
+    async fn test_status_running_process() {
+        let temp_dir = TempDir::new().unwrap();
+        let registry = ProcessRegistry::default();
+        let id = registry
+            .start("/bin/sh", "sleep 5", &temp_dir.path(), false)
+            .await
+            .unwrap();
+        let status = ProcessStatus::new(registry.clone());
+
+        let result = status
+            .call(ProcessStatusInput { process_id: Some(id) })
+            .await
+            .unwrap();
+
+        assert!(result.starts_with("[1] running"));
+        assert!(result.contains("sleep 5"));
+        registry.kill(id).await.unwrap();
+    }
+}
@@ -0,0 +1,349 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Display};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+
+use crate::tools::shell::Dialect;
+use crate::tools::utils::{kill_tree, own_process_group};
+
+/// Maximum number of log lines retained per process. Older lines are dropped
+/// once the limit is reached.
+const MAX_LOG_LINES: usize = 10_000;
+
+/// Time given to a killed process to exit before giving up on waiting for it.
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Lifecycle state of a background process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessState {
+    Running,
+    /// The process exited by itself, the code is missing when it was
+    /// terminated by a signal.
+    Exited(Option<i32>),
+    Killed,
+}
+
+impl Display for ProcessState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessState::Running => write!(f, "running"),
+            ProcessState::Exited(Some(code)) => write!(f, "exited with code {}", code),
+            ProcessState::Exited(None) => write!(f, "exited by signal"),
+            ProcessState::Killed => write!(f, "killed"),
+        }
+    }
+}
+
+/// Snapshot of a background process.
+#[derive(Debug, Clone)]
+pub struct ProcessInfo {
+    pub id: u64,
+    pub pid: Option<u32>,
+    pub command: String,
+    pub cwd: PathBuf,
+    pub state: ProcessState,
+    pub uptime: Duration,
+}
+
+impl Display for ProcessInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {} (pid: {}, uptime: {}s, cwd: {})\n    {}",
+            self.id,
+            self.state,
+            self.pid
+                .map(|pid| pid.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            self.uptime.as_secs(),
+            self.cwd.display(),
+            self.command
+        )
+    }
+}
+
+/// Combined stdout and stderr lines of a process, in the order they were
+/// received.
+#[derive(Default)]
+struct ProcessLogs {
+    lines: VecDeque<String>,
+    /// Number of lines that were dropped from the front of the buffer.
+    dropped: usize,
+}
+
+impl ProcessLogs {
+    fn push(&mut self, line: String) {
+        if self.lines.len() == MAX_LOG_LINES {
+            self.lines.pop_front();
+            self.dropped += 1;
+        }
+        self.lines.push_back(line);
+    }
+
+    /// Total number of lines ever received.
+    fn total(&self) -> usize {
+        self.dropped + self.lines.len()
+    }
+}
+
+/// A page of log lines returned by [`ProcessRegistry::logs`].
+#[derive(Debug, PartialEq)]
+pub struct LogSlice {
+    /// Absolute line number of the first returned line.
+    pub start: usize,
+    pub lines: Vec<String>,
+    /// Offset to pass in order to receive only the lines printed afterwards.
+    pub next_offset: usize,
+}
+
+struct BackgroundProcess {
+    command: String,
+    cwd: PathBuf,
+    pid: Option<u32>,
+    started_at: Instant,
+    // Held so that the process is killed once the registry is dropped.
+    child: Child,
+    state: ProcessState,
+    logs: Arc<std::sync::Mutex<ProcessLogs>>,
+}
+
+impl BackgroundProcess {
+    /// Refreshes the state of the process and returns a snapshot of it.
+    fn info(&mut self, id: u64) -> ProcessInfo {
+        if self.state == ProcessState::Running {
+            if let Ok(Some(status)) = self.child.try_wait() {
+                self.state = ProcessState::Exited(status.code());
+            }
+        }
+
+        ProcessInfo {
+            id,
+            pid: self.pid,
+            command: self.command.clone(),
+            cwd: self.cwd.clone(),
+            state: self.state,
+            uptime: self.started_at.elapsed(),
+        }
+    }
+}
+
+/// Shared registry of processes started in the background.
+#[derive(Clone, Default)]
+pub struct ProcessRegistry {
+    next_id: Arc<AtomicU64>,
+    processes: Arc<Mutex<HashMap<u64, BackgroundProcess>>>,
+}
+
+impl ProcessRegistry {
+    /// Spawns the command using the given shell without waiting for it to
+    /// finish and returns the id assigned to the process. The command is
+    /// passed as the dialect of the shell expects it.
+    pub async fn start(
+        &self,
+        shell: &str,
+        command: &str,
+        cwd: &Path,
+        restricted: bool,
+    ) -> anyhow::Result<u64> {
+        let mut cmd = Command::new(shell);
+        cmd.args(Dialect::detect(shell).args(command, restricted))
+            .current_dir(cwd)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        own_process_group(&mut cmd);
+
+        let mut child = cmd
+            .spawn()
+            .with_context(|| format!("Failed to start background process: {}", command))?;
+
+        let logs = Arc::new(std::sync::Mutex::new(ProcessLogs::default()));
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(collect_lines(stdout, logs.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(collect_lines(stderr, logs.clone()));
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let process = BackgroundProcess {
+            command: command.to_string(),
+            cwd: cwd.to_path_buf(),
+            pid: child.id(),
+            started_at: Instant::now(),
+            child,
+            state: ProcessState::Running,
+            logs,
+        };
+        self.processes.lock().await.insert(id, process);
+
+        Ok(id)
+    }
+
+    /// Returns the status of the process with the given id, or of all the
+    /// processes ordered by id when no id is given.
+    pub async fn status(&self, id: Option<u64>) -> anyhow::Result<Vec<ProcessInfo>> {
+        let mut processes = self.processes.lock().await;
+        match id {
+            Some(id) => {
+                let process = processes.get_mut(&id).context(not_found(id))?;
+                Ok(vec![process.info(id)])
+            }
+            None => {
+                let mut infos = processes
+                    .iter_mut()
+                    .map(|(id, process)| process.info(*id))
+                    .collect::<Vec<_>>();
+                infos.sort_by_key(|info| info.id);
+                Ok(infos)
+            }
+        }
+    }
+
+    /// Returns the log lines starting at `offset`. When `tail` is set only the
+    /// last `tail` lines of that range are returned.
+    pub async fn logs(
+        &self,
+        id: u64,
+        offset: usize,
+        tail: Option<usize>,
+    ) -> anyhow::Result<LogSlice> {
+        let processes = self.processes.lock().await;
+        let process = processes.get(&id).context(not_found(id))?;
+        let logs = process.logs.lock().unwrap_or_else(|e| e.into_inner());
+
+        let next_offset = logs.total();
+        let mut start = offset.clamp(logs.dropped, next_offset);
+        if let Some(tail) = tail {
+            start = start.max(next_offset.saturating_sub(tail));
+        }
+
+        let lines = logs
+            .lines
+            .iter()
+            .skip(start - logs.dropped)
+            .cloned()
+            .collect();
+
+        Ok(LogSlice { start, lines, next_offset })
+    }
+
+    /// Kills the process along with all of its child processes. The process
+    /// is kept in the registry so that its logs remain available.
+    pub async fn kill(&self, id: u64) -> anyhow::Result<ProcessInfo> {
+        let mut processes = self.processes.lock().await;
+        let process = processes.get_mut(&id).context(not_found(id))?;
+
+        if process.info(id).state == ProcessState::Running {
+            if let Some(pid) = process.pid {
+                kill_tree(pid).await;
+            }
+            let _ = process.child.start_kill();
+            let _ = tokio::time::timeout(KILL_GRACE_PERIOD, process.child.wait()).await;
+            process.state = ProcessState::Killed;
+        }
+
+        Ok(process.info(id))
+    }
+}
+
+fn not_found(id: u64) -> String {
+    format!("No background process found with id {}", id)
+}
+
+/// Appends every line read from the reader to the logs until EOF.
+async fn collect_lines<R: AsyncRead + Unpin>(reader: R, logs: Arc<std::sync::Mutex<ProcessLogs>>) {
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        logs.lock().unwrap_or_else(|e| e.into_inner()).push(line);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    /// Waits until the process is no longer running.
+    async fn wait_for_exit(registry: &ProcessRegistry, id: u64) -> ProcessInfo {
+        loop {
+            let info = registry.status(Some(id)).await.unwrap().remove(0);
+            if info.state != ProcessState::Running {
+                return info;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_process_exit_and_logs() {
+        let fixture = TempDir::new().unwrap();
+        let registry = ProcessRegistry::default();
+
+        let id = registry
+            .start(
+                "/bin/sh",
+                "echo one; echo two; exit 3",
+                &fixture.path(),
+                false,
+            )
+            .await
+            .unwrap();
+        let actual = wait_for_exit(&registry, id).await;
+        assert_eq!(actual.state, ProcessState::Exited(Some(3)));
+
+        // Readers may still be draining the pipes right after the exit.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let actual = registry.logs(id, 0, None).await.unwrap();
+        let expected = LogSlice {
+            start: 0,
+            lines: vec!["one".to_string(), "two".to_string()],
+            next_offset: 2,
+        };
+        assert_eq!(actual, expected);
+
+        let actual = registry.logs(id, 1, None).await.unwrap();
+        assert_eq!(actual.lines, vec!["two".to_string()]);
+
+        let actual = registry.logs(id, 0, Some(1)).await.unwrap();
+        assert_eq!(actual.start, 1);
+    }
+
+    #[tokio::test]
+    async fn test_kill_running_process() {
+        let fixture = TempDir::new().unwrap();
+        let registry = ProcessRegistry::default();
+
+        let id = registry
+            .start("/bin/sh", "sleep 30 & sleep 30", &fixture.path(), false)
+            .await
+            .unwrap();
+        let actual = registry.kill(id).await.unwrap();
+
+        assert_eq!(actual.state, ProcessState::Killed);
+        assert_eq!(
+            registry.status(None).await.unwrap()[0].state,
+            ProcessState::Killed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_process() {
+        let registry = ProcessRegistry::default();
+
+        assert!(registry.status(Some(7)).await.is_err());
+        assert!(registry.logs(7, 0, None).await.is_err());
+        assert!(registry.kill(7).await.is_err());
+    }
+}
@@ -0,0 +1,7 @@
+mod scm_issue;
+mod scm_pull_request;
+mod scm_review_comment;
+
+pub use scm_issue::*;
+pub use scm_pull_request::*;
+pub use scm_review_comment::*;
@@ -0,0 +1,46 @@
+use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_scm::Scm;
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ScmIssueInput {
+    /// The number of the issue.
+    pub number: u64,
+    /// The repository on the same host, eg: `owner/name` (default: the
+    /// repository of the origin remote).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+}
+
+/// Fetches an issue from GitHub, GitLab or Bitbucket, detected from the origin
+/// remote, with its title, state, labels, description and comments. Use it to
+/// understand what an issue asks for before working on it, eg: when asked to
+/// fix issue #123.
+#[derive(ToolDescription)]
+pub struct ScmIssue {
+    scm: Scm,
+}
+
+impl ScmIssue {
+    pub fn new(scm: Scm) -> Self {
+        Self { scm }
+    }
+}
+
+impl NamedTool for ScmIssue {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_scm_issue")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for ScmIssue {
+    type Input = ScmIssueInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let provider = self.scm.provider(input.repo.as_deref()).await?;
+        Ok(provider.issue(input.number).await?.to_string())
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::bail;
+use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_scm::{NewPullRequest, Scm};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ScmPullRequestInput {
+    /// The title of the pull request, a short summary of the change.
+    pub title: String,
+    /// The description of the pull request in markdown: what changed, why,
+    /// and how it was verified. Reference the issue it resolves with `Fixes
+    /// #123`.
+    pub body: String,
+    /// The branch the changes are merged into (default: the default branch
+    /// of the repository).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// Whether to open the pull request as a draft (default: false).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub draft: Option<bool>,
+}
+
+/// Pushes the current branch to the origin remote and opens a pull request
+/// for it on GitHub or Bitbucket, or a merge request on GitLab. Commit the
+/// changes on a branch other than the base one first. Write the title and
+/// description yourself from the changes, and return the URL of the pull
+/// request to the user.
+#[derive(ToolDescription)]
+pub struct ScmPullRequest {
+    scm: Scm,
+}
+
+impl ScmPullRequest {
+    pub fn new(scm: Scm) -> Self {
+        Self { scm }
+    }
+}
+
+impl NamedTool for ScmPullRequest {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_scm_pr_create")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for ScmPullRequest {
+    type Input = ScmPullRequestInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let provider = self.scm.provider(None).await?;
+        let head = self.scm.git(&["rev-parse", "--abbrev-ref", "HEAD"]).await?;
+        if head == "HEAD" {
+            bail!("HEAD is detached, check out a branch with the changes first");
+        }
+        let base = match input.base {
+            Some(base) => base,
+            None => provider.default_branch().await?,
+        };
+        if head == base {
+            bail!("The changes are on the base branch {base}, commit them on a new branch first");
+        }
+
+        self.scm
+            .git(&["push", "--set-upstream", "origin", &head])
+            .await?;
+        let pull = provider
+            .create_pull_request(NewPullRequest {
+                title: input.title,
+                body: input.body,
+                head: head.clone(),
+                base: base.clone(),
+                draft: input.draft.unwrap_or_default(),
+            })
+            .await?;
+        Ok(format!(
+            "Created {} #{} from {head} into {base}: {}",
+            provider.kind().pull_request(),
+            pull.number,
+            pull.url
+        ))
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::bail;
+use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_scm::{LinePosition, ReviewComment, Scm};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ScmReviewCommentInput {
+    /// The number of the pull request.
+    pub number: u64,
+    /// The comment in markdown.
+    pub body: String,
+    /// The path of the file to comment on, relative to the root of the
+    /// repository. Omit it to comment on the pull request as a whole.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// The line of the file in the pull request's version that the comment
+    /// applies to, required along with the path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+    /// The repository on the same host, eg: `owner/name` (default: the
+    /// repository of the origin remote).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+}
+
+/// Posts a review comment on a pull request of GitHub or Bitbucket, or a merge
+/// request of GitLab, either on a line of a changed file or on the pull request
+/// as a whole. Use it to report the findings of a review where the author sees
+/// them.
+#[derive(ToolDescription)]
+pub struct ScmReviewComment {
+    scm: Scm,
+}
+
+impl ScmReviewComment {
+    pub fn new(scm: Scm) -> Self {
+        Self { scm }
+    }
+}
+
+impl NamedTool for ScmReviewComment {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_scm_review_comment")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for ScmReviewComment {
+    type Input = ScmReviewCommentInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let position = match (input.path, input.line) {
+            (Some(path), Some(line)) => Some(LinePosition { path, line }),
+            (None, None) => None,
+            _ => bail!("The path and the line are required together to comment on a line"),
+        };
+        let provider = self.scm.provider(input.repo.as_deref()).await?;
+        let url = provider
+            .comment(input.number, ReviewComment { body: input.body, position })
+            .await?;
+        Ok(format!("Commented on #{}: {url}", input.number))
+    }
+}
@@ -0,0 +1,158 @@
+use base64::Engine;
+
+/// Quotes PowerShell treats as a single quote, which must all be doubled in a
+/// single-quoted string.
+const POWERSHELL_QUOTES: [char; 5] = ['\'', '\u{2018}', '\u{2019}', '\u{201A}', '\u{201B}'];
+
+/// Command language of a shell, which decides how commands are passed to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    /// sh, bash and rbash
+    Posix,
+    /// Windows PowerShell and PowerShell 7 (pwsh)
+    PowerShell,
+    Cmd,
+}
+
+impl Dialect {
+    /// Detects the dialect from the path of the shell, eg:
+    /// `C:\Program Files\PowerShell\7\pwsh.exe`.
+    pub fn detect(shell: &str) -> Self {
+        // note: the path is split by hand since `Path` only knows the
+        // separators of the current platform.
+        let name = shell
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or(shell)
+            .to_lowercase();
+        match name.strip_suffix(".exe").unwrap_or(&name) {
+            "pwsh" | "powershell" => Self::PowerShell,
+            "cmd" => Self::Cmd,
+            _ => Self::Posix,
+        }
+    }
+
+    /// Flag that precedes the command when it's displayed.
+    pub fn flag(&self) -> &'static str {
+        match self {
+            Dialect::Posix => "-c",
+            Dialect::PowerShell => "-Command",
+            Dialect::Cmd => "/C",
+        }
+    }
+
+    /// Arguments of the shell to run `command`. In restricted mode PowerShell
+    /// runs it in the constrained language, the counterpart of rbash.
+    pub fn args(&self, command: &str, restricted: bool) -> Vec<String> {
+        match self {
+            Dialect::Posix | Dialect::Cmd => vec![self.flag().to_string(), command.to_string()],
+            // note: the script is encoded since the quotes of a command line
+            // are mangled by the quoting rules of Windows.
+            Dialect::PowerShell => vec![
+                "-NoLogo".to_string(),
+                "-NoProfile".to_string(),
+                "-NonInteractive".to_string(),
+                "-EncodedCommand".to_string(),
+                encode(&powershell_script(command, restricted)),
+            ],
+        }
+    }
+}
+
+/// Wraps the command so that its output is UTF-8 and the shell exits with
+/// the code of the failed native command, since PowerShell exits with 0.
+fn powershell_script(command: &str, restricted: bool) -> String {
+    let mut script = String::from(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8\n$global:LASTEXITCODE = 0\n",
+    );
+    if restricted {
+        // note: the command is only parsed once the language is constrained,
+        // since a script keeps the language mode it was parsed in.
+        script.push_str("$ExecutionContext.SessionState.LanguageMode = 'ConstrainedLanguage'\n");
+        script.push_str(&format!("Invoke-Expression {}\n", single_quote(command)));
+    } else {
+        script.push_str(command);
+        script.push('\n');
+    }
+    script.push_str(
+        "if (-not $?) { exit $(if ($LASTEXITCODE) { $LASTEXITCODE } else { 1 }) }\nexit $LASTEXITCODE\n",
+    );
+    script
+}
+
+/// Quotes `value` as a PowerShell string whose content is taken literally.
+fn single_quote(value: &str) -> String {
+    let mut quoted = String::from("'");
+    for c in value.chars() {
+        if POWERSHELL_QUOTES.contains(&c) {
+            quoted.push(c);
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Encodes the script for `-EncodedCommand`: base64 of its UTF-16LE bytes.
+fn encode(script: &str) -> String {
+    let bytes = script
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect::<Vec<_>>();
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let actual = [
+            "/bin/rbash",
+            "/usr/bin/pwsh",
+            r"C:\Program Files\PowerShell\7\pwsh.exe",
+            r"C:\Windows\System32\WindowsPowerShell\v1.0\PowerShell.exe",
+            r"C:\Windows\system32\cmd.exe",
+        ]
+        .map(Dialect::detect);
+        let expected = [
+            Dialect::Posix,
+            Dialect::PowerShell,
+            Dialect::PowerShell,
+            Dialect::PowerShell,
+            Dialect::Cmd,
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_posix_args() {
+        let actual = Dialect::Posix.args("echo 'hi'", true);
+        assert_eq!(actual, ["-c", "echo 'hi'"]);
+    }
+
+    #[test]
+    fn test_powershell_args_are_encoded() {
+        let actual = Dialect::PowerShell.args("dir", false);
+        assert_eq!(
+            actual[..4],
+            [
+                "-NoLogo",
+                "-NoProfile",
+                "-NonInteractive",
+                "-EncodedCommand"
+            ]
+        );
+        assert_eq!(encode("dir"), "ZABpAHIA");
+    }
+
+    #[test]
+    fn test_powershell_script_restricted() {
+        let actual = powershell_script("Write-Output 'it’s here'", true);
+        let expected = "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8\n$global:LASTEXITCODE = 0\n$ExecutionContext.SessionState.LanguageMode = 'ConstrainedLanguage'\nInvoke-Expression 'Write-Output ''it’’s here'''\nif (-not $?) { exit $(if ($LASTEXITCODE) { $LASTEXITCODE } else { 1 }) }\nexit $LASTEXITCODE\n";
+        assert_eq!(actual, expected);
+    }
+}
@@ -1,25 +1,35 @@
 use std::io::{self, Write};
+use std::time::Duration;
 
 use tokio::io::AsyncRead;
 use tokio::process::Command;
 
+use crate::tools::utils::{kill_tree, own_process_group};
+
+/// Grace period to collect the remaining output once a timed out process tree
+/// has been killed.
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
 /// A command executor that handles command creation and execution
 #[derive(Debug)]
 pub struct CommandExecutor {
     command: Command,
+    timeout: Option<Duration>,
 }
 
 pub struct Output {
     pub stdout: String,
     pub stderr: String,
     pub success: bool,
+    /// Set when the command was killed because it exceeded its timeout.
+    pub timed_out: Option<Duration>,
 }
 
 impl CommandExecutor {
     /// Create a new command executor with the specified command and working
     /// directory
     pub fn new(command: Command) -> Self {
-        Self { command }
+        Self { command, timeout: None }
     }
 
     /// Enable colored output for the command. bydefault it's disabled.
@@ -28,14 +38,30 @@ impl CommandExecutor {
         self
     }
 
+    /// Kill the command along with all the processes it spawned once the
+    /// timeout elapses.
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
     fn configure_pipes(&mut self) {
         // in order to stream the output of the command to stdout and stderr,
         // we need to set it to piped. but to pass the input to the child process
         // we need to set the stdin to inherit.
         self.command
-            .stdin(std::process::Stdio::inherit())
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped());
+
+        if self.timeout.is_some() {
+            // Run the command in its own process group so that the whole tree can
+            // be killed on timeout. note: a background group stops when it reads
+            // the terminal and doesn't get its Ctrl+C, so it gets no input.
+            self.command.stdin(std::process::Stdio::null());
+            own_process_group(&mut self.command);
+        } else {
+            self.command.stdin(std::process::Stdio::inherit());
+        }
     }
 
     /// executes the command and streams the output of command to stdout,
@@ -44,31 +70,73 @@ impl CommandExecutor {
         self.configure_pipes();
 
         let mut child = self.command.spawn()?;
+        let pid = child.id();
         let mut stdout_pipe = child.stdout.take();
         let mut stderr_pipe = child.stderr.take();
 
         // stream the output of the command to stdout and stderr.
-        let (status, stdout, stderr) = tokio::try_join!(
-            child.wait(),
-            stream(&mut stdout_pipe, io::stdout()),
-            stream(&mut stderr_pipe, io::stderr())
-        )?;
+        let (result, timed_out) = {
+            let execution = async {
+                tokio::try_join!(
+                    child.wait(),
+                    stream(&mut stdout_pipe, io::stdout()),
+                    stream(&mut stderr_pipe, io::stderr())
+                )
+            };
+            tokio::pin!(execution);
+
+            let mut timed_out = None;
+            let result = match self.timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, &mut execution).await {
+                    Ok(result) => Some(result),
+                    Err(_) => {
+                        timed_out = Some(timeout);
+                        if let Some(pid) = pid {
+                            kill_tree(pid).await;
+                        }
+                        // Collect whatever was written before the process was killed
+                        tokio::time::timeout(KILL_GRACE_PERIOD, &mut execution)
+                            .await
+                            .ok()
+                    }
+                },
+                None => Some(execution.await),
+            };
+            (result, timed_out)
+        };
 
         // Drop happens after `try_join` due to <https://github.com/tokio-rs/tokio/issues/4309>
         drop(stdout_pipe);
         drop(stderr_pipe);
 
         // Helper function to process output bytes into string.
-        let process_output = |bytes: &[u8]| String::from_utf8_lossy(bytes).into_owned();
+        let process_output = |bytes: &[u8]| normalize_newlines(&String::from_utf8_lossy(bytes));
 
-        Ok(Output {
-            success: status.success(),
-            stdout: process_output(&stdout),
-            stderr: process_output(&stderr),
-        })
+        let output = match result.transpose()? {
+            Some((status, stdout, stderr)) => Output {
+                success: status.success() && timed_out.is_none(),
+                stdout: process_output(&stdout),
+                stderr: process_output(&stderr),
+                timed_out,
+            },
+            None => Output {
+                success: false,
+                stdout: String::new(),
+                stderr: String::new(),
+                timed_out,
+            },
+        };
+
+        Ok(output)
     }
 }
 
+/// Replaces the CRLF line endings of Windows programs, keeping the lone
+/// carriage returns of progress bars.
+fn normalize_newlines(output: &str) -> String {
+    output.replace("\r\n", "\n")
+}
+
 /// reads the output from A and writes it to W
 async fn stream<A: AsyncRead + Unpin, W: Write>(
     io: &mut Option<A>,
@@ -91,3 +159,16 @@ async fn stream<A: AsyncRead + Unpin, W: Write>(
     }
     Ok(output)
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_normalize_newlines() {
+        let actual = normalize_newlines("Directory: C:\\forge\r\n\r\n10%\r100%\r\n");
+        assert_eq!(actual, "Directory: C:\\forge\n\n10%\r100%\n");
+    }
+}
@@ -1,4 +1,9 @@
-mod executor;
+mod dialect;
+pub(super) mod executor;
+mod session;
+mod shell_reset;
 mod shell_tool;
 
+pub use dialect::Dialect;
+pub use shell_reset::*;
 pub use shell_tool::*;
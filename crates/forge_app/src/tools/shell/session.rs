@@ -0,0 +1,394 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+
+use anyhow::{bail, Context};
+use forge_domain::{ConversationId, TOOL_CONVERSATION};
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStderr, ChildStdin, Command};
+use tokio::sync::Mutex;
+
+use super::dialect::Dialect;
+use super::executor::Output;
+use crate::tools::utils::kill_tree;
+
+/// Size of the terminal the commands of a session write to.
+const TERMINAL_ROWS: u16 = 24;
+const TERMINAL_COLUMNS: u16 = 120;
+
+/// Output of a command executed inside a persistent shell session.
+pub struct SessionOutput {
+    pub output: Output,
+    /// Working directory of the session after the command finished.
+    pub cwd: PathBuf,
+    /// Set when the command terminated the underlying shell (eg: `exit`).
+    pub terminated: bool,
+}
+
+/// A long-lived shell process that keeps its working directory, exported
+/// environment variables and sourced scripts (eg: virtualenv activation)
+/// between commands. The commands write their output to a terminal, like they
+/// would when run by the user, and their errors to a pipe.
+pub struct ShellSession {
+    // Held so that the shell is killed once the session is dropped.
+    child: Child,
+    stdin: ChildStdin,
+    /// Master side of the terminal, from which the output is read.
+    stdout: BufReader<File>,
+    stderr: BufReader<ChildStderr>,
+    cwd: PathBuf,
+}
+
+impl ShellSession {
+    /// Spawns a new shell process rooted at `cwd`.
+    pub fn start(shell: &str, cwd: &Path) -> anyhow::Result<Self> {
+        // note: the command and its trailer are written in POSIX shell.
+        if Dialect::detect(shell) != Dialect::Posix {
+            bail!("Persistent shell sessions require a POSIX shell, {shell} isn't one")
+        }
+
+        let (master, terminal) =
+            open_terminal().context("Failed to open a terminal for the shell session")?;
+        // note: the shell reads the commands from a pipe so that it isn't
+        // interactive, whatever the shell.
+        let mut command = Command::new(shell);
+        command
+            .current_dir(cwd)
+            .env("CLICOLOR_FORCE", "1")
+            .stdin(Stdio::piped())
+            .stdout(terminal)
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        lead_session(&mut command);
+        let mut child = command
+            .spawn()
+            .with_context(|| format!("Failed to start shell session using {}", shell))?;
+        // note: the command holds the terminal too, which would keep the
+        // output open once the shell exited.
+        drop(command);
+
+        let stdin = child.stdin.take().context("Shell session has no stdin")?;
+        let stderr = child.stderr.take().context("Shell session has no stderr")?;
+
+        Ok(Self {
+            child,
+            stdin,
+            stdout: BufReader::new(File::from_std(master)),
+            stderr: BufReader::new(stderr),
+            cwd: cwd.to_path_buf(),
+        })
+    }
+
+    /// Kills the shell along with every process the commands started.
+    pub async fn kill(self) {
+        if let Some(pid) = self.child.id() {
+            kill_tree(pid).await;
+        }
+    }
+
+    /// Executes a command in the session and waits for it to finish. The
+    /// command is evaluated by the running shell, so `cd`, `export` and
+    /// `source` affect all subsequent commands of the session.
+    pub async fn execute(&mut self, command: &str) -> anyhow::Result<SessionOutput> {
+        let marker = format!("__FORGE_{}__", uuid::Uuid::new_v4().simple());
+        let script = format!(
+            "eval '{}' < /dev/null\n__forge_status=$?\nprintf '\\n{marker} %s %s\\n' \"$__forge_status\" \"$PWD\"\nprintf '\\n{marker}\\n' >&2\n",
+            command.replace('\'', r"'\''"),
+        );
+
+        self.stdin.write_all(script.as_bytes()).await?;
+        self.stdin.flush().await?;
+
+        let (stdout, stderr) = tokio::try_join!(
+            read_until_marker(&mut self.stdout, &marker, io::stdout()),
+            read_until_marker(&mut self.stderr, &marker, io::stderr())
+        )?;
+
+        let (status, cwd) = match stdout.trailer.as_deref().and_then(parse_trailer) {
+            Some((status, cwd)) => (Some(status), cwd),
+            None => (None, self.cwd.clone()),
+        };
+        self.cwd = cwd;
+
+        Ok(SessionOutput {
+            output: Output {
+                stdout: stdout.content,
+                stderr: stderr.content,
+                success: status == Some(0),
+                timed_out: None,
+            },
+            cwd: self.cwd.clone(),
+            terminated: status.is_none(),
+        })
+    }
+}
+
+/// Conversation the session belongs to, if any, along with its id.
+type SessionKey = (Option<ConversationId>, String);
+
+/// Shared registry of shell sessions keyed by the conversation of the tool
+/// call, so that conversations using the same session id don't share their
+/// shell, and by their session id.
+#[derive(Clone, Default)]
+pub struct ShellSessions {
+    sessions: Arc<Mutex<HashMap<SessionKey, ShellSession>>>,
+}
+
+impl ShellSessions {
+    fn key(id: &str) -> SessionKey {
+        (
+            TOOL_CONVERSATION.try_with(Clone::clone).ok(),
+            id.to_string(),
+        )
+    }
+
+    /// Removes the session from the registry so that it can be used
+    /// exclusively by the caller. Sessions that are taken out and never
+    /// returned (eg: because the call was cancelled) are killed on drop.
+    pub async fn take(&self, id: &str) -> Option<ShellSession> {
+        self.sessions.lock().await.remove(&Self::key(id))
+    }
+
+    /// Returns a session back into the registry.
+    pub async fn put(&self, id: &str, session: ShellSession) {
+        self.sessions.lock().await.insert(Self::key(id), session);
+    }
+
+    /// Kills the session with the given id, along with the processes it
+    /// started, and returns the last known working directory of it.
+    pub async fn reset(&self, id: &str) -> Option<PathBuf> {
+        let session = self.take(id).await?;
+        let cwd = session.cwd.clone();
+        session.kill().await;
+        Some(cwd)
+    }
+}
+
+/// Opens a terminal in raw mode, so that the output of the commands isn't
+/// translated, returning its master side and the terminal itself.
+#[cfg(unix)]
+fn open_terminal() -> io::Result<(std::fs::File, Stdio)> {
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+    let (mut master, mut terminal) = (0, 0);
+    let mut size = libc::winsize {
+        ws_row: TERMINAL_ROWS,
+        ws_col: TERMINAL_COLUMNS,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: openpty only writes the descriptors it opened, which are then
+    // owned here, and the termios is initialized by tcgetattr before use.
+    unsafe {
+        if libc::openpty(
+            &mut master,
+            &mut terminal,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            // note: the size is mutable on some platforms only.
+            std::ptr::addr_of_mut!(size),
+        ) == -1
+        {
+            return Err(io::Error::last_os_error());
+        }
+        let (master, terminal) = (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(terminal));
+        // note: the descriptors aren't inherited by the processes started
+        // elsewhere, which would keep the terminal open.
+        for fd in [&master, &terminal] {
+            if libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        let mut termios = std::mem::zeroed::<libc::termios>();
+        if libc::tcgetattr(terminal.as_raw_fd(), &mut termios) == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        libc::cfmakeraw(&mut termios);
+        if libc::tcsetattr(terminal.as_raw_fd(), libc::TCSANOW, &termios) == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok((master.into(), terminal.into()))
+    }
+}
+
+#[cfg(not(unix))]
+fn open_terminal() -> io::Result<(std::fs::File, Stdio)> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Starts the shell as the leader of a new session whose controlling terminal
+/// is the one of its output. The shell then leads the process group of the
+/// commands, which [`kill_tree`] kills along with it.
+fn lead_session(command: &mut Command) {
+    #[cfg(unix)]
+    // SAFETY: setsid and ioctl are async-signal-safe.
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 || libc::ioctl(1, libc::TIOCSCTTY, 0) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    #[cfg(not(unix))]
+    let _ = command;
+}
+
+/// Whether reading failed because the other side was closed, which is how
+/// reading the master side of a terminal fails once the processes using it
+/// exited.
+#[cfg(unix)]
+fn is_closed(error: &io::Error) -> bool {
+    error.raw_os_error() == Some(libc::EIO)
+}
+
+#[cfg(not(unix))]
+fn is_closed(_: &io::Error) -> bool {
+    false
+}
+
+struct MarkedOutput {
+    content: String,
+    /// Text that followed the marker on the marker line.
+    trailer: Option<String>,
+}
+
+/// Reads lines from the reader until a line starting with the given marker is
+/// found, mirroring everything read to the writer.
+async fn read_until_marker<R: AsyncRead + Unpin, W: Write>(
+    reader: &mut BufReader<R>,
+    marker: &str,
+    mut writer: W,
+) -> anyhow::Result<MarkedOutput> {
+    let mut content = String::new();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = match reader.read_line(&mut line).await {
+            Ok(read) => read,
+            Err(error) if is_closed(&error) => 0,
+            Err(error) => return Err(error.into()),
+        };
+        if read == 0 {
+            // The shell exited before reaching the marker.
+            return Ok(MarkedOutput { content, trailer: None });
+        }
+
+        if let Some(trailer) = line.strip_prefix(marker) {
+            // The marker is always preceded by an additional newline
+            if content.ends_with('\n') {
+                content.pop();
+            }
+            return Ok(MarkedOutput { content, trailer: Some(trailer.trim().to_string()) });
+        }
+
+        writer.write_all(line.as_bytes())?;
+        writer.flush()?;
+        content.push_str(&line);
+    }
+}
+
+/// Parses the `<status> <cwd>` trailer written after every command.
+fn parse_trailer(trailer: &str) -> Option<(i32, PathBuf)> {
+    let (status, cwd) = trailer.split_once(' ')?;
+    Some((status.parse().ok()?, PathBuf::from(cwd)))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    #[tokio::test]
+    async fn test_session_persists_cwd_and_env() {
+        let fixture = TempDir::new().unwrap();
+        let mut session = ShellSession::start("/bin/sh", &fixture.path()).unwrap();
+
+        session
+            .execute("mkdir nested && cd nested && export FORGE_TEST=42")
+            .await
+            .unwrap();
+        let actual = session.execute("echo $FORGE_TEST").await.unwrap();
+
+        assert_eq!(actual.output.stdout, "42\n");
+        assert_eq!(actual.cwd, fixture.path().join("nested"));
+        assert!(actual.output.success);
+    }
+
+    #[tokio::test]
+    async fn test_session_reports_failure() {
+        let fixture = TempDir::new().unwrap();
+        let mut session = ShellSession::start("/bin/sh", &fixture.path()).unwrap();
+
+        let actual = session.execute("echo oops >&2; false").await.unwrap();
+
+        assert_eq!(actual.output.stderr, "oops\n");
+        assert!(!actual.output.success);
+        assert!(!actual.terminated);
+    }
+
+    #[tokio::test]
+    async fn test_session_writes_to_a_terminal() {
+        let fixture = TempDir::new().unwrap();
+        let mut session = ShellSession::start("/bin/sh", &fixture.path()).unwrap();
+
+        let actual = session
+            .execute("test -t 1 && echo terminal; test -t 2 || echo pipe >&2")
+            .await
+            .unwrap();
+
+        assert_eq!(actual.output.stdout, "terminal\n");
+        assert_eq!(actual.output.stderr, "pipe\n");
+    }
+
+    #[tokio::test]
+    async fn test_kill_kills_the_started_processes() {
+        let fixture = TempDir::new().unwrap();
+        let mut session = ShellSession::start("/bin/sh", &fixture.path()).unwrap();
+
+        // note: the process ignores the hangup of the terminal, so only
+        // killing the process group stops it.
+        session
+            .execute("(trap '' HUP; sleep 1; touch done) &")
+            .await
+            .unwrap();
+        session.kill().await;
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+        assert!(!fixture.path().join("done").exists());
+    }
+
+    #[tokio::test]
+    async fn test_sessions_are_kept_per_conversation() {
+        let fixture = TempDir::new().unwrap();
+        let sessions = ShellSessions::default();
+        let session = ShellSession::start("/bin/sh", &fixture.path()).unwrap();
+        let conversation = ConversationId::generate();
+
+        TOOL_CONVERSATION
+            .scope(conversation.clone(), sessions.put("dev", session))
+            .await;
+
+        assert!(sessions.take("dev").await.is_none());
+        let other = TOOL_CONVERSATION.scope(ConversationId::generate(), sessions.take("dev"));
+        assert!(other.await.is_none());
+        let actual = TOOL_CONVERSATION.scope(conversation, sessions.take("dev"));
+        assert!(actual.await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_session_exit_terminates() {
+        let fixture = TempDir::new().unwrap();
+        let mut session = ShellSession::start("/bin/sh", &fixture.path()).unwrap();
+
+        let actual = session.execute("exit 3").await.unwrap();
+
+        assert!(actual.terminated);
+        assert!(!actual.output.success);
+    }
+}
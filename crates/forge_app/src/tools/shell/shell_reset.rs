@@ -0,0 +1,86 @@
+use forge_domain::{ExecutableTool, NamedTool, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::session::ShellSessions;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ShellResetInput {
+    /// The id of the shell session to reset.
+    pub session_id: String,
+}
+
+/// Terminates a persistent shell session created by the shell tool, along with
+/// the processes it started, discarding its working directory and environment.
+/// The next command using the same session id starts a fresh shell. Use it when a session is stuck or its state
+/// is no longer wanted.
+#[derive(ToolDescription)]
+pub struct ShellReset {
+    sessions: ShellSessions,
+}
+
+impl ShellReset {
+    pub fn new(sessions: ShellSessions) -> Self {
+        Self { sessions }
+    }
+}
+
+impl NamedTool for ShellReset {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_process_shell_reset")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for ShellReset {
+    type Input = ShellResetInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        match self.sessions.reset(&input.session_id).await {
+            Some(cwd) => Ok(format!(
+                "Shell session '{}' was reset. Last working directory: {}",
+                input.session_id,
+                cwd.display()
+            )),
+            None => Err(anyhow::anyhow!(
+                "No shell session found with id '{}'",
+                input.session_id
+            )),
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::tools::shell::session::ShellSession;
+    use crate::tools::utils::TempDir;
+
+    #[tokio::test]
+    async fn test_reset_existing_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let sessions = ShellSessions::default();
+        let session = ShellSession::start("/bin/sh", &temp_dir.path()).unwrap();
+        sessions.put("dev", session).await;
+
+        let reset = ShellReset::new(sessions.clone());
+        let result = reset
+            .call(ShellResetInput { session_id: "dev".to_string() })
+            .await
+            .unwrap();
+
+        assert!(result.contains("'dev' was reset"));
+        assert!(sessions.take("dev").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reset_unknown_session() {
+        let reset = ShellReset::new(ShellSessions::default());
+        let result = reset
+            .call(ShellResetInput { session_id: "missing".to_string() })
+            .await;
+
+        assert!(result.is_err());
+    }
+}
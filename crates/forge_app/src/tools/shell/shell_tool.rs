@@ -1,4 +1,5 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
 use anyhow::bail;
 use forge_domain::{Environment, ExecutableTool, NamedTool, ToolDescription, ToolName};
@@ -7,34 +8,93 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tokio::process::Command;
 
+use super::dialect::Dialect;
 use super::executor::Output;
+use super::session::{ShellSession, ShellSessions};
 use crate::tools::shell::executor::CommandExecutor;
 
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct ShellInput {
     /// The shell command to execute.
     pub command: String,
-    /// The working directory where the command should be executed.
+    /// The working directory where the command should be executed. Ignored
+    /// once the session is created.
     pub cwd: PathBuf,
+    /// Optional persistent shell session id. Commands of the conversation
+    /// sharing it keep the working directory and environment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Seconds after which the command and its child processes are killed,
+    /// the command then gets no input from the terminal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Maximum bytes returned per stream, larger output keeps head and tail
+    /// (default: 40000).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_bytes: Option<usize>,
+}
+
+/// Default limit on the amount of output returned for each stream.
+const DEFAULT_MAX_OUTPUT_BYTES: usize = 40_000;
+
+/// Truncates the content to at most `max_bytes` by keeping its head and tail
+/// and replacing the middle with a note about the omitted bytes.
+fn truncate_output(content: &str, max_bytes: usize) -> String {
+    if content.len() <= max_bytes {
+        return content.to_string();
+    }
+
+    let mut head_end = max_bytes / 2;
+    while !content.is_char_boundary(head_end) {
+        head_end -= 1;
+    }
+
+    let mut tail_start = content.len() - (max_bytes - head_end);
+    while !content.is_char_boundary(tail_start) {
+        tail_start += 1;
+    }
+
+    format!(
+        "{}\n... [output truncated: {} bytes omitted] ...\n{}",
+        &content[..head_end],
+        tail_start - head_end,
+        &content[tail_start..]
+    )
 }
 
 /// Formats command output by wrapping non-empty stdout/stderr in XML tags.
 /// stderr is commonly used for warnings and progress info, so success is
 /// determined by exit status, not stderr presence. Returns Ok(output) on
 /// success or Err(output) on failure, with a status message if both streams are
-/// empty.
-fn format_output(output: Output) -> anyhow::Result<String> {
+/// empty. Each stream is truncated to `max_output_bytes`.
+fn format_output(output: Output, max_output_bytes: usize) -> anyhow::Result<String> {
     let mut formatted_output = String::new();
 
     if !output.stdout.trim().is_empty() {
-        formatted_output.push_str(&format!("<stdout>{}</stdout>", output.stdout));
+        formatted_output.push_str(&format!(
+            "<stdout>{}</stdout>",
+            truncate_output(&output.stdout, max_output_bytes)
+        ));
     }
 
     if !output.stderr.trim().is_empty() {
         if !formatted_output.is_empty() {
             formatted_output.push('\n');
         }
-        formatted_output.push_str(&format!("<stderr>{}</stderr>", output.stderr));
+        formatted_output.push_str(&format!(
+            "<stderr>{}</stderr>",
+            truncate_output(&output.stderr, max_output_bytes)
+        ));
+    }
+
+    if let Some(timeout) = output.timed_out {
+        if !formatted_output.is_empty() {
+            formatted_output.push('\n');
+        }
+        formatted_output.push_str(&format!(
+            "Command timed out after {} seconds and was killed.",
+            timeout.as_secs()
+        ));
     }
 
     let result = if formatted_output.is_empty() {
@@ -57,17 +117,79 @@ fn format_output(output: Output) -> anyhow::Result<String> {
 /// Execute shell commands with safety checks and validation. By default, uses
 /// restricted bash (rbash) for enhanced security, preventing potentially
 /// dangerous operations like absolute path execution and directory changes.
+/// On Windows commands run in PowerShell, in its constrained language when
+/// restricted.
 /// When a command requires unrestricted access, suggest the user to run the
-/// forge CLI with the `-u` flag.
+/// forge CLI with the `-u` flag. Pass a `session_id` to keep `cd`, exported
+/// variables and virtualenv activation across calls.
 #[derive(ToolDescription)]
 pub struct Shell {
     env: Environment,
+    sessions: ShellSessions,
 }
 
 impl Shell {
     /// Create a new Shell with environment configuration
     pub fn new(env: Environment) -> Self {
-        Self { env }
+        Self { env, sessions: ShellSessions::default() }
+    }
+
+    /// Sessions owned by this shell, shared with [`ShellReset`].
+    pub fn sessions(&self) -> ShellSessions {
+        self.sessions.clone()
+    }
+
+    async fn execute_in_session(
+        &self,
+        session_id: &str,
+        input: &ShellInput,
+    ) -> anyhow::Result<String> {
+        let mut session = match self.sessions.take(session_id).await {
+            Some(session) => session,
+            None => ShellSession::start(&self.env.shell, &input.cwd)?,
+        };
+
+        let max_output_bytes = input.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT_BYTES);
+        let result = match input.timeout_secs {
+            Some(secs) => {
+                match tokio::time::timeout(
+                    Duration::from_secs(secs),
+                    session.execute(&input.command),
+                )
+                .await
+                {
+                    Ok(result) => result?,
+                    Err(_) => {
+                        session.kill().await;
+                        bail!(
+                            "Command timed out after {} seconds. Shell session '{}' was terminated",
+                            secs,
+                            session_id
+                        )
+                    }
+                }
+            }
+            None => session.execute(&input.command).await?,
+        };
+        let cwd = format!("<cwd>{}</cwd>", result.cwd.display());
+
+        if result.terminated {
+            let output =
+                format_output(result.output, max_output_bytes).unwrap_or_else(|e| e.to_string());
+            bail!(
+                "{}\n{}\nShell session '{}' was terminated",
+                output,
+                cwd,
+                session_id
+            );
+        }
+
+        self.sessions.put(session_id, session).await;
+
+        match format_output(result.output, max_output_bytes) {
+            Ok(output) => Ok(format!("{}\n{}", output, cwd)),
+            Err(output) => Err(anyhow::anyhow!("{}\n{}", output, cwd)),
+        }
     }
 }
 
@@ -87,11 +209,7 @@ impl ExecutableTool for Shell {
             bail!("Command string is empty or contains only whitespace".to_string());
         }
 
-        let parameter = if cfg!(target_os = "windows") {
-            "/C"
-        } else {
-            "-c"
-        };
+        let dialect = Dialect::detect(&self.env.shell);
 
         #[cfg(not(test))]
         {
@@ -101,22 +219,37 @@ impl ExecutableTool for Shell {
                 "{}",
                 TitleFormat::execute(format!(
                     "{} {} {}",
-                    self.env.shell, parameter, &input.command
+                    self.env.shell,
+                    dialect.flag(),
+                    &input.command
                 ))
                 .format()
             );
         }
 
+        if let Some(session_id) = input.session_id.as_deref() {
+            return self.execute_in_session(session_id, &input).await;
+        }
+
         let mut command = Command::new(&self.env.shell);
 
-        command.args([parameter, &input.command]);
+        command.args(dialect.args(&input.command, self.env.config.restricted == Some(true)));
 
         // Set the current working directory for the command
         command.current_dir(input.cwd);
         // Kill the command when the handler is dropped
         command.kill_on_drop(true);
 
-        format_output(CommandExecutor::new(command).colored().execute().await?)
+        let output = CommandExecutor::new(command)
+            .colored()
+            .timeout(input.timeout_secs.map(Duration::from_secs))
+            .execute()
+            .await?;
+
+        format_output(
+            output,
+            input.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT_BYTES),
+        )
     }
 }
 
@@ -139,6 +272,7 @@ mod tests {
             } else {
                 "/bin/sh".to_string()
             },
+            toolchain: Default::default(),
             provider_key: String::default(),
             provider_url: Default::default(),
             base_path: PathBuf::new(),
@@ -146,6 +280,14 @@ mod tests {
             qdrant_cluster: None,
             pid: std::process::id(),
             openai_key: None,
+            github_token: None,
+            gitlab_token: None,
+            bitbucket_token: None,
+            record_path: None,
+            replay_path: None,
+            trusted: true,
+            redaction_key: Vec::new(),
+            config: Default::default(),
         }
     }
 
@@ -170,6 +312,9 @@ mod tests {
             .call(ShellInput {
                 command: "echo 'Hello, World!'".to_string(),
                 cwd: env::current_dir().unwrap(),
+                session_id: None,
+                timeout_secs: None,
+                max_output_bytes: None,
             })
             .await
             .unwrap();
@@ -188,6 +333,9 @@ mod tests {
                     "echo 'to stderr' >&2; echo 'to stdout'".to_string()
                 },
                 cwd: env::current_dir().unwrap(),
+                session_id: None,
+                timeout_secs: None,
+                max_output_bytes: None,
             })
             .await
             .unwrap();
@@ -205,6 +353,9 @@ mod tests {
             .call(ShellInput {
                 command: "echo 'to stdout' && echo 'to stderr' >&2".to_string(),
                 cwd: env::current_dir().unwrap(),
+                session_id: None,
+                timeout_secs: None,
+                max_output_bytes: None,
             })
             .await
             .unwrap();
@@ -228,6 +379,9 @@ mod tests {
                     "pwd".to_string()
                 },
                 cwd: temp_dir.clone(),
+                session_id: None,
+                timeout_secs: None,
+                max_output_bytes: None,
             })
             .await
             .unwrap();
@@ -241,6 +395,9 @@ mod tests {
             .call(ShellInput {
                 command: "non_existent_command".to_string(),
                 cwd: env::current_dir().unwrap(),
+                session_id: None,
+                timeout_secs: None,
+                max_output_bytes: None,
             })
             .await;
 
@@ -263,7 +420,13 @@ mod tests {
     async fn test_shell_empty_command() {
         let shell = Shell::new(test_env());
         let result = shell
-            .call(ShellInput { command: "".to_string(), cwd: env::current_dir().unwrap() })
+            .call(ShellInput {
+                command: "".to_string(),
+                cwd: env::current_dir().unwrap(),
+                session_id: None,
+                timeout_secs: None,
+                max_output_bytes: None,
+            })
             .await;
         assert!(result.is_err());
         assert_eq!(
@@ -289,6 +452,9 @@ mod tests {
                     "pwd".to_string()
                 },
                 cwd: current_dir.clone(),
+                session_id: None,
+                timeout_secs: None,
+                max_output_bytes: None,
             })
             .await
             .unwrap();
@@ -306,6 +472,9 @@ mod tests {
             .call(ShellInput {
                 command: "echo 'first' && echo 'second'".to_string(),
                 cwd: env::current_dir().unwrap(),
+                session_id: None,
+                timeout_secs: None,
+                max_output_bytes: None,
             })
             .await
             .unwrap();
@@ -319,6 +488,9 @@ mod tests {
             .call(ShellInput {
                 command: "true".to_string(),
                 cwd: env::current_dir().unwrap(),
+                session_id: None,
+                timeout_secs: None,
+                max_output_bytes: None,
             })
             .await
             .unwrap();
@@ -334,6 +506,9 @@ mod tests {
             .call(ShellInput {
                 command: "echo ''".to_string(),
                 cwd: env::current_dir().unwrap(),
+                session_id: None,
+                timeout_secs: None,
+                max_output_bytes: None,
             })
             .await
             .unwrap();
@@ -349,6 +524,9 @@ mod tests {
             .call(ShellInput {
                 command: "echo $PATH".to_string(),
                 cwd: env::current_dir().unwrap(),
+                session_id: None,
+                timeout_secs: None,
+                max_output_bytes: None,
             })
             .await
             .unwrap();
@@ -357,6 +535,79 @@ mod tests {
         assert!(!result.contains("Error:"));
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_shell_session_keeps_state() {
+        let shell = Shell::new(test_env());
+        let temp_dir = fs::canonicalize(env::temp_dir()).unwrap();
+        let input = |command: &str| ShellInput {
+            command: command.to_string(),
+            cwd: env::current_dir().unwrap(),
+            session_id: Some("test".to_string()),
+            timeout_secs: None,
+            max_output_bytes: None,
+        };
+
+        shell
+            .call(input(&format!(
+                "cd {} && export FOO=bar",
+                temp_dir.display()
+            )))
+            .await
+            .unwrap();
+        let actual = shell.call(input("echo $FOO")).await.unwrap();
+
+        let expected = format!("<stdout>bar\n</stdout>\n<cwd>{}</cwd>", temp_dir.display());
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_shell_timeout_kills_command() {
+        let shell = Shell::new(test_env());
+        let result = shell
+            .call(ShellInput {
+                command: "echo started; sleep 30 & sleep 30".to_string(),
+                cwd: env::current_dir().unwrap(),
+                session_id: None,
+                timeout_secs: Some(1),
+                max_output_bytes: None,
+            })
+            .await;
+
+        let actual = result.unwrap_err().to_string();
+        let expected =
+            "<stdout>started\n</stdout>\nCommand timed out after 1 seconds and was killed.";
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_shell_output_truncation() {
+        let shell = Shell::new(test_env());
+        let result = shell
+            .call(ShellInput {
+                command: "echo 0123456789abcdefghij".to_string(),
+                cwd: env::current_dir().unwrap(),
+                session_id: None,
+                timeout_secs: None,
+                max_output_bytes: Some(10),
+            })
+            .await
+            .unwrap();
+
+        let expected =
+            "<stdout>01234\n... [output truncated: 11 bytes omitted] ...\nghij\n</stdout>";
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_truncate_output_respects_char_boundaries() {
+        let fixture = "ééééé";
+        let actual = truncate_output(fixture, 5);
+        let expected = "é\n... [output truncated: 6 bytes omitted] ...\né";
+        assert_eq!(actual, expected);
+    }
+
     #[tokio::test]
     async fn test_shell_full_path_command() {
         let shell = Shell::new(test_env());
@@ -368,7 +619,13 @@ mod tests {
         };
 
         let result = shell
-            .call(ShellInput { command: cmd.to_string(), cwd: env::current_dir().unwrap() })
+            .call(ShellInput {
+                command: cmd.to_string(),
+                cwd: env::current_dir().unwrap(),
+                session_id: None,
+                timeout_secs: None,
+                max_output_bytes: None,
+            })
             .await;
 
         // In rbash, this would fail with a permission error
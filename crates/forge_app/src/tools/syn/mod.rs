@@ -1,3 +1,5 @@
+mod query;
 mod validate;
 
-pub use validate::validate;
+pub use query::query;
+pub use validate::{extension, grammars, validate};
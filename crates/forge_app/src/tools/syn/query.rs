@@ -0,0 +1,150 @@
+use tree_sitter::{Language, Parser, Query, QueryCursor, StreamingIterator};
+
+/// A node matched by a tree-sitter query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryMatch {
+    /// Name of the capture the node was matched by.
+    pub capture: String,
+    /// One based line of the start of the node.
+    pub line: usize,
+    /// One based column of the start of the node.
+    pub column: usize,
+    /// First line of the text of the node.
+    pub snippet: String,
+}
+
+/// Runs a compiled tree-sitter query against the content and returns the
+/// matched nodes in the order they appear.
+///
+/// # Arguments
+/// * `language` - The language the query was compiled for
+/// * `query` - The compiled query
+/// * `content` - The source code to search
+/// * `capture` - Name of the capture to report. When not set, the outermost
+///   captured node of each match is reported.
+pub fn query(
+    language: &Language,
+    query: &Query,
+    content: &str,
+    capture: Option<&str>,
+) -> anyhow::Result<Vec<QueryMatch>> {
+    let mut parser = Parser::new();
+    parser.set_language(language)?;
+    let Some(tree) = parser.parse(content, None) else {
+        return Ok(vec![]);
+    };
+
+    let capture_index = match capture {
+        Some(name) => Some(
+            query
+                .capture_index_for_name(name)
+                .ok_or_else(|| anyhow::anyhow!("Query has no capture named '@{}'", name))?,
+        ),
+        None => None,
+    };
+
+    let mut result: Vec<QueryMatch> = vec![];
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
+    while let Some(m) = matches.next() {
+        let selected = match capture_index {
+            Some(index) => m.captures.iter().find(|c| c.index == index),
+            None => m
+                .captures
+                .iter()
+                .max_by_key(|c| c.node.end_byte() - c.node.start_byte()),
+        };
+        let Some(selected) = selected else {
+            continue;
+        };
+
+        let start = selected.node.start_position();
+        let snippet = content[selected.node.byte_range()]
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim_end()
+            .to_string();
+        let found = QueryMatch {
+            capture: query.capture_names()[selected.index as usize].to_string(),
+            line: start.row + 1,
+            column: start.column + 1,
+            snippet,
+        };
+
+        if !result.contains(&found) {
+            result.push(found);
+        }
+    }
+
+    result.sort_by_key(|m| (m.line, m.column));
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const FIXTURE: &str = r#"fn helper() {}
+
+fn main() {
+    helper();
+    other();
+}
+"#;
+
+    fn rust() -> Language {
+        tree_sitter_rust::LANGUAGE.into()
+    }
+
+    #[test]
+    fn test_query_outermost_capture() {
+        let language = rust();
+        let fixture = Query::new(
+            &language,
+            r#"(call_expression function: (identifier) @callee (#eq? @callee "helper")) @call"#,
+        )
+        .unwrap();
+
+        let actual = query(&language, &fixture, FIXTURE, None).unwrap();
+
+        let expected = vec![QueryMatch {
+            capture: "call".to_string(),
+            line: 4,
+            column: 5,
+            snippet: "helper()".to_string(),
+        }];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_query_named_capture() {
+        let language = rust();
+        let fixture = Query::new(
+            &language,
+            "(function_item name: (identifier) @name) @function",
+        )
+        .unwrap();
+
+        let actual = query(&language, &fixture, FIXTURE, Some("name"))
+            .unwrap()
+            .into_iter()
+            .map(|m| m.snippet)
+            .collect::<Vec<_>>();
+
+        let expected = vec!["helper".to_string(), "main".to_string()];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_query_unknown_capture() {
+        let language = rust();
+        let fixture = Query::new(&language, "(function_item) @function").unwrap();
+
+        let actual = query(&language, &fixture, FIXTURE, Some("missing"));
+
+        assert!(actual.is_err());
+    }
+}
@@ -1,7 +1,43 @@
+use std::fmt::{self, Display};
 use std::path::Path;
 
 use thiserror::Error;
-use tree_sitter::{Language, LanguageError, Parser};
+use tree_sitter::{Language, LanguageError, Node, Parser};
+
+/// Maximum number of syntax errors reported for a single file.
+const MAX_REPORTED_ERRORS: usize = 5;
+
+/// Maximum number of characters of the offending source included in a
+/// syntax error.
+const MAX_SNIPPET_LENGTH: usize = 20;
+
+/// Location and description of a single syntax error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    /// One based line of the error
+    pub line: usize,
+    /// One based column of the error
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+fn format_errors(errors: &[SyntaxError]) -> String {
+    errors
+        .iter()
+        .map(|error| error.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
 
 /// Represents possible errors that can occur during syntax validation
 #[derive(Debug, Error, PartialEq)]
@@ -14,11 +50,13 @@ pub enum Error {
     Language(#[from] LanguageError),
     /// Failed to parse the content
     #[error(
-        "Syntax error found in file with extension {extension}. Hint: Please retry in raw mode without HTML-encoding angle brackets."
+        "Syntax error found in file with extension {extension} ({}). Hint: Please retry in raw mode without HTML-encoding angle brackets.",
+        format_errors(.errors)
     )]
     Parse {
         file_path: String,
         extension: String,
+        errors: Vec<SyntaxError>,
     },
 }
 
@@ -36,8 +74,10 @@ pub enum Error {
 ///
 /// # Supported Languages
 /// * Rust (.rs)
-/// * JavaScript/TypeScript (.js, .jsx, .ts, .tsx)
+/// * JavaScript/TypeScript (.js, .jsx, .mjs, .cjs, .ts, .tsx)
 /// * Python (.py)
+/// * C++, CSS, Go, Java, Ruby, Scala, C# and PHP
+/// * JSON (.json), YAML (.yaml, .yml) and TOML (.toml)
 pub fn extension(ext: &str) -> Option<Language> {
     match ext.to_lowercase().as_str() {
         "rs" => Some(tree_sitter_rust::LANGUAGE.into()),
@@ -47,13 +87,51 @@ pub fn extension(ext: &str) -> Option<Language> {
         "go" => Some(tree_sitter_go::LANGUAGE.into()),
         "java" => Some(tree_sitter_java::LANGUAGE.into()),
         "rb" => Some(tree_sitter_ruby::LANGUAGE.into()),
+        "cs" => Some(tree_sitter_c_sharp::LANGUAGE.into()),
+        "php" => Some(tree_sitter_php::LANGUAGE_PHP.into()),
         "scala" => Some(tree_sitter_scala::LANGUAGE.into()),
-        "ts" | "js" => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
-        "tsx" => Some(tree_sitter_typescript::LANGUAGE_TSX.into()),
+        "ts" => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
+        // JavaScript files commonly contain JSX, which only the TSX grammar accepts
+        "tsx" | "js" | "jsx" | "mjs" | "cjs" => Some(tree_sitter_typescript::LANGUAGE_TSX.into()),
+        "json" => Some(tree_sitter_json::LANGUAGE.into()),
+        "yaml" | "yml" => Some(tree_sitter_yaml::LANGUAGE.into()),
+        "toml" => Some(tree_sitter_toml_ng::LANGUAGE.into()),
         _ => None,
     }
 }
 
+/// Languages with a bundled grammar, along with one of their extensions.
+const LANGUAGES: [(&str, &str); 15] = [
+    ("Rust", "rs"),
+    ("Python", "py"),
+    ("C++", "cpp"),
+    ("CSS", "css"),
+    ("Go", "go"),
+    ("Java", "java"),
+    ("Ruby", "rb"),
+    ("C#", "cs"),
+    ("PHP", "php"),
+    ("Scala", "scala"),
+    ("TypeScript", "ts"),
+    ("TSX", "tsx"),
+    ("JSON", "json"),
+    ("YAML", "yaml"),
+    ("TOML", "toml"),
+];
+
+/// Loads every bundled grammar into a parser, which fails when the grammar was
+/// generated for a version of tree-sitter that the runtime doesn't support.
+pub fn grammars() -> Vec<(&'static str, Result<(), LanguageError>)> {
+    LANGUAGES
+        .iter()
+        .map(|(name, ext)| {
+            // note: every extension of the list has a grammar.
+            let language = extension(ext).unwrap();
+            (*name, Parser::new().set_language(&language))
+        })
+        .collect()
+}
+
 /// Validates source code content using Tree-sitter parsers.
 ///
 /// This function attempts to parse the provided content using a Tree-sitter
@@ -95,6 +173,7 @@ pub fn validate(path: impl AsRef<Path>, content: &str) -> Option<Error> {
         return Some(Error::Parse {
             file_path: path.display().to_string(),
             extension: ext.to_string(),
+            errors: vec![],
         });
     };
 
@@ -103,9 +182,53 @@ pub fn validate(path: impl AsRef<Path>, content: &str) -> Option<Error> {
     (root_node.has_error() || root_node.is_error()).then(|| Error::Parse {
         file_path: path.display().to_string(),
         extension: ext.to_string(),
+        errors: syntax_errors(root_node, content),
     })
 }
 
+/// Collects the locations of the error and missing nodes of the tree, in the
+/// order they appear in the source.
+fn syntax_errors(root: Node, content: &str) -> Vec<SyntaxError> {
+    let mut errors = vec![];
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if errors.len() == MAX_REPORTED_ERRORS {
+            break;
+        }
+
+        if node.is_missing() || node.is_error() {
+            let position = node.start_position();
+            let message = if node.is_missing() {
+                format!("missing `{}`", node.kind())
+            } else {
+                let snippet = content[node.byte_range()]
+                    .lines()
+                    .find(|line| !line.trim().is_empty())
+                    .unwrap_or_default()
+                    .trim()
+                    .chars()
+                    .take(MAX_SNIPPET_LENGTH)
+                    .collect::<String>();
+                // The message ends up in XML attributes, so avoid double quotes
+                format!("unexpected `{}`", snippet.replace('"', "'"))
+            };
+            errors.push(SyntaxError {
+                line: position.row + 1,
+                column: position.column + 1,
+                message,
+            });
+            continue;
+        }
+
+        if node.has_error() {
+            let mut cursor = node.walk();
+            let children = node.children(&mut cursor).collect::<Vec<_>>();
+            stack.extend(children.into_iter().rev());
+        }
+    }
+    errors
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -119,6 +242,24 @@ mod tests {
     const JAVASCRIPT_INVALID: &str = include_str!("lang/javascript/invalid.js");
     const PYTHON_VALID: &str = include_str!("lang/python/valid.py");
     const PYTHON_INVALID: &str = include_str!("lang/python/invalid.py");
+    const GO_VALID: &str = include_str!("lang/go/valid.go");
+    const GO_INVALID: &str = include_str!("lang/go/invalid.go");
+    const JSON_VALID: &str = include_str!("lang/json/valid.json");
+    const JSON_INVALID: &str = include_str!("lang/json/invalid.json");
+    const YAML_VALID: &str = include_str!("lang/yaml/valid.yaml");
+    const YAML_INVALID: &str = include_str!("lang/yaml/invalid.yaml");
+    const TOML_VALID: &str = include_str!("lang/toml/valid.toml");
+    const TOML_INVALID: &str = include_str!("lang/toml/invalid.toml");
+
+    #[test]
+    fn test_grammars_load() {
+        let failed = grammars()
+            .into_iter()
+            .filter(|(_, result)| result.is_err())
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        assert!(failed.is_empty(), "Failed to load grammars: {failed:?}");
+    }
 
     #[test]
     fn test_rust_valid() {
@@ -159,6 +300,77 @@ mod tests {
         assert!(matches!(result, Some(Error::Parse { .. })));
     }
 
+    #[test]
+    fn test_jsx_valid() {
+        let path = PathBuf::from("test.jsx");
+        let content = "const App = () => <div className='app'>Hello</div>;";
+        assert!(validate(&path, content).is_none());
+    }
+
+    #[test]
+    fn test_go_valid() {
+        let path = PathBuf::from("test.go");
+        assert!(validate(&path, GO_VALID).is_none());
+    }
+
+    #[test]
+    fn test_go_invalid() {
+        let path = PathBuf::from("test.go");
+        let result = validate(&path, GO_INVALID);
+        assert!(matches!(result, Some(Error::Parse { .. })));
+    }
+
+    #[test]
+    fn test_json_valid() {
+        let path = PathBuf::from("test.json");
+        assert!(validate(&path, JSON_VALID).is_none());
+    }
+
+    #[test]
+    fn test_json_invalid() {
+        let path = PathBuf::from("test.json");
+        let result = validate(&path, JSON_INVALID);
+        assert!(matches!(result, Some(Error::Parse { .. })));
+    }
+
+    #[test]
+    fn test_yaml_valid() {
+        let path = PathBuf::from("test.yml");
+        assert!(validate(&path, YAML_VALID).is_none());
+    }
+
+    #[test]
+    fn test_yaml_invalid() {
+        let path = PathBuf::from("test.yaml");
+        let result = validate(&path, YAML_INVALID);
+        assert!(matches!(result, Some(Error::Parse { .. })));
+    }
+
+    #[test]
+    fn test_toml_valid() {
+        let path = PathBuf::from("test.toml");
+        assert!(validate(&path, TOML_VALID).is_none());
+    }
+
+    #[test]
+    fn test_toml_invalid() {
+        let path = PathBuf::from("test.toml");
+        let result = validate(&path, TOML_INVALID);
+        assert!(matches!(result, Some(Error::Parse { .. })));
+    }
+
+    #[test]
+    fn test_error_locations() {
+        let path = PathBuf::from("test.rs");
+        let Some(Error::Parse { errors, .. }) = validate(&path, RUST_INVALID) else {
+            panic!("Expected a parse error");
+        };
+        let actual = errors.first().cloned();
+        let expected =
+            Some(SyntaxError { line: 2, column: 42, message: "missing `)`".to_string() });
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_unsupported_extension() {
         let content = "Some random content";
@@ -184,7 +396,7 @@ mod tests {
         let error = validate(&path, "fn main() { let x = ").unwrap();
         assert_eq!(
             error.to_string(),
-            "Syntax error found in file with extension rs. Hint: Please retry in raw mode without HTML-encoding angle brackets."
+            "Syntax error found in file with extension rs (line 1, column 1: unexpected `fn main() { let x =`). Hint: Please retry in raw mode without HTML-encoding angle brackets."
         );
     }
 }
@@ -0,0 +1,401 @@
+use std::fmt::{self, Display};
+use std::path::Path;
+
+/// Lines of a failure message that are kept, the rest is noise such as
+/// backtraces and code frames.
+const MAX_MESSAGE_LINES: usize = 20;
+
+/// Test frameworks the tests of a project are run with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framework {
+    Cargo,
+    Go,
+    Jest,
+    Pytest,
+}
+
+/// A test that failed, as parsed from the output of the framework.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestFailure {
+    pub name: String,
+    /// Location of the failure, eg: `src/lib.rs:10:9`, when it's reported
+    pub file: Option<String>,
+    pub message: String,
+}
+
+impl Display for TestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FAILED {}", self.name)?;
+        if let Some(file) = &self.file {
+            write!(f, " at {file}")?;
+        }
+        for line in self.message.lines() {
+            write!(f, "\n    {line}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Framework {
+    /// Detects the framework from the manifests in the directory of the
+    /// project.
+    pub fn detect(dir: &Path) -> Option<Self> {
+        if dir.join("Cargo.toml").is_file() {
+            return Some(Self::Cargo);
+        }
+        if dir.join("go.mod").is_file() {
+            return Some(Self::Go);
+        }
+        let uses_jest = std::fs::read_to_string(dir.join("package.json"))
+            .is_ok_and(|manifest| manifest.contains("jest"));
+        if uses_jest {
+            return Some(Self::Jest);
+        }
+        [
+            "pytest.ini",
+            "pyproject.toml",
+            "setup.cfg",
+            "tox.ini",
+            "setup.py",
+        ]
+        .iter()
+        .any(|file| dir.join(file).is_file())
+        .then_some(Self::Pytest)
+    }
+
+    /// The program and arguments that run the tests, limited to those
+    /// matching `filter` if any.
+    pub fn command(&self, filter: Option<&str>) -> (&'static str, Vec<String>) {
+        let (program, args, filter_flag): (_, &[&str], _) = match self {
+            Self::Cargo => ("cargo", &["test", "--no-fail-fast"], None),
+            Self::Go => ("go", &["test", "./..."], Some("-run")),
+            Self::Jest => ("npx", &["jest", "--ci"], Some("-t")),
+            Self::Pytest => ("python", &["-m", "pytest", "-q", "-rfE"], Some("-k")),
+        };
+        let mut args = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        if let Some(filter) = filter {
+            args.extend(filter_flag.map(str::to_string));
+            args.push(filter.to_string());
+        }
+        (program, args)
+    }
+
+    /// Parses the failed tests from the combined output of the run.
+    pub fn parse(&self, output: &str) -> Vec<TestFailure> {
+        match self {
+            Self::Cargo => parse_cargo(output),
+            Self::Go => parse_go(output),
+            Self::Jest => parse_jest(output),
+            Self::Pytest => parse_pytest(output),
+        }
+    }
+}
+
+fn message(lines: &[&str]) -> String {
+    let lines = lines
+        .iter()
+        .map(|line| line.trim_end())
+        .skip_while(|line| line.trim().is_empty())
+        .collect::<Vec<_>>();
+    let end = lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(0, |i| i + 1);
+    let mut message = lines[..end.min(MAX_MESSAGE_LINES)].join("\n");
+    if end > MAX_MESSAGE_LINES {
+        message.push_str(&format!("\n... {} more lines", end - MAX_MESSAGE_LINES));
+    }
+    message
+}
+
+/// Parses the `---- <name> stdout ----` sections of the failed tests.
+fn parse_cargo(output: &str) -> Vec<TestFailure> {
+    let lines = output.lines().collect::<Vec<_>>();
+    let mut failures = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let Some(name) = lines[i]
+            .strip_prefix("---- ")
+            .and_then(|line| line.strip_suffix(" stdout ----"))
+        else {
+            i += 1;
+            continue;
+        };
+        let start = i + 1;
+        i = start;
+        while i < lines.len() && !lines[i].starts_with("---- ") && lines[i] != "failures:" {
+            i += 1;
+        }
+
+        let section = &lines[start..i];
+        let panic = section
+            .iter()
+            .position(|line| line.contains("panicked at "));
+        let (file, body) = match panic {
+            Some(at) => {
+                let (_, location) = section[at].split_once("panicked at ").unwrap_or_default();
+                // note: older versions of Rust print `panicked at 'message', file`.
+                match location
+                    .strip_prefix('\'')
+                    .and_then(|rest| rest.rsplit_once("', "))
+                {
+                    Some((text, file)) => (Some(file.to_string()), vec![text]),
+                    None => (
+                        Some(location.trim_end_matches(':').to_string()),
+                        section[at + 1..].to_vec(),
+                    ),
+                }
+            }
+            None => (None, section.to_vec()),
+        };
+        let body = body
+            .into_iter()
+            .take_while(|line| !line.starts_with("note: run with `RUST_BACKTRACE"))
+            .collect::<Vec<_>>();
+        failures.push(TestFailure { name: name.to_string(), file, message: message(&body) });
+    }
+    failures
+}
+
+/// Parses the `--- FAIL: <name>` lines, followed by the indented output of
+/// the test such as `add_test.go:8: expected 4, got 3`.
+fn parse_go(output: &str) -> Vec<TestFailure> {
+    let lines = output.lines().collect::<Vec<_>>();
+    let mut failures = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let Some(rest) = line.trim_start().strip_prefix("--- FAIL: ") else {
+            continue;
+        };
+        let name = rest.split(" (").next().unwrap_or(rest).to_string();
+        let indent = line.len() - line.trim_start().len();
+        let body = lines[i + 1..]
+            .iter()
+            .take_while(|line| {
+                line.len() - line.trim_start().len() > indent
+                    && !line.trim_start().starts_with("--- ")
+            })
+            .map(|line| line.trim())
+            .collect::<Vec<_>>();
+
+        let location = body.first().and_then(|line| {
+            let (file, _) = line.split_once(": ")?;
+            file.contains(".go:").then_some(file)
+        });
+        let body = match location {
+            Some(file) => {
+                let mut body = body.clone();
+                body[0] = &body[0][file.len() + 2..];
+                body
+            }
+            None => body,
+        };
+        failures.push(TestFailure {
+            name,
+            file: location.map(str::to_string),
+            message: message(&body),
+        });
+    }
+    failures
+}
+
+/// Parses the `● <name>` blocks, which follow the `FAIL <file>` line of the
+/// test file.
+fn parse_jest(output: &str) -> Vec<TestFailure> {
+    fn finish(failures: &mut [TestFailure], body: &mut Vec<&str>) {
+        if body.is_empty() {
+            return;
+        }
+        if let Some(failure) = failures.last_mut() {
+            // note: the code frames and stack traces are left out.
+            let text = body
+                .iter()
+                .copied()
+                .take_while(|line| !line.contains(" | ") && !line.trim_start().starts_with("at "))
+                .map(str::trim)
+                .collect::<Vec<_>>();
+            failure.message = message(&text);
+        }
+        body.clear();
+    }
+
+    let mut failures: Vec<TestFailure> = Vec::new();
+    let mut file = None;
+    let mut body: Vec<&str> = Vec::new();
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if let Some(path) = trimmed.strip_prefix("FAIL ") {
+            finish(&mut failures, &mut body);
+            file = Some(path.trim().to_string());
+        } else if let Some(name) = trimmed.strip_prefix("● ") {
+            finish(&mut failures, &mut body);
+            failures.push(TestFailure {
+                name: name.to_string(),
+                file: file.clone(),
+                message: String::new(),
+            });
+        } else if trimmed.starts_with("PASS ") || trimmed.starts_with("Test Suites:") {
+            finish(&mut failures, &mut body);
+            file = None;
+        } else if failures.last().is_some() {
+            body.push(line);
+        }
+    }
+    finish(&mut failures, &mut body);
+    failures
+}
+
+/// Parses the `FAILED <file>::<name> - <message>` lines of the short test
+/// summary.
+fn parse_pytest(output: &str) -> Vec<TestFailure> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let rest = line
+                .strip_prefix("FAILED ")
+                .or_else(|| line.strip_prefix("ERROR "))?;
+            let (id, text) = rest.split_once(" - ").unwrap_or((rest, ""));
+            let (file, name) = match id.split_once("::") {
+                Some((file, name)) => (Some(file.to_string()), name.to_string()),
+                None => (None, id.to_string()),
+            };
+            Some(TestFailure { name, file, message: text.to_string() })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    fn failure(name: &str, file: Option<&str>, message: &str) -> TestFailure {
+        TestFailure {
+            name: name.to_string(),
+            file: file.map(str::to_string),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_detect() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(Framework::detect(&dir.path()), None);
+
+        std::fs::write(dir.path().join("package.json"), r#"{"scripts": {}}"#).unwrap();
+        std::fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+        assert_eq!(Framework::detect(&dir.path()), Some(Framework::Pytest));
+
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"devDependencies": {"jest": "^29"}}"#,
+        )
+        .unwrap();
+        assert_eq!(Framework::detect(&dir.path()), Some(Framework::Jest));
+    }
+
+    #[test]
+    fn test_command() {
+        let actual = Framework::Go.command(Some("TestAdd"));
+        let expected = ("go", vec!["test", "./...", "-run", "TestAdd"]);
+        assert_eq!(
+            (actual.0, actual.1.iter().map(String::as_str).collect()),
+            expected
+        );
+
+        let actual = Framework::Cargo.command(Some("add"));
+        assert_eq!(actual.1, vec!["test", "--no-fail-fast", "add"]);
+    }
+
+    #[test]
+    fn test_parse_cargo() {
+        let output = r#"
+running 2 tests
+test tests::test_add ... FAILED
+test tests::test_sub ... ok
+
+failures:
+
+---- tests::test_add stdout ----
+
+thread 'tests::test_add' panicked at src/lib.rs:10:9:
+assertion `left == right` failed
+  left: 3
+ right: 4
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+
+failures:
+    tests::test_add
+
+test result: FAILED. 1 passed; 1 failed; 0 ignored
+"#;
+        let actual = Framework::Cargo.parse(output);
+        let expected = vec![failure(
+            "tests::test_add",
+            Some("src/lib.rs:10:9"),
+            "assertion `left == right` failed\n  left: 3\n right: 4",
+        )];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_go() {
+        let output = "--- FAIL: TestAdd (0.00s)\n    add_test.go:8: expected 4, got 3\n--- FAIL: TestSub (0.00s)\n    --- FAIL: TestSub/negative (0.00s)\n        sub_test.go:12: wrong sign\nFAIL\n";
+        let actual = Framework::Go.parse(output);
+        let expected = vec![
+            failure("TestAdd", Some("add_test.go:8"), "expected 4, got 3"),
+            failure("TestSub", None, ""),
+            failure("TestSub/negative", Some("sub_test.go:12"), "wrong sign"),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_jest() {
+        let output = r#" FAIL  src/sum.test.js
+  ● math › adds numbers
+
+    expect(received).toBe(expected) // Object.is equality
+
+    Expected: 4
+    Received: 3
+
+      3 | test('adds numbers', () => {
+    > 4 |   expect(sum(1, 2)).toBe(4);
+
+      at Object.toBe (src/sum.test.js:4:21)
+
+ PASS  src/other.test.js
+Test Suites: 1 failed, 1 passed, 2 total
+"#;
+        let actual = Framework::Jest.parse(output);
+        let expected = vec![failure(
+            "math › adds numbers",
+            Some("src/sum.test.js"),
+            "expect(received).toBe(expected) // Object.is equality\n\nExpected: 4\nReceived: 3",
+        )];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_pytest() {
+        let output = "F.\n=== short test summary info ===\nFAILED tests/test_math.py::test_add - assert 3 == 4\nERROR tests/test_db.py\n1 failed, 1 passed\n";
+        let actual = Framework::Pytest.parse(output);
+        let expected = vec![
+            failure("test_add", Some("tests/test_math.py"), "assert 3 == 4"),
+            failure("tests/test_db.py", None, ""),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_message_is_truncated() {
+        let lines = (1..=25).map(|i| i.to_string()).collect::<Vec<_>>();
+        let lines = lines.iter().map(String::as_str).collect::<Vec<_>>();
+
+        let actual = message(&lines);
+        assert!(actual.ends_with("20\n... 5 more lines"), "{actual}");
+    }
+}
@@ -0,0 +1,4 @@
+mod framework;
+mod run_tests;
+
+pub use run_tests::*;
@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tokio::process::Command;
+
+use super::framework::Framework;
+use crate::tools::shell::executor::CommandExecutor;
+
+/// Failures that are listed, the count of the rest is reported.
+const MAX_FAILURES: usize = 20;
+
+/// Lines at the end of the output that are returned when no failure can be
+/// parsed, eg: when the tests don't compile.
+const MAX_OUTPUT_LINES: usize = 60;
+
+/// Default seconds after which the run is killed.
+const DEFAULT_TIMEOUT_SECS: u64 = 600;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct RunTestsInput {
+    /// The absolute path of the project's root directory (default: the
+    /// current working directory).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Runs only the tests whose name matches the filter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    /// Seconds after which the run is killed (default: 600).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Runs the tests of a project and reports each failed test with its name,
+/// location and message. The framework is detected from the project's
+/// manifest: cargo test (Cargo.toml), go test (go.mod), jest (package.json)
+/// or pytest (pyproject.toml, setup.py, pytest.ini...). Prefer it over the
+/// shell tool for running tests, pass a `filter` to re-run only the failed
+/// ones.
+#[derive(ToolDescription)]
+pub struct RunTests {
+    guard: PathGuard,
+}
+
+impl RunTests {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for RunTests {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_test_run")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for RunTests {
+    type Input = RunTestsInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let dir = match &input.path {
+            Some(path) => self.guard.resolve(path)?,
+            None => self.guard.cwd().to_path_buf(),
+        };
+        let framework = Framework::detect(&dir).with_context(|| {
+            format!(
+                "No supported test framework detected in {}, run the tests with the shell tool instead",
+                dir.display()
+            )
+        })?;
+        let (program, args) = framework.command(input.filter.as_deref());
+        let command_line = format!("{program} {}", args.join(" "));
+
+        #[cfg(not(test))]
+        {
+            use forge_display::TitleFormat;
+
+            println!("{}", TitleFormat::execute(&command_line).format());
+        }
+
+        let mut command = Command::new(program);
+        command.args(&args).current_dir(&dir).kill_on_drop(true);
+        let timeout = input.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS);
+        let output = CommandExecutor::new(command)
+            .timeout(Some(Duration::from_secs(timeout)))
+            .execute()
+            .await
+            .with_context(|| format!("Failed to run {command_line}"))?;
+
+        if output.success {
+            return Ok(format!("All tests passed: {command_line}"));
+        }
+
+        let combined = format!("{}\n{}", output.stdout, output.stderr);
+        let failures = framework.parse(&combined);
+        let mut report = format!("<command>{command_line}</command>\n");
+        if let Some(timeout) = output.timed_out {
+            report.push_str(&format!(
+                "The tests timed out after {} seconds and were killed.\n",
+                timeout.as_secs()
+            ));
+        }
+        if failures.is_empty() {
+            let lines = combined.trim_end().lines().collect::<Vec<_>>();
+            let tail = &lines[lines.len().saturating_sub(MAX_OUTPUT_LINES)..];
+            report.push_str(&format!(
+                "No failed test could be parsed from the output, its last lines are:\n<output>{}</output>",
+                tail.join("\n")
+            ));
+        } else {
+            report.push_str(&format!("{} tests failed:", failures.len()));
+            for failure in failures.iter().take(MAX_FAILURES) {
+                report.push_str(&format!("\n{failure}"));
+            }
+            if failures.len() > MAX_FAILURES {
+                report.push_str(&format!(
+                    "\n... {} more failures",
+                    failures.len() - MAX_FAILURES
+                ));
+            }
+        }
+        Err(anyhow::anyhow!(report))
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Context;
+use chrono::DateTime;
+use forge_domain::{ExecutableTool, NamedTool, PathGuard, ToolDescription, ToolName};
+use forge_tool_macros::ToolDescription;
+use forge_walker::Walker;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+/// Comments that are returned, the count of the rest is reported.
+const DEFAULT_MAX_RESULTS: usize = 200;
+
+/// Tags that are looked for unless others are given.
+const DEFAULT_TAGS: [&str; 3] = ["TODO", "FIXME", "HACK"];
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ScanTodosInput {
+    /// The absolute path of the file or directory to scan (default: the
+    /// current working directory). Directories are scanned recursively.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Tags to look for, matched in uppercase (default: TODO, FIXME and HACK).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    /// Whether the author and date of each comment are looked up with git
+    /// blame (default: true).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blame: Option<bool>,
+    /// Maximum comments to return (default: 200).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_results: Option<usize>,
+}
+
+/// A TODO comment of the code.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Todo {
+    pub file: String,
+    pub line: usize,
+    pub tag: String,
+    /// Who the comment is assigned to, eg: `alice` of `TODO(alice): ...`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub text: String,
+    /// Author of the line according to git blame
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Date the line was committed, eg: `2025-01-31`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+}
+
+/// Lists the TODO, FIXME and HACK comments of the files in a directory as JSON
+/// objects with their file, line, tag, owner (eg: `TODO(alice)`) and text,
+/// along with the author and date of the line from git blame. Use it to find
+/// the work left in a module, eg: to clean up its TODOs or to plan a task,
+/// rather than searching for each tag.
+#[derive(ToolDescription)]
+pub struct ScanTodos {
+    guard: PathGuard,
+}
+
+impl ScanTodos {
+    pub fn new(guard: PathGuard) -> Self {
+        Self { guard }
+    }
+}
+
+impl NamedTool for ScanTodos {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_code_todos")
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutableTool for ScanTodos {
+    type Input = ScanTodosInput;
+
+    async fn call(&self, input: Self::Input) -> anyhow::Result<String> {
+        let path = match &input.path {
+            Some(path) => self.guard.resolve(path)?,
+            None => self.guard.cwd().to_path_buf(),
+        };
+        let tags = input
+            .tags
+            .unwrap_or_else(|| DEFAULT_TAGS.map(String::from).to_vec());
+        let regex = comment_regex(&tags)?;
+        let max_results = input.max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+
+        #[cfg(not(test))]
+        {
+            use forge_display::TitleFormat;
+
+            println!(
+                "{}",
+                TitleFormat::execute("todos")
+                    .sub_title(path.display().to_string())
+                    .format()
+            );
+        }
+
+        let files = if path.is_file() {
+            vec![path.clone()]
+        } else {
+            let mut files = Walker::max_all()
+                .cwd(path.clone())
+                .get()
+                .await
+                .with_context(|| format!("Failed to walk directory '{}'", path.display()))?
+                .into_iter()
+                .filter(|file| !file.is_dir())
+                .map(|file| path.join(file.path))
+                .collect::<Vec<_>>();
+            files.sort();
+            files
+        };
+
+        let mut todos = Vec::new();
+        let mut total = 0;
+        for file in files {
+            // Skip binary or unreadable files
+            let Ok(content) = tokio::fs::read_to_string(&file).await else {
+                continue;
+            };
+            let mut found = scan(&regex, &file.display().to_string(), &content);
+            if found.is_empty() {
+                continue;
+            }
+            total += found.len();
+            found.truncate(max_results.saturating_sub(todos.len()));
+            if input.blame.unwrap_or(true) && !found.is_empty() {
+                let blame = blame(&file).await;
+                for todo in found.iter_mut() {
+                    if let Some((author, date)) = blame.get(&todo.line) {
+                        todo.author = Some(author.clone());
+                        todo.date = date.clone();
+                    }
+                }
+            }
+            todos.extend(found);
+        }
+
+        if todos.is_empty() {
+            return Ok(format!("No {} comments found", tags.join(", ")));
+        }
+        let mut report = format!("{total} comments found");
+        for todo in &todos {
+            report.push_str(&format!("\n{}", serde_json::to_string(todo)?));
+        }
+        if total > todos.len() {
+            report.push_str(&format!(
+                "\n... {} more comments, scan a narrower path or increase max_results",
+                total - todos.len()
+            ));
+        }
+        Ok(report)
+    }
+}
+
+/// Matches the tags in comments, eg: `// TODO(alice): ...`, `# FIXME ...` or
+/// `/* HACK: ... */`, but not in identifiers such as `todo_list`.
+fn comment_regex(tags: &[String]) -> anyhow::Result<Regex> {
+    let tags = tags
+        .iter()
+        .map(|tag| regex::escape(&tag.to_uppercase()))
+        .collect::<Vec<_>>()
+        .join("|");
+    let pattern =
+        format!(r"(?://+!?|#+|/\*+|^\s*\*|--|<!--)(?:.*?\W)?({tags})\b(?:\(([^)]*)\))?:?\s*(.*)");
+    Regex::new(&pattern).context("Invalid tags")
+}
+
+fn scan(regex: &Regex, file: &str, content: &str) -> Vec<Todo> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let captures = regex.captures(line)?;
+            let text = captures.get(3).map_or("", |text| text.as_str());
+            let text = text
+                .trim_end()
+                .trim_end_matches("-->")
+                .trim_end_matches("*/")
+                .trim_end();
+            Some(Todo {
+                file: file.to_string(),
+                line: index + 1,
+                tag: captures[1].to_string(),
+                owner: captures
+                    .get(2)
+                    .map(|owner| owner.as_str().trim().to_string())
+                    .filter(|owner| !owner.is_empty()),
+                text: text.to_string(),
+                author: None,
+                date: None,
+            })
+        })
+        .collect()
+}
+
+/// The author and date of each line of the file, by its number. Empty when
+/// the file isn't tracked by git.
+async fn blame(file: &Path) -> HashMap<usize, (String, Option<String>)> {
+    let (Some(dir), Some(name)) = (file.parent(), file.file_name()) else {
+        return HashMap::new();
+    };
+    let output = Command::new("git")
+        .args(["blame", "--line-porcelain", "--"])
+        .arg(name)
+        .current_dir(dir)
+        .kill_on_drop(true)
+        .output()
+        .await;
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_blame(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => HashMap::new(),
+    }
+}
+
+/// Parses the output of `git blame --line-porcelain`, in which every line of
+/// the file is preceded by the headers of its commit. The lines that aren't
+/// committed yet have no author.
+fn parse_blame(output: &str) -> HashMap<usize, (String, Option<String>)> {
+    let mut lines = HashMap::new();
+    let mut line = None;
+    let mut author = None;
+    let mut date = None;
+    for header in output.lines() {
+        if header.starts_with('\t') {
+            if let (Some(line), Some(author)) = (line.take(), author.take()) {
+                lines.insert(line, (author, date.take()));
+            }
+            continue;
+        }
+        let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+        match key {
+            "author" => author = Some(value.to_string()),
+            "author-time" => {
+                date = value
+                    .parse()
+                    .ok()
+                    .and_then(|time| DateTime::from_timestamp(time, 0))
+                    .map(|time| time.format("%Y-%m-%d").to_string())
+            }
+            key if matches!(key.len(), 40 | 64) && key.chars().all(|c| c.is_ascii_hexdigit()) => {
+                // note: the uncommitted lines are blamed on the zero commit.
+                line = value
+                    .split(' ')
+                    .nth(1)
+                    .and_then(|line| line.parse().ok())
+                    .filter(|_| key.chars().any(|c| c != '0'));
+                author = None;
+                date = None;
+            }
+            _ => {}
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+    use tokio::fs;
+
+    use super::*;
+    use crate::tools::utils::TempDir;
+
+    fn tags() -> Vec<String> {
+        DEFAULT_TAGS.map(String::from).to_vec()
+    }
+
+    #[test]
+    fn test_scan() {
+        let regex = comment_regex(&tags()).unwrap();
+        let content = r#"fn main() {
+    // TODO(alice): handle the errors
+    let todo_list = vec![]; // FIXME remove the clone
+    /* HACK: the parser can't tell them apart */
+    let message = "TODO";
+}
+# TODO
+<!-- TODO: translate the page -->"#;
+
+        let actual = scan(&regex, "main.rs", content)
+            .into_iter()
+            .map(|todo| (todo.line, todo.tag, todo.owner, todo.text))
+            .collect::<Vec<_>>();
+        let expected = vec![
+            (
+                2,
+                "TODO".to_string(),
+                Some("alice".to_string()),
+                "handle the errors".to_string(),
+            ),
+            (3, "FIXME".to_string(), None, "remove the clone".to_string()),
+            (
+                4,
+                "HACK".to_string(),
+                None,
+                "the parser can't tell them apart".to_string(),
+            ),
+            (7, "TODO".to_string(), None, String::new()),
+            (
+                8,
+                "TODO".to_string(),
+                None,
+                "translate the page".to_string(),
+            ),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_blame() {
+        let output = "\
+4b825dc642cb6eb9a060e54bf8d69288fbee4904 1 1 2
+author Alice
+author-mail <alice@example.com>
+author-time 1738281600
+author-tz +0000
+summary Add the parser
+filename main.rs
+\tfn main() {
+4b825dc642cb6eb9a060e54bf8d69288fbee4904 2 2
+author Alice
+author-mail <alice@example.com>
+author-time 1738281600
+author-tz +0000
+summary Add the parser
+filename main.rs
+\t    // TODO(alice): handle the errors
+0000000000000000000000000000000000000000 3 3 1
+author Not Committed Yet
+author-mail <not.committed.yet>
+author-time 1738368000
+author-tz +0000
+summary Version of main.rs from main.rs
+filename main.rs
+\t    // FIXME remove the clone
+";
+
+        let actual = parse_blame(output);
+        let expected = HashMap::from([
+            (1, ("Alice".to_string(), Some("2025-01-31".to_string()))),
+            (2, ("Alice".to_string(), Some("2025-01-31".to_string()))),
+        ]);
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_scan_todos() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("a.py"),
+            "# TODO: cache the results\nprint('done')\n",
+        )
+        .await
+        .unwrap();
+        fs::write(temp_dir.path().join("b.rs"), "// XXX: unsafe\n// HACK\n")
+            .await
+            .unwrap();
+
+        let actual = ScanTodos::new(TempDir::guard())
+            .call(ScanTodosInput {
+                path: Some(temp_dir.path().to_string_lossy().to_string()),
+                tags: Some(vec!["todo".to_string(), "xxx".to_string()]),
+                blame: Some(false),
+                max_results: Some(1),
+            })
+            .await
+            .unwrap();
+        let expected = format!(
+            "2 comments found\n{}\n... 1 more comments, scan a narrower path or increase max_results",
+            serde_json::json!({
+                "file": temp_dir.path().join("a.py").display().to_string(),
+                "line": 1,
+                "tag": "TODO",
+                "text": "cache the results"
+            })
+        );
+        assert_eq!(actual, expected);
+    }
+}
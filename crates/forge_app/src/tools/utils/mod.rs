@@ -1,7 +1,7 @@
-mod path_validation;
+mod process_tree;
 #[cfg(test)]
 mod temp_dir;
 
-pub use path_validation::*;
+pub use process_tree::*;
 #[cfg(test)]
 pub use temp_dir::*;
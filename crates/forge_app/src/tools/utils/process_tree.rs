@@ -0,0 +1,32 @@
+use tokio::process::Command;
+
+/// Starts the command as the leader of a new process group so that the
+/// command, along with everything it spawns, can be killed using
+/// [`kill_tree`].
+pub fn own_process_group(command: &mut Command) {
+    #[cfg(unix)]
+    command.process_group(0);
+    #[cfg(not(unix))]
+    let _ = command;
+}
+
+/// Kills the process with the given id along with all of its descendants.
+pub async fn kill_tree(pid: u32) {
+    #[cfg(unix)]
+    {
+        // The process was started as the leader of its own process group, so
+        // signalling the negative pid reaches every process in the group.
+        // SAFETY: kill has no memory safety requirements.
+        unsafe {
+            libc::kill(-(pid as i32), libc::SIGKILL);
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        let _ = Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &pid.to_string()])
+            .output()
+            .await;
+    }
+}
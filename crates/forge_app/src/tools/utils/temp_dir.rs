@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 
 use anyhow::Context;
+use forge_domain::PathGuard;
 
 pub struct TempDir {
     temp_dir: tempfile::TempDir,
@@ -26,6 +27,11 @@ impl TempDir {
         self.temp_dir.path().to_path_buf()
     }
 
+    /// Guard of the tools under test, which allows every temp directory.
+    pub fn guard() -> PathGuard {
+        PathGuard::new(Self::temp_dir().unwrap())
+    }
+
     fn temp_dir() -> anyhow::Result<PathBuf> {
         Ok(std::env::temp_dir().canonicalize()?)
     }
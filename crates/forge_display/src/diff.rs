@@ -1,10 +1,10 @@
 use std::fmt;
 use std::path::PathBuf;
 
-use console::{style, Style};
+use console::Style;
 use similar::{ChangeTag, TextDiff};
 
-use crate::TitleFormat;
+use crate::{theme, TitleFormat};
 
 struct Line(Option<usize>);
 
@@ -21,6 +21,7 @@ pub struct DiffFormat;
 
 impl DiffFormat {
     pub fn format(path: PathBuf, old: &str, new: &str) -> String {
+        let theme = theme();
         let diff = TextDiff::from_lines(old, new);
         let ops = diff.grouped_ops(3);
 
@@ -30,31 +31,36 @@ impl DiffFormat {
         );
 
         if ops.is_empty() {
-            output.push_str(&format!("{}\n", style("No changes applied").dim()));
+            output.push_str(&format!("{}\n", theme.muted.apply_to("No changes applied")));
             return output;
         }
 
         for (idx, group) in ops.iter().enumerate() {
             if idx > 0 {
-                output.push_str(&format!("{}\n", style("...").dim()));
+                output.push_str(&format!("{}\n", theme.muted.apply_to("...")));
             }
             for op in group {
                 for change in diff.iter_inline_changes(op) {
                     let (sign, s) = match change.tag() {
-                        ChangeTag::Delete => ("-", Style::new().blue()),
-                        ChangeTag::Insert => ("+", Style::new().yellow()),
-                        ChangeTag::Equal => (" ", Style::new().dim()),
+                        ChangeTag::Delete => ("-", &theme.removed),
+                        ChangeTag::Insert => ("+", &theme.added),
+                        ChangeTag::Equal => (" ", &theme.muted),
                     };
 
                     output.push_str(&format!(
                         "{}{} |{}",
-                        style(Line(change.old_index())).dim(),
-                        style(Line(change.new_index())).dim(),
+                        theme.muted.apply_to(Line(change.old_index())),
+                        theme.muted.apply_to(Line(change.new_index())),
                         s.apply_to(sign),
                     ));
 
-                    for (_, value) in change.iter_strings_lossy() {
-                        output.push_str(&format!("{}", s.apply_to(value)));
+                    // Words that changed within a line are additionally emphasized
+                    for (emphasized, value) in change.iter_strings_lossy() {
+                        if emphasized {
+                            output.push_str(&format!("{}", s.apply_to(value).underlined().bold()));
+                        } else {
+                            output.push_str(&format!("{}", s.apply_to(value)));
+                        }
                     }
                     if change.missing_newline() {
                         output.push('\n');
@@ -64,12 +70,62 @@ impl DiffFormat {
         }
         output
     }
+
+    /// Produces a plain unified diff between the two contents, suitable for
+    /// consumption by both humans and models. Returns an empty string when
+    /// the contents are identical.
+    pub fn unified(path: &str, old: &str, new: &str) -> String {
+        TextDiff::from_lines(old, new)
+            .unified_diff()
+            .context_radius(3)
+            .header(&format!("a{}", path), &format!("b{}", path))
+            .to_string()
+    }
+
+    /// Colors a unified diff produced by [`DiffFormat::unified`] for display
+    /// in the terminal.
+    pub fn preview(path: PathBuf, diff: &str) -> String {
+        Self::color(TitleFormat::execute("preview"), path, diff)
+    }
+
+    /// Colors the unified diff of a change that was applied, eg: the one a
+    /// tool reported.
+    pub fn applied(path: PathBuf, diff: &str) -> String {
+        Self::color(TitleFormat::success("diff"), path, diff)
+    }
+
+    fn color(title: TitleFormat, path: PathBuf, diff: &str) -> String {
+        let theme = theme();
+        let mut output = format!("{}\n\n", title.sub_title(path.display().to_string()));
+
+        if diff.trim().is_empty() {
+            output.push_str(&format!("{}\n", theme.muted.apply_to("No changes")));
+            return output;
+        }
+
+        for line in diff.lines() {
+            let s = if line.starts_with("+++") || line.starts_with("---") {
+                Style::new().bold()
+            } else if line.starts_with("@@") {
+                theme.hunk.clone()
+            } else if line.starts_with('+') {
+                theme.added.clone()
+            } else if line.starts_with('-') {
+                theme.removed.clone()
+            } else {
+                theme.muted.clone()
+            };
+            output.push_str(&format!("{}\n", s.apply_to(line)));
+        }
+        output
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use console::strip_ansi_codes;
     use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
 
     use super::*;
 
@@ -105,4 +161,27 @@ mod tests {
         let clean_diff = strip_ansi_codes(&diff);
         assert_snapshot!(clean_diff);
     }
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "line 1\nline 2\nline 3\n";
+        let new = "line 1\nmodified line\nline 3\n";
+        let actual = DiffFormat::unified("/abc.txt", old, new);
+        let expected = "--- a/abc.txt\n+++ b/abc.txt\n@@ -1,3 +1,3 @@\n line 1\n-line 2\n+modified line\n line 3\n";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_unified_diff_no_differences() {
+        let content = "line 1\nline 2\n";
+        let actual = DiffFormat::unified("/abc.txt", content, content);
+        assert_eq!(actual, "");
+    }
+
+    #[test]
+    fn test_preview() {
+        let diff = DiffFormat::unified("/abc.txt", "a\nb\n", "a\nc\n");
+        let actual = strip_ansi_codes(&DiffFormat::preview("/abc.txt".into(), &diff)).to_string();
+        assert!(actual.contains("-b\n+c\n"));
+    }
 }
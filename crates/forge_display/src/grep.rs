@@ -1,8 +1,9 @@
 use std::collections::BTreeMap;
 
-use console::style;
 use regex::Regex;
 
+use crate::theme;
+
 /// RipGrepFormatter formats search results in ripgrep-like style.
 #[derive(Clone)]
 pub struct GrepFormat(Vec<String>);
@@ -75,7 +76,10 @@ impl GrepFormat {
 
     /// Format a single line with colorization and consistent padding
     fn format_line(num: &str, content: &str, regex: &Regex, padding: usize) -> String {
-        let num = style(format!("{:>padding$}: ", num, padding = padding)).dim();
+        let theme = theme();
+        let num = theme
+            .muted
+            .apply_to(format!("{:>padding$}: ", num, padding = padding));
 
         // Format the content with highlighting
         let line = regex.find(content).map_or_else(
@@ -84,7 +88,7 @@ impl GrepFormat {
                 format!(
                     "{}{}{}",
                     &content[..mat.start()],
-                    style(&content[mat.start()..mat.end()]).yellow().bold(),
+                    theme.matched.apply_to(&content[mat.start()..mat.end()]),
                     &content[mat.end()..]
                 )
             },
@@ -100,7 +104,7 @@ impl GrepFormat {
         regex: &Regex,
         max_num_width: usize,
     ) -> String {
-        let file_header = style(path).cyan();
+        let file_header = theme().info.apply_to(path);
         let formatted_lines = group
             .into_iter()
             .map(|(num, content)| Self::format_line(num, content, regex, max_num_width))
@@ -0,0 +1,192 @@
+use crate::theme;
+
+const RUST: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe",
+    "use", "where", "while",
+];
+
+const PYTHON: &[&str] = &[
+    "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif",
+    "else", "except", "False", "finally", "for", "from", "global", "if", "import", "in", "is",
+    "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return", "True", "try", "while",
+    "with", "yield",
+];
+
+const JAVASCRIPT: &[&str] = &[
+    "async",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "from",
+    "function",
+    "if",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "return",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "type",
+    "typeof",
+    "undefined",
+    "var",
+    "void",
+    "while",
+    "yield",
+];
+
+const GO: &[&str] = &[
+    "break",
+    "case",
+    "chan",
+    "const",
+    "continue",
+    "default",
+    "defer",
+    "else",
+    "false",
+    "for",
+    "func",
+    "go",
+    "if",
+    "import",
+    "interface",
+    "map",
+    "nil",
+    "package",
+    "range",
+    "return",
+    "select",
+    "struct",
+    "switch",
+    "true",
+    "type",
+    "var",
+];
+
+const SHELL: &[&str] = &[
+    "case", "do", "done", "echo", "elif", "else", "esac", "exit", "export", "fi", "for",
+    "function", "if", "in", "local", "return", "then", "until", "while",
+];
+
+/// Keywords of the languages without a list of their own, mostly C-like.
+const GENERIC: &[&str] = &[
+    "break", "case", "class", "const", "continue", "default", "do", "else", "enum", "false", "for",
+    "func", "function", "if", "import", "new", "null", "private", "public", "return", "static",
+    "struct", "switch", "this", "true", "void", "while",
+];
+
+/// Keywords and line comment marker of the language of a code block.
+fn syntax(language: &str) -> (&'static [&'static str], &'static str) {
+    match language.to_lowercase().as_str() {
+        "rust" | "rs" => (RUST, "//"),
+        "python" | "py" => (PYTHON, "#"),
+        "javascript" | "js" | "jsx" | "typescript" | "ts" | "tsx" => (JAVASCRIPT, "//"),
+        "go" => (GO, "//"),
+        "sh" | "bash" | "zsh" | "shell" | "console" => (SHELL, "#"),
+        "ruby" | "rb" | "toml" | "yaml" | "yml" | "dockerfile" => (GENERIC, "#"),
+        "sql" | "lua" | "haskell" | "hs" => (GENERIC, "--"),
+        _ => (GENERIC, "//"),
+    }
+}
+
+/// Highlights a line of code: keywords, strings, numbers and comments.
+pub fn highlight(line: &str, language: &str) -> String {
+    let (keywords, comment) = syntax(language);
+    let theme = theme();
+    let chars = line.char_indices().collect::<Vec<_>>();
+    let mut output = String::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let (offset, c) = chars[index];
+        let rest = &line[offset..];
+        if rest.starts_with(comment) {
+            output.push_str(&theme.muted.apply_to(rest).to_string());
+            break;
+        }
+        if matches!(c, '"' | '\'' | '`') {
+            let mut end = index + 1;
+            while end < chars.len() && chars[end].1 != c {
+                // note: an escaped character can't close the string.
+                end += if chars[end].1 == '\\' { 2 } else { 1 };
+            }
+            let end = (end + 1).min(chars.len());
+            output.push_str(
+                &theme
+                    .string
+                    .apply_to(slice(line, &chars, index, end))
+                    .to_string(),
+            );
+            index = end;
+        } else if c.is_alphanumeric() || c == '_' {
+            let mut end = index;
+            while end < chars.len() && (chars[end].1.is_alphanumeric() || chars[end].1 == '_') {
+                end += 1;
+            }
+            let word = slice(line, &chars, index, end);
+            if c.is_ascii_digit() {
+                output.push_str(&theme.number.apply_to(word).to_string());
+            } else if keywords.contains(&word) {
+                output.push_str(&theme.keyword.apply_to(word).to_string());
+            } else {
+                output.push_str(word);
+            }
+            index = end;
+        } else {
+            output.push(c);
+            index += 1;
+        }
+    }
+    output
+}
+
+/// The text between the `start` and `end` char of the line.
+fn slice<'a>(line: &'a str, chars: &[(usize, char)], start: usize, end: usize) -> &'a str {
+    let to = chars.get(end).map_or(line.len(), |(offset, _)| *offset);
+    &line[chars[start].0..to]
+}
+
+#[cfg(test)]
+mod tests {
+    use console::strip_ansi_codes;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_highlight_keeps_the_text() {
+        let lines = [
+            ("let name = \"forge \\\" cli\"; // the name", "rust"),
+            ("def main(): # entry", "python"),
+            ("echo 'it''s' 42", "sh"),
+            ("unterminated \"string", ""),
+        ];
+
+        for (line, language) in lines {
+            let actual = strip_ansi_codes(&highlight(line, language)).to_string();
+            assert_eq!(actual, line);
+        }
+    }
+}
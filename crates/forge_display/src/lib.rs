@@ -1,7 +1,12 @@
 pub mod diff;
 pub mod grep;
+mod highlight;
+pub mod markdown;
+mod theme;
 pub mod title;
 
 pub use diff::DiffFormat;
 pub use grep::GrepFormat;
+pub use markdown::MarkdownFormat;
+pub use theme::{set_theme, theme, Theme};
 pub use title::*;
@@ -0,0 +1,289 @@
+use console::{measure_text_width, style};
+
+use crate::highlight::highlight;
+use crate::theme;
+
+/// Renders markdown as it's streamed, chunk by chunk. Complete lines are
+/// rendered for good, while the line being streamed is redrawn on every chunk
+/// so that its formatting shows up as soon as it's complete, eg: a closing
+/// `**`.
+#[derive(Debug, Default)]
+pub struct MarkdownFormat {
+    /// Columns of the terminal, to know how many rows a line was drawn on.
+    width: usize,
+    /// Language of the open code block, if any.
+    code: Option<String>,
+    /// Text of the line being streamed.
+    partial: String,
+    /// Rows of the terminal the line being streamed is drawn on.
+    drawn_rows: usize,
+}
+
+impl MarkdownFormat {
+    pub fn new(width: usize) -> Self {
+        Self { width: width.max(1), ..Default::default() }
+    }
+
+    /// Returns what to write to the terminal for the next chunk of the
+    /// message.
+    pub fn push(&mut self, chunk: &str) -> String {
+        let mut output = self.erase();
+        self.partial.push_str(chunk);
+        while let Some(end) = self.partial.find('\n') {
+            let line = self.partial[..end].trim_end_matches('\r').to_string();
+            self.partial.drain(..=end);
+            output.push_str(&self.render(&line));
+            output.push('\n');
+            self.toggle_code(&line);
+        }
+        output.push_str(&self.draw());
+        output
+    }
+
+    /// Returns what to write to the terminal once the message is complete,
+    /// and resets the state for the next one.
+    pub fn finish(&mut self) -> String {
+        let output = self.erase() + &self.render(&self.partial);
+        *self = Self::new(self.width);
+        output
+    }
+
+    /// Draws the line being streamed.
+    fn draw(&mut self) -> String {
+        if self.partial.is_empty() {
+            return String::new();
+        }
+        let rendered = self.render(&self.partial);
+        self.drawn_rows = measure_text_width(&rendered).max(1).div_ceil(self.width);
+        rendered
+    }
+
+    /// Erases the line being streamed, moving back to its start.
+    fn erase(&mut self) -> String {
+        let rows = std::mem::take(&mut self.drawn_rows);
+        match rows {
+            0 => String::new(),
+            1 => "\r\x1b[J".to_string(),
+            rows => format!("\r\x1b[{}A\x1b[J", rows - 1),
+        }
+    }
+
+    fn toggle_code(&mut self, line: &str) {
+        if let Some(language) = fence(line) {
+            self.code = match self.code {
+                Some(_) => None,
+                None => Some(language.to_string()),
+            };
+        }
+    }
+
+    fn render(&self, line: &str) -> String {
+        if fence(line).is_some() {
+            return theme().muted.apply_to(line).to_string();
+        }
+        match &self.code {
+            Some(language) => highlight(line, language),
+            None => block(line, self.width),
+        }
+    }
+}
+
+/// The language of a code fence, which is empty when it's not set.
+fn fence(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    line.strip_prefix("```")
+        .or_else(|| line.strip_prefix("~~~"))
+        .map(str::trim)
+}
+
+/// Renders a line outside of a code block: headers, lists, quotes and rules.
+fn block(line: &str, width: usize) -> String {
+    let text = line.trim_start();
+    let indent = &line[..line.len() - text.len()];
+
+    let level = text.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&level) && text[level..].starts_with(' ') {
+        let title = style(inline(text[level..].trim())).bold();
+        return if level == 1 {
+            title.underlined().to_string()
+        } else {
+            title.to_string()
+        };
+    }
+
+    let marks = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    if marks.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|mark| marks.chars().all(|c| c == *mark))
+    {
+        return theme()
+            .muted
+            .apply_to("─".repeat(width.min(80)))
+            .to_string();
+    }
+
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(item) = text.strip_prefix(bullet) {
+            return format!("{indent}{} {}", theme().info.apply_to("•"), inline(item));
+        }
+    }
+
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 && (text[digits..].starts_with(". ") || text[digits..].starts_with(") ")) {
+        return format!(
+            "{indent}{} {}",
+            theme().info.apply_to(&text[..digits + 1]),
+            inline(&text[digits + 2..])
+        );
+    }
+
+    if let Some(quote) = text.strip_prefix('>') {
+        return format!(
+            "{indent}{} {}",
+            theme().muted.apply_to("│"),
+            inline(quote.trim_start())
+        );
+    }
+
+    format!("{indent}{}", inline(text))
+}
+
+/// Renders the inline formatting of a line: code, bold, italic and links.
+/// Unclosed markers are kept as is.
+fn inline(text: &str) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if let Some((rendered, len)) = span(rest, output.chars().last()) {
+            output.push_str(&rendered);
+            rest = &rest[len..];
+        } else {
+            output.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    output
+}
+
+/// Renders the span at the start of `text` along with its length, if it's
+/// a complete one.
+fn span(text: &str, previous: Option<char>) -> Option<(String, usize)> {
+    if let Some(code) = text.strip_prefix('`') {
+        let end = code.find('`')?;
+        return Some((theme().code.apply_to(&code[..end]).to_string(), end + 2));
+    }
+    for marker in ["**", "__"] {
+        if let Some(bold) = text.strip_prefix(marker) {
+            let end = bold.find(marker).filter(|end| *end > 0)?;
+            return Some((style(&bold[..end]).bold().to_string(), end + 4));
+        }
+    }
+    // note: underscores within words are kept, eg: `snake_case`.
+    let starts_word = !previous.is_some_and(char::is_alphanumeric);
+    for marker in ['*', '_'] {
+        if let Some(italic) = text.strip_prefix(marker) {
+            if italic.starts_with(char::is_whitespace) || (marker == '_' && !starts_word) {
+                return None;
+            }
+            let end = italic.find(marker).filter(|end| *end > 0)?;
+            return Some((style(&italic[..end]).italic().to_string(), end + 2));
+        }
+    }
+    if let Some(link) = text.strip_prefix('[') {
+        let (label, rest) = link.split_once("](")?;
+        let (url, _) = rest.split_once(')')?;
+        let rendered = if label == url {
+            style(url).underlined().to_string()
+        } else {
+            format!(
+                "{} {}",
+                style(label).underlined(),
+                theme().muted.apply_to(format!("({url})"))
+            )
+        };
+        return Some((rendered, label.len() + url.len() + 4));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use console::strip_ansi_codes;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn render(chunks: &[&str]) -> String {
+        let mut markdown = MarkdownFormat::new(80);
+        let mut output = chunks
+            .iter()
+            .map(|chunk| markdown.push(chunk))
+            .collect::<String>();
+        output.push_str(&markdown.finish());
+        output
+    }
+
+    #[test]
+    fn test_blocks() {
+        let actual = strip_ansi_codes(&render(&[
+            "# Title\n",
+            "## Steps\n",
+            "1. Read the **config**\n",
+            "  - see [docs](https://forgecode.dev)\n",
+            "> note\n",
+            "---\n",
+            "a snake_case `name`\n",
+        ]))
+        .to_string();
+        let expected = format!(
+            "Title\nSteps\n1. Read the config\n  • see docs (https://forgecode.dev)\n│ note\n{}\na snake_case name\n",
+            "─".repeat(80)
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_code_block() {
+        let actual = strip_ansi_codes(&render(&[
+            "```rust\n",
+            "# not a title\n",
+            "```\n",
+            "# title\n",
+        ]))
+        .to_string();
+        assert_eq!(actual, "```rust\n# not a title\n```\ntitle\n");
+    }
+
+    #[test]
+    fn test_partial_line_is_redrawn() {
+        let mut markdown = MarkdownFormat::new(80);
+
+        let actual = [
+            markdown.push("**bo"),
+            markdown.push("ld** text\nnext"),
+            markdown.finish(),
+        ]
+        .map(|output| strip_ansi_codes(&output).to_string());
+        let expected = ["**bo", "\rbold text\nnext", "\rnext"];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_erase_wrapped_line() {
+        let mut markdown = MarkdownFormat::new(10);
+        markdown.push(&"a".repeat(25));
+
+        let actual = markdown.erase();
+        assert_eq!(actual, "\r\x1b[2A\x1b[J");
+    }
+
+    #[test]
+    fn test_unclosed_markers_are_kept() {
+        let actual = strip_ansi_codes(&inline("2 * 3 = 6, a **b and [c](d")).to_string();
+        assert_eq!(actual, "2 * 3 = 6, a **b and [c](d");
+    }
+}
@@ -0,0 +1,165 @@
+use std::sync::RwLock;
+
+use console::{Color, Style};
+
+static THEME: RwLock<Option<Theme>> = RwLock::new(None);
+
+/// Styles of the output by role rather than by color, so that all of the
+/// formats can be made readable on a light terminal or without colors at
+/// once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// Whether the theme has colors at all, which are turned off for
+    /// everything else that's printed otherwise.
+    pub colored: bool,
+    /// Tools being executed, paths of the search results and list markers
+    pub info: Style,
+    pub success: Style,
+    pub error: Style,
+    /// Secondary text: timestamps, line numbers, code fences and comments
+    pub muted: Style,
+    pub added: Style,
+    pub removed: Style,
+    /// Headers of the hunks of a diff
+    pub hunk: Style,
+    /// Matches of the search results
+    pub matched: Style,
+    /// Inline code of markdown
+    pub code: Style,
+    pub keyword: Style,
+    pub string: Style,
+    pub number: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            colored: true,
+            info: Style::new().cyan(),
+            success: Style::new().green(),
+            error: Style::new().red(),
+            muted: Style::new().dim(),
+            added: Style::new().yellow(),
+            removed: Style::new().blue(),
+            hunk: Style::new().cyan(),
+            matched: Style::new().yellow().bold(),
+            code: Style::new().yellow(),
+            keyword: Style::new().magenta(),
+            string: Style::new().green(),
+            number: Style::new().yellow(),
+        }
+    }
+
+    /// Avoids yellow and dimmed text, which are hard to read on a light
+    /// background.
+    pub fn light() -> Self {
+        let gray = Style::new().fg(Color::Color256(244));
+        Self {
+            colored: true,
+            info: Style::new().blue(),
+            success: Style::new().green(),
+            error: Style::new().red(),
+            muted: gray,
+            added: Style::new().green(),
+            removed: Style::new().red(),
+            hunk: Style::new().magenta(),
+            matched: Style::new().magenta().bold(),
+            code: Style::new().magenta(),
+            keyword: Style::new().blue(),
+            string: Style::new().green(),
+            number: Style::new().magenta(),
+        }
+    }
+
+    /// Bright and bold colors, with no dimmed text.
+    pub fn high_contrast() -> Self {
+        Self {
+            colored: true,
+            info: Style::new().cyan().bright().bold(),
+            success: Style::new().green().bright().bold(),
+            error: Style::new().red().bright().bold(),
+            muted: Style::new().white(),
+            added: Style::new().green().bright().bold(),
+            removed: Style::new().red().bright().bold(),
+            hunk: Style::new().magenta().bright().bold(),
+            matched: Style::new().black().on_yellow().bright(),
+            code: Style::new().yellow().bright(),
+            keyword: Style::new().magenta().bright().bold(),
+            string: Style::new().green().bright(),
+            number: Style::new().yellow().bright(),
+        }
+    }
+
+    pub fn no_color() -> Self {
+        Self {
+            colored: false,
+            info: Style::new(),
+            success: Style::new(),
+            error: Style::new(),
+            muted: Style::new(),
+            added: Style::new(),
+            removed: Style::new(),
+            hunk: Style::new(),
+            matched: Style::new(),
+            code: Style::new(),
+            keyword: Style::new(),
+            string: Style::new(),
+            number: Style::new(),
+        }
+    }
+}
+
+/// Whether colors are turned off by the user, see <https://no-color.org>.
+fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+/// Sets the theme of the output, unless colors are turned off by `NO_COLOR`.
+pub fn set_theme(theme: Theme) {
+    let theme = if no_color() { Theme::no_color() } else { theme };
+    if !theme.colored {
+        console::set_colors_enabled(false);
+        colored::control::set_override(false);
+    }
+    *THEME.write().unwrap_or_else(|error| error.into_inner()) = Some(theme);
+}
+
+/// The theme of the output, the dark one unless another one is set.
+pub fn theme() -> Theme {
+    let theme = THEME.read().unwrap_or_else(|error| error.into_inner());
+    match theme.as_ref() {
+        Some(theme) => theme.clone(),
+        None if no_color() => Theme::no_color(),
+        None => Theme::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_no_color_is_plain() {
+        let theme = Theme::no_color();
+
+        let actual = [&theme.error, &theme.muted, &theme.matched]
+            .map(|style| style.apply_to("text").force_styling(true).to_string());
+        assert_eq!(actual, ["text", "text", "text"]);
+    }
+
+    #[test]
+    fn test_themes_are_colored() {
+        for theme in [Theme::dark(), Theme::light(), Theme::high_contrast()] {
+            let actual = theme.error.apply_to("text").force_styling(true).to_string();
+            assert!(actual.starts_with('\x1b'), "{actual:?}");
+        }
+    }
+}
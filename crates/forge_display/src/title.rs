@@ -1,8 +1,9 @@
 use std::fmt::{self, Display, Formatter};
 
-use colored::Colorize;
 use derive_setters::Setters;
 
+use crate::theme;
+
 #[derive(Clone)]
 pub enum Kind {
     Execute,
@@ -82,28 +83,17 @@ impl TitleFormat {
     }
 
     pub fn format(&self) -> String {
-        let (icon, label, message) = match self.kind {
-            Kind::Execute => (
-                self.icon().cyan(),
-                self.label().bold().cyan(),
-                format!("{} ", self.title),
-            ),
-            Kind::Success => (
-                self.icon().green(),
-                self.label().bold().green(),
-                self.title.to_string(),
-            ),
+        let theme = theme();
+        let (style, message) = match self.kind {
+            Kind::Execute => (&theme.info, format!("{} ", self.title)),
+            Kind::Success => (&theme.success, self.title.to_string()),
             Kind::Failed => {
                 let error_suffix = self
                     .error
                     .as_ref()
-                    .map(|e| format!(" ({})", e))
+                    .map(|e| theme.error.apply_to(format!(" ({})", e)).to_string())
                     .unwrap_or_default();
-                (
-                    self.icon().red(),
-                    self.label().bold().red(),
-                    format!("{}{}", self.title, error_suffix.red()),
-                )
+                (&theme.error, format!("{}{}", self.title, error_suffix))
             }
         };
 
@@ -113,10 +103,16 @@ impl TitleFormat {
         } else {
             &chrono::Local::now().format("%H:%M:%S%.3f").to_string()
         };
-        let mut result = format!("{} {} {} {}", timestamp.dimmed(), icon, label, message);
+        let mut result = format!(
+            "{} {} {} {}",
+            theme.muted.apply_to(timestamp),
+            style.apply_to(self.icon()),
+            style.clone().bold().apply_to(self.label()),
+            message
+        );
 
         if let Some(ref sub_title) = self.sub_title {
-            result.push_str(&format!(" {}", sub_title).dimmed().to_string());
+            result.push_str(&theme.muted.apply_to(format!(" {}", sub_title)).to_string());
         }
 
         result
@@ -3,7 +3,7 @@ use derive_setters::Setters;
 use serde::{Deserialize, Serialize};
 
 use crate::template::Template;
-use crate::{Environment, EventContext, ModelId, ToolName};
+use crate::{BestOf, Environment, EventContext, ModelId, ModelParameters, ToolName};
 
 #[derive(Debug, Default, Setters, Clone, Serialize, Deserialize)]
 #[setters(strip_option)]
@@ -16,6 +16,13 @@ pub struct SystemContext {
     pub tool_supported: Option<bool>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub files: Vec<String>,
+    /// Ranked summary of the repository's key files and symbols
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo_map: Option<String>,
+    /// Instruction files of the repository, such as `AGENTS.md`, from its
+    /// root down to the directories the task touched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
 }
 
 #[derive(Debug, Display, Eq, PartialEq, Hash, Clone, Serialize, Deserialize)]
@@ -42,12 +49,60 @@ impl Agent {
     pub fn default_walker_depth() -> usize {
         5
     }
+
+    /// Default number of tokens the repository map may occupy.
+    pub fn default_repo_map_tokens() -> usize {
+        2048
+    }
+
+    /// Default number of tokens the files attached by `auto_context` may
+    /// occupy.
+    pub fn default_auto_context_tokens() -> usize {
+        8192
+    }
+
+    /// Default number of tokens the instruction files may occupy.
+    pub fn default_instructions_tokens() -> usize {
+        4096
+    }
+}
+
+/// Whether the agent plans the task before making any change.
+#[derive(Debug, Display, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentMode {
+    /// Every tool of the agent is available
+    #[default]
+    #[display("act")]
+    Act,
+    /// Only the read-only tools are available, until the plan the agent
+    /// submits is approved
+    #[display("plan")]
+    Plan,
+}
+
+impl AgentMode {
+    pub fn is_act(&self) -> bool {
+        *self == Self::Act
+    }
+
+    /// Whether an agent in this mode may call the tool.
+    pub fn allows(&self, tool: &ToolName) -> bool {
+        match self {
+            Self::Act => true,
+            Self::Plan => tool.is_read_only(),
+        }
+    }
 }
 
 fn is_true(value: &bool) -> bool {
     *value
 }
 
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
 fn truth() -> bool {
     true
 }
@@ -56,6 +111,9 @@ fn truth() -> bool {
 pub struct Agent {
     pub id: AgentId,
     pub model: ModelId,
+    /// Generation parameters such as temperature, top_p and max_tokens
+    #[serde(default, skip_serializing_if = "ModelParameters::is_empty")]
+    pub parameters: ModelParameters,
     pub description: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub system_prompt: Option<Template<SystemContext>>,
@@ -78,7 +136,7 @@ pub struct Agent {
     pub enable: bool,
 
     /// Tools that the agent can use    
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tools: Vec<ToolName>,
 
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
@@ -95,6 +153,52 @@ pub struct Agent {
     /// Depth to which the file walker should traverse for this agent
     #[serde(default = "Agent::default_walker_depth")]
     pub walker_depth: usize,
+
+    /// When set to true the system prompt contains a ranked summary of the
+    /// repository's key files and symbols.
+    #[serde(skip_serializing_if = "is_false", default)]
+    pub repo_map: bool,
+
+    /// Maximum number of tokens the repository map may occupy
+    #[serde(default = "Agent::default_repo_map_tokens")]
+    pub repo_map_tokens: usize,
+
+    /// When set to true the files of the repository most relevant to each
+    /// message of the user are attached to it.
+    #[serde(skip_serializing_if = "is_false", default)]
+    pub auto_context: bool,
+
+    /// Maximum number of tokens the files attached by `auto_context` may
+    /// occupy
+    #[serde(default = "Agent::default_auto_context_tokens")]
+    pub auto_context_tokens: usize,
+
+    /// Maximum number of tokens the instruction files, such as `AGENTS.md`,
+    /// may occupy in the system prompt
+    #[serde(default = "Agent::default_instructions_tokens")]
+    pub instructions_tokens: usize,
+
+    /// JSON schema the final answer of the agent must conform to. The model is
+    /// asked for structured output and invalid answers are retried with the
+    /// validation errors.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub output_schema: Option<serde_json::Value>,
+
+    /// When set to `plan` the agent is restricted to read-only tools and
+    /// submits a plan for the user to approve, before it's switched to `act`
+    #[serde(skip_serializing_if = "AgentMode::is_act", default)]
+    pub mode: AgentMode,
+
+    /// Tools that verify the project, such as `tool_forge_project_check` or
+    /// `tool_forge_test_run`, which are run after every turn in which the
+    /// agent changed files. Their failures are fed back to the agent.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub verify_after_write: Vec<ToolName>,
+
+    /// Samples several completions of every request in parallel and keeps
+    /// the best one, as picked by consensus or by a judge agent.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub best_of: Option<BestOf>,
 }
 
 /// Transformations that can be applied to the agent's context before sending it
@@ -0,0 +1,242 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{AgentId, Event, ToolCallFull, ToolDefinition};
+
+/// Sampling several completions of every request of an agent in parallel and
+/// keeping the best one, for hard edits where a single completion is often
+/// wrong. Every sample is paid for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BestOf {
+    /// Number of completions requested for each request
+    pub samples: usize,
+    /// Temperatures of the samples, in order. They're spread from 0.2 to 1.0
+    /// unless set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub temperatures: Vec<f32>,
+    /// How the best sample is picked
+    #[serde(default)]
+    pub judge: Judge,
+}
+
+/// Picks the best of the samples.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Judge {
+    /// The sample whose tool calls are valid and that most other samples
+    /// agree with, ie: make the same tool calls or give the same answer.
+    #[default]
+    Consensus,
+    /// An agent of the workflow, which is shown the task and the samples and
+    /// replies with the number of the best one.
+    Agent(AgentId),
+}
+
+/// A completion of the request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub content: String,
+    pub tool_calls: Vec<ToolCallFull>,
+}
+
+impl BestOf {
+    /// Name of the event the judge agent is started with.
+    pub const EVENT: &'static str = "best_of";
+
+    /// Temperature of each sample.
+    pub fn temperatures(&self) -> Vec<f32> {
+        if !self.temperatures.is_empty() {
+            return self
+                .temperatures
+                .iter()
+                .copied()
+                .cycle()
+                .take(self.samples)
+                .collect();
+        }
+        let step = 0.8 / self.samples.saturating_sub(1).max(1) as f32;
+        (0..self.samples)
+            .map(|index| ((0.2 + step * index as f32) * 100.0).round() / 100.0)
+            .collect()
+    }
+
+    /// Index of the best sample by consensus. The samples that call unknown
+    /// tools, leave out required arguments or are empty come last, then the
+    /// ones most other samples agree with, then the first ones.
+    pub fn consensus(samples: &[Sample], tools: &[ToolDefinition]) -> usize {
+        (0..samples.len())
+            .max_by_key(|&index| {
+                let sample = &samples[index];
+                let agreement = samples
+                    .iter()
+                    .enumerate()
+                    .filter(|(other, _)| *other != index)
+                    .filter(|(_, other)| sample.agrees_with(other))
+                    .count();
+                (sample.is_valid(tools), agreement, std::cmp::Reverse(index))
+            })
+            .unwrap_or_default()
+    }
+
+    /// The event the judge agent is started with, which shows it the task and
+    /// the samples.
+    pub fn judge_event(task: &str, samples: &[Sample]) -> Event {
+        let count = samples.len();
+        let samples = samples
+            .iter()
+            .enumerate()
+            .map(|(index, sample)| {
+                let tool_calls = sample
+                    .tool_calls
+                    .iter()
+                    .map(|tool_call| {
+                        format!(
+                            "<tool_call name=\"{}\">{}</tool_call>",
+                            tool_call.name.as_str(),
+                            tool_call.arguments
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!(
+                    "<sample number=\"{}\">\n{}\n{tool_calls}\n</sample>",
+                    index + 1,
+                    sample.content.trim()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Event::new(
+            Self::EVENT,
+            format!(
+                "An agent was given the task below and came up with {} responses to it. Judge \
+                 which one is the most correct and complete, eg: whose edits compile and keep \
+                 the tests passing, and reply with its number only.\n\n<task>\n{}\n</task>\n\n{samples}",
+                count,
+                task.trim()
+            ),
+        )
+    }
+
+    /// Index of the sample the judge picked in its reply, if it's one of
+    /// them.
+    pub fn parse_pick(reply: &str, count: usize) -> Option<usize> {
+        reply
+            .split(|c: char| !c.is_ascii_digit())
+            .find(|word| !word.is_empty())
+            .and_then(|number| number.parse::<usize>().ok())
+            .filter(|number| (1..=count).contains(number))
+            .map(|number| number - 1)
+    }
+}
+
+impl Sample {
+    fn is_valid(&self, tools: &[ToolDefinition]) -> bool {
+        if self.content.trim().is_empty() && self.tool_calls.is_empty() {
+            return false;
+        }
+        self.tool_calls.iter().all(|tool_call| {
+            tools
+                .iter()
+                .find(|tool| tool.name == tool_call.name)
+                .is_some_and(|tool| {
+                    let required = tool
+                        .input_schema
+                        .schema
+                        .object
+                        .as_ref()
+                        .map(|object| &object.required);
+                    required.into_iter().flatten().all(|name| {
+                        tool_call
+                            .arguments
+                            .as_object()
+                            .is_some_and(|arguments| arguments.contains_key(name))
+                    })
+                })
+        })
+    }
+
+    /// Whether both make the same tool calls, or give the same answer when
+    /// they make none.
+    fn agrees_with(&self, other: &Sample) -> bool {
+        if self.tool_calls.is_empty() && other.tool_calls.is_empty() {
+            return self.content.trim() == other.content.trim();
+        }
+        self.tool_calls.len() == other.tool_calls.len()
+            && self
+                .tool_calls
+                .iter()
+                .zip(other.tool_calls.iter())
+                .all(|(a, b)| a.name == b.name && a.arguments == b.arguments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use schemars::JsonSchema;
+
+    use super::*;
+    use crate::ToolName;
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Read {
+        path: String,
+    }
+
+    fn sample(content: &str, paths: &[&str]) -> Sample {
+        Sample {
+            content: content.to_string(),
+            tool_calls: paths
+                .iter()
+                .map(|path| {
+                    ToolCallFull::new(ToolName::new("tool_forge_fs_read"))
+                        .arguments(serde_json::json!({ "path": path }))
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_temperatures() {
+        let best_of = BestOf {
+            samples: 3,
+            temperatures: Vec::new(),
+            judge: Judge::Consensus,
+        };
+        assert_eq!(best_of.temperatures(), vec![0.2, 0.6, 1.0]);
+
+        let best_of = BestOf { samples: 3, temperatures: vec![0.0, 0.7], ..best_of };
+        assert_eq!(best_of.temperatures(), vec![0.0, 0.7, 0.0]);
+    }
+
+    #[test]
+    fn test_consensus() {
+        let tools = vec![
+            ToolDefinition::new("tool_forge_fs_read").input_schema(schemars::schema_for!(Read))
+        ];
+        let mut invalid = sample("", &["a.rs"]);
+        invalid.tool_calls[0].arguments = serde_json::json!({});
+        let samples = vec![
+            sample("", &["b.rs"]),
+            invalid,
+            sample("Reading", &["a.rs"]),
+            sample("", &["a.rs"]),
+        ];
+        assert_eq!(BestOf::consensus(&samples, &tools), 2);
+
+        let samples = vec![sample("", &[]), sample("Done", &[])];
+        assert_eq!(BestOf::consensus(&samples, &tools), 1);
+    }
+
+    #[test]
+    fn test_parse_pick() {
+        let actual = [
+            BestOf::parse_pick("2", 3),
+            BestOf::parse_pick("Sample 3 is the best.", 3),
+            BestOf::parse_pick("4", 3),
+            BestOf::parse_pick("None of them", 3),
+        ];
+        assert_eq!(actual, [Some(1), Some(2), None, None]);
+    }
+}
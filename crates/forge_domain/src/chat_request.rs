@@ -1,17 +1,29 @@
 use derive_setters::Setters;
 use serde::{Deserialize, Serialize};
 
-use crate::ConversationId;
+use crate::{AgentId, ConversationId, Image};
 
 #[derive(Debug, Serialize, Deserialize, Clone, Setters)]
 #[setters(into, strip_option)]
 pub struct ChatRequest {
     pub content: String,
     pub conversation_id: ConversationId,
+    /// Agent the message is sent to, instead of the agents subscribed to the
+    /// events of the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<AgentId>,
+    /// Images attached to the message, eg: pasted from the clipboard
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<Image>,
 }
 
 impl ChatRequest {
     pub fn new(content: impl ToString, conversation_id: ConversationId) -> Self {
-        Self { content: content.to_string(), conversation_id }
+        Self {
+            content: content.to_string(),
+            conversation_id,
+            agent: None,
+            images: Vec::new(),
+        }
     }
 }
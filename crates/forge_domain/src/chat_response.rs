@@ -1,6 +1,8 @@
+use std::time::Duration;
+
 use serde::Serialize;
 
-use crate::{Event, ToolCallFull, ToolResult, Usage};
+use crate::{AgentId, Event, ModelId, Plan, Question, Redacted, ToolCallFull, ToolResult, Usage};
 
 /// Events that are emitted by the agent for external consumption. This includes
 /// events for all internal state changes.
@@ -10,6 +12,137 @@ pub enum ChatResponse {
     Text(String),
     ToolCallStart(ToolCallFull),
     ToolCallEnd(ToolResult),
+    DiffPreview(DiffPreview),
     Usage(Usage),
+    /// Usage of the request in progress, estimated from the context and the
+    /// streamed chunks at about 4 characters per token until the provider
+    /// reports the actual `Usage`
+    UsageEstimate(Usage),
     Custom(Event),
+    /// The agent was stopped as it kept repeating a failing tool call, the
+    /// reason is to be shown to the user before they reply
+    NeedsUserInput(String),
+    /// The agent is waiting for the user to answer the question
+    Question(Question),
+    /// The agent submitted its plan, which waits for the user's approval
+    Plan(Plan),
+    /// The request of the agent is queued for the given time, so as to stay
+    /// within the rate limits of the provider
+    RateLimited(Duration),
+    /// Files of the repository attached to the user's message as they're
+    /// relevant to it
+    RelevantFiles(Vec<RelevantFile>),
+    /// Secrets that were masked in the context before it was sent to the
+    /// provider
+    Redacted(Vec<Redacted>),
+    /// The agent started working on an event, on behalf of the `parent` agent
+    /// if any, eg: the one that spawned it or whose transform it is. Its
+    /// requests are made with `model`, which agents spawned at runtime don't
+    /// declare in the workflow.
+    AgentStarted {
+        parent: Option<AgentId>,
+        model: ModelId,
+    },
+    /// The agent sends the request of the given number to the provider, from
+    /// 1, each request after the first one following its tool calls
+    AgentTurn(u64),
+    /// The agent finished working on the event, the tokens being those of all
+    /// its requests
+    AgentCompleted {
+        tokens: u64,
+        duration: Duration,
+    },
+}
+
+/// Unified diff of a file change that was computed by a tool running in
+/// preview mode, without persisting anything to disk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffPreview {
+    pub path: String,
+    pub diff: String,
+}
+
+impl DiffPreview {
+    /// Extracts a preview from a successful tool call that was made with
+    /// `preview` set to true.
+    pub fn parse(tool_call: &ToolCallFull, result: &ToolResult) -> Option<Self> {
+        if result.is_error {
+            return None;
+        }
+
+        let arguments = &tool_call.arguments;
+        if !arguments.get("preview")?.as_bool()? {
+            return None;
+        }
+
+        let path = arguments.get("path")?.as_str()?;
+        Some(Self { path: path.to_string(), diff: result.content.clone() })
+    }
+}
+
+/// A file of the repository that was found relevant to the user's message,
+/// along with its path relative to the working directory.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RelevantFile {
+    pub path: String,
+    /// Estimated tokens of its content
+    pub tokens: usize,
+    #[serde(skip)]
+    pub content: String,
+}
+
+impl RelevantFile {
+    /// The files as they're attached to the user's message.
+    pub fn render(files: &[RelevantFile]) -> String {
+        let files = files
+            .iter()
+            .map(|file| format!("<file path=\"{}\">\n{}\n</file>", file.path, file.content))
+            .collect::<Vec<_>>();
+        format!(
+            "<relevant_files>\nFiles of the repository that may be relevant to the task, attached automatically:\n{}\n</relevant_files>",
+            files.join("\n")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+    use crate::ToolName;
+
+    fn tool_call(arguments: serde_json::Value) -> ToolCallFull {
+        ToolCallFull::new(ToolName::new("tool_forge_fs_patch")).arguments(arguments)
+    }
+
+    #[test]
+    fn test_parse_preview() {
+        let fixture = tool_call(json!({"path": "/a.txt", "preview": true}));
+        let result = ToolResult::from(fixture.clone()).success("@@ -1 +1 @@");
+
+        let actual = DiffPreview::parse(&fixture, &result);
+        let expected =
+            Some(DiffPreview { path: "/a.txt".to_string(), diff: "@@ -1 +1 @@".to_string() });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_without_preview() {
+        let fixture = tool_call(json!({"path": "/a.txt"}));
+        let result = ToolResult::from(fixture.clone()).success("done");
+
+        let actual = DiffPreview::parse(&fixture, &result);
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn test_parse_failed_preview() {
+        let fixture = tool_call(json!({"path": "/a.txt", "preview": true}));
+        let result = ToolResult::from(fixture.clone()).failure(anyhow::anyhow!("no match"));
+
+        let actual = DiffPreview::parse(&fixture, &result);
+        assert_eq!(actual, None);
+    }
 }
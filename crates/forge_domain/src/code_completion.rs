@@ -0,0 +1,209 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Context, ContextMessage, ModelId, ModelParameters};
+
+/// Code to complete at the cursor of an editor, from the code before and
+/// after it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeCompletion {
+    pub prefix: String,
+    pub suffix: String,
+    /// Language of the code, eg: `rust`
+    pub language: String,
+}
+
+/// A prompt completed as is, without the chat template of the model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextCompletion {
+    pub prompt: String,
+    /// Sequences the completion stops at
+    pub stop: Vec<String>,
+    pub max_tokens: u32,
+    pub temperature: f32,
+}
+
+/// Fill-in-the-middle formats of the families of code models, which complete
+/// the code between a prefix and a suffix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FimFormat {
+    Qwen,
+    StarCoder,
+    DeepSeek,
+    Codestral,
+    CodeLlama,
+}
+
+impl FimFormat {
+    /// The format of the model, guessed from its id, if it's a code model
+    /// trained to fill in the middle.
+    pub fn of(model: &ModelId) -> Option<Self> {
+        let id = model.as_str().to_lowercase();
+        if id.contains("codestral") {
+            Some(Self::Codestral)
+        } else if id.contains("deepseek-coder") {
+            Some(Self::DeepSeek)
+        } else if id.contains("qwen") && id.contains("coder") {
+            Some(Self::Qwen)
+        } else if id.contains("starcoder") {
+            Some(Self::StarCoder)
+        } else if id.contains("codellama") || id.contains("code-llama") {
+            Some(Self::CodeLlama)
+        } else {
+            None
+        }
+    }
+
+    fn prompt(&self, prefix: &str, suffix: &str) -> String {
+        match self {
+            Self::Qwen => format!("<|fim_prefix|>{prefix}<|fim_suffix|>{suffix}<|fim_middle|>"),
+            Self::StarCoder => format!("<fim_prefix>{prefix}<fim_suffix>{suffix}<fim_middle>"),
+            Self::DeepSeek => format!("<｜fim▁begin｜>{prefix}<｜fim▁hole｜>{suffix}<｜fim▁end｜>"),
+            Self::Codestral => format!("[SUFFIX]{suffix}[PREFIX]{prefix}"),
+            Self::CodeLlama => format!("<PRE> {prefix} <SUF>{suffix} <MID>"),
+        }
+    }
+
+    fn stop(&self) -> Vec<String> {
+        let stop: &[&str] = match self {
+            Self::Qwen => &["<|endoftext|>", "<|fim_pad|>", "<|file_sep|>"],
+            Self::StarCoder => &["<|endoftext|>", "<file_sep>"],
+            Self::DeepSeek => &["<｜end▁of▁sentence｜>", "<｜fim▁begin｜>"],
+            Self::Codestral => &["[PREFIX]", "[SUFFIX]"],
+            Self::CodeLlama => &["<EOT>"],
+        };
+        stop.iter().map(|stop| stop.to_string()).collect()
+    }
+}
+
+impl CodeCompletion {
+    /// Bytes of the code before the cursor that are sent, the closest ones.
+    pub const MAX_PREFIX: usize = 8000;
+    /// Bytes of the code after the cursor that are sent.
+    pub const MAX_SUFFIX: usize = 2000;
+    pub const MAX_TOKENS: u32 = 256;
+
+    /// The fill-in-the-middle prompt of the code models of the format.
+    pub fn fim(&self, format: FimFormat) -> TextCompletion {
+        let (prefix, suffix) = self.clip();
+        TextCompletion {
+            prompt: format.prompt(prefix, suffix),
+            stop: format.stop(),
+            max_tokens: Self::MAX_TOKENS,
+            temperature: 0.2,
+        }
+    }
+
+    /// The request for the models that only chat.
+    pub fn context(&self) -> Context {
+        let (prefix, suffix) = self.clip();
+        Context::default()
+            .add_message(ContextMessage::system(
+                "You complete code at the cursor of an editor. Reply with the code to insert at <cursor/> only, without code fences or explanations. It's often a few lines or the rest of the current one, and it must fit with the code after the cursor.",
+            ))
+            .add_message(ContextMessage::user(format!(
+                "<code language=\"{}\">{prefix}<cursor/>{suffix}</code>",
+                self.language
+            )))
+            .parameters(
+                ModelParameters::default()
+                    .temperature(0.2)
+                    .max_tokens(Self::MAX_TOKENS),
+            )
+    }
+
+    /// The code to insert from the reply of a model that only chats, which
+    /// sometimes wraps it in a code fence.
+    pub fn parse_chat(reply: &str) -> String {
+        let trimmed = reply.trim_end();
+        match trimmed.trim_start().strip_prefix("```") {
+            Some(rest) => rest
+                .split_once('\n')
+                .map_or("", |(_, code)| code)
+                .trim_end()
+                .trim_end_matches("```")
+                .trim_end()
+                .to_string(),
+            None => trimmed.to_string(),
+        }
+    }
+
+    /// The end of the prefix and the start of the suffix, within their
+    /// limits.
+    fn clip(&self) -> (&str, &str) {
+        let mut start = self.prefix.len().saturating_sub(Self::MAX_PREFIX);
+        while !self.prefix.is_char_boundary(start) {
+            start += 1;
+        }
+        let mut end = self.suffix.len().min(Self::MAX_SUFFIX);
+        while !self.suffix.is_char_boundary(end) {
+            end -= 1;
+        }
+        (&self.prefix[start..], &self.suffix[..end])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn completion() -> CodeCompletion {
+        CodeCompletion {
+            prefix: "fn add(a: i32, b: i32) -> i32 {\n    ".to_string(),
+            suffix: "\n}\n".to_string(),
+            language: "rust".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_fim_format() {
+        let actual = [
+            "qwen/qwen-2.5-coder-32b-instruct",
+            "mistralai/codestral-2501",
+            "deepseek/deepseek-coder-v2",
+            "anthropic/claude-3.7-sonnet",
+        ]
+        .map(|id| FimFormat::of(&ModelId::new(id)));
+        let expected = [
+            Some(FimFormat::Qwen),
+            Some(FimFormat::Codestral),
+            Some(FimFormat::DeepSeek),
+            None,
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_fim() {
+        let actual = completion().fim(FimFormat::Qwen).prompt;
+        let expected =
+            "<|fim_prefix|>fn add(a: i32, b: i32) -> i32 {\n    <|fim_suffix|>\n}\n<|fim_middle|>";
+        assert_eq!(actual, expected);
+
+        let actual = completion().fim(FimFormat::Codestral).prompt;
+        let expected = "[SUFFIX]\n}\n[PREFIX]fn add(a: i32, b: i32) -> i32 {\n    ";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_clip() {
+        let completion = CodeCompletion {
+            prefix: format!("é{}", "a".repeat(CodeCompletion::MAX_PREFIX - 1)),
+            suffix: "b".repeat(CodeCompletion::MAX_SUFFIX + 10),
+            language: "text".to_string(),
+        };
+        let (prefix, suffix) = completion.clip();
+        assert_eq!(prefix.len(), CodeCompletion::MAX_PREFIX - 1);
+        assert_eq!(suffix.len(), CodeCompletion::MAX_SUFFIX);
+    }
+
+    #[test]
+    fn test_parse_chat() {
+        let actual = [
+            CodeCompletion::parse_chat("a + b"),
+            CodeCompletion::parse_chat("```rust\na + b\n```\n"),
+        ];
+        assert_eq!(actual, ["a + b", "a + b"]);
+    }
+}
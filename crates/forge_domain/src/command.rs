@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+/// A slash command defined in the workflow that sends a templated prompt to
+/// the agents, optionally including the output of a shell command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomCommand {
+    /// Name of the command without the leading '/'
+    pub name: String,
+
+    /// Short summary displayed by the completer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Shell command executed before sending the prompt. Its combined stdout
+    /// and stderr are available to the prompt as `{{output}}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run: Option<String>,
+
+    /// Skips sending the prompt when the shell command succeeds
+    #[serde(default)]
+    pub only_on_failure: bool,
+
+    /// Message sent to the agents. `{{args}}` is replaced with the text
+    /// following the command and `{{output}}` with the output of `run`.
+    pub prompt: String,
+}
+
+impl CustomCommand {
+    /// Renders the prompt for the given arguments and shell output.
+    pub fn render(&self, args: &str, output: &str) -> String {
+        self.prompt
+            .replace("{{args}}", args)
+            .replace("{{output}}", output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let fixture: CustomCommand = serde_json::from_value(serde_json::json!({
+            "name": "review",
+            "prompt": "Review {{args}}:\n{{output}}",
+        }))
+        .unwrap();
+
+        let actual = fixture.render("the parser", "+ fn parse()");
+        let expected = "Review the parser:\n+ fn parse()";
+        assert_eq!(actual, expected);
+    }
+}
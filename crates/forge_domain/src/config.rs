@@ -0,0 +1,471 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use derive_setters::Setters;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+use crate::{
+    ModelId, ModelParameters, PiiKind, RateLimit, RedactionAction, SecretKind, ToolName, Workflow,
+};
+
+/// Settings that can be set at every layer of the configuration. From the
+/// highest precedence: the CLI flags, the environment, `.forge/config.toml`
+/// of the project and `config.toml` of the base path. A value that isn't set
+/// falls through to the next layer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Setters)]
+#[serde(rename_all = "camelCase")]
+#[setters(strip_option)]
+pub struct Config {
+    /// The model used by every agent, overriding the ones of the workflow.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<ModelId>,
+    /// The model of the inline code completions, ideally a code model that
+    /// fills in the middle, eg: `mistralai/codestral-2501`. The one of the
+    /// first agent otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completion_model: Option<ModelId>,
+    /// Generation parameters of every agent, overriding the ones of the
+    /// workflow.
+    #[serde(default)]
+    pub parameters: ModelParameters,
+    /// Tools removed from every agent, eg: the shell in a sensitive repo.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disabled_tools: Option<Vec<ToolName>>,
+    /// The limits of the requests sent to the provider.
+    #[serde(default)]
+    pub rate_limit: RateLimit,
+    /// Runs the shell in restricted mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restricted: Option<bool>,
+    /// Removes the tools that change the files or run commands, to ask about
+    /// a codebase without touching it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+    /// Maximum spend of a conversation in USD.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<f64>,
+    /// Formats the files the agents write or patch with the formatter of
+    /// their project, eg: rustfmt or prettier. On unless disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format_on_write: Option<bool>,
+    /// Minimum similarity, from 0 to 1, of the text a patch replaces when its
+    /// search text isn't found exactly. 0.7 unless set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuzzy_threshold: Option<f64>,
+    /// Directories the file system tools can access besides the cwd.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_paths: Option<Vec<PathBuf>>,
+    /// Colors of the terminal output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<ThemeName>,
+    /// Speaking the final summary of each turn aloud.
+    #[serde(default)]
+    pub speech: Speech,
+    /// Databases the query tools can connect to, by their name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub databases: Option<BTreeMap<String, Database>>,
+    /// Masking the secrets of the context before it's sent to the provider,
+    /// and filtering the personal or internal data out of it.
+    #[serde(default)]
+    pub redaction: Redaction,
+    /// Reusing the responses of the provider to the same requests.
+    #[serde(default)]
+    pub response_cache: ResponseCache,
+    /// The tools of the WASM plugins.
+    #[serde(default)]
+    pub plugins: Plugins,
+    /// Tools that run a command, by their name.
+    pub tools: Option<BTreeMap<String, CommandTool>>,
+    /// Commands run on the events of the session, eg: a formatter after the
+    /// files are written.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<Vec<Hook>>,
+}
+
+/// A command run on an event, eg: `post_tool:fs_patch`. It receives the event
+/// as JSON on its stdin, and blocks the action by exiting with 2, with its
+/// stderr as the reason. See [`crate::HookEvent`] for the events.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Setters)]
+#[serde(rename_all = "camelCase")]
+#[setters(strip_option)]
+pub struct Hook {
+    /// The event, optionally followed by the tool or the agent it's limited
+    /// to, eg: `pre_tool:fs_create` or `post_turn`.
+    pub event: String,
+    /// Command run by the shell, eg: `./scripts/policy.sh`.
+    pub cmd: String,
+    /// Seconds after which the command is killed, a minute unless set.
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+/// A tool that runs a command in the cwd, eg: a deploy script. The arguments
+/// of the call are passed in the environment, as JSON in `FORGE_TOOL_ARGS`
+/// and each top level one in `FORGE_ARG_<NAME>`, eg: `FORGE_ARG_TARGET`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Setters)]
+#[serde(rename_all = "camelCase")]
+#[setters(strip_option)]
+pub struct CommandTool {
+    /// Command run by the shell, eg: `./scripts/deploy.sh`.
+    pub cmd: String,
+    /// What the tool does, for the agents to know when to call it.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// JSON schema of the arguments. None unless set.
+    #[serde(default)]
+    pub schema: Option<serde_json::Value>,
+    /// Seconds after which the command is killed, a minute unless set.
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// Variables set in the environment of the command.
+    #[serde(default)]
+    pub env: Option<BTreeMap<String, String>>,
+}
+
+impl CommandTool {
+    pub const DEFAULT_TIMEOUT: u64 = 60;
+}
+
+/// Loading the tools of the WASM plugins of a directory, on unless disabled.
+/// The plugins run in a sandbox, with access to the files of the cwd and the
+/// allowed paths and to the allowed hosts only.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Setters)]
+#[serde(rename_all = "camelCase")]
+#[setters(strip_option)]
+pub struct Plugins {
+    pub enabled: Option<bool>,
+    /// Directory of the plugins, `plugins` of the base path unless set.
+    pub dir: Option<PathBuf>,
+    /// Hosts the plugins can send requests to, eg: `api.github.com` or
+    /// `*.atlassian.net`. None unless set.
+    pub allowed_hosts: Option<Vec<String>>,
+}
+
+/// Caching the responses of the provider by the model and the whole request,
+/// so that rerunning a workflow on unchanged inputs, eg: generating the docs
+/// in CI, costs nothing. Off unless enabled.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Setters)]
+#[serde(rename_all = "camelCase")]
+#[setters(strip_option)]
+pub struct ResponseCache {
+    pub enabled: Option<bool>,
+    /// Seconds a response is reused for, a week unless set.
+    pub ttl: Option<u64>,
+    /// Directory of the cached responses, `cache/responses` of the base path
+    /// unless set.
+    pub dir: Option<PathBuf>,
+}
+
+impl ResponseCache {
+    pub const DEFAULT_TTL: u64 = 7 * 24 * 60 * 60;
+}
+
+/// Masking the secrets of the context before it's sent to the provider, on
+/// unless disabled, followed by the content filter of the rules and filters
+/// that are set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Setters)]
+#[serde(rename_all = "camelCase")]
+#[setters(strip_option)]
+pub struct Redaction {
+    pub enabled: Option<bool>,
+    /// Patterns of the values that aren't masked by kind of secret, eg: the
+    /// fake keys of the test fixtures.
+    pub allow: Option<BTreeMap<SecretKind, Vec<String>>>,
+    /// What is done with each kind of personal or internal data, eg: the
+    /// emails anonymized and the hostnames blocked.
+    pub filters: Option<BTreeMap<PiiKind, RedactionAction>>,
+    /// Domains whose hosts the hostname filter finds, eg: `corp.example.com`.
+    pub internal_domains: Option<Vec<String>>,
+    /// Patterns of the data specific to the team, eg: the ids of the
+    /// customers.
+    pub rules: Option<Vec<FilterRule>>,
+    /// File the content filter appends an entry to for each redaction, in
+    /// JSON lines. The redacted values are never written to it.
+    pub audit_log: Option<PathBuf>,
+}
+
+/// Data the content filter finds with a pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Setters)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterRule {
+    /// Name of the data, as reported, eg: `customer-id`
+    pub name: String,
+    /// Regular expression of the data, eg: `CUS-\d{8}`
+    pub pattern: String,
+    pub action: RedactionAction,
+}
+
+/// A database the query tools can connect to, which is only read unless
+/// writes are allowed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Setters)]
+#[serde(rename_all = "camelCase")]
+pub struct Database {
+    /// Connection string, eg: `postgres://app@localhost/app` or
+    /// `sqlite://data/dev.db`. It's read from the environment variable it
+    /// names when it starts with `$`, eg: `$DATABASE_URL`.
+    pub url: String,
+    /// Allows the statements that change the data or the schema.
+    #[serde(default)]
+    pub allow_writes: bool,
+}
+
+impl Database {
+    /// The connection string, read from the environment when it names a
+    /// variable.
+    pub fn connection_string(&self) -> anyhow::Result<String> {
+        match self.url.strip_prefix('$') {
+            Some(name) => std::env::var(name)
+                .map_err(|_| anyhow::anyhow!("The environment variable {name} is not set")),
+            None => Ok(self.url.clone()),
+        }
+    }
+}
+
+/// Speaking the final summary of each turn aloud, off unless enabled.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Setters)]
+#[serde(rename_all = "camelCase")]
+#[setters(strip_option)]
+pub struct Speech {
+    pub enabled: Option<bool>,
+    pub engine: Option<SpeechEngine>,
+    /// Voice of the engine, eg: `Samantha` for `say` on macOS or `alloy` for
+    /// OpenAI.
+    pub voice: Option<String>,
+}
+
+/// Engines that speak the summaries.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Display, EnumString,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum SpeechEngine {
+    /// The speech synthesizer of the OS: `say` on macOS, `espeak-ng` or
+    /// `espeak` on Linux and SAPI on Windows.
+    #[default]
+    System,
+    /// The speech API of OpenAI, with `OPENAI_API_KEY`.
+    OpenAi,
+}
+
+/// Themes of the terminal output, eg: `light` for a terminal with a light
+/// background. Colors are turned off by `NO_COLOR` whatever the theme.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Display, EnumString,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+    NoColor,
+}
+
+impl Config {
+    /// Fills the values that aren't set with the ones of `lower`, a layer with
+    /// a lower precedence.
+    pub fn or(self, lower: Config) -> Self {
+        Self {
+            model: self.model.or(lower.model),
+            completion_model: self.completion_model.or(lower.completion_model),
+            parameters: lower.parameters.merge(&self.parameters),
+            disabled_tools: self.disabled_tools.or(lower.disabled_tools),
+            rate_limit: RateLimit {
+                requests_per_minute: self
+                    .rate_limit
+                    .requests_per_minute
+                    .or(lower.rate_limit.requests_per_minute),
+                tokens_per_minute: self
+                    .rate_limit
+                    .tokens_per_minute
+                    .or(lower.rate_limit.tokens_per_minute),
+            },
+            restricted: self.restricted.or(lower.restricted),
+            read_only: self.read_only.or(lower.read_only),
+            budget: self.budget.or(lower.budget),
+            format_on_write: self.format_on_write.or(lower.format_on_write),
+            fuzzy_threshold: self.fuzzy_threshold.or(lower.fuzzy_threshold),
+            allowed_paths: self.allowed_paths.or(lower.allowed_paths),
+            theme: self.theme.or(lower.theme),
+            speech: Speech {
+                enabled: self.speech.enabled.or(lower.speech.enabled),
+                engine: self.speech.engine.or(lower.speech.engine),
+                voice: self.speech.voice.or(lower.speech.voice),
+            },
+            databases: self.databases.or(lower.databases),
+            redaction: Redaction {
+                enabled: self.redaction.enabled.or(lower.redaction.enabled),
+                allow: self.redaction.allow.or(lower.redaction.allow),
+                filters: self.redaction.filters.or(lower.redaction.filters),
+                internal_domains: self
+                    .redaction
+                    .internal_domains
+                    .or(lower.redaction.internal_domains),
+                rules: self.redaction.rules.or(lower.redaction.rules),
+                audit_log: self.redaction.audit_log.or(lower.redaction.audit_log),
+            },
+            response_cache: ResponseCache {
+                enabled: self.response_cache.enabled.or(lower.response_cache.enabled),
+                ttl: self.response_cache.ttl.or(lower.response_cache.ttl),
+                dir: self.response_cache.dir.or(lower.response_cache.dir),
+            },
+            plugins: Plugins {
+                enabled: self.plugins.enabled.or(lower.plugins.enabled),
+                dir: self.plugins.dir.or(lower.plugins.dir),
+                allowed_hosts: self.plugins.allowed_hosts.or(lower.plugins.allowed_hosts),
+            },
+            tools: self.tools.or(lower.tools),
+            hooks: self.hooks.or(lower.hooks),
+        }
+    }
+
+    /// Applies the model, parameters and disabled tools to every agent of the
+    /// workflow.
+    pub fn apply(&self, workflow: &mut Workflow) {
+        for agent in workflow.agents.iter_mut() {
+            if let Some(model) = &self.model {
+                agent.model = model.clone();
+            }
+            agent.parameters = agent.parameters.clone().merge(&self.parameters);
+            if let Some(disabled) = &self.disabled_tools {
+                agent.tools.retain(|tool| !disabled.contains(tool));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_or() {
+        let cli = Config::default()
+            .budget(2.0)
+            .redaction(Redaction::default().enabled(false))
+            .response_cache(ResponseCache::default().enabled(false));
+        let env = Config::default()
+            .model(ModelId::new("gpt-4o"))
+            .speech(Speech::default().engine(SpeechEngine::OpenAi))
+            .rate_limit(RateLimit::default().requests_per_minute(50));
+        let project = Config::default()
+            .model(ModelId::new("gpt-4o-mini"))
+            .completion_model(ModelId::new("mistralai/codestral-2501"))
+            .parameters(ModelParameters::default().temperature(0.2))
+            .read_only(true)
+            .budget(5.0)
+            .plugins(Plugins::default().allowed_hosts(vec!["api.github.com".to_string()]))
+            .redaction(Redaction::default().filters(BTreeMap::from([(
+                PiiKind::Email,
+                RedactionAction::Anonymize,
+            )])));
+        let user = Config::default()
+            .parameters(ModelParameters::default().temperature(0.7).top_p(0.9))
+            .rate_limit(RateLimit::default().tokens_per_minute(40000))
+            .restricted(true)
+            .format_on_write(false)
+            .fuzzy_threshold(0.9)
+            .theme(ThemeName::Light)
+            .speech(Speech::default().enabled(true).voice("alloy".to_string()))
+            .databases(BTreeMap::from([(
+                "app".to_string(),
+                Database::default().url("sqlite://app.db".to_string()),
+            )]))
+            .tools(BTreeMap::from([(
+                "deploy".to_string(),
+                CommandTool::default().cmd("./scripts/deploy.sh".to_string()),
+            )]))
+            .hooks(vec![Hook::default()
+                .event("post_tool:fs_patch".to_string())
+                .cmd("cargo fmt".to_string())])
+            .redaction(
+                Redaction::default()
+                    .enabled(true)
+                    .filters(BTreeMap::from([(PiiKind::Name, RedactionAction::Block)]))
+                    .audit_log(PathBuf::from("/var/log/forge/redactions.jsonl")),
+            )
+            .response_cache(ResponseCache::default().enabled(true).ttl(3600))
+            .plugins(
+                Plugins::default()
+                    .enabled(true)
+                    .allowed_hosts(vec!["*.atlassian.net".to_string()]),
+            );
+
+        let actual = cli.or(env).or(project).or(user);
+        let expected = Config {
+            model: Some(ModelId::new("gpt-4o")),
+            completion_model: Some(ModelId::new("mistralai/codestral-2501")),
+            parameters: ModelParameters::default().temperature(0.2).top_p(0.9),
+            disabled_tools: None,
+            rate_limit: RateLimit::default()
+                .requests_per_minute(50)
+                .tokens_per_minute(40000),
+            restricted: Some(true),
+            read_only: Some(true),
+            budget: Some(2.0),
+            format_on_write: Some(false),
+            fuzzy_threshold: Some(0.9),
+            allowed_paths: None,
+            theme: Some(ThemeName::Light),
+            speech: Speech::default()
+                .enabled(true)
+                .engine(SpeechEngine::OpenAi)
+                .voice("alloy".to_string()),
+            databases: Some(BTreeMap::from([(
+                "app".to_string(),
+                Database::default().url("sqlite://app.db".to_string()),
+            )])),
+            redaction: Redaction::default()
+                .enabled(false)
+                .filters(BTreeMap::from([(
+                    PiiKind::Email,
+                    RedactionAction::Anonymize,
+                )]))
+                .audit_log(PathBuf::from("/var/log/forge/redactions.jsonl")),
+            response_cache: ResponseCache::default().enabled(false).ttl(3600),
+            plugins: Plugins::default()
+                .enabled(true)
+                .allowed_hosts(vec!["api.github.com".to_string()]),
+            tools: Some(BTreeMap::from([(
+                "deploy".to_string(),
+                CommandTool::default().cmd("./scripts/deploy.sh".to_string()),
+            )])),
+            hooks: Some(vec![Hook::default()
+                .event("post_tool:fs_patch".to_string())
+                .cmd("cargo fmt".to_string())]),
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut workflow: Workflow = serde_json::from_value(serde_json::json!({
+            "agents": [{
+                "id": "software-engineer",
+                "model": "anthropic/claude-3.7-sonnet",
+                "tools": ["tool_forge_fs_read", "tool_forge_process_shell"],
+                "parameters": {"temperature": 0.7}
+            }]
+        }))
+        .unwrap();
+        let config = Config::default()
+            .model(ModelId::new("gpt-4o"))
+            .parameters(ModelParameters::default().top_p(0.9))
+            .disabled_tools(vec![ToolName::new("tool_forge_process_shell")]);
+
+        config.apply(&mut workflow);
+
+        let agent = &workflow.agents[0];
+        assert_eq!(agent.model, ModelId::new("gpt-4o"));
+        assert_eq!(
+            agent.parameters,
+            ModelParameters::default().temperature(0.7).top_p(0.9)
+        );
+        assert_eq!(agent.tools, vec![ToolName::new("tool_forge_fs_read")]);
+    }
+}
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 use super::{ToolCallFull, ToolResult};
-use crate::{ToolChoice, ToolDefinition};
+use crate::{Image, ModelParameters, ToolChoice, ToolDefinition};
 
 /// Represents a message being sent to the LLM provider
 /// NOTE: ToolResults message are part of the larger Request object and not part
@@ -18,10 +18,16 @@ pub enum ContextMessage {
 
 impl ContextMessage {
     pub fn user(content: impl ToString) -> Self {
+        Self::user_with_images(content, Vec::new())
+    }
+
+    /// A user message along with the images attached to it.
+    pub fn user_with_images(content: impl ToString, images: Vec<Image>) -> Self {
         ContentMessage {
             role: Role::User,
             content: content.to_string(),
             tool_calls: None,
+            images,
         }
         .into()
     }
@@ -31,6 +37,7 @@ impl ContextMessage {
             role: Role::System,
             content: content.to_string(),
             tool_calls: None,
+            images: Vec::new(),
         }
         .into()
     }
@@ -42,6 +49,7 @@ impl ContextMessage {
             role: Role::Assistant,
             content: content.to_string(),
             tool_calls,
+            images: Vec::new(),
         }
         .into()
     }
@@ -72,6 +80,9 @@ pub struct ContentMessage {
     pub role: Role,
     pub content: String,
     pub tool_calls: Option<Vec<ToolCallFull>>,
+    /// Images attached to a user message
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<Image>,
 }
 
 impl ContentMessage {
@@ -80,6 +91,7 @@ impl ContentMessage {
             role: Role::Assistant,
             content: content.to_string(),
             tool_calls: None,
+            images: Vec::new(),
         }
     }
 }
@@ -100,6 +112,11 @@ pub struct Context {
     pub tools: Vec<ToolDefinition>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_choice: Option<ToolChoice>,
+    #[serde(default, skip_serializing_if = "ModelParameters::is_empty")]
+    pub parameters: ModelParameters,
+    /// JSON schema the response of the model must conform to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<serde_json::Value>,
 }
 
 impl Context {
@@ -148,6 +165,38 @@ impl Context {
         }
     }
 
+    /// Paths the tools were called with, in the order of the calls and without
+    /// duplicates.
+    pub fn touched_paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        let calls = self.messages.iter().filter_map(|message| match message {
+            ContextMessage::ContentMessage(message) => message.tool_calls.as_ref(),
+            ContextMessage::ToolMessage(_) => None,
+        });
+        for call in calls.flatten() {
+            if let Some(path) = call.arguments.get("path").and_then(|path| path.as_str()) {
+                if !paths.iter().any(|touched| touched == path) {
+                    paths.push(path.to_string());
+                }
+            }
+        }
+        paths
+    }
+
+    /// Rough estimate of the prompt tokens of the context, including the
+    /// tools, at about 4 characters per token.
+    pub fn estimate_tokens(&self) -> u64 {
+        let tools = self
+            .tools
+            .iter()
+            .map(|tool| {
+                tool.description.len()
+                    + serde_json::to_string(&tool.input_schema).map_or(0, |schema| schema.len())
+            })
+            .sum::<usize>();
+        ((self.to_text().len() + tools) / 4) as u64
+    }
+
     /// Converts the context to textual format
     pub fn to_text(&self) -> String {
         let mut lines = String::new();
@@ -191,6 +240,7 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::ToolName;
 
     #[test]
     fn test_override_system_message() {
@@ -225,4 +275,22 @@ mod tests {
             ContextMessage::system("A system message")
         );
     }
+
+    #[test]
+    fn test_touched_paths() {
+        let read = ToolCallFull::new(ToolName::new("tool_forge_fs_read"))
+            .arguments(serde_json::json!({"path": "/repo/src/main.rs"}));
+        let shell = ToolCallFull::new(ToolName::new("tool_forge_process_shell"))
+            .arguments(serde_json::json!({"command": "ls"}));
+        let context = Context::default()
+            .add_message(ContextMessage::assistant(
+                "Reading",
+                Some(vec![read.clone(), shell]),
+            ))
+            .add_message(ContextMessage::assistant("Again", Some(vec![read])));
+
+        let actual = context.touched_paths();
+
+        assert_eq!(actual, vec!["/repo/src/main.rs".to_string()]);
+    }
 }
@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
 
 use anyhow::Result;
 use derive_more::derive::Display;
@@ -35,6 +36,12 @@ pub struct Conversation {
     pub state: HashMap<AgentId, AgentState>,
     pub events: Vec<Event>,
     pub workflow: Workflow,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checkpoints: Vec<Checkpoint>,
+    /// Files whose latest content is given to the agents at the start of every
+    /// turn, pinned with `/pin`
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub pinned_files: BTreeSet<PathBuf>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -43,6 +50,15 @@ pub struct AgentState {
     pub context: Option<Context>,
 }
 
+/// Named snapshot of the agents' state and the events of a conversation that
+/// the conversation can be rolled back to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub name: String,
+    pub state: HashMap<AgentId, AgentState>,
+    pub events: Vec<Event>,
+}
+
 impl Conversation {
     pub fn new(id: ConversationId, workflow: Workflow) -> Self {
         Self {
@@ -51,6 +67,8 @@ impl Conversation {
             archived: false,
             state: Default::default(),
             events: Default::default(),
+            checkpoints: Default::default(),
+            pinned_files: Default::default(),
         }
     }
 
@@ -76,4 +94,94 @@ impl Conversation {
     pub fn rfind_event(&self, event_name: &str) -> Option<&Event> {
         self.events.iter().rfind(|event| event.name == event_name)
     }
+
+    /// Snapshots the current state under the given name, replacing any
+    /// existing checkpoint with the same name.
+    pub fn checkpoint(&mut self, name: impl ToString) {
+        let checkpoint = Checkpoint {
+            name: name.to_string(),
+            state: self.state.clone(),
+            events: self.events.clone(),
+        };
+        self.checkpoints.retain(|c| c.name != checkpoint.name);
+        self.checkpoints.push(checkpoint);
+    }
+
+    /// Rolls the conversation back to the named checkpoint. The checkpoint is
+    /// kept so that multiple branches can be started from it, while the
+    /// checkpoints taken after it are discarded along with the abandoned path.
+    pub fn branch(&mut self, name: &str) -> crate::Result<()> {
+        let position = self
+            .checkpoints
+            .iter()
+            .position(|c| c.name == name)
+            .ok_or_else(|| Error::CheckpointNotFound(name.to_string()))?;
+        self.checkpoints.truncate(position + 1);
+        let checkpoint = &self.checkpoints[position];
+        self.state = checkpoint.state.clone();
+        self.events = checkpoint.events.clone();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fixture() -> Conversation {
+        let mut conversation = Conversation::new(ConversationId::generate(), Workflow::default());
+        conversation.state.insert(
+            AgentId::new("software-engineer"),
+            AgentState { turn_count: 1, context: None },
+        );
+        conversation
+    }
+
+    #[test]
+    fn test_branch_restores_checkpoint() {
+        let mut conversation = fixture();
+        let agent = AgentId::new("software-engineer");
+        conversation.checkpoint("before-refactor");
+        conversation.state.get_mut(&agent).unwrap().turn_count = 3;
+        conversation
+            .events
+            .push(Event::new("user_task_update", "try again"));
+        conversation.checkpoint("after-refactor");
+
+        conversation.branch("before-refactor").unwrap();
+
+        assert_eq!(conversation.turn_count(&agent), Some(1));
+        assert!(conversation.events.is_empty());
+        let actual = conversation
+            .checkpoints
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(actual, vec!["before-refactor"]);
+    }
+
+    #[test]
+    fn test_checkpoint_replaces_same_name() {
+        let mut conversation = fixture();
+        conversation.checkpoint("a");
+        conversation
+            .events
+            .push(Event::new("user_task_init", "task"));
+        conversation.checkpoint("a");
+
+        let actual = conversation
+            .checkpoints
+            .iter()
+            .map(|c| (c.name.as_str(), c.events.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(actual, vec![("a", 1)]);
+    }
+
+    #[test]
+    fn test_branch_unknown_checkpoint() {
+        let mut conversation = fixture();
+        assert!(conversation.branch("missing").is_err());
+    }
 }
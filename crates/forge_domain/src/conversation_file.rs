@@ -0,0 +1,96 @@
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::Conversation;
+
+/// Versioned file a conversation is exported to, `.forgeconv`, to reproduce
+/// a session on another machine or attach it to a bug report. It holds the
+/// workflow, the context of every agent along with the results of their tool
+/// calls, the events and the checkpoints.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationFile {
+    /// Version of the format, increased whenever it changes in a way older
+    /// versions of forge can't read
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub conversation: Conversation,
+}
+
+impl ConversationFile {
+    pub const VERSION: u32 = 1;
+    pub const EXTENSION: &'static str = "forgeconv";
+
+    pub fn new(conversation: Conversation) -> Self {
+        Self {
+            version: Self::VERSION,
+            exported_at: Utc::now(),
+            conversation,
+        }
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Reads the file, failing when it was written in a newer version of the
+    /// format.
+    pub fn parse(content: &str) -> anyhow::Result<Self> {
+        let value: serde_json::Value =
+            serde_json::from_str(content).context("The conversation file isn't valid JSON")?;
+        let version = value
+            .get("version")
+            .and_then(serde_json::Value::as_u64)
+            .context("The file isn't a conversation exported by forge, it has no version")?;
+        anyhow::ensure!(
+            version <= Self::VERSION as u64,
+            "The conversation was exported in version {version} of the format, this version of forge reads up to version {}, update it to import the conversation",
+            Self::VERSION
+        );
+        serde_json::from_value(value).context("The conversation file is invalid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{AgentId, AgentState, ContextMessage, ConversationId, Event, Workflow};
+
+    #[test]
+    fn test_round_trip() {
+        let mut conversation = Conversation::new(ConversationId::generate(), Workflow::default());
+        conversation.events.push(Event::task_init("Feed the cat"));
+        let context = crate::Context::default().add_message(ContextMessage::user("Feed the cat"));
+        conversation.state.insert(
+            AgentId::new("engineer"),
+            AgentState { turn_count: 1, context: Some(context) },
+        );
+        let file = ConversationFile::new(conversation.clone());
+
+        let actual = ConversationFile::parse(&file.to_json().unwrap()).unwrap();
+
+        assert_eq!(actual.version, ConversationFile::VERSION);
+        assert_eq!(
+            serde_json::to_value(&actual.conversation).unwrap(),
+            serde_json::to_value(&conversation).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_newer_version() {
+        let file = ConversationFile::new(Conversation::new(
+            ConversationId::generate(),
+            Workflow::default(),
+        ));
+        let mut value = serde_json::to_value(&file).unwrap();
+        value["version"] = serde_json::json!(ConversationFile::VERSION + 1);
+
+        let actual = ConversationFile::parse(&value.to_string()).unwrap_err();
+        assert!(actual.to_string().contains("update it to import"));
+
+        let actual = ConversationFile::parse("{}").unwrap_err();
+        assert!(actual.to_string().contains("it has no version"));
+    }
+}
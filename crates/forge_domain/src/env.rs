@@ -3,7 +3,9 @@ use std::path::PathBuf;
 use derive_setters::Setters;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Setters, Clone, Serialize, Deserialize)]
+use crate::{mask, Config, Toolchain};
+
+#[derive(Setters, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[setters(strip_option)]
 /// Represents the environment in which the application is running.
@@ -18,18 +20,80 @@ pub struct Environment {
     pub home: Option<PathBuf>,
     /// The shell being used.
     pub shell: String,
+    /// The kind of project in the working directory and the tools to build
+    /// it with.
+    #[serde(default)]
+    pub toolchain: Toolchain,
     /// The Qdrant API Key
+    #[serde(skip_serializing, default)]
     pub qdrant_key: Option<String>,
     /// The Qdrant Cluster
     pub qdrant_cluster: Option<String>,
     /// The base path relative to which everything else stored.
     pub base_path: PathBuf,
     /// The Forge API key.
+    #[serde(skip_serializing, default)]
     pub provider_key: String,
     /// The base url for provider
     pub provider_url: String,
     /// The OpenAI API key required to use embedding models.
+    #[serde(skip_serializing, default)]
     pub openai_key: Option<String>,
+    /// The tokens the tools authenticate with to the hosts of the
+    /// repositories.
+    #[serde(skip_serializing, default)]
+    pub github_token: Option<String>,
+    #[serde(skip_serializing, default)]
+    pub gitlab_token: Option<String>,
+    #[serde(skip_serializing, default)]
+    pub bitbucket_token: Option<String>,
+    /// The file to record the traffic of the provider to.
+    pub record_path: Option<PathBuf>,
+    /// The recording to replay instead of making requests to the provider.
+    pub replay_path: Option<PathBuf>,
+    /// Whether the user trusts the working directory. The config, prompts and
+    /// workflow of an untrusted one are ignored and its files can't be
+    /// changed.
+    #[serde(default)]
+    pub trusted: bool,
+    /// The secret of the install the placeholders of the anonymized values
+    /// are keyed with.
+    #[serde(skip_serializing, default)]
+    pub redaction_key: Vec<u8>,
+    /// The configuration resolved from the environment and the config files.
+    #[serde(default)]
+    pub config: Config,
+}
+
+// note: the keys aren't serialized nor debugged, since the environment is
+// rendered in the prompts and logged.
+impl std::fmt::Debug for Environment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Environment")
+            .field("os", &self.os)
+            .field("pid", &self.pid)
+            .field("cwd", &self.cwd)
+            .field("home", &self.home)
+            .field("shell", &self.shell)
+            .field("toolchain", &self.toolchain)
+            .field("qdrant_key", &self.qdrant_key.as_deref().map(mask))
+            .field("qdrant_cluster", &self.qdrant_cluster)
+            .field("base_path", &self.base_path)
+            .field("provider_key", &mask(&self.provider_key))
+            .field("provider_url", &self.provider_url)
+            .field("openai_key", &self.openai_key.as_deref().map(mask))
+            .field("github_token", &self.github_token.as_deref().map(mask))
+            .field("gitlab_token", &self.gitlab_token.as_deref().map(mask))
+            .field(
+                "bitbucket_token",
+                &self.bitbucket_token.as_deref().map(mask),
+            )
+            .field("record_path", &self.record_path)
+            .field("replay_path", &self.replay_path)
+            .field("trusted", &self.trusted)
+            .field("config", &self.config)
+            .finish()
+    }
 }
 
 impl Environment {
@@ -44,4 +108,61 @@ impl Environment {
     pub fn history_path(&self) -> PathBuf {
         self.base_path.join(".forge_history")
     }
+
+    pub fn stats_path(&self) -> PathBuf {
+        self.base_path.join("stats.jsonl")
+    }
+
+    /// The hash-chained log of the tool calls and the files they changed.
+    pub fn audit_path(&self) -> PathBuf {
+        self.base_path.join("audit.jsonl")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fixture() -> Environment {
+        Environment {
+            os: "linux".to_string(),
+            pid: 1,
+            cwd: PathBuf::from("/home/user/project"),
+            home: None,
+            shell: "/bin/bash".to_string(),
+            toolchain: Toolchain::default(),
+            qdrant_key: None,
+            qdrant_cluster: None,
+            base_path: PathBuf::from("/home/user/.config/forge"),
+            provider_key: "sk-or-v1-0123456789abcdef".to_string(),
+            provider_url: "https://api.openrouter.io/v1/".to_string(),
+            openai_key: Some("sk-proj-0123456789abcdef".to_string()),
+            github_token: Some("ghp_0123456789abcdef".to_string()),
+            gitlab_token: None,
+            bitbucket_token: None,
+            record_path: None,
+            replay_path: None,
+            trusted: true,
+            redaction_key: b"0123456789abcdef".to_vec(),
+            config: Config::default(),
+        }
+    }
+
+    #[test]
+    fn test_keys_are_not_serialized() {
+        let actual = serde_json::to_value(fixture()).unwrap();
+        assert_eq!(actual.get("providerKey"), None);
+        assert_eq!(actual.get("openaiKey"), None);
+        assert_eq!(actual.get("githubToken"), None);
+        assert_eq!(actual.get("redactionKey"), None);
+    }
+
+    #[test]
+    fn test_keys_are_masked_in_debug() {
+        let actual = format!("{:?}", fixture());
+        assert!(!actual.contains("0123456789abcdef"));
+        assert!(actual.contains("sk-...cdef"));
+    }
 }
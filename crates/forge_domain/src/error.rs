@@ -36,6 +36,15 @@ pub enum Error {
 
     #[error("Conversation not found: {0}")]
     ConversationNotFound(ConversationId),
+
+    #[error("Checkpoint not found: {0}")]
+    CheckpointNotFound(String),
+
+    #[error("Invalid output schema: {0}")]
+    OutputSchema(String),
+
+    #[error("Agent '{0}' failed to produce output matching its schema: {1}")]
+    OutputValidation(AgentId, String),
 }
 
 pub type Result<A> = std::result::Result<A, Error>;
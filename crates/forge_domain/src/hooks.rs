@@ -0,0 +1,89 @@
+//! Commands of the config run on the events of the session, eg: formatting the
+//! files the agents write, sending a notification once a turn is over or
+//! enforcing a policy on the tool calls. A hook receives the event as JSON on
+//! its stdin, eg: `{"event": "pre_tool", "tool": "tool_forge_fs_create",
+//! "arguments": {...}}`, and by its exit code:
+//!
+//! - `0` lets the action go on. The fields of a JSON object it prints replace
+//!   the ones of the event, eg: `{"arguments": {...}}` before a tool call or
+//!   `{"content": "..."}` after it.
+//! - `2` blocks the action, with its stderr as the reason the agent is given.
+//!   The end of a turn or of the session is over already, so it can't be
+//!   blocked.
+//! - Any other code is a failure of the hook, which is logged and ignored.
+
+use serde_json::Value;
+use strum_macros::{Display, EnumString};
+
+/// The events hooks run on. The tool events can be limited to a tool, eg:
+/// `pre_tool:fs_create`, and the turn events to an agent, eg:
+/// `post_turn:software-engineer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum HookEvent {
+    /// Before a tool is called, with its `tool` and `arguments`.
+    PreTool,
+    /// After a tool is called, with its `tool`, `arguments`, `content` and
+    /// `is_error`.
+    PostTool,
+    /// Before the turn of an agent, with its `agent` and `message`.
+    PreTurn,
+    /// After the turn of an agent, with its `agent` and `response`.
+    PostTurn,
+    /// Once the session is over, with its `conversation_id`.
+    SessionEnd,
+}
+
+/// What the hooks of an event decided.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HookOutcome {
+    /// The action goes on with the event as the hooks changed it.
+    Continue(Value),
+    /// A hook blocked the action, for the reason it gave.
+    Block(String),
+}
+
+impl HookEvent {
+    /// Whether the event of the hook, eg: `pre_tool:fs_create`, is this event
+    /// and its target. The tools are matched by their name with or without
+    /// their prefix, eg: `fs_create` for `tool_forge_fs_create`.
+    pub fn matches(&self, hook: &str, target: Option<&str>) -> bool {
+        let (name, filter) = match hook.split_once(':') {
+            Some((name, filter)) => (name, Some(filter)),
+            None => (hook, None),
+        };
+        if name != self.to_string() {
+            return false;
+        }
+        let Some(filter) = filter else {
+            return true;
+        };
+        target.is_some_and(|target| {
+            target == filter
+                || ["tool_forge_", "tool_"]
+                    .iter()
+                    .any(|prefix| target.strip_prefix(prefix) == Some(filter))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let actual = [
+            HookEvent::PreTool.matches("pre_tool", Some("tool_forge_fs_create")),
+            HookEvent::PreTool.matches("pre_tool:fs_create", Some("tool_forge_fs_create")),
+            HookEvent::PreTool.matches("pre_tool:command_deploy", Some("tool_command_deploy")),
+            HookEvent::PreTool.matches("pre_tool:fs_create", Some("tool_forge_fs_patch")),
+            HookEvent::PostTool.matches("pre_tool:fs_create", Some("tool_forge_fs_create")),
+            HookEvent::PostTurn.matches("post_turn:software-engineer", Some("software-engineer")),
+            HookEvent::SessionEnd.matches("session_end:software-engineer", None),
+        ];
+        assert_eq!(actual, [true, true, true, false, false, true, false]);
+    }
+}
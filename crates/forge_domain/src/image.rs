@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+/// An image attached to a user message, eg: pasted from the clipboard. It's
+/// only sent to the models that support vision.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Image {
+    /// The media type of the image, eg: `image/png`
+    pub mime_type: String,
+    /// The content of the image encoded in base64
+    pub data: String,
+}
+
+impl Image {
+    pub fn new(mime_type: impl ToString, data: impl ToString) -> Self {
+        Self { mime_type: mime_type.to_string(), data: data.to_string() }
+    }
+
+    /// The image as a `data:` URL, the form OpenAI compatible APIs accept.
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.mime_type, self.data)
+    }
+
+    /// The format of the image, ie: the subtype of its media type.
+    pub fn format(&self) -> &str {
+        self.mime_type
+            .split_once('/')
+            .map_or(self.mime_type.as_str(), |(_, format)| format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_data_url() {
+        let image = Image::new("image/png", "iVBORw0KGgo=");
+        assert_eq!(image.data_url(), "data:image/png;base64,iVBORw0KGgo=");
+        assert_eq!(image.format(), "png");
+    }
+}
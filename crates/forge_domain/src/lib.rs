@@ -1,55 +1,102 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
 mod agent;
+mod best_of;
 mod chat_request;
 mod chat_response;
+mod code_completion;
+mod command;
+mod config;
 mod context;
 mod conversation;
+mod conversation_file;
 mod env;
 mod error;
 mod event;
 mod file;
+mod hooks;
+mod image;
 mod message;
 mod model;
 mod orch;
+mod output_schema;
+mod path_guard;
+mod pii;
+mod pinned;
+mod plan;
 mod point;
 mod provider;
+mod question;
+mod rate_limit;
+mod redaction;
+mod secret;
+mod selection_edit;
+mod spawn;
 mod suggestion;
 mod summarize;
 mod template;
 mod tool;
+mod tool_audit;
 mod tool_call;
 mod tool_call_parser;
 mod tool_choice;
 mod tool_definition;
+mod tool_failure;
 mod tool_name;
 mod tool_result;
 mod tool_usage;
+mod toolchain;
+mod trigger;
 mod workflow;
 
 pub use agent::*;
+pub use best_of::*;
 pub use chat_request::*;
 pub use chat_response::*;
+pub use code_completion::*;
+pub use command::*;
+pub use config::*;
 pub use context::*;
 pub use conversation::*;
+pub use conversation_file::*;
 pub use env::*;
 pub use error::*;
 pub use event::*;
 pub use file::*;
+pub use hooks::*;
+pub use image::*;
 pub use message::*;
 pub use model::*;
 pub use orch::*;
+pub use output_schema::*;
+pub use path_guard::*;
+pub use pii::*;
+pub use pinned::*;
+pub use plan::*;
 pub use point::*;
 pub use provider::*;
+pub use question::*;
+pub use rate_limit::*;
+pub use redaction::*;
+pub use secret::*;
+pub use selection_edit::*;
+pub use spawn::*;
 pub use suggestion::*;
 pub use summarize::*;
 pub use template::*;
 pub use tool::*;
+pub use tool_audit::*;
 pub use tool_call::*;
 pub use tool_call_parser::*;
 pub use tool_choice::*;
 pub use tool_definition::*;
+pub use tool_failure::*;
 pub use tool_name::*;
 pub use tool_result::*;
 pub use tool_usage::*;
+pub use toolchain::*;
+pub use trigger::*;
 pub use workflow::*;
 
 #[async_trait::async_trait]
@@ -60,7 +107,17 @@ pub trait ProviderService: Send + Sync + 'static {
         context: Context,
     ) -> ResultStream<ChatCompletionMessage, anyhow::Error>;
     async fn models(&self) -> anyhow::Result<Vec<Model>>;
-    async fn parameters(&self, model: &ModelId) -> anyhow::Result<Parameters>;
+    async fn capabilities(&self, model: &ModelId) -> anyhow::Result<ModelCapabilities>;
+    /// Completes the prompt as is, eg: a fill-in-the-middle prompt of a code
+    /// model, returning the completion.
+    async fn complete(&self, id: &ModelId, _request: TextCompletion) -> anyhow::Result<String> {
+        anyhow::bail!("The provider doesn't support text completions with {}", id)
+    }
+    /// Reserves the capacity for a request with about `tokens` prompt tokens,
+    /// returning how long to wait before sending it.
+    async fn reserve(&self, _tokens: u64) -> std::time::Duration {
+        std::time::Duration::ZERO
+    }
 }
 
 #[async_trait::async_trait]
@@ -75,6 +132,9 @@ pub trait ToolService: Send + Sync {
 pub trait ConversationService: Send + Sync {
     async fn get(&self, id: &ConversationId) -> anyhow::Result<Option<Conversation>>;
     async fn create(&self, workflow: Workflow) -> anyhow::Result<ConversationId>;
+    /// Adds a conversation exported elsewhere, under a new id so that it
+    /// can't clash with the others
+    async fn import(&self, conversation: Conversation) -> anyhow::Result<ConversationId>;
     async fn inc_turn(&self, id: &ConversationId, agent: &AgentId) -> anyhow::Result<()>;
     async fn set_context(
         &self,
@@ -87,14 +147,37 @@ pub trait ConversationService: Send + Sync {
         conversation_id: &ConversationId,
         event: Event,
     ) -> anyhow::Result<()>;
+    /// Overrides the generation parameters of all agents in the conversation
+    async fn set_parameters(
+        &self,
+        id: &ConversationId,
+        parameters: &ModelParameters,
+    ) -> anyhow::Result<()>;
+    /// Switches all agents in the conversation to the given model
+    async fn set_model(&self, id: &ConversationId, model: &ModelId) -> anyhow::Result<()>;
+    /// Switches all agents in the conversation to the given mode
+    async fn set_mode(&self, id: &ConversationId, mode: AgentMode) -> anyhow::Result<()>;
+    /// Snapshots the agents' state of the conversation under the given name
+    async fn checkpoint(&self, id: &ConversationId, name: &str) -> anyhow::Result<()>;
+    /// Rolls the conversation back to the named checkpoint
+    async fn branch(&self, id: &ConversationId, name: &str) -> anyhow::Result<()>;
+    /// Replaces the files pinned to the context of the agents
+    async fn set_pinned_files(
+        &self,
+        id: &ConversationId,
+        files: BTreeSet<PathBuf>,
+    ) -> anyhow::Result<()>;
 }
 
 #[async_trait::async_trait]
 pub trait TemplateService: Send + Sync {
+    /// Renders the system prompt of the agent, along with the instructions of
+    /// the directories of the given paths that the task touched.
     async fn render_system(
         &self,
         agent: &Agent,
         prompt: &Template<SystemContext>,
+        paths: &[String],
     ) -> anyhow::Result<String>;
 
     async fn render_event(
@@ -103,6 +186,46 @@ pub trait TemplateService: Send + Sync {
         prompt: &Template<EventContext>,
         event: &Event,
     ) -> anyhow::Result<String>;
+
+    /// Files of the repository most relevant to the query, the best first,
+    /// that fit in about `token_budget` tokens.
+    async fn relevant_files(
+        &self,
+        _query: &str,
+        _token_budget: usize,
+    ) -> anyhow::Result<Vec<RelevantFile>> {
+        Ok(Vec::new())
+    }
+}
+
+/// The append-only audit log of the tool calls and of the files they
+/// changed.
+#[async_trait::async_trait]
+pub trait AuditService: Send + Sync {
+    /// Reads the files the tool call can change, to diff them once it's done.
+    async fn snapshot(&self, tool_call: &ToolCallFull) -> FileSnapshot;
+    /// The files the tool call changed since the snapshot was taken, along
+    /// with those its result reports.
+    async fn changes(&self, snapshot: &FileSnapshot, result: &ToolResult) -> Vec<FileChange>;
+    /// Chains the entry to the last one of the log and appends it.
+    async fn append(&self, entry: ToolAuditEntry) -> anyhow::Result<ToolAuditEntry>;
+    /// Appends the redactions of a turn to the redaction log at `path`, one
+    /// JSON object per line.
+    async fn redactions(&self, path: &Path, entries: &[AuditEntry<'_>]) -> anyhow::Result<()>;
+}
+
+/// Runs the hooks of the config on the events of the session.
+#[async_trait::async_trait]
+pub trait HookService: Send + Sync {
+    /// Runs the hooks of the event in order, each one receiving the event as
+    /// the previous ones changed it, until one of them blocks it. `target` is
+    /// the tool or the agent of the event.
+    async fn run(
+        &self,
+        event: HookEvent,
+        target: Option<&str>,
+        payload: serde_json::Value,
+    ) -> HookOutcome;
 }
 
 /// Core app trait providing access to services and repositories.
@@ -113,9 +236,13 @@ pub trait App: Send + Sync + 'static {
     type ProviderService: ProviderService;
     type ConversationService: ConversationService;
     type TemplateService: TemplateService;
+    type AuditService: AuditService;
+    type HookService: HookService;
 
     fn tool_service(&self) -> &Self::ToolService;
     fn provider_service(&self) -> &Self::ProviderService;
     fn conversation_service(&self) -> &Self::ConversationService;
     fn template_service(&self) -> &Self::TemplateService;
+    fn audit_service(&self) -> &Self::AuditService;
+    fn hook_service(&self) -> &Self::HookService;
 }
@@ -7,11 +7,15 @@ use strum_macros::EnumString;
 
 use super::ToolCall;
 
-#[derive(Default, Clone, Debug, Serialize, PartialEq, Eq)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Usage {
     pub prompt_tokens: u64,
     pub completion_tokens: u64,
     pub total_tokens: u64,
+    /// Prompt tokens read from the provider's prompt cache, which are part of
+    /// `prompt_tokens`
+    #[serde(default)]
+    pub cached_tokens: u64,
 }
 
 impl Display for Usage {
@@ -19,19 +23,33 @@ impl Display for Usage {
         if self.total_tokens > 0 {
             write!(
                 f,
-                "[tokens {}/{}/{}]",
+                "[tokens {}/{}/{}",
                 self.prompt_tokens, self.completion_tokens, self.total_tokens
-            )
+            )?;
+            if self.cached_tokens > 0 {
+                write!(f, ", {} cached", self.cached_tokens)?;
+            }
+            write!(f, "]")
         } else {
             Ok(())
         }
     }
 }
 
+/// Adds the usage of another request, eg: to total those of a conversation.
+impl std::ops::AddAssign<&Usage> for Usage {
+    fn add_assign(&mut self, other: &Usage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+        self.cached_tokens += other.cached_tokens;
+    }
+}
+
 /// Represents a message that was received from the LLM provider
 /// NOTE: Tool call messages are part of the larger Response object and not part
 /// of the message.
-#[derive(Default, Clone, Debug, Setters, PartialEq, Eq)]
+#[derive(Default, Clone, Debug, Setters, PartialEq, Eq, Serialize, Deserialize)]
 #[setters(into, strip_option)]
 pub struct ChatCompletionMessage {
     pub content: Option<Content>,
@@ -42,7 +60,8 @@ pub struct ChatCompletionMessage {
 }
 
 /// Represents partial or full content of a message
-#[derive(Clone, Debug, PartialEq, Eq, From)]
+#[derive(Clone, Debug, PartialEq, Eq, From, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Content {
     Part(ContentPart),
     Full(ContentFull),
@@ -145,9 +164,18 @@ mod tests {
     #[test]
     fn test_usage_display() {
         // Test with non-zero tokens
-        let usage = Usage { prompt_tokens: 10, completion_tokens: 20, total_tokens: 30 };
+        let usage = Usage {
+            prompt_tokens: 10,
+            completion_tokens: 20,
+            total_tokens: 30,
+            cached_tokens: 0,
+        };
         assert_eq!(usage.to_string(), "[tokens 10/20/30]");
 
+        // Test with tokens read from the prompt cache
+        let usage = Usage { cached_tokens: 8, ..usage };
+        assert_eq!(usage.to_string(), "[tokens 10/20/30, 8 cached]");
+
         // Test with zero tokens
         let usage = Usage::default();
         assert_eq!(usage.to_string(), "");
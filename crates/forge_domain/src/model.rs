@@ -2,23 +2,115 @@ use derive_more::derive::Display;
 use derive_setters::Setters;
 use serde::{Deserialize, Serialize};
 
+use crate::Usage;
+
 #[derive(Clone, Debug, Deserialize, Serialize, Setters)]
 pub struct Model {
     pub id: ModelId,
     pub name: String,
     pub description: Option<String>,
     pub context_length: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<Pricing>,
+    /// Set when the provider's listing describes the features of the model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<ModelCapabilities>,
     // TODO: add provider information to the model
 }
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
-pub struct Parameters {
-    pub tool_supported: bool,
+/// Price of using a model in USD.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Pricing {
+    /// Price per prompt token
+    pub prompt: f64,
+    /// Price per completion token
+    pub completion: f64,
+    /// Fixed price per request
+    pub request: f64,
+    /// Price per prompt token read from the prompt cache, the same as
+    /// `prompt` when unknown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read: Option<f64>,
+}
+
+impl Pricing {
+    /// Cost of a single request with the given token usage.
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        let cached_tokens = usage.cached_tokens.min(usage.prompt_tokens);
+        self.prompt * (usage.prompt_tokens - cached_tokens) as f64
+            + self.cache_read.unwrap_or(self.prompt) * cached_tokens as f64
+            + self.completion * usage.completion_tokens as f64
+            + self.request
+    }
+}
+
+/// Features supported by a model, which the orchestrator adapts the requests
+/// to. The default supports none of them, in which case tools are called
+/// through XML in the model's response.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize, Setters)]
+#[setters(strip_option)]
+pub struct ModelCapabilities {
+    /// Tools can be passed natively along with the request
+    pub supports_tools: bool,
+    /// Images can be part of the input
+    pub supports_vision: bool,
+    /// The response can be constrained to a JSON schema
+    pub supports_json_mode: bool,
+    /// Upper bound of the `max_tokens` of a request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+}
+
+/// Generation parameters sent along with every request made for an agent.
+/// Parameters that aren't set use the provider's defaults.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, Setters)]
+#[setters(strip_option)]
+pub struct ModelParameters {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+}
+
+impl ModelParameters {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Overrides the parameters with the ones that are set in `other`.
+    pub fn merge(self, other: &ModelParameters) -> Self {
+        Self {
+            temperature: other.temperature.or(self.temperature),
+            top_p: other.top_p.or(self.top_p),
+            top_k: other.top_k.or(self.top_k),
+            max_tokens: other.max_tokens.or(self.max_tokens),
+        }
+    }
+
+    /// Caps `max_tokens` to the limit of the model, as requests above it are
+    /// rejected by most providers.
+    pub fn limit_max_tokens(mut self, limit: Option<u32>) -> Self {
+        if let (Some(max_tokens), Some(limit)) = (self.max_tokens, limit) {
+            self.max_tokens = Some(max_tokens.min(limit));
+        }
+        self
+    }
 }
 
-impl Parameters {
-    pub fn new(tool_supported: bool) -> Self {
-        Self { tool_supported }
+impl ModelCapabilities {
+    /// Capabilities of models served by providers that support all the
+    /// features, such as OpenAI.
+    pub fn all() -> Self {
+        Self {
+            supports_tools: true,
+            supports_vision: true,
+            supports_json_mode: true,
+            max_output_tokens: None,
+        }
     }
 }
 
@@ -37,3 +129,81 @@ impl ModelId {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_model_parameters_merge() {
+        let fixture = ModelParameters::default().temperature(0.7).max_tokens(1024);
+        let overrides = ModelParameters::default().temperature(0.2).top_p(0.9);
+
+        let actual = fixture.merge(&overrides);
+        let expected = ModelParameters::default()
+            .temperature(0.2)
+            .top_p(0.9)
+            .max_tokens(1024);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_limit_max_tokens() {
+        let fixture = ModelParameters::default().max_tokens(16000);
+
+        assert_eq!(
+            fixture.clone().limit_max_tokens(Some(8192)).max_tokens,
+            Some(8192)
+        );
+        assert_eq!(fixture.limit_max_tokens(None).max_tokens, Some(16000));
+        assert_eq!(
+            ModelParameters::default()
+                .limit_max_tokens(Some(8192))
+                .max_tokens,
+            None
+        );
+    }
+
+    #[test]
+    fn test_pricing_cost() {
+        let fixture = Pricing {
+            prompt: 0.000003,
+            completion: 0.000015,
+            request: 0.001,
+            cache_read: None,
+        };
+        let usage = Usage {
+            prompt_tokens: 1000,
+            completion_tokens: 200,
+            total_tokens: 1200,
+            cached_tokens: 0,
+        };
+
+        let actual = fixture.cost(&usage);
+        let expected = 0.003 + 0.003 + 0.001;
+        assert!((actual - expected).abs() < f64::EPSILON);
+        assert_eq!(Pricing::default().cost(&usage), 0.0);
+    }
+
+    #[test]
+    fn test_pricing_cost_cached() {
+        let fixture = Pricing {
+            prompt: 0.000003,
+            completion: 0.000015,
+            request: 0.0,
+            cache_read: Some(0.0000003),
+        };
+        let usage = Usage {
+            prompt_tokens: 1000,
+            completion_tokens: 200,
+            total_tokens: 1200,
+            cached_tokens: 800,
+        };
+
+        let actual = fixture.cost(&usage);
+        let expected = 0.0006 + 0.00024 + 0.003;
+        assert!((actual - expected).abs() < 1e-12);
+    }
+}
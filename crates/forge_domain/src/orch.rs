@@ -1,31 +1,67 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
+use std::time::Instant;
 
 use async_recursion::async_recursion;
-use futures::future::join_all;
-use futures::{Stream, StreamExt};
-use tracing::debug;
+use futures::stream::FuturesUnordered;
+use futures::{Stream, StreamExt, TryStreamExt};
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, instrument, warn, Span};
 
 use crate::*;
 
 type ArcSender = Arc<tokio::sync::mpsc::Sender<anyhow::Result<AgentMessage<ChatResponse>>>>;
 
+/// Tools that change the files of the project, after which the agents verify
+/// their changes.
+pub const WRITE_TOOLS: [&str; 9] = [
+    "tool_forge_fs_create",
+    "tool_forge_fs_patch",
+    "tool_forge_fs_remove",
+    "tool_forge_fs_move",
+    "tool_forge_fs_scaffold",
+    "tool_forge_lsp_rename",
+    "tool_forge_package_add",
+    "tool_forge_package_remove",
+    "tool_forge_package_upgrade",
+];
+
 #[derive(Debug, Clone)]
 pub struct AgentMessage<T> {
     pub agent: AgentId,
     pub message: T,
 }
 
+/// Queue of the events dispatched during a chat request that are yet to be
+/// delivered to the agents subscribed to them.
+struct EventBus {
+    sender: mpsc::UnboundedSender<Event>,
+    receiver: Mutex<mpsc::UnboundedReceiver<Event>>,
+}
+
+impl EventBus {
+    fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self { sender, receiver: Mutex::new(receiver) }
+    }
+}
+
 pub struct Orchestrator<App> {
     app: Arc<App>,
     system_context: SystemContext,
     sender: Option<Arc<ArcSender>>,
     chat_request: ChatRequest,
+    bus: EventBus,
+    /// Answers of the user to the questions of the agents, unless there's no
+    /// user to ask
+    answers: Option<Mutex<mpsc::UnboundedReceiver<String>>>,
 }
 
 struct ChatCompletionResult {
     pub content: String,
     pub tool_calls: Vec<ToolCallFull>,
+    /// Usage reported by the provider, or the estimate when it reports none
+    pub usage: Usage,
 }
 
 impl<A: App> Orchestrator<A> {
@@ -40,9 +76,18 @@ impl<A: App> Orchestrator<A> {
             system_context,
             sender: sender.map(Arc::new),
             chat_request,
+            bus: EventBus::new(),
+            answers: None,
         }
     }
 
+    /// Lets the agents ask the user questions, which are answered through the
+    /// channel.
+    pub fn answers(mut self, answers: mpsc::UnboundedReceiver<String>) -> Self {
+        self.answers = Some(Mutex::new(answers));
+        self
+    }
+
     async fn send_message(&self, agent_id: &AgentId, message: ChatResponse) -> anyhow::Result<()> {
         if let Some(sender) = &self.sender {
             sender
@@ -67,37 +112,49 @@ impl<A: App> Orchestrator<A> {
         // Adding self to the list of tool definitions
 
         forge_tools.push(Event::tool_definition());
+        forge_tools.push(Question::tool_definition());
+        forge_tools.push(SpawnAgent::tool_definition());
 
         forge_tools
             .into_iter()
-            .filter(|tool| allowed.contains(&tool.name))
+            .filter(|tool| allowed.contains(&tool.name) && agent.mode.allows(&tool.name))
+            .chain((agent.mode == AgentMode::Plan).then(Plan::tool_definition))
             .collect::<Vec<_>>()
     }
 
-    async fn init_agent_context(&self, agent: &Agent) -> anyhow::Result<Context> {
+    /// Capabilities of the agent's model. When they can't be determined, eg:
+    /// the model isn't part of the provider's listing, the agent falls back to
+    /// calling tools through XML instead of failing.
+    async fn capabilities(&self, agent: &Agent) -> ModelCapabilities {
+        match self.app.provider_service().capabilities(&agent.model).await {
+            Ok(capabilities) => capabilities,
+            Err(error) => {
+                warn!(
+                    model = %agent.model,
+                    error = ?error,
+                    "Failed to detect model capabilities, falling back to XML tool calls"
+                );
+                ModelCapabilities::default()
+            }
+        }
+    }
+
+    async fn init_agent_context(
+        &self,
+        agent: &Agent,
+        capabilities: &ModelCapabilities,
+        event: &Event,
+    ) -> anyhow::Result<Context> {
         let tool_defs = self.init_tool_definitions(agent);
 
         let mut system_context = self.system_context.clone();
 
-        let tool_supported = self
-            .app
-            .provider_service()
-            .parameters(&agent.model)
-            .await?
-            .tool_supported;
+        let tool_supported = capabilities.supports_tools;
         system_context.tool_supported = Some(tool_supported);
 
-        let mut context = Context::default();
-
-        if let Some(system_prompt) = &agent.system_prompt {
-            let system_message = self
-                .app
-                .template_service()
-                .render_system(agent, system_prompt)
-                .await?;
-
-            context = context.set_first_system_message(system_message);
-        }
+        let context = self
+            .render_system_prompt(agent, event, Context::default())
+            .await?;
 
         Ok(context.extend_tools(if tool_supported {
             tool_defs
@@ -106,57 +163,233 @@ impl<A: App> Orchestrator<A> {
         }))
     }
 
+    /// Renders the system prompt of the agent into the context. The
+    /// instruction files it holds are those of the directories of the paths
+    /// the tools were called with so far, or that the event mentions.
+    async fn render_system_prompt(
+        &self,
+        agent: &Agent,
+        event: &Event,
+        context: Context,
+    ) -> anyhow::Result<Context> {
+        let Some(system_prompt) = &agent.system_prompt else {
+            return Ok(context);
+        };
+        let mut paths = context.touched_paths();
+        paths.extend(
+            event
+                .value
+                .split_whitespace()
+                .map(|word| word.trim_matches(|c: char| "`'\"()[]{},:;".contains(c)))
+                .filter(|word| word.contains('/'))
+                .map(str::to_string),
+        );
+        let system_message = self
+            .app
+            .template_service()
+            .render_system(agent, system_prompt, &paths)
+            .await?;
+        Ok(context.set_first_system_message(system_message))
+    }
+
+    /// The tools of a context that's carried over from the previous turns
+    /// follow the mode the agent has been switched to since.
+    fn refresh_tools(
+        &self,
+        agent: &Agent,
+        capabilities: &ModelCapabilities,
+        mut context: Context,
+    ) -> Context {
+        if capabilities.supports_tools {
+            context.tools = self.init_tool_definitions(agent);
+        }
+        context
+    }
+
+    /// Streams the response to the user, along with an estimate of its usage
+    /// that's updated as the chunks arrive, starting from the
+    /// `prompt_tokens` of the context.
     async fn collect_messages(
         &self,
         agent: &AgentId,
+        prompt_tokens: u64,
         mut response: impl Stream<Item = std::result::Result<ChatCompletionMessage, anyhow::Error>>
             + std::marker::Unpin,
     ) -> anyhow::Result<ChatCompletionResult> {
         let mut messages = Vec::new();
+        let mut estimate = Usage {
+            prompt_tokens,
+            total_tokens: prompt_tokens,
+            ..Default::default()
+        };
+        let mut streamed = 0;
+        let mut reported = None;
+        self.send(agent, ChatResponse::UsageEstimate(estimate.clone()))
+            .await?;
 
         while let Some(message) = response.next().await {
             let message = message?;
             messages.push(message.clone());
+            streamed += message
+                .tool_call
+                .iter()
+                .filter_map(|tool_call| tool_call.as_partial())
+                .map(|part| part.arguments_part.len())
+                .sum::<usize>();
             if let Some(content) = message.content {
+                streamed += content.as_str().len();
                 self.send(agent, ChatResponse::Text(content.as_str().to_string()))
                     .await?;
             }
 
             if let Some(usage) = message.usage {
+                reported = Some(usage.clone());
                 self.send(agent, ChatResponse::Usage(usage)).await?;
+            } else if reported.is_none() && (streamed / 4) as u64 != estimate.completion_tokens {
+                estimate.completion_tokens = (streamed / 4) as u64;
+                estimate.total_tokens = prompt_tokens + estimate.completion_tokens;
+                self.send(agent, ChatResponse::UsageEstimate(estimate.clone()))
+                    .await?;
             }
         }
 
-        let content = messages
-            .iter()
-            .flat_map(|m| m.content.iter())
-            .map(|content| content.as_str())
-            .collect::<Vec<_>>()
-            .join("");
+        let Sample { content, tool_calls } = parse_messages(&messages)?;
+        Ok(ChatCompletionResult { content, tool_calls, usage: reported.unwrap_or(estimate) })
+    }
 
-        // From Complete (incase streaming is disabled)
-        let mut tool_calls: Vec<ToolCallFull> = messages
-            .iter()
-            .flat_map(|message| message.tool_call.iter())
-            .filter_map(|message| message.as_full().cloned())
-            .collect::<Vec<_>>();
+    /// Requests the samples of `best_of` in parallel, each with its own
+    /// temperature, and returns the best one, which is shown to the user once
+    /// picked. Its usage is the one of all the samples since they're all paid
+    /// for.
+    async fn best_of(
+        &self,
+        agent: &Agent,
+        best_of: &BestOf,
+        context: &Context,
+        prompt_tokens: u64,
+    ) -> anyhow::Result<ChatCompletionResult> {
+        let requests = best_of.temperatures().into_iter().map(|temperature| {
+            let mut context = context.clone();
+            context.parameters.temperature = Some(temperature);
+            async move {
+                self.app
+                    .provider_service()
+                    .chat(&agent.model, context)
+                    .await?
+                    .try_collect::<Vec<_>>()
+                    .await
+            }
+        });
+        let responses = futures::future::join_all(requests).await;
 
-        // From partial tool calls
-        tool_calls.extend(ToolCallFull::try_from_parts(
-            &messages
-                .iter()
-                .filter_map(|message| message.tool_call.first())
-                .clone()
-                .filter_map(|tool_call| tool_call.as_partial().cloned())
-                .collect::<Vec<_>>(),
-        )?);
+        let mut samples = Vec::new();
+        let mut usage = Usage::default();
+        let mut error = None;
+        for response in responses {
+            let sample = response.and_then(|messages| {
+                let sample = parse_messages(&messages)?;
+                let reported = messages
+                    .into_iter()
+                    .filter_map(|message| message.usage)
+                    .next_back();
+                Ok((sample, reported))
+            });
+            match sample {
+                Ok((sample, reported)) => {
+                    let reported = match reported {
+                        Some(reported) => {
+                            self.send(&agent.id, ChatResponse::Usage(reported.clone()))
+                                .await?;
+                            reported
+                        }
+                        None => {
+                            let completion_tokens = (sample.content.len() / 4) as u64;
+                            Usage {
+                                prompt_tokens,
+                                completion_tokens,
+                                total_tokens: prompt_tokens + completion_tokens,
+                                ..Default::default()
+                            }
+                        }
+                    };
+                    usage.prompt_tokens += reported.prompt_tokens;
+                    usage.completion_tokens += reported.completion_tokens;
+                    usage.total_tokens += reported.total_tokens;
+                    usage.cached_tokens += reported.cached_tokens;
+                    samples.push(sample);
+                }
+                Err(e) => {
+                    warn!(agent = %agent.id, error = format!("{e:#}"), "A sample failed");
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        if samples.is_empty() {
+            return Err(error.unwrap_or_else(|| anyhow::anyhow!("No sample was requested")));
+        }
 
-        // From XML
-        tool_calls.extend(ToolCallFull::try_from_xml(&content)?);
+        let index = self.pick(agent, best_of, context, &samples).await?;
+        debug!(agent = %agent.id, samples = samples.len(), pick = index + 1, "Picked the best sample");
+        let Sample { content, tool_calls } = samples.swap_remove(index);
+        if !content.is_empty() {
+            self.send(&agent.id, ChatResponse::Text(content.clone()))
+                .await?;
+        }
+        Ok(ChatCompletionResult { content, tool_calls, usage })
+    }
 
-        Ok(ChatCompletionResult { content, tool_calls })
+    /// Index of the best of the samples, picked by the judge agent of
+    /// `best_of` if any. The consensus is used when the judge fails or
+    /// doesn't pick one of them.
+    #[async_recursion]
+    async fn pick(
+        &self,
+        agent: &Agent,
+        best_of: &BestOf,
+        context: &Context,
+        samples: &[Sample],
+    ) -> anyhow::Result<usize> {
+        let consensus = BestOf::consensus(samples, &context.tools);
+        let Judge::Agent(judge) = &best_of.judge else {
+            return Ok(consensus);
+        };
+        let task = context
+            .messages
+            .iter()
+            .rev()
+            .find(|message| message.has_role(Role::User))
+            .map(ContextMessage::content)
+            .unwrap_or_default();
+        let judge = self
+            .get_conversation()
+            .await?
+            .workflow
+            .get_agent(judge)?
+            .clone();
+        let reply = self
+            .run_agent(
+                &judge,
+                &BestOf::judge_event(&task, samples),
+                Some(&agent.id),
+            )
+            .await;
+        Ok(
+            match reply.map(|reply| BestOf::parse_pick(&reply, samples.len())) {
+                Ok(Some(index)) => index,
+                Ok(None) => {
+                    warn!(agent = %judge.id, "The judge didn't pick a sample, using the consensus");
+                    consensus
+                }
+                Err(e) => {
+                    warn!(agent = %judge.id, error = format!("{e:#}"), "The judge failed, using the consensus");
+                    consensus
+                }
+            },
+        )
     }
 
+    /// Records the event and queues it for the agents subscribed to it. The
+    /// subscribers run concurrently with the dispatching agent.
     async fn dispatch(&self, event: &Event) -> anyhow::Result<()> {
         debug!(
             conversation_id = %self.chat_request.conversation_id,
@@ -166,44 +399,293 @@ impl<A: App> Orchestrator<A> {
         );
 
         self.insert_event(event.clone()).await?;
-        join_all(
-            self.app
-                .conversation_service()
-                .get(&self.chat_request.conversation_id)
-                .await?
-                .ok_or(Error::ConversationNotFound(
-                    self.chat_request.conversation_id.clone(),
-                ))?
-                .entries(event.name.as_str())
-                .iter()
-                .map(|agent| self.init_agent(&agent.id, event)),
-        )
-        .await
-        .into_iter()
-        .collect::<anyhow::Result<Vec<()>>>()?;
+        self.bus
+            .sender
+            .send(event.clone())
+            .map_err(|_| anyhow::anyhow!("Event bus closed"))?;
         Ok(())
     }
 
-    #[async_recursion]
+    /// Starts the subscribers of every queued event as it arrives and waits
+    /// until all of them have finished and no more events are queued. The
+    /// `target` agent, if any, is started right away.
+    async fn deliver(&self, target: Option<(AgentId, Event)>) -> anyhow::Result<()> {
+        let mut receiver = self.bus.receiver.lock().await;
+        let mut running = FuturesUnordered::new();
+        // note: the agents run concurrently with each other but an agent runs
+        // one turn at a time, the events it receives while running being
+        // queued in the order they arrived, as concurrent turns would race on
+        // its context. An agent has an entry for as long as it runs.
+        let mut queued: HashMap<AgentId, VecDeque<Event>> = HashMap::new();
+        if let Some((agent, event)) = target {
+            queued.insert(agent.clone(), VecDeque::new());
+            running.push(self.run(agent, event));
+        }
+
+        loop {
+            let event = if running.is_empty() {
+                match receiver.try_recv() {
+                    Ok(event) => event,
+                    Err(_) => return Ok(()),
+                }
+            } else {
+                tokio::select! {
+                    Some(event) = receiver.recv() => event,
+                    Some((agent, result)) = running.next() => {
+                        result?;
+                        match queued.get_mut(&agent).and_then(VecDeque::pop_front) {
+                            Some(event) => running.push(self.run(agent, event)),
+                            None => {
+                                queued.remove(&agent);
+                            }
+                        }
+                        continue;
+                    }
+                }
+            };
+
+            for agent in self.get_conversation().await?.entries(event.name.as_str()) {
+                match queued.get_mut(&agent.id) {
+                    Some(events) => events.push_back(event.clone()),
+                    None => {
+                        queued.insert(agent.id.clone(), VecDeque::new());
+                        running.push(self.run(agent.id, event.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    async fn run(&self, agent: AgentId, event: Event) -> (AgentId, anyhow::Result<()>) {
+        let result = self.init_agent(&agent, &event, None).await;
+        (agent, result)
+    }
+
     async fn execute_tool(
         &self,
-        agent_id: &AgentId,
+        agent: &Agent,
         tool_call: &ToolCallFull,
     ) -> anyhow::Result<Option<ToolResult>> {
-        if let Some(event) = Event::parse(tool_call) {
+        let agent_id = &agent.id;
+        if let Some(question) = Question::parse(tool_call) {
+            Ok(Some(self.ask(agent_id, tool_call, question).await?))
+        } else if let Some(event) = Event::parse(tool_call) {
             self.send(agent_id, ChatResponse::Custom(event.clone()))
                 .await?;
 
             self.dispatch(&event).await?;
             Ok(None)
+        } else if let Some(spawn) = SpawnAgent::parse(tool_call) {
+            Ok(Some(self.spawn_agent(agent, tool_call, spawn).await?))
+        } else if let Some(plan) = Plan::parse(tool_call).filter(|_| agent.mode == AgentMode::Plan)
+        {
+            self.send(agent_id, ChatResponse::Plan(plan)).await?;
+            Ok(Some(ToolResult::from(tool_call.clone()).success(
+                "The plan was submitted to the user, stop here until they approve it",
+            )))
+        } else if !agent.mode.allows(&tool_call.name) {
+            Ok(Some(ToolResult::from(tool_call.clone()).failure(
+                anyhow::anyhow!(
+                    "{} isn't available in the planning mode, submit a plan with {} first",
+                    tool_call.name.as_str(),
+                    Plan::tool_name().as_str()
+                ),
+            )))
         } else {
-            Ok(Some(self.app.tool_service().call(tool_call.clone()).await))
+            Ok(Some(self.call_tool(agent_id, tool_call.clone()).await?))
         }
     }
 
+    /// Runs a child agent of `parent` to completion and returns its summary.
+    /// The child's work stays in its own context, and its failure is the
+    /// failure of the tool call rather than of the parent.
+    #[async_recursion]
+    async fn spawn_agent(
+        &self,
+        parent: &Agent,
+        tool_call: &ToolCallFull,
+        spawn: SpawnAgent,
+    ) -> anyhow::Result<ToolResult> {
+        let child = spawn.agent(parent);
+        debug!(agent = %parent.id, child = %child.id, goal = %spawn.goal, "Spawning agent");
+        let result = ToolResult::from(tool_call.clone());
+        let summary = self
+            .run_agent(&child, &spawn.event(), Some(&parent.id))
+            .await;
+        Ok(match summary {
+            Ok(summary) if summary.trim().is_empty() => {
+                result.failure(anyhow::anyhow!("The agent finished without a summary"))
+            }
+            Ok(summary) => result.success(summary),
+            Err(error) => result.failure(error),
+        })
+    }
+
+    /// Calls the tool between its hooks and records the call, along with the
+    /// files it changed, in the audit log. The turn fails rather than going
+    /// on without its record. The changed files are attached to the result
+    /// for the clients to show.
+    async fn call_tool(
+        &self,
+        agent_id: &AgentId,
+        tool_call: ToolCallFull,
+    ) -> anyhow::Result<ToolResult> {
+        let conversation_id = self.chat_request.conversation_id.clone();
+        let hooks = self.app.hook_service();
+        let payload = serde_json::json!({
+            "conversation_id": self.chat_request.conversation_id,
+            "agent": agent_id,
+            "tool": tool_call.name,
+            "arguments": tool_call.arguments,
+        });
+        let (tool_call, snapshot, mut result) = match hooks
+            .run(HookEvent::PreTool, Some(tool_call.name.as_str()), payload)
+            .await
+        {
+            HookOutcome::Block(reason) => {
+                let result = ToolResult::from(tool_call.clone())
+                    .failure(anyhow::anyhow!(reason).context("A hook blocked the call"));
+                (tool_call, FileSnapshot::default(), result)
+            }
+            HookOutcome::Continue(mut payload) => {
+                let tool_call = match payload.get_mut("arguments") {
+                    Some(arguments) => tool_call.arguments(arguments.take()),
+                    None => tool_call,
+                };
+                let snapshot = if WRITE_TOOLS.contains(&tool_call.name.as_str()) {
+                    self.app.audit_service().snapshot(&tool_call).await
+                } else {
+                    FileSnapshot::default()
+                };
+                let result = TOOL_CONVERSATION
+                    .scope(
+                        conversation_id,
+                        self.app.tool_service().call(tool_call.clone()),
+                    )
+                    .await;
+                let result = self.run_post_tool_hooks(agent_id, &tool_call, result).await;
+                (tool_call, snapshot, result)
+            }
+        };
+        result.files = self.app.audit_service().changes(&snapshot, &result).await;
+        let entry = ToolAuditEntry::new(
+            self.chat_request.conversation_id.clone(),
+            agent_id.clone(),
+            &tool_call,
+            &result,
+            &result.files,
+        );
+        self.app.audit_service().append(entry).await?;
+        Ok(result)
+    }
+
+    /// Runs the hooks after the tool call, which can replace its content or
+    /// turn it into a failure, eg: a linter rejecting the file written.
+    async fn run_post_tool_hooks(
+        &self,
+        agent_id: &AgentId,
+        tool_call: &ToolCallFull,
+        mut result: ToolResult,
+    ) -> ToolResult {
+        let payload = serde_json::json!({
+            "conversation_id": self.chat_request.conversation_id,
+            "agent": agent_id,
+            "tool": tool_call.name,
+            "arguments": tool_call.arguments,
+            "content": result.content,
+            "is_error": result.is_error,
+        });
+        match self
+            .app
+            .hook_service()
+            .run(HookEvent::PostTool, Some(tool_call.name.as_str()), payload)
+            .await
+        {
+            HookOutcome::Continue(payload) => {
+                if let Some(content) = payload.get("content").and_then(|content| content.as_str()) {
+                    result.content = content.to_string();
+                }
+                result
+            }
+            HookOutcome::Block(reason) => {
+                result.failure(anyhow::anyhow!(reason).context("A hook rejected the result"))
+            }
+        }
+    }
+
+    /// Runs the tools the agent verifies its changes with once it changed
+    /// files, so that the failures the changes caused are fed back right
+    /// away.
+    async fn verify_changes(
+        &self,
+        agent: &Agent,
+        tool_results: &[ToolResult],
+    ) -> anyhow::Result<Option<String>> {
+        let changed = tool_results
+            .iter()
+            .any(|result| !result.is_error && WRITE_TOOLS.contains(&result.name.as_str()));
+        if !changed {
+            return Ok(None);
+        }
+
+        let mut failures = Vec::new();
+        for tool in agent
+            .verify_after_write
+            .iter()
+            .filter(|tool| agent.mode.allows(tool))
+        {
+            let tool_call = ToolCallFull::new(tool.clone()).arguments(serde_json::json!({}));
+            self.send(&agent.id, ChatResponse::ToolCallStart(tool_call.clone()))
+                .await?;
+            let result = self.call_tool(&agent.id, tool_call).await?;
+            self.send(&agent.id, ChatResponse::ToolCallEnd(result.clone()))
+                .await?;
+            if result.is_error {
+                failures.push(format!(
+                    "<{}>{}</{}>",
+                    tool.as_str(),
+                    result.content,
+                    tool.as_str()
+                ));
+            }
+        }
+
+        Ok((!failures.is_empty()).then(|| {
+            format!(
+                "Verifying the project after your changes failed, fix the failures you caused:\n{}",
+                failures.join("\n")
+            )
+        }))
+    }
+
+    /// Asks the user the question and waits for the answer. Questions of
+    /// agents running concurrently are asked one at a time.
+    async fn ask(
+        &self,
+        agent_id: &AgentId,
+        tool_call: &ToolCallFull,
+        question: Question,
+    ) -> anyhow::Result<ToolResult> {
+        let result = ToolResult::from(tool_call.clone());
+        let Some(answers) = &self.answers else {
+            return Ok(result.failure(anyhow::anyhow!(
+                "There's no user to answer, carry on with your best judgement"
+            )));
+        };
+
+        let mut answers = answers.lock().await;
+        self.send(agent_id, ChatResponse::Question(question))
+            .await?;
+        Ok(match answers.recv().await {
+            Some(answer) => result.success(answer),
+            None => result.failure(anyhow::anyhow!("The question wasn't answered")),
+        })
+    }
+
     #[async_recursion]
     async fn execute_transform(
         &self,
+        agent: &AgentId,
         transforms: &[Transform],
         mut context: Context,
     ) -> anyhow::Result<Context> {
@@ -218,7 +700,7 @@ impl<A: App> Orchestrator<A> {
                     let mut summarize = Summarize::new(&mut context, *token_limit);
                     while let Some(mut summary) = summarize.summarize() {
                         let input = Event::new(input_key, summary.get());
-                        self.init_agent(agent_id, &input).await?;
+                        self.init_agent(agent_id, &input, Some(agent)).await?;
 
                         if let Some(value) = self.get_last_event(output_key).await? {
                             summary.set(serde_json::to_string(&value)?);
@@ -233,7 +715,7 @@ impl<A: App> Orchestrator<A> {
                     })) = context.messages.last_mut()
                     {
                         let task = Event::task_init(content.clone());
-                        self.init_agent(agent_id, &task).await?;
+                        self.init_agent(agent_id, &task, Some(agent)).await?;
                         if let Some(output) = self.get_last_event(output_key).await? {
                             let message = &output.value;
                             content
@@ -246,7 +728,7 @@ impl<A: App> Orchestrator<A> {
                     let input = Event::new(input_key, context.to_text());
 
                     // NOTE: Tap transformers will not modify the context
-                    self.init_agent(agent_id, &input).await?;
+                    self.init_agent(agent_id, &input, Some(agent)).await?;
                 }
             }
         }
@@ -290,7 +772,21 @@ impl<A: App> Orchestrator<A> {
             .await
     }
 
-    async fn init_agent(&self, agent: &AgentId, event: &Event) -> anyhow::Result<()> {
+    #[instrument(
+        name = "agent",
+        skip_all,
+        fields(
+            conversation_id = %self.chat_request.conversation_id,
+            agent = %agent,
+            turn = tracing::field::Empty,
+        )
+    )]
+    async fn init_agent(
+        &self,
+        agent: &AgentId,
+        event: &Event,
+        parent: Option<&AgentId>,
+    ) -> anyhow::Result<()> {
         debug!(
             conversation_id = %self.chat_request.conversation_id,
             agent = %agent,
@@ -299,13 +795,83 @@ impl<A: App> Orchestrator<A> {
         );
         let conversation = self.get_conversation().await?;
         let agent = conversation.workflow.get_agent(agent)?;
+        Span::current().record(
+            "turn",
+            conversation.turn_count(&agent.id).unwrap_or_default() + 1,
+        );
+        self.run_agent(agent, event, parent).await?;
+        Ok(())
+    }
 
+    /// Runs the turn of the agent, reporting its progress so that the agents
+    /// running on behalf of others show up nested under them, and returns its
+    /// last response. `parent` is the agent it runs on behalf of, eg: the one
+    /// that spawned it or whose transform it is.
+    async fn run_agent(
+        &self,
+        agent: &Agent,
+        event: &Event,
+        parent: Option<&AgentId>,
+    ) -> anyhow::Result<String> {
+        let hooks = self.app.hook_service();
+        let payload = serde_json::json!({
+            "conversation_id": self.chat_request.conversation_id,
+            "agent": agent.id,
+            "message": event.value,
+        });
+        if let HookOutcome::Block(reason) = hooks
+            .run(HookEvent::PreTurn, Some(agent.id.as_str()), payload)
+            .await
+        {
+            anyhow::bail!("A hook blocked the turn of {}: {reason}", agent.id)
+        }
+
+        let started = Instant::now();
+        self.send(
+            &agent.id,
+            ChatResponse::AgentStarted { parent: parent.cloned(), model: agent.model.clone() },
+        )
+        .await?;
+        let (answer, tokens) = self.run_requests(agent, event).await?;
+
+        let payload = serde_json::json!({
+            "conversation_id": self.chat_request.conversation_id,
+            "agent": agent.id,
+            "response": answer,
+        });
+        // note: the turn is over, so there's nothing left for the hooks to
+        // block.
+        if let HookOutcome::Block(reason) = hooks
+            .run(HookEvent::PostTurn, Some(agent.id.as_str()), payload)
+            .await
+        {
+            warn!(agent = %agent.id, reason = %reason, "A hook can't block the end of a turn");
+        }
+        self.send(
+            &agent.id,
+            ChatResponse::AgentCompleted { tokens, duration: started.elapsed() },
+        )
+        .await?;
+        Ok(answer)
+    }
+
+    /// Sends the requests of the agent's turn until it stops calling tools,
+    /// returning its last response and the tokens the requests used.
+    async fn run_requests(&self, agent: &Agent, event: &Event) -> anyhow::Result<(String, u64)> {
+        let conversation = self.get_conversation().await?;
+        let capabilities = self.capabilities(agent).await;
         let mut context = if agent.ephemeral {
-            self.init_agent_context(agent).await?
+            self.init_agent_context(agent, &capabilities, event).await?
         } else {
             match conversation.context(&agent.id) {
-                Some(context) => context.clone(),
-                None => self.init_agent_context(agent).await?,
+                // note: the instructions follow the directories the task moves
+                // into, so they're refreshed once per turn of the user.
+                Some(context) if Self::is_user_task(event) => {
+                    let context = self.refresh_tools(agent, &capabilities, context.clone());
+                    self.render_system_prompt(agent, event, context).await?
+                }
+                Some(context) => self.refresh_tools(agent, &capabilities, context.clone()),
+                None => self.init_agent_context(agent, &capabilities, event).await?,
             }
         };
 
@@ -320,36 +886,193 @@ impl<A: App> Orchestrator<A> {
             event.value.clone()
         };
 
-        context = context.add_message(ContextMessage::user(content));
+        // note: the JSON mode constrains every response of the model, which
+        // then can't call tools, so it's only used by the agents without any.
+        // The others, and the models without a JSON mode, are asked to follow
+        // the schema in the prompt, the answer is validated either way.
+        let json_mode = capabilities.supports_json_mode && agent.tools.is_empty();
+        let content = match &agent.output_schema {
+            Some(schema) if !json_mode => format!(
+                "{content}\n\nRespond with only a JSON document that conforms to this JSON schema:\n{schema}"
+            ),
+            _ => content,
+        };
+        let content = match agent.mode {
+            AgentMode::Plan => format!(
+                "{content}\n\nYou're in the planning mode: investigate the task with the read-only tools, then submit a plan with {} for the user to approve. Don't try to make any change yet.",
+                Plan::tool_name().as_str()
+            ),
+            AgentMode::Act => content,
+        };
+
+        let content = if agent.auto_context && Self::is_user_task(event) {
+            self.attach_relevant_files(agent, &event.value, content)
+                .await?
+        } else {
+            content
+        };
+
+        // note: models without vision reject requests with images, they're told
+        // about them instead.
+        let images = self.images(event);
+        let (content, images) = match images.len() {
+            count if count > 0 && !capabilities.supports_vision => (
+                format!("{content}\n\n[{count} attached image(s) omitted, the model doesn't support images]"),
+                Vec::new(),
+            ),
+            _ => (content, images),
+        };
+
+        // note: the pinned files are refreshed once per turn of the user, the
+        // other events are part of that turn.
+        if Self::is_user_task(event) {
+            let pinned = PinnedFiles::new(&conversation.pinned_files).render().await;
+            context = context.pin_files(pinned);
+        }
+
+        context = context
+            .add_message(ContextMessage::user_with_images(content, images))
+            .parameters(
+                agent
+                    .parameters
+                    .clone()
+                    .limit_max_tokens(capabilities.max_output_tokens),
+            );
+        let validator = match &agent.output_schema {
+            Some(schema) => {
+                if json_mode {
+                    context = context.output_schema(schema.clone());
+                }
+                Some(OutputValidator::new(schema)?)
+            }
+            None => None,
+        };
         self.set_context(&agent.id, context.clone()).await?;
 
+        let redactor = self.redactor()?;
+        let mut answer;
+        let mut tokens = 0;
+        let mut requests = 0;
+        let mut output_retries = 0;
+        let mut failures = ToolFailureTracker::default();
         loop {
-            context = self.execute_transform(&agent.transforms, context).await?;
+            context = self
+                .execute_transform(&agent.id, &agent.transforms, context)
+                .await?;
+            // note: the stored context is redacted too, so that each secret is
+            // reported once and isn't kept in the conversation.
+            if let Some(redactor) = &redactor {
+                let (redacted, report) = redactor.redact_context(context);
+                context = redacted;
+                if !report.is_empty() {
+                    debug!(agent = %agent.id, count = report.len(), "Redacted the context");
+                    self.audit(&agent.id, &report).await?;
+                    self.send(&agent.id, ChatResponse::Redacted(report)).await?;
+                }
+            }
             self.set_context(&agent.id, context.clone()).await?;
-            let response = self
-                .app
-                .provider_service()
-                .chat(&agent.model, context.clone())
+            let prompt_tokens = context.estimate_tokens();
+            let wait = self.app.provider_service().reserve(prompt_tokens).await;
+            if !wait.is_zero() {
+                debug!(agent = %agent.id, wait = ?wait, "Waiting for the rate limit of the provider");
+                self.send(&agent.id, ChatResponse::RateLimited(wait))
+                    .await?;
+                tokio::time::sleep(wait).await;
+            }
+            requests += 1;
+            self.send(&agent.id, ChatResponse::AgentTurn(requests))
                 .await?;
-            let ChatCompletionResult { tool_calls, content } =
-                self.collect_messages(&agent.id, response).await?;
+            let ChatCompletionResult { tool_calls, content, usage } = match &agent.best_of {
+                Some(best_of) if best_of.samples > 1 => {
+                    self.best_of(agent, best_of, &context, prompt_tokens)
+                        .await?
+                }
+                _ => {
+                    let response = self
+                        .app
+                        .provider_service()
+                        .chat(&agent.model, context.clone())
+                        .await?;
+                    self.collect_messages(&agent.id, prompt_tokens, response)
+                        .await?
+                }
+            };
+            tokens += usage.total_tokens;
 
             let mut tool_results = Vec::new();
+            let mut feedback = Vec::new();
+            let mut needs_user_input = None;
 
             for tool_call in tool_calls.iter() {
                 self.send(&agent.id, ChatResponse::ToolCallStart(tool_call.clone()))
                     .await?;
-                if let Some(tool_result) = self.execute_tool(&agent.id, tool_call).await? {
-                    tool_results.push(tool_result.clone());
+                if let Some(tool_result) = self.execute_tool(agent, tool_call).await? {
+                    // note: the contents of the files the call changed are
+                    // for the clients, the context keeps the result without.
+                    tool_results.push(ToolResult { files: Vec::new(), ..tool_result.clone() });
+                    let definition = context
+                        .tools
+                        .iter()
+                        .find(|definition| definition.name == tool_call.name);
+                    match failures.record(tool_call, &tool_result, definition) {
+                        ToolFailureAction::Continue => {}
+                        ToolFailureAction::Feedback(note) => feedback.push(note),
+                        ToolFailureAction::NeedsUserInput(reason) => {
+                            needs_user_input = Some(reason)
+                        }
+                    }
+                    if let Some(preview) = DiffPreview::parse(tool_call, &tool_result) {
+                        self.send(&agent.id, ChatResponse::DiffPreview(preview))
+                            .await?;
+                    }
                     self.send(&agent.id, ChatResponse::ToolCallEnd(tool_result))
                         .await?;
                 }
             }
 
+            if let Some(note) = self.verify_changes(agent, &tool_results).await? {
+                feedback.push(note);
+            }
+
+            // Only the final answer, ie: the one without tool calls, is validated
+            let output_errors = validator
+                .as_ref()
+                .filter(|_| tool_results.is_empty())
+                .and_then(|validator| validator.validate(&content).err());
+
+            answer = content.clone();
             context = context
                 .add_message(ContextMessage::assistant(content, Some(tool_calls)))
                 .add_tool_results(tool_results.clone());
 
+            if let Some(reason) = needs_user_input {
+                debug!(agent = %agent.id, reason = %reason, "Stopping agent that keeps repeating a failing tool call");
+                self.set_context(&agent.id, context).await?;
+                self.send(&agent.id, ChatResponse::NeedsUserInput(reason))
+                    .await?;
+                break;
+            }
+
+            // Sent as a user message since providers like Anthropic only accept
+            // a single leading system message
+            for note in feedback {
+                context = context.add_message(ContextMessage::user(note));
+            }
+
+            if let Some(errors) = output_errors {
+                if output_retries >= MAX_OUTPUT_RETRIES {
+                    self.set_context(&agent.id, context).await?;
+                    return Err(Error::OutputValidation(agent.id.clone(), errors.join("; ")).into());
+                }
+                output_retries += 1;
+                debug!(agent = %agent.id, errors = ?errors, "Retrying output that doesn't match the schema");
+                context = context.add_message(ContextMessage::user(
+                    OutputValidator::retry_message(&errors),
+                ));
+                self.set_context(&agent.id, context.clone()).await?;
+                continue;
+            }
+
             self.set_context(&agent.id, context.clone()).await?;
 
             if tool_results.is_empty() {
@@ -359,7 +1082,88 @@ impl<A: App> Orchestrator<A> {
 
         self.complete_turn(&agent.id).await?;
 
-        Ok(())
+        Ok((answer, tokens))
+    }
+
+    /// Masks the secrets of the context before it's sent to the provider,
+    /// unless the config disables it, and applies the content filter it sets.
+    fn redactor(&self) -> anyhow::Result<Option<Redactor>> {
+        let (redaction, key) = self
+            .system_context
+            .env
+            .as_ref()
+            .map(|env| (env.config.redaction.clone(), env.redaction_key.clone()))
+            .unwrap_or_default();
+        let redactor = Redactor::new(&redaction)?.key(&key);
+        Ok((!redactor.is_empty()).then_some(redactor))
+    }
+
+    /// Records the redactions in the audit log of the config, if any. The
+    /// turn fails rather than going on without its record.
+    async fn audit(&self, agent: &AgentId, report: &[Redacted]) -> anyhow::Result<()> {
+        let Some(path) = self
+            .system_context
+            .env
+            .as_ref()
+            .and_then(|env| env.config.redaction.audit_log.clone())
+        else {
+            return Ok(());
+        };
+        let timestamp = chrono::Utc::now();
+        let entries = report
+            .iter()
+            .map(|redacted| AuditEntry {
+                timestamp,
+                conversation: &self.chat_request.conversation_id,
+                agent,
+                redacted,
+            })
+            .collect::<Vec<_>>();
+        self.app.audit_service().redactions(&path, &entries).await
+    }
+
+    /// Images of the chat request, attached to the message of the user's task
+    /// event only.
+    fn images(&self, event: &Event) -> Vec<Image> {
+        if Self::is_user_task(event) && event.value == self.chat_request.content {
+            self.chat_request.images.clone()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Attaches the files of the repository most relevant to the user's
+    /// message, and tells the user which ones. The task goes on without them
+    /// if they can't be found.
+    async fn attach_relevant_files(
+        &self,
+        agent: &Agent,
+        query: &str,
+        content: String,
+    ) -> anyhow::Result<String> {
+        let files = match self
+            .app
+            .template_service()
+            .relevant_files(query, agent.auto_context_tokens)
+            .await
+        {
+            Ok(files) => files,
+            Err(error) => {
+                warn!(agent = %agent.id, error = %error, "Failed to find the relevant files");
+                Vec::new()
+            }
+        };
+        if files.is_empty() {
+            return Ok(content);
+        }
+        let attached = RelevantFile::render(&files);
+        self.send(&agent.id, ChatResponse::RelevantFiles(files))
+            .await?;
+        Ok(format!("{content}\n\n{attached}"))
+    }
+
+    fn is_user_task(event: &Event) -> bool {
+        [Event::USER_TASK_INIT, Event::USER_TASK_UPDATE].contains(&event.name.as_str())
     }
 
     /// Initializes the appropriate dispatch event based on whether this is the
@@ -376,6 +1180,49 @@ impl<A: App> Orchestrator<A> {
 
     pub async fn execute(&self) -> anyhow::Result<()> {
         let event = self.init_dispatch_event().await?;
-        self.dispatch(&event).await
+        match &self.chat_request.agent {
+            // note: the event is recorded but not delivered to the subscribers.
+            Some(agent) => {
+                self.get_conversation().await?.workflow.get_agent(agent)?;
+                self.insert_event(event.clone()).await?;
+                self.deliver(Some((agent.clone(), event))).await
+            }
+            None => {
+                self.dispatch(&event).await?;
+                self.deliver(None).await
+            }
+        }
     }
 }
+
+/// The content of the response's messages along with the tool calls made in
+/// them, whether complete, streamed in parts or written as XML.
+fn parse_messages(messages: &[ChatCompletionMessage]) -> anyhow::Result<Sample> {
+    let content = messages
+        .iter()
+        .flat_map(|m| m.content.iter())
+        .map(|content| content.as_str())
+        .collect::<Vec<_>>()
+        .join("");
+
+    // From Complete (incase streaming is disabled)
+    let mut tool_calls: Vec<ToolCallFull> = messages
+        .iter()
+        .flat_map(|message| message.tool_call.iter())
+        .filter_map(|message| message.as_full().cloned())
+        .collect::<Vec<_>>();
+
+    // From partial tool calls
+    tool_calls.extend(ToolCallFull::try_from_parts(
+        &messages
+            .iter()
+            .flat_map(|message| message.tool_call.iter())
+            .filter_map(|tool_call| tool_call.as_partial().cloned())
+            .collect::<Vec<_>>(),
+    )?);
+
+    // From XML
+    tool_calls.extend(ToolCallFull::try_from_xml(&content)?);
+
+    Ok(Sample { content, tool_calls })
+}
@@ -0,0 +1,116 @@
+use serde_json::Value;
+
+use crate::Error;
+
+/// Number of times an agent is asked to fix a final answer that doesn't match
+/// its output schema before giving up.
+pub const MAX_OUTPUT_RETRIES: usize = 3;
+
+/// Validates the final answers of an agent against its `output_schema`.
+pub struct OutputValidator {
+    validator: jsonschema::Validator,
+}
+
+impl OutputValidator {
+    pub fn new(schema: &Value) -> crate::Result<Self> {
+        let validator = jsonschema::validator_for(schema)
+            .map_err(|error| Error::OutputSchema(error.to_string()))?;
+        Ok(Self { validator })
+    }
+
+    /// Parses the answer, optionally wrapped in a ```json fence, and checks it
+    /// against the schema. Returns the problems found otherwise.
+    pub fn validate(&self, content: &str) -> Result<Value, Vec<String>> {
+        let value: Value = serde_json::from_str(strip_fence(content))
+            .map_err(|error| vec![format!("The answer is not valid JSON: {error}")])?;
+
+        let errors = self
+            .validator
+            .iter_errors(&value)
+            .map(|error| match error.instance_path.to_string() {
+                path if path.is_empty() => error.to_string(),
+                path => format!("{path}: {error}"),
+            })
+            .collect::<Vec<_>>();
+
+        if errors.is_empty() {
+            Ok(value)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Message asking the agent to fix its answer.
+    pub fn retry_message(errors: &[String]) -> String {
+        let errors = errors
+            .iter()
+            .map(|error| format!("- {error}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!(
+            "<output_validation_errors>\n{errors}\n</output_validation_errors>\nYour answer doesn't match the required JSON schema. Reply again with only the corrected JSON document."
+        )
+    }
+}
+
+fn strip_fence(content: &str) -> &str {
+    let content = content.trim();
+    content
+        .strip_prefix("```json")
+        .or_else(|| content.strip_prefix("```"))
+        .and_then(|content| content.strip_suffix("```"))
+        .map_or(content, str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    fn fixture() -> OutputValidator {
+        OutputValidator::new(&json!({
+            "type": "object",
+            "properties": {"summary": {"type": "string"}, "score": {"type": "integer"}},
+            "required": ["summary", "score"],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_valid_answer() {
+        let actual = fixture()
+            .validate(r#"{"summary": "ok", "score": 3}"#)
+            .unwrap();
+        let expected = json!({"summary": "ok", "score": 3});
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_fenced_answer() {
+        let actual = fixture().validate("```json\n{\"summary\": \"ok\", \"score\": 3}\n```");
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_schema_violation() {
+        let actual = fixture()
+            .validate(r#"{"summary": "ok", "score": "high"}"#)
+            .unwrap_err();
+        let expected = vec![r#"/score: "high" is not of type "integer""#.to_string()];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_malformed_answer() {
+        let actual = fixture().validate("Here is the summary").unwrap_err();
+        assert!(actual[0].starts_with("The answer is not valid JSON"));
+    }
+
+    #[test]
+    fn test_invalid_schema() {
+        let actual = OutputValidator::new(&json!({"type": 12}));
+        assert!(actual.is_err());
+    }
+}
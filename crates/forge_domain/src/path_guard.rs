@@ -0,0 +1,227 @@
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{bail, Context};
+
+/// Confines the paths of the file system tools to a set of roots: the cwd
+/// and the directories allowed by the config. Paths are resolved the way the
+/// OS would before they're checked, so neither `..` nor a symlink can reach
+/// outside of the roots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathGuard {
+    roots: Vec<PathBuf>,
+}
+
+impl PathGuard {
+    pub fn new(cwd: impl AsRef<Path>) -> Self {
+        Self { roots: Vec::new() }.allow(cwd)
+    }
+
+    /// Allows the paths under `root` as well.
+    pub fn allow(mut self, root: impl AsRef<Path>) -> Self {
+        let root = root.as_ref();
+        self.roots
+            .push(canonicalize(root).unwrap_or_else(|_| root.to_path_buf()));
+        self
+    }
+
+    /// The cwd the guard was created with, in its canonical form.
+    pub fn cwd(&self) -> &Path {
+        &self.roots[0]
+    }
+
+    /// Resolves `path` to its canonical form, failing when it's relative or
+    /// outside of the roots. The tools must only access the returned path.
+    pub fn resolve(&self, path: impl AsRef<Path>) -> anyhow::Result<PathBuf> {
+        let path = path.as_ref();
+        if !path.is_absolute() {
+            bail!("Path must be absolute. Please provide an absolute path starting with '/' (Unix) or 'C:\\' (Windows)")
+        }
+        let resolved = canonicalize(path)?;
+        if !self.roots.iter().any(|root| resolved.starts_with(root)) {
+            bail!(
+                "Access denied: {} is outside of the allowed directories: {}. Add its directory to `allowed_paths` in the config to allow it.",
+                path.display(),
+                self.roots
+                    .iter()
+                    .map(|root| root.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        }
+        Ok(resolved)
+    }
+}
+
+/// Canonicalizes a path that may not exist yet, eg: a file about to be
+/// written. Its existing ancestors are resolved by the OS, following the
+/// symlinks, and the rest is appended as is.
+fn canonicalize(path: &Path) -> anyhow::Result<PathBuf> {
+    let mut resolved = PathBuf::new();
+    let mut exists = true;
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => resolved.push(component),
+            Component::CurDir => {}
+            // note: the parent of a resolved path is the one the OS would go
+            // to, even when the path was reached through a symlink.
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::Normal(name) => {
+                resolved.push(name);
+                if exists {
+                    match std::fs::canonicalize(&resolved) {
+                        Ok(canonical) => resolved = canonical,
+                        // note: writing through a dangling symlink would
+                        // create its target, wherever it is.
+                        Err(error) if resolved.symlink_metadata().is_ok() => {
+                            return Err(error).with_context(|| {
+                                format!("Failed to resolve the symlink {}", resolved.display())
+                            })
+                        }
+                        Err(_) => exists = false,
+                    }
+                }
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fixture() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("project/src")).unwrap();
+        std::fs::create_dir_all(root.join("outside")).unwrap();
+        std::fs::write(root.join("outside/secret.txt"), "secret").unwrap();
+        (dir, root)
+    }
+
+    #[test]
+    fn test_resolve_inside() {
+        let (_dir, root) = fixture();
+        let guard = PathGuard::new(root.join("project"));
+
+        let actual = [
+            guard.resolve(root.join("project/src")).unwrap(),
+            guard.resolve(root.join("project/src/new/main.rs")).unwrap(),
+            guard
+                .resolve(root.join("project/./src/../README.md"))
+                .unwrap(),
+        ];
+        let expected = [
+            root.join("project/src"),
+            root.join("project/src/new/main.rs"),
+            root.join("project/README.md"),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_resolve_relative_path() {
+        let (_dir, root) = fixture();
+        let guard = PathGuard::new(root.join("project"));
+
+        for path in ["relative/path", "./current/path", "../parent/path"] {
+            let actual = guard.resolve(path).unwrap_err().to_string();
+            assert!(actual.starts_with("Path must be absolute"), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_resolve_parent_traversal() {
+        let (_dir, root) = fixture();
+        let guard = PathGuard::new(root.join("project"));
+
+        let actual = [
+            root.join("project/../outside/secret.txt"),
+            root.join("project/src/../../outside/secret.txt"),
+            root.join("project/missing/../../outside/new.txt"),
+        ]
+        .map(|path| guard.resolve(path).is_err());
+        assert_eq!(actual, [true, true, true]);
+    }
+
+    #[test]
+    fn test_resolve_allowed_root() {
+        let (_dir, root) = fixture();
+        let guard = PathGuard::new(root.join("project")).allow(root.join("outside"));
+
+        let actual = guard
+            .resolve(root.join("project/../outside/secret.txt"))
+            .unwrap();
+        assert_eq!(actual, root.join("outside/secret.txt"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_symlink_escape() {
+        use std::os::unix::fs::symlink;
+
+        let (_dir, root) = fixture();
+        symlink(root.join("outside"), root.join("project/link")).unwrap();
+        symlink(
+            root.join("outside/secret.txt"),
+            root.join("project/secret.txt"),
+        )
+        .unwrap();
+        let guard = PathGuard::new(root.join("project"));
+
+        let actual = [
+            root.join("project/link/secret.txt"),
+            root.join("project/link/new.txt"),
+            root.join("project/secret.txt"),
+            // note: `..` applies to the target of the symlink.
+            root.join("project/link/../project/src"),
+        ]
+        .map(|path| guard.resolve(path).is_err());
+        assert_eq!(actual, [true, true, true, false]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_symlink_inside() {
+        use std::os::unix::fs::symlink;
+
+        let (_dir, root) = fixture();
+        symlink(root.join("project/src"), root.join("project/code")).unwrap();
+        let guard = PathGuard::new(root.join("project"));
+
+        let actual = guard.resolve(root.join("project/code/lib.rs")).unwrap();
+        assert_eq!(actual, root.join("project/src/lib.rs"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_dangling_symlink() {
+        use std::os::unix::fs::symlink;
+
+        let (_dir, root) = fixture();
+        symlink(root.join("outside/new.txt"), root.join("project/new.txt")).unwrap();
+        let guard = PathGuard::new(root.join("project"));
+
+        let actual = guard.resolve(root.join("project/new.txt")).unwrap_err();
+        assert!(actual
+            .to_string()
+            .starts_with("Failed to resolve the symlink"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_root() {
+        use std::os::unix::fs::symlink;
+
+        let (_dir, root) = fixture();
+        symlink(root.join("project"), root.join("alias")).unwrap();
+        let guard = PathGuard::new(root.join("alias"));
+
+        let actual = guard.resolve(root.join("alias/src")).unwrap();
+        assert_eq!(actual, root.join("project/src"));
+    }
+}
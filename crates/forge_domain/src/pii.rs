@@ -0,0 +1,345 @@
+use std::ops::Range;
+
+use anyhow::bail;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+use crate::{Detector, PatternDetector};
+
+/// Words that start many capitalized runs without being part of a name, eg:
+/// `The`, `Error` or the days of the week.
+const STOPWORDS: &[&str] = &[
+    "A",
+    "An",
+    "And",
+    "Are",
+    "As",
+    "At",
+    "But",
+    "By",
+    "Can",
+    "Could",
+    "Did",
+    "Do",
+    "Does",
+    "Error",
+    "For",
+    "From",
+    "Friday",
+    "He",
+    "Her",
+    "His",
+    "How",
+    "I",
+    "If",
+    "In",
+    "Is",
+    "It",
+    "Its",
+    "Monday",
+    "My",
+    "No",
+    "Not",
+    "Note",
+    "Of",
+    "On",
+    "Or",
+    "Our",
+    "Saturday",
+    "See",
+    "She",
+    "Should",
+    "So",
+    "Sunday",
+    "That",
+    "The",
+    "Their",
+    "Then",
+    "There",
+    "These",
+    "They",
+    "This",
+    "Thursday",
+    "To",
+    "Tuesday",
+    "Use",
+    "Warning",
+    "We",
+    "Wednesday",
+    "What",
+    "When",
+    "Where",
+    "Which",
+    "Who",
+    "Why",
+    "Will",
+    "With",
+    "Would",
+    "Yes",
+    "You",
+    "Your",
+];
+
+/// Common first names, a run of capitalized words that starts with one of
+/// them is taken for a full name.
+const FIRST_NAMES: &[&str] = &[
+    "Aaron",
+    "Adam",
+    "Ahmed",
+    "Alex",
+    "Alice",
+    "Amanda",
+    "Amy",
+    "Ana",
+    "Andrea",
+    "Andrew",
+    "Anna",
+    "Anne",
+    "Anthony",
+    "Ben",
+    "Brian",
+    "Carlos",
+    "Carol",
+    "Charles",
+    "Chen",
+    "Chris",
+    "Christopher",
+    "Daniel",
+    "David",
+    "Deborah",
+    "Diana",
+    "Donald",
+    "Elena",
+    "Elizabeth",
+    "Emily",
+    "Emma",
+    "Eric",
+    "Fatima",
+    "George",
+    "Hannah",
+    "Hiroshi",
+    "Ivan",
+    "Jack",
+    "James",
+    "Jane",
+    "Jason",
+    "Jennifer",
+    "Jessica",
+    "John",
+    "Jose",
+    "Joseph",
+    "Juan",
+    "Julia",
+    "Karen",
+    "Kevin",
+    "Laura",
+    "Li",
+    "Linda",
+    "Lisa",
+    "Lucas",
+    "Luis",
+    "Maria",
+    "Mark",
+    "Mary",
+    "Matthew",
+    "Michael",
+    "Michelle",
+    "Mohammed",
+    "Nancy",
+    "Olga",
+    "Olivia",
+    "Omar",
+    "Patricia",
+    "Paul",
+    "Peter",
+    "Priya",
+    "Rahul",
+    "Raj",
+    "Richard",
+    "Robert",
+    "Ryan",
+    "Sam",
+    "Sarah",
+    "Sandra",
+    "Sofia",
+    "Steven",
+    "Susan",
+    "Thomas",
+    "Tim",
+    "Wei",
+    "William",
+    "Yuki",
+];
+
+/// Titles that precede a name, eg: `Dr Smith`.
+const HONORIFICS: &[&str] = &["Mr", "Mrs", "Ms", "Miss", "Dr", "Prof"];
+
+/// Text that precedes a name in commits, emails or docs, eg: `Author: `.
+const CUES: &[&str] = &[
+    "author:",
+    "signed-off-by:",
+    "co-authored-by:",
+    "reviewed-by:",
+    "from:",
+    "to:",
+    "cc:",
+    "by",
+];
+
+/// Score from which a run of capitalized words is taken for a name.
+const MIN_NAME_SCORE: u32 = 2;
+
+/// The kinds of personal or internal data the content filter can find.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Display, EnumString,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum PiiKind {
+    Email,
+    /// The names of people, found by heuristics so some are missed
+    Name,
+    /// The hosts of the internal domains of the config, eg:
+    /// `db1.corp.example.com`
+    Hostname,
+}
+
+impl PiiKind {
+    /// The detector of the kind, where the hostnames are the ones of the
+    /// internal domains.
+    pub fn detector(&self, internal_domains: &[String]) -> anyhow::Result<Box<dyn Detector>> {
+        match self {
+            Self::Email => Ok(Box::new(PatternDetector::new(
+                self,
+                r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b",
+            )?)),
+            Self::Name => Ok(Box::new(NameDetector)),
+            Self::Hostname => {
+                if internal_domains.is_empty() {
+                    bail!("The hostname filter requires `internal_domains` to be set");
+                }
+                let domains = internal_domains
+                    .iter()
+                    .map(|domain| regex::escape(domain.trim_start_matches('.')))
+                    .collect::<Vec<_>>()
+                    .join("|");
+                let pattern =
+                    format!(r"(?i)\b(?:[a-z0-9](?:[a-z0-9-]*[a-z0-9])?\.)*(?:{domains})\b");
+                Ok(Box::new(PatternDetector::new(self, &pattern)?))
+            }
+        }
+    }
+}
+
+/// Finds the names of people with a few weighted cues rather than a model,
+/// eg: a known first name, an honorific, an email that follows or a commit
+/// trailer that precedes.
+pub struct NameDetector;
+
+impl NameDetector {
+    /// How much the context suggests that the run of words is a name.
+    fn score(text: &str, range: &Range<usize>, words: &[&str]) -> u32 {
+        let before = text[..range.start].trim_end();
+        let after = &text[range.end..];
+        let previous = before
+            .rsplit(|c: char| c.is_whitespace())
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('.');
+
+        let mut score = 0;
+        if words.len() >= 2 && FIRST_NAMES.contains(&words[0]) {
+            score += 2;
+        }
+        if HONORIFICS.contains(&previous) {
+            score += 2;
+        }
+        if after.starts_with(" <")
+            && after
+                .split('>')
+                .next()
+                .is_some_and(|email| email.contains('@'))
+        {
+            score += 2;
+        }
+        if words.len() >= 2 && CUES.contains(&previous.to_lowercase().as_str()) {
+            score += 2;
+        }
+        score
+    }
+}
+
+impl Detector for NameDetector {
+    fn name(&self) -> String {
+        PiiKind::Name.to_string()
+    }
+
+    fn detect(&self, text: &str) -> Vec<Range<usize>> {
+        let regex = Regex::new(r"\b[A-Z][a-z]+(?: [A-Z][a-z]+){0,2}\b").unwrap();
+        regex
+            .find_iter(text)
+            .filter_map(|found| {
+                let mut words = found.as_str().split(' ').collect::<Vec<_>>();
+                let mut range = found.range();
+                // note: the honorific is left out of the name, and scores it.
+                if words.len() > 1 && HONORIFICS.contains(&words[0]) {
+                    range.start += words[0].len() + 1;
+                    words.remove(0);
+                }
+                if words.iter().any(|word| STOPWORDS.contains(word)) {
+                    return None;
+                }
+                (Self::score(text, &range, &words) >= MIN_NAME_SCORE).then_some(range)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn found(kind: PiiKind, text: &str) -> Vec<&str> {
+        let domains = vec!["corp.example.com".to_string()];
+        kind.detector(&domains)
+            .unwrap()
+            .detect(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    #[test]
+    fn test_emails() {
+        let actual = found(
+            PiiKind::Email,
+            "Contact jane.doe+ops@example.org, not user@localhost",
+        );
+        assert_eq!(actual, vec!["jane.doe+ops@example.org"]);
+    }
+
+    #[test]
+    fn test_hostnames() {
+        let actual = found(
+            PiiKind::Hostname,
+            "ssh db1.eu.corp.example.com, then curl https://corp.example.com/api and example.com",
+        );
+        assert_eq!(actual, vec!["db1.eu.corp.example.com", "corp.example.com"]);
+        assert!(PiiKind::Hostname.detector(&[]).is_err());
+    }
+
+    #[test]
+    fn test_names() {
+        let fixture = "Author: Jordan Kowalski <jk@example.org>\n\
+                       Reviewed with Maria Gonzalez and Dr Okafor.\n\
+                       The Parser returns an Error when Config Loader fails on Monday.\n\
+                       Signed-off-by: Priya";
+
+        let actual = found(PiiKind::Name, fixture);
+
+        assert_eq!(actual, vec!["Jordan Kowalski", "Maria Gonzalez", "Okafor"]);
+    }
+}
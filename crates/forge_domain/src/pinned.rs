@@ -0,0 +1,123 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use crate::{ContentMessage, Context, ContextMessage, Role};
+
+const TAG: &str = "pinned_files";
+
+/// Files pinned with `/pin`, whose latest content is given to the agents at
+/// the start of every turn instead of relying on the model to read them again.
+/// As the message is added back every turn, the files survive compaction.
+pub struct PinnedFiles<'a>(&'a BTreeSet<PathBuf>);
+
+impl<'a> PinnedFiles<'a> {
+    pub fn new(files: &'a BTreeSet<PathBuf>) -> Self {
+        Self(files)
+    }
+
+    /// Reads the files into the message given to the agents, `None` when no
+    /// file is pinned. A file that can't be read is reported in its place.
+    pub async fn render(&self) -> Option<String> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let mut content = format!(
+            "<{TAG}>\nThe current content of the files pinned by the user, which supersedes any earlier version of them:\n"
+        );
+        for path in self.0 {
+            let path_display = path.display();
+            match tokio::fs::read_to_string(path).await {
+                Ok(text) => content.push_str(&format!(
+                    "<file path=\"{path_display}\">\n{text}\n</file>\n"
+                )),
+                Err(error) => content.push_str(&format!(
+                    "<file path=\"{path_display}\" error=\"{error}\"></file>\n"
+                )),
+            }
+        }
+        content.push_str(&format!("</{TAG}>"));
+        Some(content)
+    }
+
+    fn is_pinned(message: &ContextMessage) -> bool {
+        matches!(
+            message,
+            ContextMessage::ContentMessage(ContentMessage { role: Role::User, content, .. })
+                if content.starts_with(&format!("<{TAG}>"))
+        )
+    }
+}
+
+impl Context {
+    /// Replaces the message of the pinned files with the given one, so the
+    /// context only holds their latest content.
+    pub fn pin_files(mut self, pinned: Option<String>) -> Self {
+        self.messages
+            .retain(|message| !PinnedFiles::is_pinned(message));
+        match pinned {
+            Some(pinned) => self.add_message(ContextMessage::user(pinned)),
+            None => self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_render() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.md");
+        std::fs::write(&path, "Use tabs").unwrap();
+        let missing = dir.path().join("missing.md");
+        let files = BTreeSet::from([path.clone(), missing.clone()]);
+
+        let actual = PinnedFiles::new(&files).render().await.unwrap();
+
+        assert!(actual.starts_with("<pinned_files>\n"));
+        assert!(actual.contains(&format!(
+            "<file path=\"{}\">\nUse tabs\n</file>\n",
+            path.display()
+        )));
+        assert!(actual.contains(&format!("<file path=\"{}\" error=", missing.display())));
+        assert!(actual.ends_with("</pinned_files>"));
+    }
+
+    #[tokio::test]
+    async fn test_render_nothing_pinned() {
+        let actual = PinnedFiles::new(&BTreeSet::new()).render().await;
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn test_pin_files_replaces_previous_content() {
+        let context = Context::default()
+            .add_message(ContextMessage::user("<pinned_files>\nold\n</pinned_files>"))
+            .add_message(ContextMessage::user("Fix the bug"))
+            .add_message(ContextMessage::assistant("Done", None));
+
+        let actual = context
+            .pin_files(Some("<pinned_files>\nnew\n</pinned_files>".to_string()))
+            .messages;
+
+        let expected = vec![
+            ContextMessage::user("Fix the bug"),
+            ContextMessage::assistant("Done", None),
+            ContextMessage::user("<pinned_files>\nnew\n</pinned_files>"),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_pin_files_unpinned() {
+        let context = Context::default()
+            .add_message(ContextMessage::user("<pinned_files>\nold\n</pinned_files>"));
+
+        let actual = context.pin_files(None).messages;
+
+        assert_eq!(actual, Vec::new());
+    }
+}
@@ -0,0 +1,88 @@
+use std::fmt::{self, Display};
+
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+
+use crate::{NamedTool, ToolCallFull, ToolDefinition, ToolName};
+
+/// Plan an agent in the planning mode submits for the user to approve before
+/// any change is made.
+#[derive(Debug, JsonSchema, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Plan {
+    /// What the plan achieves, in a sentence or two
+    pub summary: String,
+    /// Steps to carry the plan out, in order
+    pub steps: Vec<PlanStep>,
+}
+
+#[derive(Debug, JsonSchema, Deserialize, Serialize, Clone, PartialEq)]
+pub struct PlanStep {
+    /// What is done in the step
+    pub description: String,
+    /// Paths of the files the step creates, changes or removes
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+impl NamedTool for Plan {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_plan_submit")
+    }
+}
+
+impl Plan {
+    pub fn tool_definition() -> ToolDefinition {
+        ToolDefinition {
+            name: Self::tool_name(),
+            description: "Submits the plan of the task for the user to approve, once the code \
+                          it touches has been investigated. Only read-only tools are available \
+                          until the plan is approved, so stop after submitting it."
+                .to_string(),
+            input_schema: schema_for!(Self),
+            output_schema: None,
+        }
+    }
+
+    pub fn parse(tool_call: &ToolCallFull) -> Option<Self> {
+        if tool_call.name != Self::tool_name() {
+            return None;
+        }
+        serde_json::from_value(tool_call.arguments.clone()).ok()
+    }
+}
+
+impl Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.summary)?;
+        for (i, step) in self.steps.iter().enumerate() {
+            write!(f, "\n{}. {}", i + 1, step.description)?;
+            if !step.files.is_empty() {
+                write!(f, " ({})", step.files.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let tool_call = ToolCallFull::new(Plan::tool_name()).arguments(json!({
+            "summary": "Rename the config loader",
+            "steps": [
+                {"description": "Rename the function", "files": ["src/config.rs", "src/main.rs"]},
+                {"description": "Run the tests"}
+            ]
+        }));
+
+        let actual = Plan::parse(&tool_call).unwrap().to_string();
+        let expected = "Rename the config loader\n1. Rename the function (src/config.rs, src/main.rs)\n2. Run the tests";
+        assert_eq!(actual, expected);
+    }
+}
@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 const OPEN_ROUTER_URL: &str = "https://api.openrouter.io/v1/";
 const OPENAI_URL: &str = "https://api.openai.com/v1/";
 const ANTHROPIC_URL: &str = "https://api.anthropic.com/v1/";
+const GEMINI_URL: &str = "https://generativelanguage.googleapis.com/v1beta/";
+const BEDROCK_DEFAULT_REGION: &str = "us-east-1";
 
 /// Providers that can be used.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -12,6 +14,12 @@ pub enum Provider {
     OpenRouter,
     OpenAI,
     Anthropic,
+    /// Azure OpenAI resource, whose endpoint is read from
+    /// `AZURE_OPENAI_ENDPOINT`
+    AzureOpenAI,
+    Gemini,
+    /// AWS Bedrock, in the region read from `AWS_REGION`
+    Bedrock,
 }
 
 impl Display for Provider {
@@ -20,6 +28,9 @@ impl Display for Provider {
             Provider::OpenRouter => write!(f, "OpenRouter"),
             Provider::OpenAI => write!(f, "OpenAI"),
             Provider::Anthropic => write!(f, "Anthropic"),
+            Provider::AzureOpenAI => write!(f, "Azure OpenAI"),
+            Provider::Gemini => write!(f, "Gemini"),
+            Provider::Bedrock => write!(f, "AWS Bedrock"),
         }
     }
 }
@@ -32,25 +43,46 @@ impl Provider {
             std::env::var("OPENROUTER_API_KEY"),
             std::env::var("OPENAI_API_KEY"),
             std::env::var("ANTHROPIC_API_KEY"),
+            std::env::var("AZURE_OPENAI_ENDPOINT"),
+            std::env::var("GEMINI_API_KEY"),
+            std::env::var("AWS_ACCESS_KEY_ID"),
         ) {
-            (Ok(_), _, _, _) => {
+            (Ok(_), _, _, _, _, _, _) => {
                 // note: if we're using FORGE_KEY, we need FORGE_PROVIDER_URL to be set.
                 let provider_url = std::env::var("FORGE_PROVIDER_URL").ok()?;
                 Self::from_url(&provider_url)
             }
-            (_, Ok(_), _, _) => Some(Self::OpenRouter),
-            (_, _, Ok(_), _) => Some(Self::OpenAI),
-            (_, _, _, Ok(_)) => Some(Self::Anthropic),
-            (Err(_), Err(_), Err(_), Err(_)) => None,
+            (_, Ok(_), _, _, _, _, _) => Some(Self::OpenRouter),
+            (_, _, Ok(_), _, _, _, _) => Some(Self::OpenAI),
+            (_, _, _, Ok(_), _, _, _) => Some(Self::Anthropic),
+            (_, _, _, _, Ok(_), _, _) => Some(Self::AzureOpenAI),
+            (_, _, _, _, _, Ok(_), _) => Some(Self::Gemini),
+            // note: AWS credentials are often set for other tools, so Bedrock is
+            // only picked when no other provider is configured.
+            (_, _, _, _, _, _, Ok(_)) => Some(Self::Bedrock),
+            (Err(_), Err(_), Err(_), Err(_), Err(_), Err(_), Err(_)) => None,
         }
     }
 
     /// converts the provider to it's base URL
-    pub fn to_base_url(&self) -> &str {
+    pub fn to_base_url(&self) -> String {
         match self {
-            Provider::OpenRouter => OPEN_ROUTER_URL,
-            Provider::OpenAI => OPENAI_URL,
-            Provider::Anthropic => ANTHROPIC_URL,
+            Provider::OpenRouter => OPEN_ROUTER_URL.to_string(),
+            Provider::OpenAI => OPENAI_URL.to_string(),
+            Provider::Anthropic => ANTHROPIC_URL.to_string(),
+            Provider::Gemini => GEMINI_URL.to_string(),
+            // note: every Azure resource has its own endpoint.
+            Provider::AzureOpenAI => std::env::var("FORGE_PROVIDER_URL")
+                .ok()
+                .filter(|url| is_azure_endpoint(url))
+                .or_else(|| std::env::var("AZURE_OPENAI_ENDPOINT").ok())
+                .unwrap_or_default(),
+            Provider::Bedrock => {
+                let region = std::env::var("AWS_REGION")
+                    .or_else(|_| std::env::var("AWS_DEFAULT_REGION"))
+                    .unwrap_or_else(|_| BEDROCK_DEFAULT_REGION.to_string());
+                format!("https://bedrock-runtime.{region}.amazonaws.com/")
+            }
         }
     }
 
@@ -60,7 +92,48 @@ impl Provider {
             OPENAI_URL => Some(Self::OpenAI),
             OPEN_ROUTER_URL => Some(Self::OpenRouter),
             ANTHROPIC_URL => Some(Self::Anthropic),
+            GEMINI_URL => Some(Self::Gemini),
+            url if is_azure_endpoint(url) => Some(Self::AzureOpenAI),
+            url if bedrock_region(url).is_some() => Some(Self::Bedrock),
             _ => None,
         }
     }
+
+    /// Name of the environment variable holding the key of the provider.
+    pub fn key_name(&self) -> &'static str {
+        match self {
+            Provider::OpenRouter => "OPENROUTER_API_KEY",
+            Provider::OpenAI => "OPENAI_API_KEY",
+            Provider::Anthropic => "ANTHROPIC_API_KEY",
+            Provider::AzureOpenAI => "AZURE_OPENAI_KEY",
+            Provider::Gemini => "GEMINI_API_KEY",
+            Provider::Bedrock => "AWS_SECRET_ACCESS_KEY",
+        }
+    }
+}
+
+/// Checks if the URL is the endpoint of an Azure OpenAI resource, eg:
+/// `https://my-resource.openai.azure.com/`.
+fn is_azure_endpoint(url: &str) -> bool {
+    let host = host(url);
+    [".openai.azure.com", ".cognitiveservices.azure.com"]
+        .iter()
+        .any(|suffix| host.ends_with(suffix))
+}
+
+/// Region of a Bedrock runtime endpoint, eg: `us-east-1` for
+/// `https://bedrock-runtime.us-east-1.amazonaws.com/`.
+pub fn bedrock_region(url: &str) -> Option<&str> {
+    host(url)
+        .strip_prefix("bedrock-runtime.")?
+        .strip_suffix(".amazonaws.com")
+        .filter(|region| !region.is_empty() && !region.contains('.'))
+}
+
+fn host(url: &str) -> &str {
+    url.split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split(['/', ':', '?'])
+        .next()
+        .unwrap_or_default()
 }
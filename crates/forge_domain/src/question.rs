@@ -0,0 +1,72 @@
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+
+use crate::{NamedTool, ToolCallFull, ToolDefinition, ToolName};
+
+/// Question an agent asks the user in the middle of a task to clarify it. The
+/// agent waits for the answer, which it gets as the result of the tool call.
+#[derive(Debug, JsonSchema, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Question {
+    /// The question to ask the user
+    pub question: String,
+    /// Answers the user can choose from, leave it empty to let them answer
+    /// with free text
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+impl NamedTool for Question {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_ask_followup_question")
+    }
+}
+
+impl Question {
+    pub fn tool_definition() -> ToolDefinition {
+        ToolDefinition {
+            name: Self::tool_name(),
+            description: "Asks the user a question and waits for their answer. Use it only when \
+                          the task can't be carried on without clarification, eg: it's \
+                          ambiguous or a choice is up to the user. Provide options when the \
+                          answer is one of a few choices."
+                .to_string(),
+            input_schema: schema_for!(Self),
+            output_schema: None,
+        }
+    }
+
+    pub fn parse(tool_call: &ToolCallFull) -> Option<Self> {
+        if tool_call.name != Self::tool_name() {
+            return None;
+        }
+        serde_json::from_value(tool_call.arguments.clone()).ok()
+    }
+
+    /// The answer to pass to the agent for what the user typed: the option
+    /// with that number, or the text itself.
+    pub fn answer(&self, text: &str) -> String {
+        let text = text.trim();
+        text.parse::<usize>()
+            .ok()
+            .and_then(|number| self.options.get(number.checked_sub(1)?))
+            .map_or_else(|| text.to_string(), Clone::clone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_answer() {
+        let question = Question {
+            question: "Which database?".to_string(),
+            options: vec!["Postgres".to_string(), "SQLite".to_string()],
+        };
+
+        let actual = ["2", " sqlite ", "3", "0"].map(|text| question.answer(text));
+        assert_eq!(actual, ["SQLite", "sqlite", "3", "0"]);
+    }
+}
@@ -0,0 +1,153 @@
+use std::time::{Duration, Instant};
+
+use derive_setters::Setters;
+use serde::{Deserialize, Serialize};
+
+/// Limits of the requests sent to the provider, unlimited when not set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, Setters)]
+#[serde(rename_all = "camelCase")]
+#[setters(strip_option)]
+pub struct RateLimit {
+    pub requests_per_minute: Option<u32>,
+    /// Prompt tokens sent per minute
+    pub tokens_per_minute: Option<u32>,
+}
+
+/// Token buckets shared by all the requests made to the provider, so that
+/// agents running in parallel queue up instead of being rejected by the
+/// provider with a 429.
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        let now = Instant::now();
+        Self {
+            requests: limit
+                .requests_per_minute
+                .filter(|limit| *limit > 0)
+                .map(|limit| Bucket::new(limit, now)),
+            tokens: limit
+                .tokens_per_minute
+                .filter(|limit| *limit > 0)
+                .map(|limit| Bucket::new(limit, now)),
+        }
+    }
+
+    /// Reserves the capacity for a request with about `tokens` prompt tokens,
+    /// returning how long to wait before sending it.
+    pub fn reserve(&mut self, tokens: u64) -> Duration {
+        self.reserve_at(tokens, Instant::now())
+    }
+
+    fn reserve_at(&mut self, tokens: u64, now: Instant) -> Duration {
+        let requests = self
+            .requests
+            .as_mut()
+            .map(|bucket| bucket.take(1.0, now))
+            .unwrap_or_default();
+        let tokens = self
+            .tokens
+            .as_mut()
+            .map(|bucket| bucket.take(tokens as f64, now))
+            .unwrap_or_default();
+        requests.max(tokens)
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Refilled over a minute
+    capacity: f64,
+    /// Goes below zero when requests are queued
+    available: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(per_minute: u32, now: Instant) -> Self {
+        let capacity = per_minute as f64;
+        Self { capacity, available: capacity, updated: now }
+    }
+
+    /// Takes `amount` out of the bucket, returning the time until it's
+    /// refilled back to zero.
+    fn take(&mut self, amount: f64, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.available = (self.available + elapsed * self.capacity / 60.0).min(self.capacity);
+        self.updated = self.updated.max(now);
+
+        // note: a request larger than the bucket would never fit, so it waits for
+        // the bucket to be full instead.
+        self.available -= amount.min(self.capacity);
+        if self.available >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.available * 60.0 / self.capacity)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_unlimited() {
+        let mut limiter = RateLimiter::new(RateLimit::default());
+        let now = Instant::now();
+
+        let actual = (0..100)
+            .map(|_| limiter.reserve_at(1_000_000, now))
+            .max()
+            .unwrap();
+        assert_eq!(actual, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_requests_per_minute() {
+        let mut limiter = RateLimiter::new(RateLimit::default().requests_per_minute(2));
+        let now = Instant::now();
+
+        let actual = [
+            limiter.reserve_at(0, now),
+            limiter.reserve_at(0, now),
+            limiter.reserve_at(0, now),
+            limiter.reserve_at(0, now),
+        ];
+        let expected = [
+            Duration::ZERO,
+            Duration::ZERO,
+            Duration::from_secs(30),
+            Duration::from_secs(60),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_tokens_per_minute_refill() {
+        let mut limiter = RateLimiter::new(RateLimit::default().tokens_per_minute(6000));
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve_at(6000, now), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(1000, now), Duration::from_secs(10));
+
+        // The queued request has been sent by then, and the bucket refilled since
+        let later = now + Duration::from_secs(40);
+        assert_eq!(limiter.reserve_at(3000, later), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_request_larger_than_limit() {
+        let mut limiter = RateLimiter::new(RateLimit::default().tokens_per_minute(1000));
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve_at(5000, now), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(5000, now), Duration::from_secs(60));
+    }
+}
@@ -0,0 +1,47 @@
+/// Masks a secret so that it can be displayed, keeping only enough of it to
+/// tell keys apart, eg: `sk-...3f9a`.
+pub fn mask(secret: &str) -> String {
+    let chars = secret.chars().collect::<Vec<_>>();
+    // note: short secrets would be mostly revealed by their prefix and suffix.
+    if chars.len() < 12 {
+        return "****".to_string();
+    }
+    let prefix = chars[..3].iter().collect::<String>();
+    let suffix = chars[chars.len() - 4..].iter().collect::<String>();
+    format!("{prefix}...{suffix}")
+}
+
+/// Replaces every occurrence of the secrets in `text` with their masked form.
+pub fn redact<'a>(text: &str, secrets: impl IntoIterator<Item = &'a str>) -> String {
+    secrets
+        .into_iter()
+        .filter(|secret| !secret.is_empty())
+        .fold(text.to_string(), |text, secret| {
+            text.replace(secret, &mask(secret))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_mask() {
+        let actual = [mask("sk-or-v1-0123456789abcdef"), mask("secret")];
+        let expected = ["sk-...cdef".to_string(), "****".to_string()];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_redact() {
+        let actual = redact(
+            "Request to https://api.example.com/?key=sk-or-v1-0123456789abcdef failed: invalid key sk-or-v1-0123456789abcdef",
+            ["sk-or-v1-0123456789abcdef", ""],
+        );
+        let expected =
+            "Request to https://api.example.com/?key=sk-...cdef failed: invalid key sk-...cdef";
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,180 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Context, ContextMessage};
+
+/// Lines of a file, numbered from 1, the end included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Edit of the selected lines of a file following an instruction, made with
+/// a single request to the model rather than by the agents of the workflow,
+/// eg: for the inline edits of an editor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectionEdit {
+    pub path: PathBuf,
+    pub range: LineRange,
+    pub instruction: String,
+}
+
+/// A file as edited, which is left for the caller to write.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EditedFile {
+    pub path: PathBuf,
+    /// The whole content of the file after the edit
+    pub content: String,
+    /// Unified diff of the edit
+    pub diff: String,
+}
+
+const SYSTEM_PROMPT: &str = "You edit the selected lines of a file following an instruction. Reply with the new content of the selection between <replacement> and </replacement>, without any explanation. Keep the indentation and the style of the surrounding code, and leave out the lines around the selection.";
+
+impl SelectionEdit {
+    /// Lines before and after the selection sent along with it.
+    pub const SURROUNDING_LINES: usize = 50;
+
+    /// The request for the edit of the file's `content`.
+    pub fn context(&self, content: &str) -> anyhow::Result<Context> {
+        let (before, selection, after) = self.split(content)?;
+        let before = &before[before.len().saturating_sub(Self::SURROUNDING_LINES)..];
+        let after = &after[..after.len().min(Self::SURROUNDING_LINES)];
+        let message = format!(
+            "<file path=\"{}\">\n{}<selection>\n{}</selection>\n{}</file>\n\n<instruction>{}</instruction>",
+            self.path.display(),
+            before.concat(),
+            with_newline(&selection.concat()),
+            after.concat(),
+            self.instruction.trim()
+        );
+        Ok(Context::default()
+            .add_message(ContextMessage::system(SYSTEM_PROMPT))
+            .add_message(ContextMessage::user(message)))
+    }
+
+    /// The file with the selection replaced by the one of the `response`.
+    pub fn apply(&self, content: &str, response: &str) -> anyhow::Result<EditedFile> {
+        let (before, selection, after) = self.split(content)?;
+        let mut replacement = replacement(response).to_string();
+        // note: the selection keeps its line ending, unless it's removed or
+        // ends the file without one.
+        if !replacement.is_empty() && selection.concat().ends_with('\n') {
+            replacement = with_newline(&replacement);
+        }
+        let edited = format!("{}{replacement}{}", before.concat(), after.concat());
+        let path = self.path.display().to_string();
+        let diff = similar::TextDiff::from_lines(content, &edited)
+            .unified_diff()
+            .context_radius(3)
+            .header(&path, &path)
+            .to_string();
+        Ok(EditedFile { path: self.path.clone(), content: edited, diff })
+    }
+
+    /// The lines before, in and after the selection, with their line endings.
+    fn split<'a>(
+        &self,
+        content: &'a str,
+    ) -> anyhow::Result<(Vec<&'a str>, Vec<&'a str>, Vec<&'a str>)> {
+        let mut lines = content.split_inclusive('\n').collect::<Vec<_>>();
+        let LineRange { start, end } = self.range;
+        anyhow::ensure!(
+            start >= 1 && start <= end && end <= lines.len(),
+            "The lines {start} to {end} aren't within the {} lines of {}",
+            lines.len(),
+            self.path.display()
+        );
+        let after = lines.split_off(end);
+        let selection = lines.split_off(start - 1);
+        Ok((lines, selection, after))
+    }
+}
+
+/// The content between the replacement tags of the response, or the whole
+/// response without the code fence the model wrapped it in.
+fn replacement(response: &str) -> &str {
+    let inner = match response.split_once("<replacement>") {
+        Some((_, rest)) => rest.split("</replacement>").next().unwrap_or(rest),
+        None => {
+            // note: the indentation of the first line is kept.
+            let trimmed = response.trim_start_matches(['\r', '\n']).trim_end();
+            match trimmed.trim_start().strip_prefix("```") {
+                Some(rest) => rest
+                    .split_once('\n')
+                    .map_or("", |(_, code)| code)
+                    .trim_end()
+                    .trim_end_matches("```"),
+                None => trimmed,
+            }
+        }
+    };
+    inner.strip_prefix('\n').unwrap_or(inner).trim_end()
+}
+
+fn with_newline(text: &str) -> String {
+    if text.is_empty() || text.ends_with('\n') {
+        text.to_string()
+    } else {
+        format!("{text}\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const CONTENT: &str = "fn main() {\n    let cat = \"Juniper\";\n    println!(\"{cat}\");\n}\n";
+
+    fn edit(start: usize, end: usize) -> SelectionEdit {
+        SelectionEdit {
+            path: PathBuf::from("src/main.rs"),
+            range: LineRange { start, end },
+            instruction: "Rename the cat to Pepper".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_context() {
+        let actual = edit(2, 3).context(CONTENT).unwrap();
+        let expected = "<file path=\"src/main.rs\">\nfn main() {\n<selection>\n    let cat = \"Juniper\";\n    println!(\"{cat}\");\n</selection>\n}\n</file>\n\n<instruction>Rename the cat to Pepper</instruction>";
+        assert_eq!(actual.messages[1].content(), expected);
+
+        let actual = edit(3, 5).context(CONTENT).unwrap_err().to_string();
+        assert_eq!(
+            actual,
+            "The lines 3 to 5 aren't within the 4 lines of src/main.rs"
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let responses = [
+            "<replacement>\n    let pet = \"Pepper\";\n    println!(\"{pet}\");\n</replacement>",
+            "```rust\n    let pet = \"Pepper\";\n    println!(\"{pet}\");\n```",
+        ];
+        for response in responses {
+            let actual = edit(2, 3).apply(CONTENT, response).unwrap();
+            assert_eq!(
+                actual.content,
+                "fn main() {\n    let pet = \"Pepper\";\n    println!(\"{pet}\");\n}\n"
+            );
+            assert!(actual
+                .diff
+                .starts_with("--- src/main.rs\n+++ src/main.rs\n"));
+            assert!(actual.diff.contains("\n+    let pet = \"Pepper\";\n"));
+        }
+
+        let actual = edit(3, 3)
+            .apply(CONTENT, "<replacement></replacement>")
+            .unwrap();
+        assert_eq!(
+            actual.content,
+            "fn main() {\n    let cat = \"Juniper\";\n}\n"
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+
+use crate::{Agent, AgentId, Event, NamedTool, Question, ToolCallFull, ToolDefinition, ToolName};
+
+/// Goal an agent hands to a child agent, eg: to explore the code without the
+/// long file reads and search results ending up in its own context. The child
+/// runs to completion and only its summary is returned to the agent.
+#[derive(Debug, JsonSchema, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SpawnAgent {
+    /// What the child agent should find out or do, with all the details it
+    /// needs since it doesn't see the conversation
+    pub goal: String,
+    /// Tools the child agent can use, among yours. Leave it empty to give it
+    /// your read-only tools
+    #[serde(default)]
+    pub tools: Vec<String>,
+}
+
+impl NamedTool for SpawnAgent {
+    fn tool_name() -> ToolName {
+        ToolName::new("tool_forge_agent_spawn")
+    }
+}
+
+impl SpawnAgent {
+    /// Name of the event the child agent is started with.
+    pub const EVENT: &'static str = "spawn_agent";
+
+    pub fn tool_definition() -> ToolDefinition {
+        ToolDefinition {
+            name: Self::tool_name(),
+            description: "Spawns a child agent to carry out a scoped goal, eg: finding where \
+                          something is implemented or how a module works, and returns the \
+                          summary of its result. Use it for work that would otherwise fill \
+                          your context with file reads and search results. The child doesn't \
+                          see the conversation, so describe the goal in full."
+                .to_string(),
+            input_schema: schema_for!(Self),
+            output_schema: None,
+        }
+    }
+
+    pub fn parse(tool_call: &ToolCallFull) -> Option<Self> {
+        if tool_call.name != Self::tool_name() {
+            return None;
+        }
+        serde_json::from_value(tool_call.arguments.clone()).ok()
+    }
+
+    /// The child agent of `parent`, with the same model and system prompt but
+    /// only the requested tools it has. The child can neither spawn agents
+    /// itself, dispatch events nor ask the user questions, starts afresh
+    /// every time and samples a single completion per request.
+    pub fn agent(&self, parent: &Agent) -> Agent {
+        let excluded = [Self::tool_name(), Event::tool_name(), Question::tool_name()];
+        let tools = parent
+            .tools
+            .iter()
+            .filter(|tool| !excluded.contains(tool))
+            .filter(|tool| {
+                if self.tools.is_empty() {
+                    tool.is_read_only()
+                } else {
+                    self.tools.iter().any(|name| name == tool.as_str())
+                }
+            })
+            .cloned()
+            .collect();
+
+        Agent {
+            id: AgentId::new(format!("{}.spawn", parent.id.as_str())),
+            tools,
+            ephemeral: true,
+            user_prompt: None,
+            subscribe: Vec::new(),
+            transforms: Vec::new(),
+            max_turns: None,
+            auto_context: false,
+            output_schema: None,
+            best_of: None,
+            ..parent.clone()
+        }
+    }
+
+    /// The event the child agent is started with, which asks it to end with
+    /// the summary returned to the parent.
+    pub fn event(&self) -> Event {
+        Event::new(
+            Self::EVENT,
+            format!(
+                "{}\n\nWhen you're done, reply with a concise summary of what you found or did, \
+                 with the paths and names that matter. It's the only part of your work that's \
+                 passed on, so don't leave out anything needed to act on it.",
+                self.goal
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_agent() {
+        let parent: Agent = serde_json::from_value(serde_json::json!({
+            "id": "engineer",
+            "model": "anthropic/claude-3.7-sonnet",
+            "tools": [
+                "tool_forge_fs_read",
+                "tool_forge_fs_search",
+                "tool_forge_fs_create",
+                "tool_forge_agent_spawn",
+                "tool_forge_ask_followup_question"
+            ],
+            "subscribe": ["user_task_init"]
+        }))
+        .unwrap();
+
+        let spawn = SpawnAgent { goal: "Find the parser".to_string(), tools: Vec::new() };
+        let actual = spawn.agent(&parent);
+        assert_eq!(actual.id, AgentId::new("engineer.spawn"));
+        assert!(actual.ephemeral);
+        assert_eq!(actual.subscribe, Vec::<String>::new());
+        assert_eq!(
+            actual.tools,
+            ["tool_forge_fs_read", "tool_forge_fs_search"].map(ToolName::new)
+        );
+
+        let spawn = SpawnAgent {
+            goal: "Fix the parser".to_string(),
+            tools: [
+                "tool_forge_fs_create",
+                "tool_forge_process_shell",
+                "tool_forge_agent_spawn",
+            ]
+            .map(String::from)
+            .to_vec(),
+        };
+        let actual = spawn.agent(&parent).tools;
+        assert_eq!(actual, vec![ToolName::new("tool_forge_fs_create")]);
+    }
+}
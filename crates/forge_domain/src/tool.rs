@@ -1,7 +1,14 @@
 use schemars::JsonSchema;
 use serde_json::Value;
 
-use crate::{ExecutableTool, NamedTool, ToolDefinition, ToolDescription};
+use crate::{ConversationId, ExecutableTool, NamedTool, ToolDefinition, ToolDescription};
+
+tokio::task_local! {
+    /// The conversation the tool being called works for, set by the
+    /// orchestrator for the tools that keep state between calls, eg: the shell
+    /// sessions, so that conversations don't share it.
+    pub static TOOL_CONVERSATION: ConversationId;
+}
 
 struct JsonTool<T>(T);
 
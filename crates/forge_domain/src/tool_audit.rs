@@ -0,0 +1,303 @@
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{AgentId, ConversationId, ToolCallFull, ToolName, ToolResult};
+
+/// Hash the first entry of the log is chained to.
+const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Status of a tool call, as recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolStatus {
+    Success,
+    Failure,
+}
+
+/// Unified diff of a file changed by a tool call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileDiff {
+    pub path: PathBuf,
+    pub diff: String,
+}
+
+/// A file changed by a tool call, along with its content before and after the
+/// call, `None` when the file didn't exist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: PathBuf,
+    /// Unified diff of the change
+    pub diff: String,
+    pub old: Option<Vec<u8>>,
+    pub new: Option<Vec<u8>>,
+}
+
+/// An entry of the audit log, one per tool call. Each entry holds the hash of
+/// the previous one, so that changing or removing an entry breaks the chain
+/// from there on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolAuditEntry {
+    /// Position of the entry in the log, from 0
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub conversation: ConversationId,
+    pub agent: AgentId,
+    pub tool: ToolName,
+    /// SHA-256 of the arguments of the call, which aren't kept since they
+    /// can hold the content of files
+    pub arguments: String,
+    pub status: ToolStatus,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<FileDiff>,
+    /// Hash of the previous entry
+    pub prev: String,
+    /// Hash of this entry, over the previous hash and the other fields
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub hash: String,
+}
+
+impl ToolAuditEntry {
+    pub fn new(
+        conversation: ConversationId,
+        agent: AgentId,
+        tool_call: &ToolCallFull,
+        result: &ToolResult,
+        files: &[FileChange],
+    ) -> Self {
+        Self {
+            seq: 0,
+            timestamp: Utc::now(),
+            conversation,
+            agent,
+            tool: tool_call.name.clone(),
+            arguments: sha256(tool_call.arguments.to_string().as_bytes()),
+            status: if result.is_error {
+                ToolStatus::Failure
+            } else {
+                ToolStatus::Success
+            },
+            files: files.iter().map(FileDiff::from).collect(),
+            prev: String::new(),
+            hash: String::new(),
+        }
+    }
+
+    /// Chains the entry to the last one of the log, given by its position and
+    /// hash, or makes it the first one.
+    pub fn chain(mut self, last: Option<(u64, &str)>) -> anyhow::Result<Self> {
+        (self.seq, self.prev) = match last {
+            Some((seq, hash)) => (seq + 1, hash.to_string()),
+            None => (0, GENESIS.to_string()),
+        };
+        self.hash = self.digest()?;
+        Ok(self)
+    }
+
+    /// The hash the entry should have, which doesn't depend on its `hash`.
+    fn digest(&self) -> anyhow::Result<String> {
+        let body = serde_json::to_string(&Self { hash: String::new(), ..self.clone() })?;
+        Ok(sha256(format!("{}{body}", self.prev).as_bytes()))
+    }
+}
+
+/// Why the audit log failed its verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditViolation {
+    /// Line of the log, from 1
+    pub line: usize,
+    pub reason: String,
+}
+
+impl std::fmt::Display for AuditViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+/// The entries of the log, oldest first.
+pub fn parse_audit_log(content: &str) -> anyhow::Result<Vec<ToolAuditEntry>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Invalid entry at line {} of the audit log", index + 1))
+        })
+        .collect()
+}
+
+/// Checks that every entry of the log is chained to the previous one and has
+/// the hash of its content, returning the number of entries.
+pub fn verify_audit_log(content: &str) -> anyhow::Result<Result<usize, AuditViolation>> {
+    let mut prev = GENESIS.to_string();
+    let mut count = 0;
+    for (index, line) in content.lines().enumerate() {
+        let violation = |reason: String| -> anyhow::Result<Result<usize, AuditViolation>> {
+            Ok(Err(AuditViolation { line: index + 1, reason }))
+        };
+        let entry = match serde_json::from_str::<ToolAuditEntry>(line) {
+            Ok(entry) => entry,
+            Err(error) => return violation(format!("the entry can't be read: {error}")),
+        };
+        if entry.seq != count as u64 {
+            return violation(format!("expected entry {count}, found {}", entry.seq));
+        }
+        if entry.prev != prev {
+            return violation("the entry isn't chained to the previous one".to_string());
+        }
+        if entry.digest()? != entry.hash {
+            return violation("the entry doesn't match its hash".to_string());
+        }
+        prev = entry.hash;
+        count += 1;
+    }
+    Ok(Ok(count))
+}
+
+/// Content of the files a tool call can change, taken before the call to
+/// diff them against afterwards. A file is `None` when it doesn't exist.
+#[derive(Debug, Default)]
+pub struct FileSnapshot {
+    pub files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+impl FileSnapshot {
+    /// The paths the tool call is made with: its `path`, `source` and
+    /// `destination` arguments and the `path` of its `entries`.
+    pub fn paths(tool_call: &ToolCallFull) -> Vec<PathBuf> {
+        let arguments = &tool_call.arguments;
+        let mut paths: Vec<PathBuf> = Vec::new();
+        let candidates = ["path", "source", "destination"]
+            .iter()
+            .filter_map(|name| arguments.get(name)?.as_str())
+            .chain(
+                arguments
+                    .get("entries")
+                    .and_then(|entries| entries.as_array())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|entry| entry.get("path")?.as_str()),
+            )
+            .map(PathBuf::from);
+        for path in candidates {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        paths
+    }
+}
+
+impl FileChange {
+    /// The change of the file between its two contents, diffed as text.
+    pub fn new(path: PathBuf, old: Option<Vec<u8>>, new: Option<Vec<u8>>) -> Self {
+        let text = |content: &Option<Vec<u8>>| {
+            String::from_utf8_lossy(content.as_deref().unwrap_or_default()).into_owned()
+        };
+        let diff = similar::TextDiff::from_lines(&text(&old), &text(&new))
+            .unified_diff()
+            .header(&path.display().to_string(), &path.display().to_string())
+            .to_string();
+        Self { path, diff, old, new }
+    }
+}
+
+impl From<&FileChange> for FileDiff {
+    fn from(change: &FileChange) -> Self {
+        Self { path: change.path.clone(), diff: change.diff.clone() }
+    }
+}
+
+fn sha256(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    fn entry(tool: &str) -> ToolAuditEntry {
+        let tool_call = ToolCallFull::new(ToolName::new(tool)).arguments(json!({"path": "a.txt"}));
+        let result = ToolResult::from(tool_call.clone()).success("done");
+        ToolAuditEntry::new(
+            ConversationId::generate(),
+            AgentId::new("software-engineer"),
+            &tool_call,
+            &result,
+            &[],
+        )
+    }
+
+    fn log(tools: &[&str]) -> String {
+        let mut last: Option<ToolAuditEntry> = None;
+        let mut content = String::new();
+        for tool in tools {
+            let entry = entry(tool)
+                .chain(last.as_ref().map(|last| (last.seq, last.hash.as_str())))
+                .unwrap();
+            content.push_str(&format!("{}\n", serde_json::to_string(&entry).unwrap()));
+            last = Some(entry);
+        }
+        content
+    }
+
+    #[test]
+    fn test_chain() {
+        let first = entry("tool_forge_fs_read").chain(None).unwrap();
+        let second = entry("tool_forge_fs_create")
+            .chain(Some((first.seq, &first.hash)))
+            .unwrap();
+
+        assert_eq!(first.seq, 0);
+        assert_eq!(first.prev, GENESIS);
+        assert_eq!(second.seq, 1);
+        assert_eq!(second.prev, first.hash);
+
+        let content = log(&["tool_forge_fs_read", "tool_forge_fs_create"]);
+        assert_eq!(parse_audit_log(&content).unwrap().len(), 2);
+        assert_eq!(verify_audit_log(&content).unwrap(), Ok(2));
+    }
+
+    #[test]
+    fn test_verify_detects_tampering() {
+        let content = log(&[
+            "tool_forge_fs_read",
+            "tool_forge_fs_create",
+            "tool_forge_process_shell",
+        ]);
+
+        let tampered = content.replacen("\"success\"", "\"failure\"", 2);
+        let actual = verify_audit_log(&tampered).unwrap().unwrap_err();
+        assert_eq!(actual.line, 1);
+        assert_eq!(actual.reason, "the entry doesn't match its hash");
+
+        let removed = content.lines().skip(1).collect::<Vec<_>>().join("\n");
+        let actual = verify_audit_log(&removed).unwrap().unwrap_err();
+        assert_eq!(actual.line, 1);
+        assert_eq!(actual.reason, "expected entry 0, found 1");
+    }
+
+    #[test]
+    fn test_snapshot_paths() {
+        let tool_call = ToolCallFull::new(ToolName::new("tool_forge_fs_move")).arguments(json!({
+            "source": "/project/a.txt",
+            "destination": "/project/b.txt",
+            "entries": [{"path": "/project/a.txt"}, {"path": "/project/c.txt"}],
+        }));
+
+        let actual = FileSnapshot::paths(&tool_call);
+        let expected = ["/project/a.txt", "/project/b.txt", "/project/c.txt"].map(PathBuf::from);
+        assert_eq!(actual, expected);
+    }
+}
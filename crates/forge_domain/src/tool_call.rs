@@ -26,6 +26,12 @@ impl ToolCallId {
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize, Setters)]
 #[setters(strip_option, into)]
 pub struct ToolCallPart {
+    /// Position of the call among the parallel tool calls of the response.
+    /// Parts of concurrent calls are interleaved in the stream and are told
+    /// apart using this index.
+    #[serde(default)]
+    pub index: u32,
+
     /// Optional unique identifier that represents a single call to the tool
     /// use. NOTE: Not all models support a call ID for using a tool
     pub call_id: Option<ToolCallId>,
@@ -36,6 +42,28 @@ pub struct ToolCallPart {
     pub arguments_part: String,
 }
 
+/// A tool call that's being assembled from its streamed parts.
+#[derive(Default)]
+struct PartialCall {
+    index: u32,
+    call_id: Option<ToolCallId>,
+    name: Option<ToolName>,
+    arguments: String,
+}
+
+impl PartialCall {
+    fn try_into_full(self) -> Result<ToolCallFull> {
+        let name = self.name.ok_or(Error::ToolCallMissingName)?;
+        // Tools without any input may not stream any arguments
+        let arguments = if self.arguments.trim().is_empty() {
+            Value::Object(Default::default())
+        } else {
+            serde_json::from_str(&self.arguments).map_err(Error::ToolCallArgument)?
+        };
+        Ok(ToolCallFull { name, call_id: self.call_id, arguments })
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, From)]
 pub enum ToolCall {
     Full(ToolCallFull),
@@ -74,56 +102,41 @@ impl ToolCallFull {
         Self { name: tool_name, call_id: None, arguments: Value::default() }
     }
 
+    /// Assembles the streamed parts into complete tool calls, following the
+    /// OpenAI streaming format where the parts of parallel calls share the
+    /// `index` of their call. A part carrying a new call ID at an index that's
+    /// already in use starts another call, for providers that send every call
+    /// at the same index. Calls are returned in the order of their index.
     pub fn try_from_parts(parts: &[ToolCallPart]) -> Result<Vec<Self>> {
-        if parts.is_empty() {
-            return Ok(vec![]);
-        }
+        let mut calls: Vec<PartialCall> = Vec::new();
+        for part in parts {
+            let position = calls
+                .iter()
+                .rposition(|call| call.index == part.index)
+                .filter(|position| {
+                    let call_id = &calls[*position].call_id;
+                    part.call_id.is_none() || call_id.is_none() || call_id == &part.call_id
+                });
 
-        let mut tool_name: Option<&ToolName> = None;
-        let mut tool_call_id = None;
-
-        let mut tool_calls = Vec::new();
-
-        let mut input = String::new();
-        for part in parts.iter() {
-            if let Some(value) = &part.call_id {
-                if let Some(tool_name) = tool_name {
-                    if !input.is_empty() {
-                        tool_calls.push(ToolCallFull {
-                            name: tool_name.clone(),
-                            call_id: tool_call_id,
-                            arguments: serde_json::from_str(&input)
-                                .map_err(Error::ToolCallArgument)?,
-                        });
-                        input.clear();
-                    }
+            let call = match position {
+                Some(position) => &mut calls[position],
+                None => {
+                    calls.push(PartialCall { index: part.index, ..Default::default() });
+                    calls.last_mut().expect("a call was just pushed")
                 }
-                tool_call_id = Some(value.clone());
-            }
-
-            if let Some(value) = &part.name {
-                tool_name = Some(value);
+            };
+            if call.call_id.is_none() {
+                call.call_id = part.call_id.clone();
             }
-
-            input.push_str(&part.arguments_part);
-        }
-
-        if !input.is_empty() {
-            if let Some(tool_name) = tool_name {
-                tool_calls.push(ToolCallFull {
-                    name: tool_name.clone(),
-                    call_id: tool_call_id,
-                    arguments: serde_json::from_str(&input).map_err(Error::ToolCallArgument)?,
-                });
-                input.clear();
+            if call.name.is_none() {
+                call.name = part.name.clone();
             }
+            call.arguments.push_str(&part.arguments_part);
         }
 
-        if !tool_calls.is_empty() {
-            Ok(tool_calls)
-        } else {
-            Err(Error::ToolCallMissingName)
-        }
+        // Stable, so calls sharing an index stay in the order they were received
+        calls.sort_by_key(|call| call.index);
+        calls.into_iter().map(PartialCall::try_into_full).collect()
     }
 
     /// Parse multiple tool calls from XML format.
@@ -134,23 +147,38 @@ impl ToolCallFull {
 
 #[cfg(test)]
 mod tests {
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+    use serde_json::json;
+
     use super::*;
 
+    fn part(index: u32, arguments_part: &str) -> ToolCallPart {
+        ToolCallPart {
+            index,
+            arguments_part: arguments_part.to_string(),
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_multiple_calls() {
         let input = [
             ToolCallPart {
+                index: 0,
                 call_id: Some(ToolCallId("call_1".to_string())),
                 name: Some(ToolName::new("tool_forge_fs_read")),
                 arguments_part: "{\"path\": \"crates/forge_app/src/fixtures/mascot.md\"}"
                     .to_string(),
             },
             ToolCallPart {
+                index: 0,
                 call_id: Some(ToolCallId("call_2".to_string())),
                 name: Some(ToolName::new("tool_forge_fs_read")),
                 arguments_part: "{\"path\": \"docs/onboarding.md\"}".to_string(),
             },
             ToolCallPart {
+                index: 0,
                 call_id: Some(ToolCallId("call_3".to_string())),
                 name: Some(ToolName::new("tool_forge_fs_read")),
                 arguments_part: "{\"path\": \"crates/forge_app/src/service/service.md\"}"
@@ -184,6 +212,7 @@ mod tests {
     #[test]
     fn test_single_tool_call() {
         let input = [ToolCallPart {
+            index: 0,
             call_id: Some(ToolCallId("call_1".to_string())),
             name: Some(ToolName::new("tool_forge_fs_read")),
             arguments_part: "{\"path\": \"docs/onboarding.md\"}".to_string(),
@@ -206,4 +235,127 @@ mod tests {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_interleaved_parallel_calls() {
+        let input = [
+            part(0, "")
+                .call_id(ToolCallId::new("call_1"))
+                .name(ToolName::new("tool_forge_fs_read")),
+            part(1, "")
+                .call_id(ToolCallId::new("call_2"))
+                .name(ToolName::new("tool_forge_fs_search")),
+            part(1, "{\"regex\": "),
+            part(0, "{\"path\": "),
+            part(0, "\"a.txt\"}"),
+            part(1, "\"hello\"}"),
+        ];
+
+        let actual = ToolCallFull::try_from_parts(&input).unwrap();
+        let expected = vec![
+            ToolCallFull::new(ToolName::new("tool_forge_fs_read"))
+                .call_id(ToolCallId::new("call_1"))
+                .arguments(json!({"path": "a.txt"})),
+            ToolCallFull::new(ToolName::new("tool_forge_fs_search"))
+                .call_id(ToolCallId::new("call_2"))
+                .arguments(json!({"regex": "hello"})),
+        ];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_call_without_arguments() {
+        let input = [part(0, "")
+            .call_id(ToolCallId::new("call_1"))
+            .name(ToolName::new("tool_forge_system_info"))];
+
+        let actual = ToolCallFull::try_from_parts(&input).unwrap();
+        let expected = vec![ToolCallFull::new(ToolName::new("tool_forge_system_info"))
+            .call_id(ToolCallId::new("call_1"))
+            .arguments(json!({}))];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_call_missing_name() {
+        let input = [part(0, "{}").call_id(ToolCallId::new("call_1"))];
+
+        let actual = ToolCallFull::try_from_parts(&input);
+
+        assert!(matches!(actual, Err(Error::ToolCallMissingName)));
+    }
+
+    /// Streams the calls as chunks of their arguments, interleaving the chunks
+    /// of different calls in the order picked by `schedule`.
+    fn stream(
+        calls: &[ToolCallFull],
+        splits: &[Vec<usize>],
+        schedule: &[usize],
+    ) -> Vec<ToolCallPart> {
+        let mut pending = calls
+            .iter()
+            .zip(splits)
+            .enumerate()
+            .map(|(index, (call, splits))| {
+                let arguments = serde_json::to_string(&call.arguments).unwrap();
+                let mut boundaries = splits
+                    .iter()
+                    .map(|split| split % (arguments.len() + 1))
+                    .filter(|split| arguments.is_char_boundary(*split))
+                    .collect::<Vec<_>>();
+                boundaries.extend([0, arguments.len()]);
+                boundaries.sort();
+                boundaries.dedup();
+
+                let mut chunks = boundaries
+                    .windows(2)
+                    .map(|window| part(index as u32, &arguments[window[0]..window[1]]))
+                    .collect::<std::collections::VecDeque<_>>();
+                let first = chunks.pop_front().unwrap_or_else(|| part(index as u32, ""));
+                chunks.push_front(
+                    first
+                        .call_id(call.call_id.clone().unwrap())
+                        .name(call.name.clone()),
+                );
+                chunks
+            })
+            .collect::<Vec<_>>();
+
+        let mut parts = Vec::new();
+        let mut schedule = schedule.iter().cycle();
+        while pending.iter().any(|chunks| !chunks.is_empty()) {
+            let mut remaining = pending
+                .iter_mut()
+                .filter(|chunks| !chunks.is_empty())
+                .collect::<Vec<_>>();
+            let pick = schedule.next().copied().unwrap_or_default() % remaining.len();
+            parts.extend(remaining[pick].pop_front());
+        }
+        parts
+    }
+
+    fn arb_call() -> impl Strategy<Value = ToolCallFull> {
+        ("[a-z_]{1,12}", "\\PC{0,24}", any::<u32>()).prop_map(|(name, path, id)| {
+            ToolCallFull::new(ToolName::new(format!("tool_forge_{name}")))
+                .call_id(ToolCallId::new(format!("call_{id}")))
+                .arguments(json!({"path": path, "nested": {"line": id}}))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_interleaved_deltas_roundtrip(
+            calls in prop::collection::vec(arb_call(), 1..5),
+            splits in prop::collection::vec(prop::collection::vec(any::<usize>(), 0..8), 5),
+            schedule in prop::collection::vec(any::<usize>(), 1..32),
+        ) {
+            let parts = stream(&calls, &splits, &schedule);
+
+            let actual = ToolCallFull::try_from_parts(&parts).unwrap();
+
+            prop_assert_eq!(actual, calls);
+        }
+    }
 }
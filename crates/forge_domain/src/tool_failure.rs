@@ -0,0 +1,159 @@
+use serde_json::Value;
+
+use crate::{ToolCallFull, ToolDefinition, ToolName, ToolResult};
+
+/// Number of consecutive failures of the same tool call after which the agent
+/// is sent a note on how to correct it.
+pub const TOOL_FAILURE_FEEDBACK_THRESHOLD: usize = 2;
+
+/// Number of consecutive failures of the same tool call after which the agent
+/// is stopped and the user is asked for help.
+pub const MAX_TOOL_FAILURES: usize = 4;
+
+/// What the orchestrator should do after a tool call completed.
+#[derive(Debug, PartialEq)]
+pub enum ToolFailureAction {
+    Continue,
+    /// Note to be sent to the agent along with the tool results
+    Feedback(String),
+    /// Reason for stopping the agent and asking the user for input
+    NeedsUserInput(String),
+}
+
+/// Detects an agent repeating a tool call, ie: the same tool with the same
+/// arguments, that keeps failing.
+#[derive(Debug, Default)]
+pub struct ToolFailureTracker {
+    last: Option<(ToolName, Value)>,
+    failures: usize,
+}
+
+impl ToolFailureTracker {
+    /// Records the result of a tool call. `definition` is included in the
+    /// feedback so that the agent can check the arguments it passes.
+    pub fn record(
+        &mut self,
+        tool_call: &ToolCallFull,
+        result: &ToolResult,
+        definition: Option<&ToolDefinition>,
+    ) -> ToolFailureAction {
+        if !result.is_error {
+            *self = Self::default();
+            return ToolFailureAction::Continue;
+        }
+
+        let call = (tool_call.name.clone(), tool_call.arguments.clone());
+        if self.last.as_ref() == Some(&call) {
+            self.failures += 1;
+        } else {
+            self.last = Some(call);
+            self.failures = 1;
+        }
+
+        if self.failures >= MAX_TOOL_FAILURES {
+            ToolFailureAction::NeedsUserInput(format!(
+                "The call to '{}' failed {} times in a row with the same arguments: {}",
+                tool_call.name.as_str(),
+                self.failures,
+                result.content.trim()
+            ))
+        } else if self.failures >= TOOL_FAILURE_FEEDBACK_THRESHOLD {
+            ToolFailureAction::Feedback(self.feedback(tool_call, result, definition))
+        } else {
+            ToolFailureAction::Continue
+        }
+    }
+
+    fn feedback(
+        &self,
+        tool_call: &ToolCallFull,
+        result: &ToolResult,
+        definition: Option<&ToolDefinition>,
+    ) -> String {
+        let schema = definition
+            .and_then(|definition| serde_json::to_string_pretty(&definition.input_schema).ok())
+            .map(|schema| format!("<input_schema>\n{schema}\n</input_schema>\n"))
+            .unwrap_or_default();
+        format!(
+            "<system_note>\nThe call to '{name}' failed {failures} times in a row with the same arguments. Repeating it will fail again.\n<arguments>\n{arguments}\n</arguments>\n<error>\n{error}\n</error>\n{schema}Fix the arguments according to the error and the input schema, or take a different approach.\n</system_note>",
+            name = tool_call.name.as_str(),
+            failures = self.failures,
+            arguments = tool_call.arguments,
+            error = result.content.trim(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    fn call(path: &str) -> ToolCallFull {
+        ToolCallFull::new(ToolName::new("tool_forge_fs_read")).arguments(json!({"path": path}))
+    }
+
+    fn failure(call: &ToolCallFull) -> ToolResult {
+        ToolResult::from(call.clone()).failure(anyhow::anyhow!("File not found"))
+    }
+
+    #[test]
+    fn test_feedback_after_repeated_failures() {
+        let mut tracker = ToolFailureTracker::default();
+        let fixture = call("/missing.txt");
+        let definition = ToolDefinition::new("tool_forge_fs_read");
+
+        let first = tracker.record(&fixture, &failure(&fixture), Some(&definition));
+        let second = tracker.record(&fixture, &failure(&fixture), Some(&definition));
+
+        assert_eq!(first, ToolFailureAction::Continue);
+        let ToolFailureAction::Feedback(actual) = second else {
+            panic!("expected feedback, got {second:?}");
+        };
+        assert_snapshot!(actual);
+    }
+
+    #[test]
+    fn test_needs_user_input_after_limit() {
+        let mut tracker = ToolFailureTracker::default();
+        let fixture = call("/missing.txt");
+
+        let actual = (0..MAX_TOOL_FAILURES)
+            .map(|_| tracker.record(&fixture, &failure(&fixture), None))
+            .last()
+            .unwrap();
+
+        assert!(matches!(actual, ToolFailureAction::NeedsUserInput(_)));
+    }
+
+    #[test]
+    fn test_different_arguments_reset() {
+        let mut tracker = ToolFailureTracker::default();
+        let first = call("/a.txt");
+        let second = call("/b.txt");
+
+        tracker.record(&first, &failure(&first), None);
+        let actual = tracker.record(&second, &failure(&second), None);
+
+        assert_eq!(actual, ToolFailureAction::Continue);
+    }
+
+    #[test]
+    fn test_success_resets() {
+        let mut tracker = ToolFailureTracker::default();
+        let fixture = call("/a.txt");
+
+        tracker.record(&fixture, &failure(&fixture), None);
+        tracker.record(
+            &fixture,
+            &ToolResult::from(fixture.clone()).success("hello"),
+            None,
+        );
+        let actual = tracker.record(&fixture, &failure(&fixture), None);
+
+        assert_eq!(actual, ToolFailureAction::Continue);
+    }
+}
@@ -1,5 +1,40 @@
 use serde::{Deserialize, Serialize};
 
+/// Tools that don't change the files or run commands, which are available
+/// while planning and in a read-only session. The agents spawned while
+/// planning are restricted to these tools too.
+const READ_ONLY_TOOLS: [&str; 20] = [
+    "tool_forge_fs_read",
+    "tool_forge_fs_search",
+    "tool_forge_fs_list",
+    "tool_forge_fs_info",
+    "tool_forge_code_query",
+    "tool_forge_code_outline",
+    "tool_forge_code_todos",
+    "tool_forge_lsp_definition",
+    "tool_forge_lsp_references",
+    "tool_forge_lsp_diagnostics",
+    "tool_forge_net_fetch",
+    "tool_forge_process_think",
+    "tool_forge_process_status",
+    "tool_forge_process_logs",
+    "tool_forge_package_list",
+    "tool_forge_db_schema",
+    "tool_forge_compose_logs",
+    "tool_forge_event_dispatch",
+    "tool_forge_ask_followup_question",
+    "tool_forge_agent_spawn",
+];
+
+/// Prefixes of the read-only tools that still run code of the project, eg:
+/// rust-analyzer runs its build scripts and proc macros, `pip list` imports
+/// from the working directory and yarn runs the `yarnPath` of its config.
+const RUNS_PROJECT_CODE: [&str; 3] = [
+    "tool_forge_lsp_",
+    "tool_forge_package_",
+    "tool_forge_compose_",
+];
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct ToolName(String);
@@ -18,6 +53,21 @@ impl ToolName {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Whether the tool only reads, ie: it neither changes the files nor runs
+    /// commands.
+    pub fn is_read_only(&self) -> bool {
+        READ_ONLY_TOOLS.contains(&self.as_str())
+    }
+
+    /// Whether the tool can be used in an untrusted workspace, ie: it's read
+    /// only and runs nothing from the workspace.
+    pub fn is_safe_untrusted(&self) -> bool {
+        self.is_read_only()
+            && !RUNS_PROJECT_CODE
+                .iter()
+                .any(|prefix| self.as_str().starts_with(prefix))
+    }
 }
 
 pub trait NamedTool {
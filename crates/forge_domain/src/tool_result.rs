@@ -1,7 +1,7 @@
 use derive_setters::Setters;
 use serde::{Deserialize, Serialize};
 
-use crate::{ToolCallFull, ToolCallId, ToolName};
+use crate::{FileChange, ToolCallFull, ToolCallId, ToolName};
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, Setters)]
 #[setters(strip_option, into)]
@@ -12,6 +12,11 @@ pub struct ToolResult {
     pub content: String,
     #[setters(skip)]
     pub is_error: bool,
+    /// Files the call changed, for the clients to show, they aren't part of
+    /// the context
+    #[setters(skip)]
+    #[serde(skip)]
+    pub files: Vec<FileChange>,
 }
 
 impl ToolResult {
@@ -21,6 +26,7 @@ impl ToolResult {
             call_id: None,
             content: String::default(),
             is_error: false,
+            files: Vec::new(),
         }
     }
 
@@ -51,6 +57,7 @@ impl From<ToolCallFull> for ToolResult {
             call_id: value.call_id,
             content: String::default(),
             is_error: false,
+            files: Vec::new(),
         }
     }
 }
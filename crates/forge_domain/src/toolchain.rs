@@ -0,0 +1,21 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// The kind of project in the working directory and the tools available to
+/// build it, detected once at startup so that the agents don't have to
+/// discover them with shell commands.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Toolchain {
+    /// Kinds of project found in the working directory, eg: `cargo`, `npm`
+    /// or `pyproject`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub project_types: Vec<String>,
+    /// Versions of the installed compilers and runtimes, by their name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub versions: BTreeMap<String, String>,
+    /// Package managers found in the `PATH`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub package_managers: Vec<String>,
+}
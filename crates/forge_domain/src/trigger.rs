@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+/// Runs the workflow headlessly with a prompt whenever files matching `paths`
+/// change or on a cron `schedule`, used by `forge watch`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trigger {
+    /// Name used to select the trigger from the command line
+    pub name: String,
+
+    /// Glob patterns, relative to the working directory, of the files that
+    /// trigger a run when changed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+
+    /// Cron expression with seconds, eg: `0 0 9 * * Mon-Fri`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+
+    /// Message sent to the agents. `{{files}}` is replaced with the changed
+    /// files, one per line.
+    pub prompt: String,
+}
+
+impl Trigger {
+    /// Renders the prompt for the given changed files.
+    pub fn render(&self, files: &[String]) -> String {
+        self.prompt.replace("{{files}}", &files.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let fixture: Trigger = serde_json::from_value(serde_json::json!({
+            "name": "docs",
+            "paths": ["src/**/*.rs"],
+            "prompt": "Update the docs of:\n{{files}}",
+        }))
+        .unwrap();
+
+        let actual = fixture.render(&["src/a.rs".to_string(), "src/b.rs".to_string()]);
+        let expected = "Update the docs of:\nsrc/a.rs\nsrc/b.rs";
+        assert_eq!(actual, expected);
+    }
+}
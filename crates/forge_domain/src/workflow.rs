@@ -1,10 +1,18 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{Agent, AgentId};
+use crate::{Agent, AgentId, CustomCommand, Trigger};
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct Workflow {
     pub agents: Vec<Agent>,
+
+    /// Slash commands available in addition to the built-in ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<CustomCommand>,
+
+    /// File and schedule triggers run by `forge watch`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<Trigger>,
 }
 
 impl Workflow {
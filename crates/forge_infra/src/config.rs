@@ -0,0 +1,827 @@
+//! Config files: `.forge/config.toml` of the project and `config.toml` of the
+//! base path, eg:
+//!
+//! ```toml
+//! model = "anthropic/claude-3.7-sonnet"
+//! completion_model = "mistralai/codestral-2501"
+//! disabled_tools = ["tool_forge_process_shell"]
+//! restricted = true
+//! read_only = true
+//! budget = 5.0
+//! format_on_write = false
+//! fuzzy_threshold = 0.8
+//! allowed_paths = ["~/notes"]
+//! theme = "light"
+//!
+//! [parameters]
+//! temperature = 0.2
+//!
+//! [rate_limit]
+//! requests_per_minute = 50
+//!
+//! [databases.app]
+//! url = "$DATABASE_URL"
+//!
+//! [redaction]
+//! internal_domains = ["corp.example.com"]
+//! audit_log = "~/.forge/redactions.jsonl"
+//!
+//! [redaction.allow]
+//! aws-access-key = ["EXAMPLE$"]
+//!
+//! [redaction.filters]
+//! email = "anonymize"
+//! hostname = "block"
+//!
+//! [[redaction.rules]]
+//! name = "customer-id"
+//! pattern = 'CUS-\d{8}'
+//! action = "anonymize"
+//!
+//! [response_cache]
+//! enabled = true
+//! ttl = 86400
+//!
+//! [plugins]
+//! allowed_hosts = ["*.atlassian.net"]
+//!
+//! [tools.deploy]
+//! cmd = "./scripts/deploy.sh"
+//! description = "Deploys the app to an environment"
+//! timeout = 600
+//! env = { DEPLOY_REGION = "eu-west-1" }
+//! schema = { type = "object", properties = { target = { type = "string" } }, required = ["target"] }
+//!
+//! [[hooks]]
+//! event = "post_tool:fs_patch"
+//! cmd = "cargo fmt"
+//! timeout = 30
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use forge_domain::{
+    CommandTool, Config, Database, FilterRule, Hook, HookEvent, ModelId, PiiKind, Redaction,
+    RedactionAction, Redactor, SecretKind, ThemeName, ToolName,
+};
+use toml_edit::{DocumentMut, Item, Value};
+
+use crate::env::base_path;
+
+const FILE_NAME: &str = "config.toml";
+
+/// Config file of the project in `cwd`.
+pub fn project_path(cwd: &Path) -> PathBuf {
+    cwd.join(".forge").join(FILE_NAME)
+}
+
+/// Config file of the user, shared by all the projects.
+pub fn user_path() -> PathBuf {
+    base_path().join(FILE_NAME)
+}
+
+/// Reads the config file at `path`, which is empty when it doesn't exist.
+pub fn read(path: &Path) -> Result<Config> {
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read the config: {}", path.display()))?;
+    parse(&content).with_context(|| format!("Invalid config: {}", path.display()))
+}
+
+pub fn parse(content: &str) -> Result<Config> {
+    let document = content.parse::<DocumentMut>()?;
+    let mut config = Config::default();
+    for (key, item) in document.iter() {
+        match key {
+            "model" => config.model = Some(ModelId::new(string(key, item)?)),
+            "completion_model" => config.completion_model = Some(ModelId::new(string(key, item)?)),
+            "disabled_tools" => {
+                let tools = item
+                    .as_array()
+                    .with_context(|| format!("`{key}` must be an array of tool names"))?
+                    .iter()
+                    .map(|tool| {
+                        tool.as_str()
+                            .map(ToolName::new)
+                            .with_context(|| format!("`{key}` must be an array of tool names"))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                config.disabled_tools = Some(tools);
+            }
+            "restricted" => {
+                config.restricted = Some(
+                    item.as_bool()
+                        .with_context(|| format!("`{key}` must be a boolean"))?,
+                )
+            }
+            "read_only" => {
+                config.read_only = Some(
+                    item.as_bool()
+                        .with_context(|| format!("`{key}` must be a boolean"))?,
+                )
+            }
+            "budget" => config.budget = Some(float(key, item)?),
+            "format_on_write" => {
+                config.format_on_write = Some(
+                    item.as_bool()
+                        .with_context(|| format!("`{key}` must be a boolean"))?,
+                )
+            }
+            "fuzzy_threshold" => {
+                let threshold = float(key, item)?;
+                if !(0.0..=1.0).contains(&threshold) {
+                    bail!("`{key}` must be between 0 and 1");
+                }
+                config.fuzzy_threshold = Some(threshold)
+            }
+            "allowed_paths" => {
+                let paths = item
+                    .as_array()
+                    .with_context(|| format!("`{key}` must be an array of paths"))?
+                    .iter()
+                    .map(|path| {
+                        path.as_str()
+                            .map(expand_home)
+                            .with_context(|| format!("`{key}` must be an array of paths"))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                config.allowed_paths = Some(paths);
+            }
+            "theme" => {
+                let theme = string(key, item)?;
+                config.theme = Some(theme.parse::<ThemeName>().ok().with_context(|| {
+                    format!("Unknown theme `{theme}`, expected one of: dark, light, high-contrast, no-color")
+                })?)
+            }
+            "parameters" => {
+                for (key, item) in table(key, item)? {
+                    match key {
+                        "temperature" => {
+                            config.parameters.temperature = Some(float(key, item)? as f32)
+                        }
+                        "top_p" => config.parameters.top_p = Some(float(key, item)? as f32),
+                        "top_k" => config.parameters.top_k = Some(integer(key, item)?),
+                        "max_tokens" => config.parameters.max_tokens = Some(integer(key, item)?),
+                        _ => bail!("Unknown setting `parameters.{key}`"),
+                    }
+                }
+            }
+            "rate_limit" => {
+                for (key, item) in table(key, item)? {
+                    match key {
+                        "requests_per_minute" => {
+                            config.rate_limit.requests_per_minute = Some(integer(key, item)?)
+                        }
+                        "tokens_per_minute" => {
+                            config.rate_limit.tokens_per_minute = Some(integer(key, item)?)
+                        }
+                        _ => bail!("Unknown setting `rate_limit.{key}`"),
+                    }
+                }
+            }
+            "databases" => {
+                let mut databases = BTreeMap::new();
+                for (name, item) in table(key, item)? {
+                    let mut database = Database::default();
+                    for (key, item) in table(name, item)? {
+                        match key {
+                            "url" => database.url = string(key, item)?.to_string(),
+                            "allow_writes" => {
+                                database.allow_writes = item
+                                    .as_bool()
+                                    .with_context(|| format!("`{key}` must be a boolean"))?
+                            }
+                            _ => bail!("Unknown setting `databases.{name}.{key}`"),
+                        }
+                    }
+                    if database.url.is_empty() {
+                        bail!("`databases.{name}.url` must be set");
+                    }
+                    databases.insert(name.to_string(), database);
+                }
+                config.databases = Some(databases);
+            }
+            "redaction" => {
+                for (key, item) in table(key, item)? {
+                    match key {
+                        "enabled" => {
+                            config.redaction.enabled = Some(
+                                item.as_bool()
+                                    .with_context(|| format!("`{key}` must be a boolean"))?,
+                            )
+                        }
+                        "allow" => {
+                            let mut allow = BTreeMap::new();
+                            for (kind, item) in table(key, item)? {
+                                let patterns = item
+                                    .as_array()
+                                    .with_context(|| {
+                                        format!("`{kind}` must be an array of patterns")
+                                    })?
+                                    .iter()
+                                    .map(|pattern| {
+                                        pattern.as_str().map(str::to_string).with_context(|| {
+                                            format!("`{kind}` must be an array of patterns")
+                                        })
+                                    })
+                                    .collect::<Result<Vec<_>>>()?;
+                                let kind = kind.parse::<SecretKind>().ok().with_context(|| {
+                                    format!("Unknown kind of secret `{kind}`, expected one of: private-key, aws-access-key, aws-secret-key, github-token, slack-token, api-key, env-value, high-entropy")
+                                })?;
+                                allow.insert(kind, patterns);
+                            }
+                            config.redaction.allow = Some(allow);
+                        }
+                        "filters" => {
+                            let mut filters = BTreeMap::new();
+                            for (kind, item) in table(key, item)? {
+                                let action = string(kind, item)?;
+                                let action = action.parse::<RedactionAction>().ok().with_context(|| {
+                                    format!("Unknown action `{action}` of `{kind}`, expected one of: mask, anonymize, block")
+                                })?;
+                                let kind = kind.parse::<PiiKind>().ok().with_context(|| {
+                                    format!("Unknown filter `{kind}`, expected one of: email, name, hostname")
+                                })?;
+                                filters.insert(kind, action);
+                            }
+                            config.redaction.filters = Some(filters);
+                        }
+                        "internal_domains" => {
+                            let domains = item
+                                .as_array()
+                                .with_context(|| format!("`{key}` must be an array of domains"))?
+                                .iter()
+                                .map(|domain| {
+                                    domain.as_str().map(str::to_string).with_context(|| {
+                                        format!("`{key}` must be an array of domains")
+                                    })
+                                })
+                                .collect::<Result<Vec<_>>>()?;
+                            config.redaction.internal_domains = Some(domains);
+                        }
+                        "rules" => {
+                            let mut rules = Vec::new();
+                            for rule in item.as_array_of_tables().with_context(|| {
+                                format!(
+                                    "`{key}` must be an array of tables, eg: `[[redaction.rules]]`"
+                                )
+                            })? {
+                                let (mut name, mut pattern, mut action) = (None, None, None);
+                                for (key, item) in rule.iter() {
+                                    match key {
+                                        "name" => name = Some(string(key, item)?.to_string()),
+                                        "pattern" => pattern = Some(string(key, item)?.to_string()),
+                                        "action" => {
+                                            let value = string(key, item)?;
+                                            let value = value.parse::<RedactionAction>().ok().with_context(|| {
+                                                format!("Unknown action `{value}`, expected one of: mask, anonymize, block")
+                                            })?;
+                                            action = Some(value);
+                                        }
+                                        _ => bail!("Unknown setting `redaction.rules.{key}`"),
+                                    }
+                                }
+                                rules.push(FilterRule {
+                                    name: name.context("`redaction.rules.name` must be set")?,
+                                    pattern: pattern
+                                        .context("`redaction.rules.pattern` must be set")?,
+                                    action: action.unwrap_or(RedactionAction::Mask),
+                                });
+                            }
+                            config.redaction.rules = Some(rules);
+                        }
+                        "audit_log" => {
+                            config.redaction.audit_log = Some(expand_home(string(key, item)?))
+                        }
+                        _ => bail!("Unknown setting `redaction.{key}`"),
+                    }
+                }
+                // note: the patterns are checked now, rather than once the
+                // first request is sent. The filters are left out since the
+                // internal domains can be set by another file.
+                Redactor::new(&Redaction { filters: None, ..config.redaction.clone() })?;
+            }
+            "response_cache" => {
+                for (key, item) in table(key, item)? {
+                    match key {
+                        "enabled" => {
+                            config.response_cache.enabled = Some(
+                                item.as_bool()
+                                    .with_context(|| format!("`{key}` must be a boolean"))?,
+                            )
+                        }
+                        "ttl" => config.response_cache.ttl = Some(integer(key, item)?),
+                        "dir" => config.response_cache.dir = Some(expand_home(string(key, item)?)),
+                        _ => bail!("Unknown setting `response_cache.{key}`"),
+                    }
+                }
+            }
+            "plugins" => {
+                for (key, item) in table(key, item)? {
+                    match key {
+                        "enabled" => {
+                            config.plugins.enabled = Some(
+                                item.as_bool()
+                                    .with_context(|| format!("`{key}` must be a boolean"))?,
+                            )
+                        }
+                        "dir" => config.plugins.dir = Some(expand_home(string(key, item)?)),
+                        "allowed_hosts" => {
+                            let hosts = item
+                                .as_array()
+                                .with_context(|| format!("`{key}` must be an array of hosts"))?
+                                .iter()
+                                .map(|host| {
+                                    host.as_str().map(str::to_string).with_context(|| {
+                                        format!("`{key}` must be an array of hosts")
+                                    })
+                                })
+                                .collect::<Result<Vec<_>>>()?;
+                            config.plugins.allowed_hosts = Some(hosts);
+                        }
+                        _ => bail!("Unknown setting `plugins.{key}`"),
+                    }
+                }
+            }
+            "tools" => {
+                let mut tools = BTreeMap::new();
+                for (name, item) in table(key, item)? {
+                    let mut tool = CommandTool::default();
+                    for (key, item) in table(name, item)? {
+                        match key {
+                            "cmd" => tool.cmd = string(key, item)?.to_string(),
+                            "description" => {
+                                tool.description = Some(string(key, item)?.to_string())
+                            }
+                            // note: the schema can also be written in JSON, as
+                            // a string.
+                            "schema" => {
+                                let schema = match item.as_str() {
+                                    Some(schema) => {
+                                        serde_json::from_str(schema).with_context(|| {
+                                            format!("`tools.{name}.schema` isn't valid JSON")
+                                        })?
+                                    }
+                                    None => json(item)?,
+                                };
+                                if !schema.is_object() {
+                                    bail!("`tools.{name}.schema` must be a table");
+                                }
+                                tool.schema = Some(schema);
+                            }
+                            "timeout" => tool.timeout = Some(integer(key, item)?),
+                            "env" => {
+                                let env = table(key, item)?
+                                    .into_iter()
+                                    .map(|(name, item)| {
+                                        Ok((name.to_string(), string(name, item)?.to_string()))
+                                    })
+                                    .collect::<Result<BTreeMap<_, _>>>()?;
+                                tool.env = Some(env);
+                            }
+                            _ => bail!("Unknown setting `tools.{name}.{key}`"),
+                        }
+                    }
+                    if tool.cmd.trim().is_empty() {
+                        bail!("`tools.{name}.cmd` must be set");
+                    }
+                    tools.insert(name.to_string(), tool);
+                }
+                config.tools = Some(tools);
+            }
+            "hooks" => {
+                let mut hooks = Vec::new();
+                for table in item.as_array_of_tables().with_context(|| {
+                    format!("`{key}` must be an array of tables, eg: `[[hooks]]`")
+                })? {
+                    let mut hook = Hook::default();
+                    for (key, item) in table.iter() {
+                        match key {
+                            "event" => {
+                                let value = string(key, item)?;
+                                let event = value.split_once(':').map_or(value, |(event, _)| event);
+                                event.parse::<HookEvent>().ok().with_context(|| {
+                                    format!("Unknown event `{event}`, expected one of: pre_tool, post_tool, pre_turn, post_turn, session_end")
+                                })?;
+                                hook.event = value.to_string();
+                            }
+                            "cmd" => hook.cmd = string(key, item)?.to_string(),
+                            "timeout" => hook.timeout = Some(integer(key, item)?),
+                            _ => bail!("Unknown setting `hooks.{key}`"),
+                        }
+                    }
+                    if hook.event.is_empty() {
+                        bail!("`hooks.event` must be set");
+                    }
+                    if hook.cmd.trim().is_empty() {
+                        bail!("`hooks.cmd` must be set");
+                    }
+                    hooks.push(hook);
+                }
+                config.hooks = Some(hooks);
+            }
+            _ => bail!("Unknown setting `{key}`"),
+        }
+    }
+    Ok(config)
+}
+
+/// Sets the top level `key` of the config file at `path` to the string
+/// `value`, keeping the rest of the file as is.
+pub fn set(path: &Path, key: &str, value: &str) -> Result<()> {
+    let content = std::fs::read_to_string(path).unwrap_or_default();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, set_value(&content, key, value))
+        .with_context(|| format!("Failed to write the config: {}", path.display()))
+}
+
+/// Replaces the line of the top level `key`, or inserts it before the first
+/// table since keys after it belong to the table.
+fn set_value(content: &str, key: &str, value: &str) -> String {
+    let line = format!("{key} = {}", toml_string(value));
+    let mut lines = content.lines().map(str::to_string).collect::<Vec<_>>();
+    let top_level = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .unwrap_or(lines.len());
+    let existing = lines[..top_level].iter().position(|line| {
+        line.split_once('=')
+            .is_some_and(|(name, _)| name.trim() == key)
+    });
+    match existing {
+        Some(index) => lines[index] = line,
+        None => lines.insert(top_level, line),
+    }
+    lines.join("\n") + "\n"
+}
+
+fn toml_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Expands the leading `~` of a path to the home directory.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            home.join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+fn string<'a>(key: &str, item: &'a Item) -> Result<&'a str> {
+    item.as_str()
+        .with_context(|| format!("`{key}` must be a string"))
+}
+
+fn float(key: &str, item: &Item) -> Result<f64> {
+    // note: whole numbers are accepted, eg: `budget = 5`.
+    item.as_float()
+        .or_else(|| item.as_integer().map(|value| value as f64))
+        .with_context(|| format!("`{key}` must be a number"))
+}
+
+fn integer<T: TryFrom<i64>>(key: &str, item: &Item) -> Result<T> {
+    item.as_integer()
+        .and_then(|value| T::try_from(value).ok())
+        .with_context(|| format!("`{key}` must be a positive integer"))
+}
+
+/// Converts a TOML value to JSON, eg: the schema of a tool.
+fn json(item: &Item) -> Result<serde_json::Value> {
+    if let Some(table) = item.as_table_like() {
+        return table
+            .iter()
+            .map(|(key, item)| Ok((key.to_string(), json(item)?)))
+            .collect::<Result<serde_json::Map<_, _>>>()
+            .map(serde_json::Value::Object);
+    }
+    if let Some(tables) = item.as_array_of_tables() {
+        return tables
+            .iter()
+            .map(|table| json(&Item::Table(table.clone())))
+            .collect::<Result<Vec<_>>>()
+            .map(serde_json::Value::Array);
+    }
+    Ok(match item.as_value() {
+        Some(Value::String(value)) => serde_json::json!(value.value()),
+        Some(Value::Integer(value)) => serde_json::json!(value.value()),
+        Some(Value::Float(value)) => serde_json::json!(value.value()),
+        Some(Value::Boolean(value)) => serde_json::json!(value.value()),
+        Some(Value::Datetime(value)) => serde_json::json!(value.value().to_string()),
+        Some(Value::Array(values)) => serde_json::Value::Array(
+            values
+                .iter()
+                .map(|value| json(&Item::Value(value.clone())))
+                .collect::<Result<Vec<_>>>()?,
+        ),
+        Some(Value::InlineTable(_)) | None => bail!("Unsupported TOML value"),
+    })
+}
+
+fn table<'a>(key: &str, item: &'a Item) -> Result<Vec<(&'a str, &'a Item)>> {
+    Ok(item
+        .as_table_like()
+        .with_context(|| format!("`{key}` must be a table"))?
+        .iter()
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_domain::{ModelParameters, Plugins, RateLimit, ResponseCache};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let actual = parse(
+            r#"
+model = "gpt-4o"
+completion_model = "mistralai/codestral-2501"
+disabled_tools = ["tool_forge_process_shell"]
+restricted = true
+read_only = true
+budget = 5
+format_on_write = false
+fuzzy_threshold = 0.8
+allowed_paths = ["/var/data"]
+theme = "high-contrast"
+
+[parameters]
+temperature = 0.2
+max_tokens = 4096
+
+[rate_limit]
+requests_per_minute = 50
+
+[response_cache]
+enabled = true
+ttl = 86400
+
+[plugins]
+allowed_hosts = ["api.github.com", "*.atlassian.net"]
+"#,
+        )
+        .unwrap();
+        let expected = Config::default()
+            .model(ModelId::new("gpt-4o"))
+            .completion_model(ModelId::new("mistralai/codestral-2501"))
+            .disabled_tools(vec![ToolName::new("tool_forge_process_shell")])
+            .restricted(true)
+            .read_only(true)
+            .budget(5.0)
+            .format_on_write(false)
+            .fuzzy_threshold(0.8)
+            .allowed_paths(vec![PathBuf::from("/var/data")])
+            .theme(ThemeName::HighContrast)
+            .parameters(ModelParameters::default().temperature(0.2).max_tokens(4096))
+            .rate_limit(RateLimit::default().requests_per_minute(50))
+            .response_cache(ResponseCache::default().enabled(true).ttl(86400))
+            .plugins(Plugins::default().allowed_hosts(vec![
+                "api.github.com".to_string(),
+                "*.atlassian.net".to_string(),
+            ]));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_databases() {
+        let actual = parse(
+            r#"
+[databases.app]
+url = "$DATABASE_URL"
+
+[databases.analytics]
+url = "postgres://localhost/analytics"
+allow_writes = true
+"#,
+        )
+        .unwrap();
+        let expected = Config::default().databases(BTreeMap::from([
+            (
+                "analytics".to_string(),
+                Database::default()
+                    .url("postgres://localhost/analytics".to_string())
+                    .allow_writes(true),
+            ),
+            (
+                "app".to_string(),
+                Database::default().url("$DATABASE_URL".to_string()),
+            ),
+        ]));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_tools() {
+        let actual = parse(
+            r#"
+[tools.deploy]
+cmd = "./scripts/deploy.sh"
+timeout = 600
+env = { DEPLOY_REGION = "eu-west-1" }
+
+[tools.deploy.schema]
+type = "object"
+required = ["target"]
+properties = { target = { type = "string", enum = ["staging", "production"] } }
+
+[tools.lint]
+cmd = "make lint"
+schema = '{"type": "object", "properties": {}}'
+"#,
+        )
+        .unwrap();
+        let expected = Config::default().tools(BTreeMap::from([
+            (
+                "deploy".to_string(),
+                CommandTool::default()
+                    .cmd("./scripts/deploy.sh".to_string())
+                    .timeout(600)
+                    .env(BTreeMap::from([(
+                        "DEPLOY_REGION".to_string(),
+                        "eu-west-1".to_string(),
+                    )]))
+                    .schema(serde_json::json!({
+                        "type": "object",
+                        "required": ["target"],
+                        "properties": {"target": {"type": "string", "enum": ["staging", "production"]}}
+                    })),
+            ),
+            (
+                "lint".to_string(),
+                CommandTool::default()
+                    .cmd("make lint".to_string())
+                    .schema(serde_json::json!({"type": "object", "properties": {}})),
+            ),
+        ]));
+        assert_eq!(actual, expected);
+
+        let actual = parse("[tools.deploy]\ntimeout = 600\n")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(actual, "`tools.deploy.cmd` must be set");
+    }
+
+    #[test]
+    fn test_parse_hooks() {
+        let actual = parse(
+            r#"
+[[hooks]]
+event = "post_tool:fs_patch"
+cmd = "cargo fmt"
+timeout = 30
+
+[[hooks]]
+event = "session_end"
+cmd = "notify-send 'Forge is done'"
+"#,
+        )
+        .unwrap();
+        let expected = Config::default().hooks(vec![
+            Hook::default()
+                .event("post_tool:fs_patch".to_string())
+                .cmd("cargo fmt".to_string())
+                .timeout(30),
+            Hook::default()
+                .event("session_end".to_string())
+                .cmd("notify-send 'Forge is done'".to_string()),
+        ]);
+        assert_eq!(actual, expected);
+
+        let actual = parse("[[hooks]]\nevent = \"on_write\"\ncmd = \"cargo fmt\"\n")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            actual,
+            "Unknown event `on_write`, expected one of: pre_tool, post_tool, pre_turn, post_turn, session_end"
+        );
+    }
+
+    #[test]
+    fn test_parse_redaction() {
+        let actual = parse(
+            r#"
+[redaction]
+enabled = true
+
+[redaction.allow]
+aws-access-key = ["EXAMPLE$"]
+"#,
+        )
+        .unwrap();
+        let expected = Config::default().redaction(Redaction::default().enabled(true).allow(
+            BTreeMap::from([(SecretKind::AwsAccessKey, vec!["EXAMPLE$".to_string()])]),
+        ));
+        assert_eq!(actual, expected);
+
+        let actual = parse("[redaction.allow]\npassword = [\"test\"]\n").unwrap_err();
+        assert!(actual
+            .to_string()
+            .starts_with("Unknown kind of secret `password`"));
+    }
+
+    #[test]
+    fn test_parse_content_filter() {
+        let actual = parse(
+            r#"
+[redaction]
+internal_domains = ["corp.example.com"]
+audit_log = "/var/log/forge/redactions.jsonl"
+
+[redaction.filters]
+email = "anonymize"
+hostname = "block"
+
+[[redaction.rules]]
+name = "customer-id"
+pattern = 'CUS-\d{8}'
+"#,
+        )
+        .unwrap();
+        let expected = Config::default().redaction(
+            Redaction::default()
+                .filters(BTreeMap::from([
+                    (PiiKind::Email, RedactionAction::Anonymize),
+                    (PiiKind::Hostname, RedactionAction::Block),
+                ]))
+                .internal_domains(vec!["corp.example.com".to_string()])
+                .rules(vec![FilterRule {
+                    name: "customer-id".to_string(),
+                    pattern: r"CUS-\d{8}".to_string(),
+                    action: RedactionAction::Mask,
+                }])
+                .audit_log(PathBuf::from("/var/log/forge/redactions.jsonl")),
+        );
+        assert_eq!(actual, expected);
+
+        let actual = parse("[redaction.filters]\nphone = \"block\"\n").unwrap_err();
+        assert!(actual.to_string().starts_with("Unknown filter `phone`"));
+
+        let actual = parse("[[redaction.rules]]\nname = \"id\"\npattern = \"(\"\n").unwrap_err();
+        assert!(actual.to_string().starts_with("Invalid pattern `(` of id"));
+    }
+
+    #[test]
+    fn test_parse_unknown_setting() {
+        let actual = parse("[parameters]\ntemprature = 0.2\n").unwrap_err();
+        assert_eq!(
+            actual.to_string(),
+            "Unknown setting `parameters.temprature`"
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_theme() {
+        let actual = parse("theme = \"solarized\"\n").unwrap_err();
+        assert_eq!(
+            actual.to_string(),
+            "Unknown theme `solarized`, expected one of: dark, light, high-contrast, no-color"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_type() {
+        let actual = parse("[rate_limit]\ntokens_per_minute = -1\n").unwrap_err();
+        assert_eq!(
+            actual.to_string(),
+            "`tokens_per_minute` must be a positive integer"
+        );
+
+        let actual = parse("fuzzy_threshold = 1.5\n").unwrap_err();
+        assert_eq!(actual.to_string(), "`fuzzy_threshold` must be between 0 and 1");
+    }
+
+    #[test]
+    fn test_read_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let actual = read(&project_path(dir.path())).unwrap();
+        assert_eq!(actual, Config::default());
+    }
+
+    #[test]
+    fn test_set_value() {
+        let content = "budget = 5.0\nmodel = \"gpt-4o\"\n\n[parameters]\nmodel = 1\n";
+
+        let actual = [
+            set_value(content, "model", "gpt-4o-mini"),
+            set_value("[parameters]\ntop_k = 40\n", "model", "gpt-4o"),
+            set_value("", "model", "gpt-4o"),
+        ];
+        let expected = [
+            "budget = 5.0\nmodel = \"gpt-4o-mini\"\n\n[parameters]\nmodel = 1\n",
+            "model = \"gpt-4o\"\n[parameters]\ntop_k = 40\n",
+            "model = \"gpt-4o\"\n",
+        ];
+        assert_eq!(actual, expected);
+    }
+}
@@ -1,27 +1,52 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Once, OnceLock};
 
 use forge_app::EnvironmentService;
-use forge_domain::{Environment, Provider};
+use forge_domain::{
+    Config, Environment, ModelId, Provider, RateLimit, ResponseCache, Speech, Toolchain,
+};
+use tracing::warn;
+
+use crate::trust::TrustStore;
+use crate::{config, keychain, redaction_key, toolchain};
 
 pub struct ForgeEnvironmentService {
-    restricted: bool,
+    /// Settings set by the flags, the layer above the environment
+    flags: Config,
+    /// Detected on the first request for the environment, as it takes a
+    /// process per runtime.
+    toolchain: OnceLock<Toolchain>,
+    /// Whether the working directory is trusted, checked once so that the
+    /// tools and the config agree for the whole session.
+    trusted: OnceLock<bool>,
+    /// The secret of the install, read once per session.
+    redaction_key: OnceLock<Vec<u8>>,
 }
 
 impl ForgeEnvironmentService {
     /// Creates a new EnvironmentFactory with current working directory
     ///
     /// # Arguments
-    /// * `unrestricted` - If true, use unrestricted shell mode (sh/bash) If
-    ///   false, use restricted shell mode (rbash)
-    pub fn new(restricted: bool) -> Self {
-        Self { restricted }
+    /// * `flags` - Settings of the command line, eg: `restricted` to use the
+    ///   restricted shell mode (rbash) instead of sh/bash
+    pub fn new(flags: Config) -> Self {
+        Self {
+            flags,
+            toolchain: OnceLock::new(),
+            trusted: OnceLock::new(),
+            redaction_key: OnceLock::new(),
+        }
     }
 
     /// Get path to appropriate shell based on platform and mode
-    fn get_shell_path(&self) -> String {
+    fn get_shell_path(&self, restricted: bool) -> String {
         if cfg!(target_os = "windows") {
-            std::env::var("COMSPEC").unwrap_or("cmd.exe".to_string())
-        } else if self.restricted {
+            // note: PowerShell 7 is preferred over Windows PowerShell, both
+            // restrict commands to the constrained language in restricted mode.
+            find_in_path("pwsh.exe")
+                .map(|path| path.display().to_string())
+                .unwrap_or("powershell.exe".to_string())
+        } else if restricted {
             // Default to rbash in restricted mode
             "/bin/rbash".to_string()
         } else {
@@ -31,35 +56,208 @@ impl ForgeEnvironmentService {
     }
 
     fn get(&self) -> Environment {
-        dotenv::dotenv().ok();
         let cwd = std::env::current_dir().unwrap_or(PathBuf::from("."));
+        let trusted = *self
+            .trusted
+            .get_or_init(|| TrustStore::default().is_trusted(&cwd));
+        load_env(trusted);
+        let user = read_config(&config::user_path());
+        let project = if trusted {
+            read_config(&config::project_path(&cwd))
+        } else {
+            Config::default()
+        };
+        let mut config = self
+            .flags
+            .clone()
+            .or(env_config())
+            .or(project)
+            .or(user.clone());
+        // note: a project can't widen the sandbox of its own tools.
+        config.allowed_paths = user.allowed_paths;
+        // note: an untrusted workspace can be read but not changed.
+        if !trusted {
+            config.read_only = Some(true);
+        }
 
-        let provider_key = std::env::var("FORGE_KEY")
-            .or_else(|_| std::env::var("OPENROUTER_API_KEY"))
-            .or_else(|_| std::env::var("OPENAI_API_KEY"))
-            .or_else(|_| std::env::var("ANTHROPIC_API_KEY"))
-            .expect("No API key found. Please set one of: FORGE_KEY, OPENROUTER_API_KEY, OPENAI_API_KEY or ANTHROPIC_API_KEY");
-        // note: since we know the key is set, we can unwrap here.
-        let provider = Provider::from_env().unwrap();
+        // note: replaying a recording doesn't make any request to the provider.
+        let replay_path = std::env::var("FORGE_REPLAY").ok().map(PathBuf::from);
+        // note: forge_main runs the setup when no provider is configured, see
+        // [`is_configured`].
+        let provider = Provider::from_env().unwrap_or(Provider::OpenRouter);
+        let provider_key = provider_key(&provider);
+        let toolchain = self
+            .toolchain
+            .get_or_init(|| toolchain::detect(&cwd))
+            .clone();
+        let redaction_key = self.redaction_key.get_or_init(redaction_key::get).clone();
         Environment {
             os: std::env::consts::OS.to_string(),
             pid: std::process::id(),
             cwd,
-            shell: self.get_shell_path(),
-            base_path: dirs::config_dir()
-                .map(|a| a.join("forge"))
-                .unwrap_or(PathBuf::from(".").join(".forge")),
+            shell: self.get_shell_path(config.restricted == Some(true)),
+            toolchain,
+            base_path: base_path(),
             home: dirs::home_dir(),
 
             qdrant_key: std::env::var("QDRANT_KEY").ok(),
             qdrant_cluster: std::env::var("QDRANT_CLUSTER").ok(),
             provider_key,
-            provider_url: provider.to_base_url().to_string(),
+            provider_url: provider.to_base_url(),
             openai_key: std::env::var("OPENAI_API_KEY").ok(),
+            github_token: std::env::var("GITHUB_TOKEN")
+                .or_else(|_| std::env::var("GH_TOKEN"))
+                .ok(),
+            gitlab_token: std::env::var("GITLAB_TOKEN").ok(),
+            bitbucket_token: std::env::var("BITBUCKET_TOKEN").ok(),
+            record_path: std::env::var("FORGE_RECORD").ok().map(PathBuf::from),
+            replay_path,
+            trusted,
+            redaction_key,
+            config,
         }
     }
 }
 
+pub(crate) fn find_in_path(name: &str) -> Option<PathBuf> {
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// The key of the provider, read from the first variable set.
+fn provider_key(provider: &Provider) -> String {
+    std::env::var("FORGE_KEY")
+        .or_else(|_| std::env::var("OPENROUTER_API_KEY"))
+        .or_else(|_| std::env::var("OPENAI_API_KEY"))
+        .or_else(|_| std::env::var("ANTHROPIC_API_KEY"))
+        .or_else(|_| std::env::var("AZURE_OPENAI_KEY"))
+        .or_else(|_| std::env::var("AZURE_OPENAI_AD_TOKEN"))
+        .or_else(|_| std::env::var("GEMINI_API_KEY"))
+        // note: AWS credentials are often set for other tools, so the secret
+        // is only ever sent to Bedrock.
+        .or_else(|error| match provider {
+            Provider::Bedrock => std::env::var("AWS_SECRET_ACCESS_KEY"),
+            _ => Err(error),
+        })
+        .unwrap_or_default()
+}
+
+/// Settings of the configuration that are set in the environment.
+fn env_config() -> Config {
+    Config {
+        model: std::env::var("FORGE_MODEL")
+            .ok()
+            .filter(|model| !model.is_empty())
+            .map(ModelId::new),
+        completion_model: std::env::var("FORGE_COMPLETION_MODEL")
+            .ok()
+            .filter(|model| !model.is_empty())
+            .map(ModelId::new),
+        rate_limit: RateLimit {
+            requests_per_minute: parse_env("FORGE_REQUESTS_PER_MINUTE"),
+            tokens_per_minute: parse_env("FORGE_TOKENS_PER_MINUTE"),
+        },
+        theme: parse_env("FORGE_THEME"),
+        speech: Speech { enabled: parse_env("FORGE_SPEECH"), ..Default::default() },
+        response_cache: ResponseCache {
+            enabled: parse_env("FORGE_RESPONSE_CACHE"),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Reads a config file, skipping it when it's invalid. `forge doctor` reports
+/// the error.
+fn read_config(path: &Path) -> Config {
+    config::read(path).unwrap_or_else(|error| {
+        warn!(
+            error = format!("{error:#}"),
+            "Skipping the invalid config file"
+        );
+        Config::default()
+    })
+}
+
+/// Directory everything is stored in, eg: `~/.config/forge`.
+pub(crate) fn base_path() -> PathBuf {
+    dirs::config_dir()
+        .map(|a| a.join("forge"))
+        .unwrap_or(PathBuf::from(".").join(".forge"))
+}
+
+/// File the setup writes the provider's key to when the keychain isn't
+/// available. It's loaded after the `.env` of the current directory, which
+/// takes precedence.
+pub fn config_path() -> PathBuf {
+    base_path().join(".env")
+}
+
+/// Loads the `.env` files, but the one of an untrusted working directory,
+/// along with the keys of the keychain.
+fn load_env(trusted: bool) {
+    if trusted {
+        dotenv::dotenv().ok();
+    }
+    dotenv::from_path(config_path()).ok();
+    load_keychain();
+}
+
+/// Variables of the tokens of the repository hosts, which can also be stored
+/// in the keychain, along with the other variables they're read from.
+const SCM_TOKENS: [(&str, &[&str]); 3] = [
+    ("GITHUB_TOKEN", &["GH_TOKEN"]),
+    ("GITLAB_TOKEN", &[]),
+    ("BITBUCKET_TOKEN", &[]),
+];
+
+/// Sets the key of the first provider found in the keychain, along with the
+/// tokens of the repository hosts, unless they're already set, since the
+/// environment takes precedence. It's read once as every read spawns the
+/// keychain's command line tool.
+fn load_keychain() {
+    static KEYCHAIN: Once = Once::new();
+    KEYCHAIN.call_once(|| {
+        for (name, aliases) in SCM_TOKENS {
+            if std::iter::once(&name)
+                .chain(aliases)
+                .all(|name| std::env::var(name).is_err())
+            {
+                if let Some(token) = keychain::get(name) {
+                    std::env::set_var(name, token);
+                }
+            }
+        }
+        let names = keychain::PROVIDERS.map(|provider| provider.key_name());
+        if std::iter::once("FORGE_KEY")
+            .chain(names)
+            .any(|name| std::env::var(name).is_ok())
+        {
+            return;
+        }
+        if let Some((name, key)) = names
+            .into_iter()
+            .find_map(|name| keychain::get(name).map(|key| (name, key)))
+        {
+            std::env::set_var(name, key);
+        }
+    });
+}
+
+/// Checks if a provider is configured, either in the environment, in one of
+/// the `.env` files or in the keychain.
+pub fn is_configured() -> bool {
+    let trusted = std::env::current_dir().is_ok_and(|cwd| TrustStore::default().is_trusted(&cwd));
+    load_env(trusted);
+    Provider::from_env().is_some() || std::env::var("FORGE_REPLAY").is_ok()
+}
+
+/// Reads a value from the environment, ignoring values that don't parse.
+fn parse_env<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok()?.trim().parse().ok()
+}
+
 impl EnvironmentService for ForgeEnvironmentService {
     fn get_environment(&self) -> Environment {
         self.get()
@@ -73,6 +271,8 @@ mod tests {
     use forge_domain::Provider;
     use serial_test::serial;
 
+    use super::provider_key;
+
     // reset the env variables for reliable tests
     fn reset_env() {
         env::remove_var("FORGE_KEY");
@@ -80,6 +280,12 @@ mod tests {
         env::remove_var("OPENROUTER_API_KEY");
         env::remove_var("OPENAI_API_KEY");
         env::remove_var("ANTHROPIC_API_KEY");
+        env::remove_var("AZURE_OPENAI_ENDPOINT");
+        env::remove_var("GEMINI_API_KEY");
+        env::remove_var("AWS_ACCESS_KEY_ID");
+        env::remove_var("AWS_SECRET_ACCESS_KEY");
+        env::remove_var("AWS_REGION");
+        env::remove_var("AWS_DEFAULT_REGION");
     }
 
     #[test]
@@ -133,6 +339,70 @@ mod tests {
         assert_eq!(provider, Some(Provider::Anthropic));
     }
 
+    #[test]
+    #[serial]
+    fn test_provider_from_env_with_azure_endpoint() {
+        reset_env();
+        env::set_var("AZURE_OPENAI_ENDPOINT", "https://forge.openai.azure.com/");
+
+        let provider = Provider::from_env();
+        assert_eq!(provider, Some(Provider::AzureOpenAI));
+        assert_eq!(
+            Provider::AzureOpenAI.to_base_url(),
+            "https://forge.openai.azure.com/"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_provider_from_env_with_gemini_key() {
+        reset_env();
+        env::set_var("GEMINI_API_KEY", "some_gemini_key");
+
+        let provider = Provider::from_env();
+        assert_eq!(provider, Some(Provider::Gemini));
+    }
+
+    #[test]
+    #[serial]
+    fn test_provider_from_env_with_aws_credentials() {
+        reset_env();
+        env::set_var("AWS_ACCESS_KEY_ID", "some_access_key_id");
+        env::set_var("AWS_REGION", "eu-west-1");
+
+        let provider = Provider::from_env();
+        assert_eq!(provider, Some(Provider::Bedrock));
+        assert_eq!(
+            Provider::Bedrock.to_base_url(),
+            "https://bedrock-runtime.eu-west-1.amazonaws.com/"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_provider_from_env_prefers_keys_over_aws_credentials() {
+        reset_env();
+        env::set_var("AWS_ACCESS_KEY_ID", "some_access_key_id");
+        env::set_var("ANTHROPIC_API_KEY", "some_anthropic_key");
+
+        let provider = Provider::from_env();
+        assert_eq!(provider, Some(Provider::Anthropic));
+    }
+
+    #[test]
+    #[serial]
+    fn test_aws_secret_is_only_the_key_of_bedrock() {
+        reset_env();
+        env::set_var("AWS_SECRET_ACCESS_KEY", "some_secret_access_key");
+
+        let provider = Provider::from_env().unwrap_or(Provider::OpenRouter);
+        assert_eq!(provider_key(&provider), "");
+
+        env::set_var("AWS_ACCESS_KEY_ID", "some_access_key_id");
+        let provider = Provider::from_env().unwrap();
+        assert_eq!(provider_key(&provider), "some_secret_access_key");
+    }
+
     #[test]
     #[serial]
     fn test_provider_from_env_with_no_keys() {
@@ -156,6 +426,26 @@ mod tests {
             Provider::from_url("https://api.anthropic.com/v1/"),
             Some(Provider::Anthropic)
         );
+        assert_eq!(
+            Provider::from_url("https://forge.openai.azure.com/"),
+            Some(Provider::AzureOpenAI)
+        );
+        assert_eq!(
+            Provider::from_url("https://openai.azure.com.evil.io/"),
+            None
+        );
+        assert_eq!(
+            Provider::from_url("https://generativelanguage.googleapis.com/v1beta/"),
+            Some(Provider::Gemini)
+        );
+        assert_eq!(
+            Provider::from_url("https://bedrock-runtime.us-east-1.amazonaws.com/"),
+            Some(Provider::Bedrock)
+        );
+        assert_eq!(
+            Provider::from_url("https://bedrock-runtime.amazonaws.com.evil.io/"),
+            None
+        );
         assert_eq!(Provider::from_url("https://unknown.url/"), None);
     }
 
@@ -171,5 +461,9 @@ mod tests {
             Provider::Anthropic.to_base_url(),
             "https://api.anthropic.com/v1/"
         );
+        assert_eq!(
+            Provider::Gemini.to_base_url(),
+            "https://generativelanguage.googleapis.com/v1beta/"
+        );
     }
 }
@@ -1,4 +1,5 @@
 use forge_app::{EnvironmentService, Infrastructure};
+use forge_domain::Config;
 
 use crate::embedding::OpenAIEmbeddingService;
 use crate::env::ForgeEnvironmentService;
@@ -13,8 +14,8 @@ pub struct ForgeInfra {
 }
 
 impl ForgeInfra {
-    pub fn new(restricted: bool) -> Self {
-        let _environment_service = ForgeEnvironmentService::new(restricted);
+    pub fn new(flags: Config) -> Self {
+        let _environment_service = ForgeEnvironmentService::new(flags);
         let env = _environment_service.get_environment();
         Self {
             file_read_service: ForgeFileReadService::new(),
@@ -0,0 +1,142 @@
+//! API keys stored in the keychain of the OS through its command line tool:
+//! `security` on macOS and `secret-tool` from libsecret on Linux.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+use anyhow::{bail, Context, Result};
+use forge_domain::Provider;
+
+/// Service the keys are stored under, along with the name of their variable.
+const SERVICE: &str = "forge";
+
+/// Providers whose key can be stored in the keychain, in the order they're
+/// detected by [`Provider::from_env`].
+pub const PROVIDERS: [Provider; 5] = [
+    Provider::OpenRouter,
+    Provider::OpenAI,
+    Provider::Anthropic,
+    Provider::AzureOpenAI,
+    Provider::Gemini,
+];
+
+/// Reads the key stored for the variable `name`, if any.
+pub fn get(name: &str) -> Option<String> {
+    let output = match Backend::current()? {
+        Backend::Security => Command::new("security")
+            .args(["find-generic-password", "-s", SERVICE, "-a", name, "-w"])
+            .output(),
+        Backend::SecretTool => Command::new("secret-tool")
+            .args(["lookup", "service", SERVICE, "account", name])
+            .output(),
+    }
+    .ok()?;
+    let secret = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (output.status.success() && !secret.is_empty()).then_some(secret)
+}
+
+/// Stores the key of the variable `name`, replacing the previous one.
+pub fn set(name: &str, secret: &str) -> Result<()> {
+    let Some(backend) = Backend::current() else {
+        bail!(
+            "No keychain is supported on {}, please set {name} in the environment instead",
+            std::env::consts::OS
+        );
+    };
+    // note: the secret is written to stdin so that it isn't visible in the
+    // arguments of the process.
+    let output = match backend {
+        Backend::Security => {
+            if secret.contains(['"', '\\']) {
+                bail!("The key contains characters that can't be stored in the keychain");
+            }
+            run_with_stdin(
+                Command::new("security").arg("-i"),
+                &format!(
+                    "add-generic-password -U -s {SERVICE} -a {name} -l \"Forge {name}\" -w \"{secret}\"\n"
+                ),
+            )
+        }
+        Backend::SecretTool => run_with_stdin(
+            Command::new("secret-tool").args([
+                "store",
+                "--label",
+                &format!("Forge {name}"),
+                "service",
+                SERVICE,
+                "account",
+                name,
+            ]),
+            secret,
+        ),
+    }
+    .with_context(|| format!("Failed to run {}", backend.program()))?;
+    check(backend, output)
+}
+
+/// Removes the key of the variable `name`, returning whether one was stored.
+pub fn delete(name: &str) -> Result<bool> {
+    let Some(backend) = Backend::current() else {
+        return Ok(false);
+    };
+    if get(name).is_none() {
+        return Ok(false);
+    }
+    let output = match backend {
+        Backend::Security => Command::new("security")
+            .args(["delete-generic-password", "-s", SERVICE, "-a", name])
+            .output(),
+        Backend::SecretTool => Command::new("secret-tool")
+            .args(["clear", "service", SERVICE, "account", name])
+            .output(),
+    }
+    .with_context(|| format!("Failed to run {}", backend.program()))?;
+    check(backend, output)?;
+    Ok(true)
+}
+
+#[derive(Clone, Copy)]
+enum Backend {
+    Security,
+    SecretTool,
+}
+
+impl Backend {
+    fn current() -> Option<Self> {
+        match std::env::consts::OS {
+            "macos" => Some(Self::Security),
+            "linux" | "freebsd" | "openbsd" => Some(Self::SecretTool),
+            _ => None,
+        }
+    }
+
+    fn program(&self) -> &'static str {
+        match self {
+            Backend::Security => "security",
+            Backend::SecretTool => "secret-tool, please install libsecret",
+        }
+    }
+}
+
+fn run_with_stdin(command: &mut Command, input: &str) -> std::io::Result<Output> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes())?;
+    }
+    child.wait_with_output()
+}
+
+fn check(backend: Backend, output: Output) -> Result<()> {
+    if output.status.success() {
+        return Ok(());
+    }
+    bail!(
+        "The keychain failed ({}): {}",
+        backend.program(),
+        String::from_utf8_lossy(&output.stderr).trim()
+    )
+}
@@ -1,7 +1,13 @@
+pub mod config;
 mod embedding;
 mod env;
 mod file_read;
 mod infra;
+pub mod keychain;
 mod qdrant;
+mod redaction_key;
+mod toolchain;
+pub mod trust;
 
+pub use env::{config_path, is_configured};
 pub use infra::*;
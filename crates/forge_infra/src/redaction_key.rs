@@ -0,0 +1,105 @@
+//! The secret the placeholders of the anonymized values are keyed with,
+//! stored in `redaction.key` of the base path. It's generated on first use,
+//! so the placeholders stay the same across the sessions of an install while
+//! they can't be reversed by hashing guesses of the values.
+
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use tracing::warn;
+
+use crate::env::base_path;
+
+const FILE_NAME: &str = "redaction.key";
+
+/// Bytes of the key.
+const KEY_SIZE: usize = 32;
+
+/// The key of the install, or one of this session only when it can't be
+/// read nor stored.
+pub fn get() -> Vec<u8> {
+    load(&base_path().join(FILE_NAME)).unwrap_or_else(|error| {
+        // note: the placeholders won't match those of the other sessions,
+        // but still can't be reversed.
+        warn!(
+            error = format!("{error:#}"),
+            "Using a redaction key of this session only"
+        );
+        generate()
+    })
+}
+
+/// Reads the key at `path`, generating it when it doesn't exist yet.
+fn load(path: &Path) -> Result<Vec<u8>> {
+    if let Some(key) = read(path)? {
+        return Ok(key);
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let key = generate();
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    match options.open(path) {
+        Ok(mut file) => {
+            file.write_all(&key)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            Ok(key)
+        }
+        // note: another process generated it meanwhile.
+        Err(error) if error.kind() == ErrorKind::AlreadyExists => {
+            read(path)?.with_context(|| format!("Invalid redaction key: {}", path.display()))
+        }
+        Err(error) => Err(error).with_context(|| format!("Failed to create {}", path.display())),
+    }
+}
+
+fn generate() -> Vec<u8> {
+    rand::random::<[u8; KEY_SIZE]>().to_vec()
+}
+
+/// The key at `path`, none when it doesn't exist.
+fn read(path: &Path) -> Result<Option<Vec<u8>>> {
+    match std::fs::read(path) {
+        Ok(key) if key.len() == KEY_SIZE => Ok(Some(key)),
+        Ok(_) => anyhow::bail!("Invalid redaction key: {}", path.display()),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("forge").join(FILE_NAME);
+
+        let first = load(&path).unwrap();
+        let second = load(&path).unwrap();
+
+        assert_eq!(first.len(), KEY_SIZE);
+        assert_eq!(first, second);
+        assert_ne!(
+            first,
+            load(&dir.path().join("other").join(FILE_NAME)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_load_invalid_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        std::fs::write(&path, "short").unwrap();
+
+        assert!(load(&path).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use forge_domain::Toolchain;
+
+use crate::env::find_in_path;
+
+/// Files that mark the root of a project, along with its kind.
+const PROJECT_FILES: &[(&str, &str)] = &[
+    ("Cargo.toml", "cargo"),
+    ("package.json", "npm"),
+    ("pyproject.toml", "pyproject"),
+    ("requirements.txt", "pip"),
+    ("go.mod", "go"),
+];
+
+/// Compilers and runtimes, with the commands that print their version, in
+/// order of preference.
+const RUNTIMES: &[(&str, &[&[&str]])] = &[
+    ("rustc", &[&["rustc", "--version"]]),
+    ("node", &[&["node", "--version"]]),
+    (
+        "python",
+        &[&["python3", "--version"], &["python", "--version"]],
+    ),
+    ("go", &[&["go", "version"]]),
+];
+
+const PACKAGE_MANAGERS: &[&str] = &["cargo", "npm", "pnpm", "yarn", "bun", "pip", "uv", "poetry"];
+
+/// Detects the toolchain of the project at `cwd`. The versions are asked
+/// for in parallel, since each takes a process.
+pub fn detect(cwd: &Path) -> Toolchain {
+    let project_types = PROJECT_FILES
+        .iter()
+        .filter(|(file, _)| cwd.join(file).is_file())
+        .map(|(_, kind)| kind.to_string())
+        .collect();
+
+    let versions = std::thread::scope(|scope| {
+        let handles = RUNTIMES
+            .iter()
+            .map(|(name, commands)| {
+                scope.spawn(move || {
+                    commands
+                        .iter()
+                        .find_map(|command| version(command))
+                        .map(|version| (name.to_string(), version))
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().ok().flatten())
+            .collect()
+    });
+
+    let package_managers = PACKAGE_MANAGERS
+        .iter()
+        .filter(|name| is_installed(name))
+        .map(|name| name.to_string())
+        .collect();
+
+    Toolchain { project_types, versions, package_managers }
+}
+
+fn is_installed(name: &str) -> bool {
+    if cfg!(windows) {
+        ["exe", "cmd"]
+            .iter()
+            .any(|extension| find_in_path(&format!("{name}.{extension}")).is_some())
+    } else {
+        find_in_path(name).is_some()
+    }
+}
+
+/// Runs the command and reads the version from its output, `None` when it
+/// isn't installed or fails.
+fn version(command: &[&str]) -> Option<String> {
+    let output = Command::new(command[0])
+        .args(&command[1..])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    parse_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// The first word of the output that looks like a version, eg: `1.85.0` in
+/// `rustc 1.85.0 (4d91de4e4 2025-02-17)` or `v20.11.0`.
+fn parse_version(output: &str) -> Option<String> {
+    output.split_whitespace().find_map(|word| {
+        let word = word.trim_start_matches("go").trim_start_matches('v');
+        (word.starts_with(|c: char| c.is_ascii_digit()) && word.contains('.'))
+            .then(|| word.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        let actual = [
+            "rustc 1.85.0 (4d91de4e4 2025-02-17)",
+            "v20.11.0\n",
+            "Python 3.12.1",
+            "go version go1.22.0 linux/amd64",
+            "command not found",
+        ]
+        .map(parse_version);
+        let expected = [
+            Some("1.85.0".to_string()),
+            Some("20.11.0".to_string()),
+            Some("3.12.1".to_string()),
+            Some("1.22.0".to_string()),
+            None,
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_detect_project_types() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]").unwrap();
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+
+        let actual = detect(dir.path()).project_types;
+
+        assert_eq!(actual, vec!["cargo".to_string(), "npm".to_string()]);
+    }
+}
@@ -0,0 +1,103 @@
+//! Workspaces the user trusts, stored in `trusted_workspaces.json` of the base
+//! path. The config, prompts and workflow of an untrusted workspace are
+//! ignored and it's opened in the read-only mode, since they could have been
+//! written by anyone.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::env::base_path;
+
+const FILE_NAME: &str = "trusted_workspaces.json";
+
+/// The folders the user trusted, along with their subfolders.
+pub struct TrustStore {
+    path: PathBuf,
+}
+
+impl Default for TrustStore {
+    fn default() -> Self {
+        Self::new(base_path().join(FILE_NAME))
+    }
+}
+
+impl TrustStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Whether the folder, or one of its parents, is trusted.
+    pub fn is_trusted(&self, dir: &Path) -> bool {
+        let dir = canonical(dir);
+        self.load().iter().any(|trusted| dir.starts_with(trusted))
+    }
+
+    /// Trusts the folder and its subfolders.
+    pub fn trust(&self, dir: &Path) -> Result<()> {
+        let dir = canonical(dir);
+        let mut trusted = self.load();
+        if !trusted.contains(&dir) {
+            trusted.push(dir);
+        }
+        self.save(&trusted)
+    }
+
+    /// Stops trusting the folder, its parents stay trusted.
+    pub fn revoke(&self, dir: &Path) -> Result<()> {
+        let dir = canonical(dir);
+        let mut trusted = self.load();
+        trusted.retain(|trusted| *trusted != dir);
+        self.save(&trusted)
+    }
+
+    /// The trusted folders, none when the file is missing or invalid.
+    pub fn load(&self) -> Vec<PathBuf> {
+        std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, trusted: &[PathBuf]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(trusted)?)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+/// The path with its symlinks resolved, so that a folder is trusted whichever
+/// way it's reached.
+fn canonical(dir: &Path) -> PathBuf {
+    dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_trust() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TrustStore::new(dir.path().join("forge").join(FILE_NAME));
+        let workspace = dir.path().join("workspace");
+        let nested = workspace.join("crates");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        assert!(!store.is_trusted(&workspace));
+
+        store.trust(&workspace).unwrap();
+        store.trust(&workspace).unwrap();
+        assert!(store.is_trusted(&workspace));
+        assert!(store.is_trusted(&nested));
+        assert!(!store.is_trusted(dir.path()));
+        assert_eq!(store.load().len(), 1);
+
+        store.revoke(&workspace).unwrap();
+        assert!(!store.is_trusted(&nested));
+    }
+}
@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use anyhow::Context;
-use forge_api::{AgentMessage, ChatRequest, ChatResponse, ForgeAPI, ModelId, API};
+use forge_api::{AgentMessage, ChatRequest, ChatResponse, Config, ForgeAPI, ModelId, API};
 use tokio_stream::StreamExt;
 
 const MAX_RETRIES: usize = 5;
@@ -26,7 +26,7 @@ impl Fixture {
     /// Get the API service, panicking if not validated
     fn api(&self) -> impl API {
         // NOTE: In tests the CWD is not the project root
-        ForgeAPI::init(true)
+        ForgeAPI::init(Config::default().restricted(true))
     }
 
     /// Get model response as text
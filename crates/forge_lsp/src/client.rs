@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidOpenTextDocument, Initialized, Notification, PublishDiagnostics,
+};
+use lsp_types::request::{Initialize, Request};
+use lsp_types::{
+    ClientCapabilities, Diagnostic, DidChangeTextDocumentParams, DidOpenTextDocumentParams,
+    InitializeParams, InitializedParams, PublishDiagnosticsClientCapabilities,
+    PublishDiagnosticsParams, TextDocumentClientCapabilities, TextDocumentContentChangeEvent,
+    TextDocumentItem, Url, VersionedTextDocumentIdentifier, WorkspaceFolder,
+};
+use serde_json::{json, Value};
+use tokio::io::BufReader;
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::{oneshot, Mutex, Notify};
+
+use crate::config::{language_id, ServerConfig};
+use crate::transport::{read_frame, write_message};
+
+/// Time to wait for the response of a request. Servers may need to index the
+/// project before answering the first request, hence the generous value.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+type Pending = Arc<std::sync::Mutex<HashMap<i64, oneshot::Sender<anyhow::Result<Value>>>>>;
+type Diagnostics = Arc<std::sync::Mutex<HashMap<Url, Vec<Diagnostic>>>>;
+
+/// A document that was opened on the server.
+struct Document {
+    version: i32,
+    text: String,
+}
+
+/// Client of a single language server process communicating over stdio.
+pub struct LspClient {
+    // Held so that the server is killed once the client is dropped.
+    _child: Child,
+    stdin: Arc<Mutex<ChildStdin>>,
+    next_id: AtomicI64,
+    pending: Pending,
+    diagnostics: Diagnostics,
+    diagnostics_updated: Arc<Notify>,
+    documents: Mutex<HashMap<Url, Document>>,
+    alive: Arc<AtomicBool>,
+}
+
+impl LspClient {
+    /// Starts the language server and performs the initialization handshake
+    /// for the workspace rooted at `root`.
+    pub async fn start(config: &ServerConfig, root: &Path) -> anyhow::Result<Self> {
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| {
+                format!(
+                    "Failed to start language server '{}'. Make sure it is installed and on the PATH",
+                    config.command
+                )
+            })?;
+
+        let stdin = Arc::new(Mutex::new(
+            child.stdin.take().context("Language server has no stdin")?,
+        ));
+        let stdout = child
+            .stdout
+            .take()
+            .context("Language server has no stdout")?;
+
+        let client = Self {
+            _child: child,
+            stdin,
+            next_id: AtomicI64::new(1),
+            pending: Default::default(),
+            diagnostics: Default::default(),
+            diagnostics_updated: Default::default(),
+            documents: Default::default(),
+            alive: Arc::new(AtomicBool::new(true)),
+        };
+
+        tokio::spawn(dispatch(
+            stdout,
+            client.stdin.clone(),
+            client.pending.clone(),
+            client.diagnostics.clone(),
+            client.diagnostics_updated.clone(),
+            client.alive.clone(),
+        ));
+
+        client.initialize(root).await?;
+        Ok(client)
+    }
+
+    async fn initialize(&self, root: &Path) -> anyhow::Result<()> {
+        let uri = file_url(root)?;
+        let name = root
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let params = InitializeParams {
+            process_id: Some(std::process::id()),
+            workspace_folders: Some(vec![WorkspaceFolder { uri, name }]),
+            capabilities: ClientCapabilities {
+                text_document: Some(TextDocumentClientCapabilities {
+                    publish_diagnostics: Some(PublishDiagnosticsClientCapabilities::default()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        self.request::<Initialize>(params).await?;
+        self.notify::<Initialized>(InitializedParams {}).await
+    }
+
+    /// Returns whether the messages of the server are still being read. A
+    /// client that is no longer alive can't receive any response.
+    pub fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::SeqCst)
+    }
+
+    /// Sends a request and waits for its response.
+    pub async fn request<R: Request>(&self, params: R::Params) -> anyhow::Result<R::Result> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+        lock(&self.pending).insert(id, sender);
+        // note: checked after registering the request so that it can't slip in
+        // once the dispatcher has already failed the pending requests.
+        if !self.is_alive() {
+            lock(&self.pending).remove(&id);
+            return Err(anyhow!("Language server exited unexpectedly"));
+        }
+
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": R::METHOD, "params": params});
+        write_message(&mut *self.stdin.lock().await, &message).await?;
+
+        let result = match tokio::time::timeout(REQUEST_TIMEOUT, receiver).await {
+            Ok(result) => result.map_err(|_| anyhow!("Language server exited unexpectedly"))??,
+            Err(_) => {
+                lock(&self.pending).remove(&id);
+                return Err(anyhow!(
+                    "Language server did not respond to '{}' in time",
+                    R::METHOD
+                ));
+            }
+        };
+
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Sends a notification.
+    pub async fn notify<N: Notification>(&self, params: N::Params) -> anyhow::Result<()> {
+        let message = json!({"jsonrpc": "2.0", "method": N::METHOD, "params": params});
+        write_message(&mut *self.stdin.lock().await, &message).await
+    }
+
+    /// Makes sure the server sees the current content of the file, opening it
+    /// on first use. Returns the url and the content of the file.
+    pub async fn sync_document(&self, path: &Path) -> anyhow::Result<(Url, String)> {
+        let text = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let uri = file_url(path)?;
+
+        let mut documents = self.documents.lock().await;
+        match documents.get_mut(&uri) {
+            Some(document) if document.text == text => {}
+            Some(document) => {
+                document.version += 1;
+                document.text = text.clone();
+                lock(&self.diagnostics).remove(&uri);
+                self.notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+                    text_document: VersionedTextDocumentIdentifier {
+                        uri: uri.clone(),
+                        version: document.version,
+                    },
+                    content_changes: vec![TextDocumentContentChangeEvent {
+                        range: None,
+                        range_length: None,
+                        text: text.clone(),
+                    }],
+                })
+                .await?;
+            }
+            None => {
+                self.notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+                    text_document: TextDocumentItem {
+                        uri: uri.clone(),
+                        language_id: language_id(path),
+                        version: 1,
+                        text: text.clone(),
+                    },
+                })
+                .await?;
+                documents.insert(uri.clone(), Document { version: 1, text: text.clone() });
+            }
+        }
+
+        Ok((uri, text))
+    }
+
+    /// Returns the diagnostics published for the document, waiting up to
+    /// `timeout` for the server to publish them.
+    pub async fn diagnostics(&self, uri: &Url, timeout: Duration) -> Vec<Diagnostic> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let updated = self.diagnostics_updated.notified();
+            if let Some(diagnostics) = lock(&self.diagnostics).get(uri) {
+                return diagnostics.clone();
+            }
+
+            if tokio::time::timeout_at(deadline, updated).await.is_err() {
+                return vec![];
+            }
+        }
+    }
+}
+
+/// Converts an absolute path into a `file://` url.
+pub fn file_url(path: &Path) -> anyhow::Result<Url> {
+    Url::from_file_path(path).map_err(|_| anyhow!("Path must be absolute: {}", path.display()))
+}
+
+fn lock<T>(mutex: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Reads messages from the server until it exits, resolving pending
+/// requests, answering requests made by the server and recording published
+/// diagnostics. Marks the client as no longer alive on exit.
+async fn dispatch(
+    stdout: ChildStdout,
+    stdin: Arc<Mutex<ChildStdin>>,
+    pending: Pending,
+    diagnostics: Diagnostics,
+    diagnostics_updated: Arc<Notify>,
+    alive: Arc<AtomicBool>,
+) {
+    let mut reader = BufReader::new(stdout);
+    loop {
+        let content = match read_frame(&mut reader).await {
+            Ok(Some(content)) => content,
+            Ok(None) => break,
+            Err(error) => {
+                tracing::warn!(error = %error, "Failed to read message from language server");
+                break;
+            }
+        };
+        let message = match serde_json::from_slice::<Value>(&content) {
+            Ok(message) => message,
+            Err(error) => {
+                tracing::warn!(error = %error, "Skipping malformed message from language server");
+                continue;
+            }
+        };
+        let id = message.get("id").cloned();
+        match (message.get("method").and_then(Value::as_str), id) {
+            // Response to one of our requests
+            (None, Some(id)) => {
+                let Some(sender) = id.as_i64().and_then(|id| lock(&pending).remove(&id)) else {
+                    continue;
+                };
+                let result = match message.get("error") {
+                    Some(error) => Err(anyhow!(
+                        "Language server error: {}",
+                        error
+                            .get("message")
+                            .and_then(Value::as_str)
+                            .unwrap_or("unknown error")
+                    )),
+                    None => Ok(message.get("result").cloned().unwrap_or_default()),
+                };
+                let _ = sender.send(result);
+            }
+            // Request made by the server
+            (Some(method), Some(id)) => {
+                let result = match method {
+                    "workspace/configuration" => {
+                        let items = message
+                            .pointer("/params/items")
+                            .and_then(Value::as_array)
+                            .map_or(0, Vec::len);
+                        Value::Array(vec![Value::Null; items])
+                    }
+                    _ => Value::Null,
+                };
+                let response = json!({"jsonrpc": "2.0", "id": id, "result": result});
+                if write_message(&mut *stdin.lock().await, &response)
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            (Some(PublishDiagnostics::METHOD), None) => {
+                let params = message.get("params").cloned().unwrap_or_default();
+                if let Ok(params) = serde_json::from_value::<PublishDiagnosticsParams>(params) {
+                    lock(&diagnostics).insert(params.uri, params.diagnostics);
+                    diagnostics_updated.notify_waiters();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    alive.store(false, Ordering::SeqCst);
+    // Dropping the senders fails all the requests still waiting for a response.
+    lock(&pending).clear();
+}
@@ -0,0 +1,107 @@
+use std::path::Path;
+
+/// Describes how to start the language server for a set of file extensions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerConfig {
+    /// Unique name of the server, used to share one server between all the
+    /// extensions it handles.
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub extensions: Vec<String>,
+}
+
+impl ServerConfig {
+    pub fn new(name: impl ToString, command: impl ToString) -> Self {
+        Self {
+            name: name.to_string(),
+            command: command.to_string(),
+            args: vec![],
+            extensions: vec![],
+        }
+    }
+
+    pub fn args<S: ToString>(mut self, args: impl IntoIterator<Item = S>) -> Self {
+        self.args = args.into_iter().map(|arg| arg.to_string()).collect();
+        self
+    }
+
+    pub fn extensions<S: ToString>(mut self, extensions: impl IntoIterator<Item = S>) -> Self {
+        self.extensions = extensions.into_iter().map(|ext| ext.to_string()).collect();
+        self
+    }
+
+    /// Language servers that are used when none are configured explicitly.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new("rust-analyzer", "rust-analyzer").extensions(["rs"]),
+            Self::new("typescript-language-server", "typescript-language-server")
+                .args(["--stdio"])
+                .extensions(["ts", "tsx", "js", "jsx", "mjs", "cjs"]),
+            Self::new("pyright", "pyright-langserver")
+                .args(["--stdio"])
+                .extensions(["py"]),
+            Self::new("gopls", "gopls").extensions(["go"]),
+            Self::new("clangd", "clangd").extensions(["c", "h", "cc", "cpp", "cxx", "hpp"]),
+        ]
+    }
+
+    /// Whether the server handles the given file.
+    pub fn handles(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| self.extensions.iter().any(|e| e == ext))
+    }
+}
+
+/// Returns the LSP language identifier of the given file.
+pub fn language_id(path: &Path) -> String {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+
+    match extension {
+        "rs" => "rust",
+        "py" => "python",
+        "ts" => "typescript",
+        "tsx" => "typescriptreact",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "javascriptreact",
+        "h" => "c",
+        "cc" | "cxx" | "hpp" => "cpp",
+        extension => extension,
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_default_server_for_extension() {
+        let fixture = ServerConfig::defaults();
+
+        let actual = fixture
+            .iter()
+            .find(|server| server.handles(Path::new("/src/app.tsx")))
+            .map(|server| server.name.as_str());
+
+        let expected = Some("typescript-language-server");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_language_id() {
+        let actual = ["main.rs", "app.tsx", "lib.h", "main.go"]
+            .into_iter()
+            .map(|path| language_id(Path::new(path)))
+            .collect::<Vec<_>>();
+
+        let expected = vec!["rust", "typescriptreact", "c", "go"];
+        assert_eq!(actual, expected);
+    }
+}
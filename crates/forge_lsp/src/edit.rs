@@ -0,0 +1,171 @@
+use anyhow::bail;
+use lsp_types::{DocumentChangeOperation, DocumentChanges, OneOf, TextEdit, Url, WorkspaceEdit};
+
+use crate::position::byte_offset;
+
+/// Flattens a workspace edit into the text edits of every affected document.
+/// Resource operations (creating, renaming or deleting files) are not
+/// supported.
+pub fn text_edits(edit: WorkspaceEdit) -> anyhow::Result<Vec<(Url, Vec<TextEdit>)>> {
+    let mut changes = edit
+        .changes
+        .unwrap_or_default()
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    let document_edits = match edit.document_changes {
+        None => vec![],
+        Some(DocumentChanges::Edits(edits)) => edits,
+        Some(DocumentChanges::Operations(operations)) => operations
+            .into_iter()
+            .map(|operation| match operation {
+                DocumentChangeOperation::Edit(edit) => Ok(edit),
+                DocumentChangeOperation::Op(_) => {
+                    bail!("Workspace edits that create, rename or delete files are not supported")
+                }
+            })
+            .collect::<anyhow::Result<_>>()?,
+    };
+
+    for document_edit in document_edits {
+        let edits = document_edit
+            .edits
+            .into_iter()
+            .map(|edit| match edit {
+                OneOf::Left(edit) => edit,
+                OneOf::Right(annotated) => annotated.text_edit,
+            })
+            .collect();
+        changes.push((document_edit.text_document.uri, edits));
+    }
+
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(changes)
+}
+
+/// Applies the text edits to the text, failing when they overlap as the LSP
+/// specification doesn't allow it. Edits inserting at the same position are
+/// inserted in their order.
+pub fn apply_text_edits(text: &str, edits: &[TextEdit]) -> anyhow::Result<String> {
+    let mut ranges = edits
+        .iter()
+        .map(|edit| {
+            let start = byte_offset(text, edit.range.start);
+            let end = byte_offset(text, edit.range.end).max(start);
+            (start, end, edit.new_text.as_str())
+        })
+        .collect::<Vec<_>>();
+    // note: the sort is stable, so that the inserts at the same position keep
+    // their order.
+    ranges.sort_by_key(|(start, end, _)| (*start, *end));
+    if ranges.windows(2).any(|pair| pair[1].0 < pair[0].1) {
+        bail!("The language server returned overlapping edits");
+    }
+
+    // Apply from the end so that earlier offsets stay valid.
+    let mut result = text.to_string();
+    for (start, end, new_text) in ranges.into_iter().rev() {
+        result.replace_range(start..end, new_text);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use lsp_types::{
+        OptionalVersionedTextDocumentIdentifier, Position, Range, ResourceOp, TextDocumentEdit,
+    };
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn edit(line: u32, start: u32, end: u32, new_text: &str) -> TextEdit {
+        TextEdit {
+            range: Range {
+                start: Position { line, character: start },
+                end: Position { line, character: end },
+            },
+            new_text: new_text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_apply_text_edits() {
+        let fixture = "let foo = 1;\nprintln!(\"{}\", foo);\n";
+
+        let actual =
+            apply_text_edits(fixture, &[edit(1, 15, 18, "bar"), edit(0, 4, 7, "bar")]).unwrap();
+
+        let expected = "let bar = 1;\nprintln!(\"{}\", bar);\n";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_apply_text_edits_keeps_the_order_of_inserts() {
+        let fixture = "let foo = 1;\n";
+
+        let actual = apply_text_edits(
+            fixture,
+            &[
+                edit(0, 4, 4, "mut "),
+                edit(0, 4, 4, "r#"),
+                edit(0, 4, 7, "bar"),
+            ],
+        )
+        .unwrap();
+
+        let expected = "let mut r#bar = 1;\n";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_apply_text_edits_rejects_overlapping_edits() {
+        let fixture = "let foo = 1;\n";
+
+        let actual = apply_text_edits(fixture, &[edit(0, 4, 7, "bar"), edit(0, 6, 8, "x")]);
+
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_text_edits_merges_changes_and_document_changes() {
+        let a = Url::parse("file:///a.rs").unwrap();
+        let b = Url::parse("file:///b.rs").unwrap();
+        let fixture = WorkspaceEdit {
+            changes: Some(HashMap::from([(b.clone(), vec![edit(0, 0, 1, "x")])])),
+            document_changes: Some(DocumentChanges::Edits(vec![TextDocumentEdit {
+                text_document: OptionalVersionedTextDocumentIdentifier {
+                    uri: a.clone(),
+                    version: None,
+                },
+                edits: vec![OneOf::Left(edit(0, 0, 1, "y"))],
+            }])),
+            change_annotations: None,
+        };
+
+        let actual = text_edits(fixture).unwrap();
+
+        let expected = vec![(a, vec![edit(0, 0, 1, "y")]), (b, vec![edit(0, 0, 1, "x")])];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_text_edits_rejects_resource_operations() {
+        let fixture = WorkspaceEdit {
+            changes: None,
+            document_changes: Some(DocumentChanges::Operations(vec![
+                DocumentChangeOperation::Op(ResourceOp::Delete(lsp_types::DeleteFile {
+                    uri: Url::parse("file:///a.rs").unwrap(),
+                    options: None,
+                })),
+            ])),
+            change_annotations: None,
+        };
+
+        let actual = text_edits(fixture);
+
+        assert!(actual.is_err());
+    }
+}
@@ -0,0 +1,13 @@
+mod client;
+mod config;
+mod edit;
+mod manager;
+mod position;
+mod transport;
+
+pub use client::LspClient;
+pub use config::*;
+pub use edit::apply_text_edits;
+pub use lsp_types;
+pub use manager::*;
+pub use transport::read_message;
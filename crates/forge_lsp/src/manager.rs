@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use lsp_types::request::{GotoDefinition, References, Rename};
+use lsp_types::{
+    Diagnostic, GotoDefinitionParams, GotoDefinitionResponse, Location, ReferenceContext,
+    ReferenceParams, RenameParams, TextDocumentIdentifier, TextDocumentPositionParams, TextEdit,
+};
+use tokio::sync::Mutex;
+
+use crate::client::LspClient;
+use crate::config::ServerConfig;
+use crate::edit::text_edits;
+use crate::position::{from_lsp_position, to_lsp_position};
+
+/// Time to wait for the server to publish diagnostics of a document.
+const DIAGNOSTICS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A one based line and character column within a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub path: PathBuf,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// Starts language servers on demand and routes requests to the server
+/// responsible for a file.
+pub struct LspManager {
+    root: PathBuf,
+    servers: Vec<ServerConfig>,
+    clients: Mutex<HashMap<String, Arc<LspClient>>>,
+}
+
+impl LspManager {
+    /// Creates a manager for the workspace at `root` using the default
+    /// language servers.
+    pub fn new(root: PathBuf) -> Self {
+        Self::with_servers(root, ServerConfig::defaults())
+    }
+
+    pub fn with_servers(root: PathBuf, servers: Vec<ServerConfig>) -> Self {
+        Self { root, servers, clients: Default::default() }
+    }
+
+    /// Returns the client of the server handling the file, starting the server
+    /// if it is not running yet or restarting it once it stopped responding.
+    async fn client(&self, path: &Path) -> anyhow::Result<Arc<LspClient>> {
+        let config = self
+            .servers
+            .iter()
+            .find(|server| server.handles(path))
+            .with_context(|| format!("No language server configured for {}", path.display()))?;
+
+        let mut clients = self.clients.lock().await;
+        if let Some(client) = clients.get(&config.name) {
+            if client.is_alive() {
+                return Ok(client.clone());
+            }
+            clients.remove(&config.name);
+        }
+
+        let client = Arc::new(LspClient::start(config, &self.root).await?);
+        clients.insert(config.name.clone(), client.clone());
+        Ok(client)
+    }
+
+    async fn position_params(
+        &self,
+        path: &Path,
+        line: u32,
+        column: u32,
+    ) -> anyhow::Result<(Arc<LspClient>, TextDocumentPositionParams)> {
+        let client = self.client(path).await?;
+        let (uri, text) = client.sync_document(path).await?;
+        let params = TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri },
+            position: to_lsp_position(&text, line, column),
+        };
+        Ok((client, params))
+    }
+
+    /// Finds where the symbol at the given one based position is defined.
+    pub async fn definition(
+        &self,
+        path: &Path,
+        line: u32,
+        column: u32,
+    ) -> anyhow::Result<Vec<SourceLocation>> {
+        let (client, params) = self.position_params(path, line, column).await?;
+        let response = client
+            .request::<GotoDefinition>(GotoDefinitionParams {
+                text_document_position_params: params,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await?;
+
+        let locations = match response {
+            None => vec![],
+            Some(GotoDefinitionResponse::Scalar(location)) => vec![location],
+            Some(GotoDefinitionResponse::Array(locations)) => locations,
+            Some(GotoDefinitionResponse::Link(links)) => links
+                .into_iter()
+                .map(|link| Location { uri: link.target_uri, range: link.target_selection_range })
+                .collect(),
+        };
+
+        Ok(source_locations(locations).await)
+    }
+
+    /// Finds all the references to the symbol at the given one based position,
+    /// including its declaration.
+    pub async fn references(
+        &self,
+        path: &Path,
+        line: u32,
+        column: u32,
+    ) -> anyhow::Result<Vec<SourceLocation>> {
+        let (client, params) = self.position_params(path, line, column).await?;
+        let locations = client
+            .request::<References>(ReferenceParams {
+                text_document_position: params,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                context: ReferenceContext { include_declaration: true },
+            })
+            .await?
+            .unwrap_or_default();
+
+        Ok(source_locations(locations).await)
+    }
+
+    /// Returns the diagnostics the language server reports for the file.
+    pub async fn diagnostics(&self, path: &Path) -> anyhow::Result<Vec<Diagnostic>> {
+        let client = self.client(path).await?;
+        let (uri, _) = client.sync_document(path).await?;
+        Ok(client.diagnostics(&uri, DIAGNOSTICS_TIMEOUT).await)
+    }
+
+    /// Computes the edits renaming the symbol at the given one based position
+    /// across the workspace, by file. Nothing is written so that the caller
+    /// can check the files and the edits first.
+    pub async fn rename(
+        &self,
+        path: &Path,
+        line: u32,
+        column: u32,
+        new_name: &str,
+    ) -> anyhow::Result<Vec<(PathBuf, Vec<TextEdit>)>> {
+        let (client, params) = self.position_params(path, line, column).await?;
+        let edit = client
+            .request::<Rename>(RenameParams {
+                text_document_position: params,
+                new_name: new_name.to_string(),
+                work_done_progress_params: Default::default(),
+            })
+            .await?
+            .context("The symbol at the given position can not be renamed")?;
+
+        text_edits(edit)?
+            .into_iter()
+            .map(|(uri, edits)| {
+                let path = uri
+                    .to_file_path()
+                    .map_err(|_| anyhow!("Unsupported document url: {}", uri))?;
+                Ok((path, edits))
+            })
+            .collect()
+    }
+
+    /// Sends the content of a file that was changed on disk to its server.
+    pub async fn sync(&self, path: &Path) -> anyhow::Result<()> {
+        self.client(path).await?.sync_document(path).await?;
+        Ok(())
+    }
+}
+
+/// Converts LSP locations into one based locations. Files that can not be read
+/// fall back to the UTF-16 based column reported by the server.
+async fn source_locations(locations: Vec<Location>) -> Vec<SourceLocation> {
+    let mut result = Vec::with_capacity(locations.len());
+    for location in locations {
+        let Ok(path) = location.uri.to_file_path() else {
+            continue;
+        };
+        let text = tokio::fs::read_to_string(&path).await.unwrap_or_default();
+        let (line, column) = from_lsp_position(&text, location.range.start);
+        let column = if text.is_empty() {
+            location.range.start.character + 1
+        } else {
+            column
+        };
+        result.push(SourceLocation { path, line, column });
+    }
+    result
+}
@@ -0,0 +1,100 @@
+use lsp_types::Position;
+
+/// Returns the line with the given zero based index, or an empty string when
+/// the text has fewer lines.
+fn nth_line(text: &str, line: u32) -> &str {
+    text.split('\n').nth(line as usize).unwrap_or_default()
+}
+
+/// Converts a one based line and character column into an LSP position, whose
+/// character offset is counted in UTF-16 code units.
+pub fn to_lsp_position(text: &str, line: u32, column: u32) -> Position {
+    let line = line.saturating_sub(1);
+    let character = nth_line(text, line)
+        .chars()
+        .take(column.saturating_sub(1) as usize)
+        .map(|c| c.len_utf16() as u32)
+        .sum();
+
+    Position { line, character }
+}
+
+/// Converts an LSP position into a one based line and character column.
+pub fn from_lsp_position(text: &str, position: Position) -> (u32, u32) {
+    let mut units = 0;
+    let mut column = 0;
+    for c in nth_line(text, position.line).chars() {
+        if units >= position.character {
+            break;
+        }
+        units += c.len_utf16() as u32;
+        column += 1;
+    }
+
+    (position.line + 1, column + 1)
+}
+
+/// Converts an LSP position into a byte offset within the text. Positions
+/// past the end of a line or of the text are clamped.
+pub fn byte_offset(text: &str, position: Position) -> usize {
+    let mut offset = 0;
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        if index as u32 == position.line {
+            let mut units = 0;
+            for (byte, c) in line.char_indices() {
+                if units >= position.character || c == '\n' {
+                    return offset + byte;
+                }
+                units += c.len_utf16() as u32;
+            }
+            return offset + line.len();
+        }
+        offset += line.len();
+    }
+
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const FIXTURE: &str = "fn main() {\n    let ñ = \"😀\";\n}\n";
+
+    #[test]
+    fn test_to_lsp_position() {
+        let actual = to_lsp_position(FIXTURE, 2, 9);
+        let expected = Position { line: 1, character: 8 };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_to_lsp_position_counts_utf16() {
+        let actual = to_lsp_position(FIXTURE, 2, 16);
+        let expected = Position { line: 1, character: 16 };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_from_lsp_position() {
+        let actual = from_lsp_position(FIXTURE, Position { line: 1, character: 16 });
+        let expected = (2, 16);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_byte_offset() {
+        let actual = byte_offset(FIXTURE, Position { line: 1, character: 8 });
+        let expected = FIXTURE.find('ñ').unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_byte_offset_clamps_to_line_end() {
+        let actual = byte_offset(FIXTURE, Position { line: 0, character: 100 });
+        let expected = FIXTURE.find('\n').unwrap();
+        assert_eq!(actual, expected);
+    }
+}
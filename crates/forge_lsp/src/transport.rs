@@ -0,0 +1,117 @@
+use anyhow::Context;
+use serde_json::Value;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Reads a single JSON-RPC message framed with a `Content-Length` header.
+/// Returns `None` once the stream is closed.
+pub async fn read_message<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+) -> anyhow::Result<Option<Value>> {
+    match read_frame(reader).await? {
+        Some(content) => Ok(Some(serde_json::from_slice(&content)?)),
+        None => Ok(None),
+    }
+}
+
+/// Reads the content of a single frame without parsing it, so that a
+/// malformed message can be skipped without losing track of the stream.
+/// Returns `None` once the stream is closed.
+pub async fn read_frame<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+) -> anyhow::Result<Option<Vec<u8>>> {
+    let mut content_length = None;
+    let mut header = String::new();
+    loop {
+        header.clear();
+        if reader.read_line(&mut header).await? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = Some(value.trim().parse::<usize>()?);
+            }
+        }
+    }
+
+    let content_length = content_length.context("Message is missing the Content-Length header")?;
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content).await?;
+
+    Ok(Some(content))
+}
+
+/// Writes a single JSON-RPC message framed with a `Content-Length` header.
+pub async fn write_message<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    message: &Value,
+) -> anyhow::Result<()> {
+    let content = serde_json::to_string(message)?;
+    let header = format!("Content-Length: {}\r\n\r\n", content.len());
+
+    writer.write_all(header.as_bytes()).await?;
+    writer.write_all(content.as_bytes()).await?;
+    writer.flush().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tokio::io::BufReader;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_message_round_trip() {
+        let fixture = json!({"jsonrpc": "2.0", "id": 1, "method": "initialize"});
+
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &fixture).await.unwrap();
+        write_message(&mut buffer, &fixture).await.unwrap();
+        let mut reader = BufReader::new(buffer.as_slice());
+
+        let actual = vec![
+            read_message(&mut reader).await.unwrap(),
+            read_message(&mut reader).await.unwrap(),
+            read_message(&mut reader).await.unwrap(),
+        ];
+        let expected = vec![Some(fixture.clone()), Some(fixture), None];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_read_frame_after_malformed_content() {
+        let fixture = json!({"jsonrpc": "2.0", "id": 1});
+
+        let mut buffer = b"Content-Length: 5\r\n\r\n{nope".to_vec();
+        write_message(&mut buffer, &fixture).await.unwrap();
+        let mut reader = BufReader::new(buffer.as_slice());
+
+        let actual = vec![
+            read_frame(&mut reader).await.unwrap(),
+            read_frame(&mut reader).await.unwrap(),
+        ];
+        let expected = vec![
+            Some(b"{nope".to_vec()),
+            Some(serde_json::to_vec(&fixture).unwrap()),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_missing_content_length() {
+        let fixture = b"Content-Type: application/json\r\n\r\n{}".as_slice();
+
+        let actual = read_message(&mut BufReader::new(fixture)).await;
+
+        assert!(actual.is_err());
+    }
+}
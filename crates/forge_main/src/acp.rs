@@ -0,0 +1,705 @@
+//! `forge acp`: serves the Agent Client Protocol on stdio, so that editors
+//! like Zed can drive forge as an external agent. Messages are JSON-RPC 2.0,
+//! one per line.
+//!
+//! Every session is a conversation. A prompt streams the text and the tool
+//! calls of the agents as `session/update` notifications, along with the
+//! diffs of the files they change, and is answered once the turn ends. The
+//! questions of the agents and the approval of their plans are asked with
+//! `session/request_permission`. The files attached to a prompt are read
+//! through the editor when it supports it, so that their unsaved changes are
+//! seen, while the tools still read and write the files on disk.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context as _;
+use forge_api::{
+    AgentId, AgentMessage, AgentMode, ChatRequest, ChatResponse, Config, ConversationId, Image,
+    Plan, Question, ToolCallFull, ToolCallId, ToolName, API,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::{Stream, StreamExt};
+
+use crate::batch;
+use crate::rpc::{self, notification, parse, response, Incoming, RpcError, INVALID_REQUEST};
+use crate::ui::NO_ANSWER;
+
+const PROTOCOL_VERSION: u64 = 1;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InitializeParams {
+    #[serde(default)]
+    client_capabilities: ClientCapabilities,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ClientCapabilities {
+    #[serde(default)]
+    fs: FsCapabilities,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FsCapabilities {
+    #[serde(default)]
+    read_text_file: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionParams {
+    session_id: ConversationId,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptParams {
+    session_id: ConversationId,
+    prompt: Vec<ContentBlock>,
+}
+
+/// A part of a prompt.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text {
+        text: String,
+    },
+    Image {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+    /// A file the user mentioned, which is read
+    ResourceLink {
+        uri: String,
+        name: String,
+    },
+    /// A file along with its content
+    Resource {
+        resource: EmbeddedResource,
+    },
+    /// Audio, which isn't supported
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct EmbeddedResource {
+    uri: String,
+    /// Unset for binary files
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Session {
+    /// Set when the user cancels the prompt in progress
+    cancelled: bool,
+}
+
+pub struct AcpServer<A> {
+    api: Arc<A>,
+    /// Configuration resolved from the flags, the environment and the config
+    /// files
+    config: Config,
+    workflow: Option<PathBuf>,
+    /// Messages written to stdout, in order
+    out: mpsc::UnboundedSender<Value>,
+    /// Requests sent to the editor, waiting for its response
+    pending: Mutex<HashMap<u64, oneshot::Sender<Result<Value, RpcError>>>>,
+    next_id: AtomicU64,
+    /// Whether the editor reads the files, along with their unsaved changes
+    read_text_file: AtomicBool,
+    sessions: Mutex<HashMap<ConversationId, Session>>,
+}
+
+impl<A: API + Send + Sync + 'static> AcpServer<A> {
+    /// Serves the editor until it closes stdin.
+    pub async fn serve(
+        api: Arc<A>,
+        config: Config,
+        workflow: Option<PathBuf>,
+    ) -> anyhow::Result<()> {
+        let env = api.environment();
+        let _guard = forge_tracker::init_tracing(env.log_path())?;
+        let config = config.or(env.config.clone());
+        // note: the output of the tools is redirected to stderr, so that it
+        // can't corrupt the messages.
+        let out = rpc::writer(batch::take_stdout()?, write_line);
+
+        let server = Arc::new(Self {
+            api,
+            config,
+            workflow,
+            out,
+            pending: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+            read_text_file: AtomicBool::new(false),
+            sessions: Mutex::new(HashMap::new()),
+        });
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let message = match serde_json::from_str::<Value>(&line) {
+                Ok(message) => message,
+                Err(error) => {
+                    server.send(response(
+                        Value::Null,
+                        Err(RpcError { code: INVALID_REQUEST, message: error.to_string() }),
+                    ));
+                    continue;
+                }
+            };
+            if message.get("method").is_none() {
+                server.resolve(message);
+                continue;
+            }
+            match serde_json::from_value::<Incoming>(message) {
+                Ok(incoming) => {
+                    // note: requests are handled concurrently, eg: a prompt
+                    // can be cancelled while it's running.
+                    let server = server.clone();
+                    tokio::spawn(async move { server.dispatch(incoming).await });
+                }
+                Err(error) => server.send(response(
+                    Value::Null,
+                    Err(RpcError { code: INVALID_REQUEST, message: error.to_string() }),
+                )),
+            }
+        }
+        Ok(())
+    }
+
+    fn send(&self, message: Value) {
+        // note: the writer only stops once stdout is closed, when there's no
+        // one left to tell.
+        let _ = self.out.send(message);
+    }
+
+    /// Hands the response of the editor to the request waiting for it.
+    fn resolve(&self, message: Value) {
+        let Some(id) = message.get("id").and_then(Value::as_u64) else {
+            return;
+        };
+        let Some(pending) = self.pending.lock().unwrap().remove(&id) else {
+            return;
+        };
+        let result = match message.get("error") {
+            Some(error) => Err(serde_json::from_value(error.clone())
+                .unwrap_or(RpcError { code: rpc::INTERNAL_ERROR, message: error.to_string() })),
+            None => Ok(message.get("result").cloned().unwrap_or_default()),
+        };
+        let _ = pending.send(result);
+    }
+
+    /// Sends a request to the editor and waits for its response.
+    async fn request(&self, method: &str, params: Value) -> anyhow::Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, sender);
+        self.send(rpc::request(id, method, params));
+        receiver
+            .await
+            .context("The editor is disconnected")?
+            .map_err(|error| anyhow::anyhow!("{method} failed: {}", error.message))
+    }
+
+    async fn dispatch(self: Arc<Self>, incoming: Incoming) {
+        let result = self.handle(&incoming.method, incoming.params).await;
+        match incoming.id {
+            Some(id) => self.send(response(id, result)),
+            None => {
+                if let Err(error) = result {
+                    tracing::warn!(method = %incoming.method, error = %error.message, "Notification failed");
+                }
+            }
+        }
+    }
+
+    async fn handle(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        let result = match method {
+            "initialize" => {
+                let params: InitializeParams = parse(params)?;
+                self.read_text_file.store(
+                    params.client_capabilities.fs.read_text_file,
+                    Ordering::SeqCst,
+                );
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "agentCapabilities": {
+                        "loadSession": false,
+                        "promptCapabilities": {"image": true, "audio": false, "embeddedContext": true}
+                    },
+                    "authMethods": []
+                })
+            }
+            // note: the keys are set in the environment or with `forge auth`.
+            "authenticate" => Value::Null,
+            "session/new" => json!({"sessionId": self.new_session().await?}),
+            "session/prompt" => self.prompt(parse(params)?).await?,
+            "session/cancel" => {
+                let SessionParams { session_id } = parse(params)?;
+                if let Some(session) = self.sessions.lock().unwrap().get_mut(&session_id) {
+                    session.cancelled = true;
+                }
+                self.api.cancel(&session_id);
+                Value::Null
+            }
+            _ => return Err(RpcError::method_not_found(method)),
+        };
+        Ok(result)
+    }
+
+    async fn new_session(&self) -> anyhow::Result<ConversationId> {
+        let mut workflow = self.api.load(self.workflow.as_deref()).await?;
+        self.config.apply(&mut workflow);
+        let conversation_id = self.api.init(workflow).await?;
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(conversation_id.clone(), Session::default());
+        Ok(conversation_id)
+    }
+
+    fn cancelled(&self, session_id: &ConversationId) -> bool {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .is_some_and(|session| session.cancelled)
+    }
+
+    /// Runs the turn of the prompt, along with the plan it comes up with once
+    /// the user approves it.
+    async fn prompt(&self, params: PromptParams) -> anyhow::Result<Value> {
+        let session_id = params.session_id;
+        self.sessions
+            .lock()
+            .unwrap()
+            .get_mut(&session_id)
+            .with_context(|| format!("Unknown session: {session_id}"))?
+            .cancelled = false;
+
+        let (mut content, mut images) = self.content(&session_id, params.prompt).await?;
+        loop {
+            let chat = ChatRequest {
+                content,
+                conversation_id: session_id.clone(),
+                agent: None,
+                images: std::mem::take(&mut images),
+            };
+            let result = match self.api.chat(chat).await {
+                Ok(stream) => self.stream(&session_id, stream).await,
+                Err(error) => Err(error),
+            };
+            if self.cancelled(&session_id) {
+                return Ok(json!({"stopReason": "cancelled"}));
+            }
+            let Some(plan) = result? else {
+                return Ok(json!({"stopReason": "end_turn"}));
+            };
+            if !self.approve(&session_id, &plan).await? {
+                return Ok(json!({"stopReason": "end_turn"}));
+            }
+            self.api.set_mode(&session_id, AgentMode::Act).await?;
+            content = format!("The plan is approved, carry it out:\n{plan}");
+        }
+    }
+
+    /// The message of the prompt along with its images. The files it
+    /// mentions are attached.
+    async fn content(
+        &self,
+        session_id: &ConversationId,
+        blocks: Vec<ContentBlock>,
+    ) -> anyhow::Result<(String, Vec<Image>)> {
+        let mut parts = Vec::new();
+        let mut images = Vec::new();
+        for block in blocks {
+            match block {
+                ContentBlock::Text { text } => parts.push(text),
+                ContentBlock::Image { data, mime_type } => images.push(Image::new(mime_type, data)),
+                ContentBlock::ResourceLink { uri, name } => match file_path(&uri) {
+                    Some(path) => {
+                        let content = self.read_file(session_id, &path).await?;
+                        parts.push(attachment(&path.display().to_string(), &content));
+                    }
+                    None => parts.push(format!("[{name}]({uri})")),
+                },
+                ContentBlock::Resource { resource } => {
+                    if let Some(text) = resource.text {
+                        let path = file_path(&resource.uri)
+                            .map_or(resource.uri, |path| path.display().to_string());
+                        parts.push(attachment(&path, &text));
+                    }
+                }
+                ContentBlock::Other => {}
+            }
+        }
+        Ok((parts.join("\n\n"), images))
+    }
+
+    /// Reads the file through the editor when it can, which sees the unsaved
+    /// changes, otherwise from disk.
+    async fn read_file(&self, session_id: &ConversationId, path: &Path) -> anyhow::Result<String> {
+        if !self.read_text_file.load(Ordering::SeqCst) {
+            return tokio::fs::read_to_string(path)
+                .await
+                .with_context(|| format!("Failed to read {}", path.display()));
+        }
+        let result = self
+            .request(
+                "fs/read_text_file",
+                json!({"sessionId": session_id, "path": path}),
+            )
+            .await?;
+        result
+            .get("content")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .with_context(|| format!("The editor didn't return the content of {}", path.display()))
+    }
+
+    /// Streams the chat responses as the updates of the session, returning
+    /// the plan an agent submitted, if any.
+    async fn stream(
+        &self,
+        session_id: &ConversationId,
+        mut stream: impl Stream<Item = anyhow::Result<AgentMessage<ChatResponse>>> + Unpin,
+    ) -> anyhow::Result<Option<Plan>> {
+        let mut updates = Updates::default();
+        let mut plan = None;
+        while let Some(message) = stream.next().await {
+            let message = message?;
+            if let Some(update) = updates.convert(&message) {
+                let params = json!({"sessionId": session_id, "update": update});
+                self.send(notification("session/update", params));
+            }
+            match &message.message {
+                ChatResponse::Question(question) => {
+                    let tool_call_id = updates.last_call(&message.agent);
+                    let answer = self.ask(session_id, tool_call_id, question).await?;
+                    self.api.answer(session_id, answer)?;
+                }
+                ChatResponse::Plan(submitted) => plan = Some(submitted.clone()),
+                _ => {}
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Asks the user the question of an agent, with an option for each of its
+    /// answers. ACP has no way to answer with free text, so the agent is told
+    /// to carry on on its own when there's no option.
+    async fn ask(
+        &self,
+        session_id: &ConversationId,
+        tool_call_id: String,
+        question: &Question,
+    ) -> anyhow::Result<String> {
+        if question.options.is_empty() {
+            return Ok(NO_ANSWER.to_string());
+        }
+        let options = question
+            .options
+            .iter()
+            .enumerate()
+            .map(|(index, option)| {
+                json!({"optionId": (index + 1).to_string(), "name": option, "kind": "allow_once"})
+            })
+            .collect::<Vec<_>>();
+        let outcome = self
+            .request(
+                "session/request_permission",
+                json!({
+                    "sessionId": session_id,
+                    "toolCall": {"toolCallId": tool_call_id, "title": question.question},
+                    "options": options
+                }),
+            )
+            .await?;
+        Ok(match selected(&outcome) {
+            Some(option) => question.answer(&option),
+            None => NO_ANSWER.to_string(),
+        })
+    }
+
+    /// Asks the user to approve the plan, which the agents then carry out.
+    async fn approve(&self, session_id: &ConversationId, plan: &Plan) -> anyhow::Result<bool> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let outcome = self
+            .request(
+                "session/request_permission",
+                json!({
+                    "sessionId": session_id,
+                    "toolCall": {"toolCallId": format!("plan-{id}"), "title": format!("Approve the plan: {}", plan.summary)},
+                    "options": [
+                        {"optionId": "approve", "name": "Carry out the plan", "kind": "allow_once"},
+                        {"optionId": "reject", "name": "Keep planning", "kind": "reject_once"}
+                    ]
+                }),
+            )
+            .await?;
+        Ok(selected(&outcome).as_deref() == Some("approve"))
+    }
+}
+
+/// Converts the chat responses of the agents into the updates of a session.
+#[derive(Debug, Default)]
+struct Updates {
+    /// Last tool call of every agent
+    last_calls: HashMap<AgentId, String>,
+}
+
+impl Updates {
+    fn convert(&mut self, message: &AgentMessage<ChatResponse>) -> Option<Value> {
+        match &message.message {
+            ChatResponse::Text(text) => Some(json!({
+                "sessionUpdate": "agent_message_chunk",
+                "content": {"type": "text", "text": text}
+            })),
+            ChatResponse::ToolCallStart(tool_call) => {
+                let id = call_id(&message.agent, tool_call.call_id.as_ref(), &tool_call.name);
+                self.last_calls.insert(message.agent.clone(), id.clone());
+                let locations = tool_call
+                    .arguments
+                    .get("path")
+                    .and_then(Value::as_str)
+                    .map(|path| vec![json!({"path": path})])
+                    .unwrap_or_default();
+                Some(json!({
+                    "sessionUpdate": "tool_call",
+                    "toolCallId": id,
+                    "title": title(tool_call),
+                    "kind": kind(&tool_call.name),
+                    "status": "in_progress",
+                    "rawInput": tool_call.arguments,
+                    "locations": locations
+                }))
+            }
+            ChatResponse::ToolCallEnd(result) => {
+                let id = call_id(&message.agent, result.call_id.as_ref(), &result.name);
+                let text = |content: &Option<Vec<u8>>| {
+                    String::from_utf8_lossy(content.as_deref().unwrap_or_default()).into_owned()
+                };
+                let content = if result.files.is_empty() {
+                    vec![
+                        json!({"type": "content", "content": {"type": "text", "text": result.content}}),
+                    ]
+                } else {
+                    result
+                        .files
+                        .iter()
+                        .map(|file| {
+                            json!({"type": "diff", "path": file.path, "oldText": text(&file.old), "newText": text(&file.new)})
+                        })
+                        .collect()
+                };
+                Some(json!({
+                    "sessionUpdate": "tool_call_update",
+                    "toolCallId": id,
+                    "status": if result.is_error { "failed" } else { "completed" },
+                    "content": content
+                }))
+            }
+            ChatResponse::Plan(plan) => {
+                let entries = plan
+                    .steps
+                    .iter()
+                    .map(|step| {
+                        json!({"content": step.description, "priority": "medium", "status": "pending"})
+                    })
+                    .collect::<Vec<_>>();
+                Some(json!({"sessionUpdate": "plan", "entries": entries}))
+            }
+            _ => None,
+        }
+    }
+
+    /// The last tool call of the agent, eg: the one asking a question.
+    fn last_call(&self, agent: &AgentId) -> String {
+        self.last_calls
+            .get(agent)
+            .cloned()
+            .unwrap_or_else(|| format!("{}/question", agent.as_str()))
+    }
+}
+
+/// The id of the tool call, made up from the agent and the tool when the
+/// provider didn't give one.
+fn call_id(agent: &AgentId, call_id: Option<&ToolCallId>, name: &ToolName) -> String {
+    call_id.map_or_else(
+        || format!("{}/{}", agent.as_str(), name.as_str()),
+        |call_id| call_id.as_str().to_string(),
+    )
+}
+
+/// The tool along with the file or command it's called on, eg: `fs_read
+/// src/main.rs`.
+fn title(tool_call: &ToolCallFull) -> String {
+    let name = tool_call.name.as_str();
+    let name = name.strip_prefix("tool_forge_").unwrap_or(name);
+    ["path", "command", "url", "pattern"]
+        .iter()
+        .find_map(|key| tool_call.arguments.get(key).and_then(Value::as_str))
+        .map_or(name.to_string(), |target| format!("{name} {target}"))
+}
+
+/// The kind of the tool, which editors pick its icon by.
+fn kind(name: &ToolName) -> &'static str {
+    match name.as_str() {
+        "tool_forge_fs_read"
+        | "tool_forge_fs_list"
+        | "tool_forge_fs_info"
+        | "tool_forge_code_outline" => "read",
+        "tool_forge_fs_create"
+        | "tool_forge_fs_patch"
+        | "tool_forge_fs_scaffold"
+        | "tool_forge_lsp_rename" => "edit",
+        "tool_forge_fs_remove" => "delete",
+        "tool_forge_fs_move" => "move",
+        "tool_forge_fs_search"
+        | "tool_forge_code_query"
+        | "tool_forge_code_todos"
+        | "tool_forge_lsp_references"
+        | "tool_forge_lsp_definition" => "search",
+        "tool_forge_process_shell"
+        | "tool_forge_test_run"
+        | "tool_forge_test_coverage"
+        | "tool_forge_test_bench"
+        | "tool_forge_project_check" => "execute",
+        "tool_forge_process_think" => "think",
+        "tool_forge_net_fetch" => "fetch",
+        _ => "other",
+    }
+}
+
+/// The option the user picked in the outcome of a permission request, unless
+/// they cancelled it.
+fn selected(outcome: &Value) -> Option<String> {
+    let outcome = outcome.get("outcome")?;
+    if outcome.get("outcome")?.as_str()? != "selected" {
+        return None;
+    }
+    outcome.get("optionId")?.as_str().map(str::to_string)
+}
+
+fn file_path(uri: &str) -> Option<PathBuf> {
+    uri.strip_prefix("file://").map(PathBuf::from)
+}
+
+fn attachment(path: &str, content: &str) -> String {
+    format!("<file path=\"{path}\">\n{}\n</file>", content.trim_end())
+}
+
+/// Writes a single message on a line of its own.
+fn write_line(out: &mut dyn Write, message: &Value) -> anyhow::Result<()> {
+    serde_json::to_writer(&mut *out, message)?;
+    out.write_all(b"\n")?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_api::{FileChange, ToolResult};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn message(message: ChatResponse) -> AgentMessage<ChatResponse> {
+        AgentMessage { agent: AgentId::new("software-engineer"), message }
+    }
+
+    #[test]
+    fn test_convert_tool_call() {
+        let path = "/cat.txt";
+        let call = ToolCallFull::new(ToolName::new("tool_forge_fs_patch"))
+            .arguments(json!({"path": path}));
+        let mut updates = Updates::default();
+
+        let actual = updates
+            .convert(&message(ChatResponse::ToolCallStart(call.clone())))
+            .unwrap();
+        let expected = json!({
+            "sessionUpdate": "tool_call",
+            "toolCallId": "software-engineer/tool_forge_fs_patch",
+            "title": format!("fs_patch {path}"),
+            "kind": "edit",
+            "status": "in_progress",
+            "rawInput": {"path": path},
+            "locations": [{"path": path}]
+        });
+        assert_eq!(actual, expected);
+
+        let mut result = ToolResult::from(call).success("patched");
+        result.files = vec![FileChange::new(
+            path.into(),
+            Some(b"Juniper\n".to_vec()),
+            Some(b"Pepper\n".to_vec()),
+        )];
+        let actual = updates
+            .convert(&message(ChatResponse::ToolCallEnd(result)))
+            .unwrap();
+        let expected = json!({
+            "sessionUpdate": "tool_call_update",
+            "toolCallId": "software-engineer/tool_forge_fs_patch",
+            "status": "completed",
+            "content": [{"type": "diff", "path": path, "oldText": "Juniper\n", "newText": "Pepper\n"}]
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_convert_text() {
+        let actual = Updates::default().convert(&message(ChatResponse::Text("Done".to_string())));
+        let expected = json!({
+            "sessionUpdate": "agent_message_chunk",
+            "content": {"type": "text", "text": "Done"}
+        });
+        assert_eq!(actual, Some(expected));
+    }
+
+    #[test]
+    fn test_content_blocks() {
+        let actual: Vec<ContentBlock> = serde_json::from_value(json!([
+            {"type": "text", "text": "Rename the cat"},
+            {"type": "resource_link", "uri": "file:///src/cat.rs", "name": "cat.rs"},
+            {"type": "audio", "data": "", "mimeType": "audio/wav"}
+        ]))
+        .unwrap();
+        let expected = vec![
+            ContentBlock::Text { text: "Rename the cat".to_string() },
+            ContentBlock::ResourceLink {
+                uri: "file:///src/cat.rs".to_string(),
+                name: "cat.rs".to_string(),
+            },
+            ContentBlock::Other,
+        ];
+        assert_eq!(actual, expected);
+        assert_eq!(
+            file_path("file:///src/cat.rs"),
+            Some(PathBuf::from("/src/cat.rs"))
+        );
+    }
+
+    #[test]
+    fn test_selected() {
+        let actual = [
+            selected(&json!({"outcome": {"outcome": "selected", "optionId": "2"}})),
+            selected(&json!({"outcome": {"outcome": "cancelled"}})),
+        ];
+        assert_eq!(actual, [Some("2".to_string()), None]);
+    }
+}
@@ -0,0 +1,135 @@
+//! Progress of the agents that run on behalf of others, eg: spawned ones or
+//! those of transforms, rendered as a tree as they go. The agents the user
+//! talks to are the root of the tree and aren't shown, their output is.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use forge_api::{AgentId, AgentMessage, ChatResponse};
+
+struct Node {
+    depth: usize,
+    turns: u64,
+}
+
+/// Nesting of the agents that are running, from their start and completion
+/// events.
+#[derive(Default)]
+pub struct AgentTree {
+    running: HashMap<AgentId, Node>,
+}
+
+impl AgentTree {
+    /// Records the progress event, returning the line to show for it if any.
+    /// The turns of a nested agent are collapsed into the line of its
+    /// completion, unless `expanded` is set.
+    pub fn record(
+        &mut self,
+        message: &AgentMessage<ChatResponse>,
+        expanded: bool,
+    ) -> Option<String> {
+        let agent = &message.agent;
+        match &message.message {
+            ChatResponse::AgentStarted { parent, .. } => {
+                let depth = parent.as_ref().map_or(0, |parent| self.depth(parent) + 1);
+                self.running.insert(agent.clone(), Node { depth, turns: 0 });
+                (depth > 0).then(|| format!("{}▸ {agent}", indent(depth)))
+            }
+            ChatResponse::AgentTurn(turn) => {
+                let node = self.running.get_mut(agent)?;
+                node.turns = *turn;
+                (node.depth > 0 && expanded).then(|| format!("{}  turn {turn}", indent(node.depth)))
+            }
+            ChatResponse::AgentCompleted { tokens, duration } => {
+                let node = self.running.remove(agent)?;
+                (node.depth > 0).then(|| {
+                    format!(
+                        "{}✓ {agent} ({} turns, {tokens} tokens, {})",
+                        indent(node.depth),
+                        node.turns,
+                        seconds(duration)
+                    )
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn depth(&self, agent: &AgentId) -> usize {
+        self.running.get(agent).map_or(0, |node| node.depth)
+    }
+}
+
+fn indent(depth: usize) -> String {
+    "  ".repeat(depth - 1)
+}
+
+fn seconds(duration: &Duration) -> String {
+    format!("{:.1}s", duration.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_api::ModelId;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn message(agent: &str, message: ChatResponse) -> AgentMessage<ChatResponse> {
+        AgentMessage { agent: AgentId::new(agent), message }
+    }
+
+    fn started(parent: Option<AgentId>) -> ChatResponse {
+        ChatResponse::AgentStarted { parent, model: ModelId::new("anthropic/claude-3.7-sonnet") }
+    }
+
+    #[test]
+    fn test_record() {
+        let engineer = AgentId::new("engineer");
+        let spawned = AgentId::new("engineer.spawn");
+        let events = [
+            message("engineer", started(None)),
+            message("engineer", ChatResponse::AgentTurn(1)),
+            message("engineer.spawn", started(Some(engineer))),
+            message("engineer.spawn", ChatResponse::AgentTurn(1)),
+            message("summarizer", started(Some(spawned))),
+            message(
+                "summarizer",
+                ChatResponse::AgentCompleted { tokens: 50, duration: Duration::from_millis(400) },
+            ),
+            message("engineer.spawn", ChatResponse::AgentTurn(2)),
+            message(
+                "engineer.spawn",
+                ChatResponse::AgentCompleted {
+                    tokens: 1200,
+                    duration: Duration::from_millis(2500),
+                },
+            ),
+            message(
+                "engineer",
+                ChatResponse::AgentCompleted { tokens: 300, duration: Duration::from_secs(3) },
+            ),
+        ];
+
+        let mut tree = AgentTree::default();
+        let actual = events
+            .iter()
+            .filter_map(|event| tree.record(event, false))
+            .collect::<Vec<_>>();
+        let expected = vec![
+            "▸ engineer.spawn",
+            "  ▸ summarizer",
+            "  ✓ summarizer (0 turns, 50 tokens, 0.4s)",
+            "✓ engineer.spawn (2 turns, 1200 tokens, 2.5s)",
+        ];
+        assert_eq!(actual, expected);
+
+        let mut tree = AgentTree::default();
+        let actual = events
+            .iter()
+            .filter_map(|event| tree.record(event, true))
+            .filter(|line| line.contains("turn "))
+            .collect::<Vec<_>>();
+        assert_eq!(actual, vec!["  turn 1", "  turn 2"]);
+    }
+}
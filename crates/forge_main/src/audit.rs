@@ -0,0 +1,74 @@
+//! Renders the audit log of the tool calls for `forge audit`.
+
+use std::fmt::Write;
+
+use forge_api::{ToolAuditEntry, ToolStatus};
+
+/// Renders the entries one per line, followed by the files each changed and,
+/// with `diff`, their diffs.
+pub fn render(entries: &[ToolAuditEntry], diff: bool) -> String {
+    let mut output = String::new();
+    for entry in entries {
+        let status = match entry.status {
+            ToolStatus::Success => "ok",
+            ToolStatus::Failure => "failed",
+        };
+        let _ = writeln!(
+            output,
+            "#{} {} {} {} {} ({}) args:{}",
+            entry.seq,
+            entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            entry.conversation.into_string(),
+            entry.agent,
+            entry.tool.as_str(),
+            status,
+            &entry.arguments[..12.min(entry.arguments.len())]
+        );
+        for file in &entry.files {
+            let _ = writeln!(output, "    {}", file.path.display());
+            if diff {
+                for line in file.diff.lines() {
+                    let _ = writeln!(output, "      {line}");
+                }
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use chrono::{TimeZone, Utc};
+    use forge_api::{AgentId, ConversationId, FileDiff, ToolName};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let entry = ToolAuditEntry {
+            seq: 3,
+            timestamp: Utc.with_ymd_and_hms(2025, 3, 1, 10, 0, 0).unwrap(),
+            conversation: ConversationId::parse("0195b1a4-5f6c-7e89-a0b1-c2d3e4f50617").unwrap(),
+            agent: AgentId::new("software-engineer"),
+            tool: ToolName::new("tool_forge_fs_patch"),
+            arguments: "9f86d081884c7d659a2feaa0c55ad015".to_string(),
+            status: ToolStatus::Success,
+            files: vec![FileDiff {
+                path: PathBuf::from("/project/a.txt"),
+                diff: "@@ -1 +1 @@\n-one\n+1\n".to_string(),
+            }],
+            prev: String::new(),
+            hash: String::new(),
+        };
+
+        let actual = render(std::slice::from_ref(&entry), false);
+        let expected = "#3 2025-03-01 10:00:00 0195b1a4-5f6c-7e89-a0b1-c2d3e4f50617 software-engineer tool_forge_fs_patch (ok) args:9f86d081884c\n    /project/a.txt\n";
+        assert_eq!(actual, expected);
+
+        let actual = render(&[entry], true);
+        assert!(actual.ends_with("    /project/a.txt\n      @@ -1 +1 @@\n      -one\n      +1\n"));
+    }
+}
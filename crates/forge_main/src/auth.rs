@@ -0,0 +1,147 @@
+//! `forge auth` commands, which manage the API keys stored in the keychain of
+//! the OS.
+
+use std::io::IsTerminal;
+
+use anyhow::{bail, Context, Result};
+use forge_api::{keychain, mask, Provider};
+use forge_display::TitleFormat;
+
+use crate::console::CONSOLE;
+use crate::setup::{choose, read_secret};
+
+/// Parses the name of a provider whose key can be stored in the keychain.
+pub fn parse_provider(name: &str) -> Result<Provider, String> {
+    keychain::PROVIDERS
+        .into_iter()
+        .find(|provider| {
+            provider.to_string().replace(' ', "").to_lowercase()
+                == name.replace(['-', ' '], "").to_lowercase()
+        })
+        .ok_or_else(|| {
+            format!(
+                "unknown provider, expected one of: {}",
+                keychain::PROVIDERS
+                    .map(|provider| provider.to_string().replace(' ', "").to_lowercase())
+                    .join(", ")
+            )
+        })
+}
+
+/// Stores the key of the provider, read from stdin when it isn't a terminal.
+pub fn login(provider: Option<Provider>) -> Result<()> {
+    let (provider, key) = if std::io::stdin().is_terminal() {
+        let provider = match provider {
+            Some(provider) => provider,
+            None => keychain::PROVIDERS[choose("Provider", &keychain::PROVIDERS)?].clone(),
+        };
+        let key = loop {
+            let key = read_secret(&format!("{provider} API key: "))?;
+            if !key.trim().is_empty() {
+                break key.trim().to_string();
+            }
+        };
+        (provider, key)
+    } else {
+        let provider = provider.context("The provider is required when the key is piped")?;
+        let mut key = String::new();
+        std::io::stdin().read_line(&mut key)?;
+        let key = key.trim().to_string();
+        if key.is_empty() {
+            bail!("No key was piped to stdin");
+        }
+        (provider, key)
+    };
+
+    keychain::set(provider.key_name(), &key)?;
+    CONSOLE.writeln(
+        TitleFormat::success("login")
+            .sub_title(format!(
+                "{provider} key {} saved to the keychain",
+                mask(&key)
+            ))
+            .format(),
+    )?;
+    Ok(())
+}
+
+/// Removes the key of the provider, or of all the providers when omitted.
+pub fn logout(provider: Option<Provider>) -> Result<()> {
+    let providers = match provider {
+        Some(provider) => vec![provider],
+        None => keychain::PROVIDERS.to_vec(),
+    };
+    let mut removed = false;
+    for provider in providers {
+        if keychain::delete(provider.key_name())? {
+            removed = true;
+            CONSOLE.writeln(
+                TitleFormat::success("logout")
+                    .sub_title(format!("{provider} key removed from the keychain"))
+                    .format(),
+            )?;
+        }
+    }
+    if !removed {
+        CONSOLE.writeln("No key is stored in the keychain")?;
+    }
+    Ok(())
+}
+
+/// Prints where the key of each provider is read from.
+pub fn status() -> Result<()> {
+    for provider in keychain::PROVIDERS {
+        let name = provider.key_name();
+        let stored = keychain::get(name);
+        // note: the key loaded from the keychain is set in the environment.
+        let title = match (std::env::var(name).ok(), stored) {
+            (Some(key), Some(stored)) if key == stored => {
+                TitleFormat::success(provider.to_string())
+                    .sub_title(format!("{} from the keychain", mask(&key)))
+            }
+            (Some(key), stored) => TitleFormat::success(provider.to_string()).sub_title(format!(
+                "{} from {name}{}",
+                mask(&key),
+                if stored.is_some() {
+                    ", overriding the keychain"
+                } else {
+                    ""
+                }
+            )),
+            (None, Some(stored)) => TitleFormat::success(provider.to_string())
+                .sub_title(format!("{} from the keychain", mask(&stored))),
+            (None, None) => TitleFormat::execute(provider.to_string()).sub_title("not set"),
+        };
+        CONSOLE.writeln(title.format())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_provider() {
+        let actual = ["openrouter", "OpenAI", "azure-openai", "gemini"]
+            .map(|name| parse_provider(name).unwrap());
+        let expected = [
+            Provider::OpenRouter,
+            Provider::OpenAI,
+            Provider::AzureOpenAI,
+            Provider::Gemini,
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_unknown_provider() {
+        let actual = parse_provider("bedrock").unwrap_err();
+        assert_eq!(
+            actual,
+            "unknown provider, expected one of: openrouter, openai, anthropic, azureopenai, gemini"
+        );
+    }
+}
@@ -0,0 +1,385 @@
+//! Headless execution of a single prompt for `forge run`, optionally reporting
+//! the progress as newline-delimited JSON events.
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use forge_api::{AgentId, AgentMessage, ChatResponse, Plan, Redacted, Usage};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Exit code of a run that failed, eg: due to a provider error or an exceeded
+/// budget.
+pub const EXIT_FAILURE: u8 = 1;
+
+/// Exit code of a run that couldn't be started due to invalid input.
+pub const EXIT_USAGE: u8 = 2;
+
+/// Events emitted by `forge run --json`, one JSON object per line.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BatchEvent {
+    /// Text produced by an agent up to a tool call or the end of the run
+    Message {
+        agent: String,
+        content: String,
+    },
+    ToolCall {
+        agent: String,
+        name: String,
+        arguments: Value,
+    },
+    ToolResult {
+        agent: String,
+        name: String,
+        content: String,
+        is_error: bool,
+    },
+    /// Unified diff of a file change, `preview` is set when it wasn't applied
+    Diff {
+        agent: String,
+        path: String,
+        diff: String,
+        preview: bool,
+    },
+    Event {
+        agent: String,
+        name: String,
+        value: String,
+    },
+    Usage {
+        agent: String,
+        usage: Usage,
+    },
+    /// The agent asked the user a question, which is left unanswered
+    Question {
+        agent: String,
+        question: String,
+        options: Vec<String>,
+    },
+    /// The agent submitted the plan of the task, which is left unapproved
+    Plan {
+        agent: String,
+        plan: Plan,
+    },
+    /// The agent was stopped as it kept repeating a failing tool call
+    NeedsUserInput {
+        agent: String,
+        reason: String,
+    },
+    /// The next request of the agent waits for the provider's rate limit
+    RateLimited {
+        agent: String,
+        wait_seconds: f64,
+    },
+    /// Files of the repository attached to the user's message
+    RelevantFiles {
+        agent: String,
+        paths: Vec<String>,
+    },
+    /// Secrets or personal data redacted from the context before it was
+    /// sent to the provider
+    Redacted {
+        agent: String,
+        redactions: Vec<Redacted>,
+    },
+    /// The agent started, on behalf of the `parent` agent if any
+    AgentStarted {
+        agent: String,
+        parent: Option<String>,
+    },
+    /// The agent sends the request of the given number of its turn, from 1
+    AgentTurn {
+        agent: String,
+        turn: u64,
+    },
+    AgentCompleted {
+        agent: String,
+        tokens: u64,
+        duration_seconds: f64,
+    },
+    /// Always the last event of a run
+    Done {
+        success: bool,
+        error: Option<String>,
+        usage: Usage,
+        cost: f64,
+    },
+}
+
+/// Where the [`BatchEvent`]s are sent to.
+pub trait EventSink {
+    fn send(&mut self, event: &BatchEvent) -> anyhow::Result<()>;
+}
+
+/// Writes the events as newline-delimited JSON.
+impl<W: Write> EventSink for W {
+    fn send(&mut self, event: &BatchEvent) -> anyhow::Result<()> {
+        serde_json::to_writer(&mut *self, event)?;
+        self.write_all(b"\n")?;
+        self.flush()?;
+        Ok(())
+    }
+}
+
+/// Converts the chat responses of a run into [`BatchEvent`]s sent to `out`.
+pub struct JsonReporter<W> {
+    out: W,
+    /// Text streamed by each agent since its last tool call
+    text: HashMap<AgentId, String>,
+}
+
+impl<W: EventSink> JsonReporter<W> {
+    pub fn new(out: W) -> Self {
+        Self { out, text: HashMap::new() }
+    }
+
+    pub fn report(&mut self, message: &AgentMessage<ChatResponse>) -> anyhow::Result<()> {
+        let agent = message.agent.as_str().to_string();
+        match &message.message {
+            ChatResponse::Text(text) => {
+                self.text
+                    .entry(message.agent.clone())
+                    .or_default()
+                    .push_str(text);
+            }
+            ChatResponse::ToolCallStart(tool_call) => {
+                self.flush(&message.agent)?;
+                self.emit(&BatchEvent::ToolCall {
+                    agent,
+                    name: tool_call.name.as_str().to_string(),
+                    arguments: tool_call.arguments.clone(),
+                })?;
+            }
+            ChatResponse::ToolCallEnd(result) => {
+                for file in &result.files {
+                    self.emit(&BatchEvent::Diff {
+                        agent: agent.clone(),
+                        path: file.path.display().to_string(),
+                        diff: file.diff.clone(),
+                        preview: false,
+                    })?;
+                }
+                self.emit(&BatchEvent::ToolResult {
+                    agent,
+                    name: result.name.as_str().to_string(),
+                    content: result.content.clone(),
+                    is_error: result.is_error,
+                })?;
+            }
+            ChatResponse::DiffPreview(preview) => self.emit(&BatchEvent::Diff {
+                agent,
+                path: preview.path.clone(),
+                diff: preview.diff.clone(),
+                preview: true,
+            })?,
+            ChatResponse::Custom(event) => self.emit(&BatchEvent::Event {
+                agent,
+                name: event.name.clone(),
+                value: event.value.clone(),
+            })?,
+            ChatResponse::Usage(usage) => {
+                self.emit(&BatchEvent::Usage { agent, usage: usage.clone() })?
+            }
+            // note: the actual usage is reported once the request is done.
+            ChatResponse::UsageEstimate(_) => {}
+            ChatResponse::Question(question) => {
+                self.flush(&message.agent)?;
+                self.emit(&BatchEvent::Question {
+                    agent,
+                    question: question.question.clone(),
+                    options: question.options.clone(),
+                })?
+            }
+            ChatResponse::Plan(plan) => {
+                self.flush(&message.agent)?;
+                self.emit(&BatchEvent::Plan { agent, plan: plan.clone() })?
+            }
+            ChatResponse::NeedsUserInput(reason) => {
+                self.flush(&message.agent)?;
+                self.emit(&BatchEvent::NeedsUserInput { agent, reason: reason.clone() })?
+            }
+            ChatResponse::RateLimited(wait) => {
+                self.emit(&BatchEvent::RateLimited { agent, wait_seconds: wait.as_secs_f64() })?
+            }
+            ChatResponse::RelevantFiles(files) => self.emit(&BatchEvent::RelevantFiles {
+                agent,
+                paths: files.iter().map(|file| file.path.clone()).collect(),
+            })?,
+            ChatResponse::Redacted(redactions) => {
+                self.emit(&BatchEvent::Redacted { agent, redactions: redactions.clone() })?
+            }
+            ChatResponse::AgentStarted { parent, .. } => self.emit(&BatchEvent::AgentStarted {
+                agent,
+                parent: parent.as_ref().map(|parent| parent.as_str().to_string()),
+            })?,
+            ChatResponse::AgentTurn(turn) => {
+                self.emit(&BatchEvent::AgentTurn { agent, turn: *turn })?
+            }
+            ChatResponse::AgentCompleted { tokens, duration } => {
+                self.flush(&message.agent)?;
+                self.emit(&BatchEvent::AgentCompleted {
+                    agent,
+                    tokens: *tokens,
+                    duration_seconds: duration.as_secs_f64(),
+                })?
+            }
+        }
+        Ok(())
+    }
+
+    /// Emits the remaining text of every agent followed by the outcome of the
+    /// run.
+    pub fn finish(
+        &mut self,
+        error: Option<String>,
+        usage: &Usage,
+        cost: f64,
+    ) -> anyhow::Result<()> {
+        let mut agents = self.text.keys().cloned().collect::<Vec<_>>();
+        agents.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        for agent in agents {
+            self.flush(&agent)?;
+        }
+
+        self.emit(&BatchEvent::Done { success: error.is_none(), error, usage: usage.clone(), cost })
+    }
+
+    fn flush(&mut self, agent: &AgentId) -> anyhow::Result<()> {
+        let Some(content) = self.text.remove(agent) else {
+            return Ok(());
+        };
+        if content.trim().is_empty() {
+            return Ok(());
+        }
+        self.emit(&BatchEvent::Message {
+            agent: agent.as_str().to_string(),
+            content: content.trim().to_string(),
+        })
+    }
+
+    fn emit(&mut self, event: &BatchEvent) -> anyhow::Result<()> {
+        self.out.send(event)
+    }
+}
+
+/// Returns a handle to the original stdout for the JSON events and redirects
+/// the stdout of the process to stderr, so that the output printed by tools
+/// and commands can't interleave with the events.
+pub fn take_stdout() -> anyhow::Result<Box<dyn Write + Send>> {
+    #[cfg(unix)]
+    {
+        use std::os::fd::AsFd;
+
+        let stdout = std::io::stdout().as_fd().try_clone_to_owned()?;
+        // SAFETY: dup2 has no memory safety requirements and only replaces a
+        // file descriptor owned by this process.
+        if unsafe { libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) } < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(Box::new(std::fs::File::from(stdout)))
+    }
+
+    #[cfg(not(unix))]
+    Ok(Box::new(std::io::stdout()))
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_api::{FileChange, ToolCallFull, ToolName, ToolResult};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    fn message(agent: &str, message: ChatResponse) -> AgentMessage<ChatResponse> {
+        AgentMessage { agent: AgentId::new(agent), message }
+    }
+
+    fn events(output: &[u8]) -> Vec<Value> {
+        String::from_utf8_lossy(output)
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_report_events() {
+        let call = ToolCallFull::new(ToolName::new("tool_forge_fs_read"))
+            .arguments(json!({"path": "/a.txt"}));
+        let mut output = Vec::new();
+        let mut reporter = JsonReporter::new(&mut output);
+
+        for fixture in [
+            ChatResponse::Text("Let me ".to_string()),
+            ChatResponse::Text("read it".to_string()),
+            ChatResponse::ToolCallStart(call.clone()),
+            ChatResponse::ToolCallEnd(ToolResult::from(call).success("hello")),
+            ChatResponse::Text("Done".to_string()),
+        ] {
+            reporter
+                .report(&message("software-engineer", fixture))
+                .unwrap();
+        }
+        let usage = Usage {
+            prompt_tokens: 10,
+            completion_tokens: 5,
+            total_tokens: 15,
+            cached_tokens: 0,
+        };
+        reporter.finish(None, &usage, 0.5).unwrap();
+
+        let actual = events(&output);
+        let expected = vec![
+            json!({"type": "message", "agent": "software-engineer", "content": "Let me read it"}),
+            json!({"type": "tool_call", "agent": "software-engineer", "name": "tool_forge_fs_read", "arguments": {"path": "/a.txt"}}),
+            json!({"type": "tool_result", "agent": "software-engineer", "name": "tool_forge_fs_read", "content": "hello", "is_error": false}),
+            json!({"type": "message", "agent": "software-engineer", "content": "Done"}),
+            json!({"type": "done", "success": true, "error": null, "usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15, "cached_tokens": 0}, "cost": 0.5}),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_report_applied_diff() {
+        let call = ToolCallFull::new(ToolName::new("tool_forge_fs_patch"))
+            .arguments(json!({"path": "/a.txt"}));
+        let mut result = ToolResult::from(call).success("patched");
+        result.files = vec![FileChange::new(
+            "/a.txt".into(),
+            Some(b"hello\n".to_vec()),
+            Some(b"world\n".to_vec()),
+        )];
+        let mut output = Vec::new();
+        let mut reporter = JsonReporter::new(&mut output);
+
+        reporter
+            .report(&message(
+                "software-engineer",
+                ChatResponse::ToolCallEnd(result),
+            ))
+            .unwrap();
+
+        let actual = events(&output)
+            .into_iter()
+            .find(|event| event["type"] == "diff")
+            .unwrap();
+        assert_eq!(actual["path"], json!("/a.txt"));
+        assert_eq!(actual["preview"], json!(false));
+        assert!(actual["diff"].as_str().unwrap().contains("-hello\n+world"));
+    }
+
+    #[test]
+    fn test_finish_with_error() {
+        let mut output = Vec::new();
+        let mut reporter = JsonReporter::new(&mut output);
+
+        reporter
+            .finish(Some("Budget exceeded".to_string()), &Usage::default(), 0.0)
+            .unwrap();
+
+        let actual = events(&output);
+        assert_eq!(actual[0]["success"], json!(false));
+        assert_eq!(actual[0]["error"], json!("Budget exceeded"));
+    }
+}
@@ -0,0 +1,480 @@
+//! `forge lsp-bridge`: the backend of the VS Code extension, which serves
+//! JSON-RPC 2.0 on stdio with messages framed by `Content-Length` headers,
+//! like a language server. The extension only renders, the conversation,
+//! the edits and the writes to the files are all made here.
+//!
+//! Requests, with their params and result:
+//! - `initialize`: the version of forge and the cwd
+//! - `chat` `{content}`: the conversation id, then its events are sent as
+//!   `chat/event` notifications, those of `forge run --json` ending with
+//!   `done`
+//! - `chat/answer` `{answer}`: answers the question an agent asked
+//! - `chat/cancel`: whether a chat was running
+//! - `chat/new`: starts the next chat in a new conversation
+//! - `edit/selection` `{path, range: {start, end}, instruction}`: the edit
+//!   proposed, `{id, path, content, diff}`, which isn't written until the
+//!   user approves it with `edit/apply` `{id}` or rejects it with
+//!   `edit/reject` `{id}`
+//! - `completion` `{prefix, suffix, language}`: the code to insert
+//! - `shutdown`, followed by the `exit` notification
+//!
+//! The extension sends the `editor/activeFile` `{path, selection}`
+//! notification whenever the active editor or its selection changes, so that
+//! the file is pinned to the conversation and the selection is mentioned in
+//! the next message.
+
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use forge_api::{
+    ChatRequest, ChatResponse, Config, ConversationId, EditedFile, LineRange, Usage, API,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::BufReader;
+use tokio::sync::{mpsc, Mutex};
+use tokio_stream::StreamExt;
+
+use crate::batch::{self, BatchEvent, EventSink, JsonReporter};
+use crate::cost::CostTracker;
+use crate::rpc::{self, notification, parse, response, Incoming, RpcError, INVALID_REQUEST};
+
+#[derive(Debug, Deserialize)]
+struct ChatParams {
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnswerParams {
+    answer: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SelectionParams {
+    path: PathBuf,
+    range: LineRange,
+    instruction: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EditParams {
+    id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompletionParams {
+    prefix: String,
+    suffix: String,
+    language: String,
+}
+
+/// The file of the active editor along with its selection.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct ActiveFile {
+    path: PathBuf,
+    #[serde(default)]
+    selection: Option<LineRange>,
+}
+
+impl ActiveFile {
+    /// Tells the agents what the user is looking at.
+    fn note(&self) -> String {
+        match self.selection {
+            Some(LineRange { start, end }) => format!(
+                "<active_file path=\"{}\" selection=\"{start}-{end}\"/>",
+                self.path.display()
+            ),
+            None => format!("<active_file path=\"{}\"/>", self.path.display()),
+        }
+    }
+}
+
+/// An edit proposed to the user, with the content of the file it was made
+/// from.
+#[derive(Debug, Clone)]
+struct ProposedEdit {
+    original: String,
+    edited: EditedFile,
+}
+
+impl ProposedEdit {
+    /// Writes the edited file, unless it changed since the edit was proposed.
+    fn apply(&self) -> anyhow::Result<()> {
+        let path = &self.edited.path;
+        let current = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        anyhow::ensure!(
+            current == self.original,
+            "{} changed since the edit was proposed, propose it again",
+            path.display()
+        );
+        std::fs::write(path, &self.edited.content)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Proposal {
+    id: u64,
+    #[serde(flatten)]
+    edit: EditedFile,
+}
+
+#[derive(Default)]
+struct Session {
+    conversation_id: Option<ConversationId>,
+    cost: CostTracker,
+    usage: Usage,
+    active_file: Option<ActiveFile>,
+    edits: HashMap<u64, ProposedEdit>,
+    next_edit: u64,
+}
+
+/// Sends the events of a chat as `chat/event` notifications.
+struct Notifier {
+    conversation_id: ConversationId,
+    out: mpsc::UnboundedSender<Value>,
+}
+
+impl EventSink for Notifier {
+    fn send(&mut self, event: &BatchEvent) -> anyhow::Result<()> {
+        let params = json!({"conversationId": self.conversation_id, "event": event});
+        self.out
+            .send(notification("chat/event", params))
+            .map_err(|_| anyhow::anyhow!("The extension is disconnected"))
+    }
+}
+
+pub struct Bridge<A> {
+    api: Arc<A>,
+    /// Configuration resolved from the flags, the environment and the config
+    /// files
+    config: Config,
+    workflow: Option<PathBuf>,
+    /// Messages written to stdout, in order
+    out: mpsc::UnboundedSender<Value>,
+    session: Mutex<Session>,
+}
+
+impl<A: API + Send + Sync + 'static> Bridge<A> {
+    /// Serves the extension until it exits or closes stdin.
+    pub async fn serve(
+        api: Arc<A>,
+        config: Config,
+        workflow: Option<PathBuf>,
+    ) -> anyhow::Result<()> {
+        let env = api.environment();
+        let _guard = forge_tracker::init_tracing(env.log_path())?;
+        let config = config.or(env.config.clone());
+
+        // note: the output of the tools is redirected to stderr, so that it
+        // can't corrupt the messages.
+        let out = rpc::writer(batch::take_stdout()?, write_message);
+
+        let bridge = Arc::new(Self {
+            api,
+            config,
+            workflow,
+            out,
+            session: Mutex::new(Session::default()),
+        });
+        let mut stdin = BufReader::new(tokio::io::stdin());
+        while let Some(message) = forge_lsp::read_message(&mut stdin).await? {
+            let incoming = match serde_json::from_value::<Incoming>(message) {
+                Ok(incoming) => incoming,
+                Err(error) => {
+                    bridge.send(response(
+                        Value::Null,
+                        Err(RpcError { code: INVALID_REQUEST, message: error.to_string() }),
+                    ));
+                    continue;
+                }
+            };
+            if incoming.method == "exit" {
+                break;
+            }
+            // note: requests are handled concurrently, eg: a chat can be
+            // cancelled while it's running.
+            let bridge = bridge.clone();
+            tokio::spawn(async move { bridge.dispatch(incoming).await });
+        }
+        Ok(())
+    }
+
+    fn send(&self, message: Value) {
+        // note: the writer only stops once stdout is closed, when there's no
+        // one left to tell.
+        let _ = self.out.send(message);
+    }
+
+    async fn dispatch(self: Arc<Self>, incoming: Incoming) {
+        let result = self.handle(&incoming.method, incoming.params).await;
+        match incoming.id {
+            Some(id) => self.send(response(id, result)),
+            None => {
+                if let Err(error) = result {
+                    tracing::warn!(method = %incoming.method, error = %error.message, "Notification failed");
+                }
+            }
+        }
+    }
+
+    async fn handle(self: &Arc<Self>, method: &str, params: Value) -> Result<Value, RpcError> {
+        let result = match method {
+            "initialize" => {
+                let env = self.api.environment();
+                json!({"version": env!("CARGO_PKG_VERSION"), "cwd": env.cwd})
+            }
+            "shutdown" => Value::Null,
+            "chat" => {
+                let ChatParams { content } = parse(params)?;
+                json!(self.chat(content).await?)
+            }
+            "chat/answer" => {
+                let AnswerParams { answer } = parse(params)?;
+                let conversation_id = self.conversation_id().await?;
+                self.api.answer(&conversation_id, answer)?;
+                Value::Null
+            }
+            "chat/cancel" => {
+                let conversation_id = self.session.lock().await.conversation_id.clone();
+                json!(conversation_id.is_some_and(|id| self.api.cancel(&id)))
+            }
+            "chat/new" => {
+                let mut session = self.session.lock().await;
+                session.conversation_id = None;
+                session.cost = CostTracker::default();
+                session.usage = Usage::default();
+                Value::Null
+            }
+            "edit/selection" => json!(self.edit_selection(parse(params)?).await?),
+            "edit/apply" => {
+                let EditParams { id } = parse(params)?;
+                let env = self.api.environment();
+                if !env.trusted || self.config.read_only == Some(true) {
+                    return Err(anyhow::anyhow!(
+                        "Files can't be changed in the read-only mode, trust the folder with `forge trust`"
+                    )
+                    .into());
+                }
+                let edit = self.take_edit(id).await?;
+                edit.apply()?;
+                Value::Null
+            }
+            "edit/reject" => {
+                let EditParams { id } = parse(params)?;
+                self.take_edit(id).await?;
+                Value::Null
+            }
+            "completion" => {
+                let CompletionParams { prefix, suffix, language } = parse(params)?;
+                json!(self.api.complete(&prefix, &suffix, &language).await?)
+            }
+            "editor/activeFile" => {
+                let active_file: Option<ActiveFile> = parse(params)?;
+                self.set_active_file(active_file).await?;
+                Value::Null
+            }
+            _ => return Err(RpcError::method_not_found(method)),
+        };
+        Ok(result)
+    }
+
+    /// The conversation of the chats, started on the first one.
+    async fn conversation_id(&self) -> anyhow::Result<ConversationId> {
+        let mut session = self.session.lock().await;
+        if let Some(conversation_id) = &session.conversation_id {
+            return Ok(conversation_id.clone());
+        }
+
+        let mut workflow = self.api.load(self.workflow.as_deref()).await?;
+        self.config.apply(&mut workflow);
+        session.cost.agents(&workflow);
+        // Spend can't be priced without models, but chatting is still possible
+        if let Ok(models) = self.api.models().await {
+            session.cost.pricing(&models);
+        }
+        let conversation_id = self.api.init(workflow).await?;
+        if let Some(active_file) = &session.active_file {
+            let files = BTreeSet::from([active_file.path.clone()]);
+            self.api.set_pinned_files(&conversation_id, files).await?;
+        }
+        session.conversation_id = Some(conversation_id.clone());
+        Ok(conversation_id)
+    }
+
+    /// Starts the chat, whose events are streamed as notifications.
+    async fn chat(self: &Arc<Self>, content: String) -> anyhow::Result<ConversationId> {
+        let conversation_id = self.conversation_id().await?;
+        let content = {
+            let session = self.session.lock().await;
+            if let Some(budget) = self.config.budget {
+                let spent = session.cost.total();
+                anyhow::ensure!(
+                    spent <= budget,
+                    "Budget of ${budget:.2} exceeded (spent ${spent:.4}), start a new conversation"
+                );
+            }
+            match &session.active_file {
+                Some(active_file) => format!("{content}\n\n{}", active_file.note()),
+                None => content,
+            }
+        };
+
+        let mut stream = self
+            .api
+            .chat(ChatRequest {
+                content,
+                conversation_id: conversation_id.clone(),
+                agent: None,
+                images: Vec::new(),
+            })
+            .await?;
+        let bridge = self.clone();
+        let notifier = Notifier {
+            conversation_id: conversation_id.clone(),
+            out: self.out.clone(),
+        };
+        tokio::spawn(async move {
+            let mut reporter = JsonReporter::new(notifier);
+            let mut error = None;
+            while let Some(message) = stream.next().await {
+                let result = match message {
+                    Ok(message) => {
+                        match &message.message {
+                            ChatResponse::AgentStarted { model, .. } => {
+                                let mut session = bridge.session.lock().await;
+                                session.cost.started(&message.agent, model);
+                            }
+                            ChatResponse::Usage(usage) => {
+                                let mut session = bridge.session.lock().await;
+                                session.cost.record(&message.agent, usage);
+                                session.usage = usage.clone();
+                            }
+                            _ => {}
+                        }
+                        reporter.report(&message)
+                    }
+                    Err(err) => Err(err),
+                };
+                if let Err(err) = result {
+                    error = Some(format!("{err:#}"));
+                    break;
+                }
+            }
+            let session = bridge.session.lock().await;
+            if let Err(err) = reporter.finish(error, &session.usage, session.cost.total()) {
+                tracing::warn!(error = ?err, "Failed to report the end of the chat");
+            }
+        });
+        Ok(conversation_id)
+    }
+
+    async fn edit_selection(&self, params: SelectionParams) -> anyhow::Result<Proposal> {
+        let path = self.api.environment().cwd.join(&params.path);
+        let original = tokio::fs::read_to_string(&path)
+            .await
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let edit = self
+            .api
+            .edit_selection(&path, params.range, &params.instruction)
+            .await?;
+
+        let mut session = self.session.lock().await;
+        session.next_edit += 1;
+        let id = session.next_edit;
+        session
+            .edits
+            .insert(id, ProposedEdit { original, edited: edit.clone() });
+        Ok(Proposal { id, edit })
+    }
+
+    async fn take_edit(&self, id: u64) -> anyhow::Result<ProposedEdit> {
+        self.session
+            .lock()
+            .await
+            .edits
+            .remove(&id)
+            .with_context(|| format!("No edit {id} is waiting for approval"))
+    }
+
+    async fn set_active_file(&self, active_file: Option<ActiveFile>) -> anyhow::Result<()> {
+        let mut session = self.session.lock().await;
+        if let Some(conversation_id) = &session.conversation_id {
+            let files = active_file
+                .iter()
+                .map(|active_file| active_file.path.clone())
+                .collect();
+            self.api.set_pinned_files(conversation_id, files).await?;
+        }
+        session.active_file = active_file;
+        Ok(())
+    }
+}
+
+/// Writes a single message framed with a `Content-Length` header.
+fn write_message(out: &mut dyn Write, message: &Value) -> anyhow::Result<()> {
+    let content = serde_json::to_string(message)?;
+    write!(out, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_message() {
+        let fixture = notification("chat/event", json!({"event": {"type": "message"}}));
+        let mut output = Vec::new();
+        write_message(&mut output, &fixture).unwrap();
+
+        let actual = forge_lsp::read_message(&mut BufReader::new(output.as_slice()))
+            .await
+            .unwrap();
+        assert_eq!(actual, Some(fixture));
+    }
+
+    #[test]
+    fn test_apply_proposed_edit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.rs");
+        std::fs::write(&path, "let cat = \"Juniper\";\n").unwrap();
+        let edit = ProposedEdit {
+            original: "let cat = \"Juniper\";\n".to_string(),
+            edited: EditedFile {
+                path: path.clone(),
+                content: "let cat = \"Pepper\";\n".to_string(),
+                diff: String::new(),
+            },
+        };
+
+        edit.apply().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "let cat = \"Pepper\";\n"
+        );
+
+        // The file changed since the edit was proposed
+        let actual = edit.apply().unwrap_err().to_string();
+        assert!(actual.contains("changed since the edit was proposed"));
+    }
+
+    #[test]
+    fn test_active_file_note() {
+        let active_file: ActiveFile = serde_json::from_value(
+            json!({"path": "src/main.rs", "selection": {"start": 2, "end": 4}}),
+        )
+        .unwrap();
+        assert_eq!(
+            active_file.note(),
+            "<active_file path=\"src/main.rs\" selection=\"2-4\"/>"
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use forge_api::{Config, ModelId, Provider, ResponseCache};
+
+use crate::auth;
 
 #[derive(Parser)]
 #[command(version = env!("CARGO_PKG_VERSION"))]
@@ -25,6 +28,49 @@ pub struct Cli {
     #[arg(long, default_value_t = false)]
     pub verbose: bool,
 
+    /// Disable diff output for file changes.
+    ///
+    /// By default every change applied by a file-modifying tool is rendered
+    /// as a line-numbered diff of the old and new content.
+    #[arg(long, default_value_t = false)]
+    pub no_diff: bool,
+
+    /// Print the responses as plain text.
+    ///
+    /// By default the markdown of a response is rendered as it's streamed:
+    /// headers, lists and code blocks with syntax highlighting.
+    #[arg(long, default_value_t = false)]
+    pub plain: bool,
+
+    /// Run in the main screen of the terminal.
+    ///
+    /// By default the interactive mode takes over the terminal, with a
+    /// scrollable conversation, a status bar and an input area that output
+    /// can't break. The conversation is printed to the terminal on exit.
+    #[arg(long, default_value_t = false)]
+    pub inline: bool,
+
+    /// Enable voice input.
+    ///
+    /// Pressing Ctrl+T or sending /voice records the microphone until a pause
+    /// and inserts the transcript into the prompt. Transcribes with the
+    /// whisper model of --whisper-model, or with the OpenAI API when none is
+    /// given. Needs forge to be built with the `voice` feature.
+    #[arg(long, default_value_t = false)]
+    pub voice: bool,
+
+    /// Path to a whisper.cpp model (ggml) used to transcribe voice input
+    /// locally.
+    #[arg(long, requires = "voice")]
+    pub whisper_model: Option<PathBuf>,
+
+    /// Maximum spend of a conversation in USD.
+    ///
+    /// The conversation is stopped as soon as the cumulative cost of its
+    /// requests exceeds the budget.
+    #[arg(long)]
+    pub budget: Option<f64>,
+
     /// Enable restricted shell mode for enhanced security.
     ///
     /// Controls the shell execution environment:
@@ -41,7 +87,154 @@ pub struct Cli {
     #[arg(long, default_value_t = false, short = 'r')]
     pub restricted: bool,
 
+    /// Observe the codebase without changing it.
+    ///
+    /// Removes the tools that write files, run commands or make changes
+    /// outside, eg: fs_create, fs_patch, the shell and the pull requests, for
+    /// the whole session.
+    #[arg(long, default_value_t = false)]
+    pub read_only: bool,
+
+    /// Send every request to the provider, bypassing the response cache.
+    ///
+    /// The fresh responses aren't cached either.
+    #[arg(long, default_value_t = false)]
+    pub no_cache: bool,
+
     /// Path to a file containing the workflow to execute.
     #[arg(long, short = 'w')]
     pub workflow: Option<PathBuf>,
+
+    /// Model used by every agent, overriding the ones of the workflow and of
+    /// the config files.
+    #[arg(long, short = 'm')]
+    pub model: Option<String>,
+
+    #[command(subcommand)]
+    pub subcommand: Option<TopLevelCommand>,
+}
+
+impl Cli {
+    /// Settings of the configuration that are set by the flags, which take
+    /// precedence over the environment and the config files.
+    pub fn config(&self) -> Config {
+        Config {
+            model: self.model.clone().map(ModelId::new),
+            restricted: self.restricted.then_some(true),
+            read_only: self.read_only.then_some(true),
+            budget: self.budget,
+            response_cache: ResponseCache {
+                enabled: self.no_cache.then_some(false),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum TopLevelCommand {
+    /// Execute a single prompt headlessly and exit, for scripting and CI.
+    ///
+    /// Exits with 0 on success, 1 when the run fails and 2 when no prompt is
+    /// provided.
+    Run {
+        /// Prompt to execute, read from stdin when omitted.
+        prompt: Option<String>,
+
+        /// Emit newline-delimited JSON events instead of formatted output.
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Run the workflow headlessly whenever files matching the triggers of the
+    /// workflow change or on their schedule.
+    Watch {
+        /// Names of the triggers to run, all of them when omitted.
+        #[arg(long = "trigger", short = 't')]
+        triggers: Vec<String>,
+    },
+    /// Print the usage statistics recorded locally: tokens, cost, tool calls
+    /// and provider latency, along with their daily trend.
+    ///
+    /// Recording can be disabled by setting FORGE_STATS=false.
+    Stats {
+        /// Number of days to include.
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+    },
+    /// Diagnose the setup: the provider and its API key, the shell, write
+    /// access to the config directory and the tree-sitter grammars.
+    ///
+    /// Prints how to fix each failing check and exits with 1 if any fails.
+    Doctor,
+    /// Manage the API keys stored in the keychain of the OS, which are used
+    /// when no key is set in the environment.
+    Auth {
+        #[command(subcommand)]
+        command: AuthCommand,
+    },
+    /// Inspect the audit log of the tool calls and of the files they changed,
+    /// which is hash-chained so that tampering with it can be detected.
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommand,
+    },
+    /// Serve the VS Code extension over JSON-RPC on stdio: chat, edits of the
+    /// selection applied once approved, completions and the active file.
+    LspBridge,
+    /// Serve the Agent Client Protocol on stdio, so that editors like Zed can
+    /// run forge as an external agent.
+    Acp,
+    /// Trust a folder, the current one when omitted, so that its config,
+    /// prompts and workflow are used and its files can be changed.
+    ///
+    /// Untrusted folders are opened in the read-only mode.
+    Trust {
+        path: Option<PathBuf>,
+
+        /// Stop trusting the folder.
+        #[arg(long, default_value_t = false, conflicts_with = "list")]
+        revoke: bool,
+
+        /// List the trusted folders.
+        #[arg(long, default_value_t = false)]
+        list: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AuditCommand {
+    /// Print the last entries of the audit log, with the diffs of the files
+    /// changed.
+    Show {
+        /// Number of entries to print.
+        #[arg(long, short = 'n', default_value_t = 20)]
+        limit: usize,
+
+        /// Print the diffs of the files changed by each tool call.
+        #[arg(long, default_value_t = false)]
+        diff: bool,
+    },
+    /// Check that no entry of the audit log was changed or removed.
+    ///
+    /// Exits with 1 when the chain of hashes is broken.
+    Verify,
+}
+
+#[derive(Subcommand)]
+pub enum AuthCommand {
+    /// Store the API key of a provider, read from stdin when it's piped.
+    Login {
+        /// Provider of the key, eg: openrouter, openai, anthropic,
+        /// azureopenai or gemini. Asked for when omitted.
+        #[arg(long, value_parser = auth::parse_provider)]
+        provider: Option<Provider>,
+    },
+    /// Remove the API key of a provider, or of all of them when omitted.
+    Logout {
+        #[arg(long, value_parser = auth::parse_provider)]
+        provider: Option<Provider>,
+    },
+    /// Show where the key of each provider is read from.
+    Status,
 }
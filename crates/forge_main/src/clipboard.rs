@@ -0,0 +1,77 @@
+//! Reads the clipboard for `/paste`.
+
+use anyhow::{bail, Context};
+use arboard::Clipboard;
+use base64::Engine;
+use forge_api::Image;
+
+/// Content of the clipboard, either text to fill the input with or an image to
+/// attach to the next message.
+pub enum Pasted {
+    Text(String),
+    Image {
+        image: Image,
+        width: usize,
+        height: usize,
+    },
+}
+
+/// Reads the clipboard, preferring an image when it holds both, as copying an
+/// image in a browser also copies its URL as text.
+pub fn paste() -> anyhow::Result<Pasted> {
+    let mut clipboard = Clipboard::new().context("Failed to open the clipboard")?;
+    if let Ok(image) = clipboard.get_image() {
+        let png = encode_png(image.width, image.height, &image.bytes)?;
+        return Ok(Pasted::Image {
+            image: Image::new(
+                "image/png",
+                base64::engine::general_purpose::STANDARD.encode(png),
+            ),
+            width: image.width,
+            height: image.height,
+        });
+    }
+    let text = clipboard
+        .get_text()
+        .context("The clipboard holds neither text nor an image")?;
+    if text.trim().is_empty() {
+        bail!("The clipboard is empty");
+    }
+    Ok(Pasted::Text(text))
+}
+
+/// Encodes the RGBA pixels the clipboard returns as a PNG, a format all the
+/// providers accept.
+fn encode_png(width: usize, height: usize, rgba: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width.try_into()?, height.try_into()?);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder
+        .write_header()
+        .context("Failed to encode the image")?;
+    writer
+        .write_image_data(rgba)
+        .context("Failed to encode the image")?;
+    writer.finish().context("Failed to encode the image")?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_encode_png() {
+        let rgba = [255, 0, 0, 255, 0, 0, 255, 128];
+        let png = encode_png(2, 1, &rgba).unwrap();
+
+        let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        let mut actual = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut actual).unwrap();
+        assert_eq!((info.width, info.height), (2, 1));
+        assert_eq!(actual, rgba);
+    }
+}
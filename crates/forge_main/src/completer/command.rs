@@ -1,18 +1,66 @@
+use forge_api::{CustomCommand, Model};
 use reedline::{Completer, Span, Suggestion};
 
-use crate::model::Command;
+use crate::model::{model_summary, Command};
 
-#[derive(Default)]
-pub struct CommandCompleter;
+/// Completes the built-in commands along with the custom commands defined in
+/// the workflow, and the models of `/model`.
+#[derive(Clone, Default)]
+pub struct CommandCompleter {
+    custom: Vec<CustomCommand>,
+    models: Vec<Model>,
+}
+
+impl CommandCompleter {
+    pub fn new(custom: Vec<CustomCommand>) -> Self {
+        Self { custom, models: Vec::new() }
+    }
+
+    /// Sets the models `/model` is completed with, once they're fetched.
+    pub fn models(mut self, models: Vec<Model>) -> Self {
+        self.models = models;
+        self
+    }
+
+    fn complete_model(&self, line: &str, query: &str) -> Vec<Suggestion> {
+        let query = query.to_lowercase();
+        self.models
+            .iter()
+            .filter(|model| model.id.as_str().to_lowercase().contains(&query))
+            .map(|model| Suggestion {
+                value: model.id.to_string(),
+                description: Some(model_summary(model)).filter(|summary| !summary.is_empty()),
+                style: None,
+                extra: None,
+                span: Span::new(line.len() - query.len(), line.len()),
+                append_whitespace: false,
+            })
+            .collect()
+    }
+}
 
 impl Completer for CommandCompleter {
     fn complete(&mut self, line: &str, _: usize) -> Vec<reedline::Suggestion> {
-        Command::available_commands()
-            .into_iter()
-            .filter(|cmd| cmd.starts_with(line))
-            .map(|cmd| Suggestion {
+        if let Some(query) = line.strip_prefix("/model ") {
+            return self.complete_model(line, query);
+        }
+
+        let builtin = Command::available_commands();
+        let custom = self
+            .custom
+            .iter()
+            .map(|command| (format!("/{}", command.name), command.description.clone()))
+            .filter(|(name, _)| !builtin.contains(name));
+
+        builtin
+            .iter()
+            .cloned()
+            .map(|cmd| (cmd, None))
+            .chain(custom)
+            .filter(|(cmd, _)| cmd.starts_with(line))
+            .map(|(cmd, description)| Suggestion {
                 value: cmd,
-                description: None,
+                description,
                 style: None,
                 extra: None,
                 span: Span::new(0, line.len()),
@@ -21,3 +69,61 @@ impl Completer for CommandCompleter {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use forge_api::ModelId;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_complete_custom_command() {
+        let mut completer = CommandCompleter::new(vec![CustomCommand {
+            name: "review".to_string(),
+            description: Some("Review the current changes".to_string()),
+            run: None,
+            only_on_failure: false,
+            prompt: "Review my changes".to_string(),
+        }]);
+
+        let actual = completer
+            .complete("/rev", 4)
+            .into_iter()
+            .map(|suggestion| (suggestion.value, suggestion.description))
+            .collect::<Vec<_>>();
+        let expected = vec![(
+            "/review".to_string(),
+            Some("Review the current changes".to_string()),
+        )];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_complete_model() {
+        let model = |id: &str| Model {
+            id: ModelId::new(id),
+            name: id.to_string(),
+            description: None,
+            context_length: Some(200_000),
+            pricing: None,
+            capabilities: None,
+        };
+        let mut completer = CommandCompleter::default().models(vec![
+            model("anthropic/claude-3.7-sonnet"),
+            model("openai/gpt-4o"),
+        ]);
+
+        let actual = completer
+            .complete("/model Claude", 13)
+            .into_iter()
+            .map(|suggestion| (suggestion.value, suggestion.description, suggestion.span))
+            .collect::<Vec<_>>();
+        let expected = vec![(
+            "anthropic/claude-3.7-sonnet".to_string(),
+            Some("200.0K context".to_string()),
+            Span::new(7, 13),
+        )];
+        assert_eq!(actual, expected);
+    }
+}
@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use forge_api::{CustomCommand, Model};
 use forge_walker::Walker;
 use reedline::{Completer, Suggestion};
 
@@ -9,12 +10,19 @@ use crate::completer::CommandCompleter;
 #[derive(Clone)]
 pub struct InputCompleter {
     walker: Walker,
+    commands: CommandCompleter,
 }
 
 impl InputCompleter {
-    pub fn new(cwd: PathBuf) -> Self {
+    pub fn new(cwd: PathBuf, custom: Vec<CustomCommand>) -> Self {
         let walker = Walker::max_all().cwd(cwd).skip_binary(true);
-        Self { walker }
+        Self { walker, commands: CommandCompleter::new(custom) }
+    }
+
+    /// Sets the models `/model` is completed with.
+    pub fn models(mut self, models: Vec<Model>) -> Self {
+        self.commands = self.commands.models(models);
+        self
     }
 }
 
@@ -23,7 +31,7 @@ impl Completer for InputCompleter {
         if line.starts_with("/") {
             // if the line starts with '/' it's probably a command, so we delegate to the
             // command completer.
-            let result = CommandCompleter.complete(line, pos);
+            let result = self.commands.complete(line, pos);
             if !result.is_empty() {
                 return result;
             }
@@ -31,28 +39,28 @@ impl Completer for InputCompleter {
 
         if let Some(query) = SearchTerm::new(line, pos).process() {
             let files = self.walker.get_blocking().unwrap_or_default();
-            files
+            let query_lower = query.term.to_lowercase();
+            let mut matches = files
                 .into_iter()
-                .filter(|file| !file.is_dir())
+                .filter(|file| file.path != "/")
                 .filter_map(|file| {
-                    if let Some(file_name) = file.file_name.as_ref() {
-                        let file_name_lower = file_name.to_lowercase();
-                        let query_lower = query.term.to_lowercase();
-                        if file_name_lower.contains(&query_lower) {
-                            Some(Suggestion {
-                                value: file.path,
-                                description: None,
-                                style: None,
-                                extra: None,
-                                span: query.span,
-                                append_whitespace: true,
-                            })
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
+                    let score = fuzzy_score(&file.path.to_lowercase(), &query_lower)?;
+                    Some((!file.is_dir(), score, file.path))
+                })
+                .collect::<Vec<_>>();
+            // Directories come first, then the closest matches.
+            matches.sort_by(|a, b| (a.0, a.1, a.2.len(), &a.2).cmp(&(b.0, b.1, b.2.len(), &b.2)));
+
+            matches
+                .into_iter()
+                .map(|(is_file, _, path)| Suggestion {
+                    value: path,
+                    description: None,
+                    style: None,
+                    extra: None,
+                    span: query.span,
+                    // note: a directory is completed further rather than ended.
+                    append_whitespace: is_file,
                 })
                 .collect()
         } else {
@@ -60,3 +68,83 @@ impl Completer for InputCompleter {
         }
     }
 }
+
+/// Scores how well `path` matches `query` when the chars of the query appear
+/// in order in the path, the lower the better: matches spread over the path
+/// score worse and the ones in the file name score best.
+fn fuzzy_score(path: &str, query: &str) -> Option<usize> {
+    let file_name = path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(path);
+    if file_name.starts_with(query) {
+        return Some(0);
+    }
+    if file_name.contains(query) {
+        return Some(1);
+    }
+
+    let mut chars = path.char_indices();
+    let mut first = None;
+    let mut last = 0;
+    for q in query.chars() {
+        let (index, _) = chars.by_ref().find(|(_, c)| *c == q)?;
+        first.get_or_insert(index);
+        last = index;
+    }
+    let spread = first.map_or(0, |first| last - first + 1 - query.len());
+    Some(2 + spread)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn complete(line: &str) -> Vec<(String, bool)> {
+        let dir = tempfile::tempdir().unwrap();
+        for file in [
+            "src/main.rs",
+            "src/map/mod.rs",
+            "docs/manual.md",
+            "README.md",
+        ] {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::create_dir(dir.path().join("target")).unwrap();
+        fs::write(dir.path().join("target/main.o"), "").unwrap();
+
+        let mut completer = InputCompleter::new(dir.path().to_path_buf(), vec![]);
+        completer
+            .complete(line, line.len())
+            .into_iter()
+            .map(|suggestion| (suggestion.value, suggestion.append_whitespace))
+            .collect()
+    }
+
+    #[test]
+    fn test_complete_path() {
+        let actual = complete("read @src/ma");
+        let expected = vec![
+            ("src/map/".to_string(), false),
+            ("src/main.rs".to_string(), true),
+            ("src/map/mod.rs".to_string(), true),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_complete_fuzzy() {
+        let actual = complete("@mnl");
+        let expected = vec![("docs/manual.md".to_string(), true)];
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,89 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+use forge_api::ModelParameters;
+
+use crate::info::Info;
+
+const USAGE: &str = "Usage: /config [set <temperature|top_p|top_k|max_tokens> <value>]";
+
+/// Arguments of the `/config` command.
+#[derive(Debug, PartialEq)]
+pub enum ConfigCommand {
+    /// Displays the configured generation parameters
+    Show,
+    /// Overrides a generation parameter for all agents
+    Set(ModelParameters),
+}
+
+impl FromStr for ConfigCommand {
+    type Err = anyhow::Error;
+
+    fn from_str(args: &str) -> Result<Self, Self::Err> {
+        let args = args.split_whitespace().collect::<Vec<_>>();
+        match args.as_slice() {
+            [] => Ok(ConfigCommand::Show),
+            ["set", key, value] => Ok(ConfigCommand::Set(parse_parameter(key, value)?)),
+            _ => bail!(USAGE),
+        }
+    }
+}
+
+fn parse_parameter(key: &str, value: &str) -> anyhow::Result<ModelParameters> {
+    let parameters = ModelParameters::default();
+    let invalid = || format!("Invalid value '{value}' for {key}");
+    Ok(match key {
+        "temperature" => parameters.temperature(value.parse().with_context(invalid)?),
+        "top_p" => parameters.top_p(value.parse().with_context(invalid)?),
+        "top_k" => parameters.top_k(value.parse().with_context(invalid)?),
+        "max_tokens" => parameters.max_tokens(value.parse().with_context(invalid)?),
+        _ => bail!("Unknown parameter '{key}'. {USAGE}"),
+    })
+}
+
+impl From<&ModelParameters> for Info {
+    fn from(parameters: &ModelParameters) -> Self {
+        fn value(value: Option<impl ToString>) -> String {
+            value.map_or("default".to_string(), |value| value.to_string())
+        }
+
+        Info::new()
+            .add_title("Model Parameters")
+            .add_item("temperature", value(parameters.temperature))
+            .add_item("top_p", value(parameters.top_p))
+            .add_item("top_k", value(parameters.top_k))
+            .add_item("max_tokens", value(parameters.max_tokens))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_show() {
+        let actual: ConfigCommand = "".parse().unwrap();
+        assert_eq!(actual, ConfigCommand::Show);
+    }
+
+    #[test]
+    fn test_parse_set() {
+        let actual: ConfigCommand = "set temperature 0.2".parse().unwrap();
+        let expected = ConfigCommand::Set(ModelParameters::default().temperature(0.2));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_set_invalid_value() {
+        let actual = "set max_tokens lots".parse::<ConfigCommand>();
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_parse_set_unknown_parameter() {
+        let actual = "set verbosity 3".parse::<ConfigCommand>();
+        assert!(actual.is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::{BTreeMap, HashMap};
+
+use forge_api::{AgentId, Model, ModelId, Pricing, Usage, Workflow};
+
+use crate::info::Info;
+
+/// Accumulates the spend of a conversation from the usage reported for every
+/// request, priced using the provider's model pricing.
+#[derive(Debug, Default)]
+pub struct CostTracker {
+    agents: HashMap<AgentId, ModelId>,
+    pricing: HashMap<ModelId, Pricing>,
+    spend: BTreeMap<String, ModelSpend>,
+    /// Usage of all the requests, including those of unknown agents
+    usage: Usage,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct ModelSpend {
+    requests: u64,
+    cost: f64,
+    /// Set when the pricing of the model is unknown
+    unpriced: bool,
+}
+
+impl CostTracker {
+    /// Registers the models used by the agents of the workflow.
+    pub fn agents(&mut self, workflow: &Workflow) {
+        self.agents = workflow
+            .agents
+            .iter()
+            .map(|agent| (agent.id.clone(), agent.model.clone()))
+            .collect();
+    }
+
+    /// Registers the model of an agent as it starts, eg: of an agent spawned
+    /// at runtime, which isn't in the workflow.
+    pub fn started(&mut self, agent: &AgentId, model: &ModelId) {
+        self.agents.insert(agent.clone(), model.clone());
+    }
+
+    /// Registers the pricing of the available models.
+    pub fn pricing(&mut self, models: &[Model]) {
+        self.pricing = models
+            .iter()
+            .filter_map(|model| Some((model.id.clone(), model.pricing?)))
+            .collect();
+    }
+
+    /// Records the usage of a single request made by the agent.
+    pub fn record(&mut self, agent: &AgentId, usage: &Usage) {
+        self.usage += usage;
+        let Some(model) = self.agents.get(agent) else {
+            return;
+        };
+
+        let spend = self.spend.entry(model.to_string()).or_default();
+        spend.requests += 1;
+        match self.pricing.get(model) {
+            Some(pricing) => spend.cost += pricing.cost(usage),
+            None => spend.unpriced = true,
+        }
+    }
+
+    /// Cost of a request of the agent without recording it, eg: of the one in
+    /// progress. Requests of unpriced models cost nothing.
+    pub fn estimate(&self, agent: &AgentId, usage: &Usage) -> f64 {
+        self.agents
+            .get(agent)
+            .and_then(|model| self.pricing.get(model))
+            .map_or(0.0, |pricing| pricing.cost(usage))
+    }
+
+    /// Usage of all the requests of the conversation, whereas each reported
+    /// usage is that of a single request.
+    pub fn usage(&self) -> &Usage {
+        &self.usage
+    }
+
+    /// Total spend of the conversation in USD.
+    pub fn total(&self) -> f64 {
+        // Summing an empty iterator of floats yields -0.0
+        self.spend
+            .values()
+            .fold(0.0, |total, spend| total + spend.cost)
+    }
+}
+
+impl From<&CostTracker> for Info {
+    fn from(tracker: &CostTracker) -> Self {
+        let mut info = Info::new().add_title("Cost");
+        for (model, spend) in tracker.spend.iter() {
+            let requests = if spend.requests == 1 {
+                "request"
+            } else {
+                "requests"
+            };
+            let pricing = if spend.unpriced {
+                ", pricing unavailable"
+            } else {
+                ""
+            };
+            info = info.add_item(
+                model,
+                format!(
+                    "${:.4} ({} {requests}{pricing})",
+                    spend.cost, spend.requests
+                ),
+            );
+        }
+        info.add_item("Total", format!("${:.4}", tracker.total()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_api::{Agent, Workflow};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fixture() -> CostTracker {
+        let agent: Agent = serde_json::from_value(serde_json::json!({
+            "id": "software-engineer",
+            "model": "anthropic/claude-3.7-sonnet",
+            "description": null,
+            "tools": [],
+        }))
+        .unwrap();
+        let model: Model = serde_json::from_value(serde_json::json!({
+            "id": "anthropic/claude-3.7-sonnet",
+            "name": "Claude 3.7 Sonnet",
+            "description": null,
+            "context_length": 200000,
+            "pricing": {"prompt": 0.000003, "completion": 0.000015, "request": 0.0},
+        }))
+        .unwrap();
+
+        let mut tracker = CostTracker::default();
+        tracker.agents(&Workflow { agents: vec![agent], ..Default::default() });
+        tracker.pricing(&[model]);
+        tracker
+    }
+
+    #[test]
+    fn test_record_accumulates_cost() {
+        let mut tracker = fixture();
+        let agent = AgentId::new("software-engineer");
+        let usage = Usage {
+            prompt_tokens: 1000,
+            completion_tokens: 200,
+            total_tokens: 1200,
+            cached_tokens: 0,
+        };
+
+        tracker.record(&agent, &usage);
+        tracker.record(&agent, &usage);
+
+        let actual = tracker.total();
+        let expected = 0.012;
+        assert!((actual - expected).abs() < 1e-9);
+
+        let actual = tracker.usage();
+        let expected = Usage {
+            prompt_tokens: 2000,
+            completion_tokens: 400,
+            total_tokens: 2400,
+            cached_tokens: 0,
+        };
+        assert_eq!(actual, &expected);
+    }
+
+    #[test]
+    fn test_record_spawned_agent() {
+        let mut tracker = fixture();
+        let agent = AgentId::new("software-engineer.spawn");
+        let usage = Usage {
+            prompt_tokens: 1000,
+            completion_tokens: 200,
+            total_tokens: 1200,
+            cached_tokens: 0,
+        };
+
+        tracker.started(&agent, &ModelId::new("anthropic/claude-3.7-sonnet"));
+        tracker.record(&agent, &usage);
+
+        let actual = tracker.total();
+        let expected = 0.006;
+        assert!((actual - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_record_unknown_agent() {
+        let mut tracker = fixture();
+        let usage = Usage {
+            prompt_tokens: 1000,
+            completion_tokens: 200,
+            total_tokens: 1200,
+            cached_tokens: 0,
+        };
+
+        tracker.record(&AgentId::new("title_generation_worker"), &usage);
+
+        assert_eq!(tracker.total(), 0.0);
+    }
+}
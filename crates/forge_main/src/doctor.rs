@@ -0,0 +1,261 @@
+//! Diagnostics of the setup for `forge doctor`, each failing check comes with
+//! the steps to fix it.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use forge_api::API;
+use forge_display::TitleFormat;
+
+/// Time given to the provider to answer before it's considered unreachable.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Outcome of a single diagnostic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+    /// Steps to fix the failure
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl ToString) -> Self {
+        Self { name, passed: true, detail: detail.to_string(), fix: None }
+    }
+
+    fn fail(name: &'static str, detail: impl ToString, fix: impl ToString) -> Self {
+        Self {
+            name,
+            passed: false,
+            detail: detail.to_string(),
+            fix: Some(fix.to_string()),
+        }
+    }
+}
+
+/// Runs all the diagnostics in order.
+pub async fn run(api: &impl API, restricted: bool) -> Vec<Check> {
+    let env = api.environment();
+    let mut checks = vec![];
+
+    match &env.replay_path {
+        // note: replaying a recording doesn't make any request to the provider.
+        Some(path) => checks.push(Check::pass(
+            "Provider",
+            format!("replaying {}, no request is made", path.display()),
+        )),
+        None => {
+            let reachable = provider_url(&env.provider_url).await;
+            let passed = reachable.passed;
+            checks.push(reachable);
+            if passed {
+                checks.push(api_key(api).await);
+            }
+        }
+    }
+    checks.push(shell(&env.shell, restricted));
+    checks.push(base_path(&env.base_path));
+    checks.push(config_files(&[
+        forge_api::config::project_path(&env.cwd),
+        forge_api::config::user_path(),
+    ]));
+    checks.push(grammars(forge_api::grammars()));
+    checks
+}
+
+/// Checks that the provider answers, any status means it's reachable.
+async fn provider_url(url: &str) -> Check {
+    const NAME: &str = "Provider URL";
+    let client = match reqwest::Client::builder().timeout(TIMEOUT).build() {
+        Ok(client) => client,
+        Err(err) => return Check::fail(NAME, err, "Report the issue, the HTTP client failed"),
+    };
+    match client.get(url).send().await {
+        Ok(response) => Check::pass(NAME, format!("{url} (HTTP {})", response.status())),
+        Err(err) => Check::fail(
+            NAME,
+            format!("{url} is unreachable: {err}"),
+            "Check your network and proxy settings, or set FORGE_PROVIDER_URL to the URL of your provider",
+        ),
+    }
+}
+
+/// Checks the key by listing the models, which costs nothing.
+async fn api_key(api: &impl API) -> Check {
+    const NAME: &str = "API key";
+    match api.models().await {
+        Ok(models) => Check::pass(NAME, format!("valid, {} models available", models.len())),
+        Err(err) => Check::fail(
+            NAME,
+            format!("{err:#}"),
+            "Set a valid key for your provider in FORGE_KEY or its own variable, eg: OPENROUTER_API_KEY",
+        ),
+    }
+}
+
+fn shell(shell: &str, restricted: bool) -> Check {
+    const NAME: &str = "Shell";
+    // note: the shell is resolved through COMSPEC on Windows, which is always set.
+    if cfg!(target_os = "windows") || Path::new(shell).is_file() {
+        return Check::pass(NAME, shell);
+    }
+    if restricted {
+        Check::fail(
+            NAME,
+            format!("{shell} is required by the restricted mode but isn't installed"),
+            "Install rbash, eg: `sudo ln -s /bin/bash /bin/rbash`, or run forge without --restricted",
+        )
+    } else {
+        Check::fail(
+            NAME,
+            format!("{shell} isn't installed"),
+            "Set SHELL to the path of an installed shell, eg: /bin/bash",
+        )
+    }
+}
+
+/// Checks that the logs, history and statistics can be written.
+fn base_path(path: &Path) -> Check {
+    const NAME: &str = "Base path";
+    let probe = path.join(".forge-doctor");
+    let result = std::fs::create_dir_all(path)
+        .and_then(|_| std::fs::write(&probe, b""))
+        .and_then(|_| std::fs::remove_file(&probe));
+    match result {
+        Ok(_) => Check::pass(NAME, format!("{} is writable", path.display())),
+        Err(err) => Check::fail(
+            NAME,
+            format!("{} isn't writable: {err}", path.display()),
+            format!(
+                "Make the directory writable by your user, eg: `chmod u+w {}`",
+                path.display()
+            ),
+        ),
+    }
+}
+
+/// Checks that the config files that exist are valid, since invalid ones are
+/// skipped.
+fn config_files(paths: &[PathBuf]) -> Check {
+    const NAME: &str = "Config files";
+    let mut found = vec![];
+    for path in paths.iter().filter(|path| path.exists()) {
+        if let Err(err) = forge_api::config::read(path) {
+            return Check::fail(
+                NAME,
+                format!("{err:#}"),
+                "Fix the file, see the README for the available settings",
+            );
+        }
+        found.push(path.display().to_string());
+    }
+    if found.is_empty() {
+        Check::pass(NAME, "none found, using the defaults")
+    } else {
+        Check::pass(NAME, found.join(", "))
+    }
+}
+
+fn grammars<E: std::fmt::Display>(results: Vec<(&'static str, Result<(), E>)>) -> Check {
+    const NAME: &str = "Tree-sitter grammars";
+    let total = results.len();
+    let failed = results
+        .into_iter()
+        .filter_map(|(name, result)| result.err().map(|err| format!("{name} ({err})")))
+        .collect::<Vec<_>>();
+    if failed.is_empty() {
+        Check::pass(NAME, format!("{total} languages available"))
+    } else {
+        Check::fail(
+            NAME,
+            format!("failed to load {}", failed.join(", ")),
+            "Reinstall forge, the grammars are bundled with it and don't match its tree-sitter version",
+        )
+    }
+}
+
+/// Renders the checks along with the fixes of the failed ones.
+pub fn render(checks: &[Check]) -> String {
+    let mut output = String::new();
+    for check in checks {
+        let title = if check.passed {
+            TitleFormat::success(check.name).sub_title(&check.detail)
+        } else {
+            TitleFormat::failed(check.name).error(&check.detail)
+        };
+        let _ = writeln!(output, "{title}");
+        if let Some(fix) = &check.fix {
+            let _ = writeln!(output, "  fix: {fix}");
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_shell_missing_in_restricted_mode() {
+        let actual = shell("/nonexistent/rbash", true);
+        assert!(!actual.passed);
+        assert!(actual.fix.unwrap().contains("--restricted"));
+    }
+
+    #[test]
+    fn test_base_path_writable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("forge");
+
+        let actual = base_path(&path);
+        assert!(actual.passed);
+        assert!(path.is_dir());
+        assert_eq!(std::fs::read_dir(&path).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_base_path_not_writable() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+
+        // A file can't be used as a directory
+        let actual = base_path(file.path());
+        assert!(!actual.passed);
+    }
+
+    #[test]
+    fn test_config_files_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "modle = \"gpt-4o\"\n").unwrap();
+
+        let actual = config_files(&[dir.path().join("missing.toml"), path.clone()]);
+        let expected = Check::fail(
+            "Config files",
+            format!(
+                "Invalid config: {}: Unknown setting `modle`",
+                path.display()
+            ),
+            "Fix the file, see the README for the available settings",
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_grammars() {
+        let actual = grammars(vec![
+            ("Rust", Ok(())),
+            ("Scala", Err("Incompatible language version 16")),
+        ]);
+        let expected = Check::fail(
+            "Tree-sitter grammars",
+            "failed to load Scala (Incompatible language version 16)",
+            "Reinstall forge, the grammars are bundled with it and don't match its tree-sitter version",
+        );
+        assert_eq!(actual, expected);
+    }
+}
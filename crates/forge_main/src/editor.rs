@@ -4,8 +4,11 @@ use reedline::{
     default_emacs_keybindings, ColumnarMenu, DefaultHinter, EditCommand, Emacs, FileBackedHistory,
     KeyCode, KeyModifiers, MenuBuilder, Prompt, Reedline, ReedlineEvent, ReedlineMenu, Signal,
 };
+use tempfile::NamedTempFile;
 
 use super::completer::InputCompleter;
+use crate::external_editor;
+use crate::validator::FenceValidator;
 
 // TODO: Store the last `HISTORY_CAPACITY` commands in the history file
 const HISTORY_CAPACITY: usize = 1024;
@@ -13,6 +16,9 @@ const COMPLETION_MENU: &str = "completion_menu";
 
 pub struct ForgeEditor {
     editor: Reedline,
+    /// File the input is edited in with the external editor, removed along
+    /// with the editor
+    _draft: Option<NamedTempFile>,
 }
 
 pub enum ReadResult {
@@ -50,6 +56,27 @@ impl ForgeEditor {
             ReedlineEvent::SearchHistory,
         );
 
+        // on CTRL + e press opens the draft in $EDITOR and sends the saved content
+        keybindings.add_binding(
+            KeyModifiers::CONTROL,
+            KeyCode::Char('e'),
+            ReedlineEvent::Multiple(vec![ReedlineEvent::OpenEditor, ReedlineEvent::Submit]),
+        );
+
+        // on CTRL + t press records voice input into the prompt
+        keybindings.add_binding(
+            KeyModifiers::CONTROL,
+            KeyCode::Char('t'),
+            ReedlineEvent::ExecuteHostCommand("/voice".to_string()),
+        );
+
+        // on SHIFT + Enter press inserts a newline
+        keybindings.add_binding(
+            KeyModifiers::SHIFT,
+            KeyCode::Enter,
+            ReedlineEvent::Edit(vec![EditCommand::InsertNewline]),
+        );
+
         // on ALT + Enter press inserts a newline
         keybindings.add_binding(
             KeyModifiers::ALT,
@@ -60,7 +87,7 @@ impl ForgeEditor {
         keybindings
     }
 
-    pub fn start(env: Environment) -> Self {
+    pub fn start(env: Environment, completer: InputCompleter) -> Self {
         // Store file history in system config directory
         let history_file = env.history_path();
 
@@ -77,18 +104,37 @@ impl ForgeEditor {
 
         let edit_mode = Box::new(Emacs::new(Self::init()));
 
-        let editor = Reedline::create()
-            .with_completer(Box::new(InputCompleter::new(env.cwd)))
+        let mut editor = Reedline::create()
+            .with_completer(Box::new(completer))
             .with_history(history)
             .with_hinter(Box::new(
                 DefaultHinter::default().with_style(Style::new().fg(Color::DarkGray)),
             ))
             .with_menu(ReedlineMenu::EngineCompleter(completion_menu))
             .with_edit_mode(edit_mode)
+            .with_validator(Box::new(FenceValidator))
+            // Pasted text is inserted as a whole instead of being submitted line by line
+            .use_bracketed_paste(true)
+            // Required by terminals to report SHIFT + Enter as a distinct key
+            .use_kitty_keyboard_enhancement(true)
             .with_quick_completions(true)
             .with_partial_completions(true)
             .with_ansi_colors(true);
-        Self { editor }
+        // note: the input can't be edited externally without a draft file.
+        let draft = external_editor::draft_file().ok();
+        if let Some(draft) = &draft {
+            editor = editor.with_buffer_editor(
+                external_editor::editor_command(),
+                draft.path().to_path_buf(),
+            );
+        }
+        Self { editor, _draft: draft }
+    }
+
+    /// Fills the input with `text`, for the next prompt.
+    pub fn draft(&mut self, text: &str) {
+        self.editor
+            .run_edit_commands(&[EditCommand::InsertString(text.to_string())]);
     }
 
     pub fn prompt(&mut self, prompt: &dyn Prompt) -> anyhow::Result<ReadResult> {
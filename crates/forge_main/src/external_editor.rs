@@ -0,0 +1,66 @@
+//! Composes prompts in the user's preferred text editor.
+
+use std::io::Write;
+use std::process::Command;
+
+use anyhow::{bail, Context};
+use tempfile::NamedTempFile;
+
+/// Builds the command that launches the user's editor, as configured via
+/// `$VISUAL` or `$EDITOR`. Editors configured with arguments (eg: `code
+/// --wait`) are supported.
+pub fn editor_command() -> Command {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| {
+            if cfg!(target_os = "windows") {
+                "notepad".to_string()
+            } else {
+                "vi".to_string()
+            }
+        });
+
+    let mut parts = editor.split_whitespace();
+    // The editor has been checked to be non-empty above
+    let mut command = Command::new(parts.next().unwrap_or_default());
+    command.args(parts);
+    command
+}
+
+/// Creates the file the draft is written to while being edited, under a
+/// random name so that no other user can plant it beforehand. It's removed
+/// once dropped. The markdown extension enables syntax highlighting in most
+/// editors.
+pub fn draft_file() -> std::io::Result<NamedTempFile> {
+    tempfile::Builder::new()
+        .prefix("forge-prompt-")
+        .suffix(".md")
+        .tempfile()
+}
+
+/// Opens the editor with the given draft and returns the saved content.
+pub fn compose(draft: &str) -> anyhow::Result<String> {
+    let mut file = draft_file().context("Failed to create the draft file")?;
+    let path = file.path().to_path_buf();
+    file.write_all(draft.as_bytes())
+        .and_then(|_| file.flush())
+        .with_context(|| format!("Failed to write draft to {}", path.display()))?;
+
+    let mut command = editor_command();
+    let status = command
+        .arg(&path)
+        .status()
+        .with_context(|| format!("Failed to launch editor {:?}", command.get_program()));
+    // note: the content is read from the path since editors can replace the
+    // file rather than write to it.
+    let content = std::fs::read_to_string(&path);
+    drop(file);
+
+    if !status?.success() {
+        bail!("Editor exited with a non-zero status, discarding the draft")
+    }
+
+    Ok(content?.trim().to_string())
+}
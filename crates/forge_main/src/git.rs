@@ -0,0 +1,73 @@
+//! Commits the changes of the session with git.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use tokio::process::Command;
+
+/// Runs git in the given directory and returns its output.
+async fn run(cwd: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .await
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Commits only the given files, leaving anything else staged by the user
+/// untouched, and returns the short hash of the commit.
+pub async fn commit(cwd: &Path, paths: &[PathBuf], message: &str) -> anyhow::Result<String> {
+    let paths = paths
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    let paths = paths.iter().map(String::as_str);
+
+    let add = ["add", "-A", "--"].into_iter().chain(paths.clone());
+    run(cwd, &add.collect::<Vec<_>>()).await?;
+    let commit = ["commit", "-m", message, "--"].into_iter().chain(paths);
+    run(cwd, &commit.collect::<Vec<_>>()).await?;
+    run(cwd, &["rev-parse", "--short", "HEAD"]).await
+}
+
+/// Cleans up the commit message written by the model, which sometimes wraps it
+/// in a code block.
+pub fn commit_message(text: &str) -> String {
+    let text = text.trim();
+    let text = match text.strip_prefix("```") {
+        Some(fenced) => fenced
+            .split_once('\n')
+            .map(|(_, body)| body)
+            .unwrap_or_default()
+            .trim_end()
+            .trim_end_matches("```"),
+        None => text,
+    };
+    text.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_commit_message() {
+        let expected = "feat(cli): add /commit\n\nCommits the session's changes.";
+        assert_eq!(commit_message(&format!("\n{expected}\n")), expected);
+        assert_eq!(
+            commit_message(&format!("```text\n{expected}\n```")),
+            expected
+        );
+    }
+}
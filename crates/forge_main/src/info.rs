@@ -1,7 +1,7 @@
 use std::fmt;
 
 use colored::Colorize;
-use forge_api::{Environment, Usage};
+use forge_api::{Context, ContextMessage, Environment, Role, Usage, Workflow};
 
 pub enum Section {
     Title(String),
@@ -39,6 +39,7 @@ impl From<&Usage> for Info {
         Info::new()
             .add_title("Usage".to_string())
             .add_item("Prompt", usage.prompt_tokens)
+            .add_item("Cached", usage.cached_tokens)
             .add_item("Completion", usage.completion_tokens)
             .add_item("Total", usage.total_tokens)
     }
@@ -52,11 +53,81 @@ impl From<&Environment> for Info {
             .add_item("PID", env.pid)
             .add_item("Working Directory", env.cwd.display())
             .add_item("Shell", &env.shell)
+            .add_item("Trusted", env.trusted)
             .add_title("Paths")
             .add_item("Config", env.base_path.display())
             .add_item("Logs", env.log_path().display())
             .add_item("Database", env.db_path().display())
             .add_item("History", env.history_path().display())
+            .add_item("Statistics", env.stats_path().display())
+    }
+}
+
+impl From<&Workflow> for Info {
+    fn from(workflow: &Workflow) -> Self {
+        let mut info = Info::new();
+        for agent in &workflow.agents {
+            let title = if agent.enable {
+                agent.id.to_string()
+            } else {
+                format!("{} (disabled)", agent.id)
+            };
+            let tools = agent
+                .tools
+                .iter()
+                .map(|tool| tool.as_str())
+                .collect::<Vec<_>>();
+            info = info
+                .add_title(title)
+                .add_item("Model", &agent.model)
+                .add_item("Tools", tools.join(", "))
+                .add_item("Subscribe", agent.subscribe.join(", "));
+        }
+        info
+    }
+}
+
+/// Estimated tokens of the parts of an agent's context, at about 4
+/// characters per token as `Context::estimate_tokens` does.
+impl From<&Context> for Info {
+    fn from(context: &Context) -> Self {
+        // note: system, user, assistant and tool results, in that order.
+        let mut parts = [(0, 0); 4];
+        for message in &context.messages {
+            let (part, len) = match message {
+                ContextMessage::ContentMessage(message) => {
+                    let calls =
+                        message.tool_calls.iter().flatten().map(|call| {
+                            call.name.as_str().len() + call.arguments.to_string().len()
+                        });
+                    let part = match message.role {
+                        Role::System => 0,
+                        Role::User => 1,
+                        Role::Assistant => 2,
+                    };
+                    (part, message.content.len() + calls.sum::<usize>())
+                }
+                ContextMessage::ToolMessage(result) => (3, result.content.len()),
+            };
+            parts[part].0 += 1;
+            parts[part].1 += len / 4;
+        }
+
+        let mut info = Info::new().add_title("Context");
+        for ((messages, tokens), label) in
+            parts
+                .into_iter()
+                .zip(["System", "User", "Assistant", "Tool results"])
+        {
+            info = info.add_item(label, format!("~{tokens} tokens in {messages} messages"));
+        }
+        let tools = context
+            .tools
+            .iter()
+            .map(|tool| tool.name.as_str())
+            .collect::<Vec<_>>();
+        info.add_item("Tools", tools.join(", "))
+            .add_item("Total", format!("~{} tokens", context.estimate_tokens()))
     }
 }
 
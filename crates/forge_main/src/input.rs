@@ -1,25 +1,115 @@
 use std::path::PathBuf;
+use std::sync::Mutex;
 
 use async_trait::async_trait;
-use forge_api::{Environment, Usage};
+use forge_api::{CustomCommand, Environment, Model, Usage};
 use forge_display::TitleFormat;
 use tokio::fs;
 
+use crate::completer::InputCompleter;
 use crate::console::CONSOLE;
 use crate::editor::{ForgeEditor, ReadResult};
+use crate::external_editor;
 use crate::model::{Command, UserInput};
 use crate::prompt::ForgePrompt;
+use crate::tui::Screen;
 
 /// Console implementation for handling user input via command line.
 #[derive(Debug)]
 pub struct Console {
     env: Environment,
+    custom: Vec<CustomCommand>,
+    /// Models the `/model` command is completed with
+    models: Vec<Model>,
+    /// Full screen interface the input is read from, if it's shown
+    screen: Option<Screen>,
+    /// Text the input is filled with the next time the user is prompted
+    draft: Mutex<Option<String>>,
 }
 
 impl Console {
     /// Creates a new instance of `Console`.
     pub fn new(env: Environment) -> Self {
-        Self { env }
+        Self {
+            env,
+            custom: Vec::new(),
+            models: Vec::new(),
+            screen: None,
+            draft: Mutex::default(),
+        }
+    }
+
+    /// Sets the custom commands that are parsed and completed along with the
+    /// built-in ones.
+    pub fn custom_commands(&mut self, custom: Vec<CustomCommand>) {
+        self.custom = custom;
+        if let Some(screen) = &self.screen {
+            screen.completer(self.completer());
+        }
+    }
+
+    /// Sets the models the `/model` command is completed with.
+    pub fn models(&mut self, models: Vec<Model>) {
+        self.models = models;
+        if let Some(screen) = &self.screen {
+            screen.completer(self.completer());
+        }
+    }
+
+    /// Shows the model the agents use in the status bar of the screen.
+    pub fn model(&self, model: impl Into<String>) {
+        if let Some(screen) = &self.screen {
+            screen.model(model.into());
+        }
+    }
+
+    /// Shows the full screen interface, until `leave_screen` is called.
+    pub fn enter_screen(&mut self, model: Option<String>) -> anyhow::Result<()> {
+        let screen = Screen::start(self.env.history_path(), self.completer(), model)?;
+        self.screen = Some(screen);
+        Ok(())
+    }
+
+    /// Closes the full screen interface, printing the conversation to the
+    /// terminal.
+    pub fn leave_screen(&mut self) {
+        self.screen = None;
+    }
+
+    pub fn has_screen(&self) -> bool {
+        self.screen.is_some()
+    }
+
+    /// Updates the status bar of the screen, while the agent is working.
+    pub fn status(&self, input: PromptInput) {
+        if let Some(screen) = &self.screen {
+            screen.status(input);
+        }
+    }
+
+    /// Opens the draft in the editor and returns the saved content.
+    pub fn compose(&self, draft: &str) -> anyhow::Result<String> {
+        match &self.screen {
+            Some(screen) => screen.suspend(|| external_editor::compose(draft)),
+            None => external_editor::compose(draft),
+        }
+    }
+
+    /// Fills the input with `text` the next time the user is prompted, eg:
+    /// to edit the last message.
+    pub fn draft(&self, text: impl Into<String>) {
+        *self.draft.lock().unwrap_or_else(|error| error.into_inner()) = Some(text.into());
+    }
+
+    fn take_draft(&self) -> Option<String> {
+        self.draft
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .take()
+    }
+
+    fn completer(&self) -> InputCompleter {
+        InputCompleter::new(self.env.cwd.clone(), self.custom.clone()).models(self.models.clone())
     }
 }
 
@@ -35,19 +125,45 @@ impl UserInput for Console {
     }
 
     async fn prompt(&self, input: Option<Self::PromptInput>) -> anyhow::Result<Command> {
+        Ok(match self.read(input).await? {
+            Some(text) => Command::parse(&text, &self.custom),
+            None => Command::Exit,
+        })
+    }
+}
+
+impl Console {
+    /// Reads the answer to a question of an agent, `None` if the user exits
+    /// instead.
+    pub async fn ask(&self, input: Option<PromptInput>) -> anyhow::Result<Option<String>> {
+        self.read(input).await
+    }
+
+    async fn read(&self, input: Option<PromptInput>) -> anyhow::Result<Option<String>> {
         CONSOLE.writeln("")?;
-        let mut engine = ForgeEditor::start(self.env.clone());
+        let draft = self.take_draft();
+        if let Some(screen) = &self.screen {
+            if let Some(draft) = draft {
+                screen.draft(draft);
+            }
+            return Ok(match screen.prompt(input).await {
+                ReadResult::Success(text) => Some(text),
+                _ => None,
+            });
+        }
+        let mut engine = ForgeEditor::start(self.env.clone(), self.completer());
+        if let Some(draft) = draft {
+            engine.draft(&draft);
+        }
         let prompt: ForgePrompt = input.map(Into::into).unwrap_or_default();
 
         loop {
             let result = engine.prompt(&prompt);
             match result {
                 Ok(ReadResult::Continue) => continue,
-                Ok(ReadResult::Exit) => return Ok(Command::Exit),
+                Ok(ReadResult::Exit) => return Ok(None),
                 Ok(ReadResult::Empty) => continue,
-                Ok(ReadResult::Success(text)) => {
-                    return Ok(Command::parse(&text));
-                }
+                Ok(ReadResult::Success(text)) => return Ok(Some(text)),
                 Err(e) => {
                     CONSOLE.writeln(TitleFormat::failed(e.to_string()).format())?;
                 }
@@ -60,13 +176,14 @@ pub enum PromptInput {
     Update {
         title: Option<String>,
         usage: Option<Usage>,
+        cost: Option<f64>,
     },
 }
 
 impl From<PromptInput> for ForgePrompt {
     fn from(input: PromptInput) -> Self {
         match input {
-            PromptInput::Update { title, usage } => {
+            PromptInput::Update { title, usage, cost } => {
                 let mut prompt = ForgePrompt::default();
                 if let Some(title) = title {
                     prompt.title(title);
@@ -74,6 +191,9 @@ impl From<PromptInput> for ForgePrompt {
                 if let Some(usage) = usage {
                     prompt.usage(usage);
                 }
+                if let Some(cost) = cost {
+                    prompt.cost(cost);
+                }
                 prompt
             }
         }
@@ -0,0 +1,235 @@
+//! Journal of the file changes made by tools, used to restore the files of the
+//! workspace when branching from a checkpoint and to commit the changes of the
+//! session.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use forge_api::FileChange;
+use forge_display::DiffFormat;
+
+/// Content of a file right before a tool changed it, `None` when the file
+/// didn't exist.
+#[derive(Debug)]
+struct JournalEntry {
+    path: PathBuf,
+    content: Option<Vec<u8>>,
+}
+
+/// Records the files touched by tools in the order they were changed. Changes
+/// made through shell commands are not tracked.
+#[derive(Debug, Default)]
+pub struct ChangeJournal {
+    entries: Vec<JournalEntry>,
+    /// Number of entries recorded when each checkpoint was taken
+    checkpoints: HashMap<String, usize>,
+    /// Content of the files when their changes were last committed, which
+    /// the next changes are diffed against
+    committed: HashMap<PathBuf, Option<Vec<u8>>>,
+}
+
+impl ChangeJournal {
+    /// Records the content the files had before the tool call changed them.
+    pub fn record(&mut self, files: &[FileChange]) {
+        self.entries.extend(
+            files
+                .iter()
+                .map(|file| JournalEntry { path: file.path.clone(), content: file.old.clone() }),
+        );
+    }
+
+    /// Marks the current position of the journal with the given name.
+    pub fn checkpoint(&mut self, name: impl ToString) {
+        self.checkpoints
+            .insert(name.to_string(), self.entries.len());
+    }
+
+    /// Unified diffs of the files changed since the start of the session, or
+    /// their last commit, in the order they were first changed.
+    pub fn changes(&self) -> Vec<(PathBuf, String)> {
+        let mut changes = Vec::new();
+        for entry in &self.entries {
+            if changes.iter().any(|(path, _)| *path == entry.path) {
+                continue;
+            }
+            let old = self
+                .committed
+                .get(&entry.path)
+                .unwrap_or(&entry.content)
+                .clone()
+                .unwrap_or_default();
+            let new = std::fs::read(&entry.path).unwrap_or_default();
+            if old != new {
+                let path = entry.path.display().to_string();
+                let diff = DiffFormat::unified(
+                    &path,
+                    &String::from_utf8_lossy(&old),
+                    &String::from_utf8_lossy(&new),
+                );
+                changes.push((entry.path.clone(), diff));
+            }
+        }
+        changes
+    }
+
+    /// Diffs the next changes of the files against their current content.
+    pub fn commit(&mut self, paths: &[PathBuf]) {
+        for path in paths {
+            self.committed
+                .insert(path.clone(), std::fs::read(path).ok());
+        }
+    }
+
+    /// Reverts every change recorded after the named checkpoint, newest first,
+    /// and returns the restored paths. Checkpoints taken after it are
+    /// discarded.
+    pub fn revert(&mut self, name: &str) -> anyhow::Result<Vec<PathBuf>> {
+        let position = *self
+            .checkpoints
+            .get(name)
+            .with_context(|| format!("Checkpoint not found: {name}"))?;
+
+        let mut restored = Vec::new();
+        for entry in self.entries.split_off(position).into_iter().rev() {
+            // note: files that didn't exist and still don't aren't reported.
+            if entry.content.is_none() && !entry.path.exists() {
+                continue;
+            }
+            match &entry.content {
+                Some(content) => restore(&entry.path, content),
+                None if entry.path.is_file() => std::fs::remove_file(&entry.path),
+                None => Ok(()),
+            }
+            .with_context(|| format!("Failed to restore {}", entry.path.display()))?;
+
+            if !restored.contains(&entry.path) {
+                restored.push(entry.path);
+            }
+        }
+
+        self.checkpoints.retain(|_, index| *index <= position);
+        Ok(restored)
+    }
+}
+
+/// Writes the content back, along with the directories that were removed or
+/// moved with the file.
+fn restore(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// The change of the file from `old` to its current content.
+    fn change(path: &Path, old: Option<&str>) -> Vec<FileChange> {
+        vec![FileChange::new(
+            path.to_path_buf(),
+            old.map(|old| old.as_bytes().to_vec()),
+            std::fs::read(path).ok(),
+        )]
+    }
+
+    #[test]
+    fn test_revert_restores_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let patched = dir.path().join("patched.txt");
+        let created = dir.path().join("created.txt");
+        let mut journal = ChangeJournal::default();
+
+        journal.checkpoint("start");
+        std::fs::write(&patched, "world\n").unwrap();
+        journal.record(&change(&patched, Some("hello\n")));
+        std::fs::write(&patched, "again\n").unwrap();
+        journal.record(&change(&patched, Some("world\n")));
+        std::fs::write(&created, "new\n").unwrap();
+        journal.record(&change(&created, None));
+
+        let actual = journal.revert("start").unwrap();
+
+        assert_eq!(actual, vec![created.clone(), patched.clone()]);
+        assert_eq!(std::fs::read_to_string(&patched).unwrap(), "hello\n");
+        assert!(!created.exists());
+    }
+
+    #[test]
+    fn test_revert_discards_later_checkpoints() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        let mut journal = ChangeJournal::default();
+
+        journal.checkpoint("first");
+        journal.record(&change(&path, None));
+        journal.checkpoint("second");
+        journal.revert("first").unwrap();
+
+        assert!(journal.revert("second").is_err());
+        assert!(journal.revert("first").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let patched = dir.path().join("patched.txt");
+        let mut journal = ChangeJournal::default();
+
+        std::fs::write(&patched, "world\n").unwrap();
+        journal.record(&change(&patched, Some("hello\n")));
+        std::fs::write(&patched, "again\n").unwrap();
+        journal.record(&change(&patched, Some("world\n")));
+
+        let actual = journal.changes();
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].0, patched);
+        assert!(actual[0].1.contains("-hello\n+again"), "{}", actual[0].1);
+
+        journal.commit(std::slice::from_ref(&patched));
+        assert!(journal.changes().is_empty());
+    }
+
+    #[test]
+    fn test_revert_restores_removed_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("removed/inner/a.bin");
+        let mut journal = ChangeJournal::default();
+
+        journal.checkpoint("start");
+        journal.record(&[FileChange::new(
+            path.clone(),
+            Some(vec![0, 159, 146, 150]),
+            None,
+        )]);
+        journal.revert("start").unwrap();
+
+        let actual = std::fs::read(&path).unwrap();
+        assert_eq!(actual, vec![0, 159, 146, 150]);
+    }
+
+    #[test]
+    fn test_revert_undoes_move() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("old/a.txt");
+        let destination = dir.path().join("new/a.txt");
+        std::fs::create_dir(dir.path().join("new")).unwrap();
+        std::fs::write(&destination, "a\n").unwrap();
+        let mut journal = ChangeJournal::default();
+
+        journal.checkpoint("start");
+        journal.record(&[
+            FileChange::new(source.clone(), Some(b"a\n".to_vec()), None),
+            FileChange::new(destination.clone(), None, Some(b"a\n".to_vec())),
+        ]);
+        let actual = journal.revert("start").unwrap();
+
+        assert_eq!(actual, vec![destination.clone(), source.clone()]);
+        assert_eq!(std::fs::read_to_string(&source).unwrap(), "a\n");
+        assert!(!destination.exists());
+    }
+}
@@ -1,14 +1,41 @@
+mod acp;
+mod agent_tree;
+mod audit;
+mod auth;
 mod banner;
+mod batch;
+mod bridge;
 mod cli;
+mod clipboard;
 mod completer;
+mod config;
 mod console;
+mod cost;
+mod doctor;
 mod editor;
+mod external_editor;
+mod git;
 mod info;
 mod input;
+mod journal;
 mod model;
 mod normalize;
 mod prompt;
+mod rpc;
+mod setup;
+mod speech;
+mod stats;
+mod transcript;
+mod trust;
+mod tui;
 mod ui;
+mod validator;
+mod voice;
+mod watch;
 
-pub use cli::Cli;
+pub use acp::AcpServer;
+pub use bridge::Bridge;
+pub use cli::{Cli, TopLevelCommand};
+pub use setup::Setup;
+pub use trust::ask_trust;
 pub use ui::UI;
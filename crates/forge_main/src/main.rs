@@ -1,17 +1,48 @@
+use std::process::ExitCode;
 use std::sync::Arc;
 
 use anyhow::Result;
 use clap::Parser;
-use forge::{Cli, UI};
+use forge::{ask_trust, AcpServer, Bridge, Cli, Setup, TopLevelCommand, UI};
 use forge_api::ForgeAPI;
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     // Initialize and run the UI
     let cli = Cli::parse();
-    let api = Arc::new(ForgeAPI::init(cli.restricted));
+    // note: the trust is asked for before anything is read from the workspace.
+    if cli.subcommand.is_none() {
+        ask_trust()?;
+    }
+    // note: the keys are managed by `forge auth` and the trust by `forge trust`,
+    // which don't need one.
+    let auth = matches!(
+        cli.subcommand,
+        Some(TopLevelCommand::Auth { .. } | TopLevelCommand::Trust { .. })
+    );
+    // note: the bridge and the ACP server can't prompt, stdio carries their
+    // messages.
+    let bridge = matches!(cli.subcommand, Some(TopLevelCommand::LspBridge));
+    let acp = matches!(cli.subcommand, Some(TopLevelCommand::Acp));
+    let setup = if auth || bridge || acp || forge_api::is_configured() {
+        None
+    } else {
+        Some(Setup::prompt()?)
+    };
+    let api = Arc::new(ForgeAPI::init(cli.config()));
+    if let Some(setup) = setup {
+        setup.finish(api.as_ref()).await?;
+    }
+    if bridge {
+        Bridge::serve(api, cli.config(), cli.workflow.clone()).await?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    if acp {
+        AcpServer::serve(api, cli.config(), cli.workflow.clone()).await?;
+        return Ok(ExitCode::SUCCESS);
+    }
     let mut ui = UI::init(cli, api)?;
-    ui.run().await?;
+    let code = ui.run().await?;
 
-    Ok(())
+    Ok(code)
 }
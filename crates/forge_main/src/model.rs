@@ -14,6 +14,22 @@ fn humanize_context_length(length: u64) -> String {
     }
 }
 
+/// Context length and pricing of the model, where they're known.
+pub fn model_summary(model: &Model) -> String {
+    let mut summary = Vec::new();
+    if let Some(context_length) = model.context_length {
+        summary.push(humanize_context_length(context_length));
+    }
+    if let Some(pricing) = model.pricing {
+        summary.push(format!(
+            "${:.2}/${:.2} per 1M tokens",
+            pricing.prompt * 1_000_000.0,
+            pricing.completion * 1_000_000.0
+        ));
+    }
+    summary.join(", ")
+}
+
 impl From<&[Model]> for Info {
     fn from(models: &[Model]) -> Self {
         let mut info = Info::new();
@@ -33,13 +49,11 @@ impl From<&[Model]> for Info {
         for (provider, provider_models) in models_by_provider.iter() {
             info = info.add_title(provider.to_string());
             for model in provider_models {
-                if let Some(context_length) = model.context_length {
-                    info = info.add_item(
-                        &model.name,
-                        format!("{} ({})", model.id, humanize_context_length(context_length)),
-                    );
-                } else {
+                let summary = model_summary(model);
+                if summary.is_empty() {
                     info = info.add_item(&model.name, format!("{}", model.id));
+                } else {
+                    info = info.add_item(&model.name, format!("{} ({summary})", model.id));
                 }
             }
         }
@@ -51,6 +65,7 @@ impl From<&[Model]> for Info {
 use std::path::PathBuf;
 
 use async_trait::async_trait;
+use forge_api::CustomCommand;
 
 /// Represents user input types in the chat application.
 ///
@@ -75,6 +90,94 @@ pub enum Command {
     Models,
     /// Dumps the current conversation into a json file
     Dump,
+    /// Opens the user's $EDITOR prefilled with the given draft and sends the
+    /// saved content as a message.
+    /// This can be triggered with the '/edit' command.
+    Edit(String),
+    /// Exports the current conversation into a shareable transcript. Holds the
+    /// raw `[md|html|json] <path>` arguments.
+    /// This can be triggered with the '/export' command.
+    Export(String),
+    /// Exports the conversation, with the workflow and the context of every
+    /// agent, to a `.forgeconv` file to continue it elsewhere.
+    /// This can be triggered with the '/export-conversation [path]' command.
+    ExportConversation(String),
+    /// Continues the conversation of a `.forgeconv` file in a new session.
+    /// This can be triggered with the '/import-conversation <path>' command.
+    ImportConversation(String),
+    /// Displays the spend of the current conversation.
+    /// This can be triggered with the '/cost' command.
+    Cost,
+    /// Displays or changes the generation parameters. Holds the raw
+    /// `set <parameter> <value>` arguments.
+    /// This can be triggered with the '/config' command.
+    Config(String),
+    /// Snapshots the conversation and the files changed by tools under the
+    /// given name.
+    /// This can be triggered with the '/checkpoint <name>' command.
+    Checkpoint(String),
+    /// Rolls the conversation and the files changed by tools back to the given
+    /// checkpoint.
+    /// This can be triggered with the '/branch <checkpoint>' command.
+    Branch(String),
+    /// Switches the model of the agents for the next turns, or lists the
+    /// available models when none is given.
+    /// This can be triggered with the '/model <id>' command.
+    Model(String),
+    /// Lists the agents of the workflow with their models and tools.
+    /// This can be triggered with the '/agents' command.
+    Agents,
+    /// Sends the next message to the given agent only, instead of the agents
+    /// subscribed to the events of the user.
+    /// This can be triggered with the '/agent <id>' command.
+    Agent(String),
+    /// Displays the estimated tokens of the context of the given agent.
+    /// This can be triggered with the '/context <agent>' command.
+    Context(String),
+    /// Rolls the last exchange back, along with the files changed by tools
+    /// while it went on, and sends the last message again.
+    /// This can be triggered with the '/retry' command.
+    Retry,
+    /// Fills the input with the last message, to edit it before sending it.
+    /// This can be triggered with the '/editlast' command.
+    EditLast,
+    /// Restricts the agents to read-only tools until they submit a plan and
+    /// the user approves it.
+    /// This can be triggered with the '/plan' command.
+    Plan,
+    /// Makes every tool of the agents available again, leaving the planning
+    /// mode.
+    /// This can be triggered with the '/act' command.
+    Act,
+    /// Commits the files changed during the session with a message written
+    /// by an agent.
+    /// This can be triggered with the '/commit' command.
+    Commit,
+    /// Fills the input with the text on the clipboard, or attaches the image
+    /// on it to the next message.
+    /// This can be triggered with the '/paste' command.
+    Paste,
+    /// Records the microphone until a pause and fills the input with the
+    /// transcript, when voice input is enabled with `--voice`.
+    /// This can be triggered with the '/voice' command or Ctrl+T.
+    Voice,
+    /// Pins a file to the context of the agents, which get its latest content
+    /// at the start of every turn. Lists the pinned files without a path.
+    /// This can be triggered with the '/pin <path>' command.
+    Pin(String),
+    /// Unpins the given file, or all of them without a path.
+    /// This can be triggered with the '/unpin [path]' command.
+    Unpin(String),
+    /// Lists the sessions started with `/new`, or switches to the given one,
+    /// each keeping its conversation, usage and changes.
+    /// This can be triggered with the '/switch [n]' command.
+    Switch(String),
+    /// A slash command defined in the workflow along with the text following
+    /// it.
+    Custom {
+        command: CustomCommand,
+        args: String,
+    },
 }
 
 impl Command {
@@ -91,6 +194,28 @@ impl Command {
             "/exit".to_string(),
             "/models".to_string(),
             "/dump".to_string(),
+            "/edit".to_string(),
+            "/export".to_string(),
+            "/export-conversation".to_string(),
+            "/import-conversation".to_string(),
+            "/cost".to_string(),
+            "/config".to_string(),
+            "/checkpoint".to_string(),
+            "/branch".to_string(),
+            "/model".to_string(),
+            "/agents".to_string(),
+            "/agent".to_string(),
+            "/context".to_string(),
+            "/retry".to_string(),
+            "/editlast".to_string(),
+            "/plan".to_string(),
+            "/act".to_string(),
+            "/commit".to_string(),
+            "/paste".to_string(),
+            "/voice".to_string(),
+            "/pin".to_string(),
+            "/unpin".to_string(),
+            "/switch".to_string(),
         ]
     }
 
@@ -99,12 +224,10 @@ impl Command {
     /// This function:
     /// - Trims whitespace from the input
     /// - Recognizes and validates commands (starting with '/')
+    /// - Resolves the `custom` commands defined in the workflow, built-in
+    ///   commands take precedence over custom ones with the same name
     /// - Converts regular text into messages
-    ///
-    /// # Returns
-    /// - `Ok(Input)` - Successfully parsed input
-    /// - `Err` - Input was an invalid command
-    pub fn parse(input: &str) -> Self {
+    pub fn parse(input: &str, custom: &[CustomCommand]) -> Self {
         let trimmed = input.trim();
 
         match trimmed {
@@ -113,9 +236,73 @@ impl Command {
             "/exit" => Command::Exit,
             "/models" => Command::Models,
             "/dump" => Command::Dump,
-            text => Command::Message(text.to_string()),
+            "/edit" => Command::Edit(String::new()),
+            "/export" => Command::Export(String::new()),
+            "/export-conversation" => Command::ExportConversation(String::new()),
+            "/import-conversation" => Command::ImportConversation(String::new()),
+            "/cost" => Command::Cost,
+            "/config" => Command::Config(String::new()),
+            "/checkpoint" => Command::Checkpoint(String::new()),
+            "/branch" => Command::Branch(String::new()),
+            "/model" => Command::Model(String::new()),
+            "/agents" => Command::Agents,
+            "/agent" => Command::Agent(String::new()),
+            "/context" => Command::Context(String::new()),
+            "/retry" => Command::Retry,
+            "/editlast" => Command::EditLast,
+            "/plan" => Command::Plan,
+            "/act" => Command::Act,
+            "/commit" => Command::Commit,
+            "/paste" => Command::Paste,
+            "/voice" => Command::Voice,
+            "/pin" => Command::Pin(String::new()),
+            "/unpin" => Command::Unpin(String::new()),
+            "/switch" => Command::Switch(String::new()),
+            text => {
+                if let Some(draft) = text.strip_prefix("/edit ") {
+                    Command::Edit(draft.trim().to_string())
+                } else if let Some(args) = text.strip_prefix("/export ") {
+                    Command::Export(args.trim().to_string())
+                } else if let Some(path) = text.strip_prefix("/export-conversation ") {
+                    Command::ExportConversation(path.trim().to_string())
+                } else if let Some(path) = text.strip_prefix("/import-conversation ") {
+                    Command::ImportConversation(path.trim().to_string())
+                } else if let Some(args) = text.strip_prefix("/config ") {
+                    Command::Config(args.trim().to_string())
+                } else if let Some(name) = text.strip_prefix("/checkpoint ") {
+                    Command::Checkpoint(name.trim().to_string())
+                } else if let Some(name) = text.strip_prefix("/branch ") {
+                    Command::Branch(name.trim().to_string())
+                } else if let Some(path) = text.strip_prefix("/pin ") {
+                    Command::Pin(path.trim().to_string())
+                } else if let Some(path) = text.strip_prefix("/unpin ") {
+                    Command::Unpin(path.trim().to_string())
+                } else if let Some(session) = text.strip_prefix("/switch ") {
+                    Command::Switch(session.trim().to_string())
+                } else if let Some(model) = text.strip_prefix("/model ") {
+                    Command::Model(model.trim().to_string())
+                } else if let Some(agent) = text.strip_prefix("/agent ") {
+                    Command::Agent(agent.trim().to_string())
+                } else if let Some(agent) = text.strip_prefix("/context ") {
+                    Command::Context(agent.trim().to_string())
+                } else if let Some((command, args)) = Self::parse_custom(text, custom) {
+                    Command::Custom { command: command.clone(), args: args.to_string() }
+                } else {
+                    Command::Message(text.to_string())
+                }
+            }
         }
     }
+
+    fn parse_custom<'a>(
+        text: &'a str,
+        custom: &'a [CustomCommand],
+    ) -> Option<(&'a CustomCommand, &'a str)> {
+        let text = text.strip_prefix('/')?;
+        let (name, args) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let command = custom.iter().find(|command| command.name == name)?;
+        Some((command, args.trim()))
+    }
 }
 
 /// A trait for handling user input in the application.
@@ -147,3 +334,117 @@ pub trait UserInput {
     /// * `Err` - An error occurred during input processing
     async fn prompt(&self, input: Option<Self::PromptInput>) -> anyhow::Result<Command>;
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fixture() -> Vec<CustomCommand> {
+        vec![CustomCommand {
+            name: "review".to_string(),
+            description: None,
+            run: Some("git diff".to_string()),
+            only_on_failure: false,
+            prompt: "Review:\n{{output}}".to_string(),
+        }]
+    }
+
+    #[test]
+    fn test_parse_custom_command() {
+        let custom = fixture();
+        let actual = Command::parse("/review  the parser ", &custom);
+        let expected =
+            Command::Custom { command: custom[0].clone(), args: "the parser".to_string() };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_unknown_command_is_message() {
+        let actual = Command::parse("/reviews", &fixture());
+        let expected = Command::Message("/reviews".to_string());
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_retry_commands() {
+        let actual = ["/retry", " /editlast "].map(|input| Command::parse(input, &[]));
+        assert_eq!(actual, [Command::Retry, Command::EditLast]);
+    }
+
+    #[test]
+    fn test_parse_mode_commands() {
+        let actual = ["/plan", "/act"].map(|input| Command::parse(input, &[]));
+        assert_eq!(actual, [Command::Plan, Command::Act]);
+    }
+
+    #[test]
+    fn test_parse_commit() {
+        let actual = Command::parse("/commit", &[]);
+        assert_eq!(actual, Command::Commit);
+    }
+
+    #[test]
+    fn test_parse_paste() {
+        let actual = Command::parse(" /paste ", &[]);
+        assert_eq!(actual, Command::Paste);
+    }
+
+    #[test]
+    fn test_parse_voice() {
+        let actual = Command::parse("/voice", &[]);
+        assert_eq!(actual, Command::Voice);
+    }
+
+    #[test]
+    fn test_parse_pin() {
+        let actual = [
+            Command::parse("/pin src/main.rs", &[]),
+            Command::parse("/unpin", &[]),
+            Command::parse("/unpin  src/main.rs ", &[]),
+        ];
+        let expected = [
+            Command::Pin("src/main.rs".to_string()),
+            Command::Unpin(String::new()),
+            Command::Unpin("src/main.rs".to_string()),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_conversation_files() {
+        let actual = [
+            Command::parse("/export-conversation", &[]),
+            Command::parse("/export-conversation bug.forgeconv", &[]),
+            Command::parse("/import-conversation  bug.forgeconv ", &[]),
+        ];
+        let expected = [
+            Command::ExportConversation(String::new()),
+            Command::ExportConversation("bug.forgeconv".to_string()),
+            Command::ImportConversation("bug.forgeconv".to_string()),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_switch() {
+        let actual = [
+            Command::parse("/switch", &[]),
+            Command::parse("/switch 2 ", &[]),
+        ];
+        let expected = [
+            Command::Switch(String::new()),
+            Command::Switch("2".to_string()),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_builtin_commands_take_precedence() {
+        let mut custom = fixture();
+        custom[0].name = "new".to_string();
+        let actual = Command::parse("/new", &custom);
+        assert_eq!(actual, Command::New);
+    }
+}
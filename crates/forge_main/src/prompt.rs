@@ -6,9 +6,9 @@ use nu_ansi_term::{Color, Style};
 use reedline::{Prompt, PromptHistorySearchStatus};
 
 // Constants
-const AI_INDICATOR: &str = "⚡";
+pub const AI_INDICATOR: &str = "⚡";
 const MULTILINE_INDICATOR: &str = "::: ";
-const RIGHT_CHEVRON: &str = "❯";
+pub const RIGHT_CHEVRON: &str = "❯";
 
 /// Very Specialized Prompt for the Agent Chat
 #[derive(Clone, Default, Setters)]
@@ -16,6 +16,7 @@ const RIGHT_CHEVRON: &str = "❯";
 pub struct ForgePrompt {
     title: Option<String>,
     usage: Option<Usage>,
+    cost: Option<f64>,
 }
 
 impl Prompt for ForgePrompt {
@@ -33,10 +34,13 @@ impl Prompt for ForgePrompt {
 
     fn render_prompt_right(&self) -> Cow<str> {
         if let Some(usage) = self.usage.as_ref() {
-            let usage_text = format!(
+            let mut usage_text = format!(
                 "[{}/{}/{}]",
                 usage.prompt_tokens, usage.completion_tokens, usage.total_tokens
             );
+            if let Some(cost) = self.cost.filter(|cost| *cost > 0.0) {
+                usage_text.push_str(&format!(" ${cost:.4}"));
+            }
             Cow::Owned(
                 Style::new()
                     .bold()
@@ -101,7 +105,12 @@ mod tests {
 
     #[test]
     fn test_render_prompt_right_with_usage() {
-        let usage = Usage { prompt_tokens: 10, completion_tokens: 20, total_tokens: 30 };
+        let usage = Usage {
+            prompt_tokens: 10,
+            completion_tokens: 20,
+            total_tokens: 30,
+            cached_tokens: 0,
+        };
         let mut prompt = ForgePrompt::default();
         prompt.usage(usage);
         let usage_style = Style::new()
@@ -114,6 +123,27 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_render_prompt_right_with_cost() {
+        let usage = Usage {
+            prompt_tokens: 10,
+            completion_tokens: 20,
+            total_tokens: 30,
+            cached_tokens: 0,
+        };
+        let mut prompt = ForgePrompt::default();
+        prompt.usage(usage);
+        prompt.cost(0.01234);
+        let usage_style = Style::new()
+            .bold()
+            .fg(Color::DarkGray)
+            .paint("[10/20/30] $0.0123")
+            .to_string();
+        let actual = prompt.render_prompt_right();
+        let expected = usage_style;
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_render_prompt_right_without_usage() {
         let prompt = ForgePrompt::default();
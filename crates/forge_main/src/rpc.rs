@@ -0,0 +1,115 @@
+//! JSON-RPC 2.0 shared by the servers editors run on stdio: `forge lsp-bridge`
+//! and `forge acp`, which only differ in how their messages are framed.
+
+use std::io::Write;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// A request, or a notification when it has no id.
+#[derive(Debug, Deserialize)]
+pub struct Incoming {
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn method_not_found(method: &str) -> Self {
+        Self {
+            code: METHOD_NOT_FOUND,
+            message: format!("Unknown method: {method}"),
+        }
+    }
+}
+
+impl From<anyhow::Error> for RpcError {
+    fn from(error: anyhow::Error) -> Self {
+        Self { code: INTERNAL_ERROR, message: format!("{error:#}") }
+    }
+}
+
+pub fn parse<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params)
+        .map_err(|error| RpcError { code: INVALID_PARAMS, message: error.to_string() })
+}
+
+pub fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {"code": error.code, "message": error.message}
+        }),
+    }
+}
+
+pub fn request(id: u64, method: &str, params: Value) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})
+}
+
+pub fn notification(method: &str, params: Value) -> Value {
+    json!({"jsonrpc": "2.0", "method": method, "params": params})
+}
+
+/// Writes the messages sent to the returned channel to `out` in order, framed
+/// by `frame`, on a thread of its own.
+pub fn writer(
+    mut out: Box<dyn Write + Send>,
+    frame: fn(&mut dyn Write, &Value) -> anyhow::Result<()>,
+) -> mpsc::UnboundedSender<Value> {
+    let (sender, mut messages) = mpsc::unbounded_channel::<Value>();
+    std::thread::spawn(move || {
+        while let Some(message) = messages.blocking_recv() {
+            if let Err(error) = frame(&mut out, &message) {
+                tracing::error!(error = ?error, "Failed to write to the editor");
+                break;
+            }
+        }
+    });
+    sender
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Params {
+        id: u64,
+    }
+
+    #[test]
+    fn test_response() {
+        let actual = [
+            response(json!(1), Ok(json!(true))),
+            response(
+                json!(2),
+                parse::<Params>(json!({"id": "first"})).map(|_| Value::Null),
+            ),
+        ];
+        assert_eq!(
+            actual[0],
+            json!({"jsonrpc": "2.0", "id": 1, "result": true})
+        );
+        assert_eq!(actual[1]["error"]["code"], json!(INVALID_PARAMS));
+    }
+}
@@ -0,0 +1,286 @@
+//! Setup run when no provider is configured, which asks for the provider, its
+//! API key and the default model and writes them to the config file.
+
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use forge_api::{keychain, Model, Provider, API};
+use forge_display::TitleFormat;
+
+use crate::console::CONSOLE;
+
+/// Providers offered by the setup, along with the model suggested by default.
+const PROVIDERS: [(Provider, &str); 4] = [
+    (Provider::OpenRouter, "anthropic/claude-3.7-sonnet"),
+    (Provider::OpenAI, "gpt-4o"),
+    (Provider::Anthropic, "claude-3-7-sonnet-20250219"),
+    (Provider::Gemini, "gemini-2.0-flash"),
+];
+
+/// Maximum number of matching models listed when picking the default one.
+const MAX_LISTED_MODELS: usize = 10;
+
+const NOT_CONFIGURED: &str = "No API key found. Please set one of: FORGE_KEY, OPENROUTER_API_KEY, OPENAI_API_KEY, ANTHROPIC_API_KEY, AZURE_OPENAI_KEY, AZURE_OPENAI_AD_TOKEN, GEMINI_API_KEY or AWS_SECRET_ACCESS_KEY, or run forge in a terminal to set it up";
+
+pub struct Setup {
+    key_name: &'static str,
+    key: String,
+    default_model: &'static str,
+}
+
+impl Setup {
+    /// Asks for the provider and its key. The key is set in the environment of
+    /// the process, so that the API can be initialized with it.
+    pub fn prompt() -> Result<Self> {
+        if !std::io::stdin().is_terminal() {
+            bail!(NOT_CONFIGURED);
+        }
+
+        CONSOLE.writeln("Welcome to Forge! Let's set up the provider of the models.")?;
+        CONSOLE.writeln(
+            "Azure OpenAI and AWS Bedrock are configured through environment variables, see the README.\n",
+        )?;
+        let providers = PROVIDERS.map(|(provider, _)| provider);
+        let index = choose("Provider", &providers)?;
+        let (provider, default_model) = &PROVIDERS[index];
+        let key_name = provider.key_name();
+
+        let key = loop {
+            let key = read_secret(&format!("{provider} API key: "))?;
+            if !key.trim().is_empty() {
+                break key.trim().to_string();
+            }
+        };
+        std::env::set_var(key_name, &key);
+
+        Ok(Self { key_name, key, default_model })
+    }
+
+    /// Asks for the default model among the ones of the provider, which also
+    /// checks the key, and writes the config file.
+    pub async fn finish(self, api: &impl API) -> Result<()> {
+        let models = api
+            .models()
+            .await
+            .context("Failed to list the models, please check the API key")?;
+        let default = models
+            .iter()
+            .find(|model| model.id.as_str() == self.default_model)
+            .or(models.first())
+            .map(|model| model.id.clone())
+            .context("The provider has no models available")?;
+
+        let model = loop {
+            let answer = read_line(&format!("Default model [{default}]: "))?;
+            let answer = answer.trim();
+            if answer.is_empty() {
+                break default;
+            }
+
+            let matches = search(&models, answer);
+            if let Some(model) = matches.iter().find(|model| model.id.as_str() == answer) {
+                break model.id.clone();
+            }
+            match matches.as_slice() {
+                [model] => break model.id.clone(),
+                [] => CONSOLE.writeln(format!("No model matches '{answer}'"))?,
+                _ => {
+                    CONSOLE.writeln(format!("{} models match, eg:", matches.len()))?;
+                    for model in matches.iter().take(MAX_LISTED_MODELS) {
+                        CONSOLE.writeln(format!("  {}", model.id))?;
+                    }
+                }
+            }
+        };
+        let config_path = forge_api::config::user_path();
+        forge_api::config::set(&config_path, "model", model.as_str())?;
+
+        // note: the key is only written to the `.env` file when the keychain
+        // isn't available.
+        let stored = match keychain::set(self.key_name, &self.key) {
+            Ok(()) => "key saved to the keychain".to_string(),
+            Err(error) => {
+                let path = forge_api::config_path();
+                write_config(&path, &[(self.key_name, &self.key)])?;
+                format!("key saved to {} ({error})", path.display())
+            }
+        };
+        CONSOLE.writeln(
+            TitleFormat::success("setup")
+                .sub_title(format!(
+                    "{stored}, model saved to {}",
+                    config_path.display()
+                ))
+                .format(),
+        )?;
+        Ok(())
+    }
+}
+
+/// Lists the options and asks for one of them, returning its index.
+pub(crate) fn choose<T: Display>(title: &str, options: &[T]) -> Result<usize> {
+    for (i, option) in options.iter().enumerate() {
+        CONSOLE.writeln(format!("  {}. {}", i + 1, option))?;
+    }
+    loop {
+        let answer = read_line(&format!("{title} [1]: "))?;
+        match parse_choice(&answer, options.len()) {
+            Some(index) => return Ok(index),
+            None => CONSOLE.writeln(format!("Enter a number between 1 and {}", options.len()))?,
+        }
+    }
+}
+
+/// Parses the one based index of a choice, the first one when empty.
+fn parse_choice(answer: &str, len: usize) -> Option<usize> {
+    let answer = answer.trim();
+    if answer.is_empty() {
+        return Some(0);
+    }
+    answer
+        .parse::<usize>()
+        .ok()
+        .filter(|choice| (1..=len).contains(choice))
+        .map(|choice| choice - 1)
+}
+
+/// Models whose ID contains the query, ignoring case.
+fn search<'a>(models: &'a [Model], query: &str) -> Vec<&'a Model> {
+    let query = query.to_lowercase();
+    models
+        .iter()
+        .filter(|model| model.id.as_str().to_lowercase().contains(&query))
+        .collect()
+}
+
+/// Sets the variables in the `.env` file at `path`, keeping the others.
+fn write_config(path: &Path, variables: &[(&str, &str)]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let existing = std::fs::read_to_string(path).unwrap_or_default();
+    let mut lines = existing
+        .lines()
+        .filter(|line| {
+            !variables
+                .iter()
+                .any(|(name, _)| line.trim_start().starts_with(&format!("{name}=")))
+        })
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    lines.extend(
+        variables
+            .iter()
+            .map(|(name, value)| format!("{name}={value}")),
+    );
+    std::fs::write(path, lines.join("\n") + "\n")
+        .with_context(|| format!("Failed to write the config: {}", path.display()))?;
+
+    // note: the file holds the API key, so only the user may read it.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+fn read_line(prompt: &str) -> Result<String> {
+    CONSOLE.write(prompt)?;
+    let mut line = String::new();
+    if std::io::stdin().read_line(&mut line)? == 0 {
+        bail!("Setup was cancelled");
+    }
+    Ok(line)
+}
+
+/// Reads a line without echoing it.
+pub(crate) fn read_secret(prompt: &str) -> Result<String> {
+    CONSOLE.write(prompt)?;
+    terminal::enable_raw_mode()?;
+    let secret = read_hidden();
+    terminal::disable_raw_mode()?;
+    CONSOLE.newline()?;
+    secret
+}
+
+fn read_hidden() -> Result<String> {
+    let mut secret = String::new();
+    loop {
+        if let Event::Key(KeyEvent { code, modifiers, kind: KeyEventKind::Press, .. }) =
+            event::read()?
+        {
+            match code {
+                KeyCode::Enter => return Ok(secret),
+                KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                    bail!("Setup was cancelled")
+                }
+                KeyCode::Char(c) => secret.push(c),
+                KeyCode::Backspace => {
+                    secret.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_api::ModelId;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_choice() {
+        let actual = ["", "2", " 4\n", "0", "5", "openai"].map(|answer| parse_choice(answer, 4));
+        let expected = [Some(0), Some(1), Some(3), None, None, None];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_search() {
+        let models = [
+            "openai/gpt-4o",
+            "openai/gpt-4o-mini",
+            "anthropic/claude-3.7-sonnet",
+        ]
+        .map(|id| Model {
+            id: ModelId::new(id),
+            name: id.to_string(),
+            description: None,
+            context_length: None,
+            pricing: None,
+            capabilities: None,
+        });
+
+        let actual = search(&models, "GPT-4O")
+            .into_iter()
+            .map(|model| model.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(actual, ["openai/gpt-4o", "openai/gpt-4o-mini"]);
+    }
+
+    #[test]
+    fn test_write_config_keeps_other_variables() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("forge").join(".env");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "QDRANT_KEY=abc\nFORGE_MODEL=old\n").unwrap();
+
+        write_config(
+            &path,
+            &[("OPENAI_API_KEY", "sk-123"), ("FORGE_MODEL", "gpt-4o")],
+        )
+        .unwrap();
+
+        let actual = std::fs::read_to_string(&path).unwrap();
+        let expected = "QDRANT_KEY=abc\nOPENAI_API_KEY=sk-123\nFORGE_MODEL=gpt-4o\n";
+        assert_eq!(actual, expected);
+    }
+}
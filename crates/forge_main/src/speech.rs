@@ -0,0 +1,293 @@
+//! Speaks the final summary of each turn aloud, when enabled by the `speech`
+//! setting.
+
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::Stdio;
+
+use anyhow::{bail, Context};
+use forge_api::{AgentMessage, ChatResponse, Speech, SpeechEngine};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::json;
+use tokio::io::AsyncWriteExt;
+use tokio::task::JoinHandle;
+
+/// Voice of OpenAI used when none is set
+const OPENAI_VOICE: &str = "alloy";
+
+lazy_static! {
+    static ref LINK: Regex = Regex::new(r"\[([^\]]*)\]\([^)]*\)").unwrap();
+}
+
+/// Collects the text the agents stream during a turn and speaks the last part
+/// of it, the summary that follows their last tool call.
+pub struct Narrator {
+    engine: SpeechEngine,
+    voice: Option<String>,
+    openai_key: Option<String>,
+    /// Text streamed since the last tool call
+    text: String,
+    /// Speech in progress, cut off when the next one starts
+    speaking: Option<JoinHandle<()>>,
+}
+
+impl Narrator {
+    /// Returns `None` unless speech is enabled.
+    pub fn new(speech: &Speech, openai_key: Option<String>) -> Option<Self> {
+        speech.enabled.unwrap_or_default().then(|| Self {
+            engine: speech.engine.unwrap_or_default(),
+            voice: speech.voice.clone(),
+            openai_key,
+            text: String::new(),
+            speaking: None,
+        })
+    }
+
+    pub fn record(&mut self, message: &AgentMessage<ChatResponse>) {
+        // Workers don't print anything, so they aren't spoken either
+        if message.agent.as_str().to_lowercase().ends_with("worker") {
+            return;
+        }
+        match &message.message {
+            ChatResponse::Text(text) => self.text.push_str(text),
+            ChatResponse::ToolCallStart(_) => self.text.clear(),
+            _ => {}
+        }
+    }
+
+    /// Speaks the summary of the turn in the background.
+    pub fn finish(&mut self) {
+        let text = speakable(&std::mem::take(&mut self.text));
+        if text.is_empty() {
+            return;
+        }
+        if let Some(speaking) = self.speaking.take() {
+            speaking.abort();
+        }
+        let engine = self.engine;
+        let voice = self.voice.clone();
+        let key = self.openai_key.clone();
+        self.speaking = Some(tokio::spawn(async move {
+            let result = match engine {
+                SpeechEngine::System => system(&text, voice.as_deref()).await,
+                SpeechEngine::OpenAi => openai(&text, voice.as_deref(), key.as_deref()).await,
+            };
+            if let Err(error) = result {
+                tracing::warn!(error = format!("{error:#}"), "Failed to speak the summary");
+            }
+        }));
+    }
+}
+
+impl Drop for Narrator {
+    fn drop(&mut self) {
+        if let Some(speaking) = self.speaking.take() {
+            speaking.abort();
+        }
+    }
+}
+
+/// The text of markdown as it should be read: without code blocks, links
+/// reduced to their text and no formatting characters.
+fn speakable(markdown: &str) -> String {
+    let mut fenced = false;
+    let mut lines = Vec::new();
+    for line in markdown.lines() {
+        let line = line.trim();
+        if line.starts_with("```") {
+            fenced = !fenced;
+            continue;
+        }
+        if fenced || line.is_empty() {
+            continue;
+        }
+        let line = line.trim_start_matches(['#', '>', '-', '*', ' ']);
+        lines.push(LINK.replace_all(line, "$1").replace(['*', '`'], ""));
+    }
+    lines.join("\n")
+}
+
+/// A program to run along with its arguments.
+struct Program {
+    name: &'static str,
+    args: Vec<String>,
+}
+
+impl Program {
+    fn new(name: &'static str, args: impl IntoIterator<Item = impl ToString>) -> Self {
+        Self {
+            name,
+            args: args.into_iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+}
+
+/// Speaks with the speech synthesizer of the OS, which reads the text from
+/// stdin.
+async fn system(text: &str, voice: Option<&str>) -> anyhow::Result<()> {
+    let programs = if cfg!(target_os = "macos") {
+        let mut args = vec!["-f", "-"];
+        args.extend(voice.map(|voice| ["-v", voice]).into_iter().flatten());
+        vec![Program::new("say", args)]
+    } else if cfg!(windows) {
+        let select = voice
+            .map(|voice| format!("$s.SelectVoice('{}');", voice.replace('\'', "''")))
+            .unwrap_or_default();
+        let script = format!(
+            "Add-Type -AssemblyName System.Speech; \
+             $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+             {select} $s.Speak([Console]::In.ReadToEnd())"
+        );
+        vec![Program::new(
+            "powershell",
+            ["-NoProfile", "-Command", script.as_str()],
+        )]
+    } else {
+        let mut args = vec!["--stdin"];
+        args.extend(voice.map(|voice| ["-v", voice]).into_iter().flatten());
+        vec![
+            Program::new("espeak-ng", args.clone()),
+            Program::new("espeak", args),
+        ]
+    };
+    run(&programs, Some(text)).await
+}
+
+/// Speaks with the speech API of OpenAI, playing the audio with the player of
+/// the OS.
+async fn openai(text: &str, voice: Option<&str>, key: Option<&str>) -> anyhow::Result<()> {
+    let key = key.context("The OpenAI speech engine needs OPENAI_API_KEY")?;
+    let audio = reqwest::Client::new()
+        .post("https://api.openai.com/v1/audio/speech")
+        .bearer_auth(key)
+        .json(&json!({
+            "model": "gpt-4o-mini-tts",
+            "voice": voice.unwrap_or(OPENAI_VOICE),
+            "input": text,
+            "response_format": "wav",
+        }))
+        .send()
+        .await
+        .context("Failed to send the summary to OpenAI")?
+        .error_for_status()?
+        .bytes()
+        .await
+        .context("Failed to read the speech of OpenAI")?;
+
+    let path = std::env::temp_dir().join(format!("forge-speech-{}.wav", std::process::id()));
+    tokio::fs::write(&path, &audio).await?;
+    let result = run(&player(&path), None).await;
+    let _ = tokio::fs::remove_file(&path).await;
+    result
+}
+
+/// Programs that play a WAV file.
+fn player(path: &Path) -> Vec<Program> {
+    let path = path.display().to_string();
+    if cfg!(target_os = "macos") {
+        vec![Program::new("afplay", [path])]
+    } else if cfg!(windows) {
+        let script = format!(
+            "(New-Object Media.SoundPlayer '{}').PlaySync()",
+            path.replace('\'', "''")
+        );
+        vec![Program::new(
+            "powershell",
+            ["-NoProfile", "-Command", script.as_str()],
+        )]
+    } else {
+        vec![
+            Program::new("paplay", [&path]),
+            Program::new("aplay", ["-q", path.as_str()]),
+        ]
+    }
+}
+
+/// Runs the first of the programs that is installed, writing `input` to its
+/// stdin. The program is killed if the speech is cut off.
+async fn run(programs: &[Program], input: Option<&str>) -> anyhow::Result<()> {
+    for program in programs {
+        let child = tokio::process::Command::new(program.name)
+            .args(&program.args)
+            .stdin(if input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(error) if error.kind() == ErrorKind::NotFound => continue,
+            Err(error) => return Err(error).context(format!("Failed to run {}", program.name)),
+        };
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            stdin.write_all(input.as_bytes()).await?;
+        }
+        let status = child.wait().await?;
+        if !status.success() {
+            bail!("{} exited with {status}", program.name);
+        }
+        return Ok(());
+    }
+    let names = programs
+        .iter()
+        .map(|program| program.name)
+        .collect::<Vec<_>>();
+    bail!("No speech program found, install {}", names.join(" or "))
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_api::{AgentId, ToolCallFull, ToolName};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn message(agent: &str, message: ChatResponse) -> AgentMessage<ChatResponse> {
+        AgentMessage { agent: AgentId::new(agent), message }
+    }
+
+    #[test]
+    fn test_speakable() {
+        let fixture = "# Done\n\nI fixed **the bug** in `main.rs`, see [the docs](https://docs.rs).\n\n```rust\nfn main() {}\n```\n- Added a test";
+        let actual = speakable(fixture);
+        let expected = "Done\nI fixed the bug in main.rs, see the docs.\nAdded a test";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_record_keeps_text_after_last_tool_call() {
+        let mut narrator = Narrator::new(&Speech::default().enabled(true), None).unwrap();
+        let tool_call = ToolCallFull::new(ToolName::new("tool_forge_fs_read"));
+
+        narrator.record(&message(
+            "software-engineer",
+            ChatResponse::Text("Reading".into()),
+        ));
+        narrator.record(&message(
+            "software-engineer",
+            ChatResponse::ToolCallStart(tool_call),
+        ));
+        narrator.record(&message(
+            "software-engineer",
+            ChatResponse::Text("All ".into()),
+        ));
+        narrator.record(&message("title_worker", ChatResponse::Text("Title".into())));
+        narrator.record(&message(
+            "software-engineer",
+            ChatResponse::Text("done".into()),
+        ));
+
+        assert_eq!(narrator.text, "All done");
+    }
+
+    #[test]
+    fn test_new_disabled() {
+        let actual = Narrator::new(&Speech::default(), None);
+        assert!(actual.is_none());
+    }
+}
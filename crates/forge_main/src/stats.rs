@@ -0,0 +1,247 @@
+//! Records the usage statistics of chat requests locally and renders them for
+//! `forge stats`.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::Instant;
+
+use chrono::Utc;
+use forge_api::{AgentId, AgentMessage, ChatResponse, ConversationId};
+use forge_tracker::{StatsRecord, StatsSummary};
+
+/// Collects the metrics of a single chat request from its responses.
+pub struct StatsRecorder {
+    record: StatsRecord,
+    provider: String,
+    /// Spend of the conversation before the request
+    cost: f64,
+    started: Instant,
+    /// When each agent last started a request to the provider
+    requests: HashMap<AgentId, Instant>,
+}
+
+impl StatsRecorder {
+    pub fn new(conversation_id: &ConversationId, provider_url: &str, cost: f64) -> Self {
+        Self {
+            record: StatsRecord::new(conversation_id, Utc::now()),
+            provider: provider_name(provider_url),
+            cost,
+            started: Instant::now(),
+            requests: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, message: &AgentMessage<ChatResponse>) {
+        match &message.message {
+            // Usage is reported once a response of the provider is complete
+            ChatResponse::Usage(usage) => {
+                let now = Instant::now();
+                let started = self
+                    .requests
+                    .insert(message.agent.clone(), now)
+                    .unwrap_or(self.started);
+                self.record.record_usage(
+                    usage.prompt_tokens,
+                    usage.completion_tokens,
+                    usage.total_tokens,
+                );
+                self.record.record_request(&self.provider, now - started);
+            }
+            // The next request of the agent starts once its tools are done
+            ChatResponse::ToolCallEnd(result) => {
+                self.record
+                    .record_tool_call(result.name.as_str(), result.is_error);
+                self.requests.insert(message.agent.clone(), Instant::now());
+            }
+            // The time spent queued isn't part of the provider's latency
+            ChatResponse::RateLimited(wait) => {
+                self.requests
+                    .insert(message.agent.clone(), Instant::now() + *wait);
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the record given the spend of the conversation after the
+    /// request.
+    pub fn finish(mut self, cost: f64) -> StatsRecord {
+        self.record.cost = (cost - self.cost).max(0.0);
+        self.record
+    }
+}
+
+/// Host of the provider's URL, eg: `openrouter.ai`.
+fn provider_name(url: &str) -> String {
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    url.split('/').next().unwrap_or(url).to_string()
+}
+
+/// Renders the summary of the last `days` days as plain text tables.
+pub fn render(summary: &StatsSummary, days: u32) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "Usage over the last {days} days\n");
+    output.push_str(&table(
+        &[
+            "Sessions",
+            "Prompt tokens",
+            "Completion tokens",
+            "Total tokens",
+            "Cost",
+        ],
+        vec![vec![
+            summary.sessions.to_string(),
+            summary.prompt_tokens.to_string(),
+            summary.completion_tokens.to_string(),
+            summary.total_tokens.to_string(),
+            format!("${:.4}", summary.cost),
+        ]],
+    ));
+
+    if !summary.tools.is_empty() {
+        output.push_str("\nTools\n\n");
+        output.push_str(&table(
+            &["Tool", "Calls", "Failures", "Failure rate"],
+            summary
+                .tools
+                .iter()
+                .map(|(name, stats)| {
+                    vec![
+                        name.clone(),
+                        stats.calls.to_string(),
+                        stats.failures.to_string(),
+                        percent(stats.failure_rate()),
+                    ]
+                })
+                .collect(),
+        ));
+    }
+
+    if !summary.providers.is_empty() {
+        output.push_str("\nProviders\n\n");
+        output.push_str(&table(
+            &["Provider", "Requests", "Average latency", "Max latency"],
+            summary
+                .providers
+                .iter()
+                .map(|(name, stats)| {
+                    vec![
+                        name.clone(),
+                        stats.requests.to_string(),
+                        format!("{}ms", stats.average_latency_ms()),
+                        format!("{}ms", stats.max_latency_ms),
+                    ]
+                })
+                .collect(),
+        ));
+    }
+
+    if !summary.days.is_empty() {
+        output.push_str("\nDaily (UTC)\n\n");
+        output.push_str(&table(
+            &[
+                "Date",
+                "Sessions",
+                "Tokens",
+                "Cost",
+                "Tool calls",
+                "Failure rate",
+            ],
+            summary
+                .days
+                .iter()
+                .map(|(date, stats)| {
+                    vec![
+                        date.to_string(),
+                        stats.sessions.to_string(),
+                        stats.total_tokens.to_string(),
+                        format!("${:.4}", stats.cost),
+                        stats.tool_calls.to_string(),
+                        percent(stats.failure_rate()),
+                    ]
+                })
+                .collect(),
+        ));
+    }
+
+    output
+}
+
+fn percent(rate: f64) -> String {
+    format!("{:.1}%", rate * 100.0)
+}
+
+/// Aligns the rows in columns, the first one to the left and the rest, which
+/// hold numbers, to the right.
+fn table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths = headers
+        .iter()
+        .map(|header| header.len())
+        .collect::<Vec<_>>();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let headers = headers.iter().map(|header| header.to_string()).collect();
+    let mut output = String::new();
+    for row in std::iter::once(headers).chain(rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, width))| match column {
+                0 => format!("{cell:<width$}"),
+                _ => format!("{cell:>width$}"),
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        let _ = writeln!(output, "{}", line.trim_end());
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::TimeZone;
+    use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_provider_name() {
+        let actual = provider_name("https://openrouter.ai/api/v1/");
+        assert_eq!(actual, "openrouter.ai");
+    }
+
+    #[test]
+    fn test_render() {
+        let records = ["a", "a", "b"]
+            .iter()
+            .enumerate()
+            .map(|(day, session)| {
+                let timestamp = Utc
+                    .with_ymd_and_hms(2025, 3, 1 + day as u32, 10, 0, 0)
+                    .unwrap();
+                let mut record = StatsRecord::new(session, timestamp);
+                record.record_usage(1000, 200, 1200);
+                record.record_tool_call("tool_forge_fs_read", false);
+                record.record_tool_call("tool_forge_process_shell", day == 1);
+                record.record_request(
+                    "openrouter.ai",
+                    Duration::from_millis(800 + day as u64 * 100),
+                );
+                record.cost = 0.012;
+                record
+            })
+            .collect::<Vec<_>>();
+        let summary =
+            StatsSummary::new(&records, Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap());
+
+        let actual = render(&summary, 30);
+        assert_snapshot!(actual);
+    }
+}
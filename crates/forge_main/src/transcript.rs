@@ -0,0 +1,356 @@
+//! Renders a persisted conversation into a shareable transcript.
+
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::bail;
+use forge_api::{ContextMessage, Conversation, Role, Usage};
+use serde_json::json;
+
+/// File formats a transcript can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+impl TranscriptFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TranscriptFormat::Markdown => "md",
+            TranscriptFormat::Html => "html",
+            TranscriptFormat::Json => "json",
+        }
+    }
+}
+
+impl FromStr for TranscriptFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "md" | "markdown" => Ok(TranscriptFormat::Markdown),
+            "html" => Ok(TranscriptFormat::Html),
+            "json" => Ok(TranscriptFormat::Json),
+            format => bail!("Unsupported export format '{format}', expected one of md, html, json"),
+        }
+    }
+}
+
+/// Arguments of the `/export [md|html|json] <path>` command.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExportArgs {
+    pub format: TranscriptFormat,
+    pub path: Option<PathBuf>,
+}
+
+impl FromStr for ExportArgs {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parts = value.split_whitespace();
+        let format = match parts.next() {
+            Some(format) => format.parse()?,
+            None => TranscriptFormat::Markdown,
+        };
+        let path = parts.next().map(PathBuf::from);
+        if parts.next().is_some() {
+            bail!("Usage: /export [md|html|json] <path>")
+        }
+        Ok(Self { format, path })
+    }
+}
+
+/// A single renderable item of a transcript.
+enum Entry<'a> {
+    Agent(&'a str),
+    Message {
+        role: &'a Role,
+        content: &'a str,
+    },
+    ToolCall {
+        name: &'a str,
+        arguments: String,
+    },
+    ToolResult {
+        name: &'a str,
+        content: &'a str,
+        is_error: bool,
+    },
+}
+
+/// Exports a conversation including messages, tool calls, tool results (which
+/// contain the applied diffs) and token usage.
+pub struct TranscriptExporter<'a> {
+    conversation: &'a Conversation,
+    /// Usage of all the requests of the conversation
+    usage: &'a Usage,
+}
+
+impl<'a> TranscriptExporter<'a> {
+    pub fn new(conversation: &'a Conversation, usage: &'a Usage) -> Self {
+        Self { conversation, usage }
+    }
+
+    pub fn render(&self, format: TranscriptFormat) -> anyhow::Result<String> {
+        match format {
+            TranscriptFormat::Markdown => Ok(self.markdown()),
+            TranscriptFormat::Html => Ok(self.html()),
+            TranscriptFormat::Json => Ok(serde_json::to_string_pretty(&json!({
+                "conversation": self.conversation,
+                "usage": self.usage,
+            }))?),
+        }
+    }
+
+    fn entries(&self) -> Vec<Entry<'_>> {
+        let mut agents = self
+            .conversation
+            .state
+            .iter()
+            .filter_map(|(id, state)| Some((id, state.context.as_ref()?)))
+            .collect::<Vec<_>>();
+        agents.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+        let mut entries = Vec::new();
+        for (id, context) in agents {
+            entries.push(Entry::Agent(id.as_str()));
+            for message in &context.messages {
+                match message {
+                    // System prompts are not part of the session
+                    ContextMessage::ContentMessage(message) if message.role == Role::System => {}
+                    ContextMessage::ContentMessage(message) => {
+                        if !message.content.trim().is_empty() {
+                            entries.push(Entry::Message {
+                                role: &message.role,
+                                content: &message.content,
+                            });
+                        }
+                        for call in message.tool_calls.iter().flatten() {
+                            entries.push(Entry::ToolCall {
+                                name: call.name.as_str(),
+                                arguments: serde_json::to_string_pretty(&call.arguments)
+                                    .unwrap_or_default(),
+                            });
+                        }
+                    }
+                    ContextMessage::ToolMessage(result) => entries.push(Entry::ToolResult {
+                        name: result.name.as_str(),
+                        content: &result.content,
+                        is_error: result.is_error,
+                    }),
+                }
+            }
+        }
+        entries
+    }
+
+    fn markdown(&self) -> String {
+        let mut output = format!("# Conversation {}\n", self.conversation.id);
+        for entry in self.entries() {
+            // Writing to a String is infallible
+            let _ = match entry {
+                Entry::Agent(id) => write!(output, "\n## Agent `{id}`\n"),
+                Entry::Message { role, content } => {
+                    write!(output, "\n### {}\n\n{}\n", role, content.trim())
+                }
+                Entry::ToolCall { name, arguments } => {
+                    write!(
+                        output,
+                        "\n**Tool call** `{name}`\n\n{}",
+                        fenced("json", &arguments)
+                    )
+                }
+                Entry::ToolResult { name, content, is_error } => write!(
+                    output,
+                    "\n**Tool {}** `{name}`\n\n{}",
+                    if is_error { "error" } else { "result" },
+                    fenced(result_language(content), content)
+                ),
+            };
+        }
+        let _ = write!(
+            output,
+            "\n## Usage\n\n| Prompt | Completion | Total |\n| --- | --- | --- |\n| {} | {} | {} |\n",
+            self.usage.prompt_tokens, self.usage.completion_tokens, self.usage.total_tokens
+        );
+        output
+    }
+
+    fn html(&self) -> String {
+        let mut body = String::new();
+        for entry in self.entries() {
+            let _ = match entry {
+                Entry::Agent(id) => writeln!(body, "<h2>Agent <code>{}</code></h2>", escape(id)),
+                Entry::Message { role, content } => writeln!(
+                    body,
+                    "<section class=\"{0}\"><h3>{0}</h3><pre>{1}</pre></section>",
+                    role,
+                    escape(content.trim())
+                ),
+                Entry::ToolCall { name, arguments } => writeln!(
+                    body,
+                    "<details class=\"tool-call\"><summary>Tool call <code>{}</code></summary><pre>{}</pre></details>",
+                    escape(name),
+                    escape(&arguments)
+                ),
+                Entry::ToolResult { name, content, is_error } => writeln!(
+                    body,
+                    "<details class=\"tool-{0}\"><summary>Tool {0} <code>{1}</code></summary><pre>{2}</pre></details>",
+                    if is_error { "error" } else { "result" },
+                    escape(name),
+                    escape(content.trim_end())
+                ),
+            };
+        }
+
+        format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Conversation {id}</title>
+<style>
+body {{ font-family: sans-serif; max-width: 960px; margin: auto; }}
+pre {{ white-space: pre-wrap; background: #f6f8fa; padding: 8px; }}
+.tool-error summary {{ color: #cf222e; }}
+</style>
+</head>
+<body>
+<h1>Conversation {id}</h1>
+{body}<h2>Usage</h2>
+<table><tr><th>Prompt</th><th>Completion</th><th>Total</th></tr><tr><td>{prompt}</td><td>{completion}</td><td>{total}</td></tr></table>
+</body>
+</html>
+"#,
+            id = self.conversation.id,
+            prompt = self.usage.prompt_tokens,
+            completion = self.usage.completion_tokens,
+            total = self.usage.total_tokens,
+        )
+    }
+}
+
+/// Tool results that contain a unified diff are highlighted as such.
+fn result_language(content: &str) -> &'static str {
+    if content.lines().any(|line| line.starts_with("@@ ")) {
+        "diff"
+    } else {
+        ""
+    }
+}
+
+/// Wraps the content in a code fence that is longer than any backtick run in
+/// the content itself.
+fn fenced(language: &str, content: &str) -> String {
+    let mut longest = 0;
+    let mut current = 0;
+    for c in content.chars() {
+        if c == '`' {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}{language}\n{}\n{fence}\n", content.trim_end())
+}
+
+fn escape(content: &str) -> String {
+    content
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_api::{
+        AgentId, AgentState, Context, ConversationId, ToolCallFull, ToolName, ToolResult, Workflow,
+    };
+    use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    fn fixture() -> Conversation {
+        let call = ToolCallFull::new(ToolName::new("tool_forge_fs_patch"))
+            .arguments(json!({"path": "/a.txt", "preview": true}));
+        let context = Context::default()
+            .add_message(ContextMessage::system("You are a software engineer"))
+            .add_message(ContextMessage::user("Rename hello to world"))
+            .add_message(ContextMessage::assistant(
+                "Let me preview the change",
+                Some(vec![call.clone()]),
+            ))
+            .add_message(ContextMessage::tool_result(
+                ToolResult::from(call)
+                    .success("--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-hello\n+world\n"),
+            ));
+
+        let id = ConversationId::parse("5af97419-0277-410a-8ca6-0e2a252152c5").unwrap();
+        let mut conversation = Conversation::new(id, Workflow::default());
+        conversation.state.insert(
+            AgentId::new("software-engineer"),
+            AgentState { turn_count: 1, context: Some(context) },
+        );
+        conversation
+    }
+
+    fn usage() -> Usage {
+        Usage {
+            prompt_tokens: 120,
+            completion_tokens: 30,
+            total_tokens: 150,
+            cached_tokens: 0,
+        }
+    }
+
+    #[test]
+    fn test_markdown() {
+        let conversation = fixture();
+        let usage = usage();
+        let actual = TranscriptExporter::new(&conversation, &usage)
+            .render(TranscriptFormat::Markdown)
+            .unwrap();
+        assert_snapshot!(actual);
+    }
+
+    #[test]
+    fn test_html() {
+        let conversation = fixture();
+        let usage = usage();
+        let actual = TranscriptExporter::new(&conversation, &usage)
+            .render(TranscriptFormat::Html)
+            .unwrap();
+        assert_snapshot!(actual);
+    }
+
+    #[test]
+    fn test_export_args() {
+        let actual: ExportArgs = "html out/session.html".parse().unwrap();
+        let expected = ExportArgs {
+            format: TranscriptFormat::Html,
+            path: Some(PathBuf::from("out/session.html")),
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_export_args_defaults_to_markdown() {
+        let actual: ExportArgs = "".parse().unwrap();
+        let expected = ExportArgs { format: TranscriptFormat::Markdown, path: None };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_export_args_unknown_format() {
+        let actual = "pdf".parse::<ExportArgs>();
+        assert!(actual.is_err());
+    }
+}
@@ -0,0 +1,79 @@
+//! Trust of the workspace, asked for on the first run in a folder and managed
+//! by `forge trust`. An untrusted workspace is opened in the read-only mode,
+//! without its config, prompts and workflow.
+
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use forge_api::trust::TrustStore;
+use forge_display::TitleFormat;
+
+use crate::console::CONSOLE;
+use crate::setup::choose;
+
+/// Asks whether the working directory is trusted the first time forge runs in
+/// it, unless there's no terminal to ask in, in which case it stays
+/// untrusted.
+pub fn ask_trust() -> Result<()> {
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return Ok(());
+    }
+    let cwd = std::env::current_dir()?;
+    let store = TrustStore::default();
+    if store.is_trusted(&cwd) {
+        return Ok(());
+    }
+
+    CONSOLE.writeln(format!("Do you trust the files in {}?", cwd.display()))?;
+    CONSOLE.writeln(
+        "A trusted folder's .forge/config.toml, .forge/prompts, forge.yaml and .env are used, and the agents can change its files and run commands. Otherwise it's opened in the read-only mode.\n",
+    )?;
+    let options = ["No, open it in the read-only mode", "Yes, trust the folder"];
+    if choose("Trust", &options)? == 1 {
+        store.trust(&cwd)?;
+        CONSOLE.writeln(
+            TitleFormat::success("trust")
+                .sub_title(cwd.display().to_string())
+                .format(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Trusts the folder, or the working directory, or stops trusting it.
+pub fn trust(path: Option<&Path>, revoke: bool) -> Result<()> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => std::env::current_dir()?,
+    };
+    let store = TrustStore::default();
+    if revoke {
+        store.revoke(&path)?;
+        CONSOLE.writeln(
+            TitleFormat::success("untrust")
+                .sub_title(path.display().to_string())
+                .format(),
+        )?;
+    } else {
+        store.trust(&path)?;
+        CONSOLE.writeln(
+            TitleFormat::success("trust")
+                .sub_title(path.display().to_string())
+                .format(),
+        )?;
+    }
+    Ok(())
+}
+
+/// The trusted folders, one per line.
+pub fn list() -> Result<()> {
+    let trusted = TrustStore::default().load();
+    if trusted.is_empty() {
+        CONSOLE.writeln("No folder is trusted")?;
+    }
+    for path in trusted.iter().map(PathBuf::as_path) {
+        CONSOLE.writeln(path.display().to_string())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,121 @@
+pub use imp::Capture;
+
+#[cfg(unix)]
+mod imp {
+    use std::fs::File;
+    use std::io::{self, Read, Write};
+    use std::os::fd::{AsFd, AsRawFd, FromRawFd, OwnedFd};
+
+    /// Redirects the stdout and stderr of the process to a pipe while the
+    /// screen is shown, so that everything that's written to them, including
+    /// what the tools and the commands they run print, ends up in the
+    /// conversation pane instead of being drawn over the screen.
+    pub struct Capture {
+        /// The stdout and stderr the process was started with
+        saved: [OwnedFd; 2],
+        pipe: OwnedFd,
+    }
+
+    impl Capture {
+        /// Starts the redirection, passing the output to `sink` from a thread
+        /// as it's written.
+        pub fn start(sink: impl Fn(String) + Send + 'static) -> io::Result<Self> {
+            let mut fds = [0; 2];
+            // Safety: the fds of the new pipe aren't owned by anything else.
+            if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let (reader, pipe) =
+                unsafe { (File::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+            let saved = [
+                io::stdout().as_fd().try_clone_to_owned()?,
+                io::stderr().as_fd().try_clone_to_owned()?,
+            ];
+
+            let capture = Self { saved, pipe };
+            capture.resume()?;
+            std::thread::spawn(move || read(reader, sink));
+            Ok(capture)
+        }
+
+        /// The terminal the process was started with, to draw the screen on.
+        pub fn terminal(&self) -> io::Result<File> {
+            Ok(File::from(self.saved[0].try_clone()?))
+        }
+
+        /// Restores the output, eg: while the editor is open.
+        pub fn pause(&self) -> io::Result<()> {
+            redirect(&self.saved[0], &self.saved[1])
+        }
+
+        pub fn resume(&self) -> io::Result<()> {
+            redirect(&self.pipe, &self.pipe)
+        }
+    }
+
+    impl Drop for Capture {
+        fn drop(&mut self) {
+            let _ = self.pause();
+        }
+    }
+
+    /// Points the stdout and stderr of the process to the given files.
+    fn redirect(stdout: &OwnedFd, stderr: &OwnedFd) -> io::Result<()> {
+        io::stdout().flush()?;
+        io::stderr().flush()?;
+        for (fd, target) in [(libc::STDOUT_FILENO, stdout), (libc::STDERR_FILENO, stderr)] {
+            // Safety: the target is open for as long as the capture is.
+            if unsafe { libc::dup2(target.as_raw_fd(), fd) } < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+
+    /// Passes what's read from the pipe to `sink` until it's closed. A char
+    /// split across reads is passed whole.
+    fn read(mut reader: File, sink: impl Fn(String)) {
+        let mut buffer = [0; 8192];
+        let mut pending = Vec::new();
+        while let Ok(read @ 1..) = reader.read(&mut buffer) {
+            pending.extend_from_slice(&buffer[..read]);
+            let end = match std::str::from_utf8(&pending) {
+                Err(error) if error.error_len().is_none() => error.valid_up_to(),
+                _ => pending.len(),
+            };
+            let rest = pending.split_off(end);
+            sink(String::from_utf8_lossy(&pending).into_owned());
+            pending = rest;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use std::fs::File;
+    use std::io;
+
+    /// The output can't be redirected, so the screen isn't supported.
+    pub enum Capture {}
+
+    impl Capture {
+        pub fn start(_sink: impl Fn(String) + Send + 'static) -> io::Result<Self> {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "The full screen interface is only supported on Unix",
+            ))
+        }
+
+        pub fn terminal(&self) -> io::Result<File> {
+            match *self {}
+        }
+
+        pub fn pause(&self) -> io::Result<()> {
+            match *self {}
+        }
+
+        pub fn resume(&self) -> io::Result<()> {
+            match *self {}
+        }
+    }
+}
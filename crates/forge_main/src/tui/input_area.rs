@@ -0,0 +1,295 @@
+use std::ops::Range;
+
+use unicode_width::UnicodeWidthChar;
+
+/// Text being typed in the input area, along with the history of the
+/// submitted ones.
+#[derive(Debug, Default)]
+pub struct InputArea {
+    text: String,
+    /// Byte offset of the cursor in the text
+    cursor: usize,
+    history: Vec<String>,
+    /// Index of the history entry being shown, if any
+    recalled: Option<usize>,
+    /// Text that was typed before going through the history
+    draft: String,
+}
+
+impl InputArea {
+    pub fn new(history: Vec<String>) -> Self {
+        Self { history, ..Default::default() }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn insert(&mut self, text: &str) {
+        self.text.insert_str(self.cursor, text);
+        self.cursor += text.len();
+    }
+
+    /// Replaces the given range of the text, eg: the word being completed.
+    pub fn replace(&mut self, range: Range<usize>, text: &str) {
+        self.text.replace_range(range.clone(), text);
+        self.cursor = range.start + text.len();
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+            self.text.remove(self.cursor);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.text.len() {
+            self.text.remove(self.cursor);
+        }
+    }
+
+    pub fn left(&mut self) {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+        }
+    }
+
+    pub fn right(&mut self) {
+        if let Some(c) = self.text[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    /// Moves to the start of the line of the cursor.
+    pub fn home(&mut self) {
+        self.cursor = self.text[..self.cursor].rfind('\n').map_or(0, |i| i + 1);
+    }
+
+    /// Moves to the end of the line of the cursor.
+    pub fn end(&mut self) {
+        self.cursor = self.text[self.cursor..]
+            .find('\n')
+            .map_or(self.text.len(), |i| self.cursor + i);
+    }
+
+    /// Shows the previous entry of the history.
+    pub fn previous(&mut self) {
+        let index = match self.recalled {
+            Some(0) => return,
+            Some(index) => index - 1,
+            None if self.history.is_empty() => return,
+            None => {
+                self.draft = self.text.clone();
+                self.history.len() - 1
+            }
+        };
+        self.recall(Some(index));
+    }
+
+    /// Shows the next entry of the history, or the draft after the last one.
+    pub fn next(&mut self) {
+        match self.recalled {
+            Some(index) if index + 1 < self.history.len() => self.recall(Some(index + 1)),
+            Some(_) => self.recall(None),
+            None => {}
+        }
+    }
+
+    /// Shows the latest entry of the history before the `before` one that
+    /// fuzzily matches `query`, returning whether there is one.
+    pub fn search(&mut self, query: &str, before: Option<usize>) -> bool {
+        let before = before.unwrap_or(self.history.len());
+        let query = query.to_lowercase();
+        let found = self.history[..before]
+            .iter()
+            .rposition(|entry| fuzzy_match(&entry.to_lowercase(), &query));
+        if let Some(index) = found {
+            if self.recalled.is_none() {
+                self.draft = self.text.clone();
+            }
+            self.recall(Some(index));
+        }
+        found.is_some()
+    }
+
+    /// Index of the history entry being shown, if any
+    pub fn recalled(&self) -> Option<usize> {
+        self.recalled
+    }
+
+    /// Goes back to the draft, eg: when a search is cancelled.
+    pub fn restore(&mut self) {
+        if self.recalled.is_some() {
+            self.recall(None);
+        }
+    }
+
+    fn recall(&mut self, index: Option<usize>) {
+        self.recalled = index;
+        self.text = match index {
+            Some(index) => self.history[index].clone(),
+            None => std::mem::take(&mut self.draft),
+        };
+        self.cursor = self.text.len();
+    }
+
+    /// Takes the text to submit it, adding it to the history.
+    pub fn submit(&mut self) -> String {
+        let text = std::mem::take(&mut self.text);
+        if self.history.last() != Some(&text) {
+            self.history.push(text.clone());
+        }
+        self.clear();
+        text
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+        self.recalled = None;
+        self.draft.clear();
+    }
+
+    /// Wraps the text in rows of `width` columns, returning them along with
+    /// the row and column of the cursor.
+    pub fn rows(&self, width: usize) -> (Vec<String>, (usize, usize)) {
+        let width = width.max(1);
+        let mut rows = vec![String::new()];
+        let mut column = 0;
+        let mut cursor = (0, 0);
+        // note: the final newline is where the cursor is at the end of text.
+        for (index, c) in self.text.char_indices().chain([(self.text.len(), '\n')]) {
+            let c_width = if c == '\n' { 0 } else { c.width().unwrap_or(0) };
+            if column + c_width > width || (index == self.cursor && column == width) {
+                rows.push(String::new());
+                column = 0;
+            }
+            if index == self.cursor {
+                cursor = (rows.len() - 1, column);
+            }
+            if c == '\n' {
+                rows.push(String::new());
+                column = 0;
+            } else if let Some(row) = rows.last_mut() {
+                row.push(c);
+                column += c_width;
+            }
+        }
+        rows.pop();
+        (rows, cursor)
+    }
+}
+
+/// Whether the chars of `query` appear in `text` in the same order.
+fn fuzzy_match(text: &str, query: &str) -> bool {
+    let mut chars = text.chars();
+    query.chars().all(|q| chars.any(|c| c == q))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_editing() {
+        let mut input = InputArea::default();
+        input.insert("héllo");
+        input.left();
+        input.left();
+        input.backspace();
+        input.insert("ll");
+        input.home();
+        input.delete();
+        input.end();
+        input.insert("!");
+
+        let actual = (input.text(), input.cursor());
+        let expected = ("élllo!", 7);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_history() {
+        let mut input = InputArea::new(vec!["first".to_string()]);
+        input.insert("second");
+        input.submit();
+        input.insert("draft");
+
+        let mut actual = Vec::new();
+        for _ in 0..3 {
+            input.previous();
+            actual.push(input.text().to_string());
+        }
+        for _ in 0..2 {
+            input.next();
+            actual.push(input.text().to_string());
+        }
+        let expected = ["second", "first", "first", "second", "draft"];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_search() {
+        let history = ["cargo test", "git status", "cargo build --release"];
+        let mut input = InputArea::new(history.map(String::from).to_vec());
+        input.insert("draft");
+
+        let mut actual = vec![input.search("cgo", None).then(|| input.text().to_string())];
+        actual.push(
+            input
+                .search("cgo", input.recalled())
+                .then(|| input.text().to_string()),
+        );
+        actual.push(
+            input
+                .search("cgo", input.recalled())
+                .then(|| input.text().to_string()),
+        );
+        input.restore();
+        actual.push(Some(input.text().to_string()));
+
+        let expected = vec![
+            Some("cargo build --release".to_string()),
+            Some("cargo test".to_string()),
+            None,
+            Some("draft".to_string()),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_rows() {
+        let mut input = InputArea::default();
+        input.insert("abcdefg\nhi");
+        input.left();
+
+        let actual = input.rows(3);
+        let expected = (
+            vec![
+                "abc".to_string(),
+                "def".to_string(),
+                "g".to_string(),
+                "hi".to_string(),
+            ],
+            (3, 1),
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_rows_cursor_at_full_row() {
+        let mut input = InputArea::default();
+        input.insert("abc");
+
+        let actual = input.rows(3);
+        let expected = (vec!["abc".to_string(), String::new()], (1, 0));
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,719 @@
+//! Full screen interface of the interactive mode, drawn on the alternate
+//! screen of the terminal: a scrollable conversation pane, an input area and
+//! a status bar. Everything written to stdout is shown in the pane, so it
+//! can't break the input, and it's printed to the main screen on exit to be
+//! kept in the scrollback of the terminal.
+
+mod capture;
+mod input_area;
+mod transcript;
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use ansi_to_tui::IntoText;
+use colored::Colorize;
+use crossterm::event::{
+    self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
+    PushKeyboardEnhancementFlags,
+};
+use crossterm::execute;
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use forge_api::Usage;
+use forge_display::TitleFormat;
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+use reedline::{Completer, ValidationResult, Validator};
+use tokio::sync::oneshot;
+
+use self::capture::Capture;
+use self::input_area::InputArea;
+use self::transcript::Transcript;
+use crate::completer::InputCompleter;
+use crate::editor::ReadResult;
+use crate::external_editor;
+use crate::input::PromptInput;
+use crate::prompt::{AI_INDICATOR, RIGHT_CHEVRON};
+use crate::validator::FenceValidator;
+
+/// Rows the input area grows to before it scrolls
+const MAX_INPUT_ROWS: usize = 8;
+
+/// How long keys are waited for before the requests are applied
+const TICK: Duration = Duration::from_millis(30);
+
+/// How long the output written right before the screen is closed is waited
+/// for
+const DRAIN: Duration = Duration::from_millis(50);
+
+/// Escaped newline of the entries of the history file, shared with the
+/// inline mode
+const NEWLINE_ESCAPE: &str = "<\\n>";
+
+enum Request {
+    Output(String),
+    Status(PromptInput),
+    Prompt(Option<PromptInput>, oneshot::Sender<ReadResult>),
+    Completer(InputCompleter),
+    /// Replaces the input with the text
+    Draft(String),
+    Model(String),
+    /// Gives the terminal back until the second channel is signaled, once
+    /// the first one is.
+    Suspend(Sender<()>, Receiver<()>),
+    Stop,
+}
+
+/// Handle of the screen, which is drawn from a thread of its own so that it
+/// keeps up with the output while the agent is working.
+#[derive(Debug)]
+pub struct Screen {
+    requests: Sender<Request>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Screen {
+    /// Switches the terminal to the screen, until the handle is dropped.
+    pub fn start(
+        history: PathBuf,
+        completer: InputCompleter,
+        model: Option<String>,
+    ) -> anyhow::Result<Self> {
+        // note: the terminal is queried before the output is redirected.
+        let enhanced = terminal::supports_keyboard_enhancement().unwrap_or(false);
+
+        let (requests, receiver) = mpsc::channel();
+        let sink = requests.clone();
+        let capture = Capture::start(move |output| {
+            let _ = sink.send(Request::Output(output));
+        })?;
+        let tty = Tty::enter(capture, enhanced)?;
+
+        // The output isn't a terminal anymore, but it's shown on one
+        if forge_display::theme().colored {
+            colored::control::set_override(true);
+            console::set_colors_enabled(true);
+        }
+
+        // The raw mode keeps CTRL+C from raising SIGINT, so it's raised by the
+        // screen to interrupt the agent, and mustn't kill the process when
+        // nothing handles it.
+        #[cfg(unix)]
+        let _ = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt())?;
+
+        let app = App::new(history, completer, model);
+        let thread = std::thread::spawn(move || app.run(tty, receiver));
+        Ok(Self { requests, thread: Some(thread) })
+    }
+
+    /// Waits for the user to submit the input.
+    pub async fn prompt(&self, input: Option<PromptInput>) -> ReadResult {
+        let (reply, submitted) = oneshot::channel();
+        let _ = self.requests.send(Request::Prompt(input, reply));
+        // note: the screen is only closed on error, and the session with it.
+        submitted.await.unwrap_or(ReadResult::Exit)
+    }
+
+    pub fn status(&self, input: PromptInput) {
+        let _ = self.requests.send(Request::Status(input));
+    }
+
+    pub fn completer(&self, completer: InputCompleter) {
+        let _ = self.requests.send(Request::Completer(completer));
+    }
+
+    pub fn model(&self, model: String) {
+        let _ = self.requests.send(Request::Model(model));
+    }
+
+    /// Fills the input with `text`, eg: to edit the last message.
+    pub fn draft(&self, text: String) {
+        let _ = self.requests.send(Request::Draft(text));
+    }
+
+    /// Runs `f` on the main screen of the terminal, eg: to open the editor.
+    pub fn suspend<T>(&self, f: impl FnOnce() -> T) -> T {
+        let (suspended, on_suspended) = mpsc::channel();
+        let (resumed, on_resumed) = mpsc::channel();
+        if self
+            .requests
+            .send(Request::Suspend(suspended, on_resumed))
+            .is_ok()
+        {
+            let _ = on_suspended.recv();
+        }
+        let value = f();
+        let _ = resumed.send(());
+        value
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = self.requests.send(Request::Stop);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The terminal while the screen is shown, restored when dropped.
+struct Tty {
+    terminal: Terminal<CrosstermBackend<File>>,
+    capture: Capture,
+    /// Whether the terminal reports SHIFT + Enter as a distinct key
+    enhanced: bool,
+}
+
+impl Tty {
+    fn enter(capture: Capture, enhanced: bool) -> io::Result<Self> {
+        let terminal = Terminal::new(CrosstermBackend::new(capture.terminal()?))?;
+        let mut tty = Self { terminal, capture, enhanced };
+        tty.show()?;
+        Ok(tty)
+    }
+
+    fn show(&mut self) -> io::Result<()> {
+        terminal::enable_raw_mode()?;
+        let backend = self.terminal.backend_mut();
+        execute!(backend, EnterAlternateScreen, EnableBracketedPaste)?;
+        if self.enhanced {
+            execute!(
+                backend,
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+            )?;
+        }
+        self.capture.resume()?;
+        self.terminal.clear()
+    }
+
+    fn hide(&mut self) -> io::Result<()> {
+        self.capture.pause()?;
+        let backend = self.terminal.backend_mut();
+        if self.enhanced {
+            execute!(backend, PopKeyboardEnhancementFlags)?;
+        }
+        execute!(backend, DisableBracketedPaste, LeaveAlternateScreen)?;
+        self.terminal.show_cursor()?;
+        terminal::disable_raw_mode()
+    }
+
+    fn suspend<T>(&mut self, f: impl FnOnce() -> T) -> io::Result<T> {
+        self.hide()?;
+        let value = f();
+        self.show()?;
+        Ok(value)
+    }
+}
+
+impl Drop for Tty {
+    fn drop(&mut self) {
+        let _ = self.hide();
+    }
+}
+
+/// What's shown in the status bar.
+#[derive(Debug, Default)]
+struct Status {
+    model: Option<String>,
+    title: Option<String>,
+    usage: Option<Usage>,
+    cost: Option<f64>,
+}
+
+impl Status {
+    /// Updates the status of the conversation, which is reset by `None`, eg:
+    /// on `/new`.
+    fn update(&mut self, input: Option<PromptInput>) {
+        let (title, usage, cost) = match input {
+            Some(PromptInput::Update { title, usage, cost }) => (title, usage, cost),
+            None => Default::default(),
+        };
+        self.title = title;
+        self.usage = usage;
+        self.cost = cost;
+    }
+
+    fn line(&self, busy: bool, scroll: usize) -> Line<'static> {
+        let mut spans = vec![Span::from(format!(" {AI_INDICATOR} "))];
+        if let Some(title) = &self.title {
+            spans.push(Span::from(format!("{title} ")).cyan());
+        }
+        if let Some(model) = &self.model {
+            spans.push(Span::from(format!("{model} ")).dark_gray());
+        }
+        if let Some(usage) = self.usage.as_ref().filter(|usage| usage.total_tokens > 0) {
+            spans.push(
+                Span::from(format!(
+                    "tokens: {} ↑ / {} ↓ ",
+                    humanize_tokens(usage.prompt_tokens),
+                    humanize_tokens(usage.completion_tokens)
+                ))
+                .dark_gray()
+                .bold(),
+            );
+        }
+        if let Some(cost) = self.cost.filter(|cost| *cost > 0.0) {
+            spans.push(Span::from(format!("(${cost:.4}) ")).dark_gray().bold());
+        }
+        if busy {
+            spans.push(Span::from("working… ").yellow());
+        }
+        if scroll > 0 {
+            spans.push(Span::from(format!("↓ {scroll} rows")).dark_gray());
+        }
+        Line::from(spans)
+    }
+}
+
+/// Tokens in thousands above a thousand, eg: `1.2k`.
+fn humanize_tokens(tokens: u64) -> String {
+    if tokens < 1000 {
+        tokens.to_string()
+    } else {
+        format!("{:.1}k", tokens as f64 / 1000.0)
+    }
+}
+
+struct App {
+    transcript: Transcript,
+    input: InputArea,
+    status: Status,
+    history: PathBuf,
+    completer: InputCompleter,
+    /// Candidates of the last completion, when there are several ones
+    candidates: Vec<String>,
+    /// Query of the reverse search of the history, while it's going on
+    search: Option<String>,
+    /// Set while the user is prompted, to pass them what's submitted
+    prompt: Option<oneshot::Sender<ReadResult>>,
+    /// Rows the conversation pane is scrolled up by
+    scroll: usize,
+    /// Rows of the conversation pane when it was last drawn
+    height: usize,
+}
+
+impl App {
+    fn new(history: PathBuf, completer: InputCompleter, model: Option<String>) -> Self {
+        let entries = std::fs::read_to_string(&history)
+            .unwrap_or_default()
+            .lines()
+            .map(|entry| entry.replace(NEWLINE_ESCAPE, "\n"))
+            .collect();
+        Self {
+            transcript: Transcript::default(),
+            input: InputArea::new(entries),
+            status: Status { model, ..Default::default() },
+            history,
+            completer,
+            candidates: Vec::new(),
+            search: None,
+            prompt: None,
+            scroll: 0,
+            height: 0,
+        }
+    }
+
+    fn run(mut self, mut tty: Tty, requests: Receiver<Request>) {
+        if let Err(error) = self.update(&mut tty, &requests) {
+            tracing::error!(error = ?error, "The screen failed");
+        }
+        drop(tty);
+
+        while let Ok(request) = requests.recv_timeout(DRAIN) {
+            if let Request::Output(output) = request {
+                self.transcript.push(&output);
+            }
+        }
+        let _ = write!(io::stdout(), "{}", self.transcript.as_str());
+        let _ = io::stdout().flush();
+    }
+
+    /// Applies the keys and the requests until the screen is stopped.
+    fn update(&mut self, tty: &mut Tty, requests: &Receiver<Request>) -> anyhow::Result<()> {
+        let mut dirty = true;
+        loop {
+            if dirty {
+                tty.terminal.draw(|frame| self.draw(frame))?;
+                dirty = false;
+            }
+            if event::poll(TICK)? {
+                match event::read()? {
+                    Event::Key(key) if key.kind != KeyEventKind::Release => self.key(key, tty)?,
+                    Event::Paste(text) => self.input.insert(&text.replace("\r\n", "\n")),
+                    _ => {}
+                }
+                dirty = true;
+            }
+            loop {
+                match requests.try_recv() {
+                    Ok(Request::Stop) | Err(TryRecvError::Disconnected) => return Ok(()),
+                    Ok(request) => self.request(request, tty)?,
+                    Err(TryRecvError::Empty) => break,
+                }
+                dirty = true;
+            }
+        }
+    }
+
+    fn request(&mut self, request: Request, tty: &mut Tty) -> anyhow::Result<()> {
+        match request {
+            Request::Output(output) => {
+                // note: the rows that are scrolled to stay in place as output is
+                // added, as long as it's not redrawn.
+                if self.scroll > 0 {
+                    self.scroll += output.matches('\n').count();
+                }
+                self.transcript.push(&output);
+            }
+            Request::Status(input) => self.status.update(Some(input)),
+            Request::Prompt(input, reply) => {
+                self.status.update(input);
+                self.prompt = Some(reply);
+            }
+            Request::Completer(completer) => self.completer = completer,
+            Request::Model(model) => self.status.model = Some(model),
+            Request::Draft(text) => {
+                self.input.clear();
+                self.input.insert(&text);
+            }
+            Request::Suspend(suspended, resumed) => {
+                tty.suspend(|| {
+                    let _ = suspended.send(());
+                    let _ = resumed.recv();
+                })?;
+            }
+            Request::Stop => {}
+        }
+        Ok(())
+    }
+
+    fn key(&mut self, key: KeyEvent, tty: &mut Tty) -> anyhow::Result<()> {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        let newline = key
+            .modifiers
+            .intersects(KeyModifiers::SHIFT | KeyModifiers::ALT);
+        let page = (self.height / 2).max(1) as isize;
+        if key.code != KeyCode::Tab {
+            self.candidates.clear();
+        }
+        if self.searched(key, control) {
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Char('c') if control => self.interrupt(),
+            KeyCode::Char('d') if control && self.input.text().is_empty() => {
+                self.reply(ReadResult::Exit)
+            }
+            KeyCode::Char('e') if control => self.compose(tty)?,
+            KeyCode::Char('k') if control => self.transcript = Transcript::default(),
+            KeyCode::Char('r') if control => self.search = Some(String::new()),
+            KeyCode::Char('t') if control => self.reply(ReadResult::Success("/voice".to_string())),
+            KeyCode::Char(c) if !control => self.input.insert(c.encode_utf8(&mut [0; 4])),
+            KeyCode::Enter if newline => self.input.insert("\n"),
+            KeyCode::Enter => self.submit(),
+            KeyCode::Tab => self.complete(),
+            KeyCode::Backspace => self.input.backspace(),
+            KeyCode::Delete => self.input.delete(),
+            KeyCode::Left => self.input.left(),
+            KeyCode::Right => self.input.right(),
+            KeyCode::Home => self.input.home(),
+            KeyCode::End => self.input.end(),
+            KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) => self.scroll_by(1),
+            KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) => self.scroll_by(-1),
+            KeyCode::Up => self.input.previous(),
+            KeyCode::Down => self.input.next(),
+            KeyCode::PageUp => self.scroll_by(page),
+            KeyCode::PageDown => self.scroll_by(-page),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Applies the key to the reverse search of the history, if it's going
+    /// on, returning whether it was used. Other keys end the search, keeping
+    /// the entry that's found, eg: ENTER submits it.
+    fn searched(&mut self, key: KeyEvent, control: bool) -> bool {
+        let Some(query) = &mut self.search else {
+            return false;
+        };
+        match key.code {
+            KeyCode::Char('r') if control => {
+                self.input.search(query, self.input.recalled());
+            }
+            KeyCode::Char('c' | 'g') if control => {
+                self.search = None;
+                self.input.restore();
+            }
+            KeyCode::Esc => {
+                self.search = None;
+                self.input.restore();
+            }
+            KeyCode::Char(c) if !control => {
+                query.push(c);
+                self.input.search(query, None);
+            }
+            KeyCode::Backspace => {
+                query.pop();
+                self.input.search(query, None);
+            }
+            _ => {
+                self.search = None;
+                return false;
+            }
+        }
+        true
+    }
+
+    fn scroll_by(&mut self, rows: isize) {
+        self.scroll = self.scroll.saturating_add_signed(rows);
+    }
+
+    fn reply(&mut self, result: ReadResult) {
+        if let Some(prompt) = self.prompt.take() {
+            let _ = prompt.send(result);
+        }
+    }
+
+    /// Clears the input while the user is prompted, and interrupts the agent
+    /// otherwise, as CTRL+C does in the inline mode.
+    fn interrupt(&mut self) {
+        if self.prompt.is_some() {
+            self.input.clear();
+            return;
+        }
+        #[cfg(unix)]
+        // Safety: raising a signal has no preconditions.
+        unsafe {
+            libc::raise(libc::SIGINT);
+        }
+    }
+
+    /// Submits the input, unless it has an open code fence. What's typed
+    /// while the agent is working is kept until it's done.
+    fn submit(&mut self) {
+        if matches!(
+            FenceValidator.validate(self.input.text()),
+            ValidationResult::Incomplete
+        ) {
+            self.input.insert("\n");
+            return;
+        }
+        if self.prompt.is_none() || self.input.text().trim().is_empty() {
+            return;
+        }
+
+        let text = self.input.submit();
+        // note: the history is shared with the inline mode, in its format.
+        let _ = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.history)
+            .and_then(|mut file| writeln!(file, "{}", text.replace('\n', NEWLINE_ESCAPE)));
+
+        let text = text.trim().to_string();
+        self.transcript
+            .push(&format!("{} {text}\n", RIGHT_CHEVRON.bright_yellow()));
+        self.scroll = 0;
+        self.reply(ReadResult::Success(text));
+    }
+
+    /// Completes the word at the cursor, listing the candidates when there
+    /// are several ones.
+    fn complete(&mut self) {
+        let suggestions = self
+            .completer
+            .complete(self.input.text(), self.input.cursor());
+        match suggestions.as_slice() {
+            [] => {}
+            [suggestion] => {
+                let mut value = suggestion.value.clone();
+                if suggestion.append_whitespace {
+                    value.push(' ');
+                }
+                self.input
+                    .replace(suggestion.span.start..suggestion.span.end, &value);
+            }
+            suggestions => {
+                self.candidates = suggestions
+                    .iter()
+                    .map(|suggestion| suggestion.value.clone())
+                    .collect()
+            }
+        }
+    }
+
+    /// Opens the input in the editor and submits what's saved.
+    fn compose(&mut self, tty: &mut Tty) -> anyhow::Result<()> {
+        let draft = self.input.text().to_string();
+        match tty.suspend(|| external_editor::compose(&draft))? {
+            Ok(content) => {
+                self.input.clear();
+                self.input.insert(&content);
+                self.submit();
+            }
+            Err(error) => self.transcript.push(&format!(
+                "{}\n",
+                TitleFormat::failed(error.to_string()).format()
+            )),
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let (rows, cursor) = self
+            .input
+            .rows(frame.area().width.saturating_sub(2) as usize);
+        let input_rows = rows.len().min(MAX_INPUT_ROWS);
+        let [pane, input, status] = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(input_rows as u16 + 1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        frame.render_widget(self.conversation(pane), pane);
+
+        let first = (cursor.0 + 1).saturating_sub(input_rows);
+        let lines = rows
+            .into_iter()
+            .enumerate()
+            .skip(first)
+            .take(input_rows)
+            .map(|(index, row)| {
+                let prefix = if index == 0 { RIGHT_CHEVRON } else { " " };
+                Line::from(vec![
+                    Span::from(format!("{prefix} ")).light_yellow(),
+                    Span::from(row),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let title = match &self.search {
+            Some(query) => format!("reverse-search: {query}"),
+            None => self.candidates.join("  "),
+        };
+        let block = Block::new()
+            .borders(Borders::TOP)
+            .border_style(Style::new().dark_gray())
+            .title(Line::from(title).cyan());
+        frame.render_widget(Paragraph::new(lines).block(block), input);
+
+        frame.render_widget(self.status.line(self.prompt.is_none(), self.scroll), status);
+        frame.set_cursor_position((
+            input.x + 2 + cursor.1 as u16,
+            input.y + 1 + (cursor.0 - first) as u16,
+        ));
+    }
+
+    /// The bottom of the transcript, scrolled up by `scroll` rows. Only the
+    /// lines that can be shown are parsed, as it's drawn on every chunk.
+    fn conversation(&mut self, area: Rect) -> Paragraph<'static> {
+        self.height = area.height as usize;
+        let text = self.transcript.as_str();
+        let start = text
+            .rmatch_indices('\n')
+            .nth(self.height + self.scroll)
+            .map_or(0, |(index, _)| index + 1);
+        let lines = (&text.as_bytes()[start..])
+            .into_text()
+            .unwrap_or_default()
+            .lines;
+        let rows = lines
+            .iter()
+            .map(|line| {
+                Paragraph::new(line.clone())
+                    .wrap(Wrap { trim: false })
+                    .line_count(area.width)
+            })
+            .sum::<usize>();
+        if start == 0 {
+            self.scroll = self.scroll.min(rows.saturating_sub(self.height));
+        }
+
+        let top = rows.saturating_sub(self.height + self.scroll);
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((u16::try_from(top).unwrap_or(u16::MAX), 0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+
+    fn fixture(output: &str, scroll: usize) -> Vec<String> {
+        let completer = InputCompleter::new(std::env::temp_dir(), Vec::new());
+        let mut app = App::new(PathBuf::new(), completer, Some("model".to_string()));
+        app.transcript.push(output);
+        app.scroll = scroll;
+        app.input.insert("draft");
+
+        let mut terminal = Terminal::new(TestBackend::new(12, 5)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_draw() {
+        let actual = fixture("one\ntwo\n\x1b[1mthree\x1b[0m", 0);
+        let expected = vec![
+            "two         ",
+            "three       ",
+            "────────────",
+            "❯ draft     ",
+        ];
+        assert_eq!(actual[..4], expected);
+    }
+
+    #[test]
+    fn test_draw_scrolled() {
+        let actual = fixture("one two three\nfour\nfive", 1);
+        let expected = vec!["three       ", "four        "];
+        assert_eq!(actual[..2], expected);
+    }
+
+    #[test]
+    fn test_status_line() {
+        let status = Status {
+            usage: Some(Usage {
+                prompt_tokens: 1234,
+                completion_tokens: 350,
+                total_tokens: 1584,
+                cached_tokens: 0,
+            }),
+            cost: Some(0.004),
+            ..Default::default()
+        };
+
+        let actual = status.line(false, 0).to_string();
+        let expected = format!(" {AI_INDICATOR} tokens: 1.2k ↑ / 350 ↓ ($0.0040) ");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_scroll_is_bounded() {
+        let actual = fixture("one\ntwo", 10);
+        let expected = vec!["one         ", "two         "];
+        assert_eq!(actual[..2], expected);
+    }
+}
@@ -0,0 +1,128 @@
+/// Output of the session as a terminal would show it, so that what's written
+/// for the inline mode, eg: the streamed line that's redrawn by the markdown
+/// renderer or a progress bar, shows up the same way in the conversation
+/// pane. Colors are kept, other escape sequences are applied or dropped.
+#[derive(Debug, Default)]
+pub struct Transcript {
+    text: String,
+    /// End of the last chunk that can't be applied yet, eg: half an escape
+    /// sequence.
+    pending: String,
+}
+
+impl Transcript {
+    pub fn push(&mut self, chunk: &str) {
+        let input = std::mem::take(&mut self.pending) + chunk;
+        let mut rest = input.as_str();
+        while let Some(c) = rest.chars().next() {
+            match c {
+                '\r' => match rest[1..].chars().next() {
+                    None => break,
+                    Some('\n') => {
+                        self.text.push('\n');
+                        rest = &rest[2..];
+                        continue;
+                    }
+                    // note: the line is about to be overwritten.
+                    Some(_) => self.text.truncate(self.line_start()),
+                },
+                '\x1b' => match csi(rest) {
+                    Some((sequence, len)) => {
+                        self.apply(sequence);
+                        rest = &rest[len..];
+                        continue;
+                    }
+                    None if incomplete(rest) => break,
+                    // note: other sequences aren't supported, only their
+                    // escape is dropped.
+                    None => {}
+                },
+                c => self.text.push(c),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+        self.pending = rest.to_string();
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    fn line_start(&self) -> usize {
+        self.text.rfind('\n').map_or(0, |index| index + 1)
+    }
+
+    fn apply(&mut self, sequence: &str) {
+        let (params, command) = sequence.split_at(sequence.len() - 1);
+        match command {
+            "m" => {
+                self.text.push_str("\x1b[");
+                self.text.push_str(sequence);
+            }
+            // note: the lines the cursor moves up to are cleared right after.
+            "A" => {
+                for _ in 0..params.parse::<usize>().unwrap_or(1) {
+                    self.text.pop();
+                    self.text.truncate(self.line_start());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The parameters and command of the CSI sequence at the start of `text`,
+/// along with its length.
+fn csi(text: &str) -> Option<(&str, usize)> {
+    let sequence = text.strip_prefix("\x1b[")?;
+    let end = sequence.find(|c: char| ('\x40'..='\x7e').contains(&c))?;
+    Some((&sequence[..=end], end + 3))
+}
+
+/// Whether `text` may be the start of a CSI sequence that's still being
+/// written.
+fn incomplete(text: &str) -> bool {
+    text == "\x1b"
+        || text
+            .strip_prefix("\x1b[")
+            .is_some_and(|params| params.chars().all(|c| ('\x20'..'\x40').contains(&c)))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn transcript(chunks: &[&str]) -> String {
+        let mut transcript = Transcript::default();
+        for chunk in chunks {
+            transcript.push(chunk);
+        }
+        transcript.as_str().to_string()
+    }
+
+    #[test]
+    fn test_colors_are_kept() {
+        let actual = transcript(&["\x1b[1mbold", "\x1b[", "0m\r\n"]);
+        assert_eq!(actual, "\x1b[1mbold\x1b[0m\n");
+    }
+
+    #[test]
+    fn test_redrawn_line() {
+        let actual = transcript(&["done\n**bo", "\r\x1b[J", "bold\n"]);
+        assert_eq!(actual, "done\nbold\n");
+    }
+
+    #[test]
+    fn test_redrawn_lines() {
+        let actual = transcript(&["done\nwrapped\nline", "\r\x1b[1A\x1b[J", "redrawn"]);
+        assert_eq!(actual, "done\nredrawn");
+    }
+
+    #[test]
+    fn test_progress() {
+        let actual = transcript(&["10%", "\r", "100%\r", "\n"]);
+        assert_eq!(actual, "100%\n");
+    }
+}
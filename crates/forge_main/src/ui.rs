@@ -1,19 +1,54 @@
+use std::collections::BTreeSet;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::Colorize;
-use forge_api::{AgentMessage, ChatRequest, ChatResponse, ConversationId, Model, Usage, API};
-use forge_display::TitleFormat;
-use forge_tracker::EventKind;
+use forge_api::{
+    AgentId, AgentMessage, AgentMode, ChatRequest, ChatResponse, Config, ConversationFile,
+    ConversationId, CustomCommand, Dialect, ForgeHookService, HookEvent, HookService, Image, Model,
+    ModelId, ModelParameters, Plan, Question, RedactionAction, ThemeName, ToolAuditLog, Usage,
+    Workflow, API,
+};
+use forge_display::{DiffFormat, MarkdownFormat, Theme, TitleFormat};
+use forge_tracker::{EventKind, StatsStore, StatsSummary};
 use lazy_static::lazy_static;
+use tokio::io::AsyncReadExt;
 use tokio_stream::StreamExt;
 
-use crate::banner;
-use crate::cli::Cli;
+use crate::agent_tree::AgentTree;
+use crate::batch::{JsonReporter, EXIT_FAILURE, EXIT_USAGE};
+use crate::cli::{AuditCommand, AuthCommand, Cli, TopLevelCommand};
+use crate::clipboard::Pasted;
+use crate::config::ConfigCommand;
 use crate::console::CONSOLE;
+use crate::cost::CostTracker;
 use crate::info::Info;
 use crate::input::{Console, PromptInput};
+use crate::journal::ChangeJournal;
 use crate::model::{Command, UserInput};
+use crate::speech::Narrator;
+use crate::stats::StatsRecorder;
+use crate::transcript::{ExportArgs, TranscriptExporter};
+use crate::voice::Voice;
+use crate::watch::{FileWatcher, TriggerRun, Triggers};
+use crate::{audit, auth, banner, batch, doctor, git, stats, trust};
+
+/// Checkpoint taken before every message, that `/retry` rolls back to
+const RETRY_CHECKPOINT: &str = "retry";
+
+/// Answer to the questions of the agents when there's no user to ask
+pub(crate) const NO_ANSWER: &str =
+    "The user isn't available to answer, carry on with your best judgement";
+
+/// Agent of the workflow that writes the message of `/commit`
+const COMMIT_MESSAGE_AGENT: &str = "commit_message_worker";
+
+/// Characters of the diff sent to write the commit message, to keep large
+/// changes within the context of the model
+const COMMIT_DIFF_LIMIT: usize = 50_000;
 
 lazy_static! {
     pub static ref TRACKER: forge_tracker::Tracker = forge_tracker::Tracker::default();
@@ -24,6 +59,29 @@ struct UIState {
     current_title: Option<String>,
     conversation_id: Option<ConversationId>,
     usage: Usage,
+    cost: CostTracker,
+    journal: ChangeJournal,
+    /// Set when an agent stopped to ask the user for help
+    needs_user_input: Option<String>,
+    /// Statistics of the chat request in progress
+    stats: Option<StatsRecorder>,
+    /// Last message sent to the agents, for `/retry` and `/editlast`
+    last_message: Option<String>,
+    /// Images attached to the last message, sent again by `/retry`
+    last_images: Vec<Image>,
+    /// Images pasted with `/paste`, attached to the next message
+    images: Vec<Image>,
+    /// Agent the next message is sent to, set by `/agent`
+    agent: Option<AgentId>,
+    /// Mode the agents are switched to by `/plan` and `/act`, otherwise the
+    /// one of the workflow
+    mode: Option<AgentMode>,
+    /// Plan submitted in the last response, waiting for the user's approval
+    plan: Option<Plan>,
+    /// Files pinned with `/pin`, given to the conversation once it starts
+    pinned_files: BTreeSet<PathBuf>,
+    /// Agents running on behalf of others, shown nested under them
+    agents: AgentTree,
 }
 
 impl From<&UIState> for PromptInput {
@@ -31,16 +89,32 @@ impl From<&UIState> for PromptInput {
         PromptInput::Update {
             title: state.current_title.clone(),
             usage: Some(state.usage.clone()),
+            cost: Some(state.cost.total()),
         }
     }
 }
 
 pub struct UI<F> {
     state: UIState,
+    /// Sessions left with `/new`, that `/switch` goes back to
+    sessions: Vec<UIState>,
     api: Arc<F>,
     console: Console,
     cli: Cli,
     models: Option<Vec<Model>>,
+    /// Configuration resolved from the flags, the environment and the config
+    /// files
+    config: Config,
+    /// Generation parameters that override the ones of the workflow
+    parameters: ModelParameters,
+    /// Reports the chat responses as JSON events instead of rendering them
+    reporter: Option<JsonReporter<Box<dyn Write + Send>>>,
+    /// Renders the streamed markdown, unless the output is plain
+    markdown: Option<MarkdownFormat>,
+    /// Speaks the summary of each turn, when enabled by the `speech` setting
+    narrator: Option<Narrator>,
+    /// Records and transcribes voice input, when enabled with `--voice`
+    voice: Option<Voice>,
     #[allow(dead_code)] // The guard is kept alive by being held in the struct
     _guard: forge_tracker::Guard,
 }
@@ -50,26 +124,117 @@ impl<F: API> UI<F> {
         // Parse CLI arguments first to get flags
 
         let env = api.environment();
+        let config = cli.config().or(env.config.clone());
+        forge_display::set_theme(match config.theme.unwrap_or_default() {
+            ThemeName::Dark => Theme::dark(),
+            ThemeName::Light => Theme::light(),
+            ThemeName::HighContrast => Theme::high_contrast(),
+            ThemeName::NoColor => Theme::no_color(),
+        });
+        let voice = cli
+            .voice
+            .then(|| Voice::new(&env, cli.whisper_model.as_deref()))
+            .transpose()?;
+        let narrator = Narrator::new(&config.speech, env.openai_key.clone());
         Ok(Self {
             state: Default::default(),
+            sessions: Vec::new(),
             api,
             console: Console::new(env.clone()),
+            config,
             cli,
             models: None,
+            parameters: Default::default(),
+            reporter: None,
+            markdown: None,
+            narrator,
+            voice,
             _guard: forge_tracker::init_tracing(env.log_path())?,
         })
     }
 
-    pub async fn run(&mut self) -> Result<()> {
+    pub async fn run(&mut self) -> Result<ExitCode> {
+        match &self.cli.subcommand {
+            Some(TopLevelCommand::Watch { triggers }) => {
+                let triggers = triggers.clone();
+                self.handle_watch(&triggers).await?;
+                return Ok(ExitCode::SUCCESS);
+            }
+            Some(TopLevelCommand::Run { prompt, json }) => {
+                let (prompt, json) = (prompt.clone(), *json);
+                return self.handle_run(prompt, json).await;
+            }
+            Some(TopLevelCommand::Stats { days }) => {
+                self.handle_stats(*days)?;
+                return Ok(ExitCode::SUCCESS);
+            }
+            Some(TopLevelCommand::Doctor) => {
+                return self.handle_doctor().await;
+            }
+            Some(TopLevelCommand::Auth { command }) => {
+                match command {
+                    AuthCommand::Login { provider } => auth::login(provider.clone())?,
+                    AuthCommand::Logout { provider } => auth::logout(provider.clone())?,
+                    AuthCommand::Status => auth::status()?,
+                }
+                return Ok(ExitCode::SUCCESS);
+            }
+            Some(TopLevelCommand::Audit { command }) => {
+                return self.handle_audit(command);
+            }
+            Some(TopLevelCommand::Trust { path, revoke, list }) => {
+                if *list {
+                    trust::list()?;
+                } else {
+                    trust::trust(path.as_deref(), *revoke)?;
+                }
+                return Ok(ExitCode::SUCCESS);
+            }
+            // note: the bridge is served by `Bridge::serve` instead, it handles
+            // the requests of the extension concurrently.
+            Some(TopLevelCommand::LspBridge) => {
+                anyhow::bail!("The bridge isn't served by the UI")
+            }
+            // note: likewise, the ACP server is served by `AcpServer::serve`.
+            Some(TopLevelCommand::Acp) => {
+                anyhow::bail!("The ACP server isn't served by the UI")
+            }
+            None => {}
+        }
+
         // Handle direct prompt if provided
         let prompt = self.cli.prompt.clone();
         if let Some(prompt) = prompt {
-            self.chat(prompt).await?;
-            return Ok(());
+            let result = self.chat(prompt).await;
+            self.end_session().await;
+            result?;
+            return Ok(ExitCode::SUCCESS);
+        }
+
+        let workflow = self.api.load(self.cli.workflow.as_deref()).await?;
+        if !self.cli.inline && std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
+            let model = self
+                .config
+                .model
+                .as_ref()
+                .or(workflow.agents.first().map(|agent| &agent.model))
+                .map(|model| model.as_str().to_string());
+            // The inline mode still works where the screen can't be shown
+            if let Err(error) = self.console.enter_screen(model) {
+                tracing::warn!(error = ?error, "Failed to show the screen");
+            }
         }
+        self.console.custom_commands(workflow.commands);
 
         // Display the banner in dimmed colors since we're in interactive mode
         banner::display()?;
+        if !self.api.environment().trusted {
+            CONSOLE.writeln(
+                TitleFormat::execute("read-only")
+                    .sub_title("the folder isn't trusted, run `forge trust` to trust it")
+                    .format(),
+            )?;
+        }
 
         // Get initial input from file or prompt
         let mut input = match &self.cli.command {
@@ -87,7 +252,10 @@ impl<F: API> UI<F> {
                 }
                 Command::New => {
                     banner::display()?;
-                    self.state = Default::default();
+                    let session = std::mem::take(&mut self.state);
+                    if session.conversation_id.is_some() {
+                        self.sessions.push(session);
+                    }
                     input = self.console.prompt(None).await?;
 
                     continue;
@@ -104,27 +272,278 @@ impl<F: API> UI<F> {
                 }
                 Command::Message(ref content) => {
                     if let Err(err) = self.chat(content.clone()).await {
+                        self.state.plan = None;
                         CONSOLE.writeln(
                             TitleFormat::failed(format!("{:?}", err))
                                 .sub_title(self.state.usage.to_string())
                                 .format(),
                         )?;
                     }
+                    if let Some(plan) = self.state.plan.take() {
+                        match self.review_plan(&plan).await {
+                            Ok(Some(content)) => {
+                                input = Command::Message(content);
+                                continue;
+                            }
+                            Ok(None) => {}
+                            Err(err) => CONSOLE.writeln(
+                                TitleFormat::failed("plan").error(err.to_string()).format(),
+                            )?,
+                        }
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Export(ref args) => {
+                    if let Err(err) = self.handle_export(args).await {
+                        CONSOLE.writeln(
+                            TitleFormat::failed("export")
+                                .error(err.to_string())
+                                .format(),
+                        )?;
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::ExportConversation(ref path) => {
+                    if let Err(err) = self.handle_export_conversation(path).await {
+                        CONSOLE.writeln(
+                            TitleFormat::failed("export-conversation")
+                                .error(err.to_string())
+                                .format(),
+                        )?;
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::ImportConversation(ref path) => {
+                    if let Err(err) = self.handle_import_conversation(path).await {
+                        CONSOLE.writeln(
+                            TitleFormat::failed("import-conversation")
+                                .error(err.to_string())
+                                .format(),
+                        )?;
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Edit(ref draft) => {
+                    match self.console.compose(draft) {
+                        Ok(content) if !content.is_empty() => {
+                            CONSOLE.writeln(&content)?;
+                            input = Command::Message(content);
+                            continue;
+                        }
+                        Ok(_) => {}
+                        Err(err) => {
+                            CONSOLE.writeln(TitleFormat::failed(err.to_string()).format())?;
+                        }
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Checkpoint(ref name) => {
+                    if let Err(err) = self.handle_checkpoint(name).await {
+                        CONSOLE.writeln(
+                            TitleFormat::failed("checkpoint")
+                                .error(err.to_string())
+                                .format(),
+                        )?;
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Pin(ref path) => {
+                    if let Err(err) = self.handle_pin(path).await {
+                        CONSOLE
+                            .writeln(TitleFormat::failed("pin").error(err.to_string()).format())?;
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Unpin(ref path) => {
+                    if let Err(err) = self.handle_unpin(path).await {
+                        CONSOLE.writeln(
+                            TitleFormat::failed("unpin").error(err.to_string()).format(),
+                        )?;
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Switch(ref session) => {
+                    if let Err(err) = self.handle_switch(session) {
+                        CONSOLE.writeln(
+                            TitleFormat::failed("switch")
+                                .error(err.to_string())
+                                .format(),
+                        )?;
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Branch(ref name) => {
+                    if let Err(err) = self.handle_branch(name).await {
+                        CONSOLE.writeln(
+                            TitleFormat::failed("branch")
+                                .error(err.to_string())
+                                .format(),
+                        )?;
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Model(ref model) => {
+                    if let Err(err) = self.handle_model(model).await {
+                        CONSOLE.writeln(
+                            TitleFormat::failed("model").error(err.to_string()).format(),
+                        )?;
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Agents => {
+                    match self.workflow().await {
+                        Ok(workflow) => CONSOLE.writeln(Info::from(&workflow).to_string())?,
+                        Err(err) => CONSOLE.writeln(
+                            TitleFormat::failed("agents")
+                                .error(err.to_string())
+                                .format(),
+                        )?,
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Agent(ref agent) => {
+                    if let Err(err) = self.handle_agent(agent).await {
+                        CONSOLE.writeln(
+                            TitleFormat::failed("agent").error(err.to_string()).format(),
+                        )?;
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Context(ref agent) => {
+                    if let Err(err) = self.handle_context(agent).await {
+                        CONSOLE.writeln(
+                            TitleFormat::failed("context")
+                                .error(err.to_string())
+                                .format(),
+                        )?;
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Retry => match self.handle_retry().await {
+                    Ok(content) => {
+                        input = Command::Message(content);
+                        continue;
+                    }
+                    Err(err) => {
+                        CONSOLE.writeln(
+                            TitleFormat::failed("retry").error(err.to_string()).format(),
+                        )?;
+                        let prompt_input = Some((&self.state).into());
+                        input = self.console.prompt(prompt_input).await?;
+                    }
+                },
+                Command::EditLast => {
+                    match &self.state.last_message {
+                        Some(content) => self.console.draft(content),
+                        None => CONSOLE.writeln(
+                            TitleFormat::failed("editlast")
+                                .error("No message to edit yet")
+                                .format(),
+                        )?,
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Plan | Command::Act => {
+                    let mode = match input {
+                        Command::Plan => AgentMode::Plan,
+                        _ => AgentMode::Act,
+                    };
+                    if let Err(err) = self.handle_mode(mode).await {
+                        CONSOLE.writeln(
+                            TitleFormat::failed(mode.to_string())
+                                .error(err.to_string())
+                                .format(),
+                        )?;
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Paste => {
+                    if let Err(err) = self.handle_paste() {
+                        CONSOLE.writeln(
+                            TitleFormat::failed("paste").error(err.to_string()).format(),
+                        )?;
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Voice => {
+                    if let Err(err) = self.handle_voice().await {
+                        CONSOLE.writeln(
+                            TitleFormat::failed("voice").error(err.to_string()).format(),
+                        )?;
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Commit => {
+                    if let Err(err) = self.handle_commit().await {
+                        CONSOLE.writeln(
+                            TitleFormat::failed("commit")
+                                .error(err.to_string())
+                                .format(),
+                        )?;
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Custom { ref command, ref args } => {
+                    match self.handle_custom(command, args).await {
+                        Ok(Some(content)) => {
+                            input = Command::Message(content);
+                            continue;
+                        }
+                        Ok(None) => {}
+                        Err(err) => {
+                            CONSOLE.writeln(
+                                TitleFormat::failed(&command.name)
+                                    .error(err.to_string())
+                                    .format(),
+                            )?;
+                        }
+                    }
                     let prompt_input = Some((&self.state).into());
                     input = self.console.prompt(prompt_input).await?;
                 }
                 Command::Exit => {
+                    self.console.leave_screen();
                     break;
                 }
+                Command::Config(ref args) => {
+                    if let Err(err) = self.handle_config(args).await {
+                        CONSOLE.writeln(
+                            TitleFormat::failed("config")
+                                .error(err.to_string())
+                                .format(),
+                        )?;
+                    }
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
+                Command::Cost => {
+                    CONSOLE.writeln(Info::from(&self.state.cost).to_string())?;
+
+                    let prompt_input = Some((&self.state).into());
+                    input = self.console.prompt(prompt_input).await?;
+                }
                 Command::Models => {
-                    let models = if let Some(models) = self.models.as_ref() {
-                        models
-                    } else {
-                        let models = self.api.models().await?;
-                        self.models = Some(models);
-                        self.models.as_ref().unwrap()
-                    };
-                    let info: Info = models.as_slice().into();
+                    let models = self.get_models().await?;
+                    let info: Info = models.into();
                     CONSOLE.writeln(info.to_string())?;
 
                     input = self.console.prompt(None).await?;
@@ -132,51 +551,875 @@ impl<F: API> UI<F> {
             }
         }
 
-        Ok(())
+        self.end_session().await;
+        Ok(ExitCode::SUCCESS)
+    }
+
+    /// Runs the `session_end` hooks of the config, eg: a notification that
+    /// the session is over.
+    async fn end_session(&self) {
+        let payload = serde_json::json!({"conversation_id": self.state.conversation_id});
+        ForgeHookService::from_env(&self.api.environment())
+            .run(HookEvent::SessionEnd, None, payload)
+            .await;
+    }
+
+    /// Executes a single prompt, read from stdin when not provided, and
+    /// returns the exit code of the process.
+    async fn handle_run(&mut self, prompt: Option<String>, json: bool) -> Result<ExitCode> {
+        let prompt = match prompt {
+            Some(prompt) => prompt,
+            None if std::io::stdin().is_terminal() => String::new(),
+            None => {
+                let mut prompt = String::new();
+                tokio::io::stdin().read_to_string(&mut prompt).await?;
+                prompt
+            }
+        };
+        if prompt.trim().is_empty() {
+            eprintln!(
+                "{}",
+                TitleFormat::failed("run")
+                    .error("No prompt provided, pass it as an argument or through stdin")
+                    .format()
+            );
+            return Ok(ExitCode::from(EXIT_USAGE));
+        }
+
+        if json {
+            self.reporter = Some(JsonReporter::new(batch::take_stdout()?));
+        }
+
+        // A headless run can't answer an agent that's waiting for input
+        let error = self
+            .chat(prompt.trim().to_string())
+            .await
+            .err()
+            .map(|err| err.to_string())
+            .or_else(|| self.state.needs_user_input.take());
+        self.end_session().await;
+
+        match self.reporter.as_mut() {
+            Some(reporter) => reporter.finish(
+                error.clone(),
+                self.state.cost.usage(),
+                self.state.cost.total(),
+            )?,
+            None => {
+                if let Some(error) = &error {
+                    CONSOLE.writeln(TitleFormat::failed("run").error(error).format())?;
+                }
+            }
+        }
+
+        Ok(match error {
+            Some(_) => ExitCode::from(EXIT_FAILURE),
+            None => ExitCode::SUCCESS,
+        })
     }
 
     async fn chat(&mut self, content: String) -> Result<()> {
+        self.check_budget()?;
+
         let conversation_id = match self.state.conversation_id {
             Some(ref id) => id.clone(),
             None => {
-                let conversation_id = self
-                    .api
-                    .init(self.api.load(self.cli.workflow.as_deref()).await?)
-                    .await?;
+                let mut workflow = self.api.load(self.cli.workflow.as_deref()).await?;
+                self.config.apply(&mut workflow);
+                for agent in workflow.agents.iter_mut() {
+                    agent.parameters = agent.parameters.clone().merge(&self.parameters);
+                    if let Some(mode) = self.state.mode {
+                        agent.mode = mode;
+                    }
+                }
+                self.state.cost.agents(&workflow);
+                // Spend can't be priced without models, but chatting is still possible
+                if let Ok(models) = self.get_models().await.map(<[Model]>::to_vec) {
+                    self.state.cost.pricing(&models);
+                }
+
+                let conversation_id = self.api.init(workflow).await?;
                 self.state.conversation_id = Some(conversation_id.clone());
+                self.sync_pinned_files().await?;
 
                 conversation_id
             }
         };
 
-        let chat = ChatRequest { content: content.clone(), conversation_id };
+        self.api
+            .checkpoint(&conversation_id, RETRY_CHECKPOINT)
+            .await?;
+        self.state.journal.checkpoint(RETRY_CHECKPOINT);
+        self.state.last_message = Some(content.clone());
+        self.state.last_images = self.state.images.clone();
+
+        let chat = ChatRequest {
+            content: content.clone(),
+            conversation_id,
+            agent: self.state.agent.take(),
+            images: std::mem::take(&mut self.state.images),
+        };
 
         tokio::spawn(TRACKER.dispatch(EventKind::Prompt(content)));
-        match self.api.chat(chat).await {
+        if forge_tracker::stats_enabled() {
+            self.state.stats = Some(StatsRecorder::new(
+                &chat.conversation_id,
+                &self.api.environment().provider_url,
+                self.state.cost.total(),
+            ));
+        }
+        let result = match self.api.chat(chat).await {
             Ok(mut stream) => self.handle_chat_stream(&mut stream).await,
             Err(err) => Err(err),
+        };
+        self.save_stats();
+        if let (Ok(_), Some(narrator)) = (&result, self.narrator.as_mut()) {
+            narrator.finish();
         }
+        result
+    }
+
+    /// Appends the statistics of the last chat request to the local file.
+    fn save_stats(&mut self) {
+        let Some(recorder) = self.state.stats.take() else {
+            return;
+        };
+        let record = recorder.finish(self.state.cost.total());
+        if record.is_empty() {
+            return;
+        }
+        let store = StatsStore::new(self.api.environment().stats_path());
+        if let Err(err) = store.append(&record) {
+            tracing::debug!(error = ?err, "Failed to save the usage statistics");
+        }
+    }
+
+    fn handle_stats(&self, days: u32) -> Result<()> {
+        let store = StatsStore::new(self.api.environment().stats_path());
+        let records = store
+            .load()
+            .map_err(|err| anyhow::anyhow!("Failed to read the usage statistics: {err:?}"))?;
+        let since = chrono::Utc::now() - chrono::Duration::days(days.into());
+        let summary = StatsSummary::new(&records, since);
+
+        if summary.sessions == 0 {
+            let message = if forge_tracker::stats_enabled() {
+                format!("No usage recorded in the last {days} days")
+            } else {
+                "Usage statistics are disabled by FORGE_STATS=false".to_string()
+            };
+            CONSOLE.writeln(message)?;
+            return Ok(());
+        }
+        CONSOLE.write(stats::render(&summary, days))?;
+        Ok(())
+    }
+
+    fn handle_audit(&self, command: &AuditCommand) -> Result<ExitCode> {
+        let log = ToolAuditLog::new(self.api.environment().audit_path());
+        match command {
+            AuditCommand::Show { limit, diff } => {
+                let entries = log.load()?;
+                if entries.is_empty() {
+                    CONSOLE.writeln("No tool call recorded")?;
+                } else {
+                    let start = entries.len().saturating_sub(*limit);
+                    CONSOLE.write(audit::render(&entries[start..], *diff))?;
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            AuditCommand::Verify => match log.verify()? {
+                Ok(count) => {
+                    CONSOLE.writeln(
+                        TitleFormat::success("audit")
+                            .sub_title(format!("{count} entries verified"))
+                            .format(),
+                    )?;
+                    Ok(ExitCode::SUCCESS)
+                }
+                Err(violation) => {
+                    CONSOLE.writeln(
+                        TitleFormat::failed("audit")
+                            .error(format!("The audit log was tampered with at {violation}"))
+                            .format(),
+                    )?;
+                    Ok(ExitCode::from(EXIT_FAILURE))
+                }
+            },
+        }
+    }
+
+    async fn handle_doctor(&self) -> Result<ExitCode> {
+        let checks = doctor::run(self.api.as_ref(), self.config.restricted == Some(true)).await;
+        CONSOLE.write(doctor::render(&checks))?;
+        Ok(if checks.iter().all(|check| check.passed) {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::from(EXIT_FAILURE)
+        })
     }
 
     async fn handle_chat_stream(
         &mut self,
         stream: &mut (impl StreamExt<Item = Result<AgentMessage<ChatResponse>>> + Unpin),
     ) -> Result<()> {
-        loop {
+        // note: the lines are wrapped by the screen, and otherwise the width is
+        // read for every message, as the terminal may have been resized.
+        self.markdown = if self.cli.plain {
+            None
+        } else if self.console.has_screen() {
+            Some(MarkdownFormat::new(usize::MAX))
+        } else {
+            std::io::stdout().is_terminal().then(|| {
+                MarkdownFormat::new(
+                    crossterm::terminal::size().map_or(80, |(width, _)| width as usize),
+                )
+            })
+        };
+        let result = loop {
             tokio::select! {
                 _ = tokio::signal::ctrl_c() => {
-                    return Ok(());
+                    if let Some(conversation_id) = &self.state.conversation_id {
+                        self.api.cancel(conversation_id);
+                    }
+                    break Ok(());
                 }
                 maybe_message = stream.next() => {
                     match maybe_message {
-                        Some(Ok(message)) => self.handle_chat_response(message)?,
+                        Some(Ok(message)) => {
+                            let question = match &message.message {
+                                ChatResponse::Question(question) => Some(question.clone()),
+                                _ => None,
+                            };
+                            if let Err(err) = self
+                                .handle_chat_response(message)
+                                .and_then(|_| self.check_budget())
+                            {
+                                break Err(err);
+                            }
+                            if let Some(question) = question {
+                                if let Err(err) = self.answer(&question).await {
+                                    break Err(err);
+                                }
+                            }
+                        }
                         Some(Err(err)) => {
-                            return Err(err);
+                            break Err(err);
                         }
-                        None => return Ok(()),
+                        None => break Ok(()),
+                    }
+                }
+            }
+        };
+        self.flush_markdown()?;
+        result
+    }
+
+    /// Asks the user the question of an agent, which waits for the answer.
+    /// Without a user to ask, eg: in `forge run`, the agent is told to carry on
+    /// on its own.
+    async fn answer(&mut self, question: &Question) -> Result<()> {
+        let conversation_id = self
+            .state
+            .conversation_id
+            .clone()
+            .context("No conversation to answer the question of")?;
+        let answer = if self.reporter.is_some() || !std::io::stdin().is_terminal() {
+            None
+        } else {
+            self.console.ask(Some((&self.state).into())).await?
+        };
+        let answer = match answer {
+            Some(text) => question.answer(&text),
+            None => NO_ANSWER.to_string(),
+        };
+        self.api.answer(&conversation_id, answer)
+    }
+
+    /// Writes the rest of the streamed markdown, before anything else is
+    /// written to the console.
+    fn flush_markdown(&mut self) -> Result<()> {
+        if let Some(markdown) = self.markdown.as_mut() {
+            CONSOLE.write(markdown.finish())?;
+        }
+        Ok(())
+    }
+
+    async fn handle_config(&mut self, args: &str) -> Result<()> {
+        match args.parse()? {
+            ConfigCommand::Show => {}
+            ConfigCommand::Set(parameters) => {
+                self.parameters = self.parameters.clone().merge(&parameters);
+                if let Some(conversation_id) = &self.state.conversation_id {
+                    self.api
+                        .set_parameters(conversation_id, &parameters)
+                        .await?;
+                }
+            }
+        }
+        CONSOLE.writeln(Info::from(&self.parameters).to_string())?;
+        Ok(())
+    }
+
+    /// Runs the triggers of the workflow until interrupted, each run in a new
+    /// conversation.
+    async fn handle_watch(&mut self, names: &[String]) -> Result<()> {
+        let env = self.api.environment();
+        let workflow = self.api.load(self.cli.workflow.as_deref()).await?;
+        let triggers = Triggers::new(env.cwd.clone(), workflow.triggers, names)?;
+        let mut watcher = if triggers.has_paths() {
+            Some(FileWatcher::new(&env.cwd)?)
+        } else {
+            None
+        };
+
+        CONSOLE.writeln(
+            TitleFormat::execute("watch")
+                .sub_title(format!("path: {}", env.cwd.display()))
+                .format(),
+        )?;
+
+        loop {
+            let scheduled = triggers.scheduled(chrono::Local::now());
+            let next_run = async {
+                match &scheduled {
+                    Some((time, _)) => {
+                        let delay = (*time - chrono::Local::now()).to_std().unwrap_or_default();
+                        tokio::time::sleep(delay).await
                     }
+                    None => std::future::pending().await,
                 }
+            };
+            let changes = async {
+                match watcher.as_mut() {
+                    Some(watcher) => watcher.next().await,
+                    None => std::future::pending().await,
+                }
+            };
+
+            let runs = tokio::select! {
+                _ = tokio::signal::ctrl_c() => return Ok(()),
+                paths = changes => triggers.changed(&paths),
+                _ = next_run => scheduled.clone().map(|(_, runs)| runs).unwrap_or_default(),
+            };
+
+            for TriggerRun { name, prompt } in runs {
+                self.state = Default::default();
+                CONSOLE.writeln(TitleFormat::execute(format!("trigger {name}")).format())?;
+                match self.chat(prompt).await {
+                    Ok(()) => CONSOLE.writeln(
+                        TitleFormat::success(format!("trigger {name}"))
+                            .sub_title(self.state.usage.to_string())
+                            .format(),
+                    )?,
+                    Err(err) => CONSOLE.writeln(
+                        TitleFormat::failed(format!("trigger {name}"))
+                            .error(err.to_string())
+                            .format(),
+                    )?,
+                }
+            }
+
+            // Changes made by the triggered runs must not trigger them again
+            if let Some(watcher) = watcher.as_mut() {
+                watcher.drain();
+            }
+        }
+    }
+
+    async fn handle_checkpoint(&mut self, name: &str) -> Result<()> {
+        if name.is_empty() {
+            anyhow::bail!("Usage: /checkpoint <name>");
+        }
+        if name == RETRY_CHECKPOINT {
+            anyhow::bail!("The checkpoint name `{RETRY_CHECKPOINT}` is reserved for /retry");
+        }
+        let conversation_id = self
+            .state
+            .conversation_id
+            .clone()
+            .context("No conversation to checkpoint yet")?;
+
+        self.api.checkpoint(&conversation_id, name).await?;
+        self.state.journal.checkpoint(name);
+
+        CONSOLE.writeln(
+            TitleFormat::success("checkpoint")
+                .sub_title(format!("name: {name}"))
+                .format(),
+        )?;
+        Ok(())
+    }
+
+    async fn handle_branch(&mut self, name: &str) -> Result<()> {
+        if name.is_empty() {
+            anyhow::bail!("Usage: /branch <checkpoint>");
+        }
+        let conversation_id = self
+            .state
+            .conversation_id
+            .clone()
+            .context("No conversation to branch yet")?;
+
+        self.api.branch(&conversation_id, name).await?;
+        let restored = self.state.journal.revert(name)?;
+
+        CONSOLE.writeln(
+            TitleFormat::success("branch")
+                .sub_title(format!(
+                    "checkpoint: {name}, restored files: {}",
+                    restored.len()
+                ))
+                .format(),
+        )?;
+        for path in restored {
+            CONSOLE.writeln(format!("  {}", path.display()).dimmed().to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Workflow of the current conversation, or the one a new conversation
+    /// would be started with.
+    async fn workflow(&self) -> Result<Workflow> {
+        if let Some(conversation_id) = &self.state.conversation_id {
+            if let Some(conversation) = self.api.conversation(conversation_id).await? {
+                return Ok(conversation.workflow);
+            }
+        }
+        let mut workflow = self.api.load(self.cli.workflow.as_deref()).await?;
+        self.config.apply(&mut workflow);
+        Ok(workflow)
+    }
+
+    async fn handle_agent(&mut self, agent: &str) -> Result<()> {
+        if agent.is_empty() {
+            anyhow::bail!("Usage: /agent <id>, run /agents to list them");
+        }
+        let agent = AgentId::new(agent);
+        self.workflow().await?.get_agent(&agent)?;
+
+        CONSOLE.writeln(
+            TitleFormat::success("agent")
+                .sub_title(format!("the next message is sent to {agent}"))
+                .format(),
+        )?;
+        self.state.agent = Some(agent);
+        Ok(())
+    }
+
+    /// Switches the agents to the mode for the next messages, along with the
+    /// ones of a new conversation.
+    async fn handle_mode(&mut self, mode: AgentMode) -> Result<()> {
+        if let Some(conversation_id) = &self.state.conversation_id {
+            self.api.set_mode(conversation_id, mode).await?;
+        }
+        self.state.mode = Some(mode);
+
+        let sub_title = match mode {
+            AgentMode::Plan => "the agents use read-only tools until their plan is approved",
+            AgentMode::Act => "the agents use all of their tools",
+        };
+        CONSOLE.writeln(
+            TitleFormat::success(mode.to_string())
+                .sub_title(sub_title)
+                .format(),
+        )?;
+        Ok(())
+    }
+
+    /// Asks the user to approve the plan, which switches the agents to carry
+    /// it out. Returns the message to send next: the go-ahead, or the changes
+    /// the user asks for instead.
+    async fn review_plan(&mut self, plan: &Plan) -> Result<Option<String>> {
+        if !std::io::stdin().is_terminal() {
+            return Ok(None);
+        }
+        CONSOLE.writeln(
+            TitleFormat::execute("plan")
+                .sub_title("type y to approve it, or what to change")
+                .format(),
+        )?;
+        let Some(answer) = self.console.ask(Some((&self.state).into())).await? else {
+            return Ok(None);
+        };
+        let answer = answer.trim();
+        if !matches!(answer.to_lowercase().as_str(), "y" | "yes") {
+            return Ok((!answer.is_empty()).then(|| answer.to_string()));
+        }
+
+        self.handle_mode(AgentMode::Act).await?;
+        Ok(Some(format!("The plan is approved, carry it out:\n{plan}")))
+    }
+
+    /// Commits the files changed by the agents during the session, once the
+    /// user approves the message written for them.
+    async fn handle_commit(&mut self) -> Result<()> {
+        let changes = self.state.journal.changes();
+        if changes.is_empty() {
+            anyhow::bail!("No changes to commit");
+        }
+        let conversation_id = self
+            .state
+            .conversation_id
+            .clone()
+            .context("No conversation yet")?;
+
+        let diff = changes
+            .iter()
+            .map(|(_, diff)| diff.as_str())
+            .collect::<String>();
+        let chat = ChatRequest {
+            content: diff.chars().take(COMMIT_DIFF_LIMIT).collect(),
+            conversation_id,
+            agent: Some(AgentId::new(COMMIT_MESSAGE_AGENT)),
+            images: Vec::new(),
+        };
+        let mut stream = self.api.chat(chat).await.with_context(|| {
+            format!("The workflow needs a {COMMIT_MESSAGE_AGENT} agent to write the message")
+        })?;
+        let mut text = String::new();
+        while let Some(message) = stream.next().await {
+            let message = message?;
+            match message.message {
+                ChatResponse::Text(chunk) => text.push_str(&chunk),
+                ChatResponse::Usage(usage) => self.state.cost.record(&message.agent, &usage),
+                _ => {}
+            }
+        }
+        let mut message = git::commit_message(&text);
+        if message.is_empty() {
+            anyhow::bail!("No commit message was written");
+        }
+
+        let paths = changes
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        CONSOLE.writeln(format!("\n{message}\n"))?;
+        CONSOLE.writeln(
+            TitleFormat::execute("commit")
+                .sub_title(format!(
+                    "{} files, type y to commit, e to edit the message",
+                    paths.len()
+                ))
+                .format(),
+        )?;
+        let answer = self.console.ask(Some((&self.state).into())).await?;
+        match answer.as_deref().map(str::trim) {
+            Some("y" | "yes") => {}
+            Some("e") => message = git::commit_message(&self.console.compose(&message)?),
+            _ => {
+                CONSOLE.writeln(
+                    TitleFormat::success("commit")
+                        .sub_title("cancelled")
+                        .format(),
+                )?;
+                return Ok(());
+            }
+        }
+        if message.is_empty() {
+            anyhow::bail!("The commit message is empty");
+        }
+
+        let hash = git::commit(&self.api.environment().cwd, &paths, &message).await?;
+        self.state.journal.commit(&paths);
+        CONSOLE.writeln(
+            TitleFormat::success("commit")
+                .sub_title(format!(
+                    "{hash} {}",
+                    message.lines().next().unwrap_or_default()
+                ))
+                .format(),
+        )?;
+        Ok(())
+    }
+
+    async fn handle_context(&self, agent: &str) -> Result<()> {
+        if agent.is_empty() {
+            anyhow::bail!("Usage: /context <agent>");
+        }
+        let agent = AgentId::new(agent);
+        let conversation = match &self.state.conversation_id {
+            Some(conversation_id) => self.api.conversation(conversation_id).await?,
+            None => None,
+        }
+        .context("No conversation yet")?;
+        conversation.workflow.get_agent(&agent)?;
+        let context = conversation
+            .context(&agent)
+            .with_context(|| format!("{agent} hasn't run yet"))?;
+
+        CONSOLE.writeln(Info::from(context).to_string())?;
+        Ok(())
+    }
+
+    /// Switches the agents to the model for the next turns and saves it to
+    /// the config of the project. Without a model, lists the available ones
+    /// and fills the input with `/model ` to pick one with the completion.
+    async fn handle_model(&mut self, model: &str) -> Result<()> {
+        if model.is_empty() {
+            let info: Info = self.get_models().await?.into();
+            CONSOLE.writeln(info.to_string())?;
+            self.console.draft("/model ");
+            return Ok(());
+        }
+        // note: models can still be picked when the provider doesn't list them.
+        if let Ok(models) = self.get_models().await {
+            if !models.iter().any(|m| m.id.as_str() == model) {
+                anyhow::bail!("Unknown model `{model}`, run /models to list them");
+            }
+        }
+
+        let model = ModelId::new(model);
+        self.config.model = Some(model.clone());
+        if let Some(conversation_id) = &self.state.conversation_id {
+            self.api.set_model(conversation_id, &model).await?;
+            if let Some(conversation) = self.api.conversation(conversation_id).await? {
+                self.state.cost.agents(&conversation.workflow);
+            }
+        }
+        let path = forge_api::config::project_path(&self.api.environment().cwd);
+        forge_api::config::set(&path, "model", model.as_str())?;
+        self.console.model(model.as_str());
+
+        CONSOLE.writeln(
+            TitleFormat::success("model")
+                .sub_title(format!("{model}, saved to {}", path.display()))
+                .format(),
+        )?;
+        Ok(())
+    }
+
+    /// Rolls the conversation and the files changed by tools back to right
+    /// before the last message, returning it to be sent again.
+    async fn handle_retry(&mut self) -> Result<String> {
+        let content = self
+            .state
+            .last_message
+            .clone()
+            .context("No message to retry yet")?;
+        let conversation_id = self
+            .state
+            .conversation_id
+            .clone()
+            .context("No conversation to retry yet")?;
+
+        self.api
+            .branch(&conversation_id, RETRY_CHECKPOINT)
+            .await
+            .context("The last message was rolled back by /branch")?;
+        let restored = self.state.journal.revert(RETRY_CHECKPOINT)?;
+
+        CONSOLE.writeln(
+            TitleFormat::success("retry")
+                .sub_title(format!("restored files: {}", restored.len()))
+                .format(),
+        )?;
+        for path in restored {
+            CONSOLE.writeln(format!("  {}", path.display()).dimmed().to_string())?;
+        }
+        CONSOLE.writeln(&content)?;
+        self.state.images = self.state.last_images.clone();
+        Ok(content)
+    }
+
+    /// Fills the input with the text on the clipboard, or attaches the image
+    /// on it to the next message.
+    fn handle_paste(&mut self) -> Result<()> {
+        match crate::clipboard::paste()? {
+            Pasted::Text(text) => self.console.draft(text),
+            Pasted::Image { image, width, height } => {
+                self.state.images.push(image);
+                CONSOLE.writeln(
+                    TitleFormat::success("paste")
+                        .sub_title(format!(
+                            "{width}x{height} image attached to the next message, images: {}",
+                            self.state.images.len()
+                        ))
+                        .format(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Pins a file to the context of the agents, or lists the pinned files
+    /// without a path.
+    async fn handle_pin(&mut self, path: &str) -> Result<()> {
+        if path.is_empty() {
+            let files = &self.state.pinned_files;
+            if files.is_empty() {
+                CONSOLE.writeln("No file is pinned, pin one with /pin <path>")?;
+            }
+            for path in files {
+                CONSOLE.writeln(path.display().to_string())?;
             }
+            return Ok(());
+        }
+        let path = self.api.environment().cwd.join(path);
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("Failed to pin {}", path.display()))?;
+        anyhow::ensure!(path.is_file(), "{} isn't a file", path.display());
+        self.state.pinned_files.insert(path.clone());
+        self.sync_pinned_files().await?;
+        CONSOLE.writeln(
+            TitleFormat::success("pin")
+                .sub_title(format!(
+                    "{}, pinned files: {}",
+                    path.display(),
+                    self.state.pinned_files.len()
+                ))
+                .format(),
+        )?;
+        Ok(())
+    }
+
+    /// Lists the sessions left with `/new`, or switches to the given one. The
+    /// session switched from takes its place in the list.
+    fn handle_switch(&mut self, session: &str) -> Result<()> {
+        if session.is_empty() {
+            if self.sessions.is_empty() {
+                CONSOLE.writeln("No other session, start one with /new")?;
+            }
+            for (index, session) in self.sessions.iter().enumerate() {
+                CONSOLE.writeln(format!(
+                    "{} {} {} ({}, ${:.4})",
+                    index + 1,
+                    session.current_title.as_deref().unwrap_or("untitled"),
+                    session
+                        .conversation_id
+                        .as_ref()
+                        .map(ConversationId::into_string)
+                        .unwrap_or_default(),
+                    session.usage,
+                    session.cost.total()
+                ))?;
+            }
+            return Ok(());
+        }
+        let index = session
+            .parse::<usize>()
+            .ok()
+            .filter(|index| (1..=self.sessions.len()).contains(index))
+            .with_context(|| format!("No session {session}, list them with /switch"))?;
+        std::mem::swap(&mut self.state, &mut self.sessions[index - 1]);
+        CONSOLE.writeln(
+            TitleFormat::success("switch")
+                .sub_title(format!(
+                    "{}, {}",
+                    self.state.current_title.as_deref().unwrap_or("untitled"),
+                    self.state.usage
+                ))
+                .format(),
+        )?;
+        Ok(())
+    }
+
+    /// Unpins the given file, or all of them without a path.
+    async fn handle_unpin(&mut self, path: &str) -> Result<()> {
+        if path.is_empty() {
+            self.state.pinned_files.clear();
+        } else {
+            let path = self.api.environment().cwd.join(path);
+            let path = path.canonicalize().unwrap_or(path);
+            anyhow::ensure!(
+                self.state.pinned_files.remove(&path),
+                "{} isn't pinned",
+                path.display()
+            );
+        }
+        self.sync_pinned_files().await?;
+        CONSOLE.writeln(
+            TitleFormat::success("unpin")
+                .sub_title(format!("pinned files: {}", self.state.pinned_files.len()))
+                .format(),
+        )?;
+        Ok(())
+    }
+
+    /// Gives the pinned files to the conversation, if it has started.
+    async fn sync_pinned_files(&self) -> Result<()> {
+        if let Some(conversation_id) = &self.state.conversation_id {
+            self.api
+                .set_pinned_files(conversation_id, self.state.pinned_files.clone())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Records the microphone until the user is done speaking and fills the
+    /// input with the transcript.
+    async fn handle_voice(&mut self) -> Result<()> {
+        let voice = self
+            .voice
+            .as_ref()
+            .context("Voice input is disabled, start forge with --voice")?;
+        CONSOLE.writeln(
+            TitleFormat::execute("voice")
+                .sub_title("listening, stops after a pause or on Ctrl+C")
+                .format(),
+        )?;
+        let recording = voice.record().await?.context("No speech was heard")?;
+        CONSOLE.writeln(
+            TitleFormat::execute("voice")
+                .sub_title("transcribing")
+                .format(),
+        )?;
+        let text = voice.transcribe(recording).await?;
+        anyhow::ensure!(!text.is_empty(), "No speech was recognized");
+        self.console.draft(text);
+        Ok(())
+    }
+
+    /// Runs the shell command of a custom command and renders its prompt.
+    /// Returns `None` when there is nothing to send to the agents.
+    async fn handle_custom(&self, command: &CustomCommand, args: &str) -> Result<Option<String>> {
+        let Some(run) = &command.run else {
+            return Ok(Some(command.render(args, "")));
+        };
+
+        let env = self.api.environment();
+        CONSOLE.writeln(TitleFormat::execute(run).format())?;
+        let restricted = env.config.restricted == Some(true);
+        let output = tokio::process::Command::new(&env.shell)
+            .args(Dialect::detect(&env.shell).args(run, restricted))
+            .current_dir(&env.cwd)
+            .output()
+            .await
+            .with_context(|| format!("Failed to run '{run}'"))?;
+
+        if command.only_on_failure && output.status.success() {
+            CONSOLE.writeln(TitleFormat::success(run).format())?;
+            return Ok(None);
+        }
+
+        let content = [output.stdout, output.stderr]
+            .iter()
+            .map(|stream| String::from_utf8_lossy(stream))
+            .filter(|stream| !stream.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(Some(command.render(args, content.trim_end())))
+    }
+
+    async fn get_models(&mut self) -> Result<&[Model]> {
+        if self.models.is_none() {
+            let models = self.api.models().await?;
+            self.console.models(models.clone());
+            self.models = Some(models);
+        }
+        Ok(self.models.as_deref().unwrap_or_default())
+    }
+
+    /// Fails once the spend of the conversation exceeds the configured budget
+    fn check_budget(&self) -> Result<()> {
+        match self.config.budget {
+            Some(budget) if self.state.cost.total() > budget => anyhow::bail!(
+                "Budget of ${budget:.2} exceeded (spent ${:.4}), start a new conversation with /new",
+                self.state.cost.total()
+            ),
+            _ => Ok(()),
         }
     }
 
@@ -184,16 +1427,7 @@ impl<F: API> UI<F> {
         if let Some(conversation_id) = self.state.conversation_id.clone() {
             let conversation = self.api.conversation(&conversation_id).await?;
             if let Some(conversation) = conversation {
-                let timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
-                let path = self
-                    .state
-                    .current_title
-                    .as_ref()
-                    .map_or(format!("{timestamp}"), |title| {
-                        format!("{timestamp}-{title}")
-                    });
-
-                let path = format!("{path}.json");
+                let path = self.default_path("json");
 
                 let content = serde_json::to_string_pretty(&conversation)?;
                 tokio::fs::write(path.as_str(), content).await?;
@@ -215,13 +1449,157 @@ impl<F: API> UI<F> {
         Ok(())
     }
 
+    /// Exports the conversation to a `.forgeconv` file, which
+    /// `/import-conversation` continues on another machine.
+    async fn handle_export_conversation(&mut self, path: &str) -> Result<()> {
+        let conversation_id = self
+            .state
+            .conversation_id
+            .clone()
+            .context("No conversation to export yet")?;
+        let conversation = self
+            .api
+            .conversation(&conversation_id)
+            .await?
+            .with_context(|| format!("Conversation {conversation_id} not found"))?;
+        let path = match path {
+            "" => PathBuf::from(self.default_path(ConversationFile::EXTENSION)),
+            path => PathBuf::from(path),
+        };
+
+        let content = ConversationFile::new(conversation).to_json()?;
+        tokio::fs::write(&path, content)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        CONSOLE.writeln(
+            TitleFormat::success("export-conversation")
+                .sub_title(format!("path: {}", path.display()))
+                .format(),
+        )?;
+        Ok(())
+    }
+
+    /// Continues the conversation of a `.forgeconv` file in a new session,
+    /// the current one being kept for `/switch`.
+    async fn handle_import_conversation(&mut self, path: &str) -> Result<()> {
+        anyhow::ensure!(!path.is_empty(), "Usage: /import-conversation <path>");
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read {path}"))?;
+        let file = ConversationFile::parse(&content)?;
+        let current_title = file
+            .conversation
+            .rfind_event("title")
+            .map(|event| event.value.clone());
+        let pinned_files = file.conversation.pinned_files.clone();
+        let conversation_id = self.api.import_conversation(file.conversation).await?;
+
+        let state = UIState {
+            conversation_id: Some(conversation_id.clone()),
+            current_title,
+            pinned_files,
+            ..Default::default()
+        };
+        let session = std::mem::replace(&mut self.state, state);
+        if session.conversation_id.is_some() {
+            self.sessions.push(session);
+        }
+        CONSOLE.writeln(
+            TitleFormat::success("import-conversation")
+                .sub_title(format!(
+                    "conversation_id: {conversation_id}, exported at {}",
+                    file.exported_at.format("%Y-%m-%d %H:%M:%S")
+                ))
+                .format(),
+        )?;
+        Ok(())
+    }
+
+    async fn handle_export(&mut self, args: &str) -> Result<()> {
+        let args: ExportArgs = args.parse()?;
+        let conversation_id = self
+            .state
+            .conversation_id
+            .clone()
+            .context("No conversation to export yet")?;
+        let conversation = self
+            .api
+            .conversation(&conversation_id)
+            .await?
+            .with_context(|| format!("Conversation {conversation_id} not found"))?;
+
+        let content =
+            TranscriptExporter::new(&conversation, self.state.cost.usage()).render(args.format)?;
+        let path = match args.path {
+            Some(path) => path.display().to_string(),
+            None => self.default_path(args.format.extension()),
+        };
+        tokio::fs::write(&path, content).await?;
+
+        CONSOLE.writeln(
+            TitleFormat::success("export")
+                .sub_title(format!("path: {path}"))
+                .format(),
+        )?;
+        Ok(())
+    }
+
+    /// File name derived from the current time and conversation title
+    fn default_path(&self, extension: &str) -> String {
+        let timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+        let path = self
+            .state
+            .current_title
+            .as_ref()
+            .map_or(format!("{timestamp}"), |title| {
+                format!("{timestamp}-{title}")
+            });
+
+        format!("{path}.{extension}")
+    }
+
     fn handle_chat_response(&mut self, message: AgentMessage<ChatResponse>) -> Result<()> {
+        if let ChatResponse::NeedsUserInput(reason) = &message.message {
+            self.state.needs_user_input = Some(reason.clone());
+        }
+        if let Some(stats) = self.state.stats.as_mut() {
+            stats.record(&message);
+        }
+        if let ChatResponse::AgentStarted { model, .. } = &message.message {
+            self.state.cost.started(&message.agent, model);
+        }
+
+        if let Some(reporter) = self.reporter.as_mut() {
+            if let ChatResponse::Usage(usage) = &message.message {
+                self.state.cost.record(&message.agent, usage);
+                self.state.usage = usage.clone();
+            }
+            return reporter.report(&message);
+        }
+        if let Some(narrator) = self.narrator.as_mut() {
+            narrator.record(&message);
+        }
+
+        if !matches!(
+            message.message,
+            ChatResponse::Text(_)
+                | ChatResponse::Custom(_)
+                | ChatResponse::Usage(_)
+                | ChatResponse::UsageEstimate(_)
+                | ChatResponse::AgentTurn(_)
+        ) {
+            self.flush_markdown()?;
+        }
+
         match message.message {
             ChatResponse::Text(text) => {
                 // Any agent that ends with "worker" is considered a worker agent.
                 // Worker agents don't print anything to the console.
                 if !message.agent.as_str().to_lowercase().ends_with("worker") {
-                    CONSOLE.write(&text)?;
+                    match self.markdown.as_mut() {
+                        Some(markdown) => CONSOLE.write(markdown.push(&text))?,
+                        None => CONSOLE.write(&text)?,
+                    }
                 }
             }
             ChatResponse::ToolCallStart(_) => {
@@ -229,13 +1607,27 @@ impl<F: API> UI<F> {
                 CONSOLE.newline()?;
             }
             ChatResponse::ToolCallEnd(tool_result) => {
+                self.state.journal.record(&tool_result.files);
+                let diffs = tool_result
+                    .files
+                    .iter()
+                    .filter(|_| !self.cli.no_diff)
+                    .map(|file| DiffFormat::applied(file.path.clone(), &file.diff))
+                    .collect::<Vec<_>>();
+
+                for diff in &diffs {
+                    CONSOLE.writeln(diff)?;
+                }
+
                 if !self.cli.verbose {
                     return Ok(());
                 }
 
                 let tool_name = tool_result.name.as_str();
 
-                CONSOLE.writeln(format!("{}", tool_result.content.dimmed()))?;
+                if diffs.is_empty() {
+                    CONSOLE.writeln(format!("{}", tool_result.content.dimmed()))?;
+                }
 
                 if tool_result.is_error {
                     CONSOLE.writeln(
@@ -251,13 +1643,94 @@ impl<F: API> UI<F> {
                     )?;
                 }
             }
+            ChatResponse::DiffPreview(preview) => {
+                CONSOLE.writeln(DiffFormat::preview(preview.path.into(), &preview.diff))?;
+            }
             ChatResponse::Custom(event) => {
                 if event.name == "title" {
                     self.state.current_title = Some(event.value);
+                    self.console.status((&self.state).into());
                 }
             }
             ChatResponse::Usage(u) => {
+                self.state.cost.record(&message.agent, &u);
                 self.state.usage = u;
+                self.console.status((&self.state).into());
+            }
+            ChatResponse::UsageEstimate(usage) => {
+                let cost =
+                    self.state.cost.total() + self.state.cost.estimate(&message.agent, &usage);
+                self.console.status(PromptInput::Update {
+                    title: self.state.current_title.clone(),
+                    usage: Some(usage),
+                    cost: Some(cost),
+                });
+            }
+            ChatResponse::Question(question) => {
+                CONSOLE.newline()?;
+                CONSOLE.writeln(TitleFormat::execute("question").format())?;
+                CONSOLE.writeln(&question.question)?;
+                for (i, option) in question.options.iter().enumerate() {
+                    CONSOLE.writeln(format!("  {}. {option}", i + 1))?;
+                }
+            }
+            ChatResponse::Plan(plan) => {
+                CONSOLE.newline()?;
+                CONSOLE.writeln(TitleFormat::success("plan").format())?;
+                CONSOLE.writeln(plan.to_string())?;
+                self.state.plan = Some(plan);
+            }
+            ChatResponse::NeedsUserInput(reason) => {
+                CONSOLE.newline()?;
+                CONSOLE.writeln(
+                    TitleFormat::failed("Stopped, waiting for your input")
+                        .error(reason)
+                        .format(),
+                )?;
+            }
+            ChatResponse::RateLimited(wait) => {
+                CONSOLE.writeln(
+                    TitleFormat::execute("Rate limited")
+                        .sub_title(format!("waiting {:.1}s", wait.as_secs_f64()))
+                        .format(),
+                )?;
+            }
+            ChatResponse::RelevantFiles(files) => {
+                let paths = files
+                    .iter()
+                    .map(|file| format!("{} (~{} tokens)", file.path, file.tokens))
+                    .collect::<Vec<_>>();
+                CONSOLE.writeln(
+                    TitleFormat::success("Auto context")
+                        .sub_title(paths.join(", "))
+                        .format(),
+                )?;
+            }
+            ChatResponse::Redacted(redactions) => {
+                let redactions = redactions
+                    .iter()
+                    .map(|redacted| match redacted.action {
+                        RedactionAction::Block => {
+                            format!("{} withheld ({})", redacted.source, redacted.kind)
+                        }
+                        _ => format!(
+                            "{} {} in {}",
+                            redacted.kind, redacted.replacement, redacted.source
+                        ),
+                    })
+                    .collect::<Vec<_>>();
+                CONSOLE.writeln(
+                    TitleFormat::execute("Redacted")
+                        .sub_title(redactions.join(", "))
+                        .format(),
+                )?;
+            }
+            ChatResponse::AgentStarted { .. }
+            | ChatResponse::AgentTurn(_)
+            | ChatResponse::AgentCompleted { .. } => {
+                if let Some(line) = self.state.agents.record(&message, self.cli.verbose) {
+                    CONSOLE.writeln(format!("{}", line.dimmed()))?;
+                }
             }
         }
         Ok(())
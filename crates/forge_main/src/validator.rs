@@ -0,0 +1,52 @@
+use reedline::{ValidationResult, Validator};
+
+const FENCE: &str = "```";
+
+/// Keeps the input open across multiple lines while a ``` code fence is left
+/// unclosed, so that code blocks can be typed without being submitted on the
+/// first Enter.
+pub struct FenceValidator;
+
+impl Validator for FenceValidator {
+    fn validate(&self, line: &str) -> ValidationResult {
+        let fences = line
+            .lines()
+            .filter(|line| line.trim_start().starts_with(FENCE))
+            .count();
+
+        if fences % 2 == 0 {
+            ValidationResult::Complete
+        } else {
+            ValidationResult::Incomplete
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_complete(line: &str) -> bool {
+        matches!(FenceValidator.validate(line), ValidationResult::Complete)
+    }
+
+    #[test]
+    fn test_plain_input_is_complete() {
+        assert!(is_complete("explain this error"));
+    }
+
+    #[test]
+    fn test_open_fence_is_incomplete() {
+        assert!(!is_complete("fix this\n```rust\nfn main() {}"));
+    }
+
+    #[test]
+    fn test_closed_fence_is_complete() {
+        assert!(is_complete("fix this\n```rust\nfn main() {}\n```"));
+    }
+
+    #[test]
+    fn test_inline_fence_is_ignored() {
+        assert!(is_complete("use ```code``` blocks"));
+    }
+}
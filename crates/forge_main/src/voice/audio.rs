@@ -0,0 +1,142 @@
+/// Sample rate whisper transcribes, in Hz
+pub const SAMPLE_RATE: u32 = 16_000;
+
+/// Level above which a chunk of the recording is considered speech
+const SPEECH_LEVEL: f32 = 0.02;
+
+/// Seconds of silence after the speech that end the recording
+const PAUSE_SECS: u32 = 3;
+
+/// Seconds waited for the speech to start
+const WAIT_SECS: u32 = 10;
+
+/// Longest recording, in seconds
+const MAX_SECS: u32 = 300;
+
+/// Detects when the user is done speaking, from the chunks of the recording
+/// as they come.
+pub struct Endpoint {
+    rate: usize,
+    heard: bool,
+    silent: usize,
+    total: usize,
+}
+
+impl Endpoint {
+    pub fn new(rate: u32) -> Self {
+        Self { rate: rate as usize, heard: false, silent: 0, total: 0 }
+    }
+
+    /// Adds the next chunk, returning whether the recording is over: after a
+    /// pause that follows the speech, when nothing is said for a while or at
+    /// the length limit.
+    pub fn push(&mut self, chunk: &[f32]) -> bool {
+        let level = (chunk.iter().map(|sample| sample * sample).sum::<f32>()
+            / chunk.len().max(1) as f32)
+            .sqrt();
+        if level > SPEECH_LEVEL {
+            self.heard = true;
+            self.silent = 0;
+        } else {
+            self.silent += chunk.len();
+        }
+        self.total += chunk.len();
+
+        let secs = |secs: u32| secs as usize * self.rate;
+        let silence = if self.heard { PAUSE_SECS } else { WAIT_SECS };
+        self.silent >= secs(silence) || self.total >= secs(MAX_SECS)
+    }
+
+    pub fn heard(&self) -> bool {
+        self.heard
+    }
+}
+
+/// Resamples mono samples to `SAMPLE_RATE` by linear interpolation, which is
+/// good enough for speech.
+pub fn resample(samples: &[f32], rate: u32) -> Vec<f32> {
+    if rate == SAMPLE_RATE || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let step = rate as f64 / SAMPLE_RATE as f64;
+    let len = (samples.len() as f64 / step) as usize;
+    (0..len)
+        .map(|index| {
+            let position = index as f64 * step;
+            let before = position as usize;
+            let after = (before + 1).min(samples.len() - 1);
+            let weight = (position - before as f64) as f32;
+            samples[before] * (1.0 - weight) + samples[after] * weight
+        })
+        .collect()
+}
+
+/// Encodes mono samples at `SAMPLE_RATE` as a 16-bit PCM WAV file.
+pub fn wav(samples: &[f32]) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, mono
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    // bytes per second, bytes per sample and bits per sample
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_endpoint_stops_after_pause() {
+        let mut endpoint = Endpoint::new(10);
+        let speech = [0.5; 10];
+        let silence = [0.0; 10];
+
+        let actual = [&speech, &silence, &silence, &silence].map(|chunk| endpoint.push(chunk));
+        assert_eq!(actual, [false, false, false, true]);
+        assert!(endpoint.heard());
+    }
+
+    #[test]
+    fn test_endpoint_gives_up_without_speech() {
+        let mut endpoint = Endpoint::new(10);
+        let actual = (0..WAIT_SECS)
+            .map(|_| endpoint.push(&[0.0; 10]))
+            .collect::<Vec<_>>();
+        assert_eq!(actual.iter().filter(|done| **done).count(), 1);
+        assert_eq!(actual.last(), Some(&true));
+        assert!(!endpoint.heard());
+    }
+
+    #[test]
+    fn test_resample() {
+        let samples = [0.0, 0.5, 1.0, 0.5];
+        let actual = resample(&samples, SAMPLE_RATE * 2);
+        assert_eq!(actual, vec![0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_wav() {
+        let actual = wav(&[0.0, 1.0, -1.0]);
+        assert_eq!(actual.len(), 44 + 6);
+        assert_eq!(&actual[..4], b"RIFF");
+        assert_eq!(&actual[8..16], b"WAVEfmt ");
+        assert_eq!(&actual[44..], &[0, 0, 0xff, 0x7f, 0x01, 0x80]);
+    }
+}
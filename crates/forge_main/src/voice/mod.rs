@@ -0,0 +1,55 @@
+//! Voice input for `--voice`: records the microphone and transcribes it into
+//! the prompt. Recording needs the `voice` feature, as it links the audio
+//! libraries of the OS and whisper.cpp.
+
+#[cfg(feature = "voice")]
+mod audio;
+#[cfg(feature = "voice")]
+mod recorder;
+#[cfg(feature = "voice")]
+mod transcriber;
+
+use std::path::Path;
+
+use forge_api::Environment;
+
+pub struct Voice {
+    #[cfg(feature = "voice")]
+    transcriber: transcriber::Transcriber,
+}
+
+impl Voice {
+    #[cfg(feature = "voice")]
+    pub fn new(env: &Environment, model: Option<&Path>) -> anyhow::Result<Self> {
+        Ok(Self { transcriber: transcriber::Transcriber::new(env, model)? })
+    }
+
+    #[cfg(not(feature = "voice"))]
+    pub fn new(_env: &Environment, _model: Option<&Path>) -> anyhow::Result<Self> {
+        anyhow::bail!(
+            "This build of forge has no voice input, install it with `cargo install --features voice`"
+        )
+    }
+
+    /// Records until the user is done speaking, or `None` when nothing was
+    /// said.
+    #[cfg(feature = "voice")]
+    pub async fn record(&self) -> anyhow::Result<Option<Vec<f32>>> {
+        recorder::record().await
+    }
+
+    #[cfg(feature = "voice")]
+    pub async fn transcribe(&self, recording: Vec<f32>) -> anyhow::Result<String> {
+        self.transcriber.transcribe(recording).await
+    }
+
+    #[cfg(not(feature = "voice"))]
+    pub async fn record(&self) -> anyhow::Result<Option<Vec<f32>>> {
+        Ok(None)
+    }
+
+    #[cfg(not(feature = "voice"))]
+    pub async fn transcribe(&self, _recording: Vec<f32>) -> anyhow::Result<String> {
+        Ok(String::new())
+    }
+}
@@ -0,0 +1,106 @@
+use std::sync::mpsc;
+use std::thread;
+
+use anyhow::Context;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample, Stream, StreamConfig};
+use tokio::sync::{mpsc as chunks, oneshot};
+
+use super::audio::{self, Endpoint};
+
+/// Records the default microphone until the user is done speaking or presses
+/// Ctrl+C, returning mono samples at `audio::SAMPLE_RATE`, or `None` when
+/// nothing was said.
+pub async fn record() -> anyhow::Result<Option<Vec<f32>>> {
+    let (sender, mut receiver) = chunks::unbounded_channel();
+    let (opened, open) = oneshot::channel();
+    let (stop, stopped) = mpsc::channel::<()>();
+
+    // The stream isn't `Send`, so it lives on its own thread until dropped
+    thread::spawn(move || match open_stream(sender) {
+        Ok((stream, rate)) => {
+            let _ = opened.send(Ok(rate));
+            let _ = stopped.recv();
+            drop(stream);
+        }
+        Err(error) => {
+            let _ = opened.send(Err(error));
+        }
+    });
+    let rate = open.await.context("Failed to open the microphone")??;
+
+    let mut endpoint = Endpoint::new(rate);
+    let mut samples = Vec::new();
+    let interrupt = tokio::signal::ctrl_c();
+    tokio::pin!(interrupt);
+    loop {
+        tokio::select! {
+            _ = &mut interrupt => break,
+            chunk = receiver.recv() => {
+                let Some(chunk) = chunk else { break };
+                let done = endpoint.push(&chunk);
+                samples.extend(chunk);
+                if done {
+                    break;
+                }
+            }
+        }
+    }
+    drop(stop);
+
+    Ok(endpoint.heard().then(|| audio::resample(&samples, rate)))
+}
+
+fn open_stream(sender: chunks::UnboundedSender<Vec<f32>>) -> anyhow::Result<(Stream, u32)> {
+    let device = cpal::default_host()
+        .default_input_device()
+        .context("No microphone found")?;
+    let config = device
+        .default_input_config()
+        .context("Failed to read the microphone configuration")?;
+    let format = config.sample_format();
+    let config: StreamConfig = config.into();
+    let stream = match format {
+        SampleFormat::F32 => build::<f32>(&device, &config, sender),
+        SampleFormat::I16 => build::<i16>(&device, &config, sender),
+        SampleFormat::U16 => build::<u16>(&device, &config, sender),
+        SampleFormat::I32 => build::<i32>(&device, &config, sender),
+        format => anyhow::bail!("Unsupported microphone sample format {format}"),
+    }?;
+    stream.play().context("Failed to start recording")?;
+    Ok((stream, config.sample_rate.0))
+}
+
+/// Builds a stream that mixes the channels down to mono `f32` chunks.
+fn build<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    sender: chunks::UnboundedSender<Vec<f32>>,
+) -> anyhow::Result<Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels as usize;
+    let stream = device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                let chunk = data
+                    .chunks(channels)
+                    .map(|frame| {
+                        frame
+                            .iter()
+                            .map(|sample| f32::from_sample(*sample))
+                            .sum::<f32>()
+                            / channels as f32
+                    })
+                    .collect();
+                let _ = sender.send(chunk);
+            },
+            |error| tracing::error!(error = %error, "Microphone recording failed"),
+            None,
+        )
+        .context("Failed to open the microphone")?;
+    Ok(stream)
+}
@@ -0,0 +1,95 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Context;
+use forge_api::Environment;
+use reqwest::multipart::{Form, Part};
+use serde::Deserialize;
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+use super::audio;
+
+/// Turns recordings into text, locally with whisper.cpp when a model is given
+/// and with the OpenAI API otherwise.
+pub enum Transcriber {
+    Local(Arc<WhisperContext>),
+    Api {
+        client: reqwest::Client,
+        key: String,
+    },
+}
+
+#[derive(Deserialize)]
+struct Transcription {
+    text: String,
+}
+
+impl Transcriber {
+    pub fn new(env: &Environment, model: Option<&Path>) -> anyhow::Result<Self> {
+        if let Some(model) = model {
+            let path = model
+                .to_str()
+                .context("The whisper model path isn't UTF-8")?;
+            let context =
+                WhisperContext::new_with_params(path, WhisperContextParameters::default())
+                    .with_context(|| {
+                        format!("Failed to load the whisper model {}", model.display())
+                    })?;
+            return Ok(Self::Local(Arc::new(context)));
+        }
+        let key = env.openai_key.clone().context(
+            "Voice input needs a whisper model, given with --whisper-model, or OPENAI_API_KEY to transcribe with the OpenAI API",
+        )?;
+        Ok(Self::Api { client: reqwest::Client::new(), key })
+    }
+
+    /// Transcribes mono samples at `audio::SAMPLE_RATE`.
+    pub async fn transcribe(&self, samples: Vec<f32>) -> anyhow::Result<String> {
+        let text = match self {
+            Self::Local(context) => {
+                let context = context.clone();
+                tokio::task::spawn_blocking(move || local(&context, &samples)).await??
+            }
+            Self::Api { client, key } => api(client, key, &samples).await?,
+        };
+        Ok(text.trim().to_string())
+    }
+}
+
+fn local(context: &WhisperContext, samples: &[f32]) -> anyhow::Result<String> {
+    let mut state = context.create_state().context("Failed to start whisper")?;
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_language(Some("auto"));
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_special(false);
+    params.set_print_timestamps(false);
+    state
+        .full(params, samples)
+        .context("Failed to transcribe the recording")?;
+
+    let mut text = String::new();
+    for segment in 0..state.full_n_segments()? {
+        text.push_str(&state.full_get_segment_text(segment)?);
+    }
+    Ok(text)
+}
+
+async fn api(client: &reqwest::Client, key: &str, samples: &[f32]) -> anyhow::Result<String> {
+    let file = Part::bytes(audio::wav(samples))
+        .file_name("voice.wav")
+        .mime_str("audio/wav")?;
+    let form = Form::new().text("model", "whisper-1").part("file", file);
+    let transcription: Transcription = client
+        .post("https://api.openai.com/v1/audio/transcriptions")
+        .bearer_auth(key)
+        .multipart(form)
+        .send()
+        .await
+        .context("Failed to send the recording to OpenAI")?
+        .error_for_status()?
+        .json()
+        .await
+        .context("Failed to parse the OpenAI transcription")?;
+    Ok(transcription.text)
+}
@@ -0,0 +1,286 @@
+//! Matches file changes and cron schedules against the triggers of the
+//! workflow for `forge watch`.
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::{DateTime, Local};
+use cron::Schedule;
+use forge_api::Trigger;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+/// Time to wait for further changes before running the triggers, so that a
+/// burst of writes results in a single run.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// A run of the workflow requested by a trigger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriggerRun {
+    pub name: String,
+    pub prompt: String,
+}
+
+struct CompiledTrigger {
+    trigger: Trigger,
+    paths: GlobSet,
+    schedule: Option<Schedule>,
+}
+
+impl CompiledTrigger {
+    fn run(&self, files: &[String]) -> TriggerRun {
+        TriggerRun {
+            name: self.trigger.name.clone(),
+            prompt: self.trigger.render(files),
+        }
+    }
+}
+
+/// The triggers selected for a `forge watch` session.
+pub struct Triggers {
+    cwd: PathBuf,
+    triggers: Vec<CompiledTrigger>,
+}
+
+impl Triggers {
+    /// Compiles the glob patterns and schedules of the triggers, keeping only
+    /// the ones named in `names` unless it's empty.
+    pub fn new(cwd: PathBuf, triggers: Vec<Trigger>, names: &[String]) -> anyhow::Result<Self> {
+        if let Some(name) = names
+            .iter()
+            .find(|name| !triggers.iter().any(|trigger| &trigger.name == *name))
+        {
+            anyhow::bail!("Trigger '{name}' is not defined in the workflow");
+        }
+
+        let triggers = triggers
+            .into_iter()
+            .filter(|trigger| names.is_empty() || names.contains(&trigger.name))
+            .map(|trigger| {
+                let mut paths = GlobSetBuilder::new();
+                for pattern in &trigger.paths {
+                    paths.add(Glob::new(pattern).with_context(|| {
+                        format!("Invalid pattern '{pattern}' of trigger '{}'", trigger.name)
+                    })?);
+                }
+                let schedule = trigger
+                    .schedule
+                    .as_deref()
+                    .map(|schedule| {
+                        Schedule::from_str(schedule).with_context(|| {
+                            format!(
+                                "Invalid schedule '{schedule}' of trigger '{}'",
+                                trigger.name
+                            )
+                        })
+                    })
+                    .transpose()?;
+                Ok(CompiledTrigger { paths: paths.build()?, schedule, trigger })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        if triggers.is_empty() {
+            anyhow::bail!("No triggers defined in the workflow");
+        }
+        Ok(Self { cwd, triggers })
+    }
+
+    /// Returns a run of every trigger matching one of the changed paths.
+    pub fn changed(&self, paths: &[PathBuf]) -> Vec<TriggerRun> {
+        let mut files = paths
+            .iter()
+            .filter_map(|path| path.strip_prefix(&self.cwd).ok())
+            .collect::<Vec<_>>();
+        files.sort();
+        files.dedup();
+
+        self.triggers
+            .iter()
+            .filter_map(|compiled| {
+                let matched = files
+                    .iter()
+                    .filter(|file| compiled.paths.is_match(file))
+                    .map(|file| file.display().to_string())
+                    .collect::<Vec<_>>();
+                (!matched.is_empty()).then(|| compiled.run(&matched))
+            })
+            .collect()
+    }
+
+    /// Returns the time of the next scheduled run after `now` along with a run
+    /// of every trigger scheduled at that time.
+    pub fn scheduled(&self, now: DateTime<Local>) -> Option<(DateTime<Local>, Vec<TriggerRun>)> {
+        let upcoming = self
+            .triggers
+            .iter()
+            .filter_map(|compiled| {
+                let next = compiled.schedule.as_ref()?.after(&now).next()?;
+                Some((next, compiled))
+            })
+            .collect::<Vec<_>>();
+        let next = upcoming.iter().map(|(next, _)| *next).min()?;
+
+        let triggers = upcoming
+            .into_iter()
+            .filter(|(time, _)| *time == next)
+            .map(|(_, compiled)| compiled.run(&[]))
+            .collect();
+        Some((next, triggers))
+    }
+
+    /// Whether any of the triggers watches files.
+    pub fn has_paths(&self) -> bool {
+        self.triggers
+            .iter()
+            .any(|compiled| !compiled.trigger.paths.is_empty())
+    }
+}
+
+/// Recursively watches a directory and reports the changed paths in batches.
+pub struct FileWatcher {
+    // Stops watching once dropped
+    _watcher: RecommendedWatcher,
+    receiver: mpsc::UnboundedReceiver<PathBuf>,
+}
+
+impl FileWatcher {
+    pub fn new(dir: &Path) -> anyhow::Result<Self> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event {
+                    if !event.kind.is_access() {
+                        for path in event.paths {
+                            let _ = sender.send(path);
+                        }
+                    }
+                }
+            })?;
+        watcher
+            .watch(dir, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch {}", dir.display()))?;
+        Ok(Self { _watcher: watcher, receiver })
+    }
+
+    /// Waits for the next change and returns it along with the ones that
+    /// follow it within the debounce period.
+    pub async fn next(&mut self) -> Vec<PathBuf> {
+        let Some(path) = self.receiver.recv().await else {
+            return std::future::pending().await;
+        };
+        tokio::time::sleep(DEBOUNCE).await;
+
+        let mut paths = vec![path];
+        paths.extend(self.drain());
+        paths
+    }
+
+    /// Discards the pending changes, eg: the ones made by a triggered run.
+    pub fn drain(&mut self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        while let Ok(path) = self.receiver.try_recv() {
+            paths.push(path);
+        }
+        paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn trigger(name: &str, paths: &[&str], schedule: Option<&str>) -> Trigger {
+        Trigger {
+            name: name.to_string(),
+            paths: paths.iter().map(|path| path.to_string()).collect(),
+            schedule: schedule.map(str::to_string),
+            prompt: format!("{name}: {{{{files}}}}"),
+        }
+    }
+
+    #[test]
+    fn test_changed_matches_globs() {
+        let fixture = Triggers::new(
+            PathBuf::from("/repo"),
+            vec![
+                trigger("rust", &["src/**/*.rs"], None),
+                trigger("docs", &["docs/*.md"], None),
+            ],
+            &[],
+        )
+        .unwrap();
+
+        let actual = fixture.changed(&[
+            PathBuf::from("/repo/src/a/b.rs"),
+            PathBuf::from("/repo/src/a/b.rs"),
+            PathBuf::from("/repo/README.md"),
+        ]);
+        let expected = vec![TriggerRun {
+            name: "rust".to_string(),
+            prompt: "rust: src/a/b.rs".to_string(),
+        }];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_scheduled() {
+        let fixture = Triggers::new(
+            PathBuf::from("/repo"),
+            vec![
+                trigger("hourly", &[], Some("0 0 * * * *")),
+                trigger("daily", &[], Some("0 0 9 * * *")),
+            ],
+            &[],
+        )
+        .unwrap();
+        let now = Local.with_ymd_and_hms(2025, 3, 1, 8, 30, 0).unwrap();
+
+        let actual = fixture.scheduled(now).unwrap();
+        let expected = (
+            Local.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap(),
+            vec![
+                TriggerRun { name: "hourly".to_string(), prompt: "hourly: ".to_string() },
+                TriggerRun { name: "daily".to_string(), prompt: "daily: ".to_string() },
+            ],
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_select_by_name() {
+        let fixture = Triggers::new(
+            PathBuf::from("/repo"),
+            vec![
+                trigger("rust", &["src/**/*.rs"], None),
+                trigger("docs", &["*.md"], None),
+            ],
+            &["docs".to_string()],
+        )
+        .unwrap();
+
+        let actual = fixture.changed(&[PathBuf::from("/repo/src/main.rs")]);
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_trigger() {
+        let actual = Triggers::new(PathBuf::from("/repo"), vec![], &["docs".to_string()]);
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_invalid_schedule() {
+        let actual = Triggers::new(
+            PathBuf::from("/repo"),
+            vec![trigger("bad", &[], Some("every hour"))],
+            &[],
+        );
+        assert!(actual.is_err());
+    }
+}
@@ -1,7 +1,8 @@
 use anyhow::Context as _;
 use derive_setters::Setters;
 use forge_domain::{
-    ChatCompletionMessage, Context, Model, ModelId, Parameters, ProviderService, ResultStream,
+    ChatCompletionMessage, Context, Model, ModelCapabilities, ModelId, ProviderService,
+    ResultStream,
 };
 use reqwest::header::{HeaderMap, HeaderValue};
 use reqwest::{Client, Url};
@@ -9,7 +10,7 @@ use reqwest_eventsource::{Event, RequestBuilderExt};
 use tokio_stream::StreamExt;
 
 use super::request::Request;
-use super::response::{EventData, ListModelResponse};
+use super::response::{Event as AnthropicEvent, EventData, ListModelResponse};
 
 #[derive(Debug, Default, Clone, Setters)]
 #[setters(into, strip_option)]
@@ -92,12 +93,9 @@ impl ProviderService for Anthropic {
         id: &ModelId,
         context: Context,
     ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
-        // TODO: depending on model, we've to set the max_tokens for request. for now,
-        // we're setting it to 4000.
         let request = Request::try_from(context)?
             .model(id.to_string())
-            .stream(true)
-            .max_tokens(4000u64);
+            .stream(true);
 
         let es = self
             .client
@@ -117,11 +115,7 @@ impl ProviderService for Anthropic {
                         }
                         Event::Message(_event) => Some(
                             serde_json::from_str::<EventData>(&_event.data)
-                                .with_context(|| "Failed to parse Anthronic event")
-                                .and_then(|event| {
-                                    ChatCompletionMessage::try_from(event)
-                                        .with_context(|| "Failed to create completion message")
-                                }),
+                                .with_context(|| "Failed to parse Anthronic event"),
                         ),
                     },
                     Err(reqwest_eventsource::Error::StreamEnded) => None,
@@ -129,7 +123,29 @@ impl ProviderService for Anthropic {
                 }
             });
 
-        Ok(Box::pin(stream.filter_map(|x| x)))
+        // note: the usage is split between the start of the message, which has the
+        // input tokens, and its end, which has the output tokens.
+        let mut start_usage = None;
+        let stream = stream.filter_map(|x| x).map(move |event| {
+            let event = match event? {
+                EventData::KnownEvent(AnthropicEvent::MessageStart { message }) => {
+                    start_usage = Some(message.usage.clone());
+                    EventData::KnownEvent(AnthropicEvent::MessageStart { message })
+                }
+                EventData::KnownEvent(AnthropicEvent::MessageDelta { delta, usage }) => {
+                    let usage = match start_usage.take() {
+                        Some(start) => usage.or(start),
+                        None => usage,
+                    };
+                    EventData::KnownEvent(AnthropicEvent::MessageDelta { delta, usage })
+                }
+                event => event,
+            };
+            ChatCompletionMessage::try_from(event)
+                .with_context(|| "Failed to create completion message")
+        });
+
+        Ok(Box::pin(stream))
     }
     async fn models(&self) -> anyhow::Result<Vec<Model>> {
         let text = self
@@ -145,10 +161,15 @@ impl ProviderService for Anthropic {
         let response: ListModelResponse = serde_json::from_str(&text)?;
         Ok(response.data.into_iter().map(Into::into).collect())
     }
-    async fn parameters(&self, _model: &ModelId) -> anyhow::Result<Parameters> {
-        // TODO: anthropic provider doesn't have this API, so for now allowing tool
-        // calls for all models.
-        Ok(Parameters { tool_supported: true })
+    async fn capabilities(&self, _model: &ModelId) -> anyhow::Result<ModelCapabilities> {
+        // note: the listing doesn't describe the models, all of which support tools
+        // and images. Structured output is described in the prompt instead.
+        Ok(ModelCapabilities {
+            supports_tools: true,
+            supports_vision: true,
+            supports_json_mode: false,
+            max_output_tokens: None,
+        })
     }
 }
 
@@ -189,6 +210,7 @@ mod tests {
                 call_id: Some(ToolCallId::new("math-1")),
                 content: serde_json::json!({"result": 4}).to_string(),
                 is_error: false,
+                files: Vec::new(),
             }])
             .tool_choice(ToolChoice::Call(ToolName::new("math")));
         let request = Request::try_from(context)
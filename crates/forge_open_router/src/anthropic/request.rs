@@ -14,8 +14,8 @@ pub struct Request {
     stop_sequence: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    system: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    system: Vec<Content>,
     #[serde(skip_serializing_if = "Option::is_none")]
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -28,6 +28,10 @@ pub struct Request {
     top_p: Option<f32>,
 }
 
+// TODO: depending on model, we've to set the max_tokens for request. for now,
+// we're setting it to 4000 unless configured.
+const DEFAULT_MAX_TOKENS: u64 = 4000;
+
 impl TryFrom<forge_domain::Context> for Request {
     type Error = anyhow::Error;
     fn try_from(request: forge_domain::Context) -> std::result::Result<Self, Self::Error> {
@@ -46,26 +50,62 @@ impl TryFrom<forge_domain::Context> for Request {
             }
         });
 
+        // note: Anthropic has no structured output mode, so the schema is
+        // described in the system prompt instead.
+        let system = match &request.output_schema {
+            Some(schema) => Some(format!(
+                "{}\n\nRespond with only a JSON document that conforms to this JSON schema:\n{}",
+                system.unwrap_or_default(),
+                schema
+            )),
+            None => system,
+        };
+
+        let parameters = request.parameters.clone();
+
+        let mut messages = request
+            .messages
+            .into_iter()
+            .filter(|message| {
+                // note: Anthropic does not support system messages in message field.
+                if let ContextMessage::ContentMessage(chat_message) = message {
+                    chat_message.role != forge_domain::Role::System
+                } else {
+                    true
+                }
+            })
+            .map(Message::try_from)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        // note: the cache breakpoints mark the end of the prefixes that are reused by
+        // the next request: the tools and the system prompt, which don't change in
+        // a conversation, and the conversation so far.
+        // ref: https://docs.anthropic.com/en/docs/build-with-claude/prompt-caching
+        if let Some(content) = messages
+            .last_mut()
+            .and_then(|message| message.content.last_mut())
+        {
+            content.cached();
+        }
+
         Ok(Self {
-            messages: request
-                .messages
-                .into_iter()
-                .filter(|message| {
-                    // note: Anthropic does not support system messages in message field.
-                    if let ContextMessage::ContentMessage(chat_message) = message {
-                        chat_message.role != forge_domain::Role::System
-                    } else {
-                        true
-                    }
-                })
-                .map(Message::try_from)
-                .collect::<std::result::Result<Vec<_>, _>>()?,
+            max_tokens: parameters
+                .max_tokens
+                .map(u64::from)
+                .unwrap_or(DEFAULT_MAX_TOKENS),
+            temperature: parameters.temperature,
+            top_p: parameters.top_p,
+            top_k: parameters.top_k.map(u64::from),
+            messages,
             tools: request
                 .tools
                 .into_iter()
                 .map(ToolDefinition::try_from)
                 .collect::<std::result::Result<Vec<_>, _>>()?,
-            system,
+            system: system
+                .map(|text| Content::Text { text, cache_control: Some(CacheControl::Ephemeral) })
+                .into_iter()
+                .collect(),
             tool_choice: request.tool_choice.map(ToolChoice::from),
             ..Default::default()
         })
@@ -98,6 +138,15 @@ impl TryFrom<ContextMessage> for Message {
                         + 1,
                 );
 
+                for image in chat_message.images {
+                    content.push(Content::Image {
+                        source: ImageSource::Base64 {
+                            media_type: image.mime_type,
+                            data: image.data,
+                        },
+                        cache_control: None,
+                    });
+                }
                 if !chat_message.content.is_empty() {
                     // note: Anthropic does not allow empty text content.
                     content.push(Content::Text { text: chat_message.content, cache_control: None });
@@ -132,6 +181,11 @@ enum Content {
         #[serde(skip_serializing_if = "Option::is_none")]
         cache_control: Option<CacheControl>,
     },
+    Image {
+        source: ImageSource,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControl>,
+    },
     ToolUse {
         id: String,
         input: Option<serde_json::Value>,
@@ -150,6 +204,22 @@ enum Content {
     },
 }
 
+impl Content {
+    fn cached(&mut self) {
+        let (Content::Text { cache_control, .. }
+        | Content::Image { cache_control, .. }
+        | Content::ToolUse { cache_control, .. }
+        | Content::ToolResult { cache_control, .. }) = self;
+        *cache_control = Some(CacheControl::Ephemeral);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+enum ImageSource {
+    Base64 { media_type: String, data: String },
+}
+
 impl TryFrom<forge_domain::ToolCallFull> for Content {
     type Error = anyhow::Error;
     fn try_from(value: forge_domain::ToolCallFull) -> std::result::Result<Self, Self::Error> {
@@ -184,8 +254,7 @@ impl TryFrom<forge_domain::ToolResult> for Content {
 }
 
 #[derive(Serialize)]
-#[serde(rename_all = "snake_case")]
-#[allow(dead_code)]
+#[serde(rename_all = "snake_case", tag = "type")]
 pub enum CacheControl {
     Ephemeral,
 }
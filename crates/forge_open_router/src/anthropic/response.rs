@@ -1,6 +1,8 @@
 use std::fmt::{self, Display, Formatter};
 
-use forge_domain::{ChatCompletionMessage, Content, ModelId, ToolCallId, ToolCallPart, ToolName};
+use forge_domain::{
+    ChatCompletionMessage, Content, ModelId, ToolCall, ToolCallId, ToolCallPart, ToolName,
+};
 use serde::Deserialize;
 
 use super::request::Role;
@@ -23,6 +25,8 @@ impl From<Model> for forge_domain::Model {
             name: value.display_name,
             description: None,
             context_length: None,
+            pricing: None,
+            capabilities: None,
         }
     }
 }
@@ -43,6 +47,42 @@ pub struct MessageStart {
 pub struct Usage {
     pub input_tokens: Option<u64>,
     pub output_tokens: Option<u64>,
+    pub cache_creation_input_tokens: Option<u64>,
+    pub cache_read_input_tokens: Option<u64>,
+}
+
+impl Usage {
+    /// Fills the counts missing from the usage of a `message_delta` event with
+    /// the ones reported when the message started.
+    pub fn or(self, start: Usage) -> Usage {
+        Usage {
+            input_tokens: self.input_tokens.or(start.input_tokens),
+            output_tokens: self.output_tokens.or(start.output_tokens),
+            cache_creation_input_tokens: self
+                .cache_creation_input_tokens
+                .or(start.cache_creation_input_tokens),
+            cache_read_input_tokens: self
+                .cache_read_input_tokens
+                .or(start.cache_read_input_tokens),
+        }
+    }
+}
+
+impl From<Usage> for forge_domain::Usage {
+    fn from(usage: Usage) -> Self {
+        // note: the input tokens exclude the ones written to and read from the cache.
+        let cached_tokens = usage.cache_read_input_tokens.unwrap_or_default();
+        let prompt_tokens = usage.input_tokens.unwrap_or_default()
+            + usage.cache_creation_input_tokens.unwrap_or_default()
+            + cached_tokens;
+        let completion_tokens = usage.output_tokens.unwrap_or_default();
+        forge_domain::Usage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            cached_tokens,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -159,12 +199,21 @@ impl TryFrom<Event> for ChatCompletionMessage {
     type Error = anyhow::Error;
     fn try_from(value: Event) -> Result<Self, Self::Error> {
         let result = match value {
-            Event::ContentBlockStart { content_block, .. }
-            | Event::ContentBlockDelta { delta: content_block, .. } => {
-                ChatCompletionMessage::try_from(content_block)?
+            Event::ContentBlockStart { index, content_block }
+            | Event::ContentBlockDelta { index, delta: content_block } => {
+                let mut message = ChatCompletionMessage::try_from(content_block)?;
+                // Parallel tool calls are streamed as separate content blocks
+                for tool_call in message.tool_call.iter_mut() {
+                    if let ToolCall::Part(part) = tool_call {
+                        part.index = index;
+                    }
+                }
+                message
             }
-            Event::MessageDelta { delta, .. } => {
-                ChatCompletionMessage::assistant(Content::part("")).finish_reason(delta.stop_reason)
+            Event::MessageDelta { delta, usage } => {
+                ChatCompletionMessage::assistant(Content::part(""))
+                    .finish_reason(delta.stop_reason)
+                    .usage(usage)
             }
             Event::Error { error } => {
                 return Err(anyhow::anyhow!("Anthropic API error: {}", error));
@@ -189,6 +238,7 @@ impl TryFrom<ContentBlock> for ChatCompletionMessage {
                 let is_empty =
                     input.is_null() || input.as_object().is_some_and(|map| map.is_empty());
                 ChatCompletionMessage::assistant(Content::part("")).add_tool_call(ToolCallPart {
+                    index: 0,
                     call_id: Some(ToolCallId::new(id)),
                     name: Some(ToolName::new(name)),
                     arguments_part: if is_empty {
@@ -200,6 +250,7 @@ impl TryFrom<ContentBlock> for ChatCompletionMessage {
             }
             ContentBlock::InputJsonDelta { partial_json } => {
                 ChatCompletionMessage::assistant(Content::part("")).add_tool_call(ToolCallPart {
+                    index: 0,
                     call_id: None,
                     name: None,
                     arguments_part: partial_json,
@@ -244,7 +295,12 @@ mod tests {
                         model: "claude-3-opus-20240229".to_string(),
                         stop_reason: None,
                         stop_sequence: None,
-                        usage: Usage { input_tokens: Some(10), output_tokens: Some(1) },
+                        usage: Usage {
+                            input_tokens: Some(10),
+                            output_tokens: Some(1),
+                            cache_creation_input_tokens: None,
+                            cache_read_input_tokens: None,
+                        },
                     },
                 },
             ),
@@ -283,7 +339,12 @@ mod tests {
                 r#"{"type":"message_delta","delta":{"stop_reason":"end_turn","stop_sequence":null},"usage":{"output_tokens":12}}"#,
                 Event::MessageDelta {
                     delta: MessageDelta { stop_reason: StopReason::EndTurn, stop_sequence: None },
-                    usage: Usage { input_tokens: None, output_tokens: Some(12) },
+                    usage: Usage {
+                        input_tokens: None,
+                        output_tokens: Some(12),
+                        cache_creation_input_tokens: None,
+                        cache_read_input_tokens: None,
+                    },
                 },
             ),
             (
@@ -323,4 +384,22 @@ mod tests {
         assert!(response.is_ok());
         assert!(response.unwrap().data.len() == 2);
     }
+
+    #[test]
+    fn test_usage_with_cache() {
+        let start: Usage = serde_json::from_str(
+            r#"{"input_tokens":20,"cache_creation_input_tokens":100,"cache_read_input_tokens":2000,"output_tokens":1}"#,
+        )
+        .unwrap();
+        let delta: Usage = serde_json::from_str(r#"{"output_tokens":300}"#).unwrap();
+
+        let actual = forge_domain::Usage::from(delta.or(start));
+        let expected = forge_domain::Usage {
+            prompt_tokens: 2120,
+            completion_tokens: 300,
+            total_tokens: 2420,
+            cached_tokens: 2000,
+        };
+        assert_eq!(actual, expected);
+    }
 }
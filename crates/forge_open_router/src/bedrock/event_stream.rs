@@ -0,0 +1,178 @@
+//! Decoder of the `application/vnd.amazon.eventstream` binary framing used by
+//! the streaming Bedrock APIs.
+//! ref: https://docs.aws.amazon.com/transcribe/latest/dg/streaming-setting-up.html#streaming-event-stream
+
+use std::collections::HashMap;
+
+use anyhow::{bail, Context as _};
+
+/// Total length, headers length and the CRC of both
+const PRELUDE_LEN: usize = 12;
+const CRC_LEN: usize = 4;
+
+/// Single decoded frame of the event stream.
+#[derive(Debug, PartialEq)]
+pub struct Frame {
+    /// Headers with a string value, eg: `:event-type`, other values are skipped
+    pub headers: HashMap<String, String>,
+    pub payload: Vec<u8>,
+}
+
+impl Frame {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+/// Buffers the chunks of the response body and yields the frames once they're
+/// complete.
+#[derive(Debug, Default)]
+pub struct EventStreamDecoder {
+    buffer: Vec<u8>,
+}
+
+impl EventStreamDecoder {
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Returns the next complete frame, or `None` if more data is needed.
+    pub fn next_frame(&mut self) -> anyhow::Result<Option<Frame>> {
+        if self.buffer.len() < PRELUDE_LEN {
+            return Ok(None);
+        }
+
+        let total_len = read_u32(&self.buffer[0..4]) as usize;
+        let headers_len = read_u32(&self.buffer[4..8]) as usize;
+        if crc32fast::hash(&self.buffer[0..8]) != read_u32(&self.buffer[8..12]) {
+            bail!("Event stream prelude checksum mismatch");
+        }
+        if total_len < PRELUDE_LEN + headers_len + CRC_LEN {
+            bail!("Invalid event stream frame length: {total_len}");
+        }
+        if self.buffer.len() < total_len {
+            return Ok(None);
+        }
+
+        let frame = self.buffer.drain(..total_len).collect::<Vec<_>>();
+        let (message, crc) = frame.split_at(total_len - CRC_LEN);
+        if crc32fast::hash(message) != read_u32(crc) {
+            bail!("Event stream message checksum mismatch");
+        }
+
+        let headers = parse_headers(&message[PRELUDE_LEN..PRELUDE_LEN + headers_len])?;
+        let payload = message[PRELUDE_LEN + headers_len..].to_vec();
+        Ok(Some(Frame { headers, payload }))
+    }
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn parse_headers(mut bytes: &[u8]) -> anyhow::Result<HashMap<String, String>> {
+    let mut headers = HashMap::new();
+    while !bytes.is_empty() {
+        let name_len = bytes[0] as usize;
+        let name = bytes
+            .get(1..1 + name_len)
+            .context("Truncated event stream header name")?;
+        let name = String::from_utf8(name.to_vec())?;
+        let value_type = *bytes
+            .get(1 + name_len)
+            .context("Truncated event stream header")?;
+        bytes = &bytes[2 + name_len..];
+
+        let value_len = match value_type {
+            // bool true and false have no value
+            0 | 1 => 0,
+            // byte, short, integer and long
+            2 => 1,
+            3 => 2,
+            4 => 4,
+            5 => 8,
+            // byte array and string, prefixed with their length
+            6 | 7 => {
+                let len = bytes
+                    .get(0..2)
+                    .context("Truncated event stream header value")?;
+                2 + u16::from_be_bytes([len[0], len[1]]) as usize
+            }
+            // timestamp
+            8 => 8,
+            // uuid
+            9 => 16,
+            _ => bail!("Unknown event stream header type: {value_type}"),
+        };
+        let value = bytes
+            .get(..value_len)
+            .context("Truncated event stream header value")?;
+        if value_type == 7 {
+            headers.insert(name, String::from_utf8(value[2..].to_vec())?);
+        }
+        bytes = &bytes[value_len..];
+    }
+    Ok(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn encode(headers: &[(&str, &str)], payload: &[u8]) -> Vec<u8> {
+        let mut encoded_headers = Vec::new();
+        for (name, value) in headers {
+            encoded_headers.push(name.len() as u8);
+            encoded_headers.extend_from_slice(name.as_bytes());
+            encoded_headers.push(7);
+            encoded_headers.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            encoded_headers.extend_from_slice(value.as_bytes());
+        }
+        let total_len = PRELUDE_LEN + encoded_headers.len() + payload.len() + CRC_LEN;
+
+        let mut frame = Vec::new();
+        frame.extend_from_slice(&(total_len as u32).to_be_bytes());
+        frame.extend_from_slice(&(encoded_headers.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&crc32fast::hash(&frame).to_be_bytes());
+        frame.extend_from_slice(&encoded_headers);
+        frame.extend_from_slice(payload);
+        frame.extend_from_slice(&crc32fast::hash(&frame).to_be_bytes());
+        frame
+    }
+
+    #[test]
+    fn test_decode_frames_split_across_chunks() {
+        let payload = br#"{"delta":{"text":"Hello"}}"#;
+        let mut bytes = encode(&[(":event-type", "contentBlockDelta")], payload);
+        bytes.extend(encode(&[(":event-type", "messageStop")], b"{}"));
+        let mut decoder = EventStreamDecoder::default();
+
+        let mut actual = vec![];
+        for chunk in bytes.chunks(7) {
+            decoder.push(chunk);
+            while let Some(frame) = decoder.next_frame().unwrap() {
+                actual.push(frame);
+            }
+        }
+
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual[0].header(":event-type"), Some("contentBlockDelta"));
+        assert_eq!(actual[0].payload, payload);
+        assert_eq!(actual[1].header(":event-type"), Some("messageStop"));
+    }
+
+    #[test]
+    fn test_decode_checksum_mismatch() {
+        let mut bytes = encode(&[(":event-type", "messageStop")], b"{}");
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        let mut decoder = EventStreamDecoder::default();
+        decoder.push(&bytes);
+
+        let actual = decoder.next_frame();
+
+        assert!(actual.is_err());
+    }
+}
@@ -0,0 +1,8 @@
+mod event_stream;
+mod model;
+mod provider;
+mod request;
+mod response;
+mod sigv4;
+
+pub use provider::Bedrock;
@@ -0,0 +1,81 @@
+use forge_domain::{ModelCapabilities, ModelId};
+
+/// Families of the models available on Bedrock, which differ in the features
+/// of the Converse API they support.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModelFamily {
+    Claude,
+    Llama,
+    Other,
+}
+
+impl ModelFamily {
+    /// Detects the family from the model id, which may be prefixed with the
+    /// geography of a cross-region inference profile, eg:
+    /// `us.anthropic.claude-3-5-sonnet-20241022-v2:0`.
+    pub fn from_id(id: &ModelId) -> Self {
+        let id = id.as_str();
+        if id.contains("anthropic.claude") {
+            ModelFamily::Claude
+        } else if id.contains("meta.llama") {
+            ModelFamily::Llama
+        } else {
+            ModelFamily::Other
+        }
+    }
+}
+
+/// Checks if the model supports tool use through the Converse API.
+/// ref: https://docs.aws.amazon.com/bedrock/latest/userguide/conversation-inference-supported-models-features.html
+fn supports_tools(id: &ModelId) -> bool {
+    match ModelFamily::from_id(id) {
+        // note: only Claude 3 and later models support tools.
+        ModelFamily::Claude => !["claude-v2", "claude-instant"]
+            .iter()
+            .any(|legacy| id.as_str().contains(legacy)),
+        // note: Llama supports tools since 3.1, eg: `meta.llama3-1-70b-instruct-v1:0`.
+        ModelFamily::Llama => !["meta.llama2", "meta.llama3-8b", "meta.llama3-70b"]
+            .iter()
+            .any(|legacy| id.as_str().contains(legacy)),
+        ModelFamily::Other => false,
+    }
+}
+
+/// Capabilities of the model through the Converse API, which has no JSON
+/// mode.
+pub fn capabilities(id: &ModelId) -> ModelCapabilities {
+    let supports_tools = supports_tools(id);
+    ModelCapabilities {
+        supports_tools,
+        // note: the Claude models that support tools also support images.
+        supports_vision: supports_tools && ModelFamily::from_id(id) == ModelFamily::Claude,
+        supports_json_mode: false,
+        max_output_tokens: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_supports_tools() {
+        let actual = [
+            "anthropic.claude-3-5-sonnet-20241022-v2:0",
+            "us.anthropic.claude-3-7-sonnet-20250219-v1:0",
+            "anthropic.claude-v2:1",
+            "anthropic.claude-instant-v1",
+            "meta.llama3-1-70b-instruct-v1:0",
+            "us.meta.llama3-3-70b-instruct-v1:0",
+            "meta.llama3-8b-instruct-v1:0",
+            "meta.llama2-13b-chat-v1",
+            "amazon.titan-text-express-v1",
+        ]
+        .map(|id| supports_tools(&ModelId::new(id)));
+
+        let expected = [true, true, false, false, true, true, false, false, false];
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,220 @@
+use anyhow::Context as _;
+use derive_setters::Setters;
+use forge_domain::{
+    ChatCompletionMessage, Context, Model, ModelCapabilities, ModelId, ProviderService,
+    ResultStream,
+};
+use futures::StreamExt;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE};
+use reqwest::{Client, Url};
+
+use super::event_stream::EventStreamDecoder;
+use super::model::{capabilities, ModelFamily};
+use super::request::Request;
+use super::response::{EventData, ListModelResponse};
+use super::sigv4::{uri_encode, Credentials, SigV4};
+
+/// Families of the models listed, which are the ones that Forge is tested with.
+const MODEL_PROVIDERS: [&str; 2] = ["Anthropic", "Meta"];
+
+#[derive(Debug, Default, Clone, Setters)]
+#[setters(into, strip_option)]
+pub struct BedrockBuilder {
+    region: Option<String>,
+    access_key_id: Option<String>,
+    secret_access_key: Option<String>,
+    session_token: Option<String>,
+}
+
+impl BedrockBuilder {
+    pub fn build(self) -> anyhow::Result<Bedrock> {
+        let client = Client::builder().build()?;
+        let region = self.region.unwrap_or_else(|| "us-east-1".to_string());
+        let credentials = Credentials {
+            access_key_id: self
+                .access_key_id
+                .ok_or_else(|| anyhow::anyhow!("AWS access key id is required"))?,
+            secret_access_key: self
+                .secret_access_key
+                .ok_or_else(|| anyhow::anyhow!("AWS secret access key is required"))?,
+            session_token: self.session_token,
+        };
+
+        // note: both the runtime and the control plane endpoints are signed for the
+        // `bedrock` service.
+        Ok(Bedrock {
+            client,
+            signer: SigV4::new(credentials, &region, "bedrock"),
+            region,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct Bedrock {
+    client: Client,
+    region: String,
+    signer: SigV4,
+}
+
+impl Bedrock {
+    pub fn builder() -> BedrockBuilder {
+        BedrockBuilder::default()
+    }
+
+    fn chat_url(&self, model: &ModelId) -> anyhow::Result<Url> {
+        // note: model ids contain `:`, eg: `anthropic.claude-v2:1`, which has to be
+        // encoded in the path.
+        let url = format!(
+            "https://bedrock-runtime.{}.amazonaws.com/model/{}/converse-stream",
+            self.region,
+            uri_encode(model.as_str())
+        );
+        Url::parse(&url).with_context(|| format!("Failed to parse URL: {}", url))
+    }
+
+    fn models_url(&self) -> anyhow::Result<Url> {
+        let url = format!(
+            "https://bedrock.{}.amazonaws.com/foundation-models",
+            self.region
+        );
+        Url::parse(&url).with_context(|| format!("Failed to parse URL: {}", url))
+    }
+}
+
+#[async_trait::async_trait]
+impl ProviderService for Bedrock {
+    async fn chat(
+        &self,
+        id: &ModelId,
+        context: Context,
+    ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+        let request = Request::try_from(context)?.for_model(ModelFamily::from_id(id));
+        let body = serde_json::to_vec(&request)?;
+        let url = self.chat_url(id)?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        self.signer
+            .sign("POST", &url, &mut headers, &body, chrono::Utc::now())?;
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("application/vnd.amazon.eventstream"),
+        );
+
+        let response = self
+            .client
+            .post(url)
+            .headers(headers)
+            .body(body)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Bedrock API error ({}): {}", status, text);
+        }
+
+        let mut decoder = EventStreamDecoder::default();
+        let stream = response
+            .bytes_stream()
+            .map(move |chunk| {
+                let chunk = match chunk {
+                    Ok(chunk) => chunk,
+                    Err(err) => return vec![Err(err.into())],
+                };
+                decoder.push(&chunk);
+                let mut messages = vec![];
+                loop {
+                    match decoder.next_frame() {
+                        Ok(Some(frame)) => messages.push(
+                            EventData::try_from(frame)
+                                .with_context(|| "Failed to parse Bedrock event")
+                                .map(ChatCompletionMessage::from),
+                        ),
+                        Ok(None) => break,
+                        Err(err) => {
+                            messages.push(Err(err));
+                            break;
+                        }
+                    }
+                }
+                messages
+            })
+            .flat_map(futures::stream::iter);
+
+        Ok(Box::pin(stream))
+    }
+    async fn models(&self) -> anyhow::Result<Vec<Model>> {
+        let url = self.models_url()?;
+        let mut headers = HeaderMap::new();
+        self.signer
+            .sign("GET", &url, &mut headers, b"", chrono::Utc::now())?;
+
+        let text = self
+            .client
+            .get(url)
+            .headers(headers)
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| "Failed because of a non 200 status code".to_string())?
+            .text()
+            .await?;
+        let response: ListModelResponse = serde_json::from_str(&text)?;
+        Ok(response
+            .model_summaries
+            .into_iter()
+            .filter(|model| {
+                model.response_streaming_supported
+                    && MODEL_PROVIDERS.contains(&model.provider_name.as_str())
+            })
+            .map(Into::into)
+            .collect())
+    }
+    async fn capabilities(&self, model: &ModelId) -> anyhow::Result<ModelCapabilities> {
+        Ok(capabilities(model))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fixture() -> Bedrock {
+        Bedrock::builder()
+            .region("eu-west-1")
+            .access_key_id("AKIDEXAMPLE")
+            .secret_access_key("secret")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_chat_url() {
+        let actual = fixture()
+            .chat_url(&ModelId::new("anthropic.claude-3-5-sonnet-20241022-v2:0"))
+            .unwrap();
+        assert_eq!(
+            actual.as_str(),
+            "https://bedrock-runtime.eu-west-1.amazonaws.com/model/anthropic.claude-3-5-sonnet-20241022-v2%3A0/converse-stream"
+        );
+    }
+
+    #[test]
+    fn test_models_url() {
+        let actual = fixture().models_url().unwrap();
+        assert_eq!(
+            actual.as_str(),
+            "https://bedrock.eu-west-1.amazonaws.com/foundation-models"
+        );
+    }
+
+    #[test]
+    fn test_missing_credentials() {
+        let actual = Bedrock::builder().region("eu-west-1").build();
+        assert!(actual.is_err());
+    }
+}
@@ -0,0 +1,394 @@
+use forge_domain::ContextMessage;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::model::ModelFamily;
+
+/// Body of the Converse API, which is the same for all the models.
+/// ref: https://docs.aws.amazon.com/bedrock/latest/APIReference/API_runtime_ConverseStream.html
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    system: Vec<SystemContent>,
+    #[serde(skip_serializing_if = "InferenceConfig::is_empty")]
+    inference_config: InferenceConfig,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_config: Option<ToolConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    additional_model_request_fields: Option<Value>,
+    #[serde(skip)]
+    top_k: Option<u32>,
+}
+
+impl Request {
+    /// Drops the fields the family of the model doesn't support.
+    pub fn for_model(mut self, family: ModelFamily) -> Self {
+        match family {
+            // note: top_k isn't part of the Converse API, it's passed to Claude as is.
+            ModelFamily::Claude => {
+                self.additional_model_request_fields =
+                    self.top_k.map(|top_k| json!({ "top_k": top_k }));
+            }
+            // note: only Claude and Mistral models support forcing a tool call.
+            ModelFamily::Llama | ModelFamily::Other => {
+                if let Some(tool_config) = self.tool_config.as_mut() {
+                    tool_config.tool_choice = None;
+                }
+            }
+        }
+        self
+    }
+}
+
+impl TryFrom<forge_domain::Context> for Request {
+    type Error = anyhow::Error;
+    fn try_from(request: forge_domain::Context) -> std::result::Result<Self, Self::Error> {
+        let mut system = vec![];
+        let mut messages: Vec<Message> = vec![];
+        for message in request.messages {
+            if let ContextMessage::ContentMessage(chat_message) = &message {
+                if chat_message.role == forge_domain::Role::System {
+                    system.push(SystemContent { text: chat_message.content.clone() });
+                    continue;
+                }
+            }
+
+            let message = Message::try_from(message)?;
+            if message.content.is_empty() {
+                continue;
+            }
+            // note: Converse requires the roles to alternate, so consecutive messages
+            // of the same role, eg: the results of parallel tool calls, are merged.
+            match messages.last_mut() {
+                Some(last) if last.role == message.role => last.content.extend(message.content),
+                _ => messages.push(message),
+            }
+        }
+
+        // note: Converse has no structured output mode, so the schema is
+        // described in the system prompt instead.
+        if let Some(schema) = &request.output_schema {
+            system.push(SystemContent {
+                text: format!(
+                    "Respond with only a JSON document that conforms to this JSON schema:\n{}",
+                    schema
+                ),
+            });
+        }
+
+        let tools = request
+            .tools
+            .into_iter()
+            .map(Tool::try_from)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let tool_config = (!tools.is_empty()).then(|| ToolConfig {
+            tools,
+            tool_choice: request.tool_choice.and_then(ToolChoice::from_domain),
+        });
+
+        let parameters = request.parameters;
+        Ok(Self {
+            messages,
+            system,
+            inference_config: InferenceConfig {
+                max_tokens: parameters.max_tokens,
+                temperature: parameters.temperature,
+                top_p: parameters.top_p,
+            },
+            tool_config,
+            top_k: parameters.top_k,
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct SystemContent {
+    text: String,
+}
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct InferenceConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+}
+
+impl InferenceConfig {
+    fn is_empty(&self) -> bool {
+        self.max_tokens.is_none() && self.temperature.is_none() && self.top_p.is_none()
+    }
+}
+
+#[derive(Serialize)]
+pub struct Message {
+    role: Role,
+    content: Vec<ContentBlock>,
+}
+
+impl TryFrom<ContextMessage> for Message {
+    type Error = anyhow::Error;
+    fn try_from(value: ContextMessage) -> std::result::Result<Self, Self::Error> {
+        Ok(match value {
+            ContextMessage::ContentMessage(chat_message) => {
+                let mut content = vec![];
+                // note: Converse does not allow blank text content.
+                if !chat_message.content.trim().is_empty() {
+                    content.push(ContentBlock::Text(chat_message.content));
+                }
+                for image in chat_message.images {
+                    content.push(ContentBlock::Image(ImageBlock {
+                        format: image.format().to_string(),
+                        source: ImageSource { bytes: image.data },
+                    }));
+                }
+                for tool_call in chat_message.tool_calls.into_iter().flatten() {
+                    content.push(tool_call.try_into()?);
+                }
+                let role = match chat_message.role {
+                    forge_domain::Role::User => Role::User,
+                    forge_domain::Role::Assistant => Role::Assistant,
+                    forge_domain::Role::System => {
+                        return Err(anyhow::anyhow!(
+                            "system role messages are not supported in the messages for bedrock provider"
+                        ));
+                    }
+                };
+                Message { role, content }
+            }
+            ContextMessage::ToolMessage(tool_result) => {
+                Message { role: Role::User, content: vec![tool_result.try_into()?] }
+            }
+        })
+    }
+}
+
+#[derive(Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+enum ContentBlock {
+    Text(String),
+    Image(ImageBlock),
+    ToolUse(ToolUse),
+    ToolResult(ToolResultBlock),
+}
+
+#[derive(Serialize)]
+struct ImageBlock {
+    /// The subtype of the media type, eg: `png`
+    format: String,
+    source: ImageSource,
+}
+
+/// The image encoded in base64, as the JSON API takes the bytes
+#[derive(Serialize)]
+struct ImageSource {
+    bytes: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolUse {
+    tool_use_id: String,
+    name: String,
+    input: Value,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolResultBlock {
+    tool_use_id: String,
+    content: Vec<ToolResultContent>,
+    status: ToolResultStatus,
+}
+
+#[derive(Serialize)]
+struct ToolResultContent {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ToolResultStatus {
+    Success,
+    Error,
+}
+
+impl TryFrom<forge_domain::ToolCallFull> for ContentBlock {
+    type Error = anyhow::Error;
+    fn try_from(value: forge_domain::ToolCallFull) -> std::result::Result<Self, Self::Error> {
+        let call_id = value
+            .call_id
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("`call_id` is required for tool_call"))?;
+
+        Ok(ContentBlock::ToolUse(ToolUse {
+            tool_use_id: call_id.as_str().to_string(),
+            name: value.name.into_string(),
+            input: value.arguments,
+        }))
+    }
+}
+
+impl TryFrom<forge_domain::ToolResult> for ContentBlock {
+    type Error = anyhow::Error;
+    fn try_from(value: forge_domain::ToolResult) -> std::result::Result<Self, Self::Error> {
+        let call_id = value
+            .call_id
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("`call_id` is required for tool_result"))?;
+        Ok(ContentBlock::ToolResult(ToolResultBlock {
+            tool_use_id: call_id.as_str().to_string(),
+            content: vec![ToolResultContent { text: value.content }],
+            status: if value.is_error {
+                ToolResultStatus::Error
+            } else {
+                ToolResultStatus::Success
+            },
+        }))
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolConfig {
+    tools: Vec<Tool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<ToolChoice>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+enum Tool {
+    ToolSpec(ToolSpec),
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolSpec {
+    name: String,
+    description: String,
+    input_schema: InputSchema,
+}
+
+#[derive(Serialize)]
+struct InputSchema {
+    json: Value,
+}
+
+impl TryFrom<forge_domain::ToolDefinition> for Tool {
+    type Error = anyhow::Error;
+    fn try_from(value: forge_domain::ToolDefinition) -> std::result::Result<Self, Self::Error> {
+        Ok(Tool::ToolSpec(ToolSpec {
+            name: value.name.into_string(),
+            description: value.description,
+            input_schema: InputSchema { json: serde_json::to_value(value.input_schema)? },
+        }))
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+enum ToolChoice {
+    Auto {},
+    Any {},
+    Tool { name: String },
+}
+
+impl ToolChoice {
+    // note: Converse can't disable tool calls while tools are configured, so
+    // `None` falls back to the model's default.
+    fn from_domain(value: forge_domain::ToolChoice) -> Option<Self> {
+        match value {
+            forge_domain::ToolChoice::Auto => Some(ToolChoice::Auto {}),
+            forge_domain::ToolChoice::Required => Some(ToolChoice::Any {}),
+            forge_domain::ToolChoice::Call(tool_name) => {
+                Some(ToolChoice::Tool { name: tool_name.into_string() })
+            }
+            forge_domain::ToolChoice::None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_domain::{
+        Context, ContextMessage, ModelParameters, ToolCallFull, ToolCallId, ToolChoice,
+        ToolDefinition, ToolName, ToolResult,
+    };
+
+    use super::*;
+
+    fn fixture() -> Context {
+        Context::default()
+            .add_message(ContextMessage::system(
+                "You're expert at math, so you should resolve all user queries.",
+            ))
+            .add_message(ContextMessage::user("what's 2 + 2 and 3 + 3 ?"))
+            .add_message(ContextMessage::assistant(
+                "here are the system calls.",
+                Some(vec![
+                    ToolCallFull {
+                        name: ToolName::new("math"),
+                        call_id: Some(ToolCallId::new("math-1")),
+                        arguments: serde_json::json!({"expression": "2 + 2"}),
+                    },
+                    ToolCallFull {
+                        name: ToolName::new("math"),
+                        call_id: Some(ToolCallId::new("math-2")),
+                        arguments: serde_json::json!({"expression": "3 + 3"}),
+                    },
+                ]),
+            ))
+            .add_tool_results(vec![
+                ToolResult {
+                    name: ToolName::new("math"),
+                    call_id: Some(ToolCallId::new("math-1")),
+                    content: serde_json::json!({"result": 4}).to_string(),
+                    is_error: false,
+                    files: Vec::new(),
+                },
+                ToolResult {
+                    name: ToolName::new("math"),
+                    call_id: Some(ToolCallId::new("math-2")),
+                    content: "invalid expression".to_string(),
+                    is_error: true,
+                    files: Vec::new(),
+                },
+            ])
+            .add_tool(ToolDefinition::new("math").description("Evaluates an expression"))
+            .tool_choice(ToolChoice::Call(ToolName::new("math")))
+            .parameters(ModelParameters::default().max_tokens(1024).top_k(40))
+    }
+
+    #[test]
+    fn test_request_conversion() {
+        let request = Request::try_from(fixture())
+            .unwrap()
+            .for_model(ModelFamily::Claude);
+        insta::assert_snapshot!(serde_json::to_string_pretty(&request).unwrap());
+    }
+
+    #[test]
+    fn test_request_conversion_for_llama() {
+        let request = Request::try_from(fixture())
+            .unwrap()
+            .for_model(ModelFamily::Llama);
+        let actual = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(actual["toolConfig"].get("toolChoice"), None);
+        assert_eq!(actual.get("additionalModelRequestFields"), None);
+    }
+}
@@ -0,0 +1,360 @@
+use forge_domain::{ChatCompletionMessage, Content, ModelId, ToolCallId, ToolCallPart, ToolName};
+use serde::Deserialize;
+
+use super::event_stream::Frame;
+use super::model::capabilities;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListModelResponse {
+    pub model_summaries: Vec<Model>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+    pub model_id: String,
+    model_name: String,
+    pub provider_name: String,
+    #[serde(default)]
+    pub response_streaming_supported: bool,
+}
+
+impl From<Model> for forge_domain::Model {
+    fn from(value: Model) -> Self {
+        let id = ModelId::new(value.model_id);
+        Self {
+            id: id.clone(),
+            name: format!("{} {}", value.provider_name, value.model_name),
+            description: None,
+            context_length: None,
+            pricing: None,
+            capabilities: Some(capabilities(&id)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    EndTurn,
+    ToolUse,
+    MaxTokens,
+    StopSequence,
+    GuardrailIntervened,
+    ContentFiltered,
+}
+
+impl From<StopReason> for forge_domain::FinishReason {
+    fn from(value: StopReason) -> Self {
+        match value {
+            StopReason::EndTurn | StopReason::StopSequence => forge_domain::FinishReason::Stop,
+            StopReason::ToolUse => forge_domain::FinishReason::ToolCalls,
+            StopReason::MaxTokens => forge_domain::FinishReason::Length,
+            StopReason::GuardrailIntervened | StopReason::ContentFiltered => {
+                forge_domain::FinishReason::ContentFilter
+            }
+        }
+    }
+}
+
+/// Events of the ConverseStream API, named by the `:event-type` header of the
+/// frame that holds them.
+/// ref: https://docs.aws.amazon.com/bedrock/latest/APIReference/API_runtime_ConverseStreamOutput.html
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum Event {
+    MessageStart {
+        role: String,
+    },
+    ContentBlockStart {
+        content_block_index: u32,
+        start: ContentBlockStart,
+    },
+    ContentBlockDelta {
+        content_block_index: u32,
+        delta: ContentBlockDelta,
+    },
+    ContentBlockStop {
+        content_block_index: u32,
+    },
+    MessageStop {
+        stop_reason: StopReason,
+    },
+    Metadata {
+        usage: Usage,
+    },
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(untagged)]
+pub enum EventData {
+    KnownEvent(Event),
+    // Events and content blocks added to the API later, eg: reasoning content
+    Unknown(serde_json::Value),
+}
+
+impl TryFrom<Frame> for EventData {
+    type Error = anyhow::Error;
+    fn try_from(frame: Frame) -> Result<Self, Self::Error> {
+        match frame.header(":message-type") {
+            Some("event") => {
+                let event_type = frame.header(":event-type").unwrap_or_default();
+                let payload = serde_json::from_slice::<serde_json::Value>(&frame.payload)?;
+                Ok(serde_json::from_value(
+                    serde_json::json!({ event_type: payload }),
+                )?)
+            }
+            // note: exceptions carry their message in the payload, eg: throttlingException.
+            Some("exception") => {
+                let message = serde_json::from_slice::<serde_json::Value>(&frame.payload)
+                    .ok()
+                    .and_then(|payload| payload["message"].as_str().map(str::to_string))
+                    .unwrap_or_else(|| String::from_utf8_lossy(&frame.payload).to_string());
+                Err(anyhow::anyhow!(
+                    "Bedrock API error: {}: {}",
+                    frame.header(":exception-type").unwrap_or("unknown"),
+                    message
+                ))
+            }
+            _ => Err(anyhow::anyhow!(
+                "Bedrock API error: {}: {}",
+                frame.header(":error-code").unwrap_or("unknown"),
+                frame.header(":error-message").unwrap_or_default()
+            )),
+        }
+    }
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum ContentBlockStart {
+    #[serde(rename_all = "camelCase")]
+    ToolUse { tool_use_id: String, name: String },
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum ContentBlockDelta {
+    Text(String),
+    ToolUse { input: String },
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Usage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+    #[serde(default)]
+    pub cache_read_input_tokens: u64,
+    #[serde(default)]
+    pub cache_write_input_tokens: u64,
+}
+
+impl From<Usage> for forge_domain::Usage {
+    fn from(usage: Usage) -> Self {
+        // note: the input tokens exclude the ones written to and read from the cache.
+        forge_domain::Usage {
+            prompt_tokens: usage.input_tokens
+                + usage.cache_read_input_tokens
+                + usage.cache_write_input_tokens,
+            completion_tokens: usage.output_tokens,
+            total_tokens: usage.total_tokens,
+            cached_tokens: usage.cache_read_input_tokens,
+        }
+    }
+}
+
+impl From<EventData> for ChatCompletionMessage {
+    fn from(value: EventData) -> Self {
+        match value {
+            EventData::KnownEvent(event) => ChatCompletionMessage::from(event),
+            // Ignore any unknown events
+            EventData::Unknown(_) => ChatCompletionMessage::assistant(Content::part("")),
+        }
+    }
+}
+
+impl From<Event> for ChatCompletionMessage {
+    fn from(value: Event) -> Self {
+        let message = ChatCompletionMessage::assistant(Content::part(""));
+        match value {
+            Event::ContentBlockStart {
+                content_block_index,
+                start: ContentBlockStart::ToolUse { tool_use_id, name },
+            } => message.add_tool_call(ToolCallPart {
+                index: content_block_index,
+                call_id: Some(ToolCallId::new(tool_use_id)),
+                name: Some(ToolName::new(name)),
+                arguments_part: "".to_string(),
+            }),
+            Event::ContentBlockDelta { delta: ContentBlockDelta::Text(text), .. } => {
+                ChatCompletionMessage::assistant(Content::part(text))
+            }
+            Event::ContentBlockDelta {
+                content_block_index,
+                delta: ContentBlockDelta::ToolUse { input },
+            } => message.add_tool_call(ToolCallPart {
+                index: content_block_index,
+                call_id: None,
+                name: None,
+                arguments_part: input,
+            }),
+            Event::MessageStop { stop_reason } => message.finish_reason(stop_reason),
+            Event::Metadata { usage } => message.usage(usage),
+            Event::MessageStart { .. } | Event::ContentBlockStop { .. } => message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use forge_domain::{FinishReason, ToolCall};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn frame(event_type: &str, payload: &str) -> Frame {
+        Frame {
+            headers: HashMap::from([
+                (":message-type".to_string(), "event".to_string()),
+                (":event-type".to_string(), event_type.to_string()),
+            ]),
+            payload: payload.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_event_deser() {
+        let tests = vec![
+            (
+                frame("messageStart", r#"{"p":"abc","role":"assistant"}"#),
+                Event::MessageStart { role: "assistant".to_string() },
+            ),
+            (
+                frame(
+                    "contentBlockStart",
+                    r#"{"contentBlockIndex":1,"p":"abc","start":{"toolUse":{"name":"math","toolUseId":"tooluse_1"}}}"#,
+                ),
+                Event::ContentBlockStart {
+                    content_block_index: 1,
+                    start: ContentBlockStart::ToolUse {
+                        tool_use_id: "tooluse_1".to_string(),
+                        name: "math".to_string(),
+                    },
+                },
+            ),
+            (
+                frame(
+                    "contentBlockDelta",
+                    r#"{"contentBlockIndex":0,"delta":{"text":"Hello"},"p":"abc"}"#,
+                ),
+                Event::ContentBlockDelta {
+                    content_block_index: 0,
+                    delta: ContentBlockDelta::Text("Hello".to_string()),
+                },
+            ),
+            (
+                frame(
+                    "contentBlockDelta",
+                    r#"{"contentBlockIndex":1,"delta":{"toolUse":{"input":"{\"expression\":"}},"p":"abc"}"#,
+                ),
+                Event::ContentBlockDelta {
+                    content_block_index: 1,
+                    delta: ContentBlockDelta::ToolUse { input: "{\"expression\":".to_string() },
+                },
+            ),
+            (
+                frame("contentBlockStop", r#"{"contentBlockIndex":1,"p":"abc"}"#),
+                Event::ContentBlockStop { content_block_index: 1 },
+            ),
+            (
+                frame("messageStop", r#"{"p":"abc","stopReason":"tool_use"}"#),
+                Event::MessageStop { stop_reason: StopReason::ToolUse },
+            ),
+            (
+                frame(
+                    "metadata",
+                    r#"{"metrics":{"latencyMs":512},"p":"abc","usage":{"inputTokens":10,"outputTokens":12,"totalTokens":22}}"#,
+                ),
+                Event::Metadata {
+                    usage: Usage {
+                        input_tokens: 10,
+                        output_tokens: 12,
+                        total_tokens: 22,
+                        cache_read_input_tokens: 0,
+                        cache_write_input_tokens: 0,
+                    },
+                },
+            ),
+        ];
+        for (frame, expected) in tests {
+            let actual = EventData::try_from(frame).unwrap();
+            assert_eq!(actual, EventData::KnownEvent(expected));
+        }
+    }
+
+    #[test]
+    fn test_unknown_event() {
+        let actual = EventData::try_from(frame(
+            "contentBlockDelta",
+            r#"{"contentBlockIndex":0,"delta":{"reasoningContent":{"text":"Hmm"}}}"#,
+        ))
+        .unwrap();
+        assert!(matches!(actual, EventData::Unknown(_)));
+    }
+
+    #[test]
+    fn test_exception() {
+        let fixture = Frame {
+            headers: HashMap::from([
+                (":message-type".to_string(), "exception".to_string()),
+                (
+                    ":exception-type".to_string(),
+                    "throttlingException".to_string(),
+                ),
+            ]),
+            payload: br#"{"message":"Too many requests"}"#.to_vec(),
+        };
+
+        let actual = EventData::try_from(fixture).unwrap_err().to_string();
+
+        assert_eq!(
+            actual,
+            "Bedrock API error: throttlingException: Too many requests"
+        );
+    }
+
+    #[test]
+    fn test_tool_call_message() {
+        let fixture = Event::ContentBlockStart {
+            content_block_index: 2,
+            start: ContentBlockStart::ToolUse {
+                tool_use_id: "tooluse_1".to_string(),
+                name: "math".to_string(),
+            },
+        };
+
+        let actual = ChatCompletionMessage::from(fixture);
+
+        let expected = ChatCompletionMessage::assistant(Content::part("")).add_tool_call(
+            ToolCall::Part(ToolCallPart {
+                index: 2,
+                call_id: Some(ToolCallId::new("tooluse_1")),
+                name: Some(ToolName::new("math")),
+                arguments_part: "".to_string(),
+            }),
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_stop_message() {
+        let actual =
+            ChatCompletionMessage::from(Event::MessageStop { stop_reason: StopReason::MaxTokens });
+        assert_eq!(actual.finish_reason, Some(FinishReason::Length));
+    }
+}
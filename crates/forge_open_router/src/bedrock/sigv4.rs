@@ -0,0 +1,229 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// AWS credentials used to sign requests.
+#[derive(Clone, Debug)]
+pub struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Set when using temporary credentials, eg: from an assumed role
+    pub session_token: Option<String>,
+}
+
+/// Signs requests with AWS Signature Version 4.
+/// ref: https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_sigv-create-signed-request.html
+#[derive(Clone, Debug)]
+pub struct SigV4 {
+    credentials: Credentials,
+    region: String,
+    service: String,
+}
+
+impl SigV4 {
+    pub fn new(credentials: Credentials, region: impl ToString, service: impl ToString) -> Self {
+        Self {
+            credentials,
+            region: region.to_string(),
+            service: service.to_string(),
+        }
+    }
+
+    /// Adds the `x-amz-date`, `x-amz-security-token` and `authorization`
+    /// headers to a request that already holds the other headers to sign.
+    pub fn sign(
+        &self,
+        method: &str,
+        url: &Url,
+        headers: &mut HeaderMap,
+        payload: &[u8],
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        let timestamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let host = match url.port() {
+            Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+
+        headers.insert("host", HeaderValue::from_str(&host)?);
+        headers.insert("x-amz-date", HeaderValue::from_str(&timestamp)?);
+        if let Some(token) = &self.credentials.session_token {
+            headers.insert("x-amz-security-token", HeaderValue::from_str(token)?);
+        }
+
+        let mut signed = headers
+            .iter()
+            .map(|(name, value)| {
+                let value = value.to_str().unwrap_or_default();
+                (
+                    name.as_str(),
+                    value.split_whitespace().collect::<Vec<_>>().join(" "),
+                )
+            })
+            .collect::<Vec<_>>();
+        signed.sort();
+        let canonical_headers = signed
+            .iter()
+            .map(|(name, value)| format!("{name}:{value}\n"))
+            .collect::<String>();
+        let signed_headers = signed
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+
+        let canonical_request = format!(
+            "{method}\n{}\n{}\n{canonical_headers}\n{signed_headers}\n{}",
+            canonical_uri(url),
+            canonical_query(url),
+            hex::encode(Sha256::digest(payload)),
+        );
+
+        let scope = format!("{date}/{}/{}/aws4_request", self.region, self.service);
+        let string_to_sign = format!(
+            "{ALGORITHM}\n{timestamp}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let key = [
+            date.as_str(),
+            self.region.as_str(),
+            self.service.as_str(),
+            "aws4_request",
+        ]
+        .iter()
+        .fold(
+            format!("AWS4{}", self.credentials.secret_access_key).into_bytes(),
+            |key, data| hmac(&key, data.as_bytes()),
+        );
+        let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+
+        let authorization = format!(
+            "{ALGORITHM} Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            self.credentials.access_key_id
+        );
+        headers.insert(AUTHORIZATION, HeaderValue::from_str(&authorization)?);
+        // note: reqwest sets the host header from the URL itself.
+        headers.remove(HeaderName::from_static("host"));
+        Ok(())
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Encodes every byte except the unreserved characters of RFC 3986.
+pub fn uri_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Path of the URL with each segment encoded once more, as required by all
+/// services except S3.
+fn canonical_uri(url: &Url) -> String {
+    let path = url
+        .path()
+        .split('/')
+        .map(uri_encode)
+        .collect::<Vec<_>>()
+        .join("/");
+    if path.is_empty() {
+        "/".to_string()
+    } else {
+        path
+    }
+}
+
+fn canonical_query(url: &Url) -> String {
+    let mut pairs = url
+        .query_pairs()
+        .map(|(key, value)| (uri_encode(&key), uri_encode(&value)))
+        .collect::<Vec<_>>();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fixture(session_token: Option<&str>) -> SigV4 {
+        SigV4::new(
+            Credentials {
+                access_key_id: "AKIDEXAMPLE".to_string(),
+                secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+                session_token: session_token.map(str::to_string),
+            },
+            "us-east-1",
+            "service",
+        )
+    }
+
+    // "get-vanilla" from the AWS SigV4 test suite
+    #[test]
+    fn test_sign_get_vanilla() {
+        let url = "https://example.amazonaws.com/".parse().unwrap();
+        let now = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+        let mut headers = HeaderMap::new();
+
+        fixture(None)
+            .sign("GET", &url, &mut headers, b"", now)
+            .unwrap();
+
+        assert_eq!(
+            headers[AUTHORIZATION],
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, SignedHeaders=host;x-amz-date, Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+        assert_eq!(headers["x-amz-date"], "20150830T123600Z");
+    }
+
+    #[test]
+    fn test_sign_with_session_token() {
+        let url = "https://example.amazonaws.com/".parse().unwrap();
+        let now = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+        let mut headers = HeaderMap::new();
+
+        fixture(Some("token"))
+            .sign("GET", &url, &mut headers, b"", now)
+            .unwrap();
+
+        assert_eq!(headers["x-amz-security-token"], "token");
+        assert!(headers[AUTHORIZATION]
+            .to_str()
+            .unwrap()
+            .contains("SignedHeaders=host;x-amz-date;x-amz-security-token"));
+    }
+
+    #[test]
+    fn test_canonical_uri_encodes_twice() {
+        let url = "https://bedrock-runtime.us-east-1.amazonaws.com/model/anthropic.claude-v2%3A1/converse-stream"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            canonical_uri(&url),
+            "/model/anthropic.claude-v2%253A1/converse-stream"
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use forge_domain::{
+    ChatCompletionMessage, Context, Model, ModelCapabilities, ModelId, ProviderService,
+    ResultStream, TextCompletion, Usage,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio_stream::StreamExt;
+use tracing::{debug, warn};
+
+/// A response of the provider, stored in a file named after the hash of its
+/// request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    created_at: DateTime<Utc>,
+    model: ModelId,
+    response: Vec<ChatCompletionMessage>,
+}
+
+/// Provider that reuses the responses of another provider to the same
+/// requests, ie: with the same model, messages, tools and parameters, until
+/// they expire. Reused responses report no usage, so they cost nothing.
+pub struct CachedProvider {
+    provider: Box<dyn ProviderService>,
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl CachedProvider {
+    /// Caches the responses of `provider` in `dir` for `ttl`.
+    pub fn new(provider: Box<dyn ProviderService>, dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self { provider, dir: dir.into(), ttl }
+    }
+
+    fn path(&self, model: &ModelId, context: &Context) -> anyhow::Result<PathBuf> {
+        let mut hasher = Sha256::new();
+        hasher.update(model.as_str());
+        hasher.update(serde_json::to_vec(context)?);
+        Ok(self
+            .dir
+            .join(format!("{}.json", hex::encode(hasher.finalize()))))
+    }
+
+    /// The response cached at `path`, unless it's missing, invalid or expired.
+    fn load(&self, path: &Path) -> Option<Vec<ChatCompletionMessage>> {
+        let content = std::fs::read_to_string(path).ok()?;
+        let entry: Entry = serde_json::from_str(&content).ok()?;
+        let age = (Utc::now() - entry.created_at).to_std().unwrap_or_default();
+        (age < self.ttl).then_some(entry.response)
+    }
+}
+
+fn save(path: &Path, entry: &Entry) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl ProviderService for CachedProvider {
+    async fn chat(
+        &self,
+        id: &ModelId,
+        context: Context,
+    ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+        let path = self.path(id, &context)?;
+        if let Some(mut response) = self.load(&path) {
+            debug!(model = %id, path = %path.display(), "Reusing the cached response");
+            for message in response.iter_mut() {
+                message.usage = None;
+            }
+            if let Some(last) = response.last_mut() {
+                last.usage = Some(Usage::default());
+            }
+            return Ok(Box::pin(tokio_stream::iter(response.into_iter().map(Ok))));
+        }
+
+        let stream = self.provider.chat(id, context).await?;
+        // note: the response is cached once the stream ends without an error,
+        // so that it's still streamed while caching.
+        let response = Arc::new(Mutex::new(Some(Vec::new())));
+        let tap = response.clone();
+        let stream = stream.map(move |message| {
+            let mut response = tap.lock().unwrap();
+            match &message {
+                Ok(message) => {
+                    if let Some(response) = response.as_mut() {
+                        response.push(message.clone())
+                    }
+                }
+                Err(_) => *response = None,
+            }
+            Some(message)
+        });
+        let model = id.clone();
+        let end = futures::stream::once(async move {
+            if let Some(response) = response.lock().unwrap().take() {
+                let entry = Entry { created_at: Utc::now(), model, response };
+                if let Err(error) = save(&path, &entry) {
+                    warn!(error = format!("{error:#}"), "Failed to cache the response");
+                }
+            }
+            None
+        });
+        Ok(Box::pin(stream.chain(end).filter_map(|message| message)))
+    }
+
+    async fn complete(&self, id: &ModelId, request: TextCompletion) -> anyhow::Result<String> {
+        // note: completions follow the cursor, so they're rarely requested twice.
+        self.provider.complete(id, request).await
+    }
+
+    async fn models(&self) -> anyhow::Result<Vec<Model>> {
+        self.provider.models().await
+    }
+
+    async fn capabilities(&self, model: &ModelId) -> anyhow::Result<ModelCapabilities> {
+        self.provider.capabilities(model).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use forge_domain::{Content, ContextMessage, FinishReason, ModelParameters};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Answers with the number of requests made to it so far.
+    #[derive(Default)]
+    struct Stub {
+        requests: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl ProviderService for Stub {
+        async fn chat(
+            &self,
+            _id: &ModelId,
+            _context: Context,
+        ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+            let count = self.requests.fetch_add(1, Ordering::SeqCst) + 1;
+            let messages = vec![
+                ChatCompletionMessage::assistant(Content::part(format!("Request {count}"))),
+                ChatCompletionMessage::assistant(Content::part(""))
+                    .finish_reason(FinishReason::Stop)
+                    .usage(Usage {
+                        prompt_tokens: 10,
+                        completion_tokens: 5,
+                        total_tokens: 15,
+                        cached_tokens: 0,
+                    }),
+            ];
+            Ok(Box::pin(tokio_stream::iter(messages.into_iter().map(Ok))))
+        }
+
+        async fn models(&self) -> anyhow::Result<Vec<Model>> {
+            Ok(vec![])
+        }
+
+        async fn capabilities(&self, _model: &ModelId) -> anyhow::Result<ModelCapabilities> {
+            Ok(ModelCapabilities::all())
+        }
+    }
+
+    async fn chat(provider: &CachedProvider, context: Context) -> Vec<ChatCompletionMessage> {
+        provider
+            .chat(&ModelId::new("gpt-4o"), context)
+            .await
+            .unwrap()
+            .collect::<anyhow::Result<Vec<_>>>()
+            .await
+            .unwrap()
+    }
+
+    fn text(response: &[ChatCompletionMessage]) -> String {
+        response
+            .iter()
+            .filter_map(|message| message.content.as_ref())
+            .map(Content::as_str)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let stub = Stub::default();
+        let requests = stub.requests.clone();
+        let provider = CachedProvider::new(Box::new(stub), dir.path(), Duration::from_secs(60));
+        let context = Context::default().add_message(ContextMessage::user("Name the cat"));
+
+        let first = chat(&provider, context.clone()).await;
+        let second = chat(&provider, context.clone()).await;
+        assert_eq!(text(&second), text(&first));
+        assert_eq!(second.last().unwrap().usage, Some(Usage::default()));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // Other parameters make another request
+        let context = context.parameters(ModelParameters::default().temperature(0.2));
+        let actual = chat(&provider, context).await;
+        assert_eq!(text(&actual), "Request 2");
+    }
+
+    #[tokio::test]
+    async fn test_cache_expired() {
+        let dir = tempfile::tempdir().unwrap();
+        let stub = Stub::default();
+        let requests = stub.requests.clone();
+        let provider = CachedProvider::new(Box::new(stub), dir.path(), Duration::ZERO);
+        let context = Context::default().add_message(ContextMessage::user("Name the cat"));
+
+        chat(&provider, context.clone()).await;
+        let actual = chat(&provider, context).await;
+        assert_eq!(text(&actual), "Request 2");
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::Context as _;
+use derive_setters::Setters;
+use forge_domain::{
+    ChatCompletionMessage, Context, Model, ModelCapabilities, ModelId, ProviderService,
+    ResultStream,
+};
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Client, Url};
+use reqwest_eventsource::{Event, RequestBuilderExt};
+use tokio_stream::StreamExt;
+
+use super::request::Request;
+use super::response::{ListModelResponse, Model as GeminiModel, Response};
+
+#[derive(Debug, Default, Clone, Setters)]
+#[setters(into, strip_option)]
+pub struct GeminiBuilder {
+    api_key: Option<String>,
+    base_url: Option<String>,
+}
+
+impl GeminiBuilder {
+    pub fn build(self) -> anyhow::Result<Gemini> {
+        let client = Client::builder().build()?;
+        let base_url = self
+            .base_url
+            .as_deref()
+            .unwrap_or("https://generativelanguage.googleapis.com/v1beta/");
+
+        let base_url = Url::parse(base_url)
+            .with_context(|| format!("Failed to parse base URL: {}", base_url))?;
+        let api_key = self
+            .api_key
+            .ok_or_else(|| anyhow::anyhow!("API key is required"))?;
+
+        Ok(Gemini { client, base_url, api_key })
+    }
+}
+
+#[derive(Clone)]
+pub struct Gemini {
+    client: Client,
+    api_key: String,
+    base_url: Url,
+}
+
+impl Gemini {
+    pub fn builder() -> GeminiBuilder {
+        GeminiBuilder::default()
+    }
+
+    fn url(&self, path: &str) -> anyhow::Result<Url> {
+        // Validate the path doesn't contain certain patterns
+        if path.contains("://") || path.contains("..") {
+            anyhow::bail!("Invalid path: Contains forbidden patterns");
+        }
+
+        // Remove leading slash to avoid double slashes
+        let path = path.trim_start_matches('/');
+
+        self.base_url
+            .join(path)
+            .with_context(|| format!("Failed to append {} to base URL: {}", path, self.base_url))
+    }
+
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        // note: the key is sent in a header rather than the query, so that it doesn't
+        // end up in logs of the URL.
+        headers.insert(
+            "x-goog-api-key",
+            HeaderValue::from_str(self.api_key.as_str()).unwrap(),
+        );
+        headers
+    }
+}
+
+#[async_trait::async_trait]
+impl ProviderService for Gemini {
+    async fn chat(
+        &self,
+        id: &ModelId,
+        context: Context,
+    ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+        let request = Request::try_from(context)?;
+
+        let es = self
+            .client
+            .post(self.url(&format!("models/{}:streamGenerateContent?alt=sse", id))?)
+            .headers(self.headers())
+            .json(&request)
+            .eventsource()?;
+
+        let stream = es
+            .take_while(|message| !matches!(message, Err(reqwest_eventsource::Error::StreamEnded)))
+            .then(|event| async {
+                match event {
+                    Ok(event) => match event {
+                        Event::Open => None,
+                        Event::Message(event) if event.data.is_empty() => None,
+                        Event::Message(event) => Some(
+                            serde_json::from_str::<Response>(&event.data)
+                                .with_context(|| "Failed to parse Gemini response")
+                                .and_then(|response| {
+                                    ChatCompletionMessage::try_from(response)
+                                        .with_context(|| "Failed to create completion message")
+                                }),
+                        ),
+                    },
+                    Err(reqwest_eventsource::Error::StreamEnded) => None,
+                    Err(reqwest_eventsource::Error::InvalidStatusCode(status, response)) => {
+                        let text = response.text().await.unwrap_or_default();
+                        Some(Err(anyhow::anyhow!(
+                            "Gemini API error ({}): {}",
+                            status,
+                            text
+                        )))
+                    }
+                    Err(err) => Some(Err(err.into())),
+                }
+            });
+
+        Ok(Box::pin(stream.filter_map(|x| x)))
+    }
+    async fn models(&self) -> anyhow::Result<Vec<Model>> {
+        let mut models = vec![];
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut url = self.url("models")?;
+            url.query_pairs_mut().append_pair("pageSize", "1000");
+            if let Some(page_token) = &page_token {
+                url.query_pairs_mut().append_pair("pageToken", page_token);
+            }
+            let text = self
+                .client
+                .get(url)
+                .headers(self.headers())
+                .send()
+                .await?
+                .error_for_status()
+                .with_context(|| "Failed because of a non 200 status code".to_string())?
+                .text()
+                .await?;
+            let response: ListModelResponse = serde_json::from_str(&text)?;
+            models.extend(
+                response
+                    .models
+                    .into_iter()
+                    // note: the list includes embedding models.
+                    .filter(|model| {
+                        model
+                            .supported_generation_methods
+                            .iter()
+                            .any(|method| method == "generateContent")
+                    })
+                    .map(Into::into),
+            );
+
+            page_token = response.next_page_token.filter(|token| !token.is_empty());
+            if page_token.is_none() {
+                return Ok(models);
+            }
+        }
+    }
+    async fn capabilities(&self, model: &ModelId) -> anyhow::Result<ModelCapabilities> {
+        let text = self
+            .client
+            .get(self.url(&format!("models/{}", model))?)
+            .headers(self.headers())
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| "Failed because of a non 200 status code".to_string())?
+            .text()
+            .await?;
+        let model: GeminiModel = serde_json::from_str(&text)?;
+        Ok(model.capabilities())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_url_for_chat() {
+        let gemini = Gemini::builder().api_key("some-key").build().unwrap();
+        assert_eq!(
+            gemini
+                .url("models/gemini-2.0-flash:streamGenerateContent?alt=sse")
+                .unwrap()
+                .as_str(),
+            "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.0-flash:streamGenerateContent?alt=sse"
+        );
+    }
+}
@@ -0,0 +1,540 @@
+use forge_domain::ContextMessage;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// Body of the `streamGenerateContent` API.
+/// ref: https://ai.google.dev/api/generate-content#request-body
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    contents: Vec<Content>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_instruction: Option<Content>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<Tool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_config: Option<ToolConfig>,
+    #[serde(skip_serializing_if = "GenerationConfig::is_empty")]
+    generation_config: GenerationConfig,
+}
+
+impl TryFrom<forge_domain::Context> for Request {
+    type Error = anyhow::Error;
+    fn try_from(request: forge_domain::Context) -> std::result::Result<Self, Self::Error> {
+        let mut system = vec![];
+        let mut contents: Vec<Content> = vec![];
+        for message in request.messages {
+            if let ContextMessage::ContentMessage(chat_message) = &message {
+                if chat_message.role == forge_domain::Role::System {
+                    system.push(Part::Text(chat_message.content.clone()));
+                    continue;
+                }
+            }
+
+            let content = Content::try_from(message)?;
+            if content.parts.is_empty() {
+                continue;
+            }
+            // note: the responses to parallel function calls have to be sent in a
+            // single content, so consecutive contents of the same role are merged.
+            match contents.last_mut() {
+                Some(last) if last.role == content.role => last.parts.extend(content.parts),
+                _ => contents.push(content),
+            }
+        }
+
+        let mut generation_config = GenerationConfig {
+            max_output_tokens: request.parameters.max_tokens,
+            temperature: request.parameters.temperature,
+            top_p: request.parameters.top_p,
+            top_k: request.parameters.top_k,
+            ..Default::default()
+        };
+        if let Some(schema) = &request.output_schema {
+            // note: Gemini doesn't support JSON mode along with function calling, so
+            // the schema is described in the system instruction instead.
+            if request.tools.is_empty() {
+                generation_config.response_mime_type = Some("application/json".to_string());
+                generation_config.response_schema = Some(to_gemini_schema(schema));
+            } else {
+                system.push(Part::Text(format!(
+                    "Respond with only a JSON document that conforms to this JSON schema:\n{}",
+                    schema
+                )));
+            }
+        }
+
+        let function_declarations = request
+            .tools
+            .into_iter()
+            .map(FunctionDeclaration::try_from)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let tools = if function_declarations.is_empty() {
+            vec![]
+        } else {
+            vec![Tool { function_declarations }]
+        };
+
+        Ok(Self {
+            contents,
+            system_instruction: (!system.is_empty())
+                .then_some(Content { role: None, parts: system }),
+            tool_config: request.tool_choice.map(ToolConfig::from),
+            tools,
+            generation_config,
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct Content {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    role: Option<Role>,
+    parts: Vec<Part>,
+}
+
+impl TryFrom<ContextMessage> for Content {
+    type Error = anyhow::Error;
+    fn try_from(value: ContextMessage) -> std::result::Result<Self, Self::Error> {
+        Ok(match value {
+            ContextMessage::ContentMessage(chat_message) => {
+                let mut parts = vec![];
+                if !chat_message.content.is_empty() {
+                    parts.push(Part::Text(chat_message.content));
+                }
+                for image in chat_message.images {
+                    parts.push(Part::InlineData(InlineData {
+                        mime_type: image.mime_type,
+                        data: image.data,
+                    }));
+                }
+                for tool_call in chat_message.tool_calls.into_iter().flatten() {
+                    parts.push(Part::FunctionCall(FunctionCall {
+                        id: tool_call.call_id.map(|id| id.as_str().to_string()),
+                        name: tool_call.name.into_string(),
+                        args: tool_call.arguments,
+                    }));
+                }
+                let role = match chat_message.role {
+                    forge_domain::Role::User => Role::User,
+                    forge_domain::Role::Assistant => Role::Model,
+                    forge_domain::Role::System => {
+                        return Err(anyhow::anyhow!(
+                            "system role messages are not supported in the contents for gemini provider"
+                        ));
+                    }
+                };
+                Content { role: Some(role), parts }
+            }
+            ContextMessage::ToolMessage(tool_result) => {
+                // note: the response has to be a JSON object.
+                let response = if tool_result.is_error {
+                    json!({ "error": tool_result.content })
+                } else {
+                    json!({ "output": tool_result.content })
+                };
+                Content {
+                    role: Some(Role::User),
+                    parts: vec![Part::FunctionResponse(FunctionResponse {
+                        id: tool_result.call_id.map(|id| id.as_str().to_string()),
+                        name: tool_result.name.into_string(),
+                        response,
+                    })],
+                }
+            }
+        })
+    }
+}
+
+#[derive(Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    User,
+    Model,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+enum Part {
+    Text(String),
+    InlineData(InlineData),
+    FunctionCall(FunctionCall),
+    FunctionResponse(FunctionResponse),
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InlineData {
+    mime_type: String,
+    data: String,
+}
+
+#[derive(Serialize)]
+struct FunctionCall {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    name: String,
+    args: Value,
+}
+
+#[derive(Serialize)]
+struct FunctionResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    name: String,
+    response: Value,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Tool {
+    function_declarations: Vec<FunctionDeclaration>,
+}
+
+#[derive(Serialize)]
+struct FunctionDeclaration {
+    name: String,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parameters: Option<Value>,
+}
+
+impl TryFrom<forge_domain::ToolDefinition> for FunctionDeclaration {
+    type Error = anyhow::Error;
+    fn try_from(value: forge_domain::ToolDefinition) -> std::result::Result<Self, Self::Error> {
+        let parameters = to_gemini_schema(&serde_json::to_value(value.input_schema)?);
+        // note: Gemini rejects object parameters without any properties.
+        let has_properties = parameters
+            .get("properties")
+            .and_then(Value::as_object)
+            .is_some_and(|properties| !properties.is_empty());
+        Ok(FunctionDeclaration {
+            name: value.name.into_string(),
+            description: value.description,
+            parameters: has_properties.then_some(parameters),
+        })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolConfig {
+    function_calling_config: FunctionCallingConfig,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FunctionCallingConfig {
+    mode: FunctionCallingMode,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    allowed_function_names: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum FunctionCallingMode {
+    Auto,
+    Any,
+    None,
+}
+
+impl From<forge_domain::ToolChoice> for ToolConfig {
+    fn from(value: forge_domain::ToolChoice) -> Self {
+        let (mode, allowed_function_names) = match value {
+            forge_domain::ToolChoice::Auto => (FunctionCallingMode::Auto, vec![]),
+            forge_domain::ToolChoice::Required => (FunctionCallingMode::Any, vec![]),
+            forge_domain::ToolChoice::None => (FunctionCallingMode::None, vec![]),
+            forge_domain::ToolChoice::Call(tool_name) => {
+                (FunctionCallingMode::Any, vec![tool_name.into_string()])
+            }
+        };
+        ToolConfig {
+            function_calling_config: FunctionCallingConfig { mode, allowed_function_names },
+        }
+    }
+}
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct GenerationConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_output_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_k: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_schema: Option<Value>,
+}
+
+impl GenerationConfig {
+    fn is_empty(&self) -> bool {
+        self.max_output_tokens.is_none()
+            && self.temperature.is_none()
+            && self.top_p.is_none()
+            && self.top_k.is_none()
+            && self.response_mime_type.is_none()
+    }
+}
+
+/// Keywords of the OpenAPI subset that Gemini accepts for schemas, the rest
+/// are rejected by the API.
+/// ref: https://ai.google.dev/api/caching#Schema
+const SCHEMA_KEYWORDS: [&str; 13] = [
+    "type",
+    "format",
+    "description",
+    "nullable",
+    "enum",
+    "items",
+    "properties",
+    "required",
+    "anyOf",
+    "minItems",
+    "maxItems",
+    "minimum",
+    "maximum",
+];
+const SCHEMA_FORMATS: [&str; 6] = ["enum", "date-time", "int32", "int64", "float", "double"];
+
+/// Depth at which references are no longer inlined, as Gemini doesn't support
+/// `$ref` and recursive types would otherwise never end.
+const MAX_SCHEMA_DEPTH: usize = 8;
+
+/// Converts a JSON schema into the subset of OpenAPI schemas Gemini accepts,
+/// inlining the references and expressing optional values as `nullable`.
+fn to_gemini_schema(schema: &Value) -> Value {
+    let definitions = schema
+        .get("definitions")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    convert_schema(schema, &definitions, 0)
+}
+
+fn convert_schema(schema: &Value, definitions: &Map<String, Value>, depth: usize) -> Value {
+    let Some(object) = schema.as_object() else {
+        // note: boolean schemas, ie: `true`, allow any value.
+        return json!({ "type": "object" });
+    };
+    if depth > MAX_SCHEMA_DEPTH {
+        return json!({ "type": "object" });
+    }
+
+    // note: schemars wraps references in `allOf` to attach a description.
+    let referenced = object
+        .get("$ref")
+        .and_then(Value::as_str)
+        .or_else(|| match object.get("allOf").and_then(Value::as_array) {
+            Some(all_of) if all_of.len() == 1 => all_of[0].get("$ref").and_then(Value::as_str),
+            _ => None,
+        })
+        .and_then(|reference| reference.strip_prefix("#/definitions/"))
+        .and_then(|name| definitions.get(name));
+    if let Some(referenced) = referenced {
+        let mut converted = convert_schema(referenced, definitions, depth + 1);
+        if let (Some(description), Some(converted)) =
+            (object.get("description"), converted.as_object_mut())
+        {
+            converted.insert("description".to_string(), description.clone());
+        }
+        return converted;
+    }
+
+    let mut converted = Map::new();
+    for (key, value) in object {
+        if !SCHEMA_KEYWORDS.contains(&key.as_str()) {
+            continue;
+        }
+        let value = match key.as_str() {
+            // note: optional values are typed as `["string", "null"]` by schemars.
+            "type" => match value.as_array() {
+                Some(types) => {
+                    if types.iter().any(|kind| kind == "null") {
+                        converted.insert("nullable".to_string(), Value::Bool(true));
+                    }
+                    match types.iter().find(|kind| *kind != "null") {
+                        Some(kind) => kind.clone(),
+                        None => continue,
+                    }
+                }
+                None => value.clone(),
+            },
+            "format" if !value.as_str().is_some_and(|f| SCHEMA_FORMATS.contains(&f)) => continue,
+            "items" => convert_schema(value, definitions, depth + 1),
+            "properties" => Value::Object(
+                value
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, property)| {
+                        (
+                            name.clone(),
+                            convert_schema(property, definitions, depth + 1),
+                        )
+                    })
+                    .collect(),
+            ),
+            "anyOf" => {
+                let mut variants = value
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|variant| {
+                        let is_null = variant.get("type").is_some_and(|kind| kind == "null");
+                        if is_null {
+                            converted.insert("nullable".to_string(), Value::Bool(true));
+                        }
+                        !is_null
+                    })
+                    .map(|variant| convert_schema(variant, definitions, depth + 1))
+                    .collect::<Vec<_>>();
+                // note: an optional value of a single type is inlined.
+                if variants.len() == 1 {
+                    if let Some(variant) = variants.pop().and_then(|v| v.as_object().cloned()) {
+                        converted.extend(variant);
+                    }
+                    continue;
+                }
+                Value::Array(variants)
+            }
+            _ => value.clone(),
+        };
+        converted.insert(key.clone(), value);
+    }
+    Value::Object(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_domain::{
+        Context, ContextMessage, ModelParameters, ToolCallFull, ToolCallId, ToolChoice,
+        ToolDefinition, ToolName, ToolResult,
+    };
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_request_conversion() {
+        let math: ToolDefinition = serde_json::from_value(json!({
+            "name": "math",
+            "description": "Evaluates an expression",
+            "input_schema": {
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "title": "MathInput",
+                "type": "object",
+                "required": ["expression"],
+                "properties": {
+                    "expression": {"description": "Expression to evaluate", "type": "string"},
+                    "precision": {"type": ["integer", "null"], "format": "uint32", "minimum": 0.0}
+                }
+            },
+            "output_schema": null
+        }))
+        .unwrap();
+        let context = Context::default()
+            .add_message(ContextMessage::system(
+                "You're expert at math, so you should resolve all user queries.",
+            ))
+            .add_message(ContextMessage::user("what's 2 + 2 and 3 + 3 ?"))
+            .add_message(ContextMessage::assistant(
+                "here are the system calls.",
+                Some(vec![
+                    ToolCallFull {
+                        name: ToolName::new("math"),
+                        call_id: None,
+                        arguments: json!({"expression": "2 + 2"}),
+                    },
+                    ToolCallFull {
+                        name: ToolName::new("math"),
+                        call_id: Some(ToolCallId::new("math-2")),
+                        arguments: json!({"expression": "3 + 3"}),
+                    },
+                ]),
+            ))
+            .add_tool_results(vec![
+                ToolResult {
+                    name: ToolName::new("math"),
+                    call_id: None,
+                    content: "4".to_string(),
+                    is_error: false,
+                    files: Vec::new(),
+                },
+                ToolResult {
+                    name: ToolName::new("math"),
+                    call_id: Some(ToolCallId::new("math-2")),
+                    content: "invalid expression".to_string(),
+                    is_error: true,
+                    files: Vec::new(),
+                },
+            ])
+            .add_tool(math)
+            .add_tool(ToolDefinition::new("clock").description("Current time"))
+            .tool_choice(ToolChoice::Call(ToolName::new("math")))
+            .parameters(ModelParameters::default().max_tokens(1024).top_k(40));
+
+        let request = Request::try_from(context).unwrap();
+        insta::assert_snapshot!(serde_json::to_string_pretty(&request).unwrap());
+    }
+
+    #[test]
+    fn test_schema_inlines_references() {
+        let fixture = json!({
+            "type": "object",
+            "properties": {
+                "range": {
+                    "description": "Lines to read",
+                    "allOf": [{"$ref": "#/definitions/Range"}]
+                },
+                "mode": {
+                    "anyOf": [{"$ref": "#/definitions/Mode"}, {"type": "null"}]
+                }
+            },
+            "definitions": {
+                "Range": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "properties": {"start": {"type": "integer", "format": "uint64"}}
+                },
+                "Mode": {"type": "string", "enum": ["read", "write"]}
+            }
+        });
+
+        let actual = to_gemini_schema(&fixture);
+
+        let expected = json!({
+            "type": "object",
+            "properties": {
+                "range": {
+                    "description": "Lines to read",
+                    "type": "object",
+                    "properties": {"start": {"type": "integer"}}
+                },
+                "mode": {"type": "string", "enum": ["read", "write"], "nullable": true}
+            }
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_schema_recursive_reference() {
+        let fixture = json!({
+            "$ref": "#/definitions/Node",
+            "definitions": {
+                "Node": {
+                    "type": "object",
+                    "properties": {"child": {"$ref": "#/definitions/Node"}}
+                }
+            }
+        });
+
+        let actual = to_gemini_schema(&fixture);
+
+        assert_eq!(actual["type"], "object");
+    }
+}
@@ -0,0 +1,336 @@
+use forge_domain::{
+    ChatCompletionMessage, Content, ModelCapabilities, ModelId, ToolCallFull, ToolCallId, ToolName,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListModelResponse {
+    #[serde(default)]
+    pub models: Vec<Model>,
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+    name: String,
+    display_name: String,
+    description: Option<String>,
+    input_token_limit: Option<u64>,
+    output_token_limit: Option<u32>,
+    #[serde(default)]
+    pub supported_generation_methods: Vec<String>,
+}
+
+impl Model {
+    fn id(&self) -> &str {
+        // note: names are prefixed with the collection, eg: `models/gemini-2.0-flash`.
+        self.name.strip_prefix("models/").unwrap_or(&self.name)
+    }
+
+    pub fn capabilities(&self) -> ModelCapabilities {
+        ModelCapabilities {
+            // note: the Gemma models served by the API don't support function calling.
+            supports_tools: !self.id().starts_with("gemma"),
+            supports_vision: true,
+            supports_json_mode: true,
+            max_output_tokens: self.output_token_limit,
+        }
+    }
+}
+
+impl From<Model> for forge_domain::Model {
+    fn from(value: Model) -> Self {
+        Self {
+            id: ModelId::new(value.id()),
+            capabilities: Some(value.capabilities()),
+            name: value.display_name,
+            description: value.description,
+            context_length: value.input_token_limit,
+            pricing: None,
+        }
+    }
+}
+
+/// Chunk of the `streamGenerateContent` API.
+/// ref: https://ai.google.dev/api/generate-content#generatecontentresponse
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    #[serde(default)]
+    pub candidates: Vec<Candidate>,
+    pub prompt_feedback: Option<PromptFeedback>,
+    pub usage_metadata: Option<UsageMetadata>,
+    pub error: Option<ErrorData>,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Candidate {
+    pub content: Option<CandidateContent>,
+    pub finish_reason: Option<FinishReason>,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+pub struct CandidateContent {
+    #[serde(default)]
+    pub parts: Vec<Part>,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Part {
+    pub text: Option<String>,
+    /// Set for the summaries of the model's thinking, which aren't part of
+    /// the answer
+    #[serde(default)]
+    pub thought: bool,
+    pub function_call: Option<FunctionCall>,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+pub struct FunctionCall {
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub args: Value,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptFeedback {
+    pub block_reason: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageMetadata {
+    #[serde(default)]
+    pub prompt_token_count: u64,
+    #[serde(default)]
+    pub candidates_token_count: u64,
+    #[serde(default)]
+    pub total_token_count: u64,
+    /// Prompt tokens served from the context cache, part of the prompt tokens
+    #[serde(default)]
+    pub cached_content_token_count: u64,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+pub struct ErrorData {
+    pub status: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, strum_macros::Display)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum FinishReason {
+    Stop,
+    MaxTokens,
+    Safety,
+    Recitation,
+    Language,
+    Other,
+    Blocklist,
+    ProhibitedContent,
+    Spii,
+    MalformedFunctionCall,
+    ImageSafety,
+    #[serde(other)]
+    Unknown,
+}
+
+impl FinishReason {
+    /// Checks if the response was cut off by Gemini's safety filters.
+    fn is_blocked(&self) -> bool {
+        matches!(
+            self,
+            FinishReason::Safety
+                | FinishReason::Recitation
+                | FinishReason::Blocklist
+                | FinishReason::ProhibitedContent
+                | FinishReason::Spii
+                | FinishReason::ImageSafety
+        )
+    }
+}
+
+impl From<FinishReason> for forge_domain::FinishReason {
+    fn from(value: FinishReason) -> Self {
+        match value {
+            FinishReason::MaxTokens => forge_domain::FinishReason::Length,
+            reason if reason.is_blocked() => forge_domain::FinishReason::ContentFilter,
+            _ => forge_domain::FinishReason::Stop,
+        }
+    }
+}
+
+impl From<UsageMetadata> for forge_domain::Usage {
+    fn from(usage: UsageMetadata) -> Self {
+        forge_domain::Usage {
+            prompt_tokens: usage.prompt_token_count,
+            completion_tokens: usage.candidates_token_count,
+            total_tokens: usage.total_token_count,
+            cached_tokens: usage.cached_content_token_count,
+        }
+    }
+}
+
+impl TryFrom<Response> for ChatCompletionMessage {
+    type Error = anyhow::Error;
+    fn try_from(value: Response) -> Result<Self, Self::Error> {
+        if let Some(error) = value.error {
+            return Err(anyhow::anyhow!(
+                "Gemini API error: {}: {}",
+                error.status.as_deref().unwrap_or("UNKNOWN"),
+                error.message
+            ));
+        }
+        if let Some(reason) = value.prompt_feedback.and_then(|f| f.block_reason) {
+            return Err(anyhow::anyhow!("Gemini blocked the prompt: {}", reason));
+        }
+
+        let mut message = ChatCompletionMessage::assistant(Content::part(""));
+        // note: only a single candidate is requested.
+        if let Some(candidate) = value.candidates.into_iter().next() {
+            let parts = candidate.content.map(|c| c.parts).unwrap_or_default();
+            let text = parts
+                .iter()
+                .filter(|part| !part.thought)
+                .filter_map(|part| part.text.as_deref())
+                .collect::<String>();
+            message = message.content_part(text);
+            // note: function calls are never split across chunks.
+            for function_call in parts.into_iter().filter_map(|part| part.function_call) {
+                message = message.add_tool_call(ToolCallFull {
+                    name: ToolName::new(function_call.name),
+                    call_id: function_call.id.map(ToolCallId::new),
+                    arguments: function_call.args,
+                });
+            }
+
+            if let Some(reason) = candidate.finish_reason {
+                if reason.is_blocked() || reason == FinishReason::MalformedFunctionCall {
+                    return Err(anyhow::anyhow!("Gemini stopped the response: {}", reason));
+                }
+                message = message.finish_reason(reason);
+            }
+        }
+        if let Some(usage) = value.usage_metadata {
+            message = message.usage(usage);
+        }
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_domain::ToolCall;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn parse(data: &str) -> anyhow::Result<ChatCompletionMessage> {
+        ChatCompletionMessage::try_from(serde_json::from_str::<Response>(data)?)
+    }
+
+    #[test]
+    fn test_text_chunk() {
+        let actual = parse(
+            r#"{"candidates":[{"content":{"parts":[{"text":"Let me think","thought":true},{"text":"Hello"}],"role":"model"},"index":0}],"modelVersion":"gemini-2.0-flash"}"#,
+        )
+        .unwrap();
+        let expected = ChatCompletionMessage::assistant(Content::part("Hello"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_function_call_chunk() {
+        let actual = parse(
+            r#"{"candidates":[{"content":{"parts":[{"functionCall":{"name":"math","args":{"expression":"2 + 2"}}},{"functionCall":{"name":"math","args":{"expression":"3 + 3"}}}],"role":"model"},"finishReason":"STOP","index":0}],"usageMetadata":{"promptTokenCount":10,"candidatesTokenCount":12,"totalTokenCount":22}}"#,
+        )
+        .unwrap();
+        let expected = ChatCompletionMessage::assistant(Content::part(""))
+            .add_tool_call(ToolCall::Full(ToolCallFull {
+                name: ToolName::new("math"),
+                call_id: None,
+                arguments: serde_json::json!({"expression": "2 + 2"}),
+            }))
+            .add_tool_call(ToolCall::Full(ToolCallFull {
+                name: ToolName::new("math"),
+                call_id: None,
+                arguments: serde_json::json!({"expression": "3 + 3"}),
+            }))
+            .finish_reason(forge_domain::FinishReason::Stop)
+            .usage(forge_domain::Usage {
+                prompt_tokens: 10,
+                completion_tokens: 12,
+                total_tokens: 22,
+                cached_tokens: 0,
+            });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_safety_finish_reason() {
+        let actual = parse(
+            r#"{"candidates":[{"content":{"parts":[{"text":""}],"role":"model"},"finishReason":"SAFETY","safetyRatings":[{"category":"HARM_CATEGORY_DANGEROUS_CONTENT","probability":"HIGH"}],"index":0}]}"#,
+        )
+        .unwrap_err()
+        .to_string();
+        assert_eq!(actual, "Gemini stopped the response: SAFETY");
+    }
+
+    #[test]
+    fn test_blocked_prompt() {
+        let actual = parse(r#"{"promptFeedback":{"blockReason":"PROHIBITED_CONTENT"}}"#)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(actual, "Gemini blocked the prompt: PROHIBITED_CONTENT");
+    }
+
+    #[test]
+    fn test_unknown_finish_reason() {
+        let actual = parse(
+            r#"{"candidates":[{"content":{"parts":[{"text":"Hi"}]},"finishReason":"SOMETHING_NEW"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(actual.finish_reason, Some(forge_domain::FinishReason::Stop));
+    }
+
+    #[test]
+    fn test_model_deser() {
+        let input = r#"{
+            "models": [
+                {
+                    "name": "models/gemini-2.0-flash",
+                    "displayName": "Gemini 2.0 Flash",
+                    "description": "Fast and versatile",
+                    "inputTokenLimit": 1048576,
+                    "outputTokenLimit": 8192,
+                    "supportedGenerationMethods": ["generateContent", "countTokens"]
+                }
+            ]
+        }"#;
+        let response = serde_json::from_str::<ListModelResponse>(input).unwrap();
+        let actual = forge_domain::Model::from(response.models.into_iter().next().unwrap());
+        assert_eq!(actual.id, ModelId::new("gemini-2.0-flash"));
+        assert_eq!(actual.context_length, Some(1048576));
+        assert_eq!(
+            actual.capabilities.and_then(|c| c.max_output_tokens),
+            Some(8192)
+        );
+    }
+
+    #[test]
+    fn test_gemma_capabilities() {
+        let model = serde_json::from_str::<Model>(
+            r#"{"name":"models/gemma-3-27b-it","displayName":"Gemma 3 27B","outputTokenLimit":8192}"#,
+        )
+        .unwrap();
+        assert!(!model.capabilities().supports_tools);
+    }
+}
@@ -1,16 +1,34 @@
 mod anthropic;
+mod bedrock;
+mod cache;
+mod gemini;
 mod open_router;
+mod redact;
+mod replay;
 
 use anthropic::Anthropic;
-use forge_domain::{Provider, ProviderService};
-use open_router::{OpenRouter, Provider as OpenRouterProvider};
+use bedrock::Bedrock;
+pub use cache::CachedProvider;
+use forge_domain::{bedrock_region, mask, Provider, ProviderService};
+use gemini::Gemini;
+use open_router::{Azure, AzureAuth, OpenRouter, Provider as OpenRouterProvider};
+use redact::Redacted;
+pub use replay::ReplayProvider;
 
-#[derive(Debug)]
 pub struct ProviderBuilder {
     url: String,
     api_key: Option<String>,
 }
 
+impl std::fmt::Debug for ProviderBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProviderBuilder")
+            .field("url", &self.url)
+            .field("api_key", &self.api_key.as_deref().map(mask))
+            .finish()
+    }
+}
+
 impl ProviderBuilder {
     pub fn from_url<S: Into<String>>(url: S) -> Self {
         Self { url: url.into(), api_key: None }
@@ -27,7 +45,14 @@ impl ProviderBuilder {
         let api_key = self
             .api_key
             .ok_or_else(|| anyhow::anyhow!("API key is required for provider: {}", provider))?;
-        Ok(match provider {
+        let secrets = [
+            Some(api_key.clone()),
+            std::env::var("AWS_SESSION_TOKEN").ok(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let provider: Box<dyn ProviderService> = match provider {
             Provider::OpenRouter => Box::new(
                 OpenRouter::builder()
                     .provider(OpenRouterProvider::OpenRouter)
@@ -40,12 +65,54 @@ impl ProviderBuilder {
                     .api_key(api_key)
                     .build()?,
             ),
+            Provider::AzureOpenAI => {
+                // note: an Azure AD token is used in place of the key when provided.
+                let auth =
+                    if std::env::var("AZURE_OPENAI_AD_TOKEN").is_ok_and(|token| token == api_key) {
+                        AzureAuth::AdToken
+                    } else {
+                        AzureAuth::ApiKey
+                    };
+                let mut azure = Azure::new(self.url.parse()?, auth);
+                if let Ok(api_version) = std::env::var("AZURE_OPENAI_API_VERSION") {
+                    azure.api_version = api_version;
+                }
+                Box::new(
+                    OpenRouter::builder()
+                        .provider(OpenRouterProvider::AzureOpenAI(azure))
+                        .api_key(api_key)
+                        .build()?,
+                )
+            }
             Provider::Anthropic => Box::new(
                 Anthropic::builder()
                     .api_key(api_key)
                     .base_url(self.url)
                     .build()?,
             ),
-        })
+            Provider::Gemini => Box::new(
+                Gemini::builder()
+                    .api_key(api_key)
+                    .base_url(self.url)
+                    .build()?,
+            ),
+            Provider::Bedrock => {
+                // note: the key is the secret access key, the rest of the AWS
+                // credentials are read from the environment.
+                let mut bedrock = Bedrock::builder().secret_access_key(api_key).access_key_id(
+                    std::env::var("AWS_ACCESS_KEY_ID").map_err(|_| {
+                        anyhow::anyhow!("AWS_ACCESS_KEY_ID is required for provider: {}", provider)
+                    })?,
+                );
+                if let Some(region) = bedrock_region(&self.url) {
+                    bedrock = bedrock.region(region);
+                }
+                if let Ok(session_token) = std::env::var("AWS_SESSION_TOKEN") {
+                    bedrock = bedrock.session_token(session_token);
+                }
+                Box::new(bedrock.build()?)
+            }
+        };
+        Ok(Box::new(Redacted::new(provider, secrets)))
     }
 }
@@ -1,8 +1,8 @@
 use anyhow::{Context as _, Result};
 use derive_setters::Setters;
 use forge_domain::{
-    self, ChatCompletionMessage, Context as ChatContext, Model, ModelId, Parameters,
-    ProviderService, ResultStream,
+    self, ChatCompletionMessage, Context as ChatContext, Model, ModelCapabilities, ModelId,
+    ProviderService, ResultStream, TextCompletion,
 };
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
 use reqwest::{Client, Url};
@@ -10,9 +10,9 @@ use reqwest_eventsource::{Event, RequestBuilderExt};
 use tokio_stream::StreamExt;
 use tracing::debug;
 
+use super::completion::{CompletionRequest, CompletionResponse};
 use super::model::{ListModelResponse, OpenRouterModel};
-use super::parameters::ParameterResponse;
-use super::provider::Provider;
+use super::provider::{AzureAuth, Provider};
 use super::request::OpenRouterRequest;
 use super::response::OpenRouterResponse;
 use crate::open_router::transformers::{ProviderPipeline, Transformer};
@@ -64,14 +64,42 @@ impl OpenRouter {
         })
     }
 
+    /// URL of the chat completions endpoint.
+    fn chat_url(&self, model_id: &ModelId) -> anyhow::Result<Url> {
+        self.model_url(model_id, "chat/completions")
+    }
+
+    /// URL of an endpoint of the model. On Azure the model is routed by the
+    /// name of its deployment and the API version is a query parameter.
+    fn model_url(&self, model_id: &ModelId, path: &str) -> anyhow::Result<Url> {
+        match &self.provider {
+            Provider::AzureOpenAI(azure) => {
+                let mut url =
+                    self.url(&format!("openai/deployments/{}/{path}", model_id.as_str()))?;
+                url.query_pairs_mut()
+                    .append_pair("api-version", &azure.api_version);
+                Ok(url)
+            }
+            _ => self.url(path),
+        }
+    }
+
     fn headers(&self) -> HeaderMap {
         let mut headers = HeaderMap::new();
 
         if let Some(ref api_key) = self.api_key {
-            headers.insert(
-                AUTHORIZATION,
-                HeaderValue::from_str(&format!("Bearer {}", api_key)).unwrap(),
-            );
+            match &self.provider {
+                // note: Azure expects its keys in the `api-key` header.
+                Provider::AzureOpenAI(azure) if azure.auth == AzureAuth::ApiKey => {
+                    headers.insert("api-key", HeaderValue::from_str(api_key).unwrap());
+                }
+                _ => {
+                    headers.insert(
+                        AUTHORIZATION,
+                        HeaderValue::from_str(&format!("Bearer {}", api_key)).unwrap(),
+                    );
+                }
+            }
         }
         headers.insert("X-Title", HeaderValue::from_static("code-forge"));
         headers
@@ -91,7 +119,7 @@ impl ProviderService for OpenRouter {
 
         request = ProviderPipeline::new(&self.provider).transform(request);
 
-        let url = self.url("chat/completions")?;
+        let url = self.chat_url(model_id)?;
         debug!(url = %url, model = %model_id, "Connecting to OpenRouter API");
         let es = self
             .client
@@ -109,14 +137,21 @@ impl ProviderService for OpenRouter {
                         Event::Message(event) if ["[DONE]", ""].contains(&event.data.as_str()) => {
                             None
                         }
-                        Event::Message(event) => Some(
-                            serde_json::from_str::<OpenRouterResponse>(&event.data)
+                        Event::Message(event) => {
+                            match serde_json::from_str::<OpenRouterResponse>(&event.data)
                                 .with_context(|| "Failed to parse OpenRouter response")
-                                .and_then(|message| {
-                                    ChatCompletionMessage::try_from(message.clone())
+                            {
+                                // note: Azure starts the stream with the results of its content
+                                // filter, which carry no choices.
+                                Ok(OpenRouterResponse::Success {
+                                    choices, usage: None, ..
+                                }) if choices.is_empty() => None,
+                                message => Some(message.and_then(|message| {
+                                    ChatCompletionMessage::try_from(message)
                                         .with_context(|| "Failed to create completion message")
-                                }),
-                        ),
+                                })),
+                            }
+                        }
                     },
                     Err(reqwest_eventsource::Error::StreamEnded) => None,
                     Err(reqwest_eventsource::Error::InvalidStatusCode(_, response)) => Some(
@@ -148,7 +183,33 @@ impl ProviderService for OpenRouter {
         Ok(Box::pin(stream.filter_map(|x| x)))
     }
 
+    async fn complete(&self, model_id: &ModelId, request: TextCompletion) -> Result<String> {
+        let url = self.model_url(model_id, "completions")?;
+        debug!(url = %url, model = %model_id, "Requesting a text completion");
+        let response = self
+            .client
+            .post(url)
+            .headers(self.headers())
+            .json(&CompletionRequest::new(model_id.clone(), request))
+            .send()
+            .await?;
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            anyhow::bail!("Failed with status {status}: {text}");
+        }
+        let response: CompletionResponse =
+            serde_json::from_str(&text).with_context(|| "Failed to parse the completion")?;
+        Ok(response.text())
+    }
+
     async fn models(&self) -> Result<Vec<Model>> {
+        // note: Azure deployments are named by the user and can't be listed with the
+        // API key of the resource.
+        if let Provider::AzureOpenAI(_) = self.provider {
+            return Ok(vec![]);
+        }
+
         let text = self
             .client
             .get(self.url("models")?)
@@ -169,52 +230,31 @@ impl ProviderService for OpenRouter {
             .collect::<Vec<Model>>())
     }
 
-    async fn parameters(&self, model: &ModelId) -> Result<Parameters> {
+    async fn capabilities(&self, model: &ModelId) -> Result<ModelCapabilities> {
         match self.provider {
-            Provider::OpenAI => {
-                // TODO: open-ai provider doesn't support parameters endpoint, so we return true
-                // for now.
-                return Ok(Parameters { tool_supported: true });
-            }
-            Provider::OpenRouter => {
-                // // For Eg: https://openrouter.ai/api/v1/parameters/google/gemini-pro-1.5-exp
-                let path = format!("parameters/{}", model.as_str());
-
-                let url = self.url(&path)?;
-
-                let text = self
-                    .client
-                    .get(url)
-                    .headers(self.headers())
-                    .send()
-                    .await?
-                    .error_for_status()?
-                    .text()
-                    .await?;
-
-                let response: ParameterResponse = serde_json::from_str(&text)
-                    .with_context(|| "Failed to parse parameter response".to_string())?;
-
-                Ok(Parameters {
-                    tool_supported: response
-                        .data
-                        .supported_parameters
-                        .iter()
-                        .flat_map(|parameter| parameter.iter())
-                        .any(|parameter| parameter == "tools"),
-                })
-            }
+            // note: OpenAI's listing doesn't describe the models, which all support tools.
+            Provider::OpenAI | Provider::AzureOpenAI(_) => Ok(ModelCapabilities::all()),
+            Provider::OpenRouter => self
+                .models()
+                .await?
+                .into_iter()
+                .find(|candidate| &candidate.id == model)
+                .and_then(|model| model.capabilities)
+                .ok_or_else(|| anyhow::anyhow!("Model not found in the listing: {}", model)),
         }
     }
 }
 
 impl From<OpenRouterModel> for Model {
     fn from(value: OpenRouterModel) -> Self {
+        let capabilities = value.capabilities();
         Model {
             id: value.id,
             name: value.name,
             description: value.description,
             context_length: Some(value.context_length),
+            capabilities,
+            pricing: value.pricing.try_into().ok(),
         }
     }
 }
@@ -224,6 +264,7 @@ mod tests {
     use anyhow::Context;
 
     use super::*;
+    use crate::open_router::provider::Azure;
 
     fn create_test_client() -> OpenRouter {
         OpenRouter {
@@ -290,6 +331,47 @@ mod tests {
         assert!(result.is_err());
     }
 
+    fn create_azure_client(auth: AzureAuth) -> OpenRouter {
+        OpenRouter {
+            client: Client::new(),
+            api_key: Some("secret".to_string()),
+            provider: Provider::AzureOpenAI(Azure::new(
+                "https://forge.openai.azure.com/".parse().unwrap(),
+                auth,
+            )),
+        }
+    }
+
+    #[test]
+    fn test_azure_chat_url() -> Result<()> {
+        let client = create_azure_client(AzureAuth::ApiKey);
+        let url = client.chat_url(&ModelId::new("gpt-4o-prod"))?;
+        assert_eq!(
+            url.as_str(),
+            "https://forge.openai.azure.com/openai/deployments/gpt-4o-prod/chat/completions?api-version=2024-10-21"
+        );
+        let url = client.model_url(&ModelId::new("codestral-prod"), "completions")?;
+        assert_eq!(
+            url.as_str(),
+            "https://forge.openai.azure.com/openai/deployments/codestral-prod/completions?api-version=2024-10-21"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_azure_api_key_header() {
+        let headers = create_azure_client(AzureAuth::ApiKey).headers();
+        assert_eq!(headers["api-key"], "secret");
+        assert!(!headers.contains_key(AUTHORIZATION));
+    }
+
+    #[test]
+    fn test_azure_ad_token_header() {
+        let headers = create_azure_client(AzureAuth::AdToken).headers();
+        assert_eq!(headers[AUTHORIZATION], "Bearer secret");
+        assert!(!headers.contains_key("api-key"));
+    }
+
     #[test]
     fn test_error_deserialization() -> Result<()> {
         let content = serde_json::to_string(&serde_json::json!({
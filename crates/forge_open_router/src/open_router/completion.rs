@@ -0,0 +1,83 @@
+use forge_domain::{ModelId, TextCompletion};
+use serde::{Deserialize, Serialize};
+
+/// Request of the text completions endpoint, which completes the prompt as
+/// is.
+#[derive(Debug, Serialize)]
+pub struct CompletionRequest {
+    model: ModelId,
+    prompt: String,
+    max_tokens: u32,
+    temperature: f32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop: Vec<String>,
+    stream: bool,
+}
+
+impl CompletionRequest {
+    pub fn new(model: ModelId, request: TextCompletion) -> Self {
+        Self {
+            model,
+            prompt: request.prompt,
+            max_tokens: request.max_tokens,
+            temperature: request.temperature,
+            stop: request.stop,
+            stream: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompletionResponse {
+    choices: Vec<CompletionChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompletionChoice {
+    text: String,
+}
+
+impl CompletionResponse {
+    /// The text of the first choice, the only one requested.
+    pub fn text(self) -> String {
+        self.choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.text)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_completion_request() {
+        let request = CompletionRequest::new(
+            ModelId::new("qwen/qwen-2.5-coder-32b-instruct"),
+            TextCompletion {
+                prompt: "<|fim_prefix|>a + <|fim_suffix|>;<|fim_middle|>".to_string(),
+                stop: vec!["<|endoftext|>".to_string()],
+                max_tokens: 256,
+                temperature: 0.5,
+            },
+        );
+        let actual = serde_json::to_value(&request).unwrap();
+        let expected = serde_json::json!({
+            "model": "qwen/qwen-2.5-coder-32b-instruct",
+            "prompt": "<|fim_prefix|>a + <|fim_suffix|>;<|fim_middle|>",
+            "max_tokens": 256,
+            "temperature": 0.5,
+            "stop": ["<|endoftext|>"],
+            "stream": false
+        });
+        assert_eq!(actual, expected);
+
+        let response: CompletionResponse =
+            serde_json::from_str(r#"{"choices": [{"text": "b", "index": 0}]}"#).unwrap();
+        assert_eq!(response.text(), "b");
+    }
+}
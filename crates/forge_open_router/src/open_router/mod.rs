@@ -1,6 +1,6 @@
+mod completion;
 mod error;
 mod model;
-mod parameters;
 mod provider;
 mod request;
 mod response;
@@ -9,4 +9,4 @@ mod transformers;
 
 mod api;
 pub use api::OpenRouter;
-pub use provider::Provider;
+pub use provider::{Azure, AzureAuth, Provider};
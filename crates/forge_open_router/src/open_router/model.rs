@@ -1,4 +1,4 @@
-use forge_domain::ModelId;
+use forge_domain::{ModelCapabilities, ModelId};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -12,6 +12,33 @@ pub struct OpenRouterModel {
     pub pricing: Pricing,
     pub top_provider: TopProvider,
     pub per_request_limits: Option<serde_json::Value>,
+    #[serde(default)]
+    pub supported_parameters: Option<Vec<String>>,
+}
+
+impl OpenRouterModel {
+    /// Capabilities of the model, unknown when the listing doesn't include
+    /// the parameters it supports.
+    pub fn capabilities(&self) -> Option<ModelCapabilities> {
+        let parameters = self.supported_parameters.as_ref()?;
+        let supports = |name: &str| parameters.iter().any(|parameter| parameter == name);
+        // note: the modality is described as `<input>-><output>`, eg:
+        // `text+image->text`.
+        let input = self
+            .architecture
+            .modality
+            .split_once("->")
+            .map_or("", |(input, _)| input);
+        Some(ModelCapabilities {
+            supports_tools: supports("tools"),
+            supports_vision: input.split('+').any(|modality| modality == "image"),
+            supports_json_mode: supports("response_format") || supports("structured_outputs"),
+            max_output_tokens: self
+                .top_provider
+                .max_completion_tokens
+                .and_then(|tokens| u32::try_from(tokens).ok()),
+        })
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -27,6 +54,23 @@ pub struct Pricing {
     pub completion: String,
     pub image: String,
     pub request: String,
+    pub input_cache_read: Option<String>,
+}
+
+impl TryFrom<Pricing> for forge_domain::Pricing {
+    type Error = std::num::ParseFloatError;
+
+    fn try_from(value: Pricing) -> Result<Self, Self::Error> {
+        Ok(Self {
+            prompt: value.prompt.parse()?,
+            completion: value.completion.parse()?,
+            request: value.request.parse()?,
+            cache_read: value
+                .input_cache_read
+                .map(|price| price.parse())
+                .transpose()?,
+        })
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -40,3 +84,56 @@ pub struct TopProvider {
 pub struct ListModelResponse {
     pub data: Vec<OpenRouterModel>,
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_capabilities() {
+        let fixture: OpenRouterModel = serde_json::from_value(serde_json::json!({
+            "id": "anthropic/claude-3.7-sonnet",
+            "name": "Claude 3.7 Sonnet",
+            "created": 1740422110,
+            "description": null,
+            "context_length": 200000,
+            "architecture": {"modality": "text+image->text", "tokenizer": "Claude", "instruct_type": null},
+            "pricing": {"prompt": "0.000003", "completion": "0.000015", "image": "0.0048", "request": "0"},
+            "top_provider": {"context_length": 200000, "max_completion_tokens": 64000, "is_moderated": false},
+            "per_request_limits": null,
+            "supported_parameters": ["max_tokens", "temperature", "tools", "tool_choice"]
+        }))
+        .unwrap();
+
+        let actual = fixture.capabilities();
+        let expected = Some(ModelCapabilities {
+            supports_tools: true,
+            supports_vision: true,
+            supports_json_mode: false,
+            max_output_tokens: Some(64000),
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_pricing_conversion() {
+        let fixture = Pricing {
+            prompt: "0.000003".to_string(),
+            completion: "0.000015".to_string(),
+            image: "0.0048".to_string(),
+            request: "0".to_string(),
+            input_cache_read: Some("0.0000003".to_string()),
+        };
+
+        let actual = forge_domain::Pricing::try_from(fixture).unwrap();
+        let expected = forge_domain::Pricing {
+            prompt: 0.000003,
+            completion: 0.000015,
+            request: 0.0,
+            cache_read: Some(0.0000003),
+        };
+        assert_eq!(actual, expected);
+    }
+}
@@ -1,15 +1,45 @@
 use reqwest::Url;
 
+/// Default `api-version` of the Azure OpenAI data plane API.
+const AZURE_API_VERSION: &str = "2024-10-21";
+
 /// A underlying provider for the open router.
 #[derive(Clone, Debug)]
 pub enum Provider {
     OpenAI,
     OpenRouter,
+    /// Azure OpenAI resource, where the model is the name of a deployment
+    AzureOpenAI(Azure),
+}
+
+/// Configuration of an Azure OpenAI resource.
+#[derive(Clone, Debug)]
+pub struct Azure {
+    pub endpoint: Url,
+    pub api_version: String,
+    pub auth: AzureAuth,
+}
+
+impl Azure {
+    pub fn new(endpoint: Url, auth: AzureAuth) -> Self {
+        Self { endpoint, api_version: AZURE_API_VERSION.to_string(), auth }
+    }
+}
+
+/// How requests to Azure OpenAI are authenticated, the credential itself is
+/// the API key of the client.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AzureAuth {
+    /// Key of the resource, sent in the `api-key` header
+    ApiKey,
+    /// Microsoft Entra ID (Azure AD) access token, sent as a bearer token
+    AdToken,
 }
 
 impl Provider {
+    /// Whether the provider accepts requests in the format of the OpenAI API.
     pub fn is_openai(&self) -> bool {
-        matches!(self, Self::OpenAI)
+        matches!(self, Self::OpenAI | Self::AzureOpenAI(_))
     }
 
     pub fn is_open_router(&self) -> bool {
@@ -20,6 +50,7 @@ impl Provider {
         match self {
             Self::OpenAI => "https://api.openai.com/v1/".parse().unwrap(),
             Self::OpenRouter => "https://openrouter.ai/api/v1/".parse().unwrap(),
+            Self::AzureOpenAI(azure) => azure.endpoint.clone(),
         }
     }
 }
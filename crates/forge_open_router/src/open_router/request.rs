@@ -99,9 +99,65 @@ pub struct OpenRouterTool {
     pub function: FunctionDescription,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct ResponseFormat {
-    pub r#type: String,
+/// ref: https://openrouter.ai/docs/features/structured-outputs
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    JsonObject,
+    JsonSchema { json_schema: JsonSchemaFormat },
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct JsonSchemaFormat {
+    pub name: String,
+    pub strict: bool,
+    pub schema: serde_json::Value,
+}
+
+impl From<serde_json::Value> for ResponseFormat {
+    fn from(schema: serde_json::Value) -> Self {
+        ResponseFormat::JsonSchema {
+            json_schema: JsonSchemaFormat {
+                name: "output".to_string(),
+                strict: is_strict(&schema),
+                schema,
+            },
+        }
+    }
+}
+
+/// Whether the schema meets the rules of the strict mode, which rejects the
+/// schemas with objects that allow other properties or have optional ones.
+fn is_strict(schema: &serde_json::Value) -> bool {
+    match schema {
+        serde_json::Value::Object(schema) => {
+            let is_object = schema.get("type").and_then(|kind| kind.as_str()) == Some("object")
+                || schema.contains_key("properties");
+            if is_object {
+                let required = schema
+                    .get("required")
+                    .and_then(|required| required.as_array())
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                let all_required = schema
+                    .get("properties")
+                    .and_then(|properties| properties.as_object())
+                    .is_none_or(|properties| {
+                        properties
+                            .keys()
+                            .all(|name| required.iter().any(|required| required == name))
+                    });
+                let closed =
+                    schema.get("additionalProperties") == Some(&serde_json::Value::Bool(false));
+                if !all_required || !closed {
+                    return false;
+                }
+            }
+            schema.values().all(is_strict)
+        }
+        serde_json::Value::Array(items) => items.iter().all(is_strict),
+        _ => true,
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -219,15 +275,15 @@ impl From<Context> for OpenRouterRequest {
             },
             model: None,
             prompt: Default::default(),
-            response_format: Default::default(),
+            response_format: request.output_schema.map(ResponseFormat::from),
             stop: Default::default(),
             stream: Default::default(),
-            max_tokens: Default::default(),
-            temperature: Default::default(),
+            max_tokens: request.parameters.max_tokens,
+            temperature: request.parameters.temperature,
             tool_choice: request.tool_choice.map(|tc| tc.into()),
             seed: Default::default(),
-            top_p: Default::default(),
-            top_k: Default::default(),
+            top_p: request.parameters.top_p,
+            top_k: request.parameters.top_k,
             frequency_penalty: Default::default(),
             presence_penalty: Default::default(),
             repetition_penalty: Default::default(),
@@ -248,6 +304,7 @@ impl From<Context> for OpenRouterRequest {
 impl From<ToolCallFull> for OpenRouterToolCall {
     fn from(value: ToolCallFull) -> Self {
         Self {
+            index: None,
             id: value.call_id,
             r#type: FunctionType,
             function: FunctionCall {
@@ -263,7 +320,19 @@ impl From<ContextMessage> for OpenRouterMessage {
         match value {
             ContextMessage::ContentMessage(chat_message) => OpenRouterMessage {
                 role: chat_message.role.into(),
-                content: Some(MessageContent::Text(chat_message.content)),
+                content: Some(if chat_message.images.is_empty() {
+                    MessageContent::Text(chat_message.content)
+                } else {
+                    let text =
+                        ContentPart::Text { text: chat_message.content, cache_control: None };
+                    let images = chat_message
+                        .images
+                        .iter()
+                        .map(|image| ContentPart::ImageUrl {
+                            image_url: ImageUrl { url: image.data_url(), detail: None },
+                        });
+                    MessageContent::Parts(std::iter::once(text).chain(images).collect())
+                }),
                 name: None,
                 tool_call_id: None,
                 tool_calls: chat_message.tool_calls.map(|tool_calls| {
@@ -306,7 +375,7 @@ pub enum OpenRouterRole {
 #[cfg(test)]
 mod tests {
     use forge_domain::{
-        ContentMessage, ContextMessage, Role, ToolCallFull, ToolCallId, ToolName, ToolResult,
+        ContentMessage, ContextMessage, Image, Role, ToolCallFull, ToolCallId, ToolName, ToolResult,
     };
     use insta::assert_json_snapshot;
     use serde_json::json;
@@ -319,11 +388,22 @@ mod tests {
             role: Role::User,
             content: "Hello".to_string(),
             tool_calls: None,
+            images: vec![],
         });
         let router_message = OpenRouterMessage::from(user_message);
         assert_json_snapshot!(router_message);
     }
 
+    #[test]
+    fn test_user_message_with_image_conversion() {
+        let user_message = ContextMessage::user_with_images(
+            "What's wrong in this screenshot?",
+            vec![Image::new("image/png", "iVBORw0KGgo=")],
+        );
+        let router_message = OpenRouterMessage::from(user_message);
+        assert_json_snapshot!(router_message);
+    }
+
     #[test]
     fn test_message_with_special_chars() {
         let xml_content = r#"Here's some XML content:
@@ -340,6 +420,7 @@ mod tests {
             role: Role::User,
             content: xml_content.to_string(),
             tool_calls: None,
+            images: vec![],
         });
         let router_message = OpenRouterMessage::from(message);
         assert_json_snapshot!(router_message);
@@ -357,6 +438,7 @@ mod tests {
             role: Role::Assistant,
             content: "Using tool".to_string(),
             tool_calls: Some(vec![tool_call]),
+            images: vec![],
         });
         let router_message = OpenRouterMessage::from(assistant_message);
         assert_json_snapshot!(router_message);
@@ -410,6 +492,60 @@ mod tests {
         assert_json_snapshot!(router_message);
     }
 
+    #[test]
+    fn test_response_format_from_output_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "summary": {"type": "string"},
+                "files": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {"path": {"type": "string"}},
+                        "required": ["path"],
+                        "additionalProperties": false,
+                    },
+                },
+            },
+            "required": ["summary", "files"],
+            "additionalProperties": false,
+        });
+        let context = forge_domain::Context::default().output_schema(schema.clone());
+
+        let actual =
+            serde_json::to_value(OpenRouterRequest::from(context).response_format).unwrap();
+        let expected = json!({
+            "type": "json_schema",
+            "json_schema": {"name": "output", "strict": true, "schema": schema},
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_response_format_not_strict() {
+        let fixtures = [
+            json!({"type": "object", "properties": {"summary": {"type": "string"}}}),
+            json!({
+                "type": "object",
+                "properties": {"summary": {"type": "string"}, "notes": {"type": "string"}},
+                "required": ["summary"],
+                "additionalProperties": false,
+            }),
+            json!({
+                "type": "array",
+                "items": {"type": "object", "properties": {}, "additionalProperties": true},
+            }),
+        ];
+        for schema in fixtures {
+            let actual = match ResponseFormat::from(schema.clone()) {
+                ResponseFormat::JsonSchema { json_schema } => json_schema.strict,
+                ResponseFormat::JsonObject => true,
+            };
+            assert!(!actual, "{schema}");
+        }
+    }
+
     #[test]
     fn test_transform_display() {
         assert_eq!(
@@ -34,6 +34,13 @@ pub struct ResponseUsage {
     pub prompt_tokens: u64,
     pub completion_tokens: u64,
     pub total_tokens: u64,
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PromptTokensDetails {
+    #[serde(default)]
+    pub cached_tokens: u64,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -88,6 +95,9 @@ pub struct ResponseMessage {
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct OpenRouterToolCall {
+    /// Position of the call among the parallel calls of a streamed response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<u32>,
     pub id: Option<ToolCallId>,
     pub r#type: FunctionType,
     pub function: FunctionCall,
@@ -106,6 +116,10 @@ impl From<ResponseUsage> for Usage {
             prompt_tokens: usage.prompt_tokens,
             completion_tokens: usage.completion_tokens,
             total_tokens: usage.total_tokens,
+            cached_tokens: usage
+                .prompt_tokens_details
+                .map(|details| details.cached_tokens)
+                .unwrap_or_default(),
         }
     }
 }
@@ -163,6 +177,7 @@ impl TryFrom<OpenRouterResponse> for ModelResponse {
                             if let Some(tool_calls) = &delta.tool_calls {
                                 for tool_call in tool_calls {
                                     resp = resp.add_tool_call(ToolCallPart {
+                                        index: tool_call.index.unwrap_or_default(),
                                         call_id: tool_call.id.clone(),
                                         name: tool_call.function.name.clone(),
                                         arguments_part: tool_call.function.arguments.clone(),
@@ -177,6 +192,9 @@ impl TryFrom<OpenRouterResponse> for ModelResponse {
                         response.usage = Some(usage.into());
                     }
                     Ok(response)
+                } else if let Some(usage) = usage {
+                    // note: the usage of a stream is sent in a final chunk without choices.
+                    Ok(ModelResponse::assistant(Content::part("")).usage(Usage::from(usage)))
                 } else {
                     Err(Error::EmptyContent)
                 }
@@ -219,4 +237,18 @@ mod tests {
         let event = "{\"id\":\"gen-1739949430-JZMcABaj4fg8oFDtRNDZ\",\"provider\":\"OpenAI\",\"model\":\"openai/gpt-4o-mini\",\"object\":\"chat.completion.chunk\",\"created\":1739949430,\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":null,\"tool_calls\":[{\"index\":0,\"id\":\"call_bhjvz9w48ov4DSRhM15qLMmh\",\"type\":\"function\",\"function\":{\"name\":\"tool_forge_process_shell\",\"arguments\":\"\"}}],\"refusal\":null},\"logprobs\":null,\"finish_reason\":null,\"native_finish_reason\":null}],\"system_fingerprint\":\"fp_00428b782a\"}";
         assert!(Fixture::test_response_compatibility(event));
     }
+
+    #[test]
+    fn test_usage_with_cached_tokens() {
+        let event = r#"{"id":"chatcmpl-1","object":"chat.completion.chunk","created":1739949029,"model":"gpt-4o","choices":[],"usage":{"prompt_tokens":2006,"completion_tokens":300,"total_tokens":2306,"prompt_tokens_details":{"cached_tokens":1920}}}"#;
+        let response = serde_json::from_str::<OpenRouterResponse>(event).unwrap();
+        let actual = ChatCompletionMessage::try_from(response).unwrap().usage;
+        let expected = Some(Usage {
+            prompt_tokens: 2006,
+            completion_tokens: 300,
+            total_tokens: 2306,
+            cached_tokens: 1920,
+        });
+        assert_eq!(actual, expected);
+    }
 }
@@ -54,11 +54,14 @@ mod tests {
                     role: Role::Assistant,
                     content: "Using tool".to_string(),
                     tool_calls: Some(vec![tool_call]),
+                    images: vec![],
                 }),
                 ContextMessage::ToolMessage(tool_result),
             ],
             tools: vec![],
             tool_choice: None,
+            parameters: Default::default(),
+            output_schema: None,
         };
 
         let request = OpenRouterRequest::from(context);
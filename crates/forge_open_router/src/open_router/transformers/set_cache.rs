@@ -1,12 +1,19 @@
 use crate::open_router::request::{OpenRouterRequest, OpenRouterRole};
 use crate::open_router::transformers::Transformer;
 
-/// Transformer that caches the last user/system message for supported models
+/// Transformer that caches the system prompt, which is stable across the
+/// conversation, and the last user/system message for supported models
 pub struct SetCache;
 
 impl Transformer for SetCache {
     fn transform(&self, mut request: OpenRouterRequest) -> OpenRouterRequest {
         if let (Some(mut messages), Some(model)) = (request.messages.take(), request.model.take()) {
+            if let Some(msg) = messages
+                .iter_mut()
+                .find(|msg| matches!(msg.role, OpenRouterRole::System))
+            {
+                msg.content = msg.content.take().map(|content| content.cached());
+            }
             if let Some(msg) = messages
                 .iter_mut()
                 .rev()
@@ -25,6 +32,7 @@ impl Transformer for SetCache {
 #[cfg(test)]
 mod tests {
     use forge_domain::{ContentMessage, Context, ContextMessage, ModelId, Role};
+    use pretty_assertions::assert_eq;
 
     use super::*;
     use crate::open_router::request::MessageContent;
@@ -36,9 +44,12 @@ mod tests {
                 role: Role::User,
                 content: "test message".to_string(),
                 tool_calls: None,
+                images: vec![],
             })],
             tools: vec![],
             tool_choice: None,
+            parameters: Default::default(),
+            output_schema: None,
         };
 
         let request =
@@ -53,4 +64,23 @@ mod tests {
             Some(MessageContent::Parts(_))
         ));
     }
+
+    #[test]
+    fn test_caches_system_prompt() {
+        let context = Context::default()
+            .add_message(ContextMessage::system("You're a software engineer"))
+            .add_message(ContextMessage::user("first message"))
+            .add_message(ContextMessage::assistant("answer", None))
+            .add_message(ContextMessage::user("second message"));
+
+        let request =
+            OpenRouterRequest::from(context).model(ModelId::new("anthropic/claude-3.5-sonnet"));
+        let messages = SetCache.transform(request).messages.unwrap();
+
+        let actual = messages
+            .iter()
+            .map(|message| matches!(message.content, Some(MessageContent::Parts(_))))
+            .collect::<Vec<_>>();
+        assert_eq!(actual, vec![true, false, false, true]);
+    }
 }
@@ -0,0 +1,145 @@
+use std::sync::Arc;
+
+use forge_domain::{
+    redact, ChatCompletionMessage, Context, Model, ModelCapabilities, ModelId, ProviderService,
+    ResultStream, TextCompletion,
+};
+use tokio_stream::StreamExt;
+
+/// Provider whose errors are stripped of its secrets, since they can be part
+/// of the URL of a failed request or echoed back in the provider's response.
+pub struct Redacted {
+    provider: Box<dyn ProviderService>,
+    secrets: Arc<Vec<String>>,
+}
+
+impl Redacted {
+    pub fn new(provider: Box<dyn ProviderService>, secrets: Vec<String>) -> Self {
+        let secrets = secrets
+            .into_iter()
+            .filter(|secret| !secret.is_empty())
+            .collect();
+        Self { provider, secrets: Arc::new(secrets) }
+    }
+}
+
+fn redact_error(secrets: &[String], error: anyhow::Error) -> anyhow::Error {
+    let message = format!("{error:#}");
+    let redacted = redact(&message, secrets.iter().map(String::as_str));
+    // note: the error is only replaced when needed, to keep its type.
+    if redacted == message {
+        error
+    } else {
+        anyhow::anyhow!(redacted)
+    }
+}
+
+#[async_trait::async_trait]
+impl ProviderService for Redacted {
+    async fn chat(
+        &self,
+        id: &ModelId,
+        context: Context,
+    ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+        let stream = self
+            .provider
+            .chat(id, context)
+            .await
+            .map_err(|error| redact_error(&self.secrets, error))?;
+        let secrets = self.secrets.clone();
+        Ok(Box::pin(stream.map(move |message| {
+            message.map_err(|error| redact_error(&secrets, error))
+        })))
+    }
+
+    async fn complete(&self, id: &ModelId, request: TextCompletion) -> anyhow::Result<String> {
+        self.provider
+            .complete(id, request)
+            .await
+            .map_err(|error| redact_error(&self.secrets, error))
+    }
+
+    async fn models(&self) -> anyhow::Result<Vec<Model>> {
+        self.provider
+            .models()
+            .await
+            .map_err(|error| redact_error(&self.secrets, error))
+    }
+
+    async fn capabilities(&self, model: &ModelId) -> anyhow::Result<ModelCapabilities> {
+        self.provider
+            .capabilities(model)
+            .await
+            .map_err(|error| redact_error(&self.secrets, error))
+    }
+
+    async fn reserve(&self, tokens: u64) -> std::time::Duration {
+        self.provider.reserve(tokens).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    struct Stub;
+
+    #[async_trait::async_trait]
+    impl ProviderService for Stub {
+        async fn chat(
+            &self,
+            _id: &ModelId,
+            _context: Context,
+        ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+            Ok(Box::pin(tokio_stream::iter([Err(anyhow::anyhow!(
+                "Incorrect API key provided: sk-proj-0123456789abcdef"
+            ))])))
+        }
+
+        async fn models(&self) -> anyhow::Result<Vec<Model>> {
+            Err(anyhow::anyhow!(
+                "Request to https://api.example.com/models?key=sk-proj-0123456789abcdef failed"
+            ))
+        }
+
+        async fn capabilities(&self, _model: &ModelId) -> anyhow::Result<ModelCapabilities> {
+            Err(anyhow::anyhow!("Unknown model"))
+        }
+    }
+
+    fn fixture() -> Redacted {
+        Redacted::new(Box::new(Stub), vec!["sk-proj-0123456789abcdef".to_string()])
+    }
+
+    #[tokio::test]
+    async fn test_chat_error_is_redacted() {
+        let mut stream = fixture()
+            .chat(&ModelId::new("gpt-4o"), Context::default())
+            .await
+            .unwrap();
+
+        let actual = stream.next().await.unwrap().unwrap_err().to_string();
+        assert_eq!(actual, "Incorrect API key provided: sk-...cdef");
+    }
+
+    #[tokio::test]
+    async fn test_models_error_is_redacted() {
+        let actual = fixture().models().await.unwrap_err().to_string();
+        assert_eq!(
+            actual,
+            "Request to https://api.example.com/models?key=sk-...cdef failed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_without_secret_is_kept() {
+        let actual = fixture()
+            .capabilities(&ModelId::new("gpt-4o"))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert_eq!(actual, "Unknown model");
+    }
+}
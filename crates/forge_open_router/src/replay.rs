@@ -0,0 +1,421 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context as _;
+use forge_domain::{
+    ChatCompletionMessage, Context, ContextMessage, Model, ModelCapabilities, ModelId,
+    ProviderService, ResultStream, Role, TextCompletion,
+};
+use serde::{Deserialize, Serialize};
+use tokio_stream::StreamExt;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// A request made to the provider along with its response, stored as a line
+/// of JSON in the recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+enum Interaction {
+    Chat {
+        model: ModelId,
+        context: Context,
+        response: Vec<ChatCompletionMessage>,
+        /// Set when the request or its stream failed
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    Models {
+        models: Vec<Model>,
+    },
+    Capabilities {
+        model: ModelId,
+        capabilities: ModelCapabilities,
+    },
+    Completion {
+        model: ModelId,
+        request: TextCompletion,
+        response: String,
+        /// Set when the request failed
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+}
+
+/// Provider that either records all the traffic of another provider to a
+/// file, or replays a recording without making any request. Replaying makes
+/// tests and bug reports independent of live APIs and keys.
+pub struct ReplayProvider {
+    mode: Mode,
+}
+
+enum Mode {
+    Record {
+        provider: Box<dyn ProviderService>,
+        recorder: Arc<Recorder>,
+    },
+    Replay {
+        interactions: Mutex<Vec<Option<Interaction>>>,
+    },
+}
+
+struct Recorder {
+    file: Mutex<File>,
+    /// Removed from everything that is recorded, eg: the provider's key
+    secrets: Vec<String>,
+}
+
+impl Recorder {
+    fn write(&self, interaction: &Interaction) -> anyhow::Result<()> {
+        let mut line = serde_json::to_string(interaction)?;
+        for secret in self.secrets.iter() {
+            line = line.replace(secret, REDACTED);
+        }
+        let mut file = self.file.lock().unwrap();
+        writeln!(file, "{line}")?;
+        Ok(())
+    }
+}
+
+impl ReplayProvider {
+    /// Records the traffic of `provider` to the file at `path`, replacing the
+    /// `secrets` that appear in it.
+    pub fn record(
+        provider: Box<dyn ProviderService>,
+        path: impl AsRef<Path>,
+        secrets: Vec<String>,
+    ) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(path)
+            .with_context(|| format!("Failed to create recording: {}", path.display()))?;
+        let secrets = secrets
+            .into_iter()
+            .filter(|secret| !secret.is_empty())
+            .collect();
+        Ok(Self {
+            mode: Mode::Record {
+                provider,
+                recorder: Arc::new(Recorder { file: Mutex::new(file), secrets }),
+            },
+        })
+    }
+
+    /// Replays the recording at `path`.
+    pub fn replay(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read recording: {}", path.display()))?;
+        let interactions = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(index, line)| {
+                serde_json::from_str(line).with_context(|| {
+                    format!("Invalid interaction on line {} of the recording", index + 1)
+                })
+            })
+            .collect::<anyhow::Result<Vec<Interaction>>>()?;
+        Ok(Self {
+            mode: Mode::Replay {
+                interactions: Mutex::new(interactions.into_iter().map(Some).collect()),
+            },
+        })
+    }
+}
+
+/// Messages of the context the response depends on. The system prompt is
+/// left out since it includes details of the machine, eg: the current time.
+fn conversation(context: &Context) -> Vec<&ContextMessage> {
+    context
+        .messages
+        .iter()
+        .filter(|message| !message.has_role(Role::System))
+        .collect()
+}
+
+#[async_trait::async_trait]
+impl ProviderService for ReplayProvider {
+    async fn chat(
+        &self,
+        id: &ModelId,
+        context: Context,
+    ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+        match &self.mode {
+            Mode::Record { provider, recorder } => {
+                let stream = match provider.chat(id, context.clone()).await {
+                    Ok(stream) => stream,
+                    Err(error) => {
+                        recorder.write(&Interaction::Chat {
+                            model: id.clone(),
+                            context,
+                            response: vec![],
+                            error: Some(format!("{error:#}")),
+                        })?;
+                        return Err(error);
+                    }
+                };
+
+                // note: the interaction is written once the stream ends, so that the
+                // response is still streamed while recording.
+                let response = Arc::new(Mutex::new((Vec::new(), None)));
+                let tap = response.clone();
+                let stream = stream.map(move |message| {
+                    let mut response = tap.lock().unwrap();
+                    match &message {
+                        Ok(message) => response.0.push(message.clone()),
+                        Err(error) => response.1 = Some(format!("{error:#}")),
+                    }
+                    Some(message)
+                });
+                let recorder = recorder.clone();
+                let model = id.clone();
+                let end = futures::stream::once(async move {
+                    let (response, error) = std::mem::take(&mut *response.lock().unwrap());
+                    recorder
+                        .write(&Interaction::Chat { model, context, response, error })
+                        .err()
+                        .map(Err)
+                });
+                Ok(Box::pin(stream.chain(end).filter_map(|message| message)))
+            }
+            Mode::Replay { interactions } => {
+                let mut interactions = interactions.lock().unwrap();
+                // note: agents run in parallel, so requests are matched by their
+                // conversation rather than by the order they were recorded in.
+                let interaction = interactions
+                    .iter_mut()
+                    .find(|interaction| {
+                        matches!(
+                            interaction,
+                            Some(Interaction::Chat { model, context: recorded, .. })
+                                if model == id && conversation(recorded) == conversation(&context)
+                        )
+                    })
+                    .and_then(Option::take);
+                let Some(Interaction::Chat { response, error, .. }) = interaction else {
+                    anyhow::bail!(
+                        "Replay diverged from the recording: no response recorded for a request to {}",
+                        id
+                    );
+                };
+                let stream = tokio_stream::iter(response.into_iter().map(Ok)).chain(
+                    tokio_stream::iter(error.map(|error| Err(anyhow::anyhow!(error)))),
+                );
+                Ok(Box::pin(stream))
+            }
+        }
+    }
+
+    async fn complete(&self, id: &ModelId, request: TextCompletion) -> anyhow::Result<String> {
+        match &self.mode {
+            Mode::Record { provider, recorder } => {
+                let result = provider.complete(id, request.clone()).await;
+                let (response, error) = match &result {
+                    Ok(response) => (response.clone(), None),
+                    Err(error) => (String::new(), Some(format!("{error:#}"))),
+                };
+                recorder.write(&Interaction::Completion {
+                    model: id.clone(),
+                    request,
+                    response,
+                    error,
+                })?;
+                result
+            }
+            Mode::Replay { interactions } => {
+                let interaction = interactions
+                    .lock()
+                    .unwrap()
+                    .iter_mut()
+                    .find(|interaction| {
+                        matches!(
+                            interaction,
+                            Some(Interaction::Completion { model, request: recorded, .. })
+                                if model == id && recorded.prompt == request.prompt
+                        )
+                    })
+                    .and_then(Option::take);
+                match interaction {
+                    Some(Interaction::Completion { error: Some(error), .. }) => {
+                        Err(anyhow::anyhow!(error))
+                    }
+                    Some(Interaction::Completion { response, .. }) => Ok(response),
+                    _ => anyhow::bail!(
+                        "Replay diverged from the recording: no completion recorded for a request to {}",
+                        id
+                    ),
+                }
+            }
+        }
+    }
+
+    async fn models(&self) -> anyhow::Result<Vec<Model>> {
+        match &self.mode {
+            Mode::Record { provider, recorder } => {
+                let models = provider.models().await?;
+                recorder.write(&Interaction::Models { models: models.clone() })?;
+                Ok(models)
+            }
+            Mode::Replay { interactions } => interactions
+                .lock()
+                .unwrap()
+                .iter()
+                .find_map(|interaction| match interaction {
+                    Some(Interaction::Models { models }) => Some(models.clone()),
+                    _ => None,
+                })
+                .ok_or_else(|| anyhow::anyhow!("No models in the recording")),
+        }
+    }
+
+    async fn capabilities(&self, model: &ModelId) -> anyhow::Result<ModelCapabilities> {
+        match &self.mode {
+            Mode::Record { provider, recorder } => {
+                let capabilities = provider.capabilities(model).await?;
+                recorder
+                    .write(&Interaction::Capabilities { model: model.clone(), capabilities })?;
+                Ok(capabilities)
+            }
+            Mode::Replay { interactions } => interactions
+                .lock()
+                .unwrap()
+                .iter()
+                .find_map(|interaction| match interaction {
+                    Some(Interaction::Capabilities { model: recorded, capabilities })
+                        if recorded == model =>
+                    {
+                        Some(*capabilities)
+                    }
+                    _ => None,
+                })
+                .ok_or_else(|| anyhow::anyhow!("No capabilities of {} in the recording", model)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_domain::{Content, FinishReason, ToolCallFull, ToolName};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    struct Stub;
+
+    #[async_trait::async_trait]
+    impl ProviderService for Stub {
+        async fn chat(
+            &self,
+            _id: &ModelId,
+            context: Context,
+        ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+            // The first request, with the system and user messages, calls a tool
+            let messages = match context.messages.len() {
+                2 => vec![
+                    ChatCompletionMessage::assistant(Content::part("Reading")),
+                    ChatCompletionMessage::assistant(Content::part("")).add_tool_call(
+                        ToolCallFull::new(ToolName::new("tool_forge_fs_read"))
+                            .arguments(serde_json::json!({"path": "juniper.md"})),
+                    ),
+                ],
+                _ => vec![
+                    ChatCompletionMessage::assistant(Content::part("The key is sk-secret"))
+                        .finish_reason(FinishReason::Stop),
+                ],
+            };
+            Ok(Box::pin(tokio_stream::iter(messages.into_iter().map(Ok))))
+        }
+
+        async fn models(&self) -> anyhow::Result<Vec<Model>> {
+            Ok(vec![])
+        }
+
+        async fn capabilities(&self, _model: &ModelId) -> anyhow::Result<ModelCapabilities> {
+            Ok(ModelCapabilities::all())
+        }
+    }
+
+    async fn chat(
+        provider: &ReplayProvider,
+        context: Context,
+    ) -> anyhow::Result<Vec<ChatCompletionMessage>> {
+        provider
+            .chat(&ModelId::new("gpt-4o"), context)
+            .await?
+            .collect::<anyhow::Result<Vec<_>>>()
+            .await
+    }
+
+    fn fixture(system: &str) -> Vec<Context> {
+        let first = Context::default()
+            .add_message(ContextMessage::system(system))
+            .add_message(ContextMessage::user("What's the name of the cat?"));
+        let second = first
+            .clone()
+            .add_message(ContextMessage::assistant("Juniper", None));
+        vec![first, second]
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let recorder =
+            ReplayProvider::record(Box::new(Stub), file.path(), vec!["sk-secret".to_string()])
+                .unwrap();
+        let mut expected = vec![];
+        for context in fixture("Today is Monday") {
+            expected.push(chat(&recorder, context).await.unwrap());
+        }
+        recorder
+            .capabilities(&ModelId::new("gpt-4o"))
+            .await
+            .unwrap();
+
+        let recording = std::fs::read_to_string(file.path()).unwrap();
+        assert!(!recording.contains("sk-secret"));
+
+        // The system prompt differs and the requests are made in another order
+        let replayer = ReplayProvider::replay(file.path()).unwrap();
+        let mut contexts = fixture("Today is Tuesday");
+        contexts.reverse();
+        let mut actual = vec![];
+        for context in contexts {
+            actual.push(chat(&replayer, context).await.unwrap());
+        }
+        actual.reverse();
+
+        expected[1][0] = ChatCompletionMessage::assistant(Content::part("The key is [REDACTED]"))
+            .finish_reason(FinishReason::Stop);
+        assert_eq!(actual, expected);
+        assert_eq!(
+            replayer
+                .capabilities(&ModelId::new("gpt-4o"))
+                .await
+                .unwrap(),
+            ModelCapabilities::all()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replay_diverged() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let recorder = ReplayProvider::record(Box::new(Stub), file.path(), vec![]).unwrap();
+        let [first, _] = fixture("").try_into().unwrap();
+        chat(&recorder, first.clone()).await.unwrap();
+
+        let replayer = ReplayProvider::replay(file.path()).unwrap();
+        chat(&replayer, first.clone()).await.unwrap();
+
+        // Every recorded response is replayed only once
+        let actual = chat(&replayer, first).await.unwrap_err().to_string();
+        assert_eq!(
+            actual,
+            "Replay diverged from the recording: no response recorded for a request to gpt-4o"
+        );
+    }
+}
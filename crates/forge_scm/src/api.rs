@@ -0,0 +1,125 @@
+use anyhow::{bail, Context};
+use reqwest::{Client, Method};
+use serde_json::Value;
+
+use crate::ScmKind;
+
+/// Client of the REST API of a host, authenticated with a bearer token.
+pub(crate) struct Api {
+    client: Client,
+    kind: ScmKind,
+    url: String,
+    token: Option<String>,
+}
+
+impl Api {
+    pub fn new(kind: ScmKind, url: String, token: Option<String>) -> Self {
+        Self { client: Client::new(), kind, url, token }
+    }
+
+    pub async fn get(&self, path: &str) -> anyhow::Result<Value> {
+        self.request(Method::GET, path, None).await
+    }
+
+    pub async fn post(&self, path: &str, body: Value) -> anyhow::Result<Value> {
+        self.request(Method::POST, path, Some(body)).await
+    }
+
+    async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> anyhow::Result<Value> {
+        let kind = self.kind;
+        let Some(token) = &self.token else {
+            bail!(
+                "No {kind} token found, set {} or store it in the keychain",
+                kind.token_variables()
+            );
+        };
+        let mut request = self
+            .client
+            .request(method.clone(), format!("{}{path}", self.url))
+            .bearer_auth(token)
+            .header("user-agent", "forge");
+        if kind == ScmKind::GitHub {
+            request = request
+                .header("accept", "application/vnd.github+json")
+                .header("x-github-api-version", "2022-11-28");
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to call the {kind} API: {method} {path}"))?;
+
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        if !status.is_success() {
+            bail!(
+                "{kind} API {method} {path} failed with {status}: {}",
+                error_message(&body)
+            );
+        }
+        Ok(body)
+    }
+}
+
+/// The string value of a response, empty when it's missing or null.
+pub(crate) fn text(value: &Value) -> String {
+    value.as_str().unwrap_or_default().to_string()
+}
+
+/// The message of an error response, in the shape of any of the hosts.
+fn error_message(body: &Value) -> String {
+    // GitHub lists the details of validation errors separately
+    let errors = body["errors"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|error| error["message"].as_str())
+        .collect::<Vec<_>>();
+    let message = [&body["message"], &body["error"]["message"], &body["error"]]
+        .into_iter()
+        .find_map(|message| match message {
+            Value::String(message) => Some(message.clone()),
+            Value::Null => None,
+            // GitLab reports validation errors as an object of the fields
+            message => message.is_object().then(|| message.to_string()),
+        })
+        .unwrap_or_default();
+    if errors.is_empty() {
+        message
+    } else {
+        format!("{message} ({})", errors.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_error_message() {
+        let actual = [
+            json!({"message": "Validation Failed", "errors": [{"message": "A pull request already exists"}]}),
+            json!({"message": {"title": ["can't be blank"]}}),
+            json!({"type": "error", "error": {"message": "Repository not found"}}),
+            json!({"error": "invalid_token"}),
+        ]
+        .map(|body| error_message(&body));
+        let expected = [
+            "Validation Failed (A pull request already exists)",
+            r#"{"title":["can't be blank"]}"#,
+            "Repository not found",
+            "invalid_token",
+        ];
+        assert_eq!(actual, expected);
+    }
+}
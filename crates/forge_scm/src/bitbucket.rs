@@ -0,0 +1,184 @@
+use serde_json::json;
+
+use crate::api::{text, Api};
+use crate::{Comment, Issue, NewPullRequest, PullRequest, ReviewComment, ScmKind, ScmProvider};
+
+/// A repository on Bitbucket Cloud.
+pub struct Bitbucket {
+    api: Api,
+    repo: String,
+}
+
+impl Bitbucket {
+    pub fn new(api_url: String, repo: String, token: Option<String>) -> Self {
+        Self { api: Api::new(ScmKind::Bitbucket, api_url, token), repo }
+    }
+}
+
+#[async_trait::async_trait]
+impl ScmProvider for Bitbucket {
+    fn kind(&self) -> ScmKind {
+        ScmKind::Bitbucket
+    }
+
+    async fn issue(&self, number: u64) -> anyhow::Result<Issue> {
+        let path = format!("/repositories/{}/issues/{number}", self.repo);
+        let issue = self.api.get(&path).await?;
+        let comments = self.api.get(&format!("{path}/comments")).await?;
+        Ok(Issue {
+            number,
+            title: text(&issue["title"]),
+            state: text(&issue["state"]),
+            author: text(&issue["reporter"]["display_name"]),
+            url: text(&issue["links"]["html"]["href"]),
+            labels: [&issue["kind"], &issue["priority"]]
+                .into_iter()
+                .map(text)
+                .filter(|label| !label.is_empty())
+                .collect(),
+            body: text(&issue["content"]["raw"]),
+            comments: comments["values"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|comment| Comment {
+                    author: text(&comment["user"]["display_name"]),
+                    body: text(&comment["content"]["raw"]),
+                })
+                .collect(),
+            is_pull_request: false,
+        })
+    }
+
+    async fn default_branch(&self) -> anyhow::Result<String> {
+        let repo = self
+            .api
+            .get(&format!("/repositories/{}", self.repo))
+            .await?;
+        Ok(text(&repo["mainbranch"]["name"]))
+    }
+
+    async fn create_pull_request(&self, pull: NewPullRequest) -> anyhow::Result<PullRequest> {
+        let created = self
+            .api
+            .post(
+                &format!("/repositories/{}/pullrequests", self.repo),
+                json!({
+                    "title": pull.title,
+                    "description": pull.body,
+                    "source": {"branch": {"name": pull.head}},
+                    "destination": {"branch": {"name": pull.base}},
+                    "draft": pull.draft,
+                }),
+            )
+            .await?;
+        Ok(PullRequest {
+            number: created["id"].as_u64().unwrap_or_default(),
+            url: text(&created["links"]["html"]["href"]),
+        })
+    }
+
+    async fn comment(&self, number: u64, comment: ReviewComment) -> anyhow::Result<String> {
+        let mut body = json!({ "content": {"raw": comment.body} });
+        if let Some(position) = comment.position {
+            body["inline"] = json!({ "path": position.path, "to": position.line });
+        }
+        let created = self
+            .api
+            .post(
+                &format!("/repositories/{}/pullrequests/{number}/comments", self.repo),
+                body,
+            )
+            .await?;
+        Ok(text(&created["links"]["html"]["href"]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::LinePosition;
+
+    fn bitbucket(server: &mockito::Server) -> Bitbucket {
+        Bitbucket::new(
+            server.url(),
+            "team/repo".to_string(),
+            Some("token".to_string()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_issue() {
+        let mut server = mockito::Server::new_async().await;
+        let issue = json!({
+            "id": 3,
+            "title": "Broken link in the docs",
+            "state": "new",
+            "kind": "bug",
+            "priority": "minor",
+            "reporter": {"display_name": "Carol"},
+            "links": {"html": {"href": "https://bitbucket.org/team/repo/issues/3"}},
+            "content": {"raw": "The install link 404s."}
+        });
+        server
+            .mock("GET", "/repositories/team/repo/issues/3")
+            .with_body(issue.to_string())
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/repositories/team/repo/issues/3/comments")
+            .with_body(json!({"values": []}).to_string())
+            .create_async()
+            .await;
+
+        let actual = bitbucket(&server).issue(3).await.unwrap();
+        let expected = Issue {
+            number: 3,
+            title: "Broken link in the docs".to_string(),
+            state: "new".to_string(),
+            author: "Carol".to_string(),
+            url: "https://bitbucket.org/team/repo/issues/3".to_string(),
+            labels: vec!["bug".to_string(), "minor".to_string()],
+            body: "The install link 404s.".to_string(),
+            comments: vec![],
+            is_pull_request: false,
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_comment_on_line() {
+        let mut server = mockito::Server::new_async().await;
+        let comment = server
+            .mock("POST", "/repositories/team/repo/pullrequests/4/comments")
+            .match_body(mockito::Matcher::Json(json!({
+                "content": {"raw": "Typo here."},
+                "inline": {"path": "README.md", "to": 8},
+            })))
+            .with_status(201)
+            .with_body(
+                json!({"links": {"html": {"href": "https://bitbucket.org/team/repo/pull-requests/4#comment-1"}}})
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let actual = bitbucket(&server)
+            .comment(
+                4,
+                ReviewComment {
+                    body: "Typo here.".to_string(),
+                    position: Some(LinePosition { path: "README.md".to_string(), line: 8 }),
+                },
+            )
+            .await
+            .unwrap();
+        comment.assert_async().await;
+        assert_eq!(
+            actual,
+            "https://bitbucket.org/team/repo/pull-requests/4#comment-1"
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use serde_json::json;
+
+use crate::api::{text, Api};
+use crate::{Comment, Issue, NewPullRequest, PullRequest, ReviewComment, ScmKind, ScmProvider};
+
+/// A repository on GitHub, or on GitHub Enterprise.
+pub struct GitHub {
+    api: Api,
+    repo: String,
+}
+
+impl GitHub {
+    pub fn new(api_url: String, repo: String, token: Option<String>) -> Self {
+        Self { api: Api::new(ScmKind::GitHub, api_url, token), repo }
+    }
+}
+
+#[async_trait::async_trait]
+impl ScmProvider for GitHub {
+    fn kind(&self) -> ScmKind {
+        ScmKind::GitHub
+    }
+
+    async fn issue(&self, number: u64) -> anyhow::Result<Issue> {
+        let path = format!("/repos/{}/issues/{number}", self.repo);
+        let issue = self.api.get(&path).await?;
+        let comments = self.api.get(&format!("{path}/comments")).await?;
+        Ok(Issue {
+            number,
+            title: text(&issue["title"]),
+            state: text(&issue["state"]),
+            author: text(&issue["user"]["login"]),
+            url: text(&issue["html_url"]),
+            labels: issue["labels"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|label| text(&label["name"]))
+                .collect(),
+            body: text(&issue["body"]),
+            comments: comments
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|comment| Comment {
+                    author: text(&comment["user"]["login"]),
+                    body: text(&comment["body"]),
+                })
+                .collect(),
+            is_pull_request: issue.get("pull_request").is_some(),
+        })
+    }
+
+    async fn default_branch(&self) -> anyhow::Result<String> {
+        let repo = self.api.get(&format!("/repos/{}", self.repo)).await?;
+        Ok(text(&repo["default_branch"]))
+    }
+
+    async fn create_pull_request(&self, pull: NewPullRequest) -> anyhow::Result<PullRequest> {
+        let created = self
+            .api
+            .post(
+                &format!("/repos/{}/pulls", self.repo),
+                json!({
+                    "title": pull.title,
+                    "body": pull.body,
+                    "head": pull.head,
+                    "base": pull.base,
+                    "draft": pull.draft,
+                }),
+            )
+            .await?;
+        Ok(PullRequest {
+            number: created["number"].as_u64().unwrap_or_default(),
+            url: text(&created["html_url"]),
+        })
+    }
+
+    async fn comment(&self, number: u64, comment: ReviewComment) -> anyhow::Result<String> {
+        let created = match comment.position {
+            Some(position) => {
+                let pull = self
+                    .api
+                    .get(&format!("/repos/{}/pulls/{number}", self.repo))
+                    .await?;
+                self.api
+                    .post(
+                        &format!("/repos/{}/pulls/{number}/comments", self.repo),
+                        json!({
+                            "body": comment.body,
+                            "commit_id": pull["head"]["sha"],
+                            "path": position.path,
+                            "line": position.line,
+                            "side": "RIGHT",
+                        }),
+                    )
+                    .await?
+            }
+            None => {
+                self.api
+                    .post(
+                        &format!("/repos/{}/issues/{number}/comments", self.repo),
+                        json!({ "body": comment.body }),
+                    )
+                    .await?
+            }
+        };
+        Ok(text(&created["html_url"]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::LinePosition;
+
+    fn github(server: &mockito::Server) -> GitHub {
+        GitHub::new(
+            server.url(),
+            "owner/repo".to_string(),
+            Some("token".to_string()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_issue() {
+        let mut server = mockito::Server::new_async().await;
+        let issue = json!({
+            "number": 123,
+            "title": "Crash on empty input",
+            "state": "open",
+            "user": {"login": "octocat"},
+            "html_url": "https://github.com/owner/repo/issues/123",
+            "labels": [{"name": "bug"}],
+            "body": "Running with an empty file panics."
+        });
+        server
+            .mock("GET", "/repos/owner/repo/issues/123")
+            .match_header("authorization", "Bearer token")
+            .with_body(issue.to_string())
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/repos/owner/repo/issues/123/comments")
+            .with_body(
+                json!([{"user": {"login": "hubot"}, "body": "Reproduced on main."}]).to_string(),
+            )
+            .create_async()
+            .await;
+
+        let actual = github(&server).issue(123).await.unwrap();
+        let expected = Issue {
+            number: 123,
+            title: "Crash on empty input".to_string(),
+            state: "open".to_string(),
+            author: "octocat".to_string(),
+            url: "https://github.com/owner/repo/issues/123".to_string(),
+            labels: vec!["bug".to_string()],
+            body: "Running with an empty file panics.".to_string(),
+            comments: vec![Comment {
+                author: "hubot".to_string(),
+                body: "Reproduced on main.".to_string(),
+            }],
+            is_pull_request: false,
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_comment_on_line() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/repos/owner/repo/pulls/7")
+            .with_body(json!({"head": {"sha": "abc123"}}).to_string())
+            .create_async()
+            .await;
+        let comment = server
+            .mock("POST", "/repos/owner/repo/pulls/7/comments")
+            .match_body(mockito::Matcher::Json(json!({
+                "body": "This can panic on empty input.",
+                "commit_id": "abc123",
+                "path": "src/lib.rs",
+                "line": 12,
+                "side": "RIGHT",
+            })))
+            .with_status(201)
+            .with_body(json!({"html_url": "https://github.com/owner/repo/pull/7#r1"}).to_string())
+            .create_async()
+            .await;
+
+        let actual = github(&server)
+            .comment(
+                7,
+                ReviewComment {
+                    body: "This can panic on empty input.".to_string(),
+                    position: Some(LinePosition { path: "src/lib.rs".to_string(), line: 12 }),
+                },
+            )
+            .await
+            .unwrap();
+        comment.assert_async().await;
+        assert_eq!(actual, "https://github.com/owner/repo/pull/7#r1");
+    }
+
+    #[tokio::test]
+    async fn test_without_token() {
+        let github = GitHub::new(
+            "https://api.github.com".to_string(),
+            "owner/repo".to_string(),
+            None,
+        );
+        let actual = github.issue(1).await.unwrap_err().to_string();
+        assert!(actual.contains("GITHUB_TOKEN"), "{actual}");
+    }
+}
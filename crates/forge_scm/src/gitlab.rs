@@ -0,0 +1,223 @@
+use serde_json::json;
+
+use crate::api::{text, Api};
+use crate::{Comment, Issue, NewPullRequest, PullRequest, ReviewComment, ScmKind, ScmProvider};
+
+/// A project on GitLab, or on a self-hosted instance.
+pub struct GitLab {
+    api: Api,
+    /// The path of the project, url-encoded as the API identifies it by
+    project: String,
+}
+
+impl GitLab {
+    pub fn new(api_url: String, repo: String, token: Option<String>) -> Self {
+        Self {
+            api: Api::new(ScmKind::GitLab, api_url, token),
+            project: repo.replace('/', "%2F"),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ScmProvider for GitLab {
+    fn kind(&self) -> ScmKind {
+        ScmKind::GitLab
+    }
+
+    async fn issue(&self, number: u64) -> anyhow::Result<Issue> {
+        let path = format!("/projects/{}/issues/{number}", self.project);
+        let issue = self.api.get(&path).await?;
+        let notes = self.api.get(&format!("{path}/notes?sort=asc")).await?;
+        Ok(Issue {
+            number,
+            title: text(&issue["title"]),
+            state: text(&issue["state"]),
+            author: text(&issue["author"]["username"]),
+            url: text(&issue["web_url"]),
+            labels: issue["labels"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(text)
+                .collect(),
+            body: text(&issue["description"]),
+            // note: system notes record events such as label changes
+            comments: notes
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|note| !note["system"].as_bool().unwrap_or_default())
+                .map(|note| Comment {
+                    author: text(&note["author"]["username"]),
+                    body: text(&note["body"]),
+                })
+                .collect(),
+            is_pull_request: false,
+        })
+    }
+
+    async fn default_branch(&self) -> anyhow::Result<String> {
+        let project = self.api.get(&format!("/projects/{}", self.project)).await?;
+        Ok(text(&project["default_branch"]))
+    }
+
+    async fn create_pull_request(&self, pull: NewPullRequest) -> anyhow::Result<PullRequest> {
+        let title = if pull.draft {
+            format!("Draft: {}", pull.title)
+        } else {
+            pull.title
+        };
+        let created = self
+            .api
+            .post(
+                &format!("/projects/{}/merge_requests", self.project),
+                json!({
+                    "title": title,
+                    "description": pull.body,
+                    "source_branch": pull.head,
+                    "target_branch": pull.base,
+                }),
+            )
+            .await?;
+        Ok(PullRequest {
+            number: created["iid"].as_u64().unwrap_or_default(),
+            url: text(&created["web_url"]),
+        })
+    }
+
+    async fn comment(&self, number: u64, comment: ReviewComment) -> anyhow::Result<String> {
+        let path = format!("/projects/{}/merge_requests/{number}", self.project);
+        let merge_request = self.api.get(&path).await?;
+        let note = match comment.position {
+            Some(position) => {
+                let refs = &merge_request["diff_refs"];
+                let discussion = self
+                    .api
+                    .post(
+                        &format!("{path}/discussions"),
+                        json!({
+                            "body": comment.body,
+                            "position": {
+                                "position_type": "text",
+                                "base_sha": refs["base_sha"],
+                                "start_sha": refs["start_sha"],
+                                "head_sha": refs["head_sha"],
+                                "new_path": position.path,
+                                "new_line": position.line,
+                            },
+                        }),
+                    )
+                    .await?;
+                discussion["notes"][0].clone()
+            }
+            None => {
+                self.api
+                    .post(&format!("{path}/notes"), json!({ "body": comment.body }))
+                    .await?
+            }
+        };
+        Ok(format!(
+            "{}#note_{}",
+            text(&merge_request["web_url"]),
+            note["id"]
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn gitlab(server: &mockito::Server) -> GitLab {
+        GitLab::new(
+            server.url(),
+            "group/repo".to_string(),
+            Some("token".to_string()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_issue() {
+        let mut server = mockito::Server::new_async().await;
+        let issue = json!({
+            "iid": 5,
+            "title": "Slow startup",
+            "state": "opened",
+            "author": {"username": "alice"},
+            "web_url": "https://gitlab.com/group/repo/-/issues/5",
+            "labels": ["performance"],
+            "description": "Takes 10s to start."
+        });
+        let notes = json!([
+            {"author": {"username": "bob"}, "body": "added ~performance label", "system": true},
+            {"author": {"username": "bob"}, "body": "Profiled it, the index is rebuilt.", "system": false}
+        ]);
+        server
+            .mock("GET", "/projects/group%2Frepo/issues/5")
+            .with_body(issue.to_string())
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/projects/group%2Frepo/issues/5/notes?sort=asc")
+            .with_body(notes.to_string())
+            .create_async()
+            .await;
+
+        let actual = gitlab(&server).issue(5).await.unwrap();
+        let expected = Issue {
+            number: 5,
+            title: "Slow startup".to_string(),
+            state: "opened".to_string(),
+            author: "alice".to_string(),
+            url: "https://gitlab.com/group/repo/-/issues/5".to_string(),
+            labels: vec!["performance".to_string()],
+            body: "Takes 10s to start.".to_string(),
+            comments: vec![Comment {
+                author: "bob".to_string(),
+                body: "Profiled it, the index is rebuilt.".to_string(),
+            }],
+            is_pull_request: false,
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_create_merge_request() {
+        let mut server = mockito::Server::new_async().await;
+        let create = server
+            .mock("POST", "/projects/group%2Frepo/merge_requests")
+            .match_body(mockito::Matcher::Json(json!({
+                "title": "Draft: Cache the index",
+                "description": "Fixes #5",
+                "source_branch": "cache-index",
+                "target_branch": "main",
+            })))
+            .with_status(201)
+            .with_body(
+                json!({"iid": 9, "web_url": "https://gitlab.com/group/repo/-/merge_requests/9"})
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let actual = gitlab(&server)
+            .create_pull_request(NewPullRequest {
+                title: "Cache the index".to_string(),
+                body: "Fixes #5".to_string(),
+                head: "cache-index".to_string(),
+                base: "main".to_string(),
+                draft: true,
+            })
+            .await
+            .unwrap();
+        create.assert_async().await;
+        let expected = PullRequest {
+            number: 9,
+            url: "https://gitlab.com/group/repo/-/merge_requests/9".to_string(),
+        };
+        assert_eq!(actual, expected);
+    }
+}
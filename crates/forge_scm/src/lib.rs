@@ -0,0 +1,14 @@
+mod api;
+mod bitbucket;
+mod github;
+mod gitlab;
+mod provider;
+mod remote;
+mod scm;
+
+pub use bitbucket::Bitbucket;
+pub use github::GitHub;
+pub use gitlab::GitLab;
+pub use provider::*;
+pub use remote::*;
+pub use scm::*;
@@ -0,0 +1,128 @@
+use std::fmt::{self, Display};
+
+use crate::ScmKind;
+
+/// An issue, or a pull request on the hosts that list them along with the
+/// issues.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    pub state: String,
+    pub author: String,
+    pub url: String,
+    pub labels: Vec<String>,
+    pub body: String,
+    pub comments: Vec<Comment>,
+    pub is_pull_request: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    pub author: String,
+    pub body: String,
+}
+
+/// A pull request to open from the `head` branch into the `base` one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewPullRequest {
+    pub title: String,
+    pub body: String,
+    pub head: String,
+    pub base: String,
+    pub draft: bool,
+}
+
+/// A pull request, or merge request on GitLab, that was opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PullRequest {
+    pub number: u64,
+    pub url: String,
+}
+
+/// A review comment, on a line of a changed file when its position is set or
+/// on the pull request as a whole.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewComment {
+    pub body: String,
+    pub position: Option<LinePosition>,
+}
+
+/// A line of a file in the pull request's version, its path relative to the
+/// root of the repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinePosition {
+    pub path: String,
+    pub line: u64,
+}
+
+/// The issues and pull requests of a repository hosted on GitHub, GitLab or
+/// Bitbucket.
+#[async_trait::async_trait]
+pub trait ScmProvider: Send + Sync {
+    /// The host of the repository.
+    fn kind(&self) -> ScmKind;
+
+    /// Fetches the issue with its comments.
+    async fn issue(&self, number: u64) -> anyhow::Result<Issue>;
+
+    /// The branch pull requests are merged into by default.
+    async fn default_branch(&self) -> anyhow::Result<String>;
+
+    async fn create_pull_request(&self, pull: NewPullRequest) -> anyhow::Result<PullRequest>;
+
+    /// Posts the comment on the pull request and returns its URL.
+    async fn comment(&self, number: u64, comment: ReviewComment) -> anyhow::Result<String>;
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.is_pull_request {
+            "Pull request"
+        } else {
+            "Issue"
+        };
+        writeln!(f, "# {} (#{})", self.title, self.number)?;
+        writeln!(f, "{kind} {} by @{}, {}", self.state, self.author, self.url)?;
+        if !self.labels.is_empty() {
+            writeln!(f, "Labels: {}", self.labels.join(", "))?;
+        }
+        writeln!(f)?;
+        if self.body.is_empty() {
+            writeln!(f, "No description provided.")?;
+        } else {
+            writeln!(f, "{}", self.body)?;
+        }
+        for comment in &self.comments {
+            writeln!(f, "\n## Comment by @{}\n{}", comment.author, comment.body)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_issue_display() {
+        let issue = Issue {
+            number: 123,
+            title: "Crash on empty input".to_string(),
+            state: "open".to_string(),
+            author: "octocat".to_string(),
+            url: "https://github.com/owner/repo/issues/123".to_string(),
+            labels: vec!["bug".to_string()],
+            body: "Running with an empty file panics.".to_string(),
+            comments: vec![Comment {
+                author: "hubot".to_string(),
+                body: "Reproduced on main.".to_string(),
+            }],
+            is_pull_request: false,
+        };
+        let expected = "# Crash on empty input (#123)\nIssue open by @octocat, https://github.com/owner/repo/issues/123\nLabels: bug\n\nRunning with an empty file panics.\n\n## Comment by @hubot\nReproduced on main.\n";
+        assert_eq!(issue.to_string(), expected);
+    }
+}
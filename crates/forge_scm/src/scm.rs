@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Context};
+use tokio::process::Command;
+
+use crate::{Remote, ScmProvider};
+
+/// Tokens of the hosts, each used for the repositories hosted there.
+#[derive(Clone, Default)]
+pub struct ScmTokens {
+    pub github: Option<String>,
+    pub gitlab: Option<String>,
+    pub bitbucket: Option<String>,
+}
+
+/// The repository of a directory, whose host is detected from the URL of its
+/// `origin` remote.
+#[derive(Clone)]
+pub struct Scm {
+    cwd: PathBuf,
+    tokens: ScmTokens,
+}
+
+impl Scm {
+    pub fn new(cwd: PathBuf, tokens: ScmTokens) -> Self {
+        Self { cwd, tokens }
+    }
+
+    /// The repository of the `origin` remote.
+    pub async fn remote(&self) -> anyhow::Result<Remote> {
+        let url = self.git(&["remote", "get-url", "origin"]).await?;
+        Remote::parse(&url).with_context(|| {
+            format!("The origin remote {url} isn't hosted on GitHub, GitLab or Bitbucket")
+        })
+    }
+
+    /// The client of the host of the `origin` remote, for the given
+    /// repository on that host or the remote's own.
+    pub async fn provider(&self, repo: Option<&str>) -> anyhow::Result<Box<dyn ScmProvider>> {
+        let remote = self.remote().await?;
+        let remote = match repo {
+            Some(repo) => {
+                let host = remote.host.clone();
+                remote
+                    .with_repo(repo)
+                    .with_context(|| format!("{repo} isn't a repository of {host}"))?
+            }
+            None => remote,
+        };
+        Ok(remote.provider(&self.tokens))
+    }
+
+    /// Runs git in the directory and returns its output.
+    pub async fn git(&self, args: &[&str]) -> anyhow::Result<String> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(&self.cwd)
+            .output()
+            .await
+            .context("Failed to run git")?;
+        if !output.status.success() {
+            bail!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
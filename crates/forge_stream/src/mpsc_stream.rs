@@ -2,7 +2,7 @@ use std::future::Future;
 
 use futures::Stream;
 use tokio::sync::mpsc::{Receiver, Sender};
-use tokio::task::JoinHandle;
+use tokio::task::{AbortHandle, JoinHandle};
 
 pub struct MpscStream<T> {
     join_handle: JoinHandle<()>,
@@ -18,6 +18,12 @@ impl<T> MpscStream<T> {
         let (tx, rx) = tokio::sync::mpsc::channel(1);
         MpscStream { join_handle: tokio::spawn(f(tx)), receiver: rx }
     }
+
+    /// Handle that stops the task producing the stream, eg: to cancel it from
+    /// elsewhere than where the stream is consumed.
+    pub fn abort_handle(&self) -> AbortHandle {
+        self.join_handle.abort_handle()
+    }
 }
 
 impl<T> Stream for MpscStream<T> {
@@ -98,4 +104,16 @@ mod test {
             "Task should have been aborted"
         );
     }
+
+    #[tokio::test]
+    async fn test_abort_handle_ends_stream() {
+        let mut stream = MpscStream::spawn(|tx| async move {
+            tx.send(1).await.unwrap();
+            std::future::pending::<()>().await;
+        });
+
+        assert_eq!(stream.next().await, Some(1));
+        stream.abort_handle().abort();
+        assert_eq!(stream.next().await, None);
+    }
 }
@@ -0,0 +1,43 @@
+use std::path::Path;
+
+use forge_domain::{
+    AuditEntry, AuditService, FileChange, FileSnapshot, ToolAuditEntry, ToolCallFull, ToolResult,
+};
+use tokio::sync::Mutex;
+
+/// Audit service that keeps the log in memory and doesn't look at the files
+/// the tools change nor record the redactions.
+#[derive(Default)]
+pub struct InMemoryAuditService {
+    entries: Mutex<Vec<ToolAuditEntry>>,
+}
+
+impl InMemoryAuditService {
+    /// The entries appended so far, oldest first.
+    pub async fn entries(&self) -> Vec<ToolAuditEntry> {
+        self.entries.lock().await.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditService for InMemoryAuditService {
+    async fn snapshot(&self, _tool_call: &ToolCallFull) -> FileSnapshot {
+        FileSnapshot::default()
+    }
+
+    async fn changes(&self, _snapshot: &FileSnapshot, _result: &ToolResult) -> Vec<FileChange> {
+        Vec::new()
+    }
+
+    async fn append(&self, entry: ToolAuditEntry) -> anyhow::Result<ToolAuditEntry> {
+        let mut entries = self.entries.lock().await;
+        let last = entries.last().map(|last| (last.seq, last.hash.as_str()));
+        let entry = entry.chain(last)?;
+        entries.push(entry.clone());
+        Ok(entry)
+    }
+
+    async fn redactions(&self, _path: &Path, _entries: &[AuditEntry<'_>]) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+
+use forge_domain::{
+    AgentId, AgentMode, Context, Conversation, ConversationId, ConversationService, Event, ModelId,
+    ModelParameters, Workflow,
+};
+use tokio::sync::Mutex;
+
+/// Conversation service that keeps the conversations in memory, so that they
+/// can be inspected once the workflow has run.
+#[derive(Default)]
+pub struct InMemoryConversationService {
+    conversations: Mutex<HashMap<ConversationId, Conversation>>,
+}
+
+impl InMemoryConversationService {
+    async fn update(
+        &self,
+        id: &ConversationId,
+        f: impl FnOnce(&mut Conversation) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let mut guard = self.conversations.lock().await;
+        let conversation = guard
+            .get_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?;
+        f(conversation)
+    }
+}
+
+#[async_trait::async_trait]
+impl ConversationService for InMemoryConversationService {
+    async fn get(&self, id: &ConversationId) -> anyhow::Result<Option<Conversation>> {
+        Ok(self.conversations.lock().await.get(id).cloned())
+    }
+
+    async fn create(&self, workflow: Workflow) -> anyhow::Result<ConversationId> {
+        let id = ConversationId::generate();
+        let conversation = Conversation::new(id.clone(), workflow);
+        self.conversations
+            .lock()
+            .await
+            .insert(id.clone(), conversation);
+        Ok(id)
+    }
+
+    async fn import(&self, conversation: Conversation) -> anyhow::Result<ConversationId> {
+        let id = ConversationId::generate();
+        self.conversations
+            .lock()
+            .await
+            .insert(id.clone(), conversation.id(id.clone()));
+        Ok(id)
+    }
+
+    async fn inc_turn(&self, id: &ConversationId, agent: &AgentId) -> anyhow::Result<()> {
+        self.update(id, |c| {
+            c.state.entry(agent.clone()).or_default().turn_count += 1;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn set_context(
+        &self,
+        id: &ConversationId,
+        agent: &AgentId,
+        context: Context,
+    ) -> anyhow::Result<()> {
+        self.update(id, |c| {
+            c.state.entry(agent.clone()).or_default().context = Some(context);
+            Ok(())
+        })
+        .await
+    }
+
+    async fn insert_event(&self, id: &ConversationId, event: Event) -> anyhow::Result<()> {
+        self.update(id, |c| {
+            c.events.push(event);
+            Ok(())
+        })
+        .await
+    }
+
+    async fn set_parameters(
+        &self,
+        id: &ConversationId,
+        parameters: &ModelParameters,
+    ) -> anyhow::Result<()> {
+        self.update(id, |c| {
+            for agent in c.workflow.agents.iter_mut() {
+                agent.parameters = agent.parameters.clone().merge(parameters);
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn set_model(&self, id: &ConversationId, model: &ModelId) -> anyhow::Result<()> {
+        self.update(id, |c| {
+            for agent in c.workflow.agents.iter_mut() {
+                agent.model = model.clone();
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn set_mode(&self, id: &ConversationId, mode: AgentMode) -> anyhow::Result<()> {
+        self.update(id, |c| {
+            for agent in c.workflow.agents.iter_mut() {
+                agent.mode = mode;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn checkpoint(&self, id: &ConversationId, name: &str) -> anyhow::Result<()> {
+        self.update(id, |c| {
+            c.checkpoint(name);
+            Ok(())
+        })
+        .await
+    }
+
+    async fn branch(&self, id: &ConversationId, name: &str) -> anyhow::Result<()> {
+        self.update(id, |c| Ok(c.branch(name)?)).await
+    }
+
+    async fn set_pinned_files(
+        &self,
+        id: &ConversationId,
+        files: BTreeSet<PathBuf>,
+    ) -> anyhow::Result<()> {
+        self.update(id, |c| {
+            c.pinned_files = files;
+            Ok(())
+        })
+        .await
+    }
+}
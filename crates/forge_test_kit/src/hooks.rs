@@ -0,0 +1,27 @@
+use forge_domain::{HookEvent, HookOutcome, HookService};
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+/// Hook service without any hook, which records the events it's run on.
+#[derive(Default)]
+pub struct FakeHookService {
+    events: Mutex<Vec<(HookEvent, Option<String>)>>,
+}
+
+impl FakeHookService {
+    /// The events and their target, in the order they happened.
+    pub async fn events(&self) -> Vec<(HookEvent, Option<String>)> {
+        self.events.lock().await.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl HookService for FakeHookService {
+    async fn run(&self, event: HookEvent, target: Option<&str>, payload: Value) -> HookOutcome {
+        self.events
+            .lock()
+            .await
+            .push((event, target.map(str::to_string)));
+        HookOutcome::Continue(payload)
+    }
+}
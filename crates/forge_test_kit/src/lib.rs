@@ -0,0 +1,15 @@
+mod audit;
+mod conversation;
+mod harness;
+mod hooks;
+mod provider;
+mod template;
+mod tools;
+
+pub use audit::InMemoryAuditService;
+pub use conversation::InMemoryConversationService;
+pub use harness::{Harness, TestApp, Transcript};
+pub use hooks::FakeHookService;
+pub use provider::{Completion, FakeProvider};
+pub use template::FakeTemplateService;
+pub use tools::FakeToolService;
@@ -0,0 +1,177 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use forge_domain::{
+    ChatCompletionMessage, Content, Context, FinishReason, Model, ModelCapabilities, ModelId,
+    ProviderService, ResultStream, ToolCallFull, ToolName,
+};
+
+/// Canned response of the [`FakeProvider`] to a single chat request.
+#[derive(Debug, Clone, Default)]
+pub struct Completion {
+    model: Option<ModelId>,
+    messages: Vec<ChatCompletionMessage>,
+    error: Option<String>,
+}
+
+impl Completion {
+    /// Streams `text` as the answer of the model.
+    pub fn text(mut self, text: impl ToString) -> Self {
+        self.messages
+            .push(ChatCompletionMessage::assistant(Content::part(text)));
+        self
+    }
+
+    /// Makes the model call the tool `name` with `arguments`.
+    pub fn tool_call(mut self, name: impl ToString, arguments: serde_json::Value) -> Self {
+        self.messages.push(
+            ChatCompletionMessage::assistant(Content::part(""))
+                .add_tool_call(ToolCallFull::new(ToolName::new(name)).arguments(arguments)),
+        );
+        self
+    }
+
+    /// Streams `message` as it is, eg: to report usage.
+    pub fn message(mut self, message: ChatCompletionMessage) -> Self {
+        self.messages.push(message);
+        self
+    }
+
+    /// Fails the request with `error` after streaming the messages, if any.
+    pub fn error(mut self, error: impl ToString) -> Self {
+        self.error = Some(error.to_string());
+        self
+    }
+
+    /// Only answers requests made to `model`, so that agents running in
+    /// parallel get their own completions.
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(ModelId::new(model));
+        self
+    }
+
+    fn matches(&self, model: &ModelId) -> bool {
+        self.model.as_ref().is_none_or(|expected| expected == model)
+    }
+}
+
+/// Provider that answers with the completions queued up front, in order, and
+/// records every request it receives.
+#[derive(Default)]
+pub struct FakeProvider {
+    completions: Mutex<VecDeque<Completion>>,
+    requests: Mutex<Vec<(ModelId, Context)>>,
+    capabilities: Option<ModelCapabilities>,
+}
+
+impl FakeProvider {
+    /// Queues the response to the next request.
+    pub fn reply(self, completion: Completion) -> Self {
+        self.completions.lock().unwrap().push_back(completion);
+        self
+    }
+
+    /// Overrides the capabilities reported for every model, all of them are
+    /// supported by default.
+    pub fn capabilities(mut self, capabilities: ModelCapabilities) -> Self {
+        self.capabilities = Some(capabilities);
+        self
+    }
+
+    /// Requests received so far, along with the model they were made to.
+    pub fn requests(&self) -> Vec<(ModelId, Context)> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Number of the queued completions that haven't been used.
+    pub fn pending(&self) -> usize {
+        self.completions.lock().unwrap().len()
+    }
+}
+
+#[async_trait::async_trait]
+impl ProviderService for FakeProvider {
+    async fn chat(
+        &self,
+        id: &ModelId,
+        context: Context,
+    ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+        self.requests.lock().unwrap().push((id.clone(), context));
+
+        let completion = {
+            let mut completions = self.completions.lock().unwrap();
+            completions
+                .iter()
+                .position(|completion| completion.matches(id))
+                .and_then(|index| completions.remove(index))
+        };
+        let Some(completion) = completion else {
+            anyhow::bail!("No completion queued for a request to {}", id);
+        };
+
+        let mut messages = completion.messages.into_iter().map(Ok).collect::<Vec<_>>();
+        match completion.error {
+            Some(error) => messages.push(Err(anyhow::anyhow!(error))),
+            None => messages.push(Ok(ChatCompletionMessage::assistant(Content::part(""))
+                .finish_reason(FinishReason::Stop))),
+        }
+        Ok(Box::pin(tokio_stream::iter(messages)))
+    }
+
+    async fn models(&self) -> anyhow::Result<Vec<Model>> {
+        Ok(vec![])
+    }
+
+    async fn capabilities(&self, _model: &ModelId) -> anyhow::Result<ModelCapabilities> {
+        Ok(self.capabilities.unwrap_or_else(ModelCapabilities::all))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tokio_stream::StreamExt;
+
+    use super::*;
+
+    async fn chat(provider: &FakeProvider, model: &str) -> anyhow::Result<Vec<String>> {
+        let messages = provider
+            .chat(&ModelId::new(model), Context::default())
+            .await?
+            .collect::<anyhow::Result<Vec<_>>>()
+            .await?;
+        Ok(messages
+            .into_iter()
+            .filter_map(|message| message.content)
+            .map(|content| content.as_str().to_string())
+            .filter(|content| !content.is_empty())
+            .collect())
+    }
+
+    #[tokio::test]
+    async fn test_completions_by_model() {
+        let provider = FakeProvider::default()
+            .reply(
+                Completion::default()
+                    .text("for the planner")
+                    .model("planner"),
+            )
+            .reply(Completion::default().text("for anyone"));
+
+        assert_eq!(chat(&provider, "coder").await.unwrap(), ["for anyone"]);
+        assert_eq!(
+            chat(&provider, "planner").await.unwrap(),
+            ["for the planner"]
+        );
+        assert_eq!(provider.requests().len(), 2);
+        assert_eq!(provider.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_nothing_queued() {
+        let provider = FakeProvider::default();
+
+        let actual = chat(&provider, "coder").await.unwrap_err().to_string();
+        assert_eq!(actual, "No completion queued for a request to coder");
+    }
+}
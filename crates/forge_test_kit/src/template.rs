@@ -0,0 +1,43 @@
+use forge_domain::{Agent, Event, EventContext, SystemContext, Template, TemplateService};
+use handlebars::Handlebars;
+
+/// Renders the agents' prompts with handlebars alone. Unlike the app, it
+/// doesn't walk the working directory or query the vector index, so the
+/// system context is empty and the partials of the prompt library aren't
+/// available.
+pub struct FakeTemplateService {
+    hb: Handlebars<'static>,
+}
+
+impl Default for FakeTemplateService {
+    fn default() -> Self {
+        let mut hb = Handlebars::new();
+        hb.register_escape_fn(|str| str.to_string());
+        Self { hb }
+    }
+}
+
+#[async_trait::async_trait]
+impl TemplateService for FakeTemplateService {
+    async fn render_system(
+        &self,
+        _agent: &Agent,
+        prompt: &Template<SystemContext>,
+        _paths: &[String],
+    ) -> anyhow::Result<String> {
+        Ok(self
+            .hb
+            .render_template(&prompt.template, &SystemContext::default())?)
+    }
+
+    async fn render_event(
+        &self,
+        _agent: &Agent,
+        prompt: &Template<EventContext>,
+        event: &Event,
+    ) -> anyhow::Result<String> {
+        Ok(self
+            .hb
+            .render_template(&prompt.template, &EventContext::new(event.clone()))?)
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::Mutex;
+
+use forge_domain::{ToolCallFull, ToolDefinition, ToolResult, ToolService};
+use serde_json::Value;
+
+type Handler = Box<dyn Fn(&Value) -> anyhow::Result<String> + Send + Sync>;
+
+struct FakeTool {
+    definition: ToolDefinition,
+    handler: Handler,
+}
+
+/// Tool service whose tools answer with canned outputs instead of touching
+/// the filesystem or network, and which records every call made to it.
+#[derive(Default)]
+pub struct FakeToolService {
+    tools: Vec<FakeTool>,
+    calls: Mutex<Vec<ToolCallFull>>,
+}
+
+impl FakeToolService {
+    /// Adds the tool `name` that answers every call with `output`.
+    pub fn tool(self, name: impl ToString, output: impl ToString) -> Self {
+        let output = output.to_string();
+        self.handler(name, move |_| Ok(output.clone()))
+    }
+
+    /// Adds the tool `name` that fails every call with `error`.
+    pub fn failing_tool(self, name: impl ToString, error: impl ToString) -> Self {
+        let error = error.to_string();
+        self.handler(name, move |_| Err(anyhow::anyhow!(error.clone())))
+    }
+
+    /// Adds the tool `name` that answers calls based on their arguments.
+    pub fn handler(
+        mut self,
+        name: impl ToString,
+        handler: impl Fn(&Value) -> anyhow::Result<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.tools.push(FakeTool {
+            definition: ToolDefinition::new(name),
+            handler: Box::new(handler),
+        });
+        self
+    }
+
+    /// Calls made so far, in order.
+    pub fn calls(&self) -> Vec<ToolCallFull> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolService for FakeToolService {
+    async fn call(&self, call: ToolCallFull) -> ToolResult {
+        self.calls.lock().unwrap().push(call.clone());
+
+        let result = ToolResult::from(call.clone());
+        match self
+            .tools
+            .iter()
+            .find(|tool| tool.definition.name == call.name)
+        {
+            Some(tool) => match (tool.handler)(&call.arguments) {
+                Ok(output) => result.success(output),
+                Err(error) => result.failure(error),
+            },
+            None => result.failure(anyhow::anyhow!(
+                "No tool with name '{}' was found",
+                call.name.as_str()
+            )),
+        }
+    }
+
+    fn list(&self) -> Vec<ToolDefinition> {
+        self.tools
+            .iter()
+            .map(|tool| tool.definition.clone())
+            .collect()
+    }
+
+    fn usage_prompt(&self) -> String {
+        self.tools
+            .iter()
+            .enumerate()
+            .map(|(i, tool)| format!("\n{}. {}", i + 1, tool.definition.usage_prompt()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forge_domain::ToolName;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    fn call(name: &str, arguments: Value) -> ToolCallFull {
+        ToolCallFull::new(ToolName::new(name)).arguments(arguments)
+    }
+
+    #[tokio::test]
+    async fn test_call() {
+        let tools = FakeToolService::default()
+            .tool("tool_forge_fs_read", "Juniper")
+            .handler("tool_forge_math", |arguments| {
+                Ok(arguments["expression"].to_string())
+            });
+
+        let actual = [
+            tools
+                .call(call("tool_forge_fs_read", json!({"path": "cat.md"})))
+                .await,
+            tools
+                .call(call("tool_forge_math", json!({"expression": "2 + 2"})))
+                .await,
+        ];
+        let expected = [
+            ToolResult::new(ToolName::new("tool_forge_fs_read")).success("Juniper"),
+            ToolResult::new(ToolName::new("tool_forge_math")).success("\"2 + 2\""),
+        ];
+        assert_eq!(actual, expected);
+        assert_eq!(tools.calls().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_call_unknown_tool() {
+        let tools = FakeToolService::default();
+
+        let actual = tools.call(call("tool_forge_fs_read", json!({}))).await;
+        assert!(actual.is_error);
+        assert_eq!(tools.calls(), [call("tool_forge_fs_read", json!({}))]);
+    }
+}
@@ -4,7 +4,9 @@ mod dispatch;
 mod error;
 mod event;
 mod log;
+mod stats;
 pub use dispatch::Tracker;
 use error::Result;
 pub use event::{Event, EventKind};
 pub use log::{init_tracing, Guard};
+pub use stats::*;
@@ -1,34 +1,96 @@
 use std::path::PathBuf;
 
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::SpanExporter;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
 use tracing::debug;
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::{self};
 
+/// Environment variables of the OTLP exporter, which sends the spans to a
+/// collector such as Jaeger or Grafana Tempo when either is set.
+const OTLP_ENDPOINTS: [&str; 2] = [
+    "OTEL_EXPORTER_OTLP_ENDPOINT",
+    "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+];
+
 pub fn init_tracing(log_path: PathBuf) -> anyhow::Result<Guard> {
     debug!(path = %log_path.display(), "Initializing logging system");
 
     let append = tracing_appender::rolling::daily(log_path, "forge.log");
     let (non_blocking, guard) = tracing_appender::non_blocking(append);
 
-    tracing_subscriber::fmt()
-        .pretty()
-        .with_env_filter(
+    let provider = otlp_provider()?;
+    let otlp = provider
+        .as_ref()
+        .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer("forge")));
+
+    tracing_subscriber::registry()
+        .with(
             tracing_subscriber::EnvFilter::try_from_env("FORGE_LOG")
                 .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("forge=debug")),
         )
-        .with_timer(tracing_subscriber::fmt::time::uptime())
-        .with_thread_ids(false)
-        .with_target(true)
-        .with_file(true)
-        .with_line_number(true)
-        .with_ansi(true)
-        .with_span_events(FmtSpan::ACTIVE)
-        .with_writer(non_blocking)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .pretty()
+                .with_timer(tracing_subscriber::fmt::time::uptime())
+                .with_thread_ids(false)
+                .with_target(true)
+                .with_file(true)
+                .with_line_number(true)
+                .with_ansi(true)
+                .with_span_events(FmtSpan::ACTIVE)
+                .with_writer(non_blocking),
+        )
+        .with(otlp)
         .init();
 
-    debug!("Logging system initialized successfully");
-    Ok(Guard(guard))
+    debug!(
+        otlp = provider.is_some(),
+        "Logging system initialized successfully"
+    );
+    Ok(Guard { _log: guard, provider })
+}
+
+/// Builds the provider exporting spans over OTLP/HTTP, configured through the
+/// standard `OTEL_*` environment variables.
+fn otlp_provider() -> anyhow::Result<Option<SdkTracerProvider>> {
+    if !OTLP_ENDPOINTS
+        .iter()
+        .any(|name| std::env::var(name).is_ok_and(|value| !value.is_empty()))
+    {
+        return Ok(None);
+    }
+
+    let exporter = SpanExporter::builder().with_http().build()?;
+    let mut resource = Resource::builder();
+    // note: the service name can still be overridden with `OTEL_SERVICE_NAME`.
+    if std::env::var("OTEL_SERVICE_NAME").is_err() {
+        resource = resource.with_service_name("forge");
+    }
+
+    Ok(Some(
+        SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(resource.build())
+            .build(),
+    ))
 }
 
-pub struct Guard(#[allow(dead_code)] WorkerGuard);
+pub struct Guard {
+    _log: WorkerGuard,
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        // Flushes the spans that are yet to be exported
+        if let Some(provider) = self.provider.take() {
+            let _ = provider.shutdown();
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use std::collections::{BTreeMap, HashSet};
+use std::env;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::Result;
+
+const STATS_ENV_VAR: &str = "FORGE_STATS";
+
+/// Checks if the usage statistics are recorded, which can be opted out of by
+/// setting `FORGE_STATS=false`.
+pub fn stats_enabled() -> bool {
+    env::var(STATS_ENV_VAR).map_or(true, |v| !v.eq_ignore_ascii_case("false"))
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolStats {
+    pub calls: u64,
+    pub failures: u64,
+}
+
+impl ToolStats {
+    pub fn failure_rate(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.failures as f64 / self.calls as f64
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderStats {
+    pub requests: u64,
+    /// Sum of the latencies of all the requests
+    pub latency_ms: u64,
+    pub max_latency_ms: u64,
+}
+
+impl ProviderStats {
+    pub fn average_latency_ms(&self) -> u64 {
+        self.latency_ms
+            .checked_div(self.requests)
+            .unwrap_or_default()
+    }
+
+    fn merge(&mut self, other: &ProviderStats) {
+        self.requests += other.requests;
+        self.latency_ms += other.latency_ms;
+        self.max_latency_ms = self.max_latency_ms.max(other.max_latency_ms);
+    }
+}
+
+/// Metrics of a single chat request within a session, stored as a line of the
+/// local statistics file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StatsRecord {
+    pub session: String,
+    pub timestamp: DateTime<Utc>,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    /// Spend in USD, zero when the pricing of the models is unknown
+    pub cost: f64,
+    #[serde(default)]
+    pub tools: BTreeMap<String, ToolStats>,
+    #[serde(default)]
+    pub providers: BTreeMap<String, ProviderStats>,
+}
+
+impl StatsRecord {
+    pub fn new(session: impl ToString, timestamp: DateTime<Utc>) -> Self {
+        Self {
+            session: session.to_string(),
+            timestamp,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            total_tokens: 0,
+            cost: 0.0,
+            tools: BTreeMap::new(),
+            providers: BTreeMap::new(),
+        }
+    }
+
+    pub fn record_usage(&mut self, prompt_tokens: u64, completion_tokens: u64, total_tokens: u64) {
+        self.prompt_tokens += prompt_tokens;
+        self.completion_tokens += completion_tokens;
+        self.total_tokens += total_tokens;
+    }
+
+    pub fn record_tool_call(&mut self, name: &str, failed: bool) {
+        let tool = self.tools.entry(name.to_string()).or_default();
+        tool.calls += 1;
+        if failed {
+            tool.failures += 1;
+        }
+    }
+
+    pub fn record_request(&mut self, provider: &str, latency: Duration) {
+        let latency_ms = latency.as_millis() as u64;
+        self.providers
+            .entry(provider.to_string())
+            .or_default()
+            .merge(&ProviderStats { requests: 1, latency_ms, max_latency_ms: latency_ms });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total_tokens == 0 && self.tools.is_empty() && self.providers.is_empty()
+    }
+}
+
+/// Newline-delimited JSON file holding the [`StatsRecord`]s, which never
+/// leave the machine.
+pub struct StatsStore {
+    path: PathBuf,
+}
+
+impl StatsStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn append(&self, record: &StatsRecord) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(&line)?;
+        Ok(())
+    }
+
+    /// Reads all the records, skipping the lines that can't be parsed, eg: a
+    /// line that was only partially written.
+    pub fn load(&self) -> Result<Vec<StatsRecord>> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DayStats {
+    pub sessions: usize,
+    pub total_tokens: u64,
+    pub cost: f64,
+    pub tool_calls: u64,
+    pub tool_failures: u64,
+}
+
+impl DayStats {
+    pub fn failure_rate(&self) -> f64 {
+        ToolStats { calls: self.tool_calls, failures: self.tool_failures }.failure_rate()
+    }
+}
+
+/// Aggregate of the records made since a point in time.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StatsSummary {
+    pub sessions: usize,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    pub cost: f64,
+    pub tools: BTreeMap<String, ToolStats>,
+    pub providers: BTreeMap<String, ProviderStats>,
+    /// Activity per day in UTC
+    pub days: BTreeMap<NaiveDate, DayStats>,
+}
+
+impl StatsSummary {
+    pub fn new(records: &[StatsRecord], since: DateTime<Utc>) -> Self {
+        let mut summary = StatsSummary::default();
+        let mut sessions = HashSet::new();
+        let mut day_sessions = HashSet::new();
+
+        for record in records.iter().filter(|record| record.timestamp >= since) {
+            let date = record.timestamp.date_naive();
+            sessions.insert(record.session.as_str());
+            summary.prompt_tokens += record.prompt_tokens;
+            summary.completion_tokens += record.completion_tokens;
+            summary.total_tokens += record.total_tokens;
+            summary.cost += record.cost;
+
+            let day = summary.days.entry(date).or_default();
+            if day_sessions.insert((date, record.session.as_str())) {
+                day.sessions += 1;
+            }
+            day.total_tokens += record.total_tokens;
+            day.cost += record.cost;
+
+            for (name, stats) in &record.tools {
+                let tool = summary.tools.entry(name.clone()).or_default();
+                tool.calls += stats.calls;
+                tool.failures += stats.failures;
+                day.tool_calls += stats.calls;
+                day.tool_failures += stats.failures;
+            }
+            for (name, stats) in &record.providers {
+                summary
+                    .providers
+                    .entry(name.clone())
+                    .or_default()
+                    .merge(stats);
+            }
+        }
+
+        summary.sessions = sessions.len();
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn record(session: &str, day: u32, failed: bool) -> StatsRecord {
+        let mut record = StatsRecord::new(
+            session,
+            Utc.with_ymd_and_hms(2025, 3, day, 10, 0, 0).unwrap(),
+        );
+        record.record_usage(100, 20, 120);
+        record.record_tool_call("tool_forge_fs_read", failed);
+        record.record_request("openrouter.ai", Duration::from_millis(400));
+        record.cost = 0.5;
+        record
+    }
+
+    #[test]
+    fn test_summary() {
+        let records = vec![
+            record("a", 1, false),
+            record("a", 1, true),
+            record("b", 2, false),
+            record("c", 3, false),
+        ];
+        let since = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+
+        let actual = StatsSummary::new(&records, since);
+
+        assert_eq!(actual.sessions, 3);
+        assert_eq!(actual.total_tokens, 480);
+        assert_eq!(actual.cost, 2.0);
+        assert_eq!(
+            actual.tools["tool_forge_fs_read"],
+            ToolStats { calls: 4, failures: 1 }
+        );
+        assert_eq!(
+            actual.providers["openrouter.ai"],
+            ProviderStats { requests: 4, latency_ms: 1600, max_latency_ms: 400 }
+        );
+        let first_day = &actual.days[&NaiveDate::from_ymd_opt(2025, 3, 1).unwrap()];
+        assert_eq!(first_day.sessions, 1);
+        assert_eq!(first_day.tool_failures, 1);
+    }
+
+    #[test]
+    fn test_summary_since() {
+        let records = vec![record("a", 1, false), record("b", 5, false)];
+        let since = Utc.with_ymd_and_hms(2025, 3, 2, 0, 0, 0).unwrap();
+
+        let actual = StatsSummary::new(&records, since);
+
+        assert_eq!(actual.sessions, 1);
+        assert_eq!(actual.days.len(), 1);
+    }
+
+    #[test]
+    fn test_store_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stats.jsonl");
+        let store = StatsStore::new(path.clone());
+        let fixture = record("a", 1, false);
+
+        store.append(&fixture).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"session\":\n")
+            .unwrap();
+        store.append(&fixture).unwrap();
+
+        let actual = store.load().unwrap();
+        let expected = vec![fixture.clone(), fixture];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = StatsStore::new(dir.path().join("stats.jsonl"));
+
+        let actual = store.load().unwrap();
+
+        assert!(actual.is_empty());
+    }
+}